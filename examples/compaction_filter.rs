@@ -50,6 +50,7 @@ impl CompactionFilter for MyFilter {
                     Decision::Keep
                 }
             },
+            ValueType::BlobIndex => Decision::Keep,
         }
     }
 
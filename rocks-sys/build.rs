@@ -316,6 +316,7 @@ fn main() {
         .file("rocks/status.cc")
         .file("rocks/table.cc")
         .file("rocks/table_properties.cc")
+        .file("rocks/transaction.cc")
         .file("rocks/transaction_log.cc")
         .file("rocks/universal_compaction.cc")
         .file("rocks/util.cc")
@@ -326,5 +327,8 @@ fn main() {
         .file("rocks/compaction_job_stats.cc")
         .file("rocks/thread_status.cc")
         .file("rocks/options_util.cc")
+        .file("rocks/memory_util.cc")
+        .file("rocks/checkpoint.cc")
+        .file("rocks/stats_history.cc")
         .compile("librocksdb_wrap");
 }
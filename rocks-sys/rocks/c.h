@@ -42,6 +42,7 @@ typedef struct rocks_ratelimiter_t rocks_ratelimiter_t;
 typedef struct rocks_envoptions_t rocks_envoptions_t;
 typedef struct rocks_logger_t rocks_logger_t;
 typedef struct rocks_env_t rocks_env_t;
+typedef struct rocks_filelock_t rocks_filelock_t;
 
 /* snapshot*/
 typedef struct rocks_snapshot_t rocks_snapshot_t;
@@ -89,6 +90,7 @@ typedef struct rocks_iostats_context_t rocks_iostats_context_t;
 
 /* perf_context */
 typedef struct rocks_perf_context_t rocks_perf_context_t;
+typedef struct rocks_perf_context_by_level_t rocks_perf_context_by_level_t;
 
 /* statistics */
 typedef struct rocks_statistics_t rocks_statistics_t;
@@ -105,6 +107,12 @@ typedef struct rocks_universal_compaction_options_t rocks_universal_compaction_o
 typedef struct rocks_logfiles_t rocks_logfiles_t;
 typedef struct rocks_transaction_log_iterator_t rocks_transaction_log_iterator_t;
 
+/* transaction */
+typedef struct rocks_transactiondb_options_t rocks_transactiondb_options_t;
+typedef struct rocks_transaction_options_t rocks_transaction_options_t;
+typedef struct rocks_optimistic_transaction_options_t rocks_optimistic_transaction_options_t;
+typedef struct rocks_transaction_t rocks_transaction_t;
+
 /* table_properties */
 typedef struct rocks_table_props_collection_t rocks_table_props_collection_t;
 
@@ -140,10 +148,18 @@ typedef struct rocks_table_file_creation_info_t rocks_table_file_creation_info_t
 typedef struct rocks_table_file_creation_brief_info_t rocks_table_file_creation_brief_info_t;
 typedef struct rocks_mem_table_info_t rocks_mem_table_info_t;
 typedef struct rocks_external_file_ingestion_info_t rocks_external_file_ingestion_info_t;
+typedef struct rocks_write_stall_info_t rocks_write_stall_info_t;
+typedef struct rocks_file_operation_info_t rocks_file_operation_info_t;
 
 /* thread_status */
 typedef struct rocks_thread_status_t rocks_thread_status_t;
 
+/* stats_history */
+typedef struct rocks_stats_history_iterator_t rocks_stats_history_iterator_t;
+
+/* checkpoint */
+typedef struct rocks_checkpoint_t rocks_checkpoint_t;
+
 /* aux */
 typedef struct cxx_string_vector_t cxx_string_vector_t;
 typedef struct cxx_string_t cxx_string_t; /* std::string */
@@ -153,10 +169,18 @@ typedef struct cxx_string_t cxx_string_t; /* std::string */
 /* status */
 void rocks_status_destroy(rocks_status_t* s);
 
+rocks_status_t* rocks_status_copy(rocks_status_t* s);
+
 int rocks_status_code(rocks_status_t* s);
 int rocks_status_subcode(rocks_status_t* s);
 int rocks_status_severity(rocks_status_t* s);
 const char* rocks_status_get_state(rocks_status_t* s);
+rocks_status_t* rocks_status_create_busy(const char* msg);
+rocks_status_t* rocks_status_create_invalid_argument(const char* msg);
+rocks_status_t* rocks_status_create_timed_out(const char* msg);
+rocks_status_t* rocks_status_create_aborted(const char* msg);
+rocks_status_t* rocks_status_create_not_supported(const char* msg);
+rocks_status_t* rocks_status_create_corruption(const char* msg);
 
 /* slice */
 rocks_pinnable_slice_t* rocks_pinnable_slice_create();
@@ -167,6 +191,10 @@ const char* rocks_pinnable_slice_data(rocks_pinnable_slice_t* s);
 
 size_t rocks_pinnable_slice_size(rocks_pinnable_slice_t* s);
 
+unsigned char rocks_pinnable_slice_is_pinned(rocks_pinnable_slice_t* s);
+
+void rocks_pinnable_slice_reset(rocks_pinnable_slice_t* s);
+
 /* ColumnFamilyDescriptor */
 const char* rocks_column_family_descriptor_get_name(const rocks_column_family_descriptor_t* desc);
 
@@ -206,7 +234,7 @@ void rocks_cfoptions_set_merge_operator_by_assoc_op_trait(rocks_cfoptions_t* opt
 
 void rocks_cfoptions_set_merge_operator_by_merge_op_trait(rocks_cfoptions_t* opt, void* op_trait_obj);
 
-void rocks_cfoptions_set_comparator_by_trait(rocks_cfoptions_t* opt, void* cp_trait_obj);
+void rocks_cfoptions_set_comparator(rocks_cfoptions_t* opt, const rocks_comparator_t* comparator);
 
 void rocks_cfoptions_set_compaction_filter_by_trait(rocks_cfoptions_t* opt, void* filter_trait_obj);
 
@@ -255,6 +283,8 @@ void rocks_cfoptions_set_min_write_buffer_number_to_merge(rocks_cfoptions_t* opt
 
 void rocks_cfoptions_set_max_write_buffer_number_to_maintain(rocks_cfoptions_t* opt, int n);
 
+void rocks_cfoptions_set_max_write_buffer_size_to_maintain(rocks_cfoptions_t* opt, int64_t n);
+
 void rocks_cfoptions_set_inplace_update_support(rocks_cfoptions_t* opt, unsigned char v);
 
 void rocks_cfoptions_set_inplace_update_num_locks(rocks_cfoptions_t* opt, size_t v);
@@ -263,6 +293,8 @@ void rocks_cfoptions_set_inplace_update_num_locks(rocks_cfoptions_t* opt, size_t
 
 void rocks_cfoptions_set_memtable_prefix_bloom_size_ratio(rocks_cfoptions_t* opt, double v);
 
+void rocks_cfoptions_set_memtable_whole_key_filtering(rocks_cfoptions_t* opt, unsigned char v);
+
 void rocks_cfoptions_set_memtable_huge_page_size(rocks_cfoptions_t* opt, size_t v);
 
 void rocks_cfoptions_set_memtable_insert_with_hint_prefix_extractor_by_trait(rocks_cfoptions_t* opt,
@@ -311,6 +343,11 @@ void rocks_cfoptions_set_universal_compaction_options(rocks_cfoptions_t* opt,
 
 void rocks_cfoptions_set_fifo_compaction_options(rocks_cfoptions_t* opt, rocks_fifo_compaction_options_t* fifo);
 
+void rocks_cfoptions_set_ttl(rocks_cfoptions_t* opt, uint64_t v);
+
+void rocks_cfoptions_set_cf_paths(rocks_cfoptions_t* opt, const char* const* paths, const size_t* path_lens,
+                                  const uint64_t* target_sizes, int size);
+
 void rocks_cfoptions_set_max_sequential_skip_in_iterations(rocks_cfoptions_t* opt, uint64_t v);
 
 // memtable_factory
@@ -379,6 +416,12 @@ void rocks_dboptions_set_delete_obsolete_files_period_micros(rocks_dboptions_t*
 
 void rocks_dboptions_set_max_background_jobs(rocks_dboptions_t* opt, int n);
 
+void rocks_dboptions_set_base_background_compactions(rocks_dboptions_t* opt, int n);
+
+void rocks_dboptions_set_max_background_compactions(rocks_dboptions_t* opt, int n);
+
+void rocks_dboptions_set_max_background_flushes(rocks_dboptions_t* opt, int n);
+
 void rocks_dboptions_set_max_subcompactions(rocks_dboptions_t* opt, uint32_t n);
 
 void rocks_dboptions_set_max_log_file_size(rocks_dboptions_t* opt, size_t v);
@@ -413,6 +456,10 @@ void rocks_dboptions_set_is_fd_close_on_exec(rocks_dboptions_t* opt, unsigned ch
 
 void rocks_dboptions_set_stats_dump_period_sec(rocks_dboptions_t* opt, unsigned int v);
 
+void rocks_dboptions_set_stats_persist_period_sec(rocks_dboptions_t* opt, unsigned int v);
+
+void rocks_dboptions_set_stats_history_buffer_size(rocks_dboptions_t* opt, size_t v);
+
 void rocks_dboptions_set_advise_random_on_open(rocks_dboptions_t* opt, unsigned char v);
 
 void rocks_dboptions_set_db_write_buffer_size(rocks_dboptions_t* opt, size_t s);
@@ -431,6 +478,12 @@ void rocks_dboptions_set_wal_bytes_per_sync(rocks_dboptions_t* opt, uint64_t v);
 
 void rocks_dboptions_add_listener(rocks_dboptions_t* opt, void* listener_trait_obj);
 
+void rocks_dboptions_add_shared_listener(rocks_dboptions_t* opt, void* listener_trait_obj, const void* key);
+
+void rocks_dboptions_remove_listener(rocks_dboptions_t* opt, const void* key);
+
+void rocks_dboptions_clear_listeners(rocks_dboptions_t* opt);
+
 void rocks_dboptions_set_enable_thread_tracking(rocks_dboptions_t* opt, unsigned char v);
 
 void rocks_dboptions_set_delayed_write_rate(rocks_dboptions_t* opt, uint64_t v);
@@ -469,12 +522,18 @@ void rocks_dboptions_set_allow_ingest_behind(rocks_dboptions_t* opt, unsigned ch
 
 void rocks_dboptions_set_manual_wal_flush(rocks_dboptions_t* opt, unsigned char v);
 
+void rocks_dboptions_set_atomic_flush(rocks_dboptions_t* opt, unsigned char v);
+
+void rocks_dboptions_set_avoid_unnecessary_blocking_io(rocks_dboptions_t* opt, unsigned char v);
+
 // opt
 
 void rocks_options_prepare_for_bulk_load(rocks_options_t* opt);
 
 void rocks_options_optimize_for_small_db(rocks_options_t* opt);
 
+void rocks_options_deterministic(rocks_options_t* opt);
+
 /*
   char *rocks_options_statistics_get_string(rocks_options_t *opt) {
   rocksdb::Statistics *statistics = opt->rep.statistics.get();
@@ -492,6 +551,8 @@ rocks_readoptions_t* rocks_readoptions_create();
 
 rocks_readoptions_t* rocks_readoptions_new(unsigned char cksum, unsigned char cache);
 
+rocks_readoptions_t* rocks_readoptions_create_from(const rocks_readoptions_t* opt);
+
 void rocks_readoptions_destroy(rocks_readoptions_t* opt);
 
 void rocks_readoptions_set_verify_checksums(rocks_readoptions_t* opt, unsigned char v);
@@ -558,6 +619,10 @@ void rocks_compactrange_options_set_target_path_id(rocks_compactrange_options_t*
 
 void rocks_compactrange_options_set_bottommost_level_compaction(rocks_compactrange_options_t* opt, int v);
 
+void rocks_compactrange_options_set_allow_write_stall(rocks_compactrange_options_t* opt, unsigned char v);
+
+void rocks_compactrange_options_set_max_subcompactions(rocks_compactrange_options_t* opt, uint32_t v);
+
 /* > ingestexternalfile_options */
 rocks_ingestexternalfile_options_t* rocks_ingestexternalfile_options_create();
 
@@ -629,6 +694,24 @@ rocks_db_t* rocks_db_open_as_secondary_column_families(const rocks_dboptions_t*
 
 void rocks_db_try_catch_up_with_primary(rocks_db_t* db, rocks_status_t** status);
 
+/* db with ttl */
+rocks_db_t* rocks_db_open_with_ttl(const rocks_options_t* options, const char* name, int32_t ttl,
+                                   unsigned char read_only, rocks_status_t** status);
+
+rocks_db_t* rocks_db_open_column_families_with_ttl(const rocks_dboptions_t* db_options, const char* name,
+                                                   int num_column_families, const char* const* column_family_names,
+                                                   const rocks_cfoptions_t* const* column_family_options,
+                                                   rocks_column_family_handle_t** column_family_handles,
+                                                   const int32_t* ttls, unsigned char read_only,
+                                                   rocks_status_t** status);
+
+rocks_column_family_handle_t* rocks_db_create_column_family_with_ttl(rocks_db_t* db,
+                                                                     const rocks_cfoptions_t* column_family_options,
+                                                                     const char* column_family_name, int32_t ttl,
+                                                                     rocks_status_t** status);
+
+void rocks_db_set_ttl(rocks_db_t* db, rocks_column_family_handle_t* column_family, int32_t ttl);
+
 rocks_db_t* rocks_db_open_column_families(const rocks_dboptions_t* db_options, const char* name,
                                           int num_column_families, const char* const* column_family_names,
                                           const rocks_cfoptions_t* const* column_family_options,
@@ -650,10 +733,19 @@ rocks_column_family_handle_t* rocks_db_create_column_family(rocks_db_t* db,
                                                             const rocks_cfoptions_t* column_family_options,
                                                             const char* column_family_name, rocks_status_t** status);
 
+/* files are only read, not destroyed */
+rocks_column_family_handle_t* rocks_db_create_column_family_with_import(
+    rocks_db_t* db, const rocks_cfoptions_t* column_family_options, const char* column_family_name,
+    unsigned char move_files, const char* db_comparator_name, size_t db_comparator_name_len,
+    const rocks_livefiles_t* files, rocks_status_t** status);
+
 rocks_column_family_handle_t* rocks_db_default_column_family(rocks_db_t* db);
 
 void rocks_db_drop_column_family(rocks_db_t* db, rocks_column_family_handle_t* handle, rocks_status_t** status);
 
+void rocks_db_drop_column_families(rocks_db_t* db, const rocks_column_family_handle_t* const* handles,
+                                   size_t num_handles, rocks_status_t** status);
+
 /* FIXME: when to use? */
 void rocks_db_destroy_column_family_handle(rocks_db_t* db, rocks_column_family_handle_t* handle,
                                            rocks_status_t** status);
@@ -707,6 +799,10 @@ void rocks_db_get_cf_pinnable(rocks_db_t* db, const rocks_readoptions_t* options
                               rocks_column_family_handle_t* column_family, const char* key, size_t keylen,
                               rocks_pinnable_slice_t* value, rocks_status_t** status);
 
+unsigned char rocks_db_get_into(rocks_db_t* db, const rocks_readoptions_t* options,
+                                rocks_column_family_handle_t* column_family, const char* key, size_t keylen,
+                                void* value, rocks_status_t** status);
+
 void rocks_db_multi_get(rocks_db_t* db, const rocks_readoptions_t* options, size_t num_keys,
                         const char* const* keys_list, const size_t* keys_list_sizes, char** values_list,
                         size_t* values_list_sizes, rocks_status_t** status);
@@ -736,6 +832,10 @@ rocks_iterator_t* rocks_db_create_iterator(rocks_db_t* db, const rocks_readoptio
 rocks_iterator_t* rocks_db_create_iterator_cf(rocks_db_t* db, const rocks_readoptions_t* options,
                                               rocks_column_family_handle_t* column_family);
 
+rocks_iterator_t* rocks_db_create_prefix_iterator_cf(rocks_db_t* db, const rocks_readoptions_t* options,
+                                                     rocks_column_family_handle_t* column_family, const char* prefix,
+                                                     size_t prefix_len);
+
 void rocks_db_create_iterators(rocks_db_t* db, rocks_readoptions_t* opts,
                                rocks_column_family_handle_t** column_families, rocks_iterator_t** iterators,
                                size_t size, rocks_status_t** status);
@@ -750,6 +850,10 @@ unsigned char rocks_db_get_property(rocks_db_t* db, const char* prop, const size
 unsigned char rocks_db_get_property_cf(rocks_db_t* db, rocks_column_family_handle_t* cf, const char* prop,
                                        const size_t prop_len, void* value);
 
+unsigned char rocks_db_get_map_property_cf(rocks_db_t* db, rocks_column_family_handle_t* cf, const char* prop,
+                                           const size_t prop_len, cxx_string_vector_t* keys,
+                                           cxx_string_vector_t* values);
+
 unsigned char rocks_db_get_int_property(rocks_db_t* db, const char* prop, const size_t prop_len, uint64_t* value);
 
 unsigned char rocks_db_get_int_property_cf(rocks_db_t* db, rocks_column_family_handle_t* cf, const char* prop,
@@ -773,6 +877,13 @@ void rocks_db_compact_range_opt_cf(rocks_db_t* db, rocks_compactrange_options_t*
                                    size_t start_key_len, const char* limit_key, size_t limit_key_len,
                                    rocks_status_t** status);
 
+void rocks_db_suggest_compact_range(rocks_db_t* db, rocks_column_family_handle_t* column_family, const char* start_key,
+                                    size_t start_key_len, const char* limit_key, size_t limit_key_len,
+                                    rocks_status_t** status);
+
+void rocks_db_promote_l0(rocks_db_t* db, rocks_column_family_handle_t* column_family, int target_level,
+                         rocks_status_t** status);
+
 void rocks_db_set_options_cf(rocks_db_t* db, rocks_column_family_handle_t* column_family, size_t num_options,
                              const char* const* keys, const size_t* key_lens, const char* const* vals,
                              const size_t* val_lens, rocks_status_t** status);
@@ -787,6 +898,9 @@ void rocks_db_compact_files(rocks_db_t* db, rocks_compaction_options_t* opt, siz
 void rocks_db_pause_background_work(rocks_db_t* db, rocks_status_t** status);
 void rocks_db_continue_background_work(rocks_db_t* db, rocks_status_t** status);
 
+void rocks_db_disable_manual_compaction(rocks_db_t* db);
+void rocks_db_enable_manual_compaction(rocks_db_t* db);
+
 void rocks_db_enable_auto_compaction(rocks_db_t* db, const rocks_column_family_handle_t* const* column_families,
                                      size_t cf_len, rocks_status_t** status);
 
@@ -804,6 +918,13 @@ void rocks_db_get_approximate_sizes_cf(rocks_db_t* db, rocks_column_family_handl
                                        const char* const* range_limit_ptrs, const size_t* range_limit_lens,
                                        uint64_t* sizes);
 
+void rocks_db_get_approximate_sizes_cf_opt(rocks_db_t* db, rocks_column_family_handle_t* column_family,
+                                           unsigned char include_memtables, unsigned char include_files,
+                                           double files_size_error_margin, size_t num_ranges,
+                                           const char* const* range_start_ptrs, const size_t* range_start_lens,
+                                           const char* const* range_limit_ptrs, const size_t* range_limit_lens,
+                                           uint64_t* sizes, rocks_status_t** status);
+
 void rocks_db_get_approximate_memtable_stats_cf(rocks_db_t* db, rocks_column_family_handle_t* column_family,
                                                 const char* range_start_ptr, size_t range_start_len,
                                                 const char* range_limit_ptr, size_t range_limit_len, uint64_t* count,
@@ -814,9 +935,14 @@ void rocks_db_get_name(rocks_db_t* db, void* s);
 void rocks_db_flush(rocks_db_t* db, rocks_flushoptions_t* options, rocks_status_t** status);
 void rocks_db_flush_cf(rocks_db_t* db, rocks_flushoptions_t* options, rocks_column_family_handle_t* column_family,
                        rocks_status_t** status);
+void rocks_db_flush_cfs(rocks_db_t* db, rocks_flushoptions_t* options,
+                        const rocks_column_family_handle_t* const* column_families, size_t num_column_families,
+                        rocks_status_t** status);
 
 void rocks_db_sync_wal(rocks_db_t* db, rocks_status_t** status);
 
+void rocks_db_flush_wal(rocks_db_t* db, unsigned char sync, rocks_status_t** status);
+
 uint64_t rocks_db_get_latest_sequence_number(rocks_db_t* db);
 
 void rocks_db_disable_file_deletions(rocks_db_t* db, rocks_status_t** status);
@@ -828,8 +954,13 @@ cxx_string_vector_t* rocks_db_get_live_files(rocks_db_t* db, unsigned char flush
 
 rocks_logfiles_t* rocks_db_get_sorted_wal_files(rocks_db_t* db, rocks_status_t** status);
 
+rocks_logfiles_t* rocks_db_get_current_wal_file(rocks_db_t* db, rocks_status_t** status);
+
 rocks_transaction_log_iterator_t* rocks_db_get_update_since(rocks_db_t* db, uint64_t seq_no, rocks_status_t** status);
 
+rocks_stats_history_iterator_t* rocks_db_get_stats_history(rocks_db_t* db, uint64_t start_time, uint64_t end_time,
+                                                           rocks_status_t** status);
+
 void rocks_db_delete_file(rocks_db_t* db, const char* name, size_t name_len, rocks_status_t** status);
 
 const rocks_livefiles_t* rocks_db_get_livefiles_metadata(rocks_db_t* db);
@@ -849,6 +980,8 @@ void rocks_db_get_db_identity(rocks_db_t* db,
                               void* identity,  // *mut String
                               rocks_status_t** status);
 
+void rocks_db_verify_checksum(rocks_db_t* db, const rocks_readoptions_t* options, rocks_status_t** status);
+
 rocks_table_props_collection_t* rocks_db_get_properties_of_all_tables(rocks_db_t* db, rocks_column_family_handle_t* cf,
                                                                       rocks_status_t** status);
 
@@ -868,19 +1001,46 @@ void rocks_repair_db(const rocks_options_t* options, const char* name, size_t le
 /* rate_limiter.h */
 rocks_ratelimiter_t* rocks_ratelimiter_create(int64_t rate_bytes_per_sec, int64_t refill_period_us, int32_t fairness);
 
+rocks_ratelimiter_t* rocks_ratelimiter_create_with_mode(int64_t rate_bytes_per_sec, int64_t refill_period_us,
+                                                        int32_t fairness, int mode, unsigned char auto_tuned);
+
 void rocks_ratelimiter_destroy(rocks_ratelimiter_t* limiter);
 
+void rocks_ratelimiter_set_bytes_per_second(rocks_ratelimiter_t* limiter, int64_t bytes_per_second);
+
+int64_t rocks_ratelimiter_get_bytes_per_second(rocks_ratelimiter_t* limiter);
+
+int64_t rocks_ratelimiter_get_single_burst_bytes(rocks_ratelimiter_t* limiter);
+
+int64_t rocks_ratelimiter_get_total_bytes_through(rocks_ratelimiter_t* limiter, int pri);
+
+int64_t rocks_ratelimiter_get_total_requests(rocks_ratelimiter_t* limiter, int pri);
+
+rocks_ratelimiter_t* rocks_ratelimiter_clone(rocks_ratelimiter_t* limiter);
+
+void rocks_ratelimiter_request(rocks_ratelimiter_t* limiter, int64_t bytes, int pri);
+
 /* env.h */
 rocks_env_t* rocks_create_default_env();
 
 rocks_env_t* rocks_create_mem_env();
 rocks_env_t* rocks_create_timed_env();
+rocks_env_t* rocks_create_latency_injection_env();
+rocks_env_t* rocks_create_mem_env_with_base(rocks_env_t* base);
+rocks_env_t* rocks_create_timed_env_with_base(rocks_env_t* base);
+rocks_env_t* rocks_create_rot13_encrypted_env(rocks_env_t* base, size_t block_size);
+/* cipher: rust Box<Box<dyn BlockCipher>>, owned by the env */
+rocks_env_t* rocks_create_encrypted_env(rocks_env_t* base, void* cipher);
+/* file_system: rust Box<Box<dyn FileSystem>>, owned by the env */
+rocks_env_t* rocks_create_rust_env(void* file_system);
+
+unsigned char rocks_env_set_injected_latency(rocks_env_t* env, int op, double probability, uint64_t min_micros,
+                                             uint64_t max_micros);
 
 void rocks_env_destroy(rocks_env_t* env);
 
-void rocks_env_set_background_threads(rocks_env_t* env, int n);
-
-void rocks_env_set_high_priority_background_threads(rocks_env_t* env, int n);
+// pri: 0 = low, 1 = high, 3 = bottom, as the Rust side Priority
+void rocks_env_set_background_threads(rocks_env_t* env, int n, int pri);
 
 void rocks_env_join_all_threads(rocks_env_t* env);
 
@@ -888,6 +1048,26 @@ unsigned int rocks_env_get_thread_pool_queue_len(rocks_env_t* env, int pri);
 
 rocks_logger_t* rocks_env_new_logger(rocks_env_t* env, const char* name_ptr, size_t name_len, rocks_status_t** status);
 
+rocks_filelock_t* rocks_env_lock_file(rocks_env_t* env, const char* fname, size_t fname_len, rocks_status_t** status);
+
+void rocks_env_unlock_file(rocks_env_t* env, rocks_filelock_t* lock, rocks_status_t** status);
+
+size_t rocks_env_get_direct_io_alignment(rocks_env_t* env, const char* fname, size_t fname_len,
+                                         rocks_status_t** status);
+
+char** rocks_env_get_children(rocks_env_t* env, const char* dir, size_t dir_len, size_t* len,
+                              rocks_status_t** status);
+void rocks_env_get_children_destroy(char** list, size_t len);
+
+void rocks_env_file_exists(rocks_env_t* env, const char* fname, size_t fname_len, rocks_status_t** status);
+
+uint64_t rocks_env_get_file_size(rocks_env_t* env, const char* fname, size_t fname_len, rocks_status_t** status);
+
+uint64_t rocks_env_get_file_modification_time(rocks_env_t* env, const char* fname, size_t fname_len,
+                                              rocks_status_t** status);
+
+void rocks_env_delete_file(rocks_env_t* env, const char* fname, size_t fname_len, rocks_status_t** status);
+
 uint64_t rocks_env_now_micros(rocks_env_t* env);
 
 uint64_t rocks_env_now_nanos(rocks_env_t* env);
@@ -926,6 +1106,12 @@ void rocks_envoptions_set_compaction_readahead_size(rocks_envoptions_t* opt, siz
 void rocks_envoptions_set_random_access_max_buffer_size(rocks_envoptions_t* opt, size_t val);
 void rocks_envoptions_set_writable_file_max_buffer_size(rocks_envoptions_t* opt, size_t val);
 
+rocks_logger_t* rocks_logger_create_rust(void* logger, int log_level);
+
+rocks_logger_t* rocks_logger_create_stderr(int log_level);
+
+rocks_logger_t* rocks_logger_create_devnull();
+
 void rocks_logger_destroy(rocks_logger_t* logger);
 
 void rocks_logger_log(rocks_logger_t* logger, int log_level, const char* msg_ptr, size_t msg_len);
@@ -953,6 +1139,8 @@ rocks_writebatch_t* rocks_writebatch_create();
 
 rocks_writebatch_t* rocks_writebatch_create_with_reserved_bytes(size_t size);
 
+rocks_writebatch_t* rocks_writebatch_create_from(const char* rep, size_t size);
+
 void rocks_writebatch_destroy(rocks_writebatch_t* b);
 
 void rocks_writebatch_clear(rocks_writebatch_t* b);
@@ -1077,6 +1265,8 @@ void rocks_plain_table_options_set_store_index_in_file(rocks_plain_table_options
 rocks_block_based_table_options_t* rocks_block_based_table_options_create();
 
 void rocks_block_based_table_options_destroy(rocks_block_based_table_options_t* options);
+void rocks_block_based_table_options_validate(const rocks_block_based_table_options_t* options,
+                                              rocks_status_t** status);
 
 // flush_block_policy_factory
 
@@ -1161,11 +1351,14 @@ void rocks_iter_get_status(const rocks_iterator_t* iter, rocks_status_t** status
 void rocks_iter_get_property(const rocks_iterator_t* iter, const char* prop, size_t prop_len, void* value,
                              rocks_status_t** status);
 
+void rocks_iter_refresh(rocks_iterator_t* iter, rocks_status_t** status);
+
 rocks_iterator_t* rocks_new_empty_iterator();
 
 /* filter_policy */
 rocks_raw_filterpolicy_t* rocks_raw_filterpolicy_new_bloomfilter(int bits_per_key,
                                                                  unsigned char use_block_based_builder);
+rocks_raw_filterpolicy_t* rocks_raw_filterpolicy_new_rust(void* trait_obj);
 void rocks_raw_filterpolicy_destroy(rocks_raw_filterpolicy_t* cache);
 
 /* cache */
@@ -1174,6 +1367,8 @@ rocks_cache_t* rocks_cache_create_lru(size_t capacity, int num_shard_bits, char
 
 rocks_cache_t* rocks_cache_create_clock(size_t capacity, int num_shard_bits, char strict_capacity_limit);
 
+rocks_cache_t* rocks_cache_clone(rocks_cache_t* cache);
+
 void rocks_cache_destroy(rocks_cache_t* cache);
 
 void rocks_cache_set_capacity(rocks_cache_t* cache, size_t capacity);
@@ -1186,6 +1381,12 @@ size_t rocks_cache_get_pinned_usage(rocks_cache_t* cache);
 
 const char* rocks_cache_name(rocks_cache_t* cache);
 
+double rocks_cache_get_high_pri_pool_ratio(rocks_cache_t* cache);
+
+void rocks_cache_set_strict_capacity_limit(rocks_cache_t* cache, unsigned char strict_capacity_limit);
+
+unsigned char rocks_cache_has_strict_capacity_limit(rocks_cache_t* cache);
+
 /* persistent_cache */
 rocks_persistent_cache_t* rocks_new_persistent_cache(const rocks_env_t* env, const char* path, size_t path_len,
                                                      uint64_t size, const rocks_logger_t* log,
@@ -1239,6 +1440,9 @@ void rocks_sst_file_writer_finish(rocks_sst_file_writer_t* writer, rocks_externa
 uint64_t rocks_sst_file_writer_file_size(rocks_sst_file_writer_t* writer);
 
 /* comparator */
+rocks_comparator_t* rocks_comparator_create(void* cp_trait_obj);
+void rocks_comparator_destroy(rocks_comparator_t* comparator);
+
 /* avoid export rocksdb::Comparator type */
 const rocks_c_comparator_t* rocks_comparator_bytewise();
 const rocks_c_comparator_t* rocks_comparator_bytewise_reversed();
@@ -1259,8 +1463,16 @@ void rocks_dump_options_set_dump_location(rocks_dump_options_t* opt, const char*
 
 void rocks_dump_options_set_anonymous(rocks_dump_options_t* opt, unsigned char v);
 
+void rocks_dump_options_set_column_family(rocks_dump_options_t* opt, const char* name, const size_t name_len);
+
+void rocks_dump_options_set_column_family_options(rocks_dump_options_t* opt, const char* name, const size_t name_len,
+                                                  const rocks_cfoptions_t* cfopts);
+
 unsigned char rocks_db_dump_tool_run(rocks_dump_options_t* dump_options, rocks_options_t* options);
 
+/* same format as rocks_db_dump_tool_run, dumps the selected column family */
+void rocks_db_dump(rocks_dump_options_t* dump_options, rocks_options_t* options, rocks_status_t** status);
+
 rocks_undump_options_t* rocks_undump_options_create();
 
 void rocks_undump_options_destroy(rocks_undump_options_t* options);
@@ -1271,8 +1483,16 @@ void rocks_undump_options_set_dump_location(rocks_undump_options_t* opt, const c
 
 void rocks_undump_options_set_compact_db(rocks_undump_options_t* opt, unsigned char v);
 
+void rocks_undump_options_set_column_family(rocks_undump_options_t* opt, const char* name, const size_t name_len);
+
+void rocks_undump_options_set_column_family_options(rocks_undump_options_t* opt, const char* name,
+                                                    const size_t name_len, const rocks_cfoptions_t* cfopts);
+
 unsigned char rocks_db_undump_tool_run(rocks_undump_options_t* undump_options, rocks_options_t* options);
 
+/* loads into the selected column family, which is created if missing */
+void rocks_db_undump(rocks_undump_options_t* undump_options, rocks_options_t* options, rocks_status_t** status);
+
 /* perf_level */
 void rocks_set_perf_level(unsigned char level);
 unsigned char rocks_get_perf_level();
@@ -1286,6 +1506,12 @@ void rocks_iostats_context_to_string(const rocks_iostats_context_t* ctx, unsigne
 rocks_perf_context_t* rocks_get_perf_context();
 void rocks_perf_context_reset(rocks_perf_context_t* ctx);
 void rocks_perf_context_to_string(const rocks_perf_context_t* ctx, unsigned char exclude_zero_counters, void* s);
+void rocks_perf_context_enable_per_level(rocks_perf_context_t* ctx);
+void rocks_perf_context_disable_per_level(rocks_perf_context_t* ctx);
+void rocks_perf_context_clear_per_level(rocks_perf_context_t* ctx);
+// fills up to `len` entries, returns the number of levels
+size_t rocks_perf_context_by_level(const rocks_perf_context_t* ctx, uint32_t* levels,
+                                   const rocks_perf_context_by_level_t** stats, size_t len);
 
 /* statistics */
 rocks_statistics_t* rocks_statistics_create();
@@ -1316,6 +1542,21 @@ unsigned char rocks_statistics_hist_enabled_for_type(rocks_statistics_t* stat, u
 
 void rocks_statistics_reset(rocks_statistics_t* stat, rocks_status_t** status);
 
+unsigned char rocks_statistics_get_stats_level(rocks_statistics_t* stat);
+
+void rocks_statistics_set_stats_level(rocks_statistics_t* stat, unsigned char level);
+
+size_t rocks_statistics_num_tickers();
+
+/* names and counts must have rocks_statistics_num_tickers() slots, names are static */
+void rocks_statistics_get_all_ticker_counts(rocks_statistics_t* stat, const char** names, uint64_t* counts);
+
+size_t rocks_statistics_num_histograms();
+
+/* names and data must have rocks_statistics_num_histograms() slots, names are static */
+void rocks_statistics_get_all_histogram_data(rocks_statistics_t* stat, const char** names,
+                                             rocks_histogram_data_t* data);
+
 /* metadata */
 int rocks_livefiles_count(const rocks_livefiles_t* lf);
 
@@ -1338,8 +1579,25 @@ const char* rocks_livefiles_largestkey(const rocks_livefiles_t* lf, int index, s
 
 unsigned char rocks_livefiles_being_compacted(const rocks_livefiles_t* lf, int index);
 
+uint64_t rocks_livefiles_num_reads_sampled(const rocks_livefiles_t* lf, int index);
+
+uint64_t rocks_livefiles_file_number(const rocks_livefiles_t* lf, int index);
+
+uint64_t rocks_livefiles_num_entries(const rocks_livefiles_t* lf, int index);
+
+uint64_t rocks_livefiles_num_deletions(const rocks_livefiles_t* lf, int index);
+
+uint64_t rocks_livefiles_oldest_blob_file_number(const rocks_livefiles_t* lf, int index);
+
 extern void rocks_livefiles_destroy(const rocks_livefiles_t* lf);
 
+rocks_livefiles_t* rocks_livefiles_create();
+
+void rocks_livefiles_push(rocks_livefiles_t* lf, const char* name, size_t name_len, const char* db_path,
+                          size_t db_path_len, int level, uint64_t smallest_seqno, uint64_t largest_seqno,
+                          const char* smallestkey, size_t smallestkey_len, const char* largestkey,
+                          size_t largestkey_len, size_t size);
+
 uint64_t rocks_column_family_metadata_size(const rocks_column_family_metadata_t* meta);
 size_t rocks_column_family_metadata_file_count(const rocks_column_family_metadata_t* meta);
 const char* rocks_column_family_metadata_name(const rocks_column_family_metadata_t* meta);
@@ -1364,6 +1622,20 @@ const char* rocks_column_family_metadata_levels_files_largestkey(const rocks_col
                                                                  int file_index, size_t* size);
 unsigned char rocks_column_family_metadata_levels_files_being_compacted(const rocks_column_family_metadata_t* meta,
                                                                         int level, int file_index);
+uint64_t rocks_column_family_metadata_levels_files_num_reads_sampled(const rocks_column_family_metadata_t* meta,
+                                                                     int level, int file_index);
+uint64_t rocks_column_family_metadata_levels_files_file_number(const rocks_column_family_metadata_t* meta, int level,
+                                                               int file_index);
+uint64_t rocks_column_family_metadata_levels_files_num_entries(const rocks_column_family_metadata_t* meta, int level,
+                                                               int file_index);
+uint64_t rocks_column_family_metadata_levels_files_num_deletions(const rocks_column_family_metadata_t* meta, int level,
+                                                                 int file_index);
+uint64_t rocks_column_family_metadata_levels_files_oldest_blob_file_number(
+    const rocks_column_family_metadata_t* meta, int level, int file_index);
+uint64_t rocks_column_family_metadata_levels_files_oldest_ancester_time(
+    const rocks_column_family_metadata_t* meta, int level, int file_index);
+uint64_t rocks_column_family_metadata_levels_files_file_creation_time(
+    const rocks_column_family_metadata_t* meta, int level, int file_index);
 
 extern void rocks_column_family_metadata_destroy(const rocks_column_family_metadata_t* meta);
 
@@ -1404,15 +1676,95 @@ void rocks_transaction_log_iterator_next(rocks_transaction_log_iterator_t* it);
 void rocks_transaction_log_iterator_status(rocks_transaction_log_iterator_t* it, rocks_status_t** status);
 rocks_writebatch_t* rocks_transaction_log_iterator_get_batch(rocks_transaction_log_iterator_t* it, uint64_t* seq_no);
 
+/* transaction */
+rocks_transactiondb_options_t* rocks_transactiondb_options_create();
+void rocks_transactiondb_options_destroy(rocks_transactiondb_options_t* opt);
+void rocks_transactiondb_options_set_max_num_locks(rocks_transactiondb_options_t* opt, int64_t v);
+void rocks_transactiondb_options_set_num_stripes(rocks_transactiondb_options_t* opt, size_t v);
+void rocks_transactiondb_options_set_transaction_lock_timeout(rocks_transactiondb_options_t* opt, int64_t v);
+void rocks_transactiondb_options_set_default_lock_timeout(rocks_transactiondb_options_t* opt, int64_t v);
+
+rocks_transaction_options_t* rocks_transaction_options_create();
+void rocks_transaction_options_destroy(rocks_transaction_options_t* opt);
+void rocks_transaction_options_set_set_snapshot(rocks_transaction_options_t* opt, unsigned char v);
+void rocks_transaction_options_set_deadlock_detect(rocks_transaction_options_t* opt, unsigned char v);
+void rocks_transaction_options_set_lock_timeout(rocks_transaction_options_t* opt, int64_t v);
+void rocks_transaction_options_set_expiration(rocks_transaction_options_t* opt, int64_t v);
+
+rocks_optimistic_transaction_options_t* rocks_optimistic_transaction_options_create();
+void rocks_optimistic_transaction_options_destroy(rocks_optimistic_transaction_options_t* opt);
+void rocks_optimistic_transaction_options_set_set_snapshot(rocks_optimistic_transaction_options_t* opt,
+                                                           unsigned char v);
+
+rocks_db_t* rocks_transactiondb_open(const rocks_options_t* options, const rocks_transactiondb_options_t* txn_db_options,
+                                     const char* name, rocks_status_t** status);
+rocks_db_t* rocks_transactiondb_open_column_families(const rocks_dboptions_t* db_options,
+                                                     const rocks_transactiondb_options_t* txn_db_options,
+                                                     const char* name, int num_column_families,
+                                                     const char* const* column_family_names,
+                                                     const rocks_cfoptions_t* const* column_family_options,
+                                                     rocks_column_family_handle_t** column_family_handles,
+                                                     rocks_status_t** status);
+rocks_transaction_t* rocks_transactiondb_begin_transaction(rocks_db_t* db, const rocks_writeoptions_t* write_options,
+                                                           const rocks_transaction_options_t* txn_options);
+rocks_transaction_t** rocks_transactiondb_get_all_prepared_transactions(rocks_db_t* db, size_t* count);
+void rocks_transaction_array_destroy(rocks_transaction_t** txns);
+
+rocks_db_t* rocks_optimistic_transactiondb_open(const rocks_options_t* options, const char* name,
+                                                rocks_status_t** status);
+rocks_db_t* rocks_optimistic_transactiondb_open_column_families(const rocks_dboptions_t* db_options,
+                                                                const char* name, int num_column_families,
+                                                                const char* const* column_family_names,
+                                                                const rocks_cfoptions_t* const* column_family_options,
+                                                                rocks_column_family_handle_t** column_family_handles,
+                                                                rocks_status_t** status);
+rocks_transaction_t* rocks_optimistic_transactiondb_begin_transaction(
+    rocks_db_t* db, const rocks_writeoptions_t* write_options,
+    const rocks_optimistic_transaction_options_t* txn_options);
+
+void rocks_transaction_destroy(rocks_transaction_t* txn);
+void rocks_transaction_set_name(rocks_transaction_t* txn, const char* name, size_t name_len, rocks_status_t** status);
+void rocks_transaction_get_name(rocks_transaction_t* txn, void* name /* *mut String */);
+uint64_t rocks_transaction_get_id(rocks_transaction_t* txn);
+void rocks_transaction_prepare(rocks_transaction_t* txn, rocks_status_t** status);
+void rocks_transaction_commit(rocks_transaction_t* txn, rocks_status_t** status);
+void rocks_transaction_rollback(rocks_transaction_t* txn, rocks_status_t** status);
+void rocks_transaction_set_snapshot(rocks_transaction_t* txn);
+const rocks_snapshot_t* rocks_transaction_get_snapshot(rocks_transaction_t* txn);
+void rocks_transaction_clear_snapshot(rocks_transaction_t* txn);
+void rocks_transaction_set_savepoint(rocks_transaction_t* txn);
+void rocks_transaction_rollback_to_savepoint(rocks_transaction_t* txn, rocks_status_t** status);
+void rocks_transaction_put(rocks_transaction_t* txn, rocks_column_family_handle_t* column_family, const char* key,
+                           size_t keylen, const char* val, size_t vallen, rocks_status_t** status);
+void rocks_transaction_delete(rocks_transaction_t* txn, rocks_column_family_handle_t* column_family, const char* key,
+                              size_t keylen, rocks_status_t** status);
+void rocks_transaction_get_pinnable(rocks_transaction_t* txn, const rocks_readoptions_t* options,
+                                    rocks_column_family_handle_t* column_family, const char* key, size_t keylen,
+                                    rocks_pinnable_slice_t* value, rocks_status_t** status);
+void rocks_transaction_get_for_update_pinnable(rocks_transaction_t* txn, const rocks_readoptions_t* options,
+                                               rocks_column_family_handle_t* column_family, const char* key,
+                                               size_t keylen, rocks_pinnable_slice_t* value, unsigned char exclusive,
+                                               rocks_status_t** status);
+void rocks_transaction_undo_get_for_update(rocks_transaction_t* txn, rocks_column_family_handle_t* column_family,
+                                           const char* key, size_t keylen);
+rocks_iterator_t* rocks_transaction_create_iterator(rocks_transaction_t* txn, const rocks_readoptions_t* options,
+                                                    rocks_column_family_handle_t* column_family);
+
 /* convenience */
 int* rocks_get_supported_compressions(size_t* len);
 void rocks_get_supported_compressions_destroy(int* ptr);
 void rocks_cancel_all_background_work(rocks_db_t* db, unsigned char wait);
 void rocks_db_delete_files_in_range(rocks_db_t* db, rocks_column_family_handle_t* column_family, const char* begin_ptr,
                                     size_t begin_len, const char* end_ptr, size_t end_len, rocks_status_t** status);
+void rocks_db_delete_files_in_ranges(rocks_db_t* db, rocks_column_family_handle_t* column_family,
+                                     const char* const* start_ptrs, const size_t* start_lens,
+                                     const char* const* limit_ptrs, const size_t* limit_lens, size_t num_ranges,
+                                     unsigned char include_end, rocks_status_t** status);
 // cxx_string_destroy must be called for following
 cxx_string_t* rocks_get_string_from_dboptions(rocks_dboptions_t* opts);
 cxx_string_t* rocks_get_string_from_cfoptions(rocks_cfoptions_t* opts);
+rocks_cfoptions_t* rocks_get_cfoptions_from_string(const rocks_cfoptions_t* base, const char* opts_str,
+                                                   size_t opts_len, rocks_status_t** status);
 
 /* table_properties */
 void rocks_table_props_collection_destroy(rocks_table_props_collection_t* coll);
@@ -1446,6 +1798,16 @@ uint64_t rocks_table_props_get_num_entries(rocks_table_props_t* prop);
 uint64_t rocks_table_props_get_format_version(rocks_table_props_t* prop);
 uint64_t rocks_table_props_get_fixed_key_len(rocks_table_props_t* prop);
 uint32_t rocks_table_props_get_column_family_id(rocks_table_props_t* prop);
+uint64_t rocks_table_props_get_index_partitions(rocks_table_props_t* prop);
+uint64_t rocks_table_props_get_top_level_index_size(rocks_table_props_t* prop);
+uint64_t rocks_table_props_get_index_key_is_user_key(rocks_table_props_t* prop);
+uint64_t rocks_table_props_get_index_value_is_delta_encoded(rocks_table_props_t* prop);
+uint64_t rocks_table_props_get_num_deletions(rocks_table_props_t* prop);
+uint64_t rocks_table_props_get_num_merge_operands(rocks_table_props_t* prop);
+uint64_t rocks_table_props_get_num_range_deletions(rocks_table_props_t* prop);
+uint64_t rocks_table_props_get_creation_time(rocks_table_props_t* prop);
+uint64_t rocks_table_props_get_oldest_key_time(rocks_table_props_t* prop);
+uint64_t rocks_table_props_get_file_creation_time(rocks_table_props_t* prop);
 const char* rocks_table_props_get_column_family_name(rocks_table_props_t* prop, size_t* len);
 const char* rocks_table_props_get_filter_policy_name(rocks_table_props_t* prop, size_t* len);
 const char* rocks_table_props_get_comparator_name(rocks_table_props_t* prop, size_t* len);
@@ -1478,12 +1840,16 @@ const char* rocks_user_collected_props_iter_value(rocks_user_collected_props_ite
 
 /* write_buffer_manager */
 rocks_write_buffer_manager_t* rocks_write_buffer_manager_create(size_t buffer_size);
+rocks_write_buffer_manager_t* rocks_write_buffer_manager_create_with_cache(size_t buffer_size, rocks_cache_t* cache);
 
 void rocks_write_buffer_manager_destroy(rocks_write_buffer_manager_t* manager);
 
 unsigned char rocks_write_buffer_manager_enabled(rocks_write_buffer_manager_t* manager);
+unsigned char rocks_write_buffer_manager_cost_to_cache(rocks_write_buffer_manager_t* manager);
 size_t rocks_write_buffer_manager_memory_usage(rocks_write_buffer_manager_t* manager);
+size_t rocks_write_buffer_manager_mutable_memtable_memory_usage(rocks_write_buffer_manager_t* manager);
 size_t rocks_write_buffer_manager_buffer_size(rocks_write_buffer_manager_t* manager);
+unsigned char rocks_write_buffer_manager_should_flush(rocks_write_buffer_manager_t* manager);
 
 /* debug */
 rocks_key_version_collection_t* rocks_db_get_all_key_versions(rocks_db_t* db, const char* begin_key,
@@ -1607,6 +1973,17 @@ uint64_t rocks_external_file_ingestion_info_get_global_seqno(const rocks_externa
 rocks_table_props_t* rocks_external_file_ingestion_info_get_table_properties(
     const rocks_external_file_ingestion_info_t* info);
 
+const char* rocks_write_stall_info_get_cf_name(const rocks_write_stall_info_t* info, size_t* len);
+int rocks_write_stall_info_get_cur(const rocks_write_stall_info_t* info);
+int rocks_write_stall_info_get_prev(const rocks_write_stall_info_t* info);
+
+const char* rocks_file_operation_info_get_path(const rocks_file_operation_info_t* info, size_t* len);
+uint64_t rocks_file_operation_info_get_offset(const rocks_file_operation_info_t* info);
+size_t rocks_file_operation_info_get_length(const rocks_file_operation_info_t* info);
+int64_t rocks_file_operation_info_get_start_timestamp(const rocks_file_operation_info_t* info);
+int64_t rocks_file_operation_info_get_finish_timestamp(const rocks_file_operation_info_t* info);
+void rocks_file_operation_info_get_status(const rocks_file_operation_info_t* info, rocks_status_t** status);
+
 /* thread_status */
 void rocks_thread_status_destroy(rocks_thread_status_t* status);
 
@@ -1625,6 +2002,34 @@ rocks_column_family_descriptor_t** rocks_load_latest_options(const char* c_dbpat
                                                              size_t* cf_descs_len, rocks_status_t** status);
 void rocks_load_options_destroy_cf_descs(rocks_column_family_descriptor_t** c_cf_descs, size_t len);
 
+/* memory_util */
+/* usage_by_type must hold 4 values, in the order of MemoryUtil::UsageType */
+void rocks_get_approximate_memory_usage_by_type(rocks_db_t* const* dbs, size_t num_dbs,
+                                                rocks_cache_t* const* caches, size_t num_caches,
+                                                uint64_t* usage_by_type, rocks_status_t** status);
+
+/* stats_history */
+void rocks_stats_history_iterator_destroy(rocks_stats_history_iterator_t* it);
+unsigned char rocks_stats_history_iterator_valid(rocks_stats_history_iterator_t* it);
+void rocks_stats_history_iterator_next(rocks_stats_history_iterator_t* it);
+void rocks_stats_history_iterator_status(rocks_stats_history_iterator_t* it, rocks_status_t** status);
+uint64_t rocks_stats_history_iterator_get_stats_time(rocks_stats_history_iterator_t* it);
+size_t rocks_stats_history_iterator_stats_map_size(rocks_stats_history_iterator_t* it);
+/* values must have rocks_stats_history_iterator_stats_map_size() slots */
+void rocks_stats_history_iterator_get_stats_map(rocks_stats_history_iterator_t* it, cxx_string_vector_t* names,
+                                                uint64_t* values);
+
+/* checkpoint */
+rocks_checkpoint_t* rocks_checkpoint_create(rocks_db_t* db, rocks_status_t** status);
+void rocks_checkpoint_destroy(rocks_checkpoint_t* checkpoint);
+void rocks_checkpoint_create_checkpoint(rocks_checkpoint_t* checkpoint, const char* checkpoint_dir, size_t dir_len,
+                                        uint64_t log_size_for_flush, rocks_status_t** status);
+/* db_comparator_name is a Rust String, returns NULL on error */
+rocks_livefiles_t* rocks_checkpoint_export_column_family(rocks_checkpoint_t* checkpoint,
+                                                         rocks_column_family_handle_t* column_family,
+                                                         const char* export_dir, size_t dir_len,
+                                                         void* db_comparator_name, rocks_status_t** status);
+
 /* aux */
 void free(void* p);
 
@@ -1633,6 +2038,7 @@ const void* cxx_vector_slice_nth(const void* list, size_t n);
 
 /* std::string */
 void cxx_string_assign(cxx_string_t* s, const char* p, size_t len);
+void cxx_string_append(cxx_string_t* s, const char* p, size_t len);
 const char* cxx_string_data(const cxx_string_t* s);
 size_t cxx_string_size(const cxx_string_t* s);
 void cxx_string_destroy(cxx_string_t* s);
@@ -1642,6 +2048,7 @@ void cxx_string_vector_destory(cxx_string_vector_t* v);
 size_t cxx_string_vector_size(cxx_string_vector_t* v);
 const char* cxx_string_vector_nth(cxx_string_vector_t* v, size_t index);
 size_t cxx_string_vector_nth_size(cxx_string_vector_t* v, size_t index);
+void cxx_string_vector_push(cxx_string_vector_t* v, const char* p, size_t len);
 
 #ifdef __cplusplus
 }
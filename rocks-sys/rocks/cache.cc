@@ -12,6 +12,7 @@ rocks_cache_t* rocks_cache_create_lru(size_t capacity, int num_shard_bits, char
                                       double high_pri_pool_ratio) {
   rocks_cache_t* c = new rocks_cache_t;
   c->rep = NewLRUCache(capacity, num_shard_bits, strict_capacity_limit, high_pri_pool_ratio);
+  c->high_pri_pool_ratio = high_pri_pool_ratio;
   return c;
 }
 
@@ -25,6 +26,8 @@ rocks_cache_t* rocks_cache_create_clock(size_t capacity, int num_shard_bits, cha
   return c;
 }
 
+rocks_cache_t* rocks_cache_clone(rocks_cache_t* cache) { return new rocks_cache_t{cache->rep, cache->high_pri_pool_ratio}; }
+
 void rocks_cache_destroy(rocks_cache_t* cache) { delete cache; }
 
 void rocks_cache_set_capacity(rocks_cache_t* cache, size_t capacity) { cache->rep->SetCapacity(capacity); }
@@ -36,6 +39,16 @@ size_t rocks_cache_get_usage(rocks_cache_t* cache) { return cache->rep->GetUsage
 size_t rocks_cache_get_pinned_usage(rocks_cache_t* cache) { return cache->rep->GetPinnedUsage(); }
 
 const char* rocks_cache_name(rocks_cache_t* cache) { return cache->rep->Name(); }
+
+double rocks_cache_get_high_pri_pool_ratio(rocks_cache_t* cache) { return cache->high_pri_pool_ratio; }
+
+void rocks_cache_set_strict_capacity_limit(rocks_cache_t* cache, unsigned char strict_capacity_limit) {
+  cache->rep->SetStrictCapacityLimit(strict_capacity_limit);
+}
+
+unsigned char rocks_cache_has_strict_capacity_limit(rocks_cache_t* cache) {
+  return cache->rep->HasStrictCapacityLimit();
+}
 }
 
 // persistent_cache
@@ -0,0 +1,41 @@
+#include "rocksdb/utilities/checkpoint.h"
+
+#include "rocks/ctypes.hpp"
+
+using namespace ROCKSDB_NAMESPACE;
+
+extern "C" {
+rocks_checkpoint_t* rocks_checkpoint_create(rocks_db_t* db, rocks_status_t** status) {
+  Checkpoint* checkpoint = nullptr;
+  if (SaveError(status, Checkpoint::Create(db->rep, &checkpoint))) {
+    return nullptr;
+  }
+  return new rocks_checkpoint_t{checkpoint};
+}
+
+void rocks_checkpoint_destroy(rocks_checkpoint_t* checkpoint) {
+  delete checkpoint->rep;
+  delete checkpoint;
+}
+
+void rocks_checkpoint_create_checkpoint(rocks_checkpoint_t* checkpoint, const char* checkpoint_dir, size_t dir_len,
+                                        uint64_t log_size_for_flush, rocks_status_t** status) {
+  SaveError(status, checkpoint->rep->CreateCheckpoint(std::string(checkpoint_dir, dir_len), log_size_for_flush));
+}
+
+rocks_livefiles_t* rocks_checkpoint_export_column_family(rocks_checkpoint_t* checkpoint,
+                                                         rocks_column_family_handle_t* column_family,
+                                                         const char* export_dir, size_t dir_len,
+                                                         void* db_comparator_name, rocks_status_t** status) {
+  ExportImportFilesMetaData* metadata = nullptr;
+  auto st = checkpoint->rep->ExportColumnFamily(column_family->rep, std::string(export_dir, dir_len), &metadata);
+  if (SaveError(status, std::move(st))) {
+    return nullptr;
+  }
+  rust_string_assign(db_comparator_name, metadata->db_comparator_name.data(), metadata->db_comparator_name.size());
+  auto files = new rocks_livefiles_t;
+  files->rep.swap(metadata->files);
+  delete metadata;
+  return files;
+}
+}
@@ -5,6 +5,10 @@
 using namespace ROCKSDB_NAMESPACE;
 
 extern "C" {
+rocks_comparator_t* rocks_comparator_create(void* cp_trait_obj) { return new rocks_comparator_t{cp_trait_obj}; }
+
+void rocks_comparator_destroy(rocks_comparator_t* comparator) { delete comparator; }
+
 const Comparator* rocks_comparator_bytewise() { return BytewiseComparator(); }
 
 const Comparator* rocks_comparator_bytewise_reversed() { return ReverseBytewiseComparator(); }
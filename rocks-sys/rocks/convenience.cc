@@ -29,6 +29,25 @@ void rocks_db_delete_files_in_range(rocks_db_t* db, rocks_column_family_handle_t
   SaveError(status, std::move(st));
 }
 
+void rocks_db_delete_files_in_ranges(rocks_db_t* db, rocks_column_family_handle_t* column_family,
+                                     const char* const* start_ptrs, const size_t* start_lens,
+                                     const char* const* limit_ptrs, const size_t* limit_lens, size_t num_ranges,
+                                     unsigned char include_end, rocks_status_t** status) {
+  std::vector<Slice> starts;
+  std::vector<Slice> limits;
+  std::vector<RangePtr> ranges;
+  starts.reserve(num_ranges);
+  limits.reserve(num_ranges);
+  ranges.reserve(num_ranges);
+  for (size_t i = 0; i < num_ranges; i++) {
+    starts.emplace_back(start_ptrs[i], start_lens[i]);
+    limits.emplace_back(limit_ptrs[i], limit_lens[i]);
+    ranges.emplace_back(&starts[i], &limits[i]);
+  }
+  auto st = DeleteFilesInRanges(db->rep, column_family->rep, ranges.data(), num_ranges, include_end != 0);
+  SaveError(status, std::move(st));
+}
+
 cxx_string_t* rocks_get_string_from_dboptions(rocks_dboptions_t* opts) {
   auto str = new std::string();
   auto st = GetStringFromDBOptions(str, opts->rep);
@@ -50,4 +69,15 @@ cxx_string_t* rocks_get_string_from_cfoptions(rocks_cfoptions_t* opts) {
     return nullptr;
   }
 }
+
+rocks_cfoptions_t* rocks_get_cfoptions_from_string(const rocks_cfoptions_t* base, const char* opts_str,
+                                                   size_t opts_len, rocks_status_t** status) {
+  ColumnFamilyOptions new_options;
+  auto st = GetColumnFamilyOptionsFromString(base->rep, std::string(opts_str, opts_len), &new_options);
+  if (!st.ok()) {
+    SaveError(status, std::move(st));
+    return nullptr;
+  }
+  return new rocks_cfoptions_t{new_options};
+}
 }
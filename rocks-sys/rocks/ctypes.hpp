@@ -4,27 +4,37 @@
 #define __RUST_ROCSK_SYS_H____
 
 #include <iostream>
+#include <map>
+#include <mutex>
+#include <unordered_map>
+#include <unordered_set>
 
 #include "rocksdb/cache.h"
 #include "rocksdb/compaction_filter.h"
 #include "rocksdb/db.h"
 #include "rocksdb/db_dump_tool.h"
 #include "rocksdb/env.h"
+#include "rocksdb/env_encryption.h"
 #include "rocksdb/filter_policy.h"
 #include "rocksdb/iterator.h"
 #include "rocksdb/listener.h"
 #include "rocksdb/merge_operator.h"
 #include "rocksdb/metadata.h"
 #include "rocksdb/options.h"
+#include "rocksdb/perf_context.h"
 #include "rocksdb/persistent_cache.h"
 #include "rocksdb/rate_limiter.h"
 #include "rocksdb/slice_transform.h"
 #include "rocksdb/sst_file_writer.h"
+#include "rocksdb/stats_history.h"
 #include "rocksdb/status.h"
 #include "rocksdb/table.h"
 #include "rocksdb/table_properties.h"
 #include "rocksdb/transaction_log.h"
+#include "rocksdb/utilities/checkpoint.h"
 #include "rocksdb/utilities/debug.h"
+#include "rocksdb/utilities/optimistic_transaction_db.h"
+#include "rocksdb/utilities/transaction_db.h"
 #include "rocksdb/write_buffer_manager.h"
 #include "rust_export.h"
 
@@ -86,6 +96,9 @@ struct rocks_column_family_handle_t {
 };
 struct rocks_db_t {
   DB* rep;
+  // prefix extractors by column family id, for prefix iterators, reset by SetOptions
+  std::mutex prefix_extractors_mu;
+  std::unordered_map<uint32_t, std::shared_ptr<const SliceTransform>> prefix_extractors;
 };
 
 /* options */
@@ -143,6 +156,16 @@ struct rocks_mergeoperator_t : public MergeOperator {
     }
     return ret != 0;
   }
+
+  bool PartialMergeMulti(const Slice& key, const std::deque<Slice>& operand_list, std::string* new_value,
+                         Logger* logger) const override {
+    // contiguous, for a Rust slice
+    std::vector<Slice> operands(operand_list.begin(), operand_list.end());
+    return rust_merge_operator_call_partial_merge_multi(this->obj, &key, operands.data(), operands.size(),
+                                                        new_value) != 0;
+  }
+
+  bool AllowSingleOperand() const override { return rust_merge_operator_allow_single_operand(this->obj) != 0; }
 };
 
 struct rocks_associative_mergeoperator_t : public AssociativeMergeOperator {
@@ -170,12 +193,10 @@ struct rocks_associative_mergeoperator_t : public AssociativeMergeOperator {
 
 /* comparator */
 struct rocks_comparator_t : public Comparator {
-  void* obj;  // rust Box<trait obj>
+  void* obj;  // rust Box<Arc<trait obj>>
 
   rocks_comparator_t(void* trait_obj) : obj(trait_obj) {}
 
-  // FIXME: since Options->comparator is a raw pointer
-  //  this may not be called
   ~rocks_comparator_t() { rust_comparator_drop(this->obj); }
 
   int Compare(const Slice& a, const Slice& b) const override { return rust_comparator_compare(this->obj, &a, &b); }
@@ -204,9 +225,19 @@ struct rocks_logger_t {
   shared_ptr<Logger> rep;
 };
 
+struct rocks_filelock_t {
+  FileLock* rep;
+};
+
+class LatencyInjectionEnv;  // env.cc
+
 struct rocks_env_t {
   Env* rep;
   bool is_default;
+  LatencyInjectionEnv* latency = nullptr;  // same object as rep, if created as a latency injection env
+  // referenced by an encrypted env, destroyed after rep
+  std::unique_ptr<BlockCipher> cipher;
+  std::unique_ptr<EncryptionProvider> encryption;
 };
 
 /* snapshot*/
@@ -300,6 +331,8 @@ struct rocks_raw_filterpolicy_t {
 /* cache */
 struct rocks_cache_t {
   shared_ptr<Cache> rep;
+  // not exposed by the Cache interface
+  double high_pri_pool_ratio = 0.0;
 };
 
 /* sst_file_writer */
@@ -348,16 +381,19 @@ struct rocks_slice_transform_t : public SliceTransform {
 
   bool InDomain(const Slice& key) const override { return rust_slice_transform_in_domain(this->obj, &key) != 0; }
 
-  // not used and remains here for backward compatibility.
-  bool InRange(const Slice& dst) const override { return false; }
+  bool InRange(const Slice& dst) const override { return rust_slice_transform_in_range(this->obj, &dst) != 0; }
 };
 
 /* db_dump_tool */
 struct rocks_dump_options_t {
   DumpOptions rep;
+  std::string column_family = kDefaultColumnFamilyName;
+  std::map<std::string, ColumnFamilyOptions> cf_options;
 };
 struct rocks_undump_options_t {
   UndumpOptions rep;
+  std::string column_family = kDefaultColumnFamilyName;
+  std::map<std::string, ColumnFamilyOptions> cf_options;
 };
 
 /* iostats_context */
@@ -365,6 +401,10 @@ typedef struct rocks_iostats_context_t rocks_iostats_context_t;
 
 /* perf_context */
 typedef struct rocks_perf_context_t rocks_perf_context_t;
+// same layout as PerfContextByLevel, read as perf_context::PerfContextByLevel by rust
+struct rocks_perf_context_by_level_t {
+  PerfContextByLevel rep;
+};
 
 /* statistics */
 struct rocks_statistics_t {
@@ -376,6 +416,9 @@ typedef struct rocks_histogram_data_t rocks_histogram_data_t;
 struct rocks_livefiles_t {
   std::vector<LiveFileMetaData> rep;
 };
+struct rocks_checkpoint_t {
+  Checkpoint* rep;
+};
 struct rocks_column_family_metadata_t {
   ColumnFamilyMetaData rep;
 };
@@ -394,6 +437,31 @@ struct rocks_transaction_log_iterator_t {
   std::unique_ptr<TransactionLogIterator> rep;
 };
 
+struct rocks_stats_history_iterator_t {
+  std::unique_ptr<StatsHistoryIterator> rep;
+};
+
+/* transaction */
+struct rocks_transactiondb_options_t {
+  TransactionDBOptions rep;
+};
+
+struct rocks_transaction_options_t {
+  TransactionOptions rep;
+};
+
+struct rocks_optimistic_transaction_options_t {
+  OptimisticTransactionOptions rep;
+};
+
+struct rocks_transaction_t {
+  Transaction* rep;
+  // for the default column family
+  DB* db;
+  // returned by rocks_transaction_get_snapshot, owned by the transaction
+  rocks_snapshot_t snapshot;
+};
+
 /* table_properties */
 struct rocks_table_props_collection_t {
   // std::unordered_map<std::string, std::shared_ptr<const TableProperties>>
@@ -438,8 +506,11 @@ struct rocks_table_props_collector_t : public TablePropertiesCollector {
     return Status::OK();
   }
 
-  // TODO:
-  UserCollectedProperties GetReadableProperties() const override { return UserCollectedProperties{}; }
+  UserCollectedProperties GetReadableProperties() const override {
+    UserCollectedProperties props;
+    rust_table_props_collector_readable_properties(this->obj, &props);
+    return props;
+  }
 
   bool NeedCompact() const override { return rust_table_props_collector_need_compact(this->obj); }
 };
@@ -472,6 +543,7 @@ struct rocks_key_version_collection_t {
 };
 
 /* listener */
+// CompactionEventListener was removed in RocksDB 6.0
 /*
 struct rocks_compaction_event_listener_t : public CompactionEventListener {
   void* obj;  // rust *mut &mut TraitObj
@@ -487,13 +559,36 @@ struct rocks_compaction_event_listener_t : public CompactionEventListener {
 };
 */
 
+// live rocks_event_listener_t objects, to tell them from other listeners without RTTI
+inline std::mutex& rocks_event_listeners_mu() {
+  static std::mutex mu;
+  return mu;
+}
+
+inline std::unordered_set<const EventListener*>& rocks_event_listeners() {
+  static std::unordered_set<const EventListener*> listeners;
+  return listeners;
+}
+
 struct rocks_event_listener_t : public EventListener {
-  void* obj;  // rust Box<trait obj>
+  void* obj;        // rust Box<trait obj>
+  const void* key;  // identity of a shared rust listener, nullptr if not shared
   // std::vector<std::unique_ptr<rocks_compaction_event_listener_t>> compaction_listeners;
 
-  rocks_event_listener_t(void* trait_obj) : obj(trait_obj) {}
+  rocks_event_listener_t(void* trait_obj) : rocks_event_listener_t(trait_obj, nullptr) {}
 
-  ~rocks_event_listener_t() { rust_event_listener_drop(this->obj); }
+  rocks_event_listener_t(void* trait_obj, const void* key) : obj(trait_obj), key(key) {
+    std::lock_guard<std::mutex> lock(rocks_event_listeners_mu());
+    rocks_event_listeners().insert(this);
+  }
+
+  ~rocks_event_listener_t() {
+    {
+      std::lock_guard<std::mutex> lock(rocks_event_listeners_mu());
+      rocks_event_listeners().erase(this);
+    }
+    rust_event_listener_drop(this->obj);
+  }
 
   void OnFlushCompleted(DB* db, const FlushJobInfo& flush_job_info) override {
     rust_event_listener_on_flush_completed(this->obj, &db, &flush_job_info);
@@ -532,13 +627,35 @@ struct rocks_event_listener_t : public EventListener {
   void OnBackgroundError(BackgroundErrorReason reason, Status* bg_error) override {
     rocks_status_t* st = nullptr;
     SaveError(&st, Status(*bg_error));  // must an error here :)
+    // st is owned by rust, the returned status is owned by us
     auto ret = rust_event_listener_on_background_error(this->obj, reason, st);
-    if (ret == 0) {
+    if (ret == nullptr) {
       *bg_error = Status::OK();  // suppress errors
+    } else {
+      *bg_error = ret->rep;
+      delete ret;
     }
-    if (st != nullptr) {
-      delete st;
-    }
+  }
+
+  void OnStallConditionsChanged(const WriteStallInfo& info) override {
+    rust_event_listener_on_stall_conditions_changed(this->obj, &info);
+  }
+
+  void OnFileReadFinish(const FileOperationInfo& info) override {
+    rust_event_listener_on_file_read_finish(this->obj, &info);
+  }
+
+  void OnFileWriteFinish(const FileOperationInfo& info) override {
+    rust_event_listener_on_file_write_finish(this->obj, &info);
+  }
+
+  bool ShouldBeNotifiedOnFileIO() override { return rust_event_listener_should_be_notified_on_file_io(this->obj); }
+
+  void OnErrorRecoveryCompleted(Status old_bg_error) override {
+    rocks_status_t* st = nullptr;
+    SaveError(&st, std::move(old_bg_error));
+    // st is owned by rust
+    rust_event_listener_on_error_recovery_completed(this->obj, st);
   }
 
   /*
@@ -5,6 +5,9 @@
 
 #include "rocks/ctypes.hpp"
 #include "rocks/rust_export.h"
+#include "rocksdb/experimental.h"
+#include "rocksdb/slice_transform.h"
+#include "rocksdb/utilities/db_ttl.h"
 #include "rocksdb/utilities/info_log_finder.h"
 
 using namespace ROCKSDB_NAMESPACE;
@@ -114,6 +117,68 @@ void rocks_db_try_catch_up_with_primary(rocks_db_t* db, rocks_status_t** status)
   SaveError(status, db->rep->TryCatchUpWithPrimary());
 }
 
+rocks_db_t* rocks_db_open_with_ttl(const rocks_options_t* options, const char* name, int32_t ttl,
+                                   unsigned char read_only, rocks_status_t** status) {
+  DBWithTTL* db = nullptr;
+  auto st = DBWithTTL::Open(options->rep, std::string(name), &db, ttl, read_only);
+  if (SaveError(status, std::move(st))) {
+    return nullptr;
+  } else {
+    rocks_db_t* result = new rocks_db_t;
+    result->rep = db;
+    return result;
+  }
+}
+
+rocks_db_t* rocks_db_open_column_families_with_ttl(const rocks_dboptions_t* db_options, const char* name,
+                                                   int num_column_families, const char* const* column_family_names,
+                                                   const rocks_cfoptions_t* const* column_family_options,
+                                                   rocks_column_family_handle_t** column_family_handles,
+                                                   const int32_t* ttls, unsigned char read_only,
+                                                   rocks_status_t** status) {
+  std::vector<ColumnFamilyDescriptor> column_families;
+  for (int i = 0; i < num_column_families; i++) {
+    column_families.push_back(ColumnFamilyDescriptor(std::string(column_family_names[i]),
+                                                     ColumnFamilyOptions(column_family_options[i]->rep)));
+  }
+  std::vector<int32_t> ttl_vec(ttls, ttls + num_column_families);
+
+  DBWithTTL* db = nullptr;
+  std::vector<ColumnFamilyHandle*> handles;
+  auto st = DBWithTTL::Open(db_options->rep, std::string(name), column_families, &handles, &db, ttl_vec, read_only);
+  if (SaveError(status, std::move(st))) {
+    return nullptr;
+  }
+
+  for (size_t i = 0; i < handles.size(); i++) {
+    rocks_column_family_handle_t* c_handle = new rocks_column_family_handle_t;
+    c_handle->rep = handles[i];
+    column_family_handles[i] = c_handle;
+  }
+  rocks_db_t* result = new rocks_db_t;
+  result->rep = db;
+  return result;
+}
+
+rocks_column_family_handle_t* rocks_db_create_column_family_with_ttl(rocks_db_t* db,
+                                                                     const rocks_cfoptions_t* column_family_options,
+                                                                     const char* column_family_name, int32_t ttl,
+                                                                     rocks_status_t** status) {
+  rocks_column_family_handle_t* handle = new rocks_column_family_handle_t;
+  auto st = static_cast<DBWithTTL*>(db->rep)->CreateColumnFamilyWithTtl(
+      ColumnFamilyOptions(column_family_options->rep), std::string(column_family_name), &(handle->rep), ttl);
+  if (SaveError(status, std::move(st))) {
+    delete handle;
+    handle = nullptr;
+  }
+  return handle;
+}
+
+// db must be opened by rocks_db_open_with_ttl or rocks_db_open_column_families_with_ttl
+void rocks_db_set_ttl(rocks_db_t* db, rocks_column_family_handle_t* column_family, int32_t ttl) {
+  static_cast<DBWithTTL*>(db->rep)->SetTtl(column_family->rep, ttl);
+}
+
 rocks_db_t* rocks_db_open_column_families(const rocks_dboptions_t* db_options, const char* name,
                                           int num_column_families, const char* const* column_family_names,
                                           const rocks_cfoptions_t* const* column_family_options,
@@ -208,6 +273,26 @@ rocks_column_family_handle_t* rocks_db_create_column_family(rocks_db_t* db,
   return handle;
 }
 
+rocks_column_family_handle_t* rocks_db_create_column_family_with_import(
+    rocks_db_t* db, const rocks_cfoptions_t* column_family_options, const char* column_family_name,
+    unsigned char move_files, const char* db_comparator_name, size_t db_comparator_name_len,
+    const rocks_livefiles_t* files, rocks_status_t** status) {
+  ImportColumnFamilyOptions import_options;
+  import_options.move_files = move_files;
+  ExportImportFilesMetaData metadata;
+  metadata.db_comparator_name = std::string(db_comparator_name, db_comparator_name_len);
+  metadata.files = files->rep;
+  rocks_column_family_handle_t* handle = new rocks_column_family_handle_t;
+  auto st = db->rep->CreateColumnFamilyWithImport(ColumnFamilyOptions(column_family_options->rep),
+                                                  std::string(column_family_name), import_options, metadata,
+                                                  &(handle->rep));
+  if (SaveError(status, std::move(st))) {
+    delete handle;
+    handle = nullptr;
+  }
+  return handle;
+}
+
 rocks_column_family_handle_t* rocks_db_default_column_family(rocks_db_t* db) {
   return new rocks_column_family_handle_t{db->rep->DefaultColumnFamily()};
 }
@@ -216,6 +301,16 @@ void rocks_db_drop_column_family(rocks_db_t* db, rocks_column_family_handle_t* h
   SaveError(status, db->rep->DropColumnFamily(handle->rep));
 }
 
+void rocks_db_drop_column_families(rocks_db_t* db, const rocks_column_family_handle_t* const* handles,
+                                   size_t num_handles, rocks_status_t** status) {
+  std::vector<ColumnFamilyHandle*> column_families;
+  column_families.reserve(num_handles);
+  for (size_t i = 0; i < num_handles; i++) {
+    column_families.push_back(handles[i]->rep);
+  }
+  SaveError(status, db->rep->DropColumnFamilies(column_families));
+}
+
 void rocks_db_destroy_column_family_handle(rocks_db_t* db, rocks_column_family_handle_t* handle,
                                            rocks_status_t** status) {
   SaveError(status, db->rep->DestroyColumnFamilyHandle(handle->rep));
@@ -310,6 +405,24 @@ void rocks_db_get_cf_pinnable(rocks_db_t* db, const rocks_readoptions_t* options
   SaveError(status, std::move(st));
 }
 
+unsigned char rocks_db_get_into(rocks_db_t* db, const rocks_readoptions_t* options,
+                                rocks_column_family_handle_t* column_family, const char* key, size_t keylen,
+                                void* value,  // *mut Vec<u8>
+                                rocks_status_t** status) {
+  auto cf = column_family != nullptr ? column_family->rep : db->rep->DefaultColumnFamily();
+  PinnableSlice val;
+  Status st = db->rep->Get(options->rep, cf, Slice(key, keylen), &val);
+  if (st.IsNotFound()) {
+    return false;
+  }
+  if (st.ok()) {
+    rust_vec_u8_assign(value, val.data(), val.size());
+    return true;
+  }
+  SaveError(status, std::move(st));
+  return false;
+}
+
 void rocks_db_multi_get(rocks_db_t* db, const rocks_readoptions_t* options, size_t num_keys,
                         const char* const* keys_list, const size_t* keys_list_sizes, char** values_list,
                         size_t* values_list_sizes, rocks_status_t** status) {
@@ -439,6 +552,68 @@ rocks_iterator_t* rocks_db_create_iterator_cf(rocks_db_t* db, const rocks_readop
   return result;
 }
 
+namespace {
+// upper bound of a prefix iterator, freed with the iterator
+struct PrefixUpperBound {
+  std::string key;
+  Slice slice;
+};
+
+void DeletePrefixUpperBound(void* arg1, void* /* arg2 */) { delete static_cast<PrefixUpperBound*>(arg1); }
+
+// the options are copied only on first use, afterwards just the extractor is read
+std::shared_ptr<const SliceTransform> PrefixExtractorOf(rocks_db_t* db, ColumnFamilyHandle* cf) {
+  std::lock_guard<std::mutex> lock(db->prefix_extractors_mu);
+  auto it = db->prefix_extractors.find(cf->GetID());
+  if (it == db->prefix_extractors.end()) {
+    it = db->prefix_extractors.emplace(cf->GetID(), db->rep->GetOptions(cf).prefix_extractor).first;
+  }
+  return it->second;
+}
+}  // namespace
+
+rocks_iterator_t* rocks_db_create_prefix_iterator_cf(rocks_db_t* db, const rocks_readoptions_t* options,
+                                                     rocks_column_family_handle_t* column_family, const char* prefix,
+                                                     size_t prefix_len) {
+  auto cf = column_family != nullptr ? column_family->rep : db->rep->DefaultColumnFamily();
+  Slice target(prefix, prefix_len);
+  ReadOptions read_options = options->rep;
+  auto extractor = PrefixExtractorOf(db, cf);
+  // prefix seek only when every key starting with target has exactly target as its prefix,
+  // otherwise the prefix bloom filter may skip matching keys
+  if (extractor != nullptr && extractor->InDomain(target) && extractor->Transform(target) == target &&
+      extractor->SameResultWhenAppended(target)) {
+    read_options.total_order_seek = false;
+    read_options.prefix_same_as_start = true;
+  } else {
+    read_options.total_order_seek = true;
+    read_options.prefix_same_as_start = false;
+  }
+
+  // the smallest key greater than all keys starting with target, none if target is all 0xff
+  std::string upper(prefix, prefix_len);
+  while (!upper.empty() && static_cast<unsigned char>(upper.back()) == 0xff) {
+    upper.pop_back();
+  }
+  PrefixUpperBound* bound = nullptr;
+  if (upper.empty()) {
+    read_options.iterate_upper_bound = nullptr;
+  } else {
+    upper.back() = static_cast<char>(static_cast<unsigned char>(upper.back()) + 1);
+    bound = new PrefixUpperBound{std::move(upper), Slice()};
+    bound->slice = Slice(bound->key);
+    read_options.iterate_upper_bound = &bound->slice;
+  }
+
+  rocks_iterator_t* result = new rocks_iterator_t;
+  result->rep = db->rep->NewIterator(read_options, cf);
+  if (bound != nullptr) {
+    result->rep->RegisterCleanup(&DeletePrefixUpperBound, bound, nullptr);
+  }
+  result->rep->Seek(target);
+  return result;
+}
+
 void rocks_db_create_iterators(rocks_db_t* db, rocks_readoptions_t* opts,
                                rocks_column_family_handle_t** column_families, rocks_iterator_t** iterators,
                                size_t size, rocks_status_t** status) {
@@ -493,6 +668,20 @@ unsigned char rocks_db_get_property_cf(rocks_db_t* db, rocks_column_family_handl
   return has;
 }
 
+unsigned char rocks_db_get_map_property_cf(rocks_db_t* db, rocks_column_family_handle_t* cf, const char* prop,
+                                           const size_t prop_len, cxx_string_vector_t* keys,
+                                           cxx_string_vector_t* values) {
+  std::map<std::string, std::string> cval;
+  auto has = db->rep->GetMapProperty(cf->rep, Slice(prop, prop_len), &cval);
+  if (has) {
+    for (const auto& kv : cval) {
+      keys->rep.push_back(kv.first);
+      values->rep.push_back(kv.second);
+    }
+  }
+  return has;
+}
+
 unsigned char rocks_db_get_int_property(rocks_db_t* db, const char* prop, const size_t prop_len, uint64_t* value) {
   auto has = db->rep->GetIntProperty(Slice(prop, prop_len), value);
   return has;
@@ -551,6 +740,21 @@ void rocks_db_compact_range_opt_cf(rocks_db_t* db, rocks_compactrange_options_t*
   SaveError(status, std::move(st));
 }
 
+void rocks_db_suggest_compact_range(rocks_db_t* db, rocks_column_family_handle_t* column_family, const char* start_key,
+                                    size_t start_key_len, const char* limit_key, size_t limit_key_len,
+                                    rocks_status_t** status) {
+  Slice a, b;
+  auto st = experimental::SuggestCompactRange(db->rep, column_family->rep,
+                                              (start_key ? (a = Slice(start_key, start_key_len), &a) : nullptr),
+                                              (limit_key ? (b = Slice(limit_key, limit_key_len), &b) : nullptr));
+  SaveError(status, std::move(st));
+}
+
+void rocks_db_promote_l0(rocks_db_t* db, rocks_column_family_handle_t* column_family, int target_level,
+                         rocks_status_t** status) {
+  SaveError(status, experimental::PromoteL0(db->rep, column_family->rep, target_level));
+}
+
 void rocks_db_set_options_cf(rocks_db_t* db, rocks_column_family_handle_t* column_family, size_t num_options,
                              const char* const* keys, const size_t* key_lens, const char* const* vals,
                              const size_t* val_lens, rocks_status_t** status) {
@@ -559,6 +763,10 @@ void rocks_db_set_options_cf(rocks_db_t* db, rocks_column_family_handle_t* colum
     new_options[std::string(keys[i], key_lens[i])] = std::string(vals[i], val_lens[i]);
   }
   auto st = db->rep->SetOptions(column_family->rep, new_options);
+  if (new_options.count("prefix_extractor") != 0) {
+    std::lock_guard<std::mutex> lock(db->prefix_extractors_mu);
+    db->prefix_extractors.erase(column_family->rep->GetID());
+  }
   SaveError(status, std::move(st));
 }
 
@@ -591,6 +799,10 @@ void rocks_db_continue_background_work(rocks_db_t* db, rocks_status_t** status)
   SaveError(status, std::move(db->rep->ContinueBackgroundWork()));
 }
 
+void rocks_db_disable_manual_compaction(rocks_db_t* db) { db->rep->DisableManualCompaction(); }
+
+void rocks_db_enable_manual_compaction(rocks_db_t* db) { db->rep->EnableManualCompaction(); }
+
 void rocks_db_enable_auto_compaction(rocks_db_t* db, const rocks_column_family_handle_t* const* column_families,
                                      size_t cf_len, rocks_status_t** status) {
   std::vector<ColumnFamilyHandle*> cfs;
@@ -630,6 +842,25 @@ void rocks_db_get_approximate_sizes_cf(rocks_db_t* db, rocks_column_family_handl
   db->rep->GetApproximateSizes(column_family->rep, ranges.data(), num_ranges, sizes);
 }
 
+void rocks_db_get_approximate_sizes_cf_opt(rocks_db_t* db, rocks_column_family_handle_t* column_family,
+                                           unsigned char include_memtables, unsigned char include_files,
+                                           double files_size_error_margin, size_t num_ranges,
+                                           const char* const* range_start_ptrs, const size_t* range_start_lens,
+                                           const char* const* range_limit_ptrs, const size_t* range_limit_lens,
+                                           uint64_t* sizes, rocks_status_t** status) {
+  std::vector<Range> ranges;
+  for (size_t i = 0; i < num_ranges; i++) {
+    ranges.push_back(
+        Range(Slice(range_start_ptrs[i], range_start_lens[i]), Slice(range_limit_ptrs[i], range_limit_lens[i])));
+  }
+  SizeApproximationOptions options;
+  options.include_memtabtles = include_memtables;
+  options.include_files = include_files;
+  options.files_size_error_margin = files_size_error_margin;
+  SaveError(status, db->rep->GetApproximateSizes(options, column_family->rep, ranges.data(),
+                                                 static_cast<int>(num_ranges), sizes));
+}
+
 void rocks_db_get_approximate_memtable_stats_cf(rocks_db_t* db, rocks_column_family_handle_t* column_family,
                                                 const char* range_start_ptr, size_t range_start_len,
                                                 const char* range_limit_ptr, size_t range_limit_len, uint64_t* count,
@@ -663,8 +894,22 @@ void rocks_db_flush_cf(rocks_db_t* db, rocks_flushoptions_t* options, rocks_colu
   SaveError(status, std::move(db->rep->Flush(options->rep, column_family->rep)));
 }
 
+void rocks_db_flush_cfs(rocks_db_t* db, rocks_flushoptions_t* options,
+                        const rocks_column_family_handle_t* const* column_families, size_t num_column_families,
+                        rocks_status_t** status) {
+  std::vector<ColumnFamilyHandle*> cfs(num_column_families);
+  for (size_t i = 0; i < num_column_families; i++) {
+    cfs[i] = column_families[i]->rep;
+  }
+  SaveError(status, std::move(db->rep->Flush(options->rep, cfs)));
+}
+
 void rocks_db_sync_wal(rocks_db_t* db, rocks_status_t** status) { SaveError(status, std::move(db->rep->SyncWAL())); }
 
+void rocks_db_flush_wal(rocks_db_t* db, unsigned char sync, rocks_status_t** status) {
+  SaveError(status, std::move(db->rep->FlushWAL(sync)));
+}
+
 uint64_t rocks_db_get_latest_sequence_number(rocks_db_t* db) { return db->rep->GetLatestSequenceNumber(); }
 
 void rocks_db_disable_file_deletions(rocks_db_t* db, rocks_status_t** status) {
@@ -697,6 +942,17 @@ rocks_logfiles_t* rocks_db_get_sorted_wal_files(rocks_db_t* db, rocks_status_t**
   return files;
 }
 
+rocks_logfiles_t* rocks_db_get_current_wal_file(rocks_db_t* db, rocks_status_t** status) {
+  std::unique_ptr<LogFile> file;
+  auto st = db->rep->GetCurrentWalFile(&file);
+  if (SaveError(status, std::move(st))) {
+    return nullptr;
+  }
+  rocks_logfiles_t* files = new rocks_logfiles_t;
+  files->rep.push_back(std::move(file));
+  return files;
+}
+
 rocks_transaction_log_iterator_t* rocks_db_get_update_since(rocks_db_t* db, uint64_t seq_no, rocks_status_t** status) {
   auto iter = new rocks_transaction_log_iterator_t;
   auto st = db->rep->GetUpdatesSince(seq_no, &iter->rep);
@@ -707,6 +963,17 @@ rocks_transaction_log_iterator_t* rocks_db_get_update_since(rocks_db_t* db, uint
   return iter;
 }
 
+rocks_stats_history_iterator_t* rocks_db_get_stats_history(rocks_db_t* db, uint64_t start_time, uint64_t end_time,
+                                                           rocks_status_t** status) {
+  auto iter = new rocks_stats_history_iterator_t;
+  auto st = db->rep->GetStatsHistory(start_time, end_time, &iter->rep);
+  if (SaveError(status, std::move(st))) {
+    delete iter;
+    return nullptr;
+  }
+  return iter;
+}
+
 void rocks_db_delete_file(rocks_db_t* db, const char* name, size_t name_len, rocks_status_t** status) {
   SaveError(status, db->rep->DeleteFile(std::string(name, name_len)));
 }
@@ -717,6 +984,7 @@ const rocks_livefiles_t* rocks_db_get_livefiles_metadata(rocks_db_t* db) {
   return result;
 }
 
+
 const rocks_column_family_metadata_t* rocks_db_get_column_family_metadata(rocks_db_t* db,
                                                                           rocks_column_family_handle_t* column_family) {
   rocks_column_family_metadata_t* result = new rocks_column_family_metadata_t;
@@ -756,6 +1024,10 @@ void rocks_db_get_db_identity(rocks_db_t* db,
   }
 }
 
+void rocks_db_verify_checksum(rocks_db_t* db, const rocks_readoptions_t* options, rocks_status_t** status) {
+  SaveError(status, std::move(db->rep->VerifyChecksum(options->rep)));
+}
+
 rocks_table_props_collection_t* rocks_db_get_properties_of_all_tables(rocks_db_t* db, rocks_column_family_handle_t* cf,
                                                                       rocks_status_t** status) {
   auto coll = new rocks_table_props_collection_t;
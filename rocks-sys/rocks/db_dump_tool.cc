@@ -1,9 +1,255 @@
 #include "rocksdb/db_dump_tool.h"
 
+#include <cstdio>
+#include <exception>
+#include <map>
+#include <memory>
+
 #include "rocks/ctypes.hpp"
 
 using namespace ROCKSDB_NAMESPACE;
 
+namespace {
+// same layout as RocksDB's DbDumpTool
+const char kDumpMagic[8] = {'R', 'O', 'C', 'K', 'D', 'U', 'M', 'P'};
+const char kDumpVersion[8] = {0, 0, 0, 0, 0, 0, 0, 1};
+
+void PutFixed32LE(std::string* dst, uint32_t v) {
+  char buf[4];
+  for (int i = 0; i < 4; i++) {
+    buf[i] = static_cast<char>((v >> (8 * i)) & 0xff);
+  }
+  dst->append(buf, 4);
+}
+
+uint32_t DecodeFixed32LE(const char* p) {
+  uint32_t v = 0;
+  for (int i = 0; i < 4; i++) {
+    v |= static_cast<uint32_t>(static_cast<unsigned char>(p[i])) << (8 * i);
+  }
+  return v;
+}
+
+std::string JsonEscape(const std::string& s) {
+  std::string out;
+  for (unsigned char c : s) {
+    if (c == '"' || c == '\\') {
+      out.push_back('\\');
+      out.push_back(static_cast<char>(c));
+    } else if (c < 0x20) {
+      char buf[8];
+      snprintf(buf, sizeof(buf), "\\u%04x", c);
+      out.append(buf);
+    } else {
+      out.push_back(static_cast<char>(c));
+    }
+  }
+  return out;
+}
+
+// reads exactly `n` bytes, lengths are checked against the bytes left in the file before allocating
+Status ReadExact(SequentialFile* file, size_t n, std::string* out, uint64_t* remaining) {
+  if (n > *remaining) {
+    return Status::Corruption("unexpected end of dump file");
+  }
+  *remaining -= n;
+  out->resize(n);
+  if (n == 0) {
+    return Status::OK();
+  }
+  Slice slice;
+  Status st = file->Read(n, &slice, &(*out)[0]);
+  if (!st.ok()) {
+    return st;
+  }
+  if (slice.size() != n) {
+    return Status::Corruption("unexpected end of dump file");
+  }
+  if (slice.data() != out->data()) {
+    out->assign(slice.data(), slice.size());
+  }
+  return st;
+}
+
+ColumnFamilyOptions OptionsOf(const Options& options, const std::map<std::string, ColumnFamilyOptions>& cf_options,
+                              const std::string& name) {
+  auto it = cf_options.find(name);
+  return it != cf_options.end() ? it->second : ColumnFamilyOptions(options);
+}
+
+// opens all column families, creating `name` if missing
+Status OpenWithColumnFamily(const Options& options, const std::map<std::string, ColumnFamilyOptions>& cf_options,
+                            const std::string& path, const std::string& name, bool read_only, DB** db,
+                            std::vector<ColumnFamilyHandle*>* handles, ColumnFamilyHandle** target) {
+  std::vector<std::string> names;
+  if (!DB::ListColumnFamilies(options, path, &names).ok()) {
+    names = {kDefaultColumnFamilyName};
+  }
+  std::vector<ColumnFamilyDescriptor> descs;
+  for (const auto& cf_name : names) {
+    descs.push_back(ColumnFamilyDescriptor(cf_name, OptionsOf(options, cf_options, cf_name)));
+  }
+  Status st = read_only ? DB::OpenForReadOnly(options, path, descs, handles, db)
+                        : DB::Open(options, path, descs, handles, db);
+  if (!st.ok()) {
+    return st;
+  }
+  for (size_t i = 0; i < names.size(); i++) {
+    if (names[i] == name) {
+      *target = (*handles)[i];
+      return st;
+    }
+  }
+  if (read_only) {
+    return Status::InvalidArgument("column family not found: ", name);
+  }
+  st = (*db)->CreateColumnFamily(OptionsOf(options, cf_options, name), name, target);
+  if (st.ok()) {
+    handles->push_back(*target);
+  }
+  return st;
+}
+
+void CloseWithColumnFamilies(DB* db, const std::vector<ColumnFamilyHandle*>& handles) {
+  for (auto handle : handles) {
+    db->DestroyColumnFamilyHandle(handle);
+  }
+  delete db;
+}
+
+Status Dump(const rocks_dump_options_t& dump, Options options) {
+  const DumpOptions& dump_options = dump.rep;
+  const std::string& column_family = dump.column_family;
+  Env* env = options.env;
+  DB* db = nullptr;
+  std::vector<ColumnFamilyHandle*> handles;
+  ColumnFamilyHandle* cf = nullptr;
+  options.create_if_missing = false;
+  Status st =
+      OpenWithColumnFamily(options, dump.cf_options, dump_options.db_path, column_family, true, &db, &handles, &cf);
+  if (!st.ok()) {
+    if (db != nullptr) {
+      CloseWithColumnFamilies(db, handles);
+    }
+    return st;
+  }
+
+  std::unique_ptr<WritableFile> file;
+  st = env->NewWritableFile(dump_options.dump_location, &file, EnvOptions());
+  if (st.ok()) {
+    std::string header(kDumpMagic, 8);
+    header.append(kDumpVersion, 8);
+    std::string info = "{}";
+    if (!dump_options.anonymous) {
+      char hostname[1024] = {0};
+      int64_t timesec = 0;
+      std::string abspath;
+      env->GetHostName(hostname, sizeof(hostname));
+      env->GetCurrentTime(&timesec);
+      env->GetAbsolutePath(dump_options.db_path, &abspath);
+      info = "{ \"database-path\": \"" + JsonEscape(abspath) + "\", \"hostname\": \"" + JsonEscape(hostname) +
+             "\", \"creation-time\": " + std::to_string(timesec) + ", \"column-family\": \"" +
+             JsonEscape(column_family) + "\" }";
+    }
+    PutFixed32LE(&header, static_cast<uint32_t>(info.size()));
+    header.append(info);
+    st = file->Append(header);
+  }
+
+  if (st.ok()) {
+    std::unique_ptr<Iterator> it(db->NewIterator(ReadOptions(), cf));
+    std::string record;
+    for (it->SeekToFirst(); st.ok() && it->Valid(); it->Next()) {
+      record.clear();
+      PutFixed32LE(&record, static_cast<uint32_t>(it->key().size()));
+      record.append(it->key().data(), it->key().size());
+      PutFixed32LE(&record, static_cast<uint32_t>(it->value().size()));
+      record.append(it->value().data(), it->value().size());
+      st = file->Append(record);
+    }
+    if (st.ok()) {
+      st = it->status();
+    }
+  }
+  if (st.ok()) {
+    st = file->Close();
+  }
+  CloseWithColumnFamilies(db, handles);
+  return st;
+}
+
+Status Undump(const rocks_undump_options_t& undump, Options options) {
+  const UndumpOptions& undump_options = undump.rep;
+  Env* env = options.env;
+  uint64_t remaining = 0;
+  Status st = env->GetFileSize(undump_options.dump_location, &remaining);
+  if (!st.ok()) {
+    return st;
+  }
+  std::unique_ptr<SequentialFile> file;
+  st = env->NewSequentialFile(undump_options.dump_location, &file, EnvOptions());
+  if (!st.ok()) {
+    return st;
+  }
+
+  std::string buf;
+  st = ReadExact(file.get(), 16, &buf, &remaining);
+  if (!st.ok()) {
+    return st;
+  }
+  if (buf.compare(0, 8, kDumpMagic, 8) != 0) {
+    return Status::Corruption("not a ROCKDUMP file");
+  }
+  if (buf.compare(8, 8, kDumpVersion, 8) != 0) {
+    return Status::NotSupported("unsupported ROCKDUMP version");
+  }
+  st = ReadExact(file.get(), 4, &buf, &remaining);
+  if (st.ok()) {
+    st = ReadExact(file.get(), DecodeFixed32LE(buf.data()), &buf, &remaining);
+  }
+  if (!st.ok()) {
+    return st;
+  }
+
+  DB* db = nullptr;
+  std::vector<ColumnFamilyHandle*> handles;
+  ColumnFamilyHandle* cf = nullptr;
+  options.create_if_missing = true;
+  st = OpenWithColumnFamily(options, undump.cf_options, undump_options.db_path, undump.column_family, false, &db,
+                            &handles, &cf);
+  if (!st.ok()) {
+    if (db != nullptr) {
+      CloseWithColumnFamilies(db, handles);
+    }
+    return st;
+  }
+
+  std::string key;
+  std::string value;
+  while (st.ok() && remaining > 0) {
+    st = ReadExact(file.get(), 4, &buf, &remaining);
+    if (st.ok()) {
+      st = ReadExact(file.get(), DecodeFixed32LE(buf.data()), &key, &remaining);
+    }
+    if (st.ok()) {
+      st = ReadExact(file.get(), 4, &buf, &remaining);
+    }
+    if (st.ok()) {
+      st = ReadExact(file.get(), DecodeFixed32LE(buf.data()), &value, &remaining);
+    }
+    if (st.ok()) {
+      st = db->Put(WriteOptions(), cf, key, value);
+    }
+  }
+
+  if (st.ok() && undump_options.compact_db) {
+    st = db->CompactRange(CompactRangeOptions(), cf, nullptr, nullptr);
+  }
+  CloseWithColumnFamilies(db, handles);
+  return st;
+}
+}  // namespace
+
 extern "C" {
 rocks_dump_options_t* rocks_dump_options_create() { return new rocks_dump_options_t; }
 
@@ -19,11 +265,29 @@ void rocks_dump_options_set_dump_location(rocks_dump_options_t* opt, const char*
 
 void rocks_dump_options_set_anonymous(rocks_dump_options_t* opt, unsigned char v) { opt->rep.anonymous = v; }
 
+void rocks_dump_options_set_column_family(rocks_dump_options_t* opt, const char* name, const size_t name_len) {
+  opt->column_family.assign(name, name_len);
+}
+
 unsigned char rocks_db_dump_tool_run(rocks_dump_options_t* dump_options, rocks_options_t* options) {
   auto tool = DbDumpTool();
   return tool.Run(dump_options->rep, options->rep);
 }
 
+void rocks_dump_options_set_column_family_options(rocks_dump_options_t* opt, const char* name, const size_t name_len,
+                                                  const rocks_cfoptions_t* cfopts) {
+  opt->cf_options[std::string(name, name_len)] = cfopts->rep;
+}
+
+void rocks_db_dump(rocks_dump_options_t* dump_options, rocks_options_t* options, rocks_status_t** status) {
+  // exceptions, e.g. bad_alloc, must not unwind into Rust
+  try {
+    SaveError(status, Dump(*dump_options, options->rep));
+  } catch (const std::exception& e) {
+    SaveError(status, Status::Aborted(e.what()));
+  }
+}
+
 rocks_undump_options_t* rocks_undump_options_create() { return new rocks_undump_options_t; }
 
 void rocks_undump_options_destroy(rocks_undump_options_t* options) { delete options; }
@@ -38,8 +302,25 @@ void rocks_undump_options_set_dump_location(rocks_undump_options_t* opt, const c
 
 void rocks_undump_options_set_compact_db(rocks_undump_options_t* opt, unsigned char v) { opt->rep.compact_db = v; }
 
+void rocks_undump_options_set_column_family(rocks_undump_options_t* opt, const char* name, const size_t name_len) {
+  opt->column_family.assign(name, name_len);
+}
+
 unsigned char rocks_db_undump_tool_run(rocks_undump_options_t* undump_options, rocks_options_t* options) {
   auto tool = DbUndumpTool();
   return tool.Run(undump_options->rep, options->rep);
 }
+
+void rocks_undump_options_set_column_family_options(rocks_undump_options_t* opt, const char* name,
+                                                    const size_t name_len, const rocks_cfoptions_t* cfopts) {
+  opt->cf_options[std::string(name, name_len)] = cfopts->rep;
+}
+
+void rocks_db_undump(rocks_undump_options_t* undump_options, rocks_options_t* options, rocks_status_t** status) {
+  try {
+    SaveError(status, Undump(*undump_options, options->rep));
+  } catch (const std::exception& e) {
+    SaveError(status, Status::Aborted(e.what()));
+  }
+}
 }
@@ -1,11 +1,539 @@
 #include "rocksdb/env.h"
 
+#include <algorithm>
+#include <atomic>
+#include <limits>
+#include <mutex>
+#include <random>
+#include <set>
+
 #include "rocks/ctypes.hpp"
 
 using namespace ROCKSDB_NAMESPACE;
 
 using std::shared_ptr;
 
+// Env with configurable latency injected into file reads, writes and syncs.
+// Used for reproducing slow-disk behavior in tests.
+class LatencyInjectionEnv : public EnvWrapper {
+ public:
+  enum Op { kRead = 0, kWrite = 1, kSync = 2, kNumOps = 3 };
+
+  struct Latency {
+    std::atomic<double> probability{0.0};
+    std::atomic<uint64_t> min_micros{0};
+    std::atomic<uint64_t> max_micros{0};
+  };
+
+  explicit LatencyInjectionEnv(Env* base) : EnvWrapper(base) {}
+
+  void SetLatency(int op, double probability, uint64_t min_micros, uint64_t max_micros) {
+    latencies_[op].probability.store(probability);
+    latencies_[op].min_micros.store(min_micros);
+    latencies_[op].max_micros.store(std::max(min_micros, max_micros));
+  }
+
+  void Inject(int op) {
+    auto& lat = latencies_[op];
+    const double probability = lat.probability.load(std::memory_order_relaxed);
+    const uint64_t max_micros = lat.max_micros.load(std::memory_order_relaxed);
+    if (probability <= 0.0 || max_micros == 0) {
+      return;
+    }
+    thread_local std::mt19937_64 rng{std::random_device{}()};
+    if (probability < 1.0 && std::uniform_real_distribution<double>(0.0, 1.0)(rng) >= probability) {
+      return;
+    }
+    const uint64_t min_micros = std::min(lat.min_micros.load(std::memory_order_relaxed), max_micros);
+    uint64_t micros = std::uniform_int_distribution<uint64_t>(min_micros, max_micros)(rng);
+    // SleepForMicroseconds() takes an int, sleep longer latencies in chunks
+    while (micros > 0) {
+      const uint64_t chunk = std::min<uint64_t>(micros, std::numeric_limits<int>::max());
+      target()->SleepForMicroseconds(static_cast<int>(chunk));
+      micros -= chunk;
+    }
+  }
+
+  Status NewSequentialFile(const std::string& f, std::unique_ptr<SequentialFile>* r,
+                           const EnvOptions& options) override {
+    std::unique_ptr<SequentialFile> file;
+    Status s = target()->NewSequentialFile(f, &file, options);
+    if (s.ok()) {
+      r->reset(new SequentialFileImpl(std::move(file), this));
+    }
+    return s;
+  }
+
+  Status NewRandomAccessFile(const std::string& f, std::unique_ptr<RandomAccessFile>* r,
+                             const EnvOptions& options) override {
+    std::unique_ptr<RandomAccessFile> file;
+    Status s = target()->NewRandomAccessFile(f, &file, options);
+    if (s.ok()) {
+      r->reset(new RandomAccessFileImpl(std::move(file), this));
+    }
+    return s;
+  }
+
+  Status NewWritableFile(const std::string& f, std::unique_ptr<WritableFile>* r,
+                         const EnvOptions& options) override {
+    std::unique_ptr<WritableFile> file;
+    Status s = target()->NewWritableFile(f, &file, options);
+    if (s.ok()) {
+      r->reset(new WritableFileImpl(std::move(file), this));
+    }
+    return s;
+  }
+
+  Status ReopenWritableFile(const std::string& f, std::unique_ptr<WritableFile>* r,
+                            const EnvOptions& options) override {
+    std::unique_ptr<WritableFile> file;
+    Status s = target()->ReopenWritableFile(f, &file, options);
+    if (s.ok()) {
+      r->reset(new WritableFileImpl(std::move(file), this));
+    }
+    return s;
+  }
+
+  Status ReuseWritableFile(const std::string& f, const std::string& old_f, std::unique_ptr<WritableFile>* r,
+                           const EnvOptions& options) override {
+    std::unique_ptr<WritableFile> file;
+    Status s = target()->ReuseWritableFile(f, old_f, &file, options);
+    if (s.ok()) {
+      r->reset(new WritableFileImpl(std::move(file), this));
+    }
+    return s;
+  }
+
+ private:
+  class SequentialFileImpl : public SequentialFileWrapper {
+   public:
+    SequentialFileImpl(std::unique_ptr<SequentialFile>&& f, LatencyInjectionEnv* env)
+        : SequentialFileWrapper(f.get()), file_(std::move(f)), env_(env) {}
+    Status Read(size_t n, Slice* result, char* scratch) override {
+      env_->Inject(kRead);
+      return file_->Read(n, result, scratch);
+    }
+    Status PositionedRead(uint64_t offset, size_t n, Slice* result, char* scratch) override {
+      env_->Inject(kRead);
+      return file_->PositionedRead(offset, n, result, scratch);
+    }
+
+   private:
+    std::unique_ptr<SequentialFile> file_;
+    LatencyInjectionEnv* env_;
+  };
+
+  class RandomAccessFileImpl : public RandomAccessFileWrapper {
+   public:
+    RandomAccessFileImpl(std::unique_ptr<RandomAccessFile>&& f, LatencyInjectionEnv* env)
+        : RandomAccessFileWrapper(f.get()), file_(std::move(f)), env_(env) {}
+    Status Read(uint64_t offset, size_t n, Slice* result, char* scratch) const override {
+      env_->Inject(kRead);
+      return file_->Read(offset, n, result, scratch);
+    }
+    Status MultiRead(ReadRequest* reqs, size_t num_reqs) override {
+      env_->Inject(kRead);
+      return file_->MultiRead(reqs, num_reqs);
+    }
+
+   private:
+    std::unique_ptr<RandomAccessFile> file_;
+    LatencyInjectionEnv* env_;
+  };
+
+  class WritableFileImpl : public WritableFileWrapper {
+   public:
+    WritableFileImpl(std::unique_ptr<WritableFile>&& f, LatencyInjectionEnv* env)
+        : WritableFileWrapper(f.get()), file_(std::move(f)), env_(env) {}
+    Status Append(const Slice& data) override {
+      env_->Inject(kWrite);
+      return file_->Append(data);
+    }
+    Status PositionedAppend(const Slice& data, uint64_t offset) override {
+      env_->Inject(kWrite);
+      return file_->PositionedAppend(data, offset);
+    }
+    Status Sync() override {
+      env_->Inject(kSync);
+      return file_->Sync();
+    }
+    Status Fsync() override {
+      env_->Inject(kSync);
+      return file_->Fsync();
+    }
+    Status RangeSync(uint64_t offset, uint64_t nbytes) override {
+      env_->Inject(kSync);
+      return file_->RangeSync(offset, nbytes);
+    }
+
+   private:
+    std::unique_ptr<WritableFile> file_;
+    LatencyInjectionEnv* env_;
+  };
+
+  Latency latencies_[kNumOps];
+};
+
+// error code returned by rust callbacks, message in the std::string
+static Status RustIOStatus(int code, const std::string& msg) {
+  switch (code) {
+    case 0:
+      return Status::OK();
+    case 1:
+      return Status::NotFound(msg);
+    case 3:
+      return Status::NotSupported(msg);
+    default:
+      return Status::IOError(msg);
+  }
+}
+
+class RustSequentialFile : public SequentialFile {
+ public:
+  explicit RustSequentialFile(void* obj) : obj_(obj) {}
+  ~RustSequentialFile() override { rust_sequential_file_drop(obj_); }
+
+  Status Read(size_t n, Slice* result, char* scratch) override {
+    size_t read = 0;
+    std::string msg;
+    auto code = rust_sequential_file_read(obj_, scratch, n, &read, &msg);
+    *result = Slice(scratch, code == 0 ? read : 0);
+    return RustIOStatus(code, msg);
+  }
+
+  Status Skip(uint64_t n) override {
+    std::string msg;
+    return RustIOStatus(rust_sequential_file_skip(obj_, n, &msg), msg);
+  }
+
+ private:
+  void* obj_;  // rust Box<Box<dyn SequentialFile>>
+};
+
+class RustRandomAccessFile : public RandomAccessFile {
+ public:
+  explicit RustRandomAccessFile(void* obj) : obj_(obj) {}
+  ~RustRandomAccessFile() override { rust_random_access_file_drop(obj_); }
+
+  Status Read(uint64_t offset, size_t n, Slice* result, char* scratch) const override {
+    size_t read = 0;
+    std::string msg;
+    auto code = rust_random_access_file_read(obj_, offset, scratch, n, &read, &msg);
+    *result = Slice(scratch, code == 0 ? read : 0);
+    return RustIOStatus(code, msg);
+  }
+
+ private:
+  void* obj_;  // rust Box<Box<dyn RandomAccessFile>>
+};
+
+class RustWritableFile : public WritableFile {
+ public:
+  explicit RustWritableFile(void* obj) : obj_(obj) {}
+  ~RustWritableFile() override { rust_writable_file_drop(obj_); }
+
+  Status Append(const Slice& data) override {
+    std::string msg;
+    auto code = rust_writable_file_append(obj_, data.data(), data.size(), &msg);
+    if (code == 0) {
+      size_ += data.size();
+    }
+    return RustIOStatus(code, msg);
+  }
+
+  Status Close() override {
+    std::string msg;
+    return RustIOStatus(rust_writable_file_close(obj_, &msg), msg);
+  }
+
+  Status Flush() override {
+    std::string msg;
+    return RustIOStatus(rust_writable_file_flush(obj_, &msg), msg);
+  }
+
+  Status Sync() override {
+    std::string msg;
+    return RustIOStatus(rust_writable_file_sync(obj_, &msg), msg);
+  }
+
+  uint64_t GetFileSize() override { return size_; }
+
+ private:
+  void* obj_;  // rust Box<Box<dyn WritableFile>>
+  uint64_t size_ = 0;
+};
+
+class RustDirectory : public Directory {
+ public:
+  RustDirectory(void* fs, const std::string& name) : fs_(fs), name_(name) {}
+
+  Status Fsync() override {
+    std::string msg;
+    return RustIOStatus(rust_file_system_fsync_dir(fs_, name_.data(), name_.size(), &msg), msg);
+  }
+
+ private:
+  void* fs_;
+  std::string name_;
+};
+
+class RustBlockCipher : public BlockCipher {
+ public:
+  explicit RustBlockCipher(void* obj) : obj_(obj), block_size_(rust_block_cipher_block_size(obj)) {}
+  ~RustBlockCipher() override { rust_block_cipher_drop(obj_); }
+
+  size_t BlockSize() override { return block_size_; }
+
+  Status Encrypt(char* data) override {
+    rust_block_cipher_encrypt(obj_, data, block_size_);
+    return Status::OK();
+  }
+
+  Status Decrypt(char* data) override {
+    rust_block_cipher_decrypt(obj_, data, block_size_);
+    return Status::OK();
+  }
+
+ private:
+  void* obj_;  // rust Box<Box<dyn BlockCipher>>
+  size_t block_size_;
+};
+
+// Env with file system operations implemented in rust, everything else,
+// like threads and clocks, is delegated to Env::Default().
+class RustEnv : public EnvWrapper {
+ public:
+  explicit RustEnv(void* obj) : EnvWrapper(Env::Default()), obj_(obj) {}
+  ~RustEnv() override { rust_file_system_drop(obj_); }
+
+  Status NewSequentialFile(const std::string& f, std::unique_ptr<SequentialFile>* r, const EnvOptions&) override {
+    void* file = nullptr;
+    std::string msg;
+    auto code = rust_file_system_new_sequential_file(obj_, f.data(), f.size(), &file, &msg);
+    if (code == 0) {
+      r->reset(new RustSequentialFile(file));
+    }
+    return RustIOStatus(code, msg);
+  }
+
+  Status NewRandomAccessFile(const std::string& f, std::unique_ptr<RandomAccessFile>* r, const EnvOptions&) override {
+    void* file = nullptr;
+    std::string msg;
+    auto code = rust_file_system_new_random_access_file(obj_, f.data(), f.size(), &file, &msg);
+    if (code == 0) {
+      r->reset(new RustRandomAccessFile(file));
+    }
+    return RustIOStatus(code, msg);
+  }
+
+  Status NewWritableFile(const std::string& f, std::unique_ptr<WritableFile>* r, const EnvOptions&) override {
+    void* file = nullptr;
+    std::string msg;
+    auto code = rust_file_system_new_writable_file(obj_, f.data(), f.size(), &file, &msg);
+    if (code == 0) {
+      r->reset(new RustWritableFile(file));
+    }
+    return RustIOStatus(code, msg);
+  }
+
+  Status ReopenWritableFile(const std::string&, std::unique_ptr<WritableFile>*, const EnvOptions&) override {
+    return Status::NotSupported("ReopenWritableFile");
+  }
+
+  Status ReuseWritableFile(const std::string& f, const std::string& old_f, std::unique_ptr<WritableFile>* r,
+                           const EnvOptions& options) override {
+    auto s = RenameFile(old_f, f);
+    if (!s.ok()) {
+      return s;
+    }
+    return NewWritableFile(f, r, options);
+  }
+
+  Status NewRandomRWFile(const std::string&, std::unique_ptr<RandomRWFile>*, const EnvOptions&) override {
+    return Status::NotSupported("NewRandomRWFile");
+  }
+
+  Status NewDirectory(const std::string& name, std::unique_ptr<Directory>* result) override {
+    result->reset(new RustDirectory(obj_, name));
+    return Status::OK();
+  }
+
+  Status FileExists(const std::string& f) override {
+    std::string msg;
+    return RustIOStatus(rust_file_system_file_exists(obj_, f.data(), f.size(), &msg), msg);
+  }
+
+  Status GetChildren(const std::string& dir, std::vector<std::string>* result) override {
+    cxx_string_vector_t children;
+    std::string msg;
+    auto code = rust_file_system_get_children(obj_, dir.data(), dir.size(), &children, &msg);
+    if (code == 0) {
+      *result = std::move(children.rep);
+    }
+    return RustIOStatus(code, msg);
+  }
+
+  Status GetChildrenFileAttributes(const std::string& dir, std::vector<FileAttributes>* result) override {
+    // the generic implementation, on top of GetChildren() and GetFileSize()
+    return Env::GetChildrenFileAttributes(dir, result);
+  }
+
+  Status DeleteFile(const std::string& f) override {
+    std::string msg;
+    return RustIOStatus(rust_file_system_delete_file(obj_, f.data(), f.size(), &msg), msg);
+  }
+
+  Status CreateDir(const std::string& d) override {
+    std::string msg;
+    return RustIOStatus(rust_file_system_create_dir(obj_, d.data(), d.size(), 0, &msg), msg);
+  }
+
+  Status CreateDirIfMissing(const std::string& d) override {
+    std::string msg;
+    return RustIOStatus(rust_file_system_create_dir(obj_, d.data(), d.size(), 1, &msg), msg);
+  }
+
+  Status DeleteDir(const std::string& d) override {
+    std::string msg;
+    return RustIOStatus(rust_file_system_delete_dir(obj_, d.data(), d.size(), &msg), msg);
+  }
+
+  Status GetFileSize(const std::string& f, uint64_t* size) override {
+    std::string msg;
+    return RustIOStatus(rust_file_system_get_file_size(obj_, f.data(), f.size(), size, &msg), msg);
+  }
+
+  Status GetFileModificationTime(const std::string& f, uint64_t* file_mtime) override {
+    std::string msg;
+    return RustIOStatus(rust_file_system_get_file_modification_time(obj_, f.data(), f.size(), file_mtime, &msg),
+                        msg);
+  }
+
+  Status RenameFile(const std::string& src, const std::string& target) override {
+    std::string msg;
+    return RustIOStatus(
+        rust_file_system_rename_file(obj_, src.data(), src.size(), target.data(), target.size(), &msg), msg);
+  }
+
+  Status LinkFile(const std::string&, const std::string&) override { return Status::NotSupported("LinkFile"); }
+
+  Status NumFileLinks(const std::string&, uint64_t*) override { return Status::NotSupported("NumFileLinks"); }
+
+  Status AreFilesSame(const std::string&, const std::string&, bool*) override {
+    return Status::NotSupported("AreFilesSame");
+  }
+
+  // locks are process local, same as the in-memory Env
+  Status LockFile(const std::string& f, FileLock** lock) override {
+    std::lock_guard<std::mutex> guard(mutex_);
+    if (!locked_.insert(f).second) {
+      return Status::IOError(f, "lock is already held");
+    }
+    *lock = new RustFileLock(f);
+    return Status::OK();
+  }
+
+  Status UnlockFile(FileLock* lock) override {
+    auto rust_lock = static_cast<RustFileLock*>(lock);
+    {
+      std::lock_guard<std::mutex> guard(mutex_);
+      locked_.erase(rust_lock->name);
+    }
+    delete rust_lock;
+    return Status::OK();
+  }
+
+  // no LOG file, set DBOptions::info_log to log somewhere else
+  Status NewLogger(const std::string&, shared_ptr<Logger>*) override { return Status::NotSupported("NewLogger"); }
+
+ private:
+  struct RustFileLock : public FileLock {
+    explicit RustFileLock(const std::string& f) : name(f) {}
+    std::string name;
+  };
+
+  void* obj_;  // rust Box<Box<dyn FileSystem>>
+  std::mutex mutex_;
+  std::set<std::string> locked_;
+};
+
+class StderrLogger : public Logger {
+ public:
+  explicit StderrLogger(InfoLogLevel log_level) : Logger(log_level) {}
+
+  using Logger::Logv;
+  void Logv(const char* format, va_list ap) override {
+    vfprintf(stderr, format, ap);
+    fprintf(stderr, "\n");
+  }
+};
+
+class DevNullLogger : public Logger {
+ public:
+  DevNullLogger() : Logger(InfoLogLevel::HEADER_LEVEL) {}
+
+  using Logger::Logv;
+  void LogHeader(const char*, va_list) override {}
+  void Logv(const char*, va_list) override {}
+  void Logv(const InfoLogLevel, const char*, va_list) override {}
+};
+
+// formats log lines and hands them to a Rust InfoLogger
+class RustLogger : public Logger {
+ public:
+  RustLogger(void* obj, InfoLogLevel log_level) : Logger(log_level), obj_(obj) {}
+  ~RustLogger() override { rust_info_logger_drop(obj_); }
+
+  void LogHeader(const char* format, va_list ap) override { Logv(InfoLogLevel::HEADER_LEVEL, format, ap); }
+
+  void Logv(const char* format, va_list ap) override { Logv(InfoLogLevel::INFO_LEVEL, format, ap); }
+
+  void Logv(const InfoLogLevel log_level, const char* format, va_list ap) override {
+    if (log_level < GetInfoLogLevel()) {
+      return;
+    }
+    char buf[512];
+    va_list backup_ap;
+    va_copy(backup_ap, ap);
+    int n = vsnprintf(buf, sizeof(buf), format, backup_ap);
+    va_end(backup_ap);
+    if (n < 0) {
+      return;
+    }
+    if (static_cast<size_t>(n) < sizeof(buf)) {
+      rust_info_logger_logv(obj_, static_cast<int>(log_level), buf, n);
+    } else {
+      std::string msg(n + 1, '\0');
+      vsnprintf(&msg[0], msg.size(), format, ap);
+      rust_info_logger_logv(obj_, static_cast<int>(log_level), msg.data(), n);
+    }
+  }
+
+  Status Close() override {
+    rust_info_logger_flush(obj_);
+    return Status::OK();
+  }
+
+  void Flush() override { rust_info_logger_flush(obj_); }
+
+ private:
+  void* obj_;  // rust Box<Box<dyn InfoLogger>>
+};
+
+// the Rust side Priority shares values with Env::IOPriority, map them to thread pools
+static Env::Priority ThreadPoolPriority(int pri) {
+  switch (pri) {
+    case 0:
+      return Env::LOW;
+    case 1:
+      return Env::HIGH;
+    case 3:
+      return Env::BOTTOM;
+    default:
+      return Env::TOTAL;
+  }
+}
+
 extern "C" {
 rocks_env_t* rocks_create_default_env() {
   rocks_env_t* result = new rocks_env_t;
@@ -28,21 +556,76 @@ rocks_env_t* rocks_create_timed_env() {
   return result;
 }
 
+rocks_env_t* rocks_create_mem_env_with_base(rocks_env_t* base) {
+  rocks_env_t* result = new rocks_env_t;
+  result->rep = rocksdb::NewMemEnv(base->rep);
+  result->is_default = false;
+  return result;
+}
+
+rocks_env_t* rocks_create_timed_env_with_base(rocks_env_t* base) {
+  rocks_env_t* result = new rocks_env_t;
+  result->rep = rocksdb::NewTimedEnv(base->rep);
+  result->is_default = false;
+  return result;
+}
+
+rocks_env_t* rocks_create_rot13_encrypted_env(rocks_env_t* base, size_t block_size) {
+  rocks_env_t* result = new rocks_env_t;
+  result->cipher.reset(new ROT13BlockCipher(block_size));
+  result->encryption.reset(new CTREncryptionProvider(*result->cipher));
+  result->rep = rocksdb::NewEncryptedEnv(base->rep, result->encryption.get());
+  result->is_default = false;
+  return result;
+}
+
+rocks_env_t* rocks_create_encrypted_env(rocks_env_t* base, void* cipher) {
+  rocks_env_t* result = new rocks_env_t;
+  result->cipher.reset(new RustBlockCipher(cipher));
+  result->encryption.reset(new CTREncryptionProvider(*result->cipher));
+  result->rep = rocksdb::NewEncryptedEnv(base->rep, result->encryption.get());
+  result->is_default = false;
+  return result;
+}
+
+rocks_env_t* rocks_create_latency_injection_env() {
+  rocks_env_t* result = new rocks_env_t;
+  auto env = new LatencyInjectionEnv(Env::Default());
+  result->rep = env;
+  result->is_default = false;
+  result->latency = env;
+  return result;
+}
+
+rocks_env_t* rocks_create_rust_env(void* file_system) {
+  rocks_env_t* result = new rocks_env_t;
+  result->rep = new RustEnv(file_system);
+  result->is_default = false;
+  return result;
+}
+
+unsigned char rocks_env_set_injected_latency(rocks_env_t* env, int op, double probability, uint64_t min_micros,
+                                             uint64_t max_micros) {
+  if (env->latency == nullptr || op < 0 || op >= LatencyInjectionEnv::kNumOps) {
+    return 0;
+  }
+  env->latency->SetLatency(op, probability, min_micros, max_micros);
+  return 1;
+}
+
 void rocks_env_destroy(rocks_env_t* env) {
   if (!env->is_default) delete env->rep;
   delete env;
 }
 
-void rocks_env_set_background_threads(rocks_env_t* env, int n) { env->rep->SetBackgroundThreads(n); }
-
-void rocks_env_set_high_priority_background_threads(rocks_env_t* env, int n) {
-  env->rep->SetBackgroundThreads(n, Env::HIGH);
+void rocks_env_set_background_threads(rocks_env_t* env, int n, int pri) {
+  env->rep->SetBackgroundThreads(n, ThreadPoolPriority(pri));
 }
 
 void rocks_env_join_all_threads(rocks_env_t* env) { env->rep->WaitForJoin(); }
 
 unsigned int rocks_env_get_thread_pool_queue_len(rocks_env_t* env, int pri) {
-  return env->rep->GetThreadPoolQueueLen(static_cast<Env::Priority>(pri));
+  return env->rep->GetThreadPoolQueueLen(ThreadPoolPriority(pri));
 }
 
 rocks_logger_t* rocks_env_new_logger(rocks_env_t* env, const char* name_ptr, size_t name_len, rocks_status_t** status) {
@@ -56,6 +639,79 @@ rocks_logger_t* rocks_env_new_logger(rocks_env_t* env, const char* name_ptr, siz
   }
 }
 
+rocks_filelock_t* rocks_env_lock_file(rocks_env_t* env, const char* fname, size_t fname_len, rocks_status_t** status) {
+  FileLock* lock = nullptr;
+  if (SaveError(status, env->rep->LockFile(std::string(fname, fname_len), &lock))) {
+    return nullptr;
+  }
+  return new rocks_filelock_t{lock};
+}
+
+void rocks_env_unlock_file(rocks_env_t* env, rocks_filelock_t* lock, rocks_status_t** status) {
+  SaveError(status, env->rep->UnlockFile(lock->rep));
+  delete lock;
+}
+
+size_t rocks_env_get_direct_io_alignment(rocks_env_t* env, const char* fname, size_t fname_len,
+                                         rocks_status_t** status) {
+  auto name = std::string(fname, fname_len);
+  EnvOptions env_options;
+  env_options.use_direct_writes = true;
+  std::unique_ptr<WritableFile> file;
+  if (SaveError(status, env->rep->NewWritableFile(name, &file, env_options))) {
+    return 0;
+  }
+  auto alignment = file->GetRequiredBufferAlignment();
+  file->Close();
+  file.reset();
+  SaveError(status, env->rep->DeleteFile(name));
+  return alignment;
+}
+
+char** rocks_env_get_children(rocks_env_t* env, const char* dir, size_t dir_len, size_t* len,
+                              rocks_status_t** status) {
+  std::vector<std::string> children;
+  if (SaveError(status, env->rep->GetChildren(std::string(dir, dir_len), &children))) {
+    *len = 0;
+    return nullptr;
+  }
+  *len = children.size();
+  char** ret = static_cast<char**>(malloc(sizeof(char*) * children.size()));
+  for (size_t i = 0; i < children.size(); i++) {
+    ret[i] = strdup(children[i].c_str());
+  }
+  return ret;
+}
+
+void rocks_env_get_children_destroy(char** list, size_t len) {
+  if (list == nullptr) return;
+  for (size_t i = 0; i < len; ++i) {
+    free(list[i]);
+  }
+  free(list);
+}
+
+void rocks_env_file_exists(rocks_env_t* env, const char* fname, size_t fname_len, rocks_status_t** status) {
+  SaveError(status, env->rep->FileExists(std::string(fname, fname_len)));
+}
+
+uint64_t rocks_env_get_file_size(rocks_env_t* env, const char* fname, size_t fname_len, rocks_status_t** status) {
+  uint64_t size = 0;
+  SaveError(status, env->rep->GetFileSize(std::string(fname, fname_len), &size));
+  return size;
+}
+
+uint64_t rocks_env_get_file_modification_time(rocks_env_t* env, const char* fname, size_t fname_len,
+                                              rocks_status_t** status) {
+  uint64_t mtime = 0;
+  SaveError(status, env->rep->GetFileModificationTime(std::string(fname, fname_len), &mtime));
+  return mtime;
+}
+
+void rocks_env_delete_file(rocks_env_t* env, const char* fname, size_t fname_len, rocks_status_t** status) {
+  SaveError(status, env->rep->DeleteFile(std::string(fname, fname_len)));
+}
+
 uint64_t rocks_env_now_micros(rocks_env_t* env) { return env->rep->NowMicros(); }
 
 uint64_t rocks_env_now_nanos(rocks_env_t* env) { return env->rep->NowNanos(); }
@@ -84,16 +740,15 @@ cxx_string_t* rocks_env_time_to_string(rocks_env_t* env, uint64_t time) {
 }
 
 int rocks_env_get_background_threads(rocks_env_t* env, int pri) {
-  auto priority = static_cast<Env::Priority>(pri);
-  return env->rep->GetBackgroundThreads(priority);
+  return env->rep->GetBackgroundThreads(ThreadPoolPriority(pri));
 }
 
 void rocks_env_inc_background_threads_if_needed(rocks_env_t* env, int number, int pri) {
-  env->rep->IncBackgroundThreadsIfNeeded(number, static_cast<Env::Priority>(pri));
+  env->rep->IncBackgroundThreadsIfNeeded(number, ThreadPoolPriority(pri));
 }
 
 void rocks_env_lower_thread_pool_io_priority(rocks_env_t* env, int pool) {
-  env->rep->LowerThreadPoolIOPriority(static_cast<Env::Priority>(pool));
+  env->rep->LowerThreadPoolIOPriority(ThreadPoolPriority(pool));
 }
 
 rocks_thread_status_t** rocks_env_get_thread_list(rocks_env_t* env, size_t* len) {
@@ -163,6 +818,16 @@ void rocks_envoptions_set_rate_limiter(rocks_envoptions_t* opt, ....) {
 }
 
 extern "C" {
+rocks_logger_t* rocks_logger_create_rust(void* logger, int log_level) {
+  return new rocks_logger_t{std::make_shared<RustLogger>(logger, static_cast<InfoLogLevel>(log_level))};
+}
+
+rocks_logger_t* rocks_logger_create_stderr(int log_level) {
+  return new rocks_logger_t{std::make_shared<StderrLogger>(static_cast<InfoLogLevel>(log_level))};
+}
+
+rocks_logger_t* rocks_logger_create_devnull() { return new rocks_logger_t{std::make_shared<DevNullLogger>()}; }
+
 void rocks_logger_destroy(rocks_logger_t* logger) { delete logger; }
 
 void rocks_logger_log(rocks_logger_t* logger, int log_level, const char* msg_ptr, size_t msg_len) {
@@ -6,6 +6,28 @@ using namespace ROCKSDB_NAMESPACE;
 
 using std::unique_ptr;
 
+namespace {
+class RustFilterPolicy : public FilterPolicy {
+ public:
+  explicit RustFilterPolicy(void* trait_obj) : obj(trait_obj) {}
+
+  ~RustFilterPolicy() override { rust_filter_policy_drop(this->obj); }
+
+  const char* Name() const override { return rust_filter_policy_name(this->obj); }
+
+  void CreateFilter(const Slice* keys, int n, std::string* dst) const override {
+    rust_filter_policy_create_filter(this->obj, keys, n, dst);
+  }
+
+  bool KeyMayMatch(const Slice& key, const Slice& filter) const override {
+    return rust_filter_policy_key_may_match(this->obj, &key, &filter) != 0;
+  }
+
+ private:
+  void* obj;  // rust Box<trait obj>
+};
+}  // namespace
+
 extern "C" {
 
 rocks_raw_filterpolicy_t* rocks_raw_filterpolicy_new_bloomfilter(int bits_per_key,
@@ -15,5 +37,11 @@ rocks_raw_filterpolicy_t* rocks_raw_filterpolicy_new_bloomfilter(int bits_per_ke
   return policy;
 }
 
+rocks_raw_filterpolicy_t* rocks_raw_filterpolicy_new_rust(void* trait_obj) {
+  rocks_raw_filterpolicy_t* policy = new rocks_raw_filterpolicy_t;
+  policy->rep.reset(new RustFilterPolicy(trait_obj));
+  return policy;
+}
+
 void rocks_raw_filterpolicy_destroy(rocks_raw_filterpolicy_t* cache) { delete cache; }
 }
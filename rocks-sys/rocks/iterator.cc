@@ -54,6 +54,10 @@ void rocks_iter_get_property(const rocks_iterator_t* iter, const char* prop, siz
   }
 }
 
+void rocks_iter_refresh(rocks_iterator_t* iter, rocks_status_t** status) {
+  SaveError(status, iter->rep->Refresh());
+}
+
 rocks_iterator_t* rocks_new_empty_iterator() {
   auto it = NewEmptyIterator();
   return new rocks_iterator_t{it};
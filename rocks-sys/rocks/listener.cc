@@ -1,3 +1,5 @@
+#include <chrono>
+
 #include "rocks/ctypes.hpp"
 
 using namespace ROCKSDB_NAMESPACE;
@@ -203,4 +205,45 @@ rocks_table_props_t* rocks_external_file_ingestion_info_get_table_properties(
   return new rocks_table_props_t{
       std::shared_ptr<TableProperties>(const_cast<TableProperties*>(&info->table_properties), [](TableProperties*) {})};
 }
+
+// WriteStallInfo
+typedef WriteStallInfo rocks_write_stall_info_t;
+
+const char* rocks_write_stall_info_get_cf_name(const rocks_write_stall_info_t* info, size_t* len) {
+  *len = info->cf_name.size();
+  return info->cf_name.data();
+}
+
+int rocks_write_stall_info_get_cur(const rocks_write_stall_info_t* info) {
+  return static_cast<int>(info->condition.cur);
+}
+
+int rocks_write_stall_info_get_prev(const rocks_write_stall_info_t* info) {
+  return static_cast<int>(info->condition.prev);
+}
+
+// FileOperationInfo
+typedef FileOperationInfo rocks_file_operation_info_t;
+
+const char* rocks_file_operation_info_get_path(const rocks_file_operation_info_t* info, size_t* len) {
+  *len = info->path.size();
+  return info->path.data();
+}
+
+uint64_t rocks_file_operation_info_get_offset(const rocks_file_operation_info_t* info) { return info->offset; }
+
+size_t rocks_file_operation_info_get_length(const rocks_file_operation_info_t* info) { return info->length; }
+
+// nanoseconds since unix epoch
+int64_t rocks_file_operation_info_get_start_timestamp(const rocks_file_operation_info_t* info) {
+  return info->start_timestamp.time_since_epoch().count();
+}
+
+int64_t rocks_file_operation_info_get_finish_timestamp(const rocks_file_operation_info_t* info) {
+  return info->finish_timestamp.time_since_epoch().count();
+}
+
+void rocks_file_operation_info_get_status(const rocks_file_operation_info_t* info, rocks_status_t** status) {
+  SaveError(status, Status(info->status));
+}
 }
@@ -0,0 +1,38 @@
+#include "rocksdb/utilities/memory_util.h"
+
+#include <map>
+#include <unordered_set>
+#include <vector>
+
+#include "rocks/ctypes.hpp"
+
+using namespace ROCKSDB_NAMESPACE;
+
+#ifdef __cplusplus
+extern "C" {
+#endif
+
+void rocks_get_approximate_memory_usage_by_type(rocks_db_t* const* dbs, size_t num_dbs,
+                                                rocks_cache_t* const* caches, size_t num_caches,
+                                                uint64_t* usage_by_type, rocks_status_t** status) {
+  std::vector<DB*> db_list;
+  for (size_t i = 0; i < num_dbs; i++) {
+    db_list.push_back(dbs[i]->rep);
+  }
+  std::unordered_set<const Cache*> cache_set;
+  for (size_t i = 0; i < num_caches; i++) {
+    cache_set.insert(caches[i]->rep.get());
+  }
+  std::map<MemoryUtil::UsageType, uint64_t> usage;
+  if (SaveError(status, MemoryUtil::GetApproximateMemoryUsageByType(db_list, cache_set, &usage))) {
+    return;
+  }
+  for (int i = 0; i < MemoryUtil::kNumUsageTypes; i++) {
+    auto it = usage.find(static_cast<MemoryUtil::UsageType>(i));
+    usage_by_type[i] = it != usage.end() ? it->second : 0;
+  }
+}
+
+#ifdef __cplusplus
+}
+#endif
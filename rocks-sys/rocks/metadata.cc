@@ -39,7 +39,39 @@ unsigned char rocks_livefiles_being_compacted(const rocks_livefiles_t* lf, int i
   return lf->rep[index].being_compacted;
 }
 
+uint64_t rocks_livefiles_num_reads_sampled(const rocks_livefiles_t* lf, int index) {
+  return lf->rep[index].num_reads_sampled;
+}
+
+uint64_t rocks_livefiles_file_number(const rocks_livefiles_t* lf, int index) { return lf->rep[index].file_number; }
+
+uint64_t rocks_livefiles_num_entries(const rocks_livefiles_t* lf, int index) { return lf->rep[index].num_entries; }
+
+uint64_t rocks_livefiles_num_deletions(const rocks_livefiles_t* lf, int index) { return lf->rep[index].num_deletions; }
+
+uint64_t rocks_livefiles_oldest_blob_file_number(const rocks_livefiles_t* lf, int index) {
+  return lf->rep[index].oldest_blob_file_number;
+}
+
 extern void rocks_livefiles_destroy(const rocks_livefiles_t* lf) { delete lf; }
+
+rocks_livefiles_t* rocks_livefiles_create() { return new rocks_livefiles_t; }
+
+void rocks_livefiles_push(rocks_livefiles_t* lf, const char* name, size_t name_len, const char* db_path,
+                          size_t db_path_len, int level, uint64_t smallest_seqno, uint64_t largest_seqno,
+                          const char* smallestkey, size_t smallestkey_len, const char* largestkey,
+                          size_t largestkey_len, size_t size) {
+  LiveFileMetaData file;
+  file.name = std::string(name, name_len);
+  file.db_path = std::string(db_path, db_path_len);
+  file.level = level;
+  file.smallest_seqno = smallest_seqno;
+  file.largest_seqno = largest_seqno;
+  file.smallestkey = std::string(smallestkey, smallestkey_len);
+  file.largestkey = std::string(largestkey, largestkey_len);
+  file.size = size;
+  lf->rep.push_back(std::move(file));
+}
 }
 
 extern "C" {
@@ -110,5 +142,41 @@ unsigned char rocks_column_family_metadata_levels_files_being_compacted(const ro
   return meta->rep.levels[level].files[file_index].being_compacted;
 }
 
+uint64_t rocks_column_family_metadata_levels_files_num_reads_sampled(const rocks_column_family_metadata_t* meta,
+                                                                     int level, int file_index) {
+  return meta->rep.levels[level].files[file_index].num_reads_sampled;
+}
+
+uint64_t rocks_column_family_metadata_levels_files_file_number(const rocks_column_family_metadata_t* meta, int level,
+                                                               int file_index) {
+  return meta->rep.levels[level].files[file_index].file_number;
+}
+
+uint64_t rocks_column_family_metadata_levels_files_num_entries(const rocks_column_family_metadata_t* meta, int level,
+                                                               int file_index) {
+  return meta->rep.levels[level].files[file_index].num_entries;
+}
+
+uint64_t rocks_column_family_metadata_levels_files_num_deletions(const rocks_column_family_metadata_t* meta, int level,
+                                                                 int file_index) {
+  return meta->rep.levels[level].files[file_index].num_deletions;
+}
+
+uint64_t rocks_column_family_metadata_levels_files_oldest_blob_file_number(
+    const rocks_column_family_metadata_t* meta, int level, int file_index) {
+  return meta->rep.levels[level].files[file_index].oldest_blob_file_number;
+}
+
+uint64_t rocks_column_family_metadata_levels_files_oldest_ancester_time(
+    const rocks_column_family_metadata_t* meta, int level, int file_index) {
+  return meta->rep.levels[level].files[file_index].oldest_ancester_time;
+}
+
+uint64_t rocks_column_family_metadata_levels_files_file_creation_time(
+    const rocks_column_family_metadata_t* meta, int level, int file_index) {
+  return meta->rep.levels[level].files[file_index].file_creation_time;
+}
+
 extern void rocks_column_family_metadata_destroy(const rocks_column_family_metadata_t* meta) { delete meta; }
+
 }
@@ -1,6 +1,8 @@
 #include "rocksdb/options.h"
 
+#include <algorithm>
 #include <iostream>
+#include <string>
 
 #include "rocks/ctypes.hpp"
 #include "rocksdb/table.h"
@@ -9,9 +11,33 @@ using namespace ROCKSDB_NAMESPACE;
 
 using std::shared_ptr;
 
+namespace {
+Status ValidateBlockBasedTableOptions(const BlockBasedTableOptions& opts) {
+  if (!opts.no_block_cache) {
+    return Status::OK();
+  }
+  if (opts.block_cache != nullptr) {
+    return Status::InvalidArgument("no_block_cache is set, but block_cache is also specified");
+  }
+  if (opts.cache_index_and_filter_blocks) {
+    return Status::InvalidArgument("no_block_cache is set, but cache_index_and_filter_blocks requires a block cache");
+  }
+  if (opts.pin_l0_filter_and_index_blocks_in_cache) {
+    return Status::InvalidArgument(
+        "no_block_cache is set, but pin_l0_filter_and_index_blocks_in_cache requires a block cache");
+  }
+  return Status::OK();
+}
+}  // namespace
+
 // rocks_options_t
 extern "C" {
 
+void rocks_block_based_table_options_validate(const rocks_block_based_table_options_t* options,
+                                              rocks_status_t** status) {
+  SaveError(status, ValidateBlockBasedTableOptions(options->rep));
+}
+
 rocks_options_t* rocks_options_create() { return new rocks_options_t; }
 
 void rocks_options_destroy(rocks_options_t* options) { delete options; }
@@ -59,9 +85,9 @@ void rocks_cfoptions_optimize_universal_style_compaction(rocks_cfoptions_t* opt,
   opt->rep.OptimizeUniversalStyleCompaction(memtable_memory_budget);
 }
 
-void rocks_cfoptions_set_comparator_by_trait(rocks_cfoptions_t* opt, void* cp_trait_obj) {
-  // FIXME: mem leak
-  opt->rep.comparator = new rocks_comparator_t{cp_trait_obj};
+void rocks_cfoptions_set_comparator(rocks_cfoptions_t* opt, const rocks_comparator_t* comparator) {
+  // Options->comparator is a raw pointer, the comparator is owned by the Rust side
+  opt->rep.comparator = comparator;
 }
 
 void rocks_cfoptions_set_bitwise_comparator(rocks_cfoptions_t* opt, unsigned char reversed) {
@@ -188,6 +214,10 @@ void rocks_cfoptions_set_max_write_buffer_number_to_maintain(rocks_cfoptions_t*
   opt->rep.max_write_buffer_number_to_maintain = n;
 }
 
+void rocks_cfoptions_set_max_write_buffer_size_to_maintain(rocks_cfoptions_t* opt, int64_t n) {
+  opt->rep.max_write_buffer_size_to_maintain = n;
+}
+
 void rocks_cfoptions_set_inplace_update_support(rocks_cfoptions_t* opt, unsigned char v) {
   opt->rep.inplace_update_support = v;
 }
@@ -202,6 +232,10 @@ void rocks_cfoptions_set_memtable_prefix_bloom_size_ratio(rocks_cfoptions_t* opt
   opt->rep.memtable_prefix_bloom_size_ratio = v;
 }
 
+void rocks_cfoptions_set_memtable_whole_key_filtering(rocks_cfoptions_t* opt, unsigned char v) {
+  opt->rep.memtable_whole_key_filtering = v;
+}
+
 void rocks_cfoptions_set_memtable_huge_page_size(rocks_cfoptions_t* opt, size_t v) {
   opt->rep.memtable_huge_page_size = v;
 }
@@ -295,6 +329,17 @@ void rocks_cfoptions_set_fifo_compaction_options(rocks_cfoptions_t* opt, rocks_f
   opt->rep.compaction_options_fifo = fifo->rep;
 }
 
+void rocks_cfoptions_set_ttl(rocks_cfoptions_t* opt, uint64_t v) { opt->rep.ttl = v; }
+
+void rocks_cfoptions_set_cf_paths(rocks_cfoptions_t* opt, const char* const* paths, const size_t* path_lens,
+                                  const uint64_t* target_sizes, int size) {
+  std::vector<DbPath> cfpaths;
+  for (int i = 0; i < size; i++) {
+    cfpaths.push_back(DbPath(std::string(paths[i], path_lens[i]), target_sizes[i]));
+  }
+  opt->rep.cf_paths = cfpaths;
+}
+
 void rocks_cfoptions_set_max_sequential_skip_in_iterations(rocks_cfoptions_t* opt, uint64_t v) {
   opt->rep.max_sequential_skip_in_iterations = v;
 }
@@ -426,6 +471,18 @@ void rocks_dboptions_set_delete_obsolete_files_period_micros(rocks_dboptions_t*
 }
 void rocks_dboptions_set_max_background_jobs(rocks_dboptions_t* opt, int n) { opt->rep.max_background_jobs = n; }
 
+void rocks_dboptions_set_base_background_compactions(rocks_dboptions_t* opt, int n) {
+  opt->rep.base_background_compactions = n;
+}
+
+void rocks_dboptions_set_max_background_compactions(rocks_dboptions_t* opt, int n) {
+  opt->rep.max_background_compactions = n;
+}
+
+void rocks_dboptions_set_max_background_flushes(rocks_dboptions_t* opt, int n) {
+  opt->rep.max_background_flushes = n;
+}
+
 void rocks_dboptions_set_max_subcompactions(rocks_dboptions_t* opt, uint32_t n) { opt->rep.max_subcompactions = n; }
 
 void rocks_dboptions_set_max_log_file_size(rocks_dboptions_t* opt, size_t v) { opt->rep.max_log_file_size = v; }
@@ -474,6 +531,14 @@ void rocks_dboptions_set_stats_dump_period_sec(rocks_dboptions_t* opt, unsigned
   opt->rep.stats_dump_period_sec = v;
 }
 
+void rocks_dboptions_set_stats_persist_period_sec(rocks_dboptions_t* opt, unsigned int v) {
+  opt->rep.stats_persist_period_sec = v;
+}
+
+void rocks_dboptions_set_stats_history_buffer_size(rocks_dboptions_t* opt, size_t v) {
+  opt->rep.stats_history_buffer_size = v;
+}
+
 void rocks_dboptions_set_advise_random_on_open(rocks_dboptions_t* opt, unsigned char v) {
   opt->rep.advise_random_on_open = v;
 }
@@ -529,6 +594,32 @@ void rocks_dboptions_add_listener(rocks_dboptions_t* opt, void* listener_trait_o
   opt->rep.listeners.push_back(std::shared_ptr<EventListener>(new rocks_event_listener_t{listener_trait_obj}));
 }
 
+void rocks_dboptions_add_shared_listener(rocks_dboptions_t* opt, void* listener_trait_obj, const void* key) {
+  opt->rep.listeners.push_back(std::shared_ptr<EventListener>(new rocks_event_listener_t{listener_trait_obj, key}));
+}
+
+void rocks_dboptions_remove_listener(rocks_dboptions_t* opt, const void* key) {
+  auto& listeners = opt->rep.listeners;
+  // found under the registry lock, erased after it, since erasing may run the destructor
+  std::unordered_set<const EventListener*> matched;
+  {
+    std::lock_guard<std::mutex> lock(rocks_event_listeners_mu());
+    const auto& known = rocks_event_listeners();
+    for (const auto& l : listeners) {
+      if (known.count(l.get()) != 0 && static_cast<const rocks_event_listener_t*>(l.get())->key == key) {
+        matched.insert(l.get());
+      }
+    }
+  }
+  listeners.erase(std::remove_if(listeners.begin(), listeners.end(),
+                                 [&matched](const std::shared_ptr<EventListener>& l) {
+                                   return matched.count(l.get()) != 0;
+                                 }),
+                  listeners.end());
+}
+
+void rocks_dboptions_clear_listeners(rocks_dboptions_t* opt) { opt->rep.listeners.clear(); }
+
 void rocks_dboptions_set_enable_thread_tracking(rocks_dboptions_t* opt, unsigned char v) {
   opt->rep.enable_thread_tracking = v;
 }
@@ -590,12 +681,44 @@ void rocks_dboptions_set_allow_ingest_behind(rocks_dboptions_t* opt, unsigned ch
 
 void rocks_dboptions_set_manual_wal_flush(rocks_dboptions_t* opt, unsigned char v) { opt->rep.manual_wal_flush = v; }
 
+void rocks_dboptions_set_atomic_flush(rocks_dboptions_t* opt, unsigned char v) { opt->rep.atomic_flush = v; }
+
+void rocks_dboptions_set_avoid_unnecessary_blocking_io(rocks_dboptions_t* opt, unsigned char v) {
+  opt->rep.avoid_unnecessary_blocking_io = v;
+}
+
+
 // opt
 
 void rocks_options_prepare_for_bulk_load(rocks_options_t* opt) { opt->rep.PrepareForBulkLoad(); }
 
 void rocks_options_optimize_for_small_db(rocks_options_t* opt) { opt->rep.OptimizeForSmallDb(); }
 
+void rocks_options_deterministic(rocks_options_t* opt) {
+  auto& rep = opt->rep;
+  // compactions only happen on explicit CompactRange/CompactFiles calls
+  rep.disable_auto_compactions = true;
+  rep.max_background_jobs = 2;
+  rep.max_background_compactions = 1;
+  rep.max_background_flushes = 1;
+  rep.max_subcompactions = 1;
+  rep.base_background_compactions = 1;
+  rep.max_file_opening_threads = 1;
+  rep.level_compaction_dynamic_level_bytes = false;
+  rep.compaction_pri = kByCompensatedSize;
+  rep.compaction_options_universal.allow_trivial_move = false;
+  // no time based compactions
+  rep.ttl = 0;
+  rep.periodic_compaction_seconds = 0;
+  rep.stats_dump_period_sec = 0;
+  rep.stats_persist_period_sec = 0;
+  // write path
+  rep.enable_pipelined_write = false;
+  rep.unordered_write = false;
+  rep.two_write_queues = false;
+  rep.avoid_flush_during_shutdown = false;
+}
+
 /*
   char *rocks_options_statistics_get_string(rocks_options_t *opt) {
   rocksdb::Statistics *statistics = opt->rep.statistics.get();
@@ -615,6 +738,15 @@ rocks_readoptions_t* rocks_readoptions_new(unsigned char cksum, unsigned char ca
   return new rocks_readoptions_t{ReadOptions(cksum, cache)};
 }
 
+rocks_readoptions_t* rocks_readoptions_create_from(const rocks_readoptions_t* opt) {
+  auto ret = new rocks_readoptions_t{opt->rep};
+  // bound to the lifetime of the source
+  ret->rep.snapshot = nullptr;
+  ret->rep.iterate_lower_bound = nullptr;
+  ret->rep.iterate_upper_bound = nullptr;
+  return ret;
+}
+
 void rocks_readoptions_destroy(rocks_readoptions_t* opt) { delete opt; }
 
 void rocks_readoptions_set_verify_checksums(rocks_readoptions_t* opt, unsigned char v) {
@@ -732,6 +864,14 @@ void rocks_compactrange_options_set_target_path_id(rocks_compactrange_options_t*
 void rocks_compactrange_options_set_bottommost_level_compaction(rocks_compactrange_options_t* opt, int v) {
   opt->rep.bottommost_level_compaction = static_cast<BottommostLevelCompaction>(v);
 }
+
+void rocks_compactrange_options_set_allow_write_stall(rocks_compactrange_options_t* opt, unsigned char v) {
+  opt->rep.allow_write_stall = v;
+}
+
+void rocks_compactrange_options_set_max_subcompactions(rocks_compactrange_options_t* opt, uint32_t v) {
+  opt->rep.max_subcompactions = v;
+}
 }
 
 extern "C" {
@@ -16,4 +16,30 @@ void rocks_perf_context_to_string(const rocks_perf_context_t* ctx, unsigned char
   auto str = reinterpret_cast<const PerfContext*>(ctx)->ToString(exclude_zero_counters);
   rust_string_assign(s, str.data(), str.size());
 }
+
+void rocks_perf_context_enable_per_level(rocks_perf_context_t* ctx) {
+  reinterpret_cast<PerfContext*>(ctx)->EnablePerLevelPerfContext();
+}
+
+void rocks_perf_context_disable_per_level(rocks_perf_context_t* ctx) {
+  reinterpret_cast<PerfContext*>(ctx)->DisablePerLevelPerfContext();
+}
+
+void rocks_perf_context_clear_per_level(rocks_perf_context_t* ctx) {
+  reinterpret_cast<PerfContext*>(ctx)->ClearPerLevelPerfContext();
+}
+
+size_t rocks_perf_context_by_level(const rocks_perf_context_t* ctx, uint32_t* levels,
+                                   const rocks_perf_context_by_level_t** stats, size_t len) {
+  auto by_level = reinterpret_cast<const PerfContext*>(ctx)->level_to_perf_context;
+  if (by_level == nullptr) {
+    return 0;
+  }
+  size_t i = 0;
+  for (auto it = by_level->begin(); it != by_level->end() && i < len; it++, i++) {
+    levels[i] = it->first;
+    stats[i] = reinterpret_cast<const rocks_perf_context_by_level_t*>(&it->second);
+  }
+  return by_level->size();
+}
 }
@@ -1,6 +1,8 @@
 
 #include "rocksdb/rate_limiter.h"
 
+#include <algorithm>
+
 #include "rocks/ctypes.hpp"
 
 using namespace ROCKSDB_NAMESPACE;
@@ -16,5 +18,54 @@ rocks_ratelimiter_t* rocks_ratelimiter_create(int64_t rate_bytes_per_sec, int64_
   return rate_limiter;
 }
 
+rocks_ratelimiter_t* rocks_ratelimiter_create_with_mode(int64_t rate_bytes_per_sec, int64_t refill_period_us,
+                                                        int32_t fairness, int mode, unsigned char auto_tuned) {
+  rocks_ratelimiter_t* rate_limiter = new rocks_ratelimiter_t;
+  rate_limiter->rep.reset(NewGenericRateLimiter(rate_bytes_per_sec, refill_period_us, fairness,
+                                                static_cast<RateLimiter::Mode>(mode), auto_tuned));
+  return rate_limiter;
+}
+
 void rocks_ratelimiter_destroy(rocks_ratelimiter_t* limiter) { delete limiter; }
+
+void rocks_ratelimiter_set_bytes_per_second(rocks_ratelimiter_t* limiter, int64_t bytes_per_second) {
+  limiter->rep->SetBytesPerSecond(bytes_per_second);
+}
+
+int64_t rocks_ratelimiter_get_bytes_per_second(rocks_ratelimiter_t* limiter) {
+  return limiter->rep->GetBytesPerSecond();
+}
+
+int64_t rocks_ratelimiter_get_single_burst_bytes(rocks_ratelimiter_t* limiter) {
+  return limiter->rep->GetSingleBurstBytes();
+}
+
+int64_t rocks_ratelimiter_get_total_bytes_through(rocks_ratelimiter_t* limiter, int pri) {
+  // counters only exist for IO_LOW, IO_HIGH and IO_TOTAL
+  if (pri < Env::IO_LOW || pri > Env::IO_TOTAL) {
+    return 0;
+  }
+  return limiter->rep->GetTotalBytesThrough(static_cast<Env::IOPriority>(pri));
+}
+
+int64_t rocks_ratelimiter_get_total_requests(rocks_ratelimiter_t* limiter, int pri) {
+  if (pri < Env::IO_LOW || pri > Env::IO_TOTAL) {
+    return 0;
+  }
+  return limiter->rep->GetTotalRequests(static_cast<Env::IOPriority>(pri));
+}
+
+rocks_ratelimiter_t* rocks_ratelimiter_clone(rocks_ratelimiter_t* limiter) {
+  return new rocks_ratelimiter_t{limiter->rep};
+}
+
+void rocks_ratelimiter_request(rocks_ratelimiter_t* limiter, int64_t bytes, int pri) {
+  // a single request must not exceed the burst size
+  auto burst = limiter->rep->GetSingleBurstBytes();
+  while (bytes > 0) {
+    auto n = std::min(bytes, burst);
+    limiter->rep->Request(n, static_cast<Env::IOPriority>(pri), nullptr);
+    bytes -= n;
+  }
+}
 }
@@ -43,6 +43,8 @@ extern const char* rust_slice_transform_name(void* t);
 
 extern char rust_slice_transform_in_domain(void* t, const Slice* key);
 
+extern char rust_slice_transform_in_range(void* t, const Slice* dst);
+
 extern void rust_slice_transform_drop(void* t);
 
 /* merge operator*/
@@ -59,6 +61,11 @@ extern const char* rust_merge_operator_name(void* op);
 
 extern int32_t rust_merge_operator_call_full_merge_v2(void* op, const void* merge_in, void* merge_out);
 
+extern int32_t rust_merge_operator_call_partial_merge_multi(void* op, const Slice* key, const Slice* operands,
+                                                            size_t num_operands, std::string* new_value);
+
+extern char rust_merge_operator_allow_single_operand(void* op);
+
 extern void rust_merge_operator_drop(void* op);
 
 /* comparator */
@@ -76,6 +83,16 @@ extern void rust_comparator_find_short_successor(void* cp, std::string* key);
 
 extern void rust_comparator_drop(void* cp);
 
+/* filter_policy */
+
+extern const char* rust_filter_policy_name(void* fp);
+
+extern void rust_filter_policy_create_filter(void* fp, const Slice* keys, int n, std::string* dst);
+
+extern char rust_filter_policy_key_may_match(void* fp, const Slice* key, const Slice* filter);
+
+extern void rust_filter_policy_drop(void* fp);
+
 /* table_properties */
 
 extern void rust_table_props_collector_add_user_key(void* c, const Slice* key, const Slice* value, int type,
@@ -87,6 +104,8 @@ extern const char* rust_table_props_collector_name(void* c);
 
 extern unsigned char rust_table_props_collector_need_compact(void* c);
 
+extern void rust_table_props_collector_readable_properties(void* c, UserCollectedProperties* props);
+
 extern void rust_table_props_collector_drop(void* c);
 
 // *mut TablePropertiesCollector
@@ -122,6 +141,79 @@ extern unsigned char rust_write_batch_handler_will_continue(void* h);
 
 extern void rust_write_batch_handler_drop(void* h);
 
+/* env */
+struct cxx_string_vector_t;
+
+// all return 0 on success, 1 for NotFound, 3 for NotSupported, or IOError, with message in msg
+extern int rust_file_system_new_sequential_file(void* fs, const char* fname, size_t fname_len, void** file,
+                                                std::string* msg);
+
+extern int rust_file_system_new_random_access_file(void* fs, const char* fname, size_t fname_len, void** file,
+                                                   std::string* msg);
+
+extern int rust_file_system_new_writable_file(void* fs, const char* fname, size_t fname_len, void** file,
+                                              std::string* msg);
+
+extern int rust_file_system_file_exists(void* fs, const char* fname, size_t fname_len, std::string* msg);
+
+extern int rust_file_system_get_children(void* fs, const char* dir, size_t dir_len, cxx_string_vector_t* result,
+                                         std::string* msg);
+
+extern int rust_file_system_delete_file(void* fs, const char* fname, size_t fname_len, std::string* msg);
+
+extern int rust_file_system_create_dir(void* fs, const char* dir, size_t dir_len, unsigned char if_missing,
+                                       std::string* msg);
+
+extern int rust_file_system_delete_dir(void* fs, const char* dir, size_t dir_len, std::string* msg);
+
+extern int rust_file_system_get_file_size(void* fs, const char* fname, size_t fname_len, uint64_t* size,
+                                          std::string* msg);
+
+extern int rust_file_system_get_file_modification_time(void* fs, const char* fname, size_t fname_len,
+                                                       uint64_t* mtime, std::string* msg);
+
+extern int rust_file_system_rename_file(void* fs, const char* src, size_t src_len, const char* target,
+                                        size_t target_len, std::string* msg);
+
+extern int rust_file_system_fsync_dir(void* fs, const char* dir, size_t dir_len, std::string* msg);
+
+extern void rust_file_system_drop(void* fs);
+
+extern int rust_sequential_file_read(void* f, char* buf, size_t n, size_t* read, std::string* msg);
+
+extern int rust_sequential_file_skip(void* f, uint64_t n, std::string* msg);
+
+extern void rust_sequential_file_drop(void* f);
+
+extern int rust_random_access_file_read(void* f, uint64_t offset, char* buf, size_t n, size_t* read,
+                                        std::string* msg);
+
+extern void rust_random_access_file_drop(void* f);
+
+extern int rust_writable_file_append(void* f, const char* data, size_t len, std::string* msg);
+
+extern int rust_writable_file_close(void* f, std::string* msg);
+
+extern int rust_writable_file_flush(void* f, std::string* msg);
+
+extern int rust_writable_file_sync(void* f, std::string* msg);
+
+extern void rust_writable_file_drop(void* f);
+
+extern size_t rust_block_cipher_block_size(void* c);
+
+extern void rust_block_cipher_encrypt(void* c, char* data, size_t len);
+
+extern void rust_block_cipher_decrypt(void* c, char* data, size_t len);
+
+extern void rust_block_cipher_drop(void* c);
+
+extern void rust_info_logger_logv(void* l, int log_level, const char* msg, size_t len);
+
+extern void rust_info_logger_flush(void* l);
+
+extern void rust_info_logger_drop(void* l);
+
 // listener
 
 extern void rust_event_listener_drop(void* l);
@@ -149,7 +241,18 @@ extern void rust_event_listener_on_external_file_ingested(void* l, DB**, const E
 
 struct rocks_status_t;
 
-extern unsigned char rust_event_listener_on_background_error(void* l, BackgroundErrorReason, rocks_status_t*);
+// returns nullptr to suppress the error, or the new background error
+extern rocks_status_t* rust_event_listener_on_background_error(void* l, BackgroundErrorReason, rocks_status_t*);
+
+extern void rust_event_listener_on_stall_conditions_changed(void* l, const WriteStallInfo*);
+
+extern void rust_event_listener_on_file_read_finish(void* l, const FileOperationInfo*);
+
+extern void rust_event_listener_on_file_write_finish(void* l, const FileOperationInfo*);
+
+extern unsigned char rust_event_listener_should_be_notified_on_file_io(void* l);
+
+extern void rust_event_listener_on_error_recovery_completed(void* l, rocks_status_t*);
 
 extern void rust_compaction_event_listener_drop(void* l);
 
@@ -12,4 +12,8 @@ void rocks_pinnable_slice_destroy(rocks_pinnable_slice_t* s) { delete s; }
 const char* rocks_pinnable_slice_data(rocks_pinnable_slice_t* s) { return s->rep.data(); }
 
 size_t rocks_pinnable_slice_size(rocks_pinnable_slice_t* s) { return s->rep.size(); }
+
+unsigned char rocks_pinnable_slice_is_pinned(rocks_pinnable_slice_t* s) { return s->rep.IsPinned(); }
+
+void rocks_pinnable_slice_reset(rocks_pinnable_slice_t* s) { s->rep.Reset(); }
 }
@@ -88,4 +88,31 @@ unsigned char rocks_statistics_hist_enabled_for_type(rocks_statistics_t* stat, u
 void rocks_statistics_reset(rocks_statistics_t* stat, rocks_status_t** status) {
   SaveError(status, stat->rep->Reset());
 }
+
+unsigned char rocks_statistics_get_stats_level(rocks_statistics_t* stat) { return stat->rep->get_stats_level(); }
+
+void rocks_statistics_set_stats_level(rocks_statistics_t* stat, unsigned char level) {
+  stat->rep->set_stats_level(static_cast<StatsLevel>(level));
+}
+
+size_t rocks_statistics_num_tickers() { return TickersNameMap.size(); }
+
+void rocks_statistics_get_all_ticker_counts(rocks_statistics_t* stat, const char** names, uint64_t* counts) {
+  for (size_t i = 0; i < TickersNameMap.size(); i++) {
+    names[i] = TickersNameMap[i].second.c_str();
+    counts[i] = stat->rep->getTickerCount(TickersNameMap[i].first);
+  }
+}
+
+size_t rocks_statistics_num_histograms() { return HistogramsNameMap.size(); }
+
+void rocks_statistics_get_all_histogram_data(rocks_statistics_t* stat, const char** names,
+                                             rocks_histogram_data_t* data) {
+  // rocks_histogram_data_t is incomplete, index the HistogramData array it points to
+  auto histograms = reinterpret_cast<HistogramData*>(data);
+  for (size_t i = 0; i < HistogramsNameMap.size(); i++) {
+    names[i] = HistogramsNameMap[i].second.c_str();
+    stat->rep->histogramData(HistogramsNameMap[i].first, &histograms[i]);
+  }
+}
 }
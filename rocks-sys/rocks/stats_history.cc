@@ -0,0 +1,34 @@
+#include "rocksdb/stats_history.h"
+
+#include "rocks/ctypes.hpp"
+
+using namespace ROCKSDB_NAMESPACE;
+
+extern "C" {
+void rocks_stats_history_iterator_destroy(rocks_stats_history_iterator_t* it) { delete it; }
+
+unsigned char rocks_stats_history_iterator_valid(rocks_stats_history_iterator_t* it) { return it->rep->Valid(); }
+
+void rocks_stats_history_iterator_next(rocks_stats_history_iterator_t* it) { it->rep->Next(); }
+
+void rocks_stats_history_iterator_status(rocks_stats_history_iterator_t* it, rocks_status_t** status) {
+  SaveError(status, it->rep->status());
+}
+
+uint64_t rocks_stats_history_iterator_get_stats_time(rocks_stats_history_iterator_t* it) {
+  return it->rep->GetStatsTime();
+}
+
+size_t rocks_stats_history_iterator_stats_map_size(rocks_stats_history_iterator_t* it) {
+  return it->rep->GetStatsMap().size();
+}
+
+void rocks_stats_history_iterator_get_stats_map(rocks_stats_history_iterator_t* it, cxx_string_vector_t* names,
+                                                uint64_t* values) {
+  size_t i = 0;
+  for (const auto& stat : it->rep->GetStatsMap()) {
+    names->rep.push_back(stat.first);
+    values[i++] = stat.second;
+  }
+}
+}
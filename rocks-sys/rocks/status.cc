@@ -9,6 +9,8 @@ extern "C" {
 
 void rocks_status_destroy(rocks_status_t* s) { delete s; }
 
+rocks_status_t* rocks_status_copy(rocks_status_t* s) { return new rocks_status_t{Status(s->rep)}; }
+
 int rocks_status_code(rocks_status_t* s) { return s->rep.code(); }
 
 int rocks_status_subcode(rocks_status_t* s) { return s->rep.subcode(); }
@@ -16,4 +18,22 @@ int rocks_status_subcode(rocks_status_t* s) { return s->rep.subcode(); }
 int rocks_status_severity(rocks_status_t* s) { return s->rep.severity(); }
 
 const char* rocks_status_get_state(rocks_status_t* s) { return s->rep.getState(); }
+
+rocks_status_t* rocks_status_create_busy(const char* msg) { return new rocks_status_t{Status::Busy(msg)}; }
+
+rocks_status_t* rocks_status_create_invalid_argument(const char* msg) {
+  return new rocks_status_t{Status::InvalidArgument(msg)};
+}
+
+rocks_status_t* rocks_status_create_timed_out(const char* msg) { return new rocks_status_t{Status::TimedOut(msg)}; }
+
+rocks_status_t* rocks_status_create_aborted(const char* msg) { return new rocks_status_t{Status::Aborted(msg)}; }
+
+rocks_status_t* rocks_status_create_not_supported(const char* msg) {
+  return new rocks_status_t{Status::NotSupported(msg)};
+}
+
+rocks_status_t* rocks_status_create_corruption(const char* msg) {
+  return new rocks_status_t{Status::Corruption(msg)};
+}
 }
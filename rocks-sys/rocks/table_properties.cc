@@ -61,6 +61,22 @@ uint64_t rocks_table_props_get_num_entries(rocks_table_props_t* prop) { return p
 uint64_t rocks_table_props_get_format_version(rocks_table_props_t* prop) { return prop->rep->format_version; }
 uint64_t rocks_table_props_get_fixed_key_len(rocks_table_props_t* prop) { return prop->rep->fixed_key_len; }
 uint32_t rocks_table_props_get_column_family_id(rocks_table_props_t* prop) { return prop->rep->column_family_id; }
+uint64_t rocks_table_props_get_index_partitions(rocks_table_props_t* prop) { return prop->rep->index_partitions; }
+uint64_t rocks_table_props_get_top_level_index_size(rocks_table_props_t* prop) {
+  return prop->rep->top_level_index_size;
+}
+uint64_t rocks_table_props_get_index_key_is_user_key(rocks_table_props_t* prop) {
+  return prop->rep->index_key_is_user_key;
+}
+uint64_t rocks_table_props_get_index_value_is_delta_encoded(rocks_table_props_t* prop) {
+  return prop->rep->index_value_is_delta_encoded;
+}
+uint64_t rocks_table_props_get_num_deletions(rocks_table_props_t* prop) { return prop->rep->num_deletions; }
+uint64_t rocks_table_props_get_num_merge_operands(rocks_table_props_t* prop) { return prop->rep->num_merge_operands; }
+uint64_t rocks_table_props_get_num_range_deletions(rocks_table_props_t* prop) { return prop->rep->num_range_deletions; }
+uint64_t rocks_table_props_get_creation_time(rocks_table_props_t* prop) { return prop->rep->creation_time; }
+uint64_t rocks_table_props_get_oldest_key_time(rocks_table_props_t* prop) { return prop->rep->oldest_key_time; }
+uint64_t rocks_table_props_get_file_creation_time(rocks_table_props_t* prop) { return prop->rep->file_creation_time; }
 const char* rocks_table_props_get_column_family_name(rocks_table_props_t* prop, size_t* len) {
   *len = prop->rep->column_family_name.size();
   return prop->rep->column_family_name.data();
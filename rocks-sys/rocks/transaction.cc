@@ -0,0 +1,272 @@
+#include "rocksdb/utilities/transaction.h"
+
+#include "rocks/ctypes.hpp"
+#include "rocksdb/utilities/optimistic_transaction_db.h"
+#include "rocksdb/utilities/transaction_db.h"
+
+using namespace ROCKSDB_NAMESPACE;
+
+using std::shared_ptr;
+
+namespace {
+ColumnFamilyHandle* column_family_or_default(rocks_transaction_t* txn, rocks_column_family_handle_t* column_family) {
+  return column_family != nullptr ? column_family->rep : txn->db->DefaultColumnFamily();
+}
+}  // namespace
+
+extern "C" {
+/* transactiondb options */
+rocks_transactiondb_options_t* rocks_transactiondb_options_create() { return new rocks_transactiondb_options_t; }
+
+void rocks_transactiondb_options_destroy(rocks_transactiondb_options_t* opt) { delete opt; }
+
+void rocks_transactiondb_options_set_max_num_locks(rocks_transactiondb_options_t* opt, int64_t v) {
+  opt->rep.max_num_locks = v;
+}
+
+void rocks_transactiondb_options_set_num_stripes(rocks_transactiondb_options_t* opt, size_t v) {
+  opt->rep.num_stripes = v;
+}
+
+void rocks_transactiondb_options_set_transaction_lock_timeout(rocks_transactiondb_options_t* opt, int64_t v) {
+  opt->rep.transaction_lock_timeout = v;
+}
+
+void rocks_transactiondb_options_set_default_lock_timeout(rocks_transactiondb_options_t* opt, int64_t v) {
+  opt->rep.default_lock_timeout = v;
+}
+
+/* transaction options */
+rocks_transaction_options_t* rocks_transaction_options_create() { return new rocks_transaction_options_t; }
+
+void rocks_transaction_options_destroy(rocks_transaction_options_t* opt) { delete opt; }
+
+void rocks_transaction_options_set_set_snapshot(rocks_transaction_options_t* opt, unsigned char v) {
+  opt->rep.set_snapshot = v;
+}
+
+void rocks_transaction_options_set_deadlock_detect(rocks_transaction_options_t* opt, unsigned char v) {
+  opt->rep.deadlock_detect = v;
+}
+
+void rocks_transaction_options_set_lock_timeout(rocks_transaction_options_t* opt, int64_t v) {
+  opt->rep.lock_timeout = v;
+}
+
+void rocks_transaction_options_set_expiration(rocks_transaction_options_t* opt, int64_t v) {
+  opt->rep.expiration = v;
+}
+
+/* optimistic transaction options */
+rocks_optimistic_transaction_options_t* rocks_optimistic_transaction_options_create() {
+  return new rocks_optimistic_transaction_options_t;
+}
+
+void rocks_optimistic_transaction_options_destroy(rocks_optimistic_transaction_options_t* opt) { delete opt; }
+
+void rocks_optimistic_transaction_options_set_set_snapshot(rocks_optimistic_transaction_options_t* opt,
+                                                           unsigned char v) {
+  opt->rep.set_snapshot = v;
+}
+
+/* transactiondb */
+rocks_db_t* rocks_transactiondb_open(const rocks_options_t* options, const rocks_transactiondb_options_t* txn_db_options,
+                                     const char* name, rocks_status_t** status) {
+  TransactionDB* db = nullptr;
+  if (SaveError(status, TransactionDB::Open(options->rep, txn_db_options->rep, std::string(name), &db))) {
+    return nullptr;
+  }
+  rocks_db_t* result = new rocks_db_t;
+  result->rep = db;
+  return result;
+}
+
+rocks_db_t* rocks_transactiondb_open_column_families(const rocks_dboptions_t* db_options,
+                                                     const rocks_transactiondb_options_t* txn_db_options,
+                                                     const char* name, int num_column_families,
+                                                     const char* const* column_family_names,
+                                                     const rocks_cfoptions_t* const* column_family_options,
+                                                     rocks_column_family_handle_t** column_family_handles,
+                                                     rocks_status_t** status) {
+  std::vector<ColumnFamilyDescriptor> column_families;
+  for (int i = 0; i < num_column_families; i++) {
+    column_families.push_back(ColumnFamilyDescriptor(std::string(column_family_names[i]),
+                                                     ColumnFamilyOptions(column_family_options[i]->rep)));
+  }
+
+  TransactionDB* db = nullptr;
+  std::vector<ColumnFamilyHandle*> handles;
+  if (SaveError(status, TransactionDB::Open(db_options->rep, txn_db_options->rep, std::string(name), column_families,
+                                            &handles, &db))) {
+    return nullptr;
+  }
+
+  for (size_t i = 0; i < handles.size(); i++) {
+    rocks_column_family_handle_t* c_handle = new rocks_column_family_handle_t;
+    c_handle->rep = handles[i];
+    column_family_handles[i] = c_handle;
+  }
+  rocks_db_t* result = new rocks_db_t;
+  result->rep = db;
+  return result;
+}
+
+// db must be opened by rocks_transactiondb_open or rocks_transactiondb_open_column_families
+rocks_transaction_t* rocks_transactiondb_begin_transaction(rocks_db_t* db, const rocks_writeoptions_t* write_options,
+                                                           const rocks_transaction_options_t* txn_options) {
+  rocks_transaction_t* result = new rocks_transaction_t;
+  result->rep = static_cast<TransactionDB*>(db->rep)->BeginTransaction(write_options->rep, txn_options->rep);
+  result->db = db->rep;
+  return result;
+}
+
+// the array must be freed by rocks_transaction_array_destroy, each transaction by rocks_transaction_destroy
+rocks_transaction_t** rocks_transactiondb_get_all_prepared_transactions(rocks_db_t* db, size_t* count) {
+  std::vector<Transaction*> txns;
+  static_cast<TransactionDB*>(db->rep)->GetAllPreparedTransactions(&txns);
+  *count = txns.size();
+  rocks_transaction_t** result = new rocks_transaction_t*[txns.size()];
+  for (size_t i = 0; i < txns.size(); i++) {
+    result[i] = new rocks_transaction_t;
+    result[i]->rep = txns[i];
+    result[i]->db = db->rep;
+  }
+  return result;
+}
+
+void rocks_transaction_array_destroy(rocks_transaction_t** txns) { delete[] txns; }
+
+/* optimistic transactiondb */
+rocks_db_t* rocks_optimistic_transactiondb_open(const rocks_options_t* options, const char* name,
+                                                rocks_status_t** status) {
+  OptimisticTransactionDB* db = nullptr;
+  if (SaveError(status, OptimisticTransactionDB::Open(options->rep, std::string(name), &db))) {
+    return nullptr;
+  }
+  rocks_db_t* result = new rocks_db_t;
+  result->rep = db;
+  return result;
+}
+
+rocks_db_t* rocks_optimistic_transactiondb_open_column_families(const rocks_dboptions_t* db_options,
+                                                                const char* name, int num_column_families,
+                                                                const char* const* column_family_names,
+                                                                const rocks_cfoptions_t* const* column_family_options,
+                                                                rocks_column_family_handle_t** column_family_handles,
+                                                                rocks_status_t** status) {
+  std::vector<ColumnFamilyDescriptor> column_families;
+  for (int i = 0; i < num_column_families; i++) {
+    column_families.push_back(ColumnFamilyDescriptor(std::string(column_family_names[i]),
+                                                     ColumnFamilyOptions(column_family_options[i]->rep)));
+  }
+
+  OptimisticTransactionDB* db = nullptr;
+  std::vector<ColumnFamilyHandle*> handles;
+  if (SaveError(status,
+                OptimisticTransactionDB::Open(db_options->rep, std::string(name), column_families, &handles, &db))) {
+    return nullptr;
+  }
+
+  for (size_t i = 0; i < handles.size(); i++) {
+    rocks_column_family_handle_t* c_handle = new rocks_column_family_handle_t;
+    c_handle->rep = handles[i];
+    column_family_handles[i] = c_handle;
+  }
+  rocks_db_t* result = new rocks_db_t;
+  result->rep = db;
+  return result;
+}
+
+// db must be opened by rocks_optimistic_transactiondb_open or rocks_optimistic_transactiondb_open_column_families
+rocks_transaction_t* rocks_optimistic_transactiondb_begin_transaction(
+    rocks_db_t* db, const rocks_writeoptions_t* write_options,
+    const rocks_optimistic_transaction_options_t* txn_options) {
+  rocks_transaction_t* result = new rocks_transaction_t;
+  result->rep =
+      static_cast<OptimisticTransactionDB*>(db->rep)->BeginTransaction(write_options->rep, txn_options->rep);
+  result->db = db->rep;
+  return result;
+}
+
+/* transaction */
+void rocks_transaction_destroy(rocks_transaction_t* txn) {
+  delete txn->rep;
+  delete txn;
+}
+
+void rocks_transaction_set_name(rocks_transaction_t* txn, const char* name, size_t name_len, rocks_status_t** status) {
+  SaveError(status, txn->rep->SetName(std::string(name, name_len)));
+}
+
+void rocks_transaction_get_name(rocks_transaction_t* txn, void* name /* *mut String */) {
+  auto txn_name = txn->rep->GetName();
+  rust_string_assign(name, txn_name.data(), txn_name.size());
+}
+
+uint64_t rocks_transaction_get_id(rocks_transaction_t* txn) { return txn->rep->GetID(); }
+
+void rocks_transaction_prepare(rocks_transaction_t* txn, rocks_status_t** status) {
+  SaveError(status, txn->rep->Prepare());
+}
+
+void rocks_transaction_commit(rocks_transaction_t* txn, rocks_status_t** status) {
+  SaveError(status, txn->rep->Commit());
+}
+
+void rocks_transaction_rollback(rocks_transaction_t* txn, rocks_status_t** status) {
+  SaveError(status, txn->rep->Rollback());
+}
+
+void rocks_transaction_set_snapshot(rocks_transaction_t* txn) { txn->rep->SetSnapshot(); }
+
+const rocks_snapshot_t* rocks_transaction_get_snapshot(rocks_transaction_t* txn) {
+  txn->snapshot.rep = txn->rep->GetSnapshot();
+  return txn->snapshot.rep != nullptr ? &txn->snapshot : nullptr;
+}
+
+void rocks_transaction_clear_snapshot(rocks_transaction_t* txn) { txn->rep->ClearSnapshot(); }
+
+void rocks_transaction_set_savepoint(rocks_transaction_t* txn) { txn->rep->SetSavePoint(); }
+
+void rocks_transaction_rollback_to_savepoint(rocks_transaction_t* txn, rocks_status_t** status) {
+  SaveError(status, txn->rep->RollbackToSavePoint());
+}
+
+void rocks_transaction_put(rocks_transaction_t* txn, rocks_column_family_handle_t* column_family, const char* key,
+                           size_t keylen, const char* val, size_t vallen, rocks_status_t** status) {
+  SaveError(status,
+            txn->rep->Put(column_family_or_default(txn, column_family), Slice(key, keylen), Slice(val, vallen)));
+}
+
+void rocks_transaction_delete(rocks_transaction_t* txn, rocks_column_family_handle_t* column_family, const char* key,
+                              size_t keylen, rocks_status_t** status) {
+  SaveError(status, txn->rep->Delete(column_family_or_default(txn, column_family), Slice(key, keylen)));
+}
+
+void rocks_transaction_get_pinnable(rocks_transaction_t* txn, const rocks_readoptions_t* options,
+                                    rocks_column_family_handle_t* column_family, const char* key, size_t keylen,
+                                    rocks_pinnable_slice_t* value, rocks_status_t** status) {
+  SaveError(status, txn->rep->Get(options->rep, column_family_or_default(txn, column_family), Slice(key, keylen),
+                                  &value->rep));
+}
+
+void rocks_transaction_get_for_update_pinnable(rocks_transaction_t* txn, const rocks_readoptions_t* options,
+                                               rocks_column_family_handle_t* column_family, const char* key,
+                                               size_t keylen, rocks_pinnable_slice_t* value, unsigned char exclusive,
+                                               rocks_status_t** status) {
+  SaveError(status, txn->rep->GetForUpdate(options->rep, column_family_or_default(txn, column_family),
+                                           Slice(key, keylen), &value->rep, exclusive));
+}
+
+void rocks_transaction_undo_get_for_update(rocks_transaction_t* txn, rocks_column_family_handle_t* column_family,
+                                           const char* key, size_t keylen) {
+  txn->rep->UndoGetForUpdate(column_family_or_default(txn, column_family), Slice(key, keylen));
+}
+
+rocks_iterator_t* rocks_transaction_create_iterator(rocks_transaction_t* txn, const rocks_readoptions_t* options,
+                                                    rocks_column_family_handle_t* column_family) {
+  rocks_iterator_t* result = new rocks_iterator_t;
+  result->rep = txn->rep->GetIterator(options->rep, column_family_or_default(txn, column_family));
+  return result;
+}
+}
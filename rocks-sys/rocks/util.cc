@@ -29,6 +29,11 @@ void cxx_string_assign(cxx_string_t* s, const char* p, size_t len) {
   str->assign(p, len);
 }
 
+void cxx_string_append(cxx_string_t* s, const char* p, size_t len) {
+  auto str = reinterpret_cast<std::string*>(s);
+  str->append(p, len);
+}
+
 const char* cxx_string_data(const cxx_string_t* s) {
   auto str = reinterpret_cast<const std::string*>(s);
   return str->data();
@@ -50,4 +55,6 @@ size_t cxx_string_vector_size(cxx_string_vector_t* v) { return v->rep.size(); }
 const char* cxx_string_vector_nth(cxx_string_vector_t* v, size_t index) { return v->rep[index].data(); }
 
 size_t cxx_string_vector_nth_size(cxx_string_vector_t* v, size_t index) { return v->rep[index].size(); }
+
+void cxx_string_vector_push(cxx_string_vector_t* v, const char* p, size_t len) { v->rep.emplace_back(p, len); }
 }
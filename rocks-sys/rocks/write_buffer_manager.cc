@@ -11,6 +11,12 @@ rocks_write_buffer_manager_t* rocks_write_buffer_manager_create(size_t buffer_si
   return manager;
 }
 
+rocks_write_buffer_manager_t* rocks_write_buffer_manager_create_with_cache(size_t buffer_size, rocks_cache_t* cache) {
+  auto manager = new rocks_write_buffer_manager_t;
+  manager->rep.reset(new WriteBufferManager(buffer_size, cache->rep));
+  return manager;
+}
+
 void rocks_write_buffer_manager_destroy(rocks_write_buffer_manager_t* manager) { delete manager; }
 
 unsigned char rocks_write_buffer_manager_enabled(rocks_write_buffer_manager_t* manager) {
@@ -21,7 +27,19 @@ size_t rocks_write_buffer_manager_memory_usage(rocks_write_buffer_manager_t* man
   return manager->rep->memory_usage();
 }
 
+unsigned char rocks_write_buffer_manager_cost_to_cache(rocks_write_buffer_manager_t* manager) {
+  return manager->rep->cost_to_cache();
+}
+
+size_t rocks_write_buffer_manager_mutable_memtable_memory_usage(rocks_write_buffer_manager_t* manager) {
+  return manager->rep->mutable_memtable_memory_usage();
+}
+
 size_t rocks_write_buffer_manager_buffer_size(rocks_write_buffer_manager_t* manager) {
   return manager->rep->buffer_size();
 }
+
+unsigned char rocks_write_buffer_manager_should_flush(rocks_write_buffer_manager_t* manager) {
+  return manager->rep->ShouldFlush();
+}
 }
@@ -102,6 +102,11 @@ pub struct rocks_env_t {
 }
 #[repr(C)]
 #[derive(Copy, Clone)]
+pub struct rocks_filelock_t {
+    _unused: [u8; 0],
+}
+#[repr(C)]
+#[derive(Copy, Clone)]
 pub struct rocks_snapshot_t {
     _unused: [u8; 0],
 }
@@ -207,6 +212,11 @@ pub struct rocks_perf_context_t {
 }
 #[repr(C)]
 #[derive(Copy, Clone)]
+pub struct rocks_perf_context_by_level_t {
+    _unused: [u8; 0],
+}
+#[repr(C)]
+#[derive(Copy, Clone)]
 pub struct rocks_statistics_t {
     _unused: [u8; 0],
 }
@@ -242,6 +252,26 @@ pub struct rocks_transaction_log_iterator_t {
 }
 #[repr(C)]
 #[derive(Copy, Clone)]
+pub struct rocks_transactiondb_options_t {
+    _unused: [u8; 0],
+}
+#[repr(C)]
+#[derive(Copy, Clone)]
+pub struct rocks_transaction_options_t {
+    _unused: [u8; 0],
+}
+#[repr(C)]
+#[derive(Copy, Clone)]
+pub struct rocks_optimistic_transaction_options_t {
+    _unused: [u8; 0],
+}
+#[repr(C)]
+#[derive(Copy, Clone)]
+pub struct rocks_transaction_t {
+    _unused: [u8; 0],
+}
+#[repr(C)]
+#[derive(Copy, Clone)]
 pub struct rocks_table_props_collection_t {
     _unused: [u8; 0],
 }
@@ -337,11 +367,31 @@ pub struct rocks_external_file_ingestion_info_t {
 }
 #[repr(C)]
 #[derive(Copy, Clone)]
+pub struct rocks_write_stall_info_t {
+    _unused: [u8; 0],
+}
+#[repr(C)]
+#[derive(Copy, Clone)]
+pub struct rocks_file_operation_info_t {
+    _unused: [u8; 0],
+}
+#[repr(C)]
+#[derive(Copy, Clone)]
 pub struct rocks_thread_status_t {
     _unused: [u8; 0],
 }
 #[repr(C)]
 #[derive(Copy, Clone)]
+pub struct rocks_stats_history_iterator_t {
+    _unused: [u8; 0],
+}
+#[repr(C)]
+#[derive(Copy, Clone)]
+pub struct rocks_checkpoint_t {
+    _unused: [u8; 0],
+}
+#[repr(C)]
+#[derive(Copy, Clone)]
 pub struct cxx_string_vector_t {
     _unused: [u8; 0],
 }
@@ -353,6 +403,9 @@ pub struct cxx_string_t {
 extern "C" {
     pub fn rocks_status_destroy(s: *mut rocks_status_t);
 }
+extern "C" {
+    pub fn rocks_status_copy(s: *mut rocks_status_t) -> *mut rocks_status_t;
+}
 extern "C" {
     pub fn rocks_status_code(s: *mut rocks_status_t) -> ::std::os::raw::c_int;
 }
@@ -365,6 +418,24 @@ extern "C" {
 extern "C" {
     pub fn rocks_status_get_state(s: *mut rocks_status_t) -> *const ::std::os::raw::c_char;
 }
+extern "C" {
+    pub fn rocks_status_create_busy(msg: *const ::std::os::raw::c_char) -> *mut rocks_status_t;
+}
+extern "C" {
+    pub fn rocks_status_create_invalid_argument(msg: *const ::std::os::raw::c_char) -> *mut rocks_status_t;
+}
+extern "C" {
+    pub fn rocks_status_create_timed_out(msg: *const ::std::os::raw::c_char) -> *mut rocks_status_t;
+}
+extern "C" {
+    pub fn rocks_status_create_aborted(msg: *const ::std::os::raw::c_char) -> *mut rocks_status_t;
+}
+extern "C" {
+    pub fn rocks_status_create_not_supported(msg: *const ::std::os::raw::c_char) -> *mut rocks_status_t;
+}
+extern "C" {
+    pub fn rocks_status_create_corruption(msg: *const ::std::os::raw::c_char) -> *mut rocks_status_t;
+}
 extern "C" {
     pub fn rocks_pinnable_slice_create() -> *mut rocks_pinnable_slice_t;
 }
@@ -377,6 +448,12 @@ extern "C" {
 extern "C" {
     pub fn rocks_pinnable_slice_size(s: *mut rocks_pinnable_slice_t) -> usize;
 }
+extern "C" {
+    pub fn rocks_pinnable_slice_is_pinned(s: *mut rocks_pinnable_slice_t) -> ::std::os::raw::c_uchar;
+}
+extern "C" {
+    pub fn rocks_pinnable_slice_reset(s: *mut rocks_pinnable_slice_t);
+}
 extern "C" {
     pub fn rocks_column_family_descriptor_get_name(
         desc: *const rocks_column_family_descriptor_t,
@@ -445,10 +522,7 @@ extern "C" {
     );
 }
 extern "C" {
-    pub fn rocks_cfoptions_set_comparator_by_trait(
-        opt: *mut rocks_cfoptions_t,
-        cp_trait_obj: *mut ::std::os::raw::c_void,
-    );
+    pub fn rocks_cfoptions_set_comparator(opt: *mut rocks_cfoptions_t, comparator: *const rocks_comparator_t);
 }
 extern "C" {
     pub fn rocks_cfoptions_set_compaction_filter_by_trait(
@@ -534,6 +608,9 @@ extern "C" {
         n: ::std::os::raw::c_int,
     );
 }
+extern "C" {
+    pub fn rocks_cfoptions_set_max_write_buffer_size_to_maintain(opt: *mut rocks_cfoptions_t, n: i64);
+}
 extern "C" {
     pub fn rocks_cfoptions_set_inplace_update_support(opt: *mut rocks_cfoptions_t, v: ::std::os::raw::c_uchar);
 }
@@ -543,6 +620,9 @@ extern "C" {
 extern "C" {
     pub fn rocks_cfoptions_set_memtable_prefix_bloom_size_ratio(opt: *mut rocks_cfoptions_t, v: f64);
 }
+extern "C" {
+    pub fn rocks_cfoptions_set_memtable_whole_key_filtering(opt: *mut rocks_cfoptions_t, v: ::std::os::raw::c_uchar);
+}
 extern "C" {
     pub fn rocks_cfoptions_set_memtable_huge_page_size(opt: *mut rocks_cfoptions_t, v: usize);
 }
@@ -638,6 +718,18 @@ extern "C" {
         fifo: *mut rocks_fifo_compaction_options_t,
     );
 }
+extern "C" {
+    pub fn rocks_cfoptions_set_ttl(opt: *mut rocks_cfoptions_t, v: u64);
+}
+extern "C" {
+    pub fn rocks_cfoptions_set_cf_paths(
+        opt: *mut rocks_cfoptions_t,
+        paths: *const *const ::std::os::raw::c_char,
+        path_lens: *const usize,
+        target_sizes: *const u64,
+        size: ::std::os::raw::c_int,
+    );
+}
 extern "C" {
     pub fn rocks_cfoptions_set_max_sequential_skip_in_iterations(opt: *mut rocks_cfoptions_t, v: u64);
 }
@@ -746,6 +838,15 @@ extern "C" {
 extern "C" {
     pub fn rocks_dboptions_set_max_background_jobs(opt: *mut rocks_dboptions_t, n: ::std::os::raw::c_int);
 }
+extern "C" {
+    pub fn rocks_dboptions_set_base_background_compactions(opt: *mut rocks_dboptions_t, n: ::std::os::raw::c_int);
+}
+extern "C" {
+    pub fn rocks_dboptions_set_max_background_compactions(opt: *mut rocks_dboptions_t, n: ::std::os::raw::c_int);
+}
+extern "C" {
+    pub fn rocks_dboptions_set_max_background_flushes(opt: *mut rocks_dboptions_t, n: ::std::os::raw::c_int);
+}
 extern "C" {
     pub fn rocks_dboptions_set_max_subcompactions(opt: *mut rocks_dboptions_t, n: u32);
 }
@@ -800,6 +901,12 @@ extern "C" {
 extern "C" {
     pub fn rocks_dboptions_set_stats_dump_period_sec(opt: *mut rocks_dboptions_t, v: ::std::os::raw::c_uint);
 }
+extern "C" {
+    pub fn rocks_dboptions_set_stats_persist_period_sec(opt: *mut rocks_dboptions_t, v: ::std::os::raw::c_uint);
+}
+extern "C" {
+    pub fn rocks_dboptions_set_stats_history_buffer_size(opt: *mut rocks_dboptions_t, v: usize);
+}
 extern "C" {
     pub fn rocks_dboptions_set_advise_random_on_open(opt: *mut rocks_dboptions_t, v: ::std::os::raw::c_uchar);
 }
@@ -842,6 +949,19 @@ extern "C" {
 extern "C" {
     pub fn rocks_dboptions_add_listener(opt: *mut rocks_dboptions_t, listener_trait_obj: *mut ::std::os::raw::c_void);
 }
+extern "C" {
+    pub fn rocks_dboptions_add_shared_listener(
+        opt: *mut rocks_dboptions_t,
+        listener_trait_obj: *mut ::std::os::raw::c_void,
+        key: *const ::std::os::raw::c_void,
+    );
+}
+extern "C" {
+    pub fn rocks_dboptions_remove_listener(opt: *mut rocks_dboptions_t, key: *const ::std::os::raw::c_void);
+}
+extern "C" {
+    pub fn rocks_dboptions_clear_listeners(opt: *mut rocks_dboptions_t);
+}
 extern "C" {
     pub fn rocks_dboptions_set_enable_thread_tracking(opt: *mut rocks_dboptions_t, v: ::std::os::raw::c_uchar);
 }
@@ -893,12 +1013,21 @@ extern "C" {
 extern "C" {
     pub fn rocks_dboptions_set_manual_wal_flush(opt: *mut rocks_dboptions_t, v: ::std::os::raw::c_uchar);
 }
+extern "C" {
+    pub fn rocks_dboptions_set_atomic_flush(opt: *mut rocks_dboptions_t, v: ::std::os::raw::c_uchar);
+}
+extern "C" {
+    pub fn rocks_dboptions_set_avoid_unnecessary_blocking_io(opt: *mut rocks_dboptions_t, v: ::std::os::raw::c_uchar);
+}
 extern "C" {
     pub fn rocks_options_prepare_for_bulk_load(opt: *mut rocks_options_t);
 }
 extern "C" {
     pub fn rocks_options_optimize_for_small_db(opt: *mut rocks_options_t);
 }
+extern "C" {
+    pub fn rocks_options_deterministic(opt: *mut rocks_options_t);
+}
 extern "C" {
     pub fn rocks_readoptions_create() -> *mut rocks_readoptions_t;
 }
@@ -908,6 +1037,9 @@ extern "C" {
         cache: ::std::os::raw::c_uchar,
     ) -> *mut rocks_readoptions_t;
 }
+extern "C" {
+    pub fn rocks_readoptions_create_from(opt: *const rocks_readoptions_t) -> *mut rocks_readoptions_t;
+}
 extern "C" {
     pub fn rocks_readoptions_destroy(opt: *mut rocks_readoptions_t);
 }
@@ -1030,6 +1162,15 @@ extern "C" {
         v: ::std::os::raw::c_int,
     );
 }
+extern "C" {
+    pub fn rocks_compactrange_options_set_allow_write_stall(
+        opt: *mut rocks_compactrange_options_t,
+        v: ::std::os::raw::c_uchar,
+    );
+}
+extern "C" {
+    pub fn rocks_compactrange_options_set_max_subcompactions(opt: *mut rocks_compactrange_options_t, v: u32);
+}
 extern "C" {
     pub fn rocks_ingestexternalfile_options_create() -> *mut rocks_ingestexternalfile_options_t;
 }
@@ -1170,6 +1311,40 @@ extern "C" {
 extern "C" {
     pub fn rocks_db_try_catch_up_with_primary(db: *mut rocks_db_t, status: *mut *mut rocks_status_t);
 }
+extern "C" {
+    pub fn rocks_db_open_with_ttl(
+        options: *const rocks_options_t,
+        name: *const ::std::os::raw::c_char,
+        ttl: i32,
+        read_only: ::std::os::raw::c_uchar,
+        status: *mut *mut rocks_status_t,
+    ) -> *mut rocks_db_t;
+}
+extern "C" {
+    pub fn rocks_db_open_column_families_with_ttl(
+        db_options: *const rocks_dboptions_t,
+        name: *const ::std::os::raw::c_char,
+        num_column_families: ::std::os::raw::c_int,
+        column_family_names: *const *const ::std::os::raw::c_char,
+        column_family_options: *const *const rocks_cfoptions_t,
+        column_family_handles: *mut *mut rocks_column_family_handle_t,
+        ttls: *const i32,
+        read_only: ::std::os::raw::c_uchar,
+        status: *mut *mut rocks_status_t,
+    ) -> *mut rocks_db_t;
+}
+extern "C" {
+    pub fn rocks_db_create_column_family_with_ttl(
+        db: *mut rocks_db_t,
+        column_family_options: *const rocks_cfoptions_t,
+        column_family_name: *const ::std::os::raw::c_char,
+        ttl: i32,
+        status: *mut *mut rocks_status_t,
+    ) -> *mut rocks_column_family_handle_t;
+}
+extern "C" {
+    pub fn rocks_db_set_ttl(db: *mut rocks_db_t, column_family: *mut rocks_column_family_handle_t, ttl: i32);
+}
 extern "C" {
     pub fn rocks_db_open_column_families(
         db_options: *const rocks_dboptions_t,
@@ -1212,6 +1387,18 @@ extern "C" {
         status: *mut *mut rocks_status_t,
     ) -> *mut rocks_column_family_handle_t;
 }
+extern "C" {
+    pub fn rocks_db_create_column_family_with_import(
+        db: *mut rocks_db_t,
+        column_family_options: *const rocks_cfoptions_t,
+        column_family_name: *const ::std::os::raw::c_char,
+        move_files: ::std::os::raw::c_uchar,
+        db_comparator_name: *const ::std::os::raw::c_char,
+        db_comparator_name_len: usize,
+        files: *const rocks_livefiles_t,
+        status: *mut *mut rocks_status_t,
+    ) -> *mut rocks_column_family_handle_t;
+}
 extern "C" {
     pub fn rocks_db_default_column_family(db: *mut rocks_db_t) -> *mut rocks_column_family_handle_t;
 }
@@ -1222,6 +1409,14 @@ extern "C" {
         status: *mut *mut rocks_status_t,
     );
 }
+extern "C" {
+    pub fn rocks_db_drop_column_families(
+        db: *mut rocks_db_t,
+        handles: *const *const rocks_column_family_handle_t,
+        num_handles: usize,
+        status: *mut *mut rocks_status_t,
+    );
+}
 extern "C" {
     pub fn rocks_db_destroy_column_family_handle(
         db: *mut rocks_db_t,
@@ -1357,6 +1552,17 @@ extern "C" {
         status: *mut *mut rocks_status_t,
     );
 }
+extern "C" {
+    pub fn rocks_db_get_into(
+        db: *mut rocks_db_t,
+        options: *const rocks_readoptions_t,
+        column_family: *mut rocks_column_family_handle_t,
+        key: *const ::std::os::raw::c_char,
+        keylen: usize,
+        value: *mut ::std::os::raw::c_void,
+        status: *mut *mut rocks_status_t,
+    ) -> ::std::os::raw::c_uchar;
+}
 extern "C" {
     pub fn rocks_db_multi_get(
         db: *mut rocks_db_t,
@@ -1435,6 +1641,15 @@ extern "C" {
         column_family: *mut rocks_column_family_handle_t,
     ) -> *mut rocks_iterator_t;
 }
+extern "C" {
+    pub fn rocks_db_create_prefix_iterator_cf(
+        db: *mut rocks_db_t,
+        options: *const rocks_readoptions_t,
+        column_family: *mut rocks_column_family_handle_t,
+        prefix: *const ::std::os::raw::c_char,
+        prefix_len: usize,
+    ) -> *mut rocks_iterator_t;
+}
 extern "C" {
     pub fn rocks_db_create_iterators(
         db: *mut rocks_db_t,
@@ -1468,6 +1683,16 @@ extern "C" {
         value: *mut ::std::os::raw::c_void,
     ) -> ::std::os::raw::c_uchar;
 }
+extern "C" {
+    pub fn rocks_db_get_map_property_cf(
+        db: *mut rocks_db_t,
+        cf: *mut rocks_column_family_handle_t,
+        prop: *const ::std::os::raw::c_char,
+        prop_len: usize,
+        keys: *mut cxx_string_vector_t,
+        values: *mut cxx_string_vector_t,
+    ) -> ::std::os::raw::c_uchar;
+}
 extern "C" {
     pub fn rocks_db_get_int_property(
         db: *mut rocks_db_t,
@@ -1535,6 +1760,25 @@ extern "C" {
         status: *mut *mut rocks_status_t,
     );
 }
+extern "C" {
+    pub fn rocks_db_suggest_compact_range(
+        db: *mut rocks_db_t,
+        column_family: *mut rocks_column_family_handle_t,
+        start_key: *const ::std::os::raw::c_char,
+        start_key_len: usize,
+        limit_key: *const ::std::os::raw::c_char,
+        limit_key_len: usize,
+        status: *mut *mut rocks_status_t,
+    );
+}
+extern "C" {
+    pub fn rocks_db_promote_l0(
+        db: *mut rocks_db_t,
+        column_family: *mut rocks_column_family_handle_t,
+        target_level: ::std::os::raw::c_int,
+        status: *mut *mut rocks_status_t,
+    );
+}
 extern "C" {
     pub fn rocks_db_set_options_cf(
         db: *mut rocks_db_t,
@@ -1576,6 +1820,12 @@ extern "C" {
 extern "C" {
     pub fn rocks_db_continue_background_work(db: *mut rocks_db_t, status: *mut *mut rocks_status_t);
 }
+extern "C" {
+    pub fn rocks_db_disable_manual_compaction(db: *mut rocks_db_t);
+}
+extern "C" {
+    pub fn rocks_db_enable_manual_compaction(db: *mut rocks_db_t);
+}
 extern "C" {
     pub fn rocks_db_enable_auto_compaction(
         db: *mut rocks_db_t,
@@ -1623,6 +1873,22 @@ extern "C" {
         sizes: *mut u64,
     );
 }
+extern "C" {
+    pub fn rocks_db_get_approximate_sizes_cf_opt(
+        db: *mut rocks_db_t,
+        column_family: *mut rocks_column_family_handle_t,
+        include_memtables: ::std::os::raw::c_uchar,
+        include_files: ::std::os::raw::c_uchar,
+        files_size_error_margin: f64,
+        num_ranges: usize,
+        range_start_ptrs: *const *const ::std::os::raw::c_char,
+        range_start_lens: *const usize,
+        range_limit_ptrs: *const *const ::std::os::raw::c_char,
+        range_limit_lens: *const usize,
+        sizes: *mut u64,
+        status: *mut *mut rocks_status_t,
+    );
+}
 extern "C" {
     pub fn rocks_db_get_approximate_memtable_stats_cf(
         db: *mut rocks_db_t,
@@ -1649,9 +1915,21 @@ extern "C" {
         status: *mut *mut rocks_status_t,
     );
 }
+extern "C" {
+    pub fn rocks_db_flush_cfs(
+        db: *mut rocks_db_t,
+        options: *mut rocks_flushoptions_t,
+        column_families: *const *const rocks_column_family_handle_t,
+        num_column_families: usize,
+        status: *mut *mut rocks_status_t,
+    );
+}
 extern "C" {
     pub fn rocks_db_sync_wal(db: *mut rocks_db_t, status: *mut *mut rocks_status_t);
 }
+extern "C" {
+    pub fn rocks_db_flush_wal(db: *mut rocks_db_t, sync: ::std::os::raw::c_uchar, status: *mut *mut rocks_status_t);
+}
 extern "C" {
     pub fn rocks_db_get_latest_sequence_number(db: *mut rocks_db_t) -> u64;
 }
@@ -1679,6 +1957,12 @@ extern "C" {
         status: *mut *mut rocks_status_t,
     ) -> *mut rocks_logfiles_t;
 }
+extern "C" {
+    pub fn rocks_db_get_current_wal_file(
+        db: *mut rocks_db_t,
+        status: *mut *mut rocks_status_t,
+    ) -> *mut rocks_logfiles_t;
+}
 extern "C" {
     pub fn rocks_db_get_update_since(
         db: *mut rocks_db_t,
@@ -1686,6 +1970,14 @@ extern "C" {
         status: *mut *mut rocks_status_t,
     ) -> *mut rocks_transaction_log_iterator_t;
 }
+extern "C" {
+    pub fn rocks_db_get_stats_history(
+        db: *mut rocks_db_t,
+        start_time: u64,
+        end_time: u64,
+        status: *mut *mut rocks_status_t,
+    ) -> *mut rocks_stats_history_iterator_t;
+}
 extern "C" {
     pub fn rocks_db_delete_file(
         db: *mut rocks_db_t,
@@ -1731,6 +2023,13 @@ extern "C" {
         status: *mut *mut rocks_status_t,
     );
 }
+extern "C" {
+    pub fn rocks_db_verify_checksum(
+        db: *mut rocks_db_t,
+        options: *const rocks_readoptions_t,
+        status: *mut *mut rocks_status_t,
+    );
+}
 extern "C" {
     pub fn rocks_db_get_properties_of_all_tables(
         db: *mut rocks_db_t,
@@ -1773,59 +2072,181 @@ extern "C" {
         fairness: i32,
     ) -> *mut rocks_ratelimiter_t;
 }
+extern "C" {
+    pub fn rocks_ratelimiter_create_with_mode(
+        rate_bytes_per_sec: i64,
+        refill_period_us: i64,
+        fairness: i32,
+        mode: ::std::os::raw::c_int,
+        auto_tuned: ::std::os::raw::c_uchar,
+    ) -> *mut rocks_ratelimiter_t;
+}
 extern "C" {
     pub fn rocks_ratelimiter_destroy(limiter: *mut rocks_ratelimiter_t);
 }
 extern "C" {
-    pub fn rocks_create_default_env() -> *mut rocks_env_t;
+    pub fn rocks_ratelimiter_set_bytes_per_second(limiter: *mut rocks_ratelimiter_t, bytes_per_second: i64);
 }
 extern "C" {
-    pub fn rocks_create_mem_env() -> *mut rocks_env_t;
+    pub fn rocks_ratelimiter_get_bytes_per_second(limiter: *mut rocks_ratelimiter_t) -> i64;
 }
 extern "C" {
-    pub fn rocks_create_timed_env() -> *mut rocks_env_t;
+    pub fn rocks_ratelimiter_get_single_burst_bytes(limiter: *mut rocks_ratelimiter_t) -> i64;
 }
 extern "C" {
-    pub fn rocks_env_destroy(env: *mut rocks_env_t);
+    pub fn rocks_ratelimiter_get_total_bytes_through(limiter: *mut rocks_ratelimiter_t, pri: ::std::os::raw::c_int)
+        -> i64;
 }
 extern "C" {
-    pub fn rocks_env_set_background_threads(env: *mut rocks_env_t, n: ::std::os::raw::c_int);
+    pub fn rocks_ratelimiter_get_total_requests(limiter: *mut rocks_ratelimiter_t, pri: ::std::os::raw::c_int) -> i64;
 }
 extern "C" {
-    pub fn rocks_env_set_high_priority_background_threads(env: *mut rocks_env_t, n: ::std::os::raw::c_int);
+    pub fn rocks_ratelimiter_clone(limiter: *mut rocks_ratelimiter_t) -> *mut rocks_ratelimiter_t;
 }
 extern "C" {
-    pub fn rocks_env_join_all_threads(env: *mut rocks_env_t);
+    pub fn rocks_ratelimiter_request(limiter: *mut rocks_ratelimiter_t, bytes: i64, pri: ::std::os::raw::c_int);
 }
 extern "C" {
-    pub fn rocks_env_get_thread_pool_queue_len(
-        env: *mut rocks_env_t,
-        pri: ::std::os::raw::c_int,
-    ) -> ::std::os::raw::c_uint;
+    pub fn rocks_create_default_env() -> *mut rocks_env_t;
 }
 extern "C" {
-    pub fn rocks_env_new_logger(
-        env: *mut rocks_env_t,
-        name_ptr: *const ::std::os::raw::c_char,
-        name_len: usize,
-        status: *mut *mut rocks_status_t,
-    ) -> *mut rocks_logger_t;
+    pub fn rocks_create_mem_env() -> *mut rocks_env_t;
 }
 extern "C" {
-    pub fn rocks_env_now_micros(env: *mut rocks_env_t) -> u64;
+    pub fn rocks_create_timed_env() -> *mut rocks_env_t;
 }
 extern "C" {
-    pub fn rocks_env_now_nanos(env: *mut rocks_env_t) -> u64;
+    pub fn rocks_create_latency_injection_env() -> *mut rocks_env_t;
 }
 extern "C" {
-    pub fn rocks_env_sleep_for_microseconds(env: *mut rocks_env_t, micros: i32);
+    pub fn rocks_create_mem_env_with_base(base: *mut rocks_env_t) -> *mut rocks_env_t;
 }
 extern "C" {
-    pub fn rocks_env_get_host_name(
-        env: *mut rocks_env_t,
-        name: *mut ::std::os::raw::c_char,
-        len: u64,
-        status: *mut *mut rocks_status_t,
+    pub fn rocks_create_timed_env_with_base(base: *mut rocks_env_t) -> *mut rocks_env_t;
+}
+extern "C" {
+    pub fn rocks_create_rot13_encrypted_env(base: *mut rocks_env_t, block_size: usize) -> *mut rocks_env_t;
+}
+extern "C" {
+    pub fn rocks_create_encrypted_env(base: *mut rocks_env_t, cipher: *mut ::std::os::raw::c_void) -> *mut rocks_env_t;
+}
+extern "C" {
+    pub fn rocks_create_rust_env(file_system: *mut ::std::os::raw::c_void) -> *mut rocks_env_t;
+}
+extern "C" {
+    pub fn rocks_env_set_injected_latency(
+        env: *mut rocks_env_t,
+        op: ::std::os::raw::c_int,
+        probability: f64,
+        min_micros: u64,
+        max_micros: u64,
+    ) -> ::std::os::raw::c_uchar;
+}
+extern "C" {
+    pub fn rocks_env_destroy(env: *mut rocks_env_t);
+}
+extern "C" {
+    pub fn rocks_env_set_background_threads(
+        env: *mut rocks_env_t,
+        n: ::std::os::raw::c_int,
+        pri: ::std::os::raw::c_int,
+    );
+}
+extern "C" {
+    pub fn rocks_env_join_all_threads(env: *mut rocks_env_t);
+}
+extern "C" {
+    pub fn rocks_env_get_thread_pool_queue_len(
+        env: *mut rocks_env_t,
+        pri: ::std::os::raw::c_int,
+    ) -> ::std::os::raw::c_uint;
+}
+extern "C" {
+    pub fn rocks_env_new_logger(
+        env: *mut rocks_env_t,
+        name_ptr: *const ::std::os::raw::c_char,
+        name_len: usize,
+        status: *mut *mut rocks_status_t,
+    ) -> *mut rocks_logger_t;
+}
+extern "C" {
+    pub fn rocks_env_lock_file(
+        env: *mut rocks_env_t,
+        fname: *const ::std::os::raw::c_char,
+        fname_len: usize,
+        status: *mut *mut rocks_status_t,
+    ) -> *mut rocks_filelock_t;
+}
+extern "C" {
+    pub fn rocks_env_unlock_file(env: *mut rocks_env_t, lock: *mut rocks_filelock_t, status: *mut *mut rocks_status_t);
+}
+extern "C" {
+    pub fn rocks_env_get_direct_io_alignment(
+        env: *mut rocks_env_t,
+        fname: *const ::std::os::raw::c_char,
+        fname_len: usize,
+        status: *mut *mut rocks_status_t,
+    ) -> usize;
+}
+extern "C" {
+    pub fn rocks_env_get_children(
+        env: *mut rocks_env_t,
+        dir: *const ::std::os::raw::c_char,
+        dir_len: usize,
+        len: *mut usize,
+        status: *mut *mut rocks_status_t,
+    ) -> *mut *mut ::std::os::raw::c_char;
+}
+extern "C" {
+    pub fn rocks_env_get_children_destroy(list: *mut *mut ::std::os::raw::c_char, len: usize);
+}
+extern "C" {
+    pub fn rocks_env_file_exists(
+        env: *mut rocks_env_t,
+        fname: *const ::std::os::raw::c_char,
+        fname_len: usize,
+        status: *mut *mut rocks_status_t,
+    );
+}
+extern "C" {
+    pub fn rocks_env_get_file_size(
+        env: *mut rocks_env_t,
+        fname: *const ::std::os::raw::c_char,
+        fname_len: usize,
+        status: *mut *mut rocks_status_t,
+    ) -> u64;
+}
+extern "C" {
+    pub fn rocks_env_get_file_modification_time(
+        env: *mut rocks_env_t,
+        fname: *const ::std::os::raw::c_char,
+        fname_len: usize,
+        status: *mut *mut rocks_status_t,
+    ) -> u64;
+}
+extern "C" {
+    pub fn rocks_env_delete_file(
+        env: *mut rocks_env_t,
+        fname: *const ::std::os::raw::c_char,
+        fname_len: usize,
+        status: *mut *mut rocks_status_t,
+    );
+}
+extern "C" {
+    pub fn rocks_env_now_micros(env: *mut rocks_env_t) -> u64;
+}
+extern "C" {
+    pub fn rocks_env_now_nanos(env: *mut rocks_env_t) -> u64;
+}
+extern "C" {
+    pub fn rocks_env_sleep_for_microseconds(env: *mut rocks_env_t, micros: i32);
+}
+extern "C" {
+    pub fn rocks_env_get_host_name(
+        env: *mut rocks_env_t,
+        name: *mut ::std::os::raw::c_char,
+        len: u64,
+        status: *mut *mut rocks_status_t,
     );
 }
 extern "C" {
@@ -1896,6 +2317,16 @@ extern "C" {
 extern "C" {
     pub fn rocks_envoptions_set_writable_file_max_buffer_size(opt: *mut rocks_envoptions_t, val: usize);
 }
+extern "C" {
+    pub fn rocks_logger_create_rust(logger: *mut ::std::os::raw::c_void, log_level: ::std::os::raw::c_int)
+        -> *mut rocks_logger_t;
+}
+extern "C" {
+    pub fn rocks_logger_create_stderr(log_level: ::std::os::raw::c_int) -> *mut rocks_logger_t;
+}
+extern "C" {
+    pub fn rocks_logger_create_devnull() -> *mut rocks_logger_t;
+}
 extern "C" {
     pub fn rocks_logger_destroy(logger: *mut rocks_logger_t);
 }
@@ -1934,6 +2365,9 @@ extern "C" {
 extern "C" {
     pub fn rocks_writebatch_create_with_reserved_bytes(size: usize) -> *mut rocks_writebatch_t;
 }
+extern "C" {
+    pub fn rocks_writebatch_create_from(rep: *const ::std::os::raw::c_char, size: usize) -> *mut rocks_writebatch_t;
+}
 extern "C" {
     pub fn rocks_writebatch_destroy(b: *mut rocks_writebatch_t);
 }
@@ -2266,6 +2700,12 @@ extern "C" {
 extern "C" {
     pub fn rocks_block_based_table_options_destroy(options: *mut rocks_block_based_table_options_t);
 }
+extern "C" {
+    pub fn rocks_block_based_table_options_validate(
+        options: *const rocks_block_based_table_options_t,
+        status: *mut *mut rocks_status_t,
+    );
+}
 extern "C" {
     pub fn rocks_block_based_table_options_set_cache_index_and_filter_blocks(
         options: *mut rocks_block_based_table_options_t,
@@ -2458,6 +2898,9 @@ extern "C" {
         status: *mut *mut rocks_status_t,
     );
 }
+extern "C" {
+    pub fn rocks_iter_refresh(iter: *mut rocks_iterator_t, status: *mut *mut rocks_status_t);
+}
 extern "C" {
     pub fn rocks_new_empty_iterator() -> *mut rocks_iterator_t;
 }
@@ -2467,6 +2910,9 @@ extern "C" {
         use_block_based_builder: ::std::os::raw::c_uchar,
     ) -> *mut rocks_raw_filterpolicy_t;
 }
+extern "C" {
+    pub fn rocks_raw_filterpolicy_new_rust(trait_obj: *mut ::std::os::raw::c_void) -> *mut rocks_raw_filterpolicy_t;
+}
 extern "C" {
     pub fn rocks_raw_filterpolicy_destroy(cache: *mut rocks_raw_filterpolicy_t);
 }
@@ -2485,6 +2931,9 @@ extern "C" {
         strict_capacity_limit: ::std::os::raw::c_char,
     ) -> *mut rocks_cache_t;
 }
+extern "C" {
+    pub fn rocks_cache_clone(cache: *mut rocks_cache_t) -> *mut rocks_cache_t;
+}
 extern "C" {
     pub fn rocks_cache_destroy(cache: *mut rocks_cache_t);
 }
@@ -2503,6 +2952,15 @@ extern "C" {
 extern "C" {
     pub fn rocks_cache_name(cache: *mut rocks_cache_t) -> *const ::std::os::raw::c_char;
 }
+extern "C" {
+    pub fn rocks_cache_get_high_pri_pool_ratio(cache: *mut rocks_cache_t) -> f64;
+}
+extern "C" {
+    pub fn rocks_cache_set_strict_capacity_limit(cache: *mut rocks_cache_t, strict_capacity_limit: ::std::os::raw::c_uchar);
+}
+extern "C" {
+    pub fn rocks_cache_has_strict_capacity_limit(cache: *mut rocks_cache_t) -> ::std::os::raw::c_uchar;
+}
 extern "C" {
     pub fn rocks_new_persistent_cache(
         env: *const rocks_env_t,
@@ -2626,6 +3084,12 @@ extern "C" {
 extern "C" {
     pub fn rocks_sst_file_writer_file_size(writer: *mut rocks_sst_file_writer_t) -> u64;
 }
+extern "C" {
+    pub fn rocks_comparator_create(cp_trait_obj: *mut ::std::os::raw::c_void) -> *mut rocks_comparator_t;
+}
+extern "C" {
+    pub fn rocks_comparator_destroy(comparator: *mut rocks_comparator_t);
+}
 extern "C" {
     pub fn rocks_comparator_bytewise() -> *const rocks_c_comparator_t;
 }
@@ -2664,12 +3128,34 @@ extern "C" {
 extern "C" {
     pub fn rocks_dump_options_set_anonymous(opt: *mut rocks_dump_options_t, v: ::std::os::raw::c_uchar);
 }
+extern "C" {
+    pub fn rocks_dump_options_set_column_family(
+        opt: *mut rocks_dump_options_t,
+        name: *const ::std::os::raw::c_char,
+        name_len: usize,
+    );
+}
+extern "C" {
+    pub fn rocks_dump_options_set_column_family_options(
+        opt: *mut rocks_dump_options_t,
+        name: *const ::std::os::raw::c_char,
+        name_len: usize,
+        cfopts: *const rocks_cfoptions_t,
+    );
+}
 extern "C" {
     pub fn rocks_db_dump_tool_run(
         dump_options: *mut rocks_dump_options_t,
         options: *mut rocks_options_t,
     ) -> ::std::os::raw::c_uchar;
 }
+extern "C" {
+    pub fn rocks_db_dump(
+        dump_options: *mut rocks_dump_options_t,
+        options: *mut rocks_options_t,
+        status: *mut *mut rocks_status_t,
+    );
+}
 extern "C" {
     pub fn rocks_undump_options_create() -> *mut rocks_undump_options_t;
 }
@@ -2693,12 +3179,34 @@ extern "C" {
 extern "C" {
     pub fn rocks_undump_options_set_compact_db(opt: *mut rocks_undump_options_t, v: ::std::os::raw::c_uchar);
 }
+extern "C" {
+    pub fn rocks_undump_options_set_column_family(
+        opt: *mut rocks_undump_options_t,
+        name: *const ::std::os::raw::c_char,
+        name_len: usize,
+    );
+}
+extern "C" {
+    pub fn rocks_undump_options_set_column_family_options(
+        opt: *mut rocks_undump_options_t,
+        name: *const ::std::os::raw::c_char,
+        name_len: usize,
+        cfopts: *const rocks_cfoptions_t,
+    );
+}
 extern "C" {
     pub fn rocks_db_undump_tool_run(
         undump_options: *mut rocks_undump_options_t,
         options: *mut rocks_options_t,
     ) -> ::std::os::raw::c_uchar;
 }
+extern "C" {
+    pub fn rocks_db_undump(
+        undump_options: *mut rocks_undump_options_t,
+        options: *mut rocks_options_t,
+        status: *mut *mut rocks_status_t,
+    );
+}
 extern "C" {
     pub fn rocks_set_perf_level(level: ::std::os::raw::c_uchar);
 }
@@ -2731,6 +3239,23 @@ extern "C" {
         s: *mut ::std::os::raw::c_void,
     );
 }
+extern "C" {
+    pub fn rocks_perf_context_enable_per_level(ctx: *mut rocks_perf_context_t);
+}
+extern "C" {
+    pub fn rocks_perf_context_disable_per_level(ctx: *mut rocks_perf_context_t);
+}
+extern "C" {
+    pub fn rocks_perf_context_clear_per_level(ctx: *mut rocks_perf_context_t);
+}
+extern "C" {
+    pub fn rocks_perf_context_by_level(
+        ctx: *const rocks_perf_context_t,
+        levels: *mut u32,
+        stats: *mut *const rocks_perf_context_by_level_t,
+        len: usize,
+    ) -> usize;
+}
 extern "C" {
     pub fn rocks_statistics_create() -> *mut rocks_statistics_t;
 }
@@ -2789,6 +3314,32 @@ extern "C" {
 extern "C" {
     pub fn rocks_statistics_reset(stat: *mut rocks_statistics_t, status: *mut *mut rocks_status_t);
 }
+extern "C" {
+    pub fn rocks_statistics_get_stats_level(stat: *mut rocks_statistics_t) -> ::std::os::raw::c_uchar;
+}
+extern "C" {
+    pub fn rocks_statistics_set_stats_level(stat: *mut rocks_statistics_t, level: ::std::os::raw::c_uchar);
+}
+extern "C" {
+    pub fn rocks_statistics_num_tickers() -> usize;
+}
+extern "C" {
+    pub fn rocks_statistics_get_all_ticker_counts(
+        stat: *mut rocks_statistics_t,
+        names: *mut *const ::std::os::raw::c_char,
+        counts: *mut u64,
+    );
+}
+extern "C" {
+    pub fn rocks_statistics_num_histograms() -> usize;
+}
+extern "C" {
+    pub fn rocks_statistics_get_all_histogram_data(
+        stat: *mut rocks_statistics_t,
+        names: *mut *const ::std::os::raw::c_char,
+        data: *mut rocks_histogram_data_t,
+    );
+}
 extern "C" {
     pub fn rocks_livefiles_count(lf: *const rocks_livefiles_t) -> ::std::os::raw::c_int;
 }
@@ -2842,9 +3393,44 @@ extern "C" {
         index: ::std::os::raw::c_int,
     ) -> ::std::os::raw::c_uchar;
 }
+extern "C" {
+    pub fn rocks_livefiles_num_reads_sampled(lf: *const rocks_livefiles_t, index: ::std::os::raw::c_int) -> u64;
+}
+extern "C" {
+    pub fn rocks_livefiles_file_number(lf: *const rocks_livefiles_t, index: ::std::os::raw::c_int) -> u64;
+}
+extern "C" {
+    pub fn rocks_livefiles_num_entries(lf: *const rocks_livefiles_t, index: ::std::os::raw::c_int) -> u64;
+}
+extern "C" {
+    pub fn rocks_livefiles_num_deletions(lf: *const rocks_livefiles_t, index: ::std::os::raw::c_int) -> u64;
+}
+extern "C" {
+    pub fn rocks_livefiles_oldest_blob_file_number(lf: *const rocks_livefiles_t, index: ::std::os::raw::c_int) -> u64;
+}
 extern "C" {
     pub fn rocks_livefiles_destroy(lf: *const rocks_livefiles_t);
 }
+extern "C" {
+    pub fn rocks_livefiles_create() -> *mut rocks_livefiles_t;
+}
+extern "C" {
+    pub fn rocks_livefiles_push(
+        lf: *mut rocks_livefiles_t,
+        name: *const ::std::os::raw::c_char,
+        name_len: usize,
+        db_path: *const ::std::os::raw::c_char,
+        db_path_len: usize,
+        level: ::std::os::raw::c_int,
+        smallest_seqno: u64,
+        largest_seqno: u64,
+        smallestkey: *const ::std::os::raw::c_char,
+        smallestkey_len: usize,
+        largestkey: *const ::std::os::raw::c_char,
+        largestkey_len: usize,
+        size: usize,
+    );
+}
 extern "C" {
     pub fn rocks_column_family_metadata_size(meta: *const rocks_column_family_metadata_t) -> u64;
 }
@@ -2937,6 +3523,55 @@ extern "C" {
         file_index: ::std::os::raw::c_int,
     ) -> ::std::os::raw::c_uchar;
 }
+extern "C" {
+    pub fn rocks_column_family_metadata_levels_files_num_reads_sampled(
+        meta: *const rocks_column_family_metadata_t,
+        level: ::std::os::raw::c_int,
+        file_index: ::std::os::raw::c_int,
+    ) -> u64;
+}
+extern "C" {
+    pub fn rocks_column_family_metadata_levels_files_file_number(
+        meta: *const rocks_column_family_metadata_t,
+        level: ::std::os::raw::c_int,
+        file_index: ::std::os::raw::c_int,
+    ) -> u64;
+}
+extern "C" {
+    pub fn rocks_column_family_metadata_levels_files_num_entries(
+        meta: *const rocks_column_family_metadata_t,
+        level: ::std::os::raw::c_int,
+        file_index: ::std::os::raw::c_int,
+    ) -> u64;
+}
+extern "C" {
+    pub fn rocks_column_family_metadata_levels_files_num_deletions(
+        meta: *const rocks_column_family_metadata_t,
+        level: ::std::os::raw::c_int,
+        file_index: ::std::os::raw::c_int,
+    ) -> u64;
+}
+extern "C" {
+    pub fn rocks_column_family_metadata_levels_files_oldest_blob_file_number(
+        meta: *const rocks_column_family_metadata_t,
+        level: ::std::os::raw::c_int,
+        file_index: ::std::os::raw::c_int,
+    ) -> u64;
+}
+extern "C" {
+    pub fn rocks_column_family_metadata_levels_files_oldest_ancester_time(
+        meta: *const rocks_column_family_metadata_t,
+        level: ::std::os::raw::c_int,
+        file_index: ::std::os::raw::c_int,
+    ) -> u64;
+}
+extern "C" {
+    pub fn rocks_column_family_metadata_levels_files_file_creation_time(
+        meta: *const rocks_column_family_metadata_t,
+        level: ::std::os::raw::c_int,
+        file_index: ::std::os::raw::c_int,
+    ) -> u64;
+}
 extern "C" {
     pub fn rocks_column_family_metadata_destroy(meta: *const rocks_column_family_metadata_t);
 }
@@ -3030,6 +3665,220 @@ extern "C" {
         seq_no: *mut u64,
     ) -> *mut rocks_writebatch_t;
 }
+extern "C" {
+    pub fn rocks_transactiondb_options_create() -> *mut rocks_transactiondb_options_t;
+}
+extern "C" {
+    pub fn rocks_transactiondb_options_destroy(opt: *mut rocks_transactiondb_options_t);
+}
+extern "C" {
+    pub fn rocks_transactiondb_options_set_max_num_locks(opt: *mut rocks_transactiondb_options_t, v: i64);
+}
+extern "C" {
+    pub fn rocks_transactiondb_options_set_num_stripes(opt: *mut rocks_transactiondb_options_t, v: usize);
+}
+extern "C" {
+    pub fn rocks_transactiondb_options_set_transaction_lock_timeout(opt: *mut rocks_transactiondb_options_t, v: i64);
+}
+extern "C" {
+    pub fn rocks_transactiondb_options_set_default_lock_timeout(opt: *mut rocks_transactiondb_options_t, v: i64);
+}
+extern "C" {
+    pub fn rocks_transaction_options_create() -> *mut rocks_transaction_options_t;
+}
+extern "C" {
+    pub fn rocks_transaction_options_destroy(opt: *mut rocks_transaction_options_t);
+}
+extern "C" {
+    pub fn rocks_transaction_options_set_set_snapshot(
+        opt: *mut rocks_transaction_options_t,
+        v: ::std::os::raw::c_uchar,
+    );
+}
+extern "C" {
+    pub fn rocks_transaction_options_set_deadlock_detect(
+        opt: *mut rocks_transaction_options_t,
+        v: ::std::os::raw::c_uchar,
+    );
+}
+extern "C" {
+    pub fn rocks_transaction_options_set_lock_timeout(opt: *mut rocks_transaction_options_t, v: i64);
+}
+extern "C" {
+    pub fn rocks_transaction_options_set_expiration(opt: *mut rocks_transaction_options_t, v: i64);
+}
+extern "C" {
+    pub fn rocks_optimistic_transaction_options_create() -> *mut rocks_optimistic_transaction_options_t;
+}
+extern "C" {
+    pub fn rocks_optimistic_transaction_options_destroy(opt: *mut rocks_optimistic_transaction_options_t);
+}
+extern "C" {
+    pub fn rocks_optimistic_transaction_options_set_set_snapshot(
+        opt: *mut rocks_optimistic_transaction_options_t,
+        v: ::std::os::raw::c_uchar,
+    );
+}
+extern "C" {
+    pub fn rocks_transactiondb_open(
+        options: *const rocks_options_t,
+        txn_db_options: *const rocks_transactiondb_options_t,
+        name: *const ::std::os::raw::c_char,
+        status: *mut *mut rocks_status_t,
+    ) -> *mut rocks_db_t;
+}
+extern "C" {
+    pub fn rocks_transactiondb_open_column_families(
+        db_options: *const rocks_dboptions_t,
+        txn_db_options: *const rocks_transactiondb_options_t,
+        name: *const ::std::os::raw::c_char,
+        num_column_families: ::std::os::raw::c_int,
+        column_family_names: *const *const ::std::os::raw::c_char,
+        column_family_options: *const *const rocks_cfoptions_t,
+        column_family_handles: *mut *mut rocks_column_family_handle_t,
+        status: *mut *mut rocks_status_t,
+    ) -> *mut rocks_db_t;
+}
+extern "C" {
+    pub fn rocks_transactiondb_begin_transaction(
+        db: *mut rocks_db_t,
+        write_options: *const rocks_writeoptions_t,
+        txn_options: *const rocks_transaction_options_t,
+    ) -> *mut rocks_transaction_t;
+}
+extern "C" {
+    pub fn rocks_transactiondb_get_all_prepared_transactions(
+        db: *mut rocks_db_t,
+        count: *mut usize,
+    ) -> *mut *mut rocks_transaction_t;
+}
+extern "C" {
+    pub fn rocks_transaction_array_destroy(txns: *mut *mut rocks_transaction_t);
+}
+extern "C" {
+    pub fn rocks_optimistic_transactiondb_open(
+        options: *const rocks_options_t,
+        name: *const ::std::os::raw::c_char,
+        status: *mut *mut rocks_status_t,
+    ) -> *mut rocks_db_t;
+}
+extern "C" {
+    pub fn rocks_optimistic_transactiondb_open_column_families(
+        db_options: *const rocks_dboptions_t,
+        name: *const ::std::os::raw::c_char,
+        num_column_families: ::std::os::raw::c_int,
+        column_family_names: *const *const ::std::os::raw::c_char,
+        column_family_options: *const *const rocks_cfoptions_t,
+        column_family_handles: *mut *mut rocks_column_family_handle_t,
+        status: *mut *mut rocks_status_t,
+    ) -> *mut rocks_db_t;
+}
+extern "C" {
+    pub fn rocks_optimistic_transactiondb_begin_transaction(
+        db: *mut rocks_db_t,
+        write_options: *const rocks_writeoptions_t,
+        txn_options: *const rocks_optimistic_transaction_options_t,
+    ) -> *mut rocks_transaction_t;
+}
+extern "C" {
+    pub fn rocks_transaction_destroy(txn: *mut rocks_transaction_t);
+}
+extern "C" {
+    pub fn rocks_transaction_set_name(
+        txn: *mut rocks_transaction_t,
+        name: *const ::std::os::raw::c_char,
+        name_len: usize,
+        status: *mut *mut rocks_status_t,
+    );
+}
+extern "C" {
+    pub fn rocks_transaction_get_name(txn: *mut rocks_transaction_t, name: *mut ::std::os::raw::c_void);
+}
+extern "C" {
+    pub fn rocks_transaction_get_id(txn: *mut rocks_transaction_t) -> u64;
+}
+extern "C" {
+    pub fn rocks_transaction_prepare(txn: *mut rocks_transaction_t, status: *mut *mut rocks_status_t);
+}
+extern "C" {
+    pub fn rocks_transaction_commit(txn: *mut rocks_transaction_t, status: *mut *mut rocks_status_t);
+}
+extern "C" {
+    pub fn rocks_transaction_rollback(txn: *mut rocks_transaction_t, status: *mut *mut rocks_status_t);
+}
+extern "C" {
+    pub fn rocks_transaction_set_snapshot(txn: *mut rocks_transaction_t);
+}
+extern "C" {
+    pub fn rocks_transaction_get_snapshot(txn: *mut rocks_transaction_t) -> *const rocks_snapshot_t;
+}
+extern "C" {
+    pub fn rocks_transaction_clear_snapshot(txn: *mut rocks_transaction_t);
+}
+extern "C" {
+    pub fn rocks_transaction_set_savepoint(txn: *mut rocks_transaction_t);
+}
+extern "C" {
+    pub fn rocks_transaction_rollback_to_savepoint(txn: *mut rocks_transaction_t, status: *mut *mut rocks_status_t);
+}
+extern "C" {
+    pub fn rocks_transaction_put(
+        txn: *mut rocks_transaction_t,
+        column_family: *mut rocks_column_family_handle_t,
+        key: *const ::std::os::raw::c_char,
+        keylen: usize,
+        val: *const ::std::os::raw::c_char,
+        vallen: usize,
+        status: *mut *mut rocks_status_t,
+    );
+}
+extern "C" {
+    pub fn rocks_transaction_delete(
+        txn: *mut rocks_transaction_t,
+        column_family: *mut rocks_column_family_handle_t,
+        key: *const ::std::os::raw::c_char,
+        keylen: usize,
+        status: *mut *mut rocks_status_t,
+    );
+}
+extern "C" {
+    pub fn rocks_transaction_get_pinnable(
+        txn: *mut rocks_transaction_t,
+        options: *const rocks_readoptions_t,
+        column_family: *mut rocks_column_family_handle_t,
+        key: *const ::std::os::raw::c_char,
+        keylen: usize,
+        value: *mut rocks_pinnable_slice_t,
+        status: *mut *mut rocks_status_t,
+    );
+}
+extern "C" {
+    pub fn rocks_transaction_get_for_update_pinnable(
+        txn: *mut rocks_transaction_t,
+        options: *const rocks_readoptions_t,
+        column_family: *mut rocks_column_family_handle_t,
+        key: *const ::std::os::raw::c_char,
+        keylen: usize,
+        value: *mut rocks_pinnable_slice_t,
+        exclusive: ::std::os::raw::c_uchar,
+        status: *mut *mut rocks_status_t,
+    );
+}
+extern "C" {
+    pub fn rocks_transaction_undo_get_for_update(
+        txn: *mut rocks_transaction_t,
+        column_family: *mut rocks_column_family_handle_t,
+        key: *const ::std::os::raw::c_char,
+        keylen: usize,
+    );
+}
+extern "C" {
+    pub fn rocks_transaction_create_iterator(
+        txn: *mut rocks_transaction_t,
+        options: *const rocks_readoptions_t,
+        column_family: *mut rocks_column_family_handle_t,
+    ) -> *mut rocks_iterator_t;
+}
 extern "C" {
     pub fn rocks_get_supported_compressions(len: *mut usize) -> *mut ::std::os::raw::c_int;
 }
@@ -3050,12 +3899,33 @@ extern "C" {
         status: *mut *mut rocks_status_t,
     );
 }
+extern "C" {
+    pub fn rocks_db_delete_files_in_ranges(
+        db: *mut rocks_db_t,
+        column_family: *mut rocks_column_family_handle_t,
+        start_ptrs: *const *const ::std::os::raw::c_char,
+        start_lens: *const usize,
+        limit_ptrs: *const *const ::std::os::raw::c_char,
+        limit_lens: *const usize,
+        num_ranges: usize,
+        include_end: ::std::os::raw::c_uchar,
+        status: *mut *mut rocks_status_t,
+    );
+}
 extern "C" {
     pub fn rocks_get_string_from_dboptions(opts: *mut rocks_dboptions_t) -> *mut cxx_string_t;
 }
 extern "C" {
     pub fn rocks_get_string_from_cfoptions(opts: *mut rocks_cfoptions_t) -> *mut cxx_string_t;
 }
+extern "C" {
+    pub fn rocks_get_cfoptions_from_string(
+        base: *const rocks_cfoptions_t,
+        opts_str: *const ::std::os::raw::c_char,
+        opts_len: usize,
+        status: *mut *mut rocks_status_t,
+    ) -> *mut rocks_cfoptions_t;
+}
 extern "C" {
     pub fn rocks_table_props_collection_destroy(coll: *mut rocks_table_props_collection_t);
 }
@@ -3129,6 +3999,36 @@ extern "C" {
 extern "C" {
     pub fn rocks_table_props_get_column_family_id(prop: *mut rocks_table_props_t) -> u32;
 }
+extern "C" {
+    pub fn rocks_table_props_get_index_partitions(prop: *mut rocks_table_props_t) -> u64;
+}
+extern "C" {
+    pub fn rocks_table_props_get_top_level_index_size(prop: *mut rocks_table_props_t) -> u64;
+}
+extern "C" {
+    pub fn rocks_table_props_get_index_key_is_user_key(prop: *mut rocks_table_props_t) -> u64;
+}
+extern "C" {
+    pub fn rocks_table_props_get_index_value_is_delta_encoded(prop: *mut rocks_table_props_t) -> u64;
+}
+extern "C" {
+    pub fn rocks_table_props_get_num_deletions(prop: *mut rocks_table_props_t) -> u64;
+}
+extern "C" {
+    pub fn rocks_table_props_get_num_merge_operands(prop: *mut rocks_table_props_t) -> u64;
+}
+extern "C" {
+    pub fn rocks_table_props_get_num_range_deletions(prop: *mut rocks_table_props_t) -> u64;
+}
+extern "C" {
+    pub fn rocks_table_props_get_creation_time(prop: *mut rocks_table_props_t) -> u64;
+}
+extern "C" {
+    pub fn rocks_table_props_get_oldest_key_time(prop: *mut rocks_table_props_t) -> u64;
+}
+extern "C" {
+    pub fn rocks_table_props_get_file_creation_time(prop: *mut rocks_table_props_t) -> u64;
+}
 extern "C" {
     pub fn rocks_table_props_get_column_family_name(
         prop: *mut rocks_table_props_t,
@@ -3227,18 +4127,37 @@ extern "C" {
 extern "C" {
     pub fn rocks_write_buffer_manager_create(buffer_size: usize) -> *mut rocks_write_buffer_manager_t;
 }
+extern "C" {
+    pub fn rocks_write_buffer_manager_create_with_cache(
+        buffer_size: usize,
+        cache: *mut rocks_cache_t,
+    ) -> *mut rocks_write_buffer_manager_t;
+}
 extern "C" {
     pub fn rocks_write_buffer_manager_destroy(manager: *mut rocks_write_buffer_manager_t);
 }
 extern "C" {
     pub fn rocks_write_buffer_manager_enabled(manager: *mut rocks_write_buffer_manager_t) -> ::std::os::raw::c_uchar;
 }
+extern "C" {
+    pub fn rocks_write_buffer_manager_cost_to_cache(
+        manager: *mut rocks_write_buffer_manager_t,
+    ) -> ::std::os::raw::c_uchar;
+}
 extern "C" {
     pub fn rocks_write_buffer_manager_memory_usage(manager: *mut rocks_write_buffer_manager_t) -> usize;
 }
+extern "C" {
+    pub fn rocks_write_buffer_manager_mutable_memtable_memory_usage(manager: *mut rocks_write_buffer_manager_t)
+        -> usize;
+}
 extern "C" {
     pub fn rocks_write_buffer_manager_buffer_size(manager: *mut rocks_write_buffer_manager_t) -> usize;
 }
+extern "C" {
+    pub fn rocks_write_buffer_manager_should_flush(manager: *mut rocks_write_buffer_manager_t)
+        -> ::std::os::raw::c_uchar;
+}
 extern "C" {
     pub fn rocks_db_get_all_key_versions(
         db: *mut rocks_db_t,
@@ -3578,6 +4497,42 @@ extern "C" {
         info: *const rocks_external_file_ingestion_info_t,
     ) -> *mut rocks_table_props_t;
 }
+extern "C" {
+    pub fn rocks_write_stall_info_get_cf_name(
+        info: *const rocks_write_stall_info_t,
+        len: *mut usize,
+    ) -> *const ::std::os::raw::c_char;
+}
+extern "C" {
+    pub fn rocks_write_stall_info_get_cur(info: *const rocks_write_stall_info_t) -> ::std::os::raw::c_int;
+}
+extern "C" {
+    pub fn rocks_write_stall_info_get_prev(info: *const rocks_write_stall_info_t) -> ::std::os::raw::c_int;
+}
+extern "C" {
+    pub fn rocks_file_operation_info_get_path(
+        info: *const rocks_file_operation_info_t,
+        len: *mut usize,
+    ) -> *const ::std::os::raw::c_char;
+}
+extern "C" {
+    pub fn rocks_file_operation_info_get_offset(info: *const rocks_file_operation_info_t) -> u64;
+}
+extern "C" {
+    pub fn rocks_file_operation_info_get_length(info: *const rocks_file_operation_info_t) -> usize;
+}
+extern "C" {
+    pub fn rocks_file_operation_info_get_start_timestamp(info: *const rocks_file_operation_info_t) -> i64;
+}
+extern "C" {
+    pub fn rocks_file_operation_info_get_finish_timestamp(info: *const rocks_file_operation_info_t) -> i64;
+}
+extern "C" {
+    pub fn rocks_file_operation_info_get_status(
+        info: *const rocks_file_operation_info_t,
+        status: *mut *mut rocks_status_t,
+    );
+}
 extern "C" {
     pub fn rocks_thread_status_destroy(status: *mut rocks_thread_status_t);
 }
@@ -3625,6 +4580,69 @@ extern "C" {
 extern "C" {
     pub fn rocks_load_options_destroy_cf_descs(c_cf_descs: *mut *mut rocks_column_family_descriptor_t, len: usize);
 }
+extern "C" {
+    pub fn rocks_get_approximate_memory_usage_by_type(
+        dbs: *const *mut rocks_db_t,
+        num_dbs: usize,
+        caches: *const *mut rocks_cache_t,
+        num_caches: usize,
+        usage_by_type: *mut u64,
+        status: *mut *mut rocks_status_t,
+    );
+}
+extern "C" {
+    pub fn rocks_stats_history_iterator_destroy(it: *mut rocks_stats_history_iterator_t);
+}
+extern "C" {
+    pub fn rocks_stats_history_iterator_valid(it: *mut rocks_stats_history_iterator_t) -> ::std::os::raw::c_uchar;
+}
+extern "C" {
+    pub fn rocks_stats_history_iterator_next(it: *mut rocks_stats_history_iterator_t);
+}
+extern "C" {
+    pub fn rocks_stats_history_iterator_status(
+        it: *mut rocks_stats_history_iterator_t,
+        status: *mut *mut rocks_status_t,
+    );
+}
+extern "C" {
+    pub fn rocks_stats_history_iterator_get_stats_time(it: *mut rocks_stats_history_iterator_t) -> u64;
+}
+extern "C" {
+    pub fn rocks_stats_history_iterator_stats_map_size(it: *mut rocks_stats_history_iterator_t) -> usize;
+}
+extern "C" {
+    pub fn rocks_stats_history_iterator_get_stats_map(
+        it: *mut rocks_stats_history_iterator_t,
+        names: *mut cxx_string_vector_t,
+        values: *mut u64,
+    );
+}
+extern "C" {
+    pub fn rocks_checkpoint_create(db: *mut rocks_db_t, status: *mut *mut rocks_status_t) -> *mut rocks_checkpoint_t;
+}
+extern "C" {
+    pub fn rocks_checkpoint_destroy(checkpoint: *mut rocks_checkpoint_t);
+}
+extern "C" {
+    pub fn rocks_checkpoint_create_checkpoint(
+        checkpoint: *mut rocks_checkpoint_t,
+        checkpoint_dir: *const ::std::os::raw::c_char,
+        dir_len: usize,
+        log_size_for_flush: u64,
+        status: *mut *mut rocks_status_t,
+    );
+}
+extern "C" {
+    pub fn rocks_checkpoint_export_column_family(
+        checkpoint: *mut rocks_checkpoint_t,
+        column_family: *mut rocks_column_family_handle_t,
+        export_dir: *const ::std::os::raw::c_char,
+        dir_len: usize,
+        db_comparator_name: *mut ::std::os::raw::c_void,
+        status: *mut *mut rocks_status_t,
+    ) -> *mut rocks_livefiles_t;
+}
 extern "C" {
     pub fn free(p: *mut ::std::os::raw::c_void);
 }
@@ -3637,6 +4655,9 @@ extern "C" {
 extern "C" {
     pub fn cxx_string_assign(s: *mut cxx_string_t, p: *const ::std::os::raw::c_char, len: usize);
 }
+extern "C" {
+    pub fn cxx_string_append(s: *mut cxx_string_t, p: *const ::std::os::raw::c_char, len: usize);
+}
 extern "C" {
     pub fn cxx_string_data(s: *const cxx_string_t) -> *const ::std::os::raw::c_char;
 }
@@ -3661,3 +4682,6 @@ extern "C" {
 extern "C" {
     pub fn cxx_string_vector_nth_size(v: *mut cxx_string_vector_t, index: usize) -> usize;
 }
+extern "C" {
+    pub fn cxx_string_vector_push(v: *mut cxx_string_vector_t, p: *const ::std::os::raw::c_char, len: usize);
+}
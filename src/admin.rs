@@ -0,0 +1,1005 @@
+//! Offline admin commands, programmatic equivalents of some `ldb` commands.
+//!
+//! - `manifest_dump()`, like `ldb manifest_dump`
+//! - `dump_wal()`, like `ldb dump_wal`
+//! - `check_consistency()`, like `ldb checkconsistency`
+//!
+//! All of them read files directly and never open the DB, so they work on DBs that fail
+//! to open, and never modify files. MANIFEST and WAL files share the log format, problems
+//! of both are reported as `WalProblem`.
+//!
+//! `plan_reopen()` compares the options persisted by a DB with the ones it is about to be
+//! reopened with, to catch changes that fail the open or need a data migration beforehand.
+//! `tune_direct_io()` checks the options that direct IO is sensitive to before opening.
+
+use std::collections::{BTreeMap, BTreeSet};
+use std::fs::{self, File};
+use std::io::{self, Read};
+use std::path::{Path, PathBuf};
+
+use crate::db::ColumnFamilyDescriptor;
+use crate::env::Env;
+use crate::options::{ColumnFamilyOptions, DBOptions};
+use crate::types::SequenceNumber;
+use crate::utilities::load_latest_options;
+use crate::wal_inspect::{read_log_records, WalProblem, WRITE_BATCH_HEADER_SIZE};
+use crate::write_batch::{WriteBatch, WriteBatchEntry, WriteBatchIteratorHandler};
+use crate::Error;
+
+// VersionEdit tags, see db/version_edit.h
+const TAG_COMPARATOR: u32 = 1;
+const TAG_LOG_NUMBER: u32 = 2;
+const TAG_NEXT_FILE_NUMBER: u32 = 3;
+const TAG_LAST_SEQUENCE: u32 = 4;
+const TAG_COMPACT_POINTER: u32 = 5;
+const TAG_DELETED_FILE: u32 = 6;
+const TAG_NEW_FILE: u32 = 7;
+const TAG_PREV_LOG_NUMBER: u32 = 9;
+const TAG_MIN_LOG_NUMBER_TO_KEEP: u32 = 10;
+const TAG_NEW_FILE2: u32 = 100;
+const TAG_NEW_FILE3: u32 = 102;
+const TAG_NEW_FILE4: u32 = 103;
+const TAG_COLUMN_FAMILY: u32 = 200;
+const TAG_COLUMN_FAMILY_ADD: u32 = 201;
+const TAG_COLUMN_FAMILY_DROP: u32 = 202;
+const TAG_MAX_COLUMN_FAMILY: u32 = 203;
+const TAG_IN_ATOMIC_GROUP: u32 = 300;
+// tags with this bit, e.g. kDbId, are length prefixed and can be skipped by older readers
+const TAG_SAFE_IGNORE_MASK: u32 = 1 << 13;
+
+// custom tags of TAG_NEW_FILE4
+const CUSTOM_TAG_TERMINATE: u32 = 1;
+const CUSTOM_TAG_PATH_ID: u32 = 65;
+const CUSTOM_TAG_NON_SAFE_IGNORE_MASK: u32 = 1 << 6;
+
+const FILE_NUMBER_MASK: u64 = 0x3FFF_FFFF_FFFF_FFFF;
+// size of the sequence number and value type of an internal key
+const INTERNAL_KEY_FOOTER_SIZE: usize = 8;
+
+/// A live SST file, as recorded in the MANIFEST.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ManifestFile {
+    pub level: u32,
+    pub number: u64,
+    /// Index of `DBOptions::db_paths`, 0 for the DB dir.
+    pub path_id: u32,
+    pub file_size: u64,
+    /// User keys.
+    pub smallest_key: Vec<u8>,
+    pub largest_key: Vec<u8>,
+    pub smallest_seqno: SequenceNumber,
+    pub largest_seqno: SequenceNumber,
+}
+
+impl ManifestFile {
+    /// The file name, e.g. `000012.sst`.
+    pub fn file_name(&self) -> String {
+        format!("{:06}.sst", self.number)
+    }
+}
+
+/// A column family, as recorded in the MANIFEST.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ManifestColumnFamily {
+    pub id: u32,
+    pub name: String,
+    pub comparator: Option<String>,
+    pub log_number: Option<u64>,
+    /// Live files, ordered by level and file number.
+    pub files: Vec<ManifestFile>,
+}
+
+/// The state recorded by a MANIFEST file, i.e. the result of applying all of its version edits.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ManifestDump {
+    pub path: PathBuf,
+    pub num_edits: u64,
+    pub next_file_number: Option<u64>,
+    pub last_sequence: Option<SequenceNumber>,
+    pub prev_log_number: Option<u64>,
+    pub min_log_number_to_keep: Option<u64>,
+    pub max_column_family: Option<u32>,
+    /// Column families not dropped, ordered by ID.
+    pub column_families: Vec<ManifestColumnFamily>,
+    /// First problem found, the state is the one before it. `None` if the file is clean.
+    pub problem: Option<WalProblem>,
+}
+
+/// Dump a MANIFEST file. `path` is either a MANIFEST file or a DB dir, in which case
+/// the current MANIFEST is read.
+pub fn manifest_dump<P: AsRef<Path>>(path: P) -> io::Result<ManifestDump> {
+    let path = path.as_ref();
+    let path = if path.is_dir() {
+        let current = fs::read_to_string(path.join("CURRENT"))?;
+        path.join(current.trim_end())
+    } else {
+        path.to_owned()
+    };
+    let mut data = Vec::new();
+    File::open(&path)?.read_to_end(&mut data)?;
+
+    let mut dump = ManifestDump {
+        path,
+        num_edits: 0,
+        next_file_number: None,
+        last_sequence: None,
+        prev_log_number: None,
+        min_log_number_to_keep: None,
+        max_column_family: None,
+        column_families: vec![],
+        problem: None,
+    };
+    // column family ID => (column family, files by (level, number))
+    let mut cfs: BTreeMap<u32, (ManifestColumnFamily, LiveFiles)> = BTreeMap::new();
+    cfs.insert(0, (new_column_family(0, "default".into()), BTreeMap::new()));
+
+    dump.problem = read_log_records(&data, None, |_, _, record| {
+        let edit = decode_version_edit(record)?;
+        dump.num_edits += 1;
+        dump.next_file_number = edit.next_file_number.or(dump.next_file_number);
+        dump.last_sequence = edit.last_sequence.map(SequenceNumber).or(dump.last_sequence);
+        dump.prev_log_number = edit.prev_log_number.or(dump.prev_log_number);
+        dump.min_log_number_to_keep = edit.min_log_number_to_keep.or(dump.min_log_number_to_keep);
+        dump.max_column_family = edit.max_column_family.or(dump.max_column_family);
+
+        if let Some(name) = edit.column_family_add {
+            cfs.insert(edit.column_family, (new_column_family(edit.column_family, name), BTreeMap::new()));
+        }
+        if edit.column_family_drop {
+            cfs.remove(&edit.column_family);
+            return Ok(());
+        }
+        let cf_id = edit.column_family;
+        let (cf, files) = cfs
+            .get_mut(&cf_id)
+            .ok_or_else(|| format!("unknown column family {}", cf_id))?;
+        cf.comparator = edit.comparator.or(cf.comparator.take());
+        cf.log_number = edit.log_number.or(cf.log_number);
+        for key in edit.deleted_files {
+            files.remove(&key);
+        }
+        for file in edit.new_files {
+            files.insert((file.level, file.number), file);
+        }
+        Ok(())
+    });
+
+    dump.column_families = cfs
+        .into_iter()
+        .map(|(_, (mut cf, files))| {
+            cf.files = files.into_values().collect();
+            cf
+        })
+        .collect();
+    Ok(dump)
+}
+
+type LiveFiles = BTreeMap<(u32, u64), ManifestFile>;
+
+fn new_column_family(id: u32, name: String) -> ManifestColumnFamily {
+    ManifestColumnFamily {
+        id,
+        name,
+        comparator: None,
+        log_number: None,
+        files: vec![],
+    }
+}
+
+#[derive(Default)]
+struct VersionEdit {
+    comparator: Option<String>,
+    log_number: Option<u64>,
+    prev_log_number: Option<u64>,
+    next_file_number: Option<u64>,
+    last_sequence: Option<u64>,
+    min_log_number_to_keep: Option<u64>,
+    max_column_family: Option<u32>,
+    deleted_files: Vec<(u32, u64)>,
+    new_files: Vec<ManifestFile>,
+    column_family: u32,
+    column_family_add: Option<String>,
+    column_family_drop: bool,
+}
+
+fn decode_version_edit(mut input: &[u8]) -> Result<VersionEdit, String> {
+    let mut edit = VersionEdit::default();
+    let input = &mut input;
+    while !input.is_empty() {
+        match get_varint32(input)? {
+            TAG_COMPARATOR => {
+                edit.comparator = Some(String::from_utf8_lossy(get_length_prefixed(input)?).into_owned());
+            },
+            TAG_LOG_NUMBER => edit.log_number = Some(get_varint64(input)?),
+            TAG_PREV_LOG_NUMBER => edit.prev_log_number = Some(get_varint64(input)?),
+            TAG_NEXT_FILE_NUMBER => edit.next_file_number = Some(get_varint64(input)?),
+            TAG_LAST_SEQUENCE => edit.last_sequence = Some(get_varint64(input)?),
+            TAG_MIN_LOG_NUMBER_TO_KEEP => edit.min_log_number_to_keep = Some(get_varint64(input)?),
+            TAG_MAX_COLUMN_FAMILY => edit.max_column_family = Some(get_varint32(input)?),
+            TAG_COMPACT_POINTER => {
+                get_varint32(input)?;
+                get_length_prefixed(input)?;
+            },
+            TAG_DELETED_FILE => {
+                let level = get_varint32(input)?;
+                let number = get_varint64(input)?;
+                edit.deleted_files.push((level, number));
+            },
+            tag @ TAG_NEW_FILE | tag @ TAG_NEW_FILE2 | tag @ TAG_NEW_FILE3 | tag @ TAG_NEW_FILE4 => {
+                edit.new_files.push(decode_new_file(tag, input)?);
+            },
+            TAG_COLUMN_FAMILY => edit.column_family = get_varint32(input)?,
+            TAG_COLUMN_FAMILY_ADD => {
+                edit.column_family_add = Some(String::from_utf8_lossy(get_length_prefixed(input)?).into_owned());
+            },
+            TAG_COLUMN_FAMILY_DROP => edit.column_family_drop = true,
+            TAG_IN_ATOMIC_GROUP => {
+                get_varint32(input)?;
+            },
+            tag if tag & TAG_SAFE_IGNORE_MASK != 0 => {
+                get_length_prefixed(input)?;
+            },
+            tag => return Err(format!("unknown version edit tag {}", tag)),
+        }
+    }
+    Ok(edit)
+}
+
+fn decode_new_file(tag: u32, input: &mut &[u8]) -> Result<ManifestFile, String> {
+    let level = get_varint32(input)?;
+    let mut number = get_varint64(input)?;
+    let mut path_id = 0;
+    if tag == TAG_NEW_FILE3 {
+        path_id = get_varint32(input)?;
+    } else if tag == TAG_NEW_FILE4 {
+        path_id = (number / (FILE_NUMBER_MASK + 1)) as u32;
+        number &= FILE_NUMBER_MASK;
+    }
+    let file_size = get_varint64(input)?;
+    let smallest_key = user_key(get_length_prefixed(input)?)?;
+    let largest_key = user_key(get_length_prefixed(input)?)?;
+    let (smallest_seqno, largest_seqno) = if tag == TAG_NEW_FILE {
+        (0, 0)
+    } else {
+        (get_varint64(input)?, get_varint64(input)?)
+    };
+    if tag == TAG_NEW_FILE4 {
+        loop {
+            let custom_tag = get_varint32(input)?;
+            if custom_tag == CUSTOM_TAG_TERMINATE {
+                break;
+            }
+            let field = get_length_prefixed(input)?;
+            if custom_tag == CUSTOM_TAG_PATH_ID {
+                path_id = *field.first().ok_or("bad path id")? as u32;
+            } else if custom_tag & CUSTOM_TAG_NON_SAFE_IGNORE_MASK != 0 {
+                return Err(format!("unknown new file custom tag {}", custom_tag));
+            }
+        }
+    }
+    Ok(ManifestFile {
+        level,
+        number,
+        path_id,
+        file_size,
+        smallest_key,
+        largest_key,
+        smallest_seqno: SequenceNumber(smallest_seqno),
+        largest_seqno: SequenceNumber(largest_seqno),
+    })
+}
+
+fn user_key(internal_key: &[u8]) -> Result<Vec<u8>, String> {
+    if internal_key.len() < INTERNAL_KEY_FOOTER_SIZE {
+        return Err("internal key too small".into());
+    }
+    Ok(internal_key[..internal_key.len() - INTERNAL_KEY_FOOTER_SIZE].to_vec())
+}
+
+fn get_varint64(input: &mut &[u8]) -> Result<u64, String> {
+    let mut result = 0;
+    for (i, &b) in input.iter().enumerate().take(10) {
+        result |= ((b & 0x7f) as u64) << (7 * i);
+        if b & 0x80 == 0 {
+            *input = &input[i + 1..];
+            return Ok(result);
+        }
+    }
+    Err("bad varint".into())
+}
+
+fn get_varint32(input: &mut &[u8]) -> Result<u32, String> {
+    let v = get_varint64(input)?;
+    if v > u32::MAX as u64 {
+        return Err("bad varint32".into());
+    }
+    Ok(v as u32)
+}
+
+fn get_length_prefixed<'a>(input: &mut &'a [u8]) -> Result<&'a [u8], String> {
+    let len = get_varint32(input)? as usize;
+    if input.len() < len {
+        return Err("truncated field".into());
+    }
+    let (field, rest) = input.split_at(len);
+    *input = rest;
+    Ok(field)
+}
+
+/// A write batch in a WAL file.
+#[derive(Debug, PartialEq, Eq)]
+pub struct WalRecord {
+    /// Offset of the record in the file.
+    pub offset: u64,
+    pub sequence: SequenceNumber,
+    pub count: u32,
+    pub entries: Vec<WriteBatchEntry>,
+}
+
+/// Content of a WAL file.
+#[derive(Debug, PartialEq, Eq)]
+pub struct WalDump {
+    pub path: PathBuf,
+    /// Intact write batches before the first problem.
+    pub records: Vec<WalRecord>,
+    /// First problem found, `None` if the file is clean.
+    pub problem: Option<WalProblem>,
+}
+
+/// Dump the write batches of a WAL file.
+pub fn dump_wal<P: AsRef<Path>>(path: P) -> io::Result<WalDump> {
+    let path = path.as_ref();
+    let mut data = Vec::new();
+    File::open(path)?.read_to_end(&mut data)?;
+    let log_number = path
+        .file_name()
+        .and_then(|name| name.to_str())
+        .and_then(|name| name.strip_suffix(".log"))
+        .and_then(|num| num.parse().ok());
+
+    let mut records = Vec::new();
+    let problem = read_log_records(&data, log_number, |start, _, record| {
+        if record.len() < WRITE_BATCH_HEADER_SIZE {
+            return Err("write batch too small".into());
+        }
+        let batch = WriteBatch::from_data(record);
+        let mut handler = WriteBatchIteratorHandler::default();
+        batch.iterate(&mut handler).map_err(|e| e.to_string())?;
+        let mut seq = [0; 8];
+        seq.copy_from_slice(&record[..8]);
+        records.push(WalRecord {
+            offset: start as u64,
+            sequence: SequenceNumber(u64::from_le_bytes(seq)),
+            count: batch.count() as u32,
+            entries: handler.entries,
+        });
+        Ok(())
+    });
+    Ok(WalDump {
+        path: path.to_owned(),
+        records,
+        problem,
+    })
+}
+
+/// A problem found by `check_consistency()`.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum ConsistencyIssue {
+    /// The MANIFEST itself is corrupted.
+    Manifest(WalProblem),
+    /// A live SST file does not exist.
+    MissingFile { column_family: String, path: PathBuf },
+    /// A live SST file has a size other than the recorded one.
+    SizeMismatch {
+        column_family: String,
+        path: PathBuf,
+        expected: u64,
+        actual: u64,
+    },
+}
+
+/// Check that every live SST file recorded in the current MANIFEST of the DB at `path`
+/// exists and has the recorded size. An empty result means consistent.
+///
+/// `db_paths` are the `DBOptions::db_paths` of the DB, if any, for files with a non-zero path ID.
+pub fn check_consistency<P: AsRef<Path>>(path: P, db_paths: &[PathBuf]) -> io::Result<Vec<ConsistencyIssue>> {
+    let path = path.as_ref();
+    let dump = manifest_dump(path)?;
+    let mut issues = Vec::new();
+    for cf in &dump.column_families {
+        for file in &cf.files {
+            let dir = match file.path_id {
+                0 if db_paths.is_empty() => path,
+                id => db_paths.get(id as usize).map(|p| p.as_path()).unwrap_or(path),
+            };
+            let file_path = dir.join(file.file_name());
+            match fs::metadata(&file_path) {
+                Ok(meta) if meta.len() != file.file_size => issues.push(ConsistencyIssue::SizeMismatch {
+                    column_family: cf.name.clone(),
+                    path: file_path,
+                    expected: file.file_size,
+                    actual: meta.len(),
+                }),
+                Ok(_) => {},
+                Err(ref e) if e.kind() == io::ErrorKind::NotFound => issues.push(ConsistencyIssue::MissingFile {
+                    column_family: cf.name.clone(),
+                    path: file_path,
+                }),
+                Err(e) => return Err(e),
+            }
+        }
+    }
+    if let Some(problem) = dump.problem {
+        issues.push(ConsistencyIssue::Manifest(problem));
+    }
+    Ok(issues)
+}
+
+/// An option whose desired value differs from the persisted one.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct OptionChange {
+    /// Nested options, `name={...}` in options strings, are prefixed by their parent and a dot,
+    /// e.g. `compaction_options_fifo.max_table_files_size`.
+    pub name: String,
+    /// `None` if the option is not persisted.
+    pub persisted: Option<String>,
+    /// `None` if the option is not in the desired options.
+    pub desired: Option<String>,
+    /// The DB fails to open, or existing data is misread, with the desired value.
+    pub requires_migration: bool,
+}
+
+/// How a column family differs, see `ReopenPlan`.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum ColumnFamilyPlan {
+    /// Persisted but not desired. The DB fails to open without all of its column
+    /// families, dropping one is a migration.
+    Missing { name: String },
+    /// Desired but not persisted, created on open with `DBOptions::create_missing_column_families()`.
+    New { name: String },
+    /// Both persisted and desired, with different options.
+    Changed { name: String, changes: Vec<OptionChange> },
+}
+
+impl ColumnFamilyPlan {
+    pub fn name(&self) -> &str {
+        match *self {
+            ColumnFamilyPlan::Missing { ref name }
+            | ColumnFamilyPlan::New { ref name }
+            | ColumnFamilyPlan::Changed { ref name, .. } => name,
+        }
+    }
+
+    pub fn requires_migration(&self) -> bool {
+        match *self {
+            ColumnFamilyPlan::Missing { .. } => true,
+            ColumnFamilyPlan::New { .. } => false,
+            ColumnFamilyPlan::Changed { ref changes, .. } => changes.iter().any(|c| c.requires_migration),
+        }
+    }
+}
+
+/// Differences between persisted and desired options, returned by `plan_reopen()`.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct ReopenPlan {
+    pub db_options: Vec<OptionChange>,
+    /// Unchanged column families are left out.
+    pub column_families: Vec<ColumnFamilyPlan>,
+}
+
+impl ReopenPlan {
+    /// Whether the desired options are the persisted ones.
+    pub fn is_unchanged(&self) -> bool {
+        self.db_options.is_empty() && self.column_families.is_empty()
+    }
+
+    /// Whether any change fails the open or misreads existing data.
+    pub fn requires_migration(&self) -> bool {
+        self.db_options.iter().any(|c| c.requires_migration)
+            || self.column_families.iter().any(|cf| cf.requires_migration())
+    }
+}
+
+/// Compare the latest options persisted by the DB at `path` to the desired ones.
+///
+/// Options are compared by their options strings, see `ColumnFamilyOptions::to_options_string()`,
+/// so only serializable options are compared, and pointer typed ones by name. Table options,
+/// e.g. the block size, are not part of them, only `table_factory` is compared.
+///
+/// RocksDB persists options after replacing some defaults on open, e.g. an empty `wal_dir` by
+/// the DB dir. Those options are skipped while left at their default.
+pub fn plan_reopen<P: AsRef<Path>>(
+    path: P,
+    db_options: &DBOptions,
+    column_families: &[ColumnFamilyDescriptor],
+) -> crate::Result<ReopenPlan> {
+    let (persisted_db, persisted_cfs) = load_latest_options(&path.as_ref().to_string_lossy())?;
+    let mut plan = ReopenPlan {
+        db_options: diff_options(
+            &db_options_string(&persisted_db)?,
+            &db_options_string(db_options)?,
+        ),
+        column_families: Vec::new(),
+    };
+    for persisted in &persisted_cfs {
+        match column_families.iter().find(|cf| cf.name() == persisted.name()) {
+            None => plan.column_families.push(ColumnFamilyPlan::Missing {
+                name: persisted.name().to_owned(),
+            }),
+            Some(desired) => {
+                let changes = diff_options(
+                    &cf_options_string(persisted.options())?,
+                    &cf_options_string(desired.options())?,
+                );
+                if !changes.is_empty() {
+                    plan.column_families.push(ColumnFamilyPlan::Changed {
+                        name: persisted.name().to_owned(),
+                        changes,
+                    });
+                }
+            },
+        }
+    }
+    for desired in column_families {
+        if !persisted_cfs.iter().any(|cf| cf.name() == desired.name()) {
+            plan.column_families.push(ColumnFamilyPlan::New {
+                name: desired.name().to_owned(),
+            });
+        }
+    }
+    Ok(plan)
+}
+
+fn db_options_string(options: &DBOptions) -> crate::Result<String> {
+    options
+        .to_options_string()
+        .ok_or_else(|| Error::invalid_argument("DB options can not be serialized"))
+}
+
+fn cf_options_string(options: &ColumnFamilyOptions) -> crate::Result<String> {
+    options
+        .to_options_string()
+        .ok_or_else(|| Error::invalid_argument("column family options can not be serialized"))
+}
+
+// options replaced on open while at these values, the persisted value is the replacement
+const SANITIZED_OPTIONS: &[(&str, &str)] = &[
+    ("wal_dir", ""),
+    ("delayed_write_rate", "0"),
+    ("bytes_per_sync", "0"),
+    ("compaction_readahead_size", "0"),
+    ("arena_block_size", "0"),
+    ("max_compaction_bytes", "0"),
+    ("periodic_compaction_seconds", "18446744073709551614"),
+    ("ttl", "18446744073709551614"),
+];
+
+fn diff_options(persisted: &str, desired: &str) -> Vec<OptionChange> {
+    let mut persisted_map = BTreeMap::new();
+    parse_options_string(persisted, "", &mut persisted_map);
+    let mut desired_map = BTreeMap::new();
+    parse_options_string(desired, "", &mut desired_map);
+
+    let names: BTreeSet<&String> = persisted_map.keys().chain(desired_map.keys()).collect();
+    names
+        .into_iter()
+        .filter_map(|name| {
+            let persisted = persisted_map.get(name);
+            let desired = desired_map.get(name);
+            if persisted == desired {
+                return None;
+            }
+            if matches!(desired, Some(d) if SANITIZED_OPTIONS.contains(&(&name[..], &d[..]))) {
+                return None;
+            }
+            Some(OptionChange {
+                name: name.clone(),
+                persisted: persisted.cloned(),
+                desired: desired.cloned(),
+                requires_migration: requires_migration(name, persisted, desired),
+            })
+        })
+        .collect()
+}
+
+fn requires_migration(name: &str, persisted: Option<&String>, desired: Option<&String>) -> bool {
+    match name {
+        // the open fails on a comparator mismatch, existing merge operands and SST files
+        // are read by the new merge operator and table factory
+        "comparator" | "merge_operator" | "table_factory" => true,
+        // files on levels beyond `num_levels` fail the open
+        "num_levels" => match (
+            persisted.and_then(|v| v.parse::<i32>().ok()),
+            desired.and_then(|v| v.parse::<i32>().ok()),
+        ) {
+            (Some(persisted), Some(desired)) => desired < persisted,
+            _ => false,
+        },
+        _ => false,
+    }
+}
+
+/// Parse `name=value;name={nested=value;...};...` into a flat map.
+fn parse_options_string(s: &str, prefix: &str, out: &mut BTreeMap<String, String>) {
+    let mut rest = s.trim_start();
+    while let Some(eq) = rest.find('=') {
+        let name = format!("{}{}", prefix, rest[..eq].trim().trim_start_matches(';'));
+        let value = rest[eq + 1..].trim_start();
+        if value.starts_with('{') {
+            let mut depth = 0;
+            let mut end = value.len();
+            for (i, c) in value.char_indices() {
+                match c {
+                    '{' => depth += 1,
+                    '}' => {
+                        depth -= 1;
+                        if depth == 0 {
+                            end = i;
+                            break;
+                        }
+                    },
+                    _ => {},
+                }
+            }
+            parse_options_string(&value[1..end], &format!("{}.", name), out);
+            rest = value.get(end + 1..).unwrap_or("");
+        } else {
+            let end = value.find(';').unwrap_or(value.len());
+            out.insert(name, value[..end].trim().to_owned());
+            rest = value.get(end + 1..).unwrap_or("");
+        }
+        rest = rest.trim_start_matches(|c: char| c == ';' || c.is_whitespace());
+    }
+}
+
+// assumed when the sector size cannot be probed
+const DEFAULT_SECTOR_SIZE: usize = 4096;
+const DIRECT_IO_COMPACTION_READAHEAD: usize = 2 << 20;
+const DIRECT_IO_PROBE_FILE: &str = "DIRECT_IO_PROBE";
+
+/// A direct IO misconfiguration found, and fixed, by `tune_direct_io()`.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct DirectIoWarning {
+    /// The option changed, as named in options strings.
+    pub option: &'static str,
+    pub message: String,
+}
+
+/// Check the options direct IO is sensitive to, for a DB at `path`.
+///
+/// Nothing is changed unless `use_direct_reads` or `use_direct_io_for_flush_and_compaction`
+/// is set. Otherwise the sector size of the file system at `path`, an existing directory, is
+/// probed by `Env::get_direct_io_alignment()`, and the returned options have:
+///
+/// - direct IO turned off, if the file system does not support it, i.e. the probe fails with
+///   `NotSupported` or `EINVAL`. Other probe errors are returned.
+/// - mmap reads and writes turned off, the DB fails to open with both mmap and direct IO
+/// - `compaction_readahead_size` of 2MB if unset with only `use_direct_io_for_flush_and_compaction`,
+///   compaction inputs are read a block at a time without it. RocksDB sets the same on open
+///   with `use_direct_reads`.
+/// - `compaction_readahead_size`, `writable_file_max_buffer_size` and `random_access_max_buffer_size`
+///   rounded up to a multiple of the sector size
+///
+/// with a warning for each change.
+pub fn tune_direct_io<P: AsRef<Path>>(
+    mut options: DBOptions,
+    env: &Env,
+    path: P,
+) -> crate::Result<(DBOptions, Vec<DirectIoWarning>)> {
+    let mut values = BTreeMap::new();
+    parse_options_string(&db_options_string(&options)?, "", &mut values);
+    let flag = |name: &str| values.get(name).map(|v| &v[..]) == Some("true");
+    let size = |name: &str| values.get(name).and_then(|v| v.parse::<usize>().ok()).unwrap_or(0);
+
+    let direct_reads = flag("use_direct_reads");
+    let direct_writes = flag("use_direct_io_for_flush_and_compaction");
+    let mut warnings = Vec::new();
+    if !direct_reads && !direct_writes {
+        return Ok((options, warnings));
+    }
+
+    let alignment = match env.get_direct_io_alignment(path.as_ref().join(DIRECT_IO_PROBE_FILE)) {
+        Ok(alignment) if alignment > 0 => alignment,
+        Ok(_) => DEFAULT_SECTOR_SIZE,
+        Err(e) if is_direct_io_unsupported(&e) => {
+            warnings.push(DirectIoWarning {
+                option: if direct_reads {
+                    "use_direct_reads"
+                } else {
+                    "use_direct_io_for_flush_and_compaction"
+                },
+                message: format!("direct IO is not supported at {}, turned off: {}", path.as_ref().display(), e),
+            });
+            let options = options.use_direct_reads(false).use_direct_io_for_flush_and_compaction(false);
+            return Ok((options, warnings));
+        },
+        Err(e) => return Err(e),
+    };
+
+    if direct_reads && flag("allow_mmap_reads") {
+        options = options.allow_mmap_reads(false);
+        warnings.push(DirectIoWarning {
+            option: "allow_mmap_reads",
+            message: "mmap reads conflict with use_direct_reads, turned off".to_owned(),
+        });
+    }
+    if direct_writes && flag("allow_mmap_writes") {
+        options = options.allow_mmap_writes(false);
+        warnings.push(DirectIoWarning {
+            option: "allow_mmap_writes",
+            message: "mmap writes conflict with use_direct_io_for_flush_and_compaction, turned off".to_owned(),
+        });
+    }
+
+    let readahead = size("compaction_readahead_size");
+    if readahead == 0 && !direct_reads {
+        options = options.compaction_readahead_size(DIRECT_IO_COMPACTION_READAHEAD);
+        warnings.push(DirectIoWarning {
+            option: "compaction_readahead_size",
+            message: format!("unset with direct IO, set to {}", DIRECT_IO_COMPACTION_READAHEAD),
+        });
+    } else if readahead % alignment != 0 {
+        options = options.compaction_readahead_size(round_up(readahead, alignment));
+        warnings.push(misaligned("compaction_readahead_size", readahead, alignment));
+    }
+    let buffer = size("writable_file_max_buffer_size");
+    if buffer % alignment != 0 {
+        options = options.writable_file_max_buffer_size(round_up(buffer, alignment));
+        warnings.push(misaligned("writable_file_max_buffer_size", buffer, alignment));
+    }
+    let buffer = size("random_access_max_buffer_size");
+    if buffer % alignment != 0 {
+        options = options.random_access_max_buffer_size(round_up(buffer, alignment));
+        warnings.push(misaligned("random_access_max_buffer_size", buffer, alignment));
+    }
+    Ok((options, warnings))
+}
+
+// opening a file with O_DIRECT fails with EINVAL where direct IO is not supported
+fn is_direct_io_unsupported(e: &Error) -> bool {
+    e.is_not_supported() || (e.is_io_error() && e.state().contains("Invalid argument"))
+}
+
+fn round_up(val: usize, alignment: usize) -> usize {
+    val + (alignment - val % alignment) % alignment
+}
+
+fn misaligned(option: &'static str, val: usize, alignment: usize) -> DirectIoWarning {
+    DirectIoWarning {
+        option,
+        message: format!(
+            "{} is not a multiple of the sector size {}, rounded up to {}",
+            val,
+            alignment,
+            round_up(val, alignment)
+        ),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::super::rocksdb::*;
+    use super::*;
+
+    #[test]
+    fn admin_commands() {
+        let tmp_dir = ::tempdir::TempDir::new_in(".", "rocks").unwrap();
+        {
+            let db = DB::open(
+                Options::default().map_db_options(|db| db.create_if_missing(true)),
+                &tmp_dir,
+            )
+            .unwrap();
+            let cf = db.create_column_family(&ColumnFamilyOptions::default(), "users").unwrap();
+            assert!(db.put(WriteOptions::default_instance(), b"k1", b"v1").is_ok());
+            assert!(cf.put(WriteOptions::default_instance(), b"a", b"1").is_ok());
+            assert!(cf.put(WriteOptions::default_instance(), b"z", b"2").is_ok());
+            assert!(cf.flush(&FlushOptions::default().wait(true)).is_ok());
+            assert!(db.delete(WriteOptions::default_instance(), b"k1").is_ok());
+        }
+
+        let dump = manifest_dump(&tmp_dir).unwrap();
+        assert!(dump.problem.is_none(), "{:?}", dump);
+        assert!(dump.num_edits > 0);
+        assert!(dump.last_sequence.is_some());
+        let names: Vec<_> = dump.column_families.iter().map(|cf| &cf.name[..]).collect();
+        assert_eq!(names, vec!["default", "users"]);
+        let users = &dump.column_families[1];
+        assert_eq!(users.comparator.as_ref().unwrap(), "leveldb.BytewiseComparator");
+        assert_eq!(users.files.len(), 1);
+        assert_eq!(users.files[0].smallest_key, b"a");
+        assert_eq!(users.files[0].largest_key, b"z");
+
+        // flushing "users" switched to a new WAL
+        let wals: Vec<_> = fs::read_dir(&tmp_dir)
+            .unwrap()
+            .map(|entry| entry.unwrap().path())
+            .filter(|path| path.extension().map_or(false, |ext| ext == "log"))
+            .map(|path| dump_wal(path).unwrap())
+            .collect();
+        assert!(wals.iter().all(|wal| wal.problem.is_none()));
+        let entries: Vec<_> = wals
+            .iter()
+            .flat_map(|wal| wal.records.iter())
+            .flat_map(|r| r.entries.iter())
+            .collect();
+        assert!(entries.contains(&&WriteBatchEntry::Put {
+            column_family_id: 0,
+            key: b"k1".to_vec(),
+            value: b"v1".to_vec(),
+        }));
+        assert!(entries.contains(&&WriteBatchEntry::Delete {
+            column_family_id: 0,
+            key: b"k1".to_vec(),
+        }));
+
+        assert_eq!(check_consistency(&tmp_dir, &[]).unwrap(), vec![]);
+        let sst = tmp_dir.path().join(users.files[0].file_name());
+        fs::remove_file(&sst).unwrap();
+        assert_eq!(
+            check_consistency(&tmp_dir, &[]).unwrap(),
+            vec![ConsistencyIssue::MissingFile {
+                column_family: "users".into(),
+                path: sst,
+            }]
+        );
+    }
+
+    #[test]
+    fn version_edit_safe_ignore_tags() {
+        // kDbId, then the log number
+        let mut record = vec![0x81, 0x40, 5];
+        record.extend_from_slice(b"db-id");
+        record.extend_from_slice(&[TAG_LOG_NUMBER as u8, 7]);
+        let edit = decode_version_edit(&record).unwrap();
+        assert_eq!(edit.log_number, Some(7));
+
+        assert!(decode_version_edit(&[11, 0]).is_err());
+    }
+
+    #[test]
+    fn reopen_plan() {
+        let tmp_dir = ::tempdir::TempDir::new_in(".", "rocks").unwrap();
+        {
+            let db = DB::open(
+                Options::default().map_db_options(|db| db.create_if_missing(true)),
+                &tmp_dir,
+            )
+            .unwrap();
+            let _ = db
+                .create_column_family(&ColumnFamilyOptions::default().write_buffer_size(8 << 20), "users")
+                .unwrap();
+            let _ = db.create_column_family(&ColumnFamilyOptions::default(), "logs").unwrap();
+        }
+
+        let db_options = DBOptions::default().create_if_missing(true);
+        let same = vec![
+            ColumnFamilyDescriptor::new("default", ColumnFamilyOptions::default()),
+            ColumnFamilyDescriptor::new("users", ColumnFamilyOptions::default().write_buffer_size(8 << 20)),
+            ColumnFamilyDescriptor::new("logs", ColumnFamilyOptions::default()),
+        ];
+        let plan = plan_reopen(&tmp_dir, &db_options, &same).unwrap();
+        assert!(plan.column_families.is_empty(), "{:?}", plan);
+        assert!(plan.db_options.is_empty(), "{:?}", plan);
+        assert!(plan.is_unchanged());
+        assert!(!plan.requires_migration());
+
+        let more_jobs = DBOptions::default().create_if_missing(true).max_background_jobs(5);
+        let plan = plan_reopen(&tmp_dir, &more_jobs, &same).unwrap();
+        assert!(plan.column_families.is_empty(), "{:?}", plan);
+        assert_eq!(
+            plan.db_options,
+            vec![OptionChange {
+                name: "max_background_jobs".into(),
+                persisted: Some("2".into()),
+                desired: Some("5".into()),
+                requires_migration: false,
+            }]
+        );
+        assert!(!plan.requires_migration());
+
+        let desired = vec![
+            ColumnFamilyDescriptor::new("default", ColumnFamilyOptions::default().num_levels(3)),
+            ColumnFamilyDescriptor::new("users", ColumnFamilyOptions::default().write_buffer_size(16 << 20)),
+            ColumnFamilyDescriptor::new("events", ColumnFamilyOptions::default()),
+        ];
+        let plan = plan_reopen(&tmp_dir, &db_options, &desired).unwrap();
+        assert!(plan.requires_migration());
+        let names: Vec<_> = plan.column_families.iter().map(|cf| cf.name()).collect();
+        assert_eq!(names, vec!["default", "users", "logs", "events"]);
+        assert_eq!(
+            plan.column_families[0],
+            ColumnFamilyPlan::Changed {
+                name: "default".into(),
+                changes: vec![OptionChange {
+                    name: "num_levels".into(),
+                    persisted: Some("7".into()),
+                    desired: Some("3".into()),
+                    requires_migration: true,
+                }],
+            }
+        );
+        assert!(!plan.column_families[1].requires_migration());
+        assert!(plan.column_families[2].requires_migration());
+        assert!(!plan.column_families[3].requires_migration());
+    }
+
+    #[test]
+    fn parse_nested_options_string() {
+        let mut map = BTreeMap::new();
+        parse_options_string(
+            "write_buffer_size=1024;table={block_size=4096;cache={capacity=8};};comparator=a.b;",
+            "",
+            &mut map,
+        );
+        let pairs: Vec<_> = map.iter().map(|(k, v)| (&k[..], &v[..])).collect();
+        assert_eq!(
+            pairs,
+            vec![
+                ("comparator", "a.b"),
+                ("table.block_size", "4096"),
+                ("table.cache.capacity", "8"),
+                ("write_buffer_size", "1024"),
+            ]
+        );
+    }
+
+    #[test]
+    fn direct_io_tuning() {
+        let tmp_dir = ::tempdir::TempDir::new_in(".", "rocks").unwrap();
+        let env = Env::default_instance();
+
+        let (_, warnings) = tune_direct_io(DBOptions::default().allow_mmap_reads(true), env, &tmp_dir).unwrap();
+        assert!(warnings.is_empty());
+
+        let options = DBOptions::default()
+            .use_direct_reads(true)
+            .allow_mmap_reads(true)
+            .compaction_readahead_size(0)
+            .writable_file_max_buffer_size(1000);
+        let (options, warnings) = tune_direct_io(options, env, &tmp_dir).unwrap();
+        let mut values = BTreeMap::new();
+        parse_options_string(&options.to_options_string().unwrap(), "", &mut values);
+        let names: Vec<_> = warnings.iter().map(|w| w.option).collect();
+        match env.get_direct_io_alignment(tmp_dir.path().join(DIRECT_IO_PROBE_FILE)) {
+            // e.g. tmpfs
+            Err(_) => {
+                assert_eq!(names, vec!["use_direct_reads"]);
+                assert_eq!(values["use_direct_reads"], "false");
+            },
+            Ok(alignment) => {
+                // the readahead is set by RocksDB on open with direct reads
+                assert_eq!(
+                    names,
+                    vec!["allow_mmap_reads", "writable_file_max_buffer_size"],
+                    "{:?}",
+                    warnings
+                );
+                assert_eq!(values["allow_mmap_reads"], "false");
+                assert_eq!(values["compaction_readahead_size"], "0");
+                let buffer = values["writable_file_max_buffer_size"].parse::<usize>().unwrap();
+                assert!(buffer >= 1000 && buffer % alignment == 0);
+            },
+        }
+        assert!(!tmp_dir.path().join(DIRECT_IO_PROBE_FILE).exists());
+    }
+
+    #[test]
+    fn direct_io_tuning_readahead() {
+        let tmp_dir = ::tempdir::TempDir::new_in(".", "rocks").unwrap();
+        let env = Env::default_instance();
+        if env.get_direct_io_alignment(tmp_dir.path().join(DIRECT_IO_PROBE_FILE)).is_err() {
+            return;
+        }
+
+        let options = DBOptions::default()
+            .use_direct_io_for_flush_and_compaction(true)
+            .compaction_readahead_size(0);
+        let (options, warnings) = tune_direct_io(options, env, &tmp_dir).unwrap();
+        let names: Vec<_> = warnings.iter().map(|w| w.option).collect();
+        assert_eq!(names, vec!["compaction_readahead_size"]);
+        let mut values = BTreeMap::new();
+        parse_options_string(&options.to_options_string().unwrap(), "", &mut values);
+        assert_eq!(values["compaction_readahead_size"], "2097152");
+    }
+}
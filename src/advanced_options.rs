@@ -72,6 +72,8 @@ impl CompactionOptionsFIFO {
     /// table file
     ///
     /// Default: 1GB
+    ///
+    /// To expire files by age, use `ColumnFamilyOptions::ttl` instead.
     pub fn max_table_files_size(self, val: u64) -> Self {
         unsafe {
             ll::rocks_fifo_compaction_options_set_max_table_files_size(self.raw, val);
@@ -0,0 +1,316 @@
+//! Group commit of small writes from many threads.
+//!
+//! `BatchedWriter` collects puts, deletes and merges into a single `WriteBatch`,
+//! which a background thread writes once `GroupCommitOptions::max_delay()` has
+//! passed since the first pending write, or once it reaches
+//! `GroupCommitOptions::max_bytes()`. With `sync`, one WAL fsync covers the
+//! whole group instead of every write.
+//!
+//! Each write returns a `Completion`, which resolves once its group is written.
+//! A group is written atomically, so all writes of a failed group fail with
+//! the same error.
+
+use std::fmt;
+use std::mem;
+use std::sync::mpsc::{self, Receiver, RecvTimeoutError, SyncSender};
+use std::sync::{Arc, Condvar, Mutex};
+use std::thread::{self, JoinHandle};
+use std::time::{Duration, Instant};
+
+use crate::db::{ColumnFamilyHandle, DBRef, DB};
+use crate::options::WriteOptions;
+use crate::write_batch::WriteBatch;
+use crate::{Error, Result};
+
+/// When and how `BatchedWriter` writes a group.
+#[derive(Debug, Clone)]
+pub struct GroupCommitOptions {
+    max_delay: Duration,
+    max_bytes: usize,
+    sync: bool,
+    disable_wal: bool,
+}
+
+impl Default for GroupCommitOptions {
+    fn default() -> Self {
+        GroupCommitOptions {
+            max_delay: Duration::from_millis(2),
+            max_bytes: 1 << 20,
+            sync: false,
+            disable_wal: false,
+        }
+    }
+}
+
+impl GroupCommitOptions {
+    /// How long the first write of a group waits for more.
+    ///
+    /// Default: 2ms
+    pub fn max_delay(mut self, val: Duration) -> Self {
+        self.max_delay = val;
+        self
+    }
+
+    /// Write a group right away once its batch reaches this size in bytes.
+    ///
+    /// Default: 1MB
+    pub fn max_bytes(mut self, val: usize) -> Self {
+        self.max_bytes = val;
+        self
+    }
+
+    /// `WriteOptions::sync()` of group writes.
+    ///
+    /// Default: false
+    pub fn sync(mut self, val: bool) -> Self {
+        self.sync = val;
+        self
+    }
+
+    /// `WriteOptions::disable_wal()` of group writes.
+    ///
+    /// Default: false
+    pub fn disable_wal(mut self, val: bool) -> Self {
+        self.disable_wal = val;
+        self
+    }
+}
+
+/// Completion of a write to a `BatchedWriter`.
+#[derive(Debug)]
+pub struct Completion {
+    rx: Receiver<Result<()>>,
+}
+
+impl Completion {
+    /// Block until the group of this write is written.
+    pub fn wait(self) -> Result<()> {
+        self.rx
+            .recv()
+            .unwrap_or_else(|_| Err(Error::aborted("batched writer: writer thread is gone")))
+    }
+
+    /// Like `wait()`, giving up with a `TimedOut` error after `timeout`.
+    pub fn wait_timeout(&self, timeout: Duration) -> Result<()> {
+        match self.rx.recv_timeout(timeout) {
+            Ok(ret) => ret,
+            Err(RecvTimeoutError::Timeout) => Err(Error::timed_out("batched writer: write not completed")),
+            Err(RecvTimeoutError::Disconnected) => Err(Error::aborted("batched writer: writer thread is gone")),
+        }
+    }
+
+    /// The channel the result is sent to, e.g. to select over many completions.
+    pub fn into_receiver(self) -> Receiver<Result<()>> {
+        self.rx
+    }
+}
+
+struct Pending {
+    batch: WriteBatch,
+    waiters: Vec<SyncSender<Result<()>>>,
+    first_write: Option<Instant>,
+    stopped: bool,
+}
+
+struct Shared {
+    pending: Mutex<Pending>,
+    cvar: Condvar,
+}
+
+/// Collects writes from many threads into group writes, see the module document.
+///
+/// Dropping the writer writes pending writes and stops the thread.
+pub struct BatchedWriter {
+    shared: Arc<Shared>,
+    max_bytes: usize,
+    thread: Option<JoinHandle<()>>,
+}
+
+impl fmt::Debug for BatchedWriter {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let pending = self.shared.pending.lock().unwrap();
+        f.debug_struct("BatchedWriter")
+            .field("pending_writes", &pending.waiters.len())
+            .field("pending_bytes", &pending.batch.get_data_size())
+            .finish()
+    }
+}
+
+impl BatchedWriter {
+    /// Start the writer thread of `db`. The thread keeps the DB alive until the writer is dropped.
+    pub fn new(db: &DB, options: GroupCommitOptions) -> BatchedWriter {
+        let shared = Arc::new(Shared {
+            pending: Mutex::new(Pending {
+                batch: WriteBatch::new(),
+                waiters: Vec::new(),
+                first_write: None,
+                stopped: false,
+            }),
+            cvar: Condvar::new(),
+        });
+        let db = db.shared();
+        let max_bytes = options.max_bytes;
+        let thread_shared = shared.clone();
+        let thread = thread::Builder::new()
+            .name("rocks-group-commit".into())
+            .spawn(move || write_groups(&db, &thread_shared, &options))
+            .expect("spawn group commit thread");
+        BatchedWriter {
+            shared,
+            max_bytes,
+            thread: Some(thread),
+        }
+    }
+
+    pub fn put(&self, key: &[u8], value: &[u8]) -> Completion {
+        self.add(|batch| batch.put(key, value))
+    }
+
+    pub fn put_cf(&self, column_family: &ColumnFamilyHandle, key: &[u8], value: &[u8]) -> Completion {
+        self.add(|batch| batch.put_cf(column_family, key, value))
+    }
+
+    pub fn delete(&self, key: &[u8]) -> Completion {
+        self.add(|batch| batch.delete(key))
+    }
+
+    pub fn delete_cf(&self, column_family: &ColumnFamilyHandle, key: &[u8]) -> Completion {
+        self.add(|batch| batch.delete_cf(column_family, key))
+    }
+
+    pub fn merge(&self, key: &[u8], value: &[u8]) -> Completion {
+        self.add(|batch| batch.merge(key, value))
+    }
+
+    pub fn merge_cf(&self, column_family: &ColumnFamilyHandle, key: &[u8], value: &[u8]) -> Completion {
+        self.add(|batch| batch.merge_cf(column_family, key, value))
+    }
+
+    /// Write pending writes now, and stop the thread.
+    pub fn stop(mut self) {
+        self.stop_and_join();
+    }
+
+    fn add<F: FnOnce(&mut WriteBatch) -> &mut WriteBatch>(&self, f: F) -> Completion {
+        // room for the result, so that the writer thread never blocks on a completion
+        let (tx, rx) = mpsc::sync_channel(1);
+        let mut pending = self.shared.pending.lock().unwrap();
+        f(&mut pending.batch);
+        pending.waiters.push(tx);
+        if pending.first_write.is_none() {
+            pending.first_write = Some(Instant::now());
+            self.shared.cvar.notify_all();
+        } else if pending.batch.get_data_size() >= self.max_bytes {
+            self.shared.cvar.notify_all();
+        }
+        Completion { rx }
+    }
+
+    fn stop_and_join(&mut self) {
+        self.shared.pending.lock().unwrap().stopped = true;
+        self.shared.cvar.notify_all();
+        if let Some(thread) = self.thread.take() {
+            let _ = thread.join();
+        }
+    }
+}
+
+impl Drop for BatchedWriter {
+    fn drop(&mut self) {
+        self.stop_and_join();
+    }
+}
+
+fn write_groups(db: &DBRef, shared: &Shared, options: &GroupCommitOptions) {
+    let write_options = WriteOptions::default()
+        .sync(options.sync)
+        .disable_wal(options.disable_wal);
+    loop {
+        let (batch, waiters) = {
+            let mut pending = shared.pending.lock().unwrap();
+            while pending.first_write.is_none() && !pending.stopped {
+                pending = shared.cvar.wait(pending).unwrap();
+            }
+            let first_write = match pending.first_write {
+                Some(first_write) => first_write,
+                // stopped, and nothing pending
+                None => return,
+            };
+            let deadline = first_write + options.max_delay;
+            while !pending.stopped && pending.batch.get_data_size() < options.max_bytes {
+                let now = Instant::now();
+                if now >= deadline {
+                    break;
+                }
+                pending = shared.cvar.wait_timeout(pending, deadline - now).unwrap().0;
+            }
+            pending.first_write = None;
+            (mem::take(&mut pending.batch), mem::take(&mut pending.waiters))
+        };
+        let ret = db.write(&write_options, &batch);
+        for waiter in waiters {
+            let _ = waiter.send(ret.clone());
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::rocksdb::*;
+
+    #[test]
+    fn batched_writer() {
+        let tmp_dir = ::tempdir::TempDir::new_in(".", "rocks").unwrap();
+        let db = DB::open(
+            Options::default().map_db_options(|db| db.create_if_missing(true)),
+            &tmp_dir,
+        )
+        .unwrap();
+        let cf = db.create_column_family(&ColumnFamilyOptions::default(), "batched").unwrap();
+        let seq = db.get_latest_sequence_number();
+
+        let writer = Arc::new(BatchedWriter::new(
+            &db,
+            GroupCommitOptions::default()
+                .max_delay(Duration::from_millis(50))
+                .sync(true),
+        ));
+        let threads: Vec<_> = (0..8)
+            .map(|i| {
+                let writer = writer.clone();
+                thread::spawn(move || {
+                    let key = format!("k{}", i);
+                    writer.put(key.as_bytes(), b"v").wait()
+                })
+            })
+            .collect();
+        for t in threads {
+            assert!(t.join().unwrap().is_ok());
+        }
+        assert!(writer.put_cf(&cf, b"cf", b"v").wait().is_ok());
+        for i in 0..8 {
+            let key = format!("k{}", i);
+            assert_eq!(db.get(&ReadOptions::default(), key.as_bytes()).unwrap(), b"v".as_ref());
+        }
+        assert_eq!(db.get_latest_sequence_number().0, seq.0 + 9);
+
+        // a full batch is written without waiting for max_delay
+        let writer = BatchedWriter::new(
+            &db,
+            GroupCommitOptions::default()
+                .max_delay(Duration::from_secs(60))
+                .max_bytes(64),
+        );
+        let first = writer.delete(b"k0");
+        assert!(first.wait_timeout(Duration::from_millis(100)).unwrap_err().is_timed_out());
+        assert!(writer.put(b"k1", &[0; 64]).wait().is_ok());
+        assert!(first.wait_timeout(Duration::from_secs(5)).is_ok());
+
+        // pending writes are written on stop
+        let pending = writer.delete(b"k1");
+        writer.stop();
+        assert!(pending.wait().is_ok());
+        assert!(db.get_opt(&ReadOptions::default(), b"k1").unwrap().is_none());
+    }
+}
@@ -20,16 +20,29 @@ pub enum Priority {
     Low,
 }
 
-// TODO: impl Copy for inner shared_ptr
-
 /// A builtin cache implementation with a least-recently-used eviction
 /// policy is provided.  Clients may use their own implementations if
 /// they want something more sophisticated (like scan-resistance, a
 /// custom eviction policy, variable cache sizing, etc.)
+///
+/// Rust: `clone()` shares the same underlying cache, keep a clone to resize
+/// a cache after handing it to options.
 pub struct Cache {
     raw: *mut ll::rocks_cache_t,
 }
 
+// Cache has internal synchronization
+unsafe impl Send for Cache {}
+unsafe impl Sync for Cache {}
+
+impl Clone for Cache {
+    fn clone(&self) -> Self {
+        Cache {
+            raw: unsafe { ll::rocks_cache_clone(self.raw) },
+        }
+    }
+}
+
 impl ToRaw<ll::rocks_cache_t> for Cache {
     fn raw(&self) -> *mut ll::rocks_cache_t {
         self.raw
@@ -49,7 +62,7 @@ impl Cache {
     /// capacity is less than the old capacity and the existing usage is
     /// greater than new capacity, the implementation will do its best job to
     /// purge the released entries from the cache in order to lower the usage
-    pub fn set_capacity(&mut self, capacity: usize) {
+    pub fn set_capacity(&self, capacity: usize) {
         unsafe {
             ll::rocks_cache_set_capacity(self.raw, capacity);
         }
@@ -60,10 +73,34 @@ impl Cache {
         unsafe { ll::rocks_cache_get_capacity(self.raw) }
     }
 
-    /// returns the memory size for a specific entry in the cache.
+    /// returns the memory size for the entries residing in the cache.
     pub fn get_usage(&self) -> usize {
         unsafe { ll::rocks_cache_get_usage(self.raw) }
     }
+
+    /// returns the memory size for the entries in use by the system
+    pub fn get_pinned_usage(&self) -> usize {
+        unsafe { ll::rocks_cache_get_pinned_usage(self.raw) }
+    }
+
+    /// The ratio of capacity reserved for high priority entries, as configured
+    /// at creation. Always 0 for ClockCache.
+    pub fn get_high_pri_pool_ratio(&self) -> f64 {
+        unsafe { ll::rocks_cache_get_high_pri_pool_ratio(self.raw) }
+    }
+
+    /// sets strict_capacity_limit flag of the cache. If the flag is set
+    /// to true, insert to cache will fail if no enough capacity can be free.
+    pub fn set_strict_capacity_limit(&self, strict_capacity_limit: bool) {
+        unsafe {
+            ll::rocks_cache_set_strict_capacity_limit(self.raw, strict_capacity_limit as u8);
+        }
+    }
+
+    /// Get the flag whether to return error on insertion when cache reaches its full capacity.
+    pub fn has_strict_capacity_limit(&self) -> bool {
+        unsafe { ll::rocks_cache_has_strict_capacity_limit(self.raw) != 0 }
+    }
 }
 
 impl Drop for Cache {
@@ -173,7 +210,7 @@ mod tests {
 
     #[test]
     fn cache_lru() {
-        let mut lru_cache = CacheBuilder::new_lru(1024)
+        let lru_cache = CacheBuilder::new_lru(1024)
             .high_pri_pool_ratio(0.3)
             .build()
             .unwrap();
@@ -182,6 +219,52 @@ mod tests {
         lru_cache.set_capacity(512);
         assert_eq!(lru_cache.get_capacity(), 512);
         assert!(lru_cache.get_usage() == 0);
+        assert_eq!(lru_cache.get_pinned_usage(), 0);
+        assert_eq!(lru_cache.get_high_pri_pool_ratio(), 0.3);
+
+        let shared = lru_cache.clone();
+        shared.set_capacity(2048);
+        assert_eq!(lru_cache.get_capacity(), 2048);
+        assert!(!lru_cache.has_strict_capacity_limit());
+        shared.set_strict_capacity_limit(true);
+        assert!(lru_cache.has_strict_capacity_limit());
+    }
+
+    #[test]
+    fn cache_clock() {
+        // ClockCache is only available when built with TBB
+        if let Some(clock_cache) = CacheBuilder::new_clock(1024).build() {
+            assert_eq!(clock_cache.get_capacity(), 1024);
+            assert_eq!(clock_cache.get_high_pri_pool_ratio(), 0.0);
+        }
+    }
+
+    #[test]
+    fn resize_block_cache() {
+        let tmp_dir = ::tempdir::TempDir::new_in("", "rocks").unwrap();
+        let cache = CacheBuilder::new_lru(1 << 20).build().unwrap();
+        let db = DB::open(
+            Options::default()
+                .map_db_options(|db| db.create_if_missing(true))
+                .map_cf_options(|cf| {
+                    cf.table_factory_block_based(BlockBasedTableOptions::default().block_cache(Some(cache.clone())))
+                }),
+            &tmp_dir,
+        )
+        .unwrap();
+        for i in 0..100 {
+            let key = format!("k{}", i);
+            assert!(db.put(WriteOptions::default_instance(), key.as_bytes(), b"v").is_ok());
+        }
+        assert!(db.flush(&FlushOptions::default().wait(true)).is_ok());
+        assert!(db.get(ReadOptions::default_instance(), b"k1").is_ok());
+        assert!(cache.get_usage() > 0);
+
+        cache.set_capacity(1 << 10);
+        assert_eq!(
+            db.get_property(crate::properties::BLOCK_CACHE_CAPACITY),
+            Some((1 << 10).to_string())
+        );
     }
 
     #[test]
@@ -0,0 +1,319 @@
+//! Change data capture, a stream of committed writes read from the WAL.
+//!
+//! `ChangeFeed` reads write batches with `DBRef::get_updates_since()` and splits
+//! them into `Change`s, one per written key, tagged with the sequence number it
+//! was written at. Each poll reopens the log from the next sequence number, so
+//! the feed follows the WAL across rotations and flushes.
+//!
+//! WAL files must be kept until the feed has read them, see
+//! `DBOptions::wal_ttl_seconds()` and `DBOptions::wal_size_limit_mb()`. Writes
+//! with `WriteOptions::disable_wal(true)` are never seen by the feed, and leave
+//! a gap in sequence numbers, see `ChangeFeed::allow_gaps()`.
+//!
+//! To follow the current contents of a key range rather than the writes to it,
+//! use a tailing iterator, `DBRef::new_tailing_iterator()`.
+
+use std::fmt;
+use std::sync::mpsc::{self, Receiver};
+use std::sync::{Arc, Condvar, Mutex};
+use std::thread::{self, JoinHandle};
+use std::time::Duration;
+
+use crate::db::{DBRef, DB};
+use crate::transaction_log::BatchResult;
+use crate::types::SequenceNumber;
+use crate::write_batch::{WriteBatchEntry, WriteBatchIteratorHandler};
+use crate::{Error, Result};
+
+const ERROR_CAPACITY: usize = 16;
+
+/// A single committed write.
+#[derive(Debug, PartialEq, Eq)]
+pub struct Change {
+    pub sequence: SequenceNumber,
+    /// One of `Put`, `Delete`, `SingleDelete`, `DeleteRange` or `Merge`.
+    pub entry: WriteBatchEntry,
+}
+
+/// Reads committed writes of a DB from its WAL.
+pub struct ChangeFeed {
+    db: Arc<DBRef>,
+    next_sequence: SequenceNumber,
+    allow_gaps: bool,
+}
+
+impl fmt::Debug for ChangeFeed {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.debug_struct("ChangeFeed")
+            .field("next_sequence", &self.next_sequence)
+            .field("allow_gaps", &self.allow_gaps)
+            .finish()
+    }
+}
+
+impl ChangeFeed {
+    /// A feed of writes committed after now.
+    pub fn new(db: &DB) -> ChangeFeed {
+        ChangeFeed {
+            next_sequence: SequenceNumber(db.get_latest_sequence_number().0 + 1),
+            db: db.shared(),
+            allow_gaps: false,
+        }
+    }
+
+    /// Start from the write at `seq`, e.g. one past the last change a consumer
+    /// processed before a restart.
+    pub fn since(mut self, seq: SequenceNumber) -> Self {
+        self.next_sequence = seq;
+        self
+    }
+
+    /// Skip over sequence numbers missing from the WAL, i.e. writes without WAL and
+    /// purged log files.
+    ///
+    /// Default: false, a gap fails `poll()` with an `Aborted` error
+    pub fn allow_gaps(mut self, val: bool) -> Self {
+        self.allow_gaps = val;
+        self
+    }
+
+    /// The sequence number of the next change to be read.
+    pub fn next_sequence(&self) -> SequenceNumber {
+        self.next_sequence
+    }
+
+    /// Read all changes committed since the last poll.
+    pub fn poll(&mut self) -> Result<Vec<Change>> {
+        let mut changes = Vec::new();
+        self.poll_with(|change| changes.push(change))?;
+        Ok(changes)
+    }
+
+    /// Like `poll()`, passing changes to `f` as they are decoded.
+    ///
+    /// On error, changes passed so far are consumed, and the next poll resumes after them.
+    pub fn poll_with<F: FnMut(Change)>(&mut self, mut f: F) -> Result<()> {
+        loop {
+            let start = self.next_sequence;
+            match self.read_log(&mut f) {
+                // the log iterator fails at a gap between batches, a new one skips
+                // over it, or reports it, from the sequence number after the last change
+                Err(_) if self.next_sequence != start => continue,
+                ret => return ret,
+            }
+        }
+    }
+
+    fn read_log<F: FnMut(Change)>(&mut self, f: &mut F) -> Result<()> {
+        if self.next_sequence.0 > self.db.get_latest_sequence_number().0 {
+            return Ok(());
+        }
+        // a new log iterator every poll, the old one is invalid once it reaches
+        // the end of the log, and does not follow a rotated WAL
+        let mut iter = self.db.get_updates_since(self.next_sequence)?;
+        while let Some(BatchResult { sequence, write_batch }) = iter.try_next()? {
+            if sequence.0 > self.next_sequence.0 && !self.allow_gaps {
+                return Err(Error::aborted(&format!(
+                    "change feed: sequence {} is missing from the WAL, next available is {}",
+                    self.next_sequence, sequence
+                )));
+            }
+            let mut handler = WriteBatchIteratorHandler::default();
+            write_batch.iterate(&mut handler)?;
+            // log data and 2PC markers do not consume sequence numbers
+            let entries = handler.entries.into_iter().filter(|entry| {
+                matches!(
+                    entry,
+                    WriteBatchEntry::Put { .. }
+                        | WriteBatchEntry::Delete { .. }
+                        | WriteBatchEntry::SingleDelete { .. }
+                        | WriteBatchEntry::DeleteRange { .. }
+                        | WriteBatchEntry::Merge { .. }
+                )
+            });
+            for (i, entry) in entries.enumerate() {
+                let seq = sequence.0 + i as u64;
+                // a batch may start before the requested sequence number
+                if seq >= self.next_sequence.0 {
+                    self.next_sequence = SequenceNumber(seq + 1);
+                    f(Change {
+                        sequence: SequenceNumber(seq),
+                        entry,
+                    });
+                }
+            }
+        }
+        Ok(())
+    }
+
+    /// Poll every `interval` on a background thread, passing changes to `f`.
+    ///
+    /// Errors are reported through `ChangeFeedHandle::errors()`, polling goes on after
+    /// an error. The thread keeps the DB alive until the handle is stopped or dropped.
+    pub fn spawn<F>(mut self, interval: Duration, mut f: F) -> ChangeFeedHandle
+    where
+        F: FnMut(Change) + Send + 'static,
+    {
+        let stopped = Arc::new((Mutex::new(false), Condvar::new()));
+        let (tx, rx) = mpsc::sync_channel(ERROR_CAPACITY);
+        let stop = stopped.clone();
+        let thread = thread::Builder::new()
+            .name("rocks-change-feed".into())
+            .spawn(move || {
+                let (lock, cvar) = &*stop;
+                loop {
+                    if let Err(e) = self.poll_with(&mut f) {
+                        // drop errors nobody is reading
+                        let _ = tx.try_send(e);
+                    }
+                    let stopped = lock.lock().unwrap();
+                    let (stopped, _) = cvar.wait_timeout_while(stopped, interval, |s| !*s).unwrap();
+                    if *stopped {
+                        break;
+                    }
+                }
+                self.next_sequence
+            })
+            .expect("spawn change feed thread");
+        ChangeFeedHandle {
+            stopped,
+            thread: Some(thread),
+            errors: rx,
+        }
+    }
+
+    /// Poll every `interval` on a background thread, sending changes to the returned channel.
+    ///
+    /// Changes are dropped once the receiver is gone.
+    pub fn spawn_channel(self, interval: Duration) -> (ChangeFeedHandle, Receiver<Change>) {
+        let (tx, rx) = mpsc::channel();
+        let handle = self.spawn(interval, move |change| {
+            let _ = tx.send(change);
+        });
+        (handle, rx)
+    }
+}
+
+/// Background polling of a `ChangeFeed`, returned by `ChangeFeed::spawn()`.
+///
+/// Dropping the handle stops the thread.
+#[derive(Debug)]
+pub struct ChangeFeedHandle {
+    stopped: Arc<(Mutex<bool>, Condvar)>,
+    thread: Option<JoinHandle<SequenceNumber>>,
+    errors: Receiver<Error>,
+}
+
+impl ChangeFeedHandle {
+    /// Errors returned by polls. Up to 16 unread errors are buffered, later ones are dropped.
+    pub fn errors(&self) -> &Receiver<Error> {
+        &self.errors
+    }
+
+    /// Stop the thread, waiting for an in-progress poll to finish.
+    ///
+    /// Returns the sequence number of the next change, to resume from with `ChangeFeed::since()`,
+    /// or `None` if the thread panicked.
+    pub fn stop(mut self) -> Option<SequenceNumber> {
+        self.stop_and_join()
+    }
+
+    fn stop_and_join(&mut self) -> Option<SequenceNumber> {
+        let (lock, cvar) = &*self.stopped;
+        *lock.lock().unwrap() = true;
+        cvar.notify_all();
+        self.thread.take().and_then(|thread| thread.join().ok())
+    }
+}
+
+impl Drop for ChangeFeedHandle {
+    fn drop(&mut self) {
+        self.stop_and_join();
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::rocksdb::*;
+
+    #[test]
+    fn change_feed() {
+        let tmp_dir = ::tempdir::TempDir::new_in(".", "rocks").unwrap();
+        let db = DB::open(
+            Options::default().map_db_options(|db| db.create_if_missing(true).wal_ttl_seconds(1000)),
+            &tmp_dir,
+        )
+        .unwrap();
+        assert!(db.put(WriteOptions::default_instance(), b"before", b"feed").is_ok());
+
+        let mut feed = ChangeFeed::new(&db);
+        let start = feed.next_sequence();
+        assert!(feed.poll().unwrap().is_empty());
+
+        let mut batch = WriteBatch::new();
+        batch.put(b"k1", b"v1").put_log_data(b"blob").delete(b"k0");
+        assert!(db.write(WriteOptions::default_instance(), &batch).is_ok());
+        // rotate the WAL
+        assert!(db.flush(&FlushOptions::default().wait(true)).is_ok());
+        assert!(db.put(WriteOptions::default_instance(), b"k2", b"v2").is_ok());
+
+        let changes = feed.poll().unwrap();
+        assert_eq!(
+            changes.iter().map(|c| c.sequence.0 - start.0).collect::<Vec<_>>(),
+            vec![0, 1, 2]
+        );
+        assert_eq!(
+            changes[1].entry,
+            WriteBatchEntry::Delete {
+                column_family_id: 0,
+                key: b"k0".to_vec()
+            }
+        );
+        assert!(feed.poll().unwrap().is_empty());
+
+        // resume, from the middle of a batch
+        let mut feed = ChangeFeed::new(&db).since(SequenceNumber(start.0 + 1));
+        assert_eq!(feed.poll().unwrap().len(), 2);
+
+        // writes without WAL leave a gap
+        assert!(db
+            .put(&WriteOptions::default().disable_wal(true), b"k3", b"v3")
+            .is_ok());
+        assert!(db.put(WriteOptions::default_instance(), b"k4", b"v4").is_ok());
+        assert!(feed.poll().is_err());
+        let mut feed = feed.allow_gaps(true);
+        let changes = feed.poll().unwrap();
+        assert_eq!(changes.len(), 1);
+        assert_eq!(
+            changes[0].entry,
+            WriteBatchEntry::Put {
+                column_family_id: 0,
+                key: b"k4".to_vec(),
+                value: b"v4".to_vec()
+            }
+        );
+
+        // and in the middle of the log
+        assert!(db.put(WriteOptions::default_instance(), b"k5", b"v5").is_ok());
+        assert!(db
+            .put(&WriteOptions::default().disable_wal(true), b"k6", b"v6")
+            .is_ok());
+        assert!(db.put(WriteOptions::default_instance(), b"k7", b"v7").is_ok());
+        assert_eq!(feed.poll().unwrap().len(), 2);
+
+        let (handle, rx) = feed.spawn_channel(Duration::from_millis(10));
+        assert!(db.put(WriteOptions::default_instance(), b"k8", b"v8").is_ok());
+        let change = rx.recv_timeout(Duration::from_secs(5)).unwrap();
+        assert_eq!(change.sequence, db.get_latest_sequence_number());
+        assert_eq!(handle.stop(), Some(SequenceNumber(change.sequence.0 + 1)));
+
+        let mut iter = db.new_tailing_iterator();
+        iter.seek(b"k9");
+        assert!(!iter.is_valid());
+        assert!(db.put(WriteOptions::default_instance(), b"k9", b"v9").is_ok());
+        iter.seek(b"k9");
+        assert!(iter.is_valid());
+        assert_eq!(iter.value(), b"v9");
+    }
+}
@@ -0,0 +1,162 @@
+//! Openable snapshots of a DB, and exports of column families.
+//!
+//! A checkpoint is a directory holding a consistent copy of a DB, which can be opened
+//! as a DB of its own. SST files are hard linked when the checkpoint is on the same
+//! filesystem, and copied otherwise.
+
+use std::fmt;
+use std::os::raw::c_void;
+use std::path::Path;
+use std::ptr;
+
+use rocks_sys as ll;
+
+use crate::db::{ColumnFamilyHandle, DBRef};
+use crate::metadata::{ExportImportFilesMetaData, LiveFileMetaData};
+use crate::operation::OperationKind;
+use crate::to_raw::ToRaw;
+use crate::utilities::path_to_bytes;
+use crate::{Error, Result};
+
+/// Creates checkpoints and column family exports of a DB.
+pub struct Checkpoint<'a> {
+    raw: *mut ll::rocks_checkpoint_t,
+    db: &'a DBRef,
+}
+
+impl<'a> fmt::Debug for Checkpoint<'a> {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.debug_struct("Checkpoint").field("db", &self.db.name()).finish()
+    }
+}
+
+impl<'a> Drop for Checkpoint<'a> {
+    fn drop(&mut self) {
+        unsafe {
+            ll::rocks_checkpoint_destroy(self.raw);
+        }
+    }
+}
+
+impl<'a> Checkpoint<'a> {
+    pub fn new(db: &'a DBRef) -> Result<Checkpoint<'a>> {
+        let mut status = ptr::null_mut();
+        unsafe {
+            let raw = ll::rocks_checkpoint_create(db.raw(), &mut status);
+            Error::from_ll(status).map(|_| Checkpoint { raw, db })
+        }
+    }
+
+    /// Builds an openable snapshot of the DB in `checkpoint_dir`, which must not exist.
+    ///
+    /// Memtables are flushed first if the WAL is at least `log_size_for_flush` bytes,
+    /// otherwise the WAL is copied. 0 always flushes.
+    ///
+    /// Listed by `DBRef::operations()` while running.
+    pub fn create_checkpoint<P: AsRef<Path>>(&self, checkpoint_dir: P, log_size_for_flush: u64) -> Result<()> {
+        let dir = path_to_bytes(checkpoint_dir);
+        let mut status = ptr::null_mut();
+        self.db.track_default(OperationKind::CreateCheckpoint, None, || unsafe {
+            ll::rocks_checkpoint_create_checkpoint(
+                self.raw,
+                dir.as_ptr() as *const _,
+                dir.len(),
+                log_size_for_flush,
+                &mut status,
+            );
+        });
+        Error::from_ll(status)
+    }
+
+    /// Exports the SST files of a column family to `export_dir`, which must not exist.
+    ///
+    /// Memtables are flushed first. The files are hard linked or copied, and can be
+    /// imported into another DB by `DBRef::create_column_family_with_import()`.
+    ///
+    /// Listed by `DBRef::operations()` while running.
+    pub fn export_column_family<P: AsRef<Path>>(
+        &self,
+        column_family: &ColumnFamilyHandle,
+        export_dir: P,
+    ) -> Result<ExportImportFilesMetaData> {
+        let dir = path_to_bytes(export_dir);
+        let mut db_comparator_name = String::new();
+        let mut status = ptr::null_mut();
+        unsafe {
+            let files = self.db.track(OperationKind::ExportColumnFamily, column_family, None, || {
+                ll::rocks_checkpoint_export_column_family(
+                    self.raw,
+                    column_family.raw(),
+                    dir.as_ptr() as *const _,
+                    dir.len(),
+                    &mut db_comparator_name as *mut String as *mut c_void,
+                    &mut status,
+                )
+            });
+            Error::from_ll(status).map(|_| ExportImportFilesMetaData {
+                db_comparator_name,
+                files: LiveFileMetaData::from_ll_list(files),
+            })
+        }
+    }
+}
+
+/// Exports a column family to `export_dir`, see `Checkpoint::export_column_family()`.
+pub fn export_column_family<P: AsRef<Path>>(
+    db: &DBRef,
+    column_family: &ColumnFamilyHandle,
+    export_dir: P,
+) -> Result<ExportImportFilesMetaData> {
+    Checkpoint::new(db)?.export_column_family(column_family, export_dir)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::rocksdb::*;
+
+    #[test]
+    fn checkpoint_and_export() {
+        let tmp_dir = ::tempdir::TempDir::new_in(".", "rocks").unwrap();
+        let db = DB::open(
+            Options::default().map_db_options(|db| db.create_if_missing(true)),
+            tmp_dir.path().join("db"),
+        )
+        .unwrap();
+        let cf = db.create_column_family(&ColumnFamilyOptions::default(), "exported").unwrap();
+        assert!(db.put(WriteOptions::default_instance(), b"k", b"v").is_ok());
+        assert!(cf.put(WriteOptions::default_instance(), b"cf-k", b"cf-v").is_ok());
+
+        let checkpoint = Checkpoint::new(&db).unwrap();
+        assert!(checkpoint.create_checkpoint(tmp_dir.path().join("checkpoint"), 0).is_ok());
+        let snapshot = DB::open(Options::default(), tmp_dir.path().join("checkpoint")).unwrap();
+        assert_eq!(snapshot.get(ReadOptions::default_instance(), b"k").unwrap(), b"v".as_ref());
+        drop(snapshot);
+
+        let metadata = export_column_family(&db, &cf, tmp_dir.path().join("export")).unwrap();
+        assert_eq!(metadata.db_comparator_name, "leveldb.BytewiseComparator");
+        assert_eq!(metadata.files.len(), 1);
+        // the export dir must not exist
+        assert!(checkpoint
+            .export_column_family(&cf, tmp_dir.path().join("export"))
+            .is_err());
+
+        let other = DB::open(
+            Options::default().map_db_options(|db| db.create_if_missing(true)),
+            tmp_dir.path().join("other"),
+        )
+        .unwrap();
+        let imported = other
+            .create_column_family_with_import(
+                &ColumnFamilyOptions::default(),
+                "imported",
+                &ImportColumnFamilyOptions::default().move_files(true),
+                &metadata,
+            )
+            .unwrap();
+        assert_eq!(
+            imported.get(ReadOptions::default_instance(), b"cf-k").unwrap(),
+            b"cf-v".as_ref()
+        );
+    }
+}
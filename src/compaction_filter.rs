@@ -8,8 +8,12 @@ use rocks_sys as ll;
 #[repr(C)]
 #[derive(Debug, PartialEq, PartialOrd, Eq, Ord, Copy, Clone)]
 pub enum ValueType {
+    /// Written with `put()`
     Value = 0,
+    /// Written with `merge()`
     MergeOperand = 1,
+    /// Used internally by BlobDB, a reference to a value in a blob file
+    BlobIndex = 2,
 }
 
 #[derive(Debug)]
@@ -21,7 +25,7 @@ pub enum Decision {
 }
 
 impl Decision {
-    // to C Decision type
+    // to C Decision type, must be the same as C part
     fn to_c(&self) -> c_int {
         match *self {
             Decision::Keep => 0,
@@ -200,19 +204,17 @@ pub mod c {
         assert!(!f.is_null());
         // FIXME: borrow as mutable
         let filter = f as *mut &mut (dyn CompactionFilter + Sync);
-        // must be the same as C part
-        match (*filter).filter(level, key, value_type, existing_value) {
-            Decision::Keep => 0,
-            Decision::Remove => 1,
-            Decision::ChangeValue(nval) => {
+        let decision = (*filter).filter(level, key, value_type, existing_value);
+        match decision {
+            Decision::ChangeValue(ref nval) => {
                 ll::cxx_string_assign(new_value as *mut _, nval.as_ptr() as *const _, nval.len());
-                2
             },
-            Decision::RemoveAndSkipUntil(skip) => {
+            Decision::RemoveAndSkipUntil(ref skip) => {
                 ll::cxx_string_assign(skip_until as *mut _, skip.as_ptr() as *const _, skip.len());
-                3
             },
+            _ => {},
         }
+        decision.to_c()
     }
 
     #[no_mangle]
@@ -324,4 +326,60 @@ mod tests {
         drop(db);
         drop(tmp_dir);
     }
+
+    pub struct MergeOperandFilter;
+
+    impl CompactionFilter for MergeOperandFilter {
+        fn filter(&mut self, level: i32, key: &[u8], value_type: ValueType, existing_value: &[u8]) -> Decision {
+            match value_type {
+                ValueType::MergeOperand if existing_value == b"drop" => Decision::Remove,
+                ValueType::MergeOperand if existing_value == b"typo" => Decision::ChangeValue(b"fixed".to_vec()),
+                _ => Decision::Keep,
+            }
+        }
+    }
+
+    lazy_static! {
+        static ref MERGE_OPERAND_FILTER: MergeOperandFilter = MergeOperandFilter;
+    }
+
+    #[test]
+    fn compaction_filter_merge_operands() {
+        use crate::merge_operator::AssociativeMergeOperator;
+
+        struct ConcatMergeOp;
+
+        impl AssociativeMergeOperator for ConcatMergeOp {
+            fn merge(&self, _: &[u8], existing_value: Option<&[u8]>, value: &[u8], _: &Logger) -> Option<Vec<u8>> {
+                let mut ret = existing_value.map(|v| v.to_vec()).unwrap_or_default();
+                if !ret.is_empty() {
+                    ret.push(b',');
+                }
+                ret.extend_from_slice(value);
+                Some(ret)
+            }
+        }
+
+        let tmp_dir = ::tempdir::TempDir::new_in(".", "rocks").unwrap();
+        let db = DB::open(
+            Options::default()
+                .map_db_options(|db| db.create_if_missing(true))
+                .map_cf_options(|cf| {
+                    cf.compaction_filter(&*MERGE_OPERAND_FILTER)
+                        .associative_merge_operator(Box::new(ConcatMergeOp))
+                }),
+            &tmp_dir,
+        )
+        .unwrap();
+
+        assert!(db.put(WriteOptions::default_instance(), b"key", b"drop").is_ok());
+        for operand in &[&b"a"[..], b"drop", b"typo", b"b"] {
+            assert!(db.merge(WriteOptions::default_instance(), b"key", operand).is_ok());
+        }
+        assert_eq!(db.get(ReadOptions::default_instance(), b"key").unwrap(), b"drop,a,drop,typo,b");
+
+        assert!(db.compact_range(&Default::default(), ..).is_ok());
+        // values are kept, only merge operands are filtered
+        assert_eq!(db.get(ReadOptions::default_instance(), b"key").unwrap(), b"drop,a,fixed,b");
+    }
 }
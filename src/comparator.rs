@@ -2,10 +2,10 @@
 //! used as keys in an sstable or a database.
 
 use std::cmp::Ordering;
-use std::mem;
 use std::os::raw::{c_char, c_int};
 use std::slice;
 use std::str;
+use std::sync::Arc;
 
 use rocks_sys as ll;
 
@@ -49,42 +49,194 @@ pub trait Comparator {
     // Advanced functions: these are used to reduce the space requirements
     // for internal data structures like index blocks.
 
-    /// If `*start < limit`, changes `*start` to a short string in `[start,limit)`.
-    /// Simple comparator implementations may return with `*start` unchanged,
+    /// If `start < limit`, returns a short key in `[start,limit)`, to replace `start`.
+    /// Simple comparator implementations may return `None`, leaving `start` unchanged,
     /// i.e., an implementation of this method that does nothing is correct.
-    fn find_shortest_separator(&self, _start: &[u8], _limit: &[u8]) -> Option<&[u8]> {
+    fn find_shortest_separator(&self, _start: &[u8], _limit: &[u8]) -> Option<Vec<u8>> {
         None
     }
 
-    /// Changes `*key` to a short string `>= *key`.
+    /// Returns a short key `>= key`, to replace `key`.
     ///
-    /// Simple comparator implementations may return with `*key` unchanged,
+    /// Simple comparator implementations may return `None`, leaving `key` unchanged,
     /// i.e., an implementation of this method that does nothing is correct.
-    fn find_short_successor(&self, _key: &[u8]) -> Option<&[u8]> {
+    fn find_short_successor(&self, _key: &[u8]) -> Option<Vec<u8>> {
         None
     }
 }
 
+/// A comparator handed to RocksDB, which only keeps a raw pointer to it.
+///
+/// Shared by the options it is set on, their copies, and the DBs opened with them,
+/// so that it is released after the last DB using it is closed.
+pub(crate) struct ComparatorHandle {
+    raw: *mut ll::rocks_comparator_t,
+}
+
+// the comparator is Send + Sync
+unsafe impl Send for ComparatorHandle {}
+unsafe impl Sync for ComparatorHandle {}
+
+impl ComparatorHandle {
+    pub(crate) fn new(val: Arc<dyn Comparator + Send + Sync>) -> ComparatorHandle {
+        // Box<Arc<dyn Comparator>>
+        let raw_ptr = Box::into_raw(Box::new(val));
+        ComparatorHandle {
+            raw: unsafe { ll::rocks_comparator_create(raw_ptr as *mut _) },
+        }
+    }
+
+    pub(crate) fn raw(&self) -> *mut ll::rocks_comparator_t {
+        self.raw
+    }
+}
+
+impl Drop for ComparatorHandle {
+    fn drop(&mut self) {
+        unsafe {
+            ll::rocks_comparator_destroy(self.raw);
+        }
+    }
+}
+
+/// Orders 8-byte little-endian keys as `u64`, i.e. keys written with `u64::to_le_bytes()`.
+///
+/// Big-endian keys need no custom comparator, see `key_encoding`.
+///
+/// Keys of another length never panic, they sort bytewise before all 8-byte keys.
+#[derive(Debug, Default, Clone, Copy)]
+pub struct U64Comparator;
+
+impl Comparator for U64Comparator {
+    fn compare(&self, a: &[u8], b: &[u8]) -> Ordering {
+        compare_fixed_u64(a, b)
+    }
+
+    fn name(&self) -> &str {
+        "rust-rocks.U64Comparator\0"
+    }
+}
+
+/// Orders keys by a fixed-size prefix bytewise, then by a little-endian `u64`
+/// suffix, e.g. an object ID followed by a version number.
+///
+/// Keys shorter than `prefix_len` compare their whole key as the prefix, suffixes
+/// other than 8 bytes are ordered as by `U64Comparator`.
+#[derive(Debug, Clone)]
+pub struct FixedPrefixU64Comparator {
+    prefix_len: usize,
+    name: String,
+}
+
+impl FixedPrefixU64Comparator {
+    pub fn new(prefix_len: usize) -> FixedPrefixU64Comparator {
+        FixedPrefixU64Comparator {
+            prefix_len,
+            name: format!("rust-rocks.FixedPrefixU64Comparator.{}\0", prefix_len),
+        }
+    }
+}
+
+impl Comparator for FixedPrefixU64Comparator {
+    fn compare(&self, a: &[u8], b: &[u8]) -> Ordering {
+        let (a_prefix, a_suffix) = a.split_at(self.prefix_len.min(a.len()));
+        let (b_prefix, b_suffix) = b.split_at(self.prefix_len.min(b.len()));
+        a_prefix
+            .cmp(b_prefix)
+            .then_with(|| compare_fixed_u64(a_suffix, b_suffix))
+    }
+
+    fn name(&self) -> &str {
+        &self.name
+    }
+}
+
+/// Reverses the order of another comparator.
+///
+/// For plain bytewise keys, `ColumnFamilyOptions::bitwise_comparator_reversed()`
+/// uses the built-in one of RocksDB, which avoids calling into Rust.
+pub struct ReverseComparator {
+    inner: Arc<dyn Comparator + Send + Sync>,
+    name: String,
+}
+
+impl ReverseComparator {
+    pub fn new(inner: Arc<dyn Comparator + Send + Sync>) -> ReverseComparator {
+        let name = format!("rust-rocks.Reverse({})\0", inner.name().trim_end_matches('\0'));
+        ReverseComparator { inner, name }
+    }
+}
+
+impl Comparator for ReverseComparator {
+    fn compare(&self, a: &[u8], b: &[u8]) -> Ordering {
+        self.inner.compare(b, a)
+    }
+
+    fn equal(&self, a: &[u8], b: &[u8]) -> bool {
+        self.inner.equal(a, b)
+    }
+
+    fn name(&self) -> &str {
+        &self.name
+    }
+}
+
+/// Little-endian `u64` keys, see `U64Comparator`.
+pub fn u64_comparator() -> Arc<dyn Comparator + Send + Sync> {
+    Arc::new(U64Comparator)
+}
+
+/// A fixed-size prefix then a little-endian `u64`, see `FixedPrefixU64Comparator`.
+pub fn fixed_prefix_then_u64(prefix_len: usize) -> Arc<dyn Comparator + Send + Sync> {
+    Arc::new(FixedPrefixU64Comparator::new(prefix_len))
+}
+
+/// The reverse order of `comparator`, see `ReverseComparator`.
+pub fn reversed(comparator: Arc<dyn Comparator + Send + Sync>) -> Arc<dyn Comparator + Send + Sync> {
+    Arc::new(ReverseComparator::new(comparator))
+}
+
+fn decode_fixed_u64(buf: &[u8]) -> Option<u64> {
+    if buf.len() != 8 {
+        return None;
+    }
+    let mut bytes = [0; 8];
+    bytes.copy_from_slice(buf);
+    Some(u64::from_le_bytes(bytes))
+}
+
+// called from the compare callback, so a malformed key must not panic across FFI
+fn compare_fixed_u64(a: &[u8], b: &[u8]) -> Ordering {
+    match (decode_fixed_u64(a), decode_fixed_u64(b)) {
+        (Some(a), Some(b)) => a.cmp(&b),
+        (None, Some(_)) => Ordering::Less,
+        (Some(_), None) => Ordering::Greater,
+        (None, None) => a.cmp(b),
+    }
+}
+
 #[doc(hidden)]
 pub mod rust_export {
     use super::*;
 
+    // Box<Arc<dyn Comparator>>
+    type ComparatorObj = Arc<dyn Comparator + Send + Sync>;
+
     #[no_mangle]
     pub unsafe extern "C" fn rust_comparator_compare(cp: *mut (), a: *const &[u8], b: *const &[u8]) -> c_int {
-        let comparator = cp as *mut &dyn Comparator;
-        // FIXME: 8 byte Ordering
-        mem::transmute::<_, i8>((*comparator).compare(*a, *b)) as c_int
+        let comparator = cp as *mut ComparatorObj;
+        (*comparator).compare(*a, *b) as c_int
     }
 
     #[no_mangle]
     pub unsafe extern "C" fn rust_comparator_equal(cp: *mut (), a: *const &[u8], b: *const &[u8]) -> c_char {
-        let comparator = cp as *mut &dyn Comparator;
+        let comparator = cp as *mut ComparatorObj;
         ((*comparator).equal(*a, *b)) as c_char
     }
 
     #[no_mangle]
     pub unsafe extern "C" fn rust_comparator_name(cp: *mut ()) -> *const c_char {
-        let comparator = cp as *mut &dyn Comparator;
+        let comparator = cp as *mut ComparatorObj;
         (*comparator).name().as_ptr() as *const _
     }
 
@@ -95,7 +247,7 @@ pub mod rust_export {
         limit: *const &[u8],
     ) {
         // Slice&
-        let comparator = cp as *mut &dyn Comparator;
+        let comparator = cp as *mut ComparatorObj;
 
         let start_ptr = ll::cxx_string_data(start as *const _);
         let start_len = ll::cxx_string_size(start as *const _);
@@ -110,7 +262,7 @@ pub mod rust_export {
     #[no_mangle]
     pub unsafe extern "C" fn rust_comparator_find_short_successor(cp: *mut (), key: *mut ()) {
         // std::string*
-        let comparator = cp as *mut &dyn Comparator;
+        let comparator = cp as *mut ComparatorObj;
 
         let key_ptr = ll::cxx_string_data(key as *const _);
         let key_len = ll::cxx_string_size(key as *const _);
@@ -124,15 +276,16 @@ pub mod rust_export {
     #[no_mangle]
     pub unsafe extern "C" fn rust_comparator_drop(op: *mut ()) {
         assert!(!op.is_null());
-        let operator = op as *mut &dyn Comparator;
-        Box::from_raw(operator);
+        let operator = op as *mut ComparatorObj;
+        drop(Box::from_raw(operator));
     }
 }
 
 #[cfg(test)]
 mod tests {
-    use lazy_static::lazy_static;
+    use std::convert::TryInto;
     use std::str;
+    use std::sync::atomic::{AtomicUsize, Ordering as AtomicOrdering};
 
     use super::super::rocksdb::*;
     use super::*;
@@ -201,17 +354,13 @@ mod tests {
         }
     }
 
-    lazy_static! {
-        static ref CMP: MyComparator = MyComparator;
-    }
-
     #[test]
     fn custom_lowercase_comparator() {
         let tmp_dir = ::tempdir::TempDir::new_in(".", "rocks").unwrap();
 
         let opts = Options::default()
             .map_db_options(|db| db.create_if_missing(true))
-            .map_cf_options(|cf| cf.comparator(&*CMP));
+            .map_cf_options(|cf| cf.comparator(Arc::new(MyComparator)));
         let db = DB::open(opts, tmp_dir).unwrap();
 
         assert!(db.put(&WriteOptions::default(), b"Key1", b"").is_ok());
@@ -227,4 +376,150 @@ mod tests {
 
         assert_eq!(ks, vec!["Key1", "kEy2", "kEY3", "key4"]);
     }
+    /// Orders 8-byte big-endian keys as u64, counting calls.
+    #[derive(Default)]
+    struct CountingComparator {
+        compared: AtomicUsize,
+        separated: AtomicUsize,
+    }
+
+    impl CountingComparator {
+        fn decode(key: &[u8]) -> u64 {
+            u64::from_be_bytes(key.try_into().expect("8-byte key"))
+        }
+    }
+
+    impl Comparator for CountingComparator {
+        fn compare(&self, a: &[u8], b: &[u8]) -> ::std::cmp::Ordering {
+            self.compared.fetch_add(1, AtomicOrdering::Relaxed);
+            Self::decode(a).cmp(&Self::decode(b))
+        }
+
+        fn name(&self) -> &str {
+            "rust-rocks.CountingComparator\0"
+        }
+
+        fn find_shortest_separator(&self, start: &[u8], limit: &[u8]) -> Option<Vec<u8>> {
+            self.separated.fetch_add(1, AtomicOrdering::Relaxed);
+            // keys are fixed-size, the largest one in [start, limit) is limit - 1
+            let (start, limit) = (Self::decode(start), Self::decode(limit));
+            if start + 1 < limit {
+                Some((limit - 1).to_be_bytes().to_vec())
+            } else {
+                None
+            }
+        }
+    }
+
+    #[test]
+    fn stateful_u64_comparator() {
+        let tmp_dir = ::tempdir::TempDir::new_in(".", "rocks").unwrap();
+        let cmp = Arc::new(CountingComparator::default());
+
+        let opts = Options::default()
+            .map_db_options(|db| db.create_if_missing(true))
+            .map_cf_options(|cf| {
+                cf.comparator(cmp.clone())
+                    .table_factory_block_based(BlockBasedTableOptions::default().block_size(8))
+            });
+        let db = DB::open(&opts, &tmp_dir).unwrap();
+        drop(opts);
+
+        for i in &[256_u64, 1, 65536, 2, 255] {
+            assert!(db.put(&WriteOptions::default(), &i.to_be_bytes(), b"").is_ok());
+        }
+        assert!(db.flush(&FlushOptions::default()).is_ok());
+        for i in &[3_u64, 1024] {
+            assert!(db.put(&WriteOptions::default(), &i.to_be_bytes(), b"").is_ok());
+        }
+
+        let ks = db
+            .new_iterator(&ReadOptions::default())
+            .keys()
+            .map(CountingComparator::decode)
+            .collect::<Vec<_>>();
+        assert_eq!(ks, vec![1, 2, 3, 255, 256, 1024, 65536]);
+        assert_eq!(db.get(&ReadOptions::default(), &255_u64.to_be_bytes()).unwrap(), b"");
+        assert!(cmp.compared.load(AtomicOrdering::Relaxed) > 0);
+        // one data block per key, separated in the index block
+        assert!(cmp.separated.load(AtomicOrdering::Relaxed) > 0);
+
+        // held by the DB
+        assert!(Arc::strong_count(&cmp) > 1);
+        drop(db);
+        assert_eq!(Arc::strong_count(&cmp), 1);
+    }
+
+    #[test]
+    fn column_family_comparator_lifetime() {
+        let tmp_dir = ::tempdir::TempDir::new_in(".", "rocks").unwrap();
+        let cmp = Arc::new(CountingComparator::default());
+        let db = DB::open(
+            Options::default().map_db_options(|db| db.create_if_missing(true)),
+            &tmp_dir,
+        )
+        .unwrap();
+
+        let cfopts = ColumnFamilyOptions::default().comparator(cmp.clone());
+        let cf = db.create_column_family(&cfopts, "u64").unwrap();
+        drop(cfopts);
+        for i in &[2_u64, 1] {
+            assert!(cf.put(&WriteOptions::default(), &i.to_be_bytes(), b"").is_ok());
+        }
+        assert!(cf.flush(&FlushOptions::default()).is_ok());
+        assert!(cmp.compared.load(AtomicOrdering::Relaxed) > 0);
+
+        drop(cf);
+        assert!(Arc::strong_count(&cmp) > 1);
+        drop(db);
+        assert_eq!(Arc::strong_count(&cmp), 1);
+    }
+    fn collect_keys(cmp: Arc<dyn Comparator + Send + Sync>, keys: &[Vec<u8>]) -> Vec<Vec<u8>> {
+        let tmp_dir = ::tempdir::TempDir::new_in(".", "rocks").unwrap();
+        let opts = Options::default()
+            .map_db_options(|db| db.create_if_missing(true))
+            .map_cf_options(|cf| cf.comparator(cmp));
+        let db = DB::open(opts, &tmp_dir).unwrap();
+        for key in keys {
+            assert!(db.put(&WriteOptions::default(), key, b"").is_ok());
+        }
+        db.new_iterator(&ReadOptions::default()).keys().map(|k| k.to_vec()).collect()
+    }
+
+    #[test]
+    fn builtin_comparators() {
+        let nums = [256_u64, 1, u64::max_value(), 0, 255];
+        let keys = nums.iter().map(|n| n.to_le_bytes().to_vec()).collect::<Vec<_>>();
+        let decoded = |ks: Vec<Vec<u8>>| ks.iter().map(|k| decode_fixed_u64(k).unwrap()).collect::<Vec<_>>();
+
+        assert_eq!(decoded(collect_keys(u64_comparator(), &keys)), vec![0, 1, 255, 256, u64::max_value()]);
+        assert_eq!(
+            decoded(collect_keys(reversed(u64_comparator()), &keys)),
+            vec![u64::max_value(), 256, 255, 1, 0]
+        );
+
+        let key = |prefix: &[u8], n: u64| [prefix, &n.to_le_bytes()[..]].concat();
+        let keys = vec![key(b"b", 1), key(b"a", 256), key(b"b", 0), key(b"a", 2)];
+        assert_eq!(
+            collect_keys(fixed_prefix_then_u64(1), &keys),
+            vec![key(b"a", 2), key(b"a", 256), key(b"b", 0), key(b"b", 1)]
+        );
+
+        assert_eq!(reversed(u64_comparator()).name(), "rust-rocks.Reverse(rust-rocks.U64Comparator)\0");
+        assert_eq!(FixedPrefixU64Comparator::new(4).name(), "rust-rocks.FixedPrefixU64Comparator.4\0");
+    }
+
+    #[test]
+    fn malformed_u64_keys() {
+        let keys = vec![b"ab".to_vec(), 1_u64.to_le_bytes().to_vec(), b"a".to_vec(), b"".to_vec()];
+        assert_eq!(
+            collect_keys(u64_comparator(), &keys),
+            vec![b"".to_vec(), b"a".to_vec(), b"ab".to_vec(), 1_u64.to_le_bytes().to_vec()]
+        );
+
+        let cmp = FixedPrefixU64Comparator::new(2);
+        assert_eq!(cmp.compare(b"a", b"ab"), Ordering::Less);
+        assert_eq!(cmp.compare(b"ab", b"ab\x01"), Ordering::Less);
+        assert_eq!(cmp.compare(b"ab\x01", &[&b"ab"[..], &0_u64.to_le_bytes()].concat()), Ordering::Less);
+    }
 }
@@ -4,36 +4,97 @@ use std::collections::hash_map::HashMap;
 use std::ffi::{CStr, CString};
 use std::fmt;
 use std::iter::IntoIterator;
-use std::mem;
 use std::ops;
 use std::os::raw::{c_char, c_int, c_void};
 use std::path::Path;
 use std::ptr;
 use std::slice;
 use std::str;
-use std::sync::Arc;
+use std::sync::mpsc::{self, Receiver};
+use std::sync::{Arc, Condvar, Mutex, PoisonError};
+use std::thread::{self, JoinHandle};
+use std::time::{Duration, Instant, SystemTime};
 
 use rocks_sys as ll;
 
+use crate::comparator::ComparatorHandle;
 use crate::debug::KeyVersionVec;
-use crate::iterator::Iterator;
-use crate::metadata::{ColumnFamilyMetaData, LevelMetaData, LiveFileMetaData, SstFileMetaData};
+use crate::iterator::{Iterator, MergedIterator, RateLimitedIterator};
+use crate::metadata::{
+    ColumnFamilyMetaData, DataAgeHistogram, ExportImportFilesMetaData, KeyRange, LevelMetaData, LiveFileMetaData,
+    SstFileMetaData,
+};
+use crate::operation::{OperationId, OperationInfo, OperationKind, OperationRegistry};
 use crate::options::{
-    ColumnFamilyOptions, CompactRangeOptions, CompactionOptions, DBOptions, FlushOptions, IngestExternalFileOptions,
-    Options, ReadOptions, WriteOptions,
+    ColumnFamilyOptions, CompactRangeOptions, CompactionOptions, DBOptions, FlushOptions, ImportColumnFamilyOptions,
+    IngestExternalFileOptions, Options, ReadOptions, SizeApproximationOptions, WaitForCompactOptions, WriteOptions,
 };
+use crate::properties::{self, CFStats, LevelStats, WriteStallState};
 use crate::slice::PinnableSlice;
 use crate::snapshot::Snapshot;
+use crate::stats_history::StatsHistoryIterator;
 use crate::table_properties::TablePropertiesCollection;
 use crate::to_raw::{FromRaw, ToRaw};
-use crate::transaction_log::{LogFile, TransactionLogIterator};
+use crate::transaction::{OptimisticTransactionOptions, Transaction, TransactionDBOptions, TransactionOptions};
+use crate::transaction_log::{LogFile, TransactionLogIterator, WalFileType};
 use crate::types::SequenceNumber;
-use crate::utilities::path_to_bytes;
+use crate::utilities::{path_to_bytes, ColumnFamilyFixups};
 use crate::write_batch::WriteBatch;
 use crate::{Error, Result};
 
 pub const DEFAULT_COLUMN_FAMILY_NAME: &'static str = "default";
 
+/// Number of attempts of `DBRef::transactional_update()` before giving up with `Busy`.
+pub const TRANSACTIONAL_UPDATE_MAX_ATTEMPTS: usize = 16;
+
+/// A write computed by the closure of `DBRef::transactional_update()`.
+///
+/// `cf` is an index into the column families passed to `transactional_update()`.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum UpdateOp {
+    Put { cf: usize, key: Vec<u8>, value: Vec<u8> },
+    Delete { cf: usize, key: Vec<u8> },
+    Merge { cf: usize, key: Vec<u8>, value: Vec<u8> },
+}
+
+/// Result of `DBRef::key_lookup()`, the out-parameters of `KeyMayExist`.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum KeyLookup {
+    /// Filtered out, by bloom filters or index blocks.
+    DefinitelyMissing,
+    /// Not filtered out, may be a false positive.
+    MaybeExists,
+    /// Found without IO, e.g. in a memtable or the block cache.
+    FoundInMemory(Vec<u8>),
+}
+
+impl KeyLookup {
+    pub fn may_exist(&self) -> bool {
+        *self != KeyLookup::DefinitelyMissing
+    }
+}
+
+impl From<(bool, Option<Vec<u8>>)> for KeyLookup {
+    fn from((may_exist, value): (bool, Option<Vec<u8>>)) -> KeyLookup {
+        match (may_exist, value) {
+            (false, _) => KeyLookup::DefinitelyMissing,
+            (true, None) => KeyLookup::MaybeExists,
+            (true, Some(value)) => KeyLookup::FoundInMemory(value),
+        }
+    }
+}
+
+// the column family at index `cf` of `transactional_update()`
+fn update_cf<'c>(column_families: &[&'c ColumnFamilyHandle], cf: usize) -> Result<&'c ColumnFamilyHandle> {
+    column_families.get(cf).cloned().ok_or_else(|| {
+        Error::invalid_argument(&format!(
+            "transactional_update: no column family at index {} of {}",
+            cf,
+            column_families.len()
+        ))
+    })
+}
+
 /// Descriptor of a column family, name and the options
 #[derive(Debug)]
 pub struct ColumnFamilyDescriptor {
@@ -97,6 +158,10 @@ pub struct ColumnFamilyHandle {
     raw: *mut ll::rocks_column_family_handle_t,
 }
 
+// ColumnFamilyHandle only exposes immutable name and id
+unsafe impl Sync for ColumnFamilyHandle {}
+unsafe impl Send for ColumnFamilyHandle {}
+
 impl Drop for ColumnFamilyHandle {
     fn drop(&mut self) {
         // this will not delete CF
@@ -145,22 +210,39 @@ impl ColumnFamilyHandle {
     }
 }
 
-/// An opened column family, owned for RAII style management
-pub struct ColumnFamily {
+// Unregisters a tracked operation once it returns or panics.
+struct OperationGuard<'a> {
+    db: &'a DBRef,
+    id: OperationId,
+}
+
+impl<'a> Drop for OperationGuard<'a> {
+    fn drop(&mut self) {
+        let raw = self.db.raw;
+        self.db
+            .operations
+            .finish(self.id, || unsafe { ll::rocks_db_enable_manual_compaction(raw) });
+    }
+}
+
+// A column family handle owned by the DB registry and every `ColumnFamily` of it.
+//
+// Destroyed by the last of them, the registry is cleared before the DB is closed.
+struct OwnedColumnFamilyHandle {
     handle: ColumnFamilyHandle,
-    db: Arc<DBRef>,
+    db: *mut ll::rocks_db_t,
     owned: bool,
 }
 
-unsafe impl Sync for ColumnFamily {}
-unsafe impl Send for ColumnFamily {}
+unsafe impl Sync for OwnedColumnFamilyHandle {}
+unsafe impl Send for OwnedColumnFamilyHandle {}
 
-impl Drop for ColumnFamily {
+impl Drop for OwnedColumnFamilyHandle {
     fn drop(&mut self) {
         if self.owned {
             let mut status = ptr::null_mut::<ll::rocks_status_t>();
             unsafe {
-                ll::rocks_db_destroy_column_family_handle(self.db.raw, self.raw(), &mut status);
+                ll::rocks_db_destroy_column_family_handle(self.db, self.handle.raw, &mut status);
                 assert!(Error::from_ll(status).is_ok());
                 // make underlying cf_handle a nullptr, rocks-sys will skip deleting it.
                 self.handle.raw = ptr::null_mut();
@@ -169,16 +251,53 @@ impl Drop for ColumnFamily {
     }
 }
 
+/// An opened column family, owned for RAII style management
+///
+/// Clones share the same handle. Handles are also kept by the DB, see `DB::column_family()`.
+/// The underlying handle is destroyed with the last of them, never while it is borrowed,
+/// e.g. by an iterator, even if the column family is dropped.
+#[derive(Clone)]
+pub struct ColumnFamily {
+    handle: Arc<OwnedColumnFamilyHandle>,
+    db: Arc<DBRef>,
+}
+
+unsafe impl Sync for ColumnFamily {}
+unsafe impl Send for ColumnFamily {}
+
+impl ColumnFamily {
+    fn new(db: &Arc<DBRef>, raw: *mut ll::rocks_column_family_handle_t, owned: bool) -> ColumnFamily {
+        ColumnFamily {
+            handle: Arc::new(OwnedColumnFamilyHandle {
+                handle: ColumnFamilyHandle { raw },
+                db: db.raw,
+                owned,
+            }),
+            db: db.clone(),
+        }
+    }
+
+    // a new handle, kept in the registry of the DB
+    fn register(db: &Arc<DBRef>, raw: *mut ll::rocks_column_family_handle_t) -> ColumnFamily {
+        let cf = ColumnFamily::new(db, raw, true);
+        db.column_families
+            .lock()
+            .unwrap()
+            .insert(cf.name().to_owned(), cf.handle.clone());
+        cf
+    }
+}
+
 impl AsRef<ColumnFamilyHandle> for ColumnFamily {
     fn as_ref(&self) -> &ColumnFamilyHandle {
-        &self.handle
+        &self.handle.handle
     }
 }
 
 impl ops::Deref for ColumnFamily {
     type Target = ColumnFamilyHandle;
     fn deref(&self) -> &ColumnFamilyHandle {
-        &self.handle
+        &self.handle.handle
     }
 }
 
@@ -288,10 +407,41 @@ impl ColumnFamily {
         }
     }
 
+    /// Resolve all merge operands of a key and write the merged value back as a
+    /// plain `Put`, so later reads no longer apply the operand chain and the next
+    /// compaction touching the key drops the shadowed operands.
+    ///
+    /// Returns false, and writes nothing, if the key does not exist.
+    ///
+    /// Shadowed operands are not removed with `SingleDelete`, mixing it with
+    /// `Merge` on the same key is undefined behavior in RocksDB. Compact
+    /// `key..=key` afterwards to reclaim them right away.
+    ///
+    /// This is a read-modify-write: a concurrent `merge()` on the same key landing
+    /// between the read and the write is lost, callers must serialize writers of
+    /// the key.
+    pub fn materialize_key(&self, options: &WriteOptions, key: &[u8]) -> Result<bool> {
+        let value = match self.get_opt(&ReadOptions::default(), key)? {
+            Some(value) => value,
+            None => return Ok(false),
+        };
+        self.put(options, key, &value).map(|_| true)
+    }
+
     pub fn get(&self, options: &ReadOptions, key: &[u8]) -> Result<PinnableSlice> {
+        let mut value = PinnableSlice::new();
+        self.get_pinned(options, key, &mut value).map(|_| value)
+    }
+
+    /// Like `get()`, but returns `Ok(None)` if there is no entry for `key`.
+    pub fn get_opt(&self, options: &ReadOptions, key: &[u8]) -> Result<Option<PinnableSlice>> {
+        not_found_to_none(self.get(options, key))
+    }
+
+    /// Like `get()`, but reuses `value`, which is released first.
+    pub fn get_pinned(&self, options: &ReadOptions, key: &[u8], value: &mut PinnableSlice) -> Result<()> {
         let mut status = ptr::null_mut::<ll::rocks_status_t>();
-        // FIXME: should be mut, should hide `new()`
-        let pinnable_val = PinnableSlice::new();
+        value.release();
         unsafe {
             ll::rocks_db_get_cf_pinnable(
                 self.db.raw,
@@ -299,11 +449,16 @@ impl ColumnFamily {
                 self.raw(),
                 key.as_ptr() as *const _,
                 key.len(),
-                pinnable_val.raw(),
+                value.raw(),
                 &mut status,
             );
-            Error::from_ll(status).map(|_| pinnable_val)
         }
+        Error::from_ll(status)
+    }
+
+    /// `DB::get_into()` in this column family.
+    pub fn get_into(&self, options: &ReadOptions, key: &[u8], buf: &mut Vec<u8>) -> Result<Option<usize>> {
+        self.db.get_into_raw(options, self.raw(), key, buf)
     }
 
     pub fn multi_get(&self, options: &ReadOptions, keys: &[&[u8]]) -> Vec<Result<PinnableSlice>> {
@@ -337,6 +492,11 @@ impl ColumnFamily {
             .collect()
     }
 
+    /// Like `multi_get()`, but keys with no entry are `Ok(None)`.
+    pub fn multi_get_opt(&self, options: &ReadOptions, keys: &[&[u8]]) -> Vec<Result<Option<PinnableSlice>>> {
+        self.multi_get(options, keys).into_iter().map(not_found_to_none).collect()
+    }
+
     /// If the key definitely does not exist in the database, then this method
     /// returns false, else true. If the caller wants to obtain value when the key
     /// is found in memory, a bool for 'value_found' must be passed. 'value_found'
@@ -381,6 +541,19 @@ impl ColumnFamily {
         }
     }
 
+    /// `key_may_get()` as a `KeyLookup`.
+    pub fn key_lookup(&self, options: &ReadOptions, key: &[u8]) -> KeyLookup {
+        self.key_may_get(options, key).into()
+    }
+
+    /// Whether `key` exists in this column family, see `DBRef::key_exists()`.
+    pub fn key_exists(&self, options: &ReadOptions, key: &[u8], confirm: bool) -> Result<bool> {
+        match self.key_lookup(options, key) {
+            KeyLookup::MaybeExists if confirm => self.get_opt(options, key).map(|v| v.is_some()),
+            lookup => Ok(lookup.may_exist()),
+        }
+    }
+
     pub fn new_iterator(&self, options: &ReadOptions) -> Iterator {
         unsafe {
             let ptr = ll::rocks_db_create_iterator_cf(self.db.raw, options.raw(), self.raw());
@@ -388,6 +561,33 @@ impl ColumnFamily {
         }
     }
 
+    /// An iterator over keys starting with `prefix`, see `DBRef::prefix_iterator()`.
+    pub fn prefix_iterator(&self, options: &ReadOptions, prefix: &[u8]) -> Iterator<'_> {
+        unsafe { self.db.create_prefix_iterator(options, self.raw(), prefix) }
+    }
+
+    /// A tailing iterator over this column family, see `DBRef::new_tailing_iterator()`.
+    pub fn new_tailing_iterator(&self) -> Iterator<'_> {
+        self.new_iterator(&ReadOptions::default().tailing(true))
+    }
+
+    /// Scan `range` of this column family yielding at most `bytes_per_sec` bytes of keys and
+    /// values per second, for housekeeping scans that must not compete with foreground traffic.
+    ///
+    /// The scan does not fill the block cache. 0 disables throttling, see also
+    /// `RateLimitedIterator::rate_limiter()`.
+    pub fn scan_rate_limited<'k, R: ops::RangeBounds<&'k [u8]>>(
+        &self,
+        range: R,
+        bytes_per_sec: u64,
+    ) -> RateLimitedIterator<'_> {
+        RateLimitedIterator::new(
+            self.new_iterator(&ReadOptions::default().fill_cache(false)),
+            range,
+            bytes_per_sec,
+        )
+    }
+
     pub fn get_property(&self, property: &str) -> Option<String> {
         let mut ret = String::new();
         let ok = unsafe {
@@ -426,7 +626,7 @@ impl ColumnFamily {
 
     pub fn compact_range<R: AsCompactRange>(&self, options: &CompactRangeOptions, range: R) -> Result<()> {
         let mut status = ptr::null_mut::<ll::rocks_status_t>();
-        unsafe {
+        self.db.track(OperationKind::CompactRange, self, None, || unsafe {
             ll::rocks_db_compact_range_opt_cf(
                 self.db.raw,
                 options.raw(),
@@ -437,8 +637,50 @@ impl ColumnFamily {
                 range.end_key_len(),
                 &mut status,
             );
-            Error::from_ll(status)
+        });
+        Error::from_ll(status)
+    }
+
+    /// Compact each of `ranges`, e.g. after deleting expired data in them.
+    ///
+    /// Overlapping or adjacent ranges are merged first, so that no key range is compacted twice.
+    /// End keys are compacted too, as `compact_range()` takes inclusive ranges.
+    pub fn compact_ranges(&self, options: &CompactRangeOptions, ranges: &[ops::Range<&[u8]>]) -> Result<()> {
+        let mut sorted = ranges.to_vec();
+        sorted.sort_by(|a, b| a.start.cmp(b.start));
+        let mut merged: Vec<ops::Range<&[u8]>> = Vec::with_capacity(sorted.len());
+        for range in sorted {
+            match merged.last_mut() {
+                Some(last) if range.start <= last.end => {
+                    if range.end > last.end {
+                        last.end = range.end;
+                    }
+                },
+                _ => merged.push(range),
+            }
+        }
+        for range in merged {
+            self.compact_range(options, range.start..=range.end)?;
+        }
+        Ok(())
+    }
+
+    /// Flush all mem-table data of this column family.
+    pub fn flush(&self, options: &FlushOptions) -> Result<()> {
+        let mut status = ptr::null_mut::<ll::rocks_status_t>();
+        unsafe {
+            ll::rocks_db_flush_cf(self.db.raw, options.raw(), self.raw(), &mut status);
         }
+        Error::from_ll(status)
+    }
+
+    /// Switch the memtable and schedule a flush without waiting for it, the
+    /// returned ticket tells when it is done.
+    pub fn flush_async(&self) -> Result<FlushTicket> {
+        self.flush(&FlushOptions::default().wait(false))?;
+        Ok(FlushTicket {
+            column_family: self.clone(),
+        })
     }
 
     pub fn set_options<T, H>(&self, new_options: H) -> Result<()>
@@ -503,6 +745,15 @@ impl ColumnFamily {
         sizes
     }
 
+    /// Approximate sizes of `ranges` in this column family, see `DB::get_approximate_sizes_with_options()`.
+    pub fn get_approximate_sizes_with_options(
+        &self,
+        options: &SizeApproximationOptions,
+        ranges: &[ops::Range<&[u8]>],
+    ) -> Result<Vec<u64>> {
+        get_approximate_sizes_with_options(self.db.raw, self, options, ranges)
+    }
+
     pub fn get_approximate_memtable_stats(&self, range: ops::Range<&[u8]>) -> (u64, u64) {
         let mut count = 0;
         let mut size = 0;
@@ -536,7 +787,7 @@ impl ColumnFamily {
             num_files += 1;
         }
         let mut status = ptr::null_mut::<ll::rocks_status_t>();
-        unsafe {
+        self.db.track(OperationKind::IngestExternalFile, self, Some(num_files), || unsafe {
             ll::rocks_db_ingest_external_file_cf(
                 self.db.raw,
                 self.raw(),
@@ -546,8 +797,14 @@ impl ColumnFamily {
                 options.raw(),
                 &mut status,
             );
-            Error::from_ll(status)
-        }
+        });
+        Error::from_ll(status)
+    }
+
+    /// Split the key space into at most `n` ranges of roughly equal size, see
+    /// `ColumnFamilyMetaData::split_into_ranges()`.
+    pub fn split_into_ranges(&self, n: usize) -> Vec<KeyRange> {
+        self.metadata().split_into_ranges(n)
     }
 
     /// Obtains the meta data of the current column family of the DB.
@@ -610,6 +867,17 @@ impl ColumnFamily {
 
                     let being_compacted =
                         ll::rocks_column_family_metadata_levels_files_being_compacted(cfmeta, lv, i) != 0;
+                    let num_reads_sampled =
+                        ll::rocks_column_family_metadata_levels_files_num_reads_sampled(cfmeta, lv, i);
+                    let file_number = ll::rocks_column_family_metadata_levels_files_file_number(cfmeta, lv, i);
+                    let num_entries = ll::rocks_column_family_metadata_levels_files_num_entries(cfmeta, lv, i);
+                    let num_deletions = ll::rocks_column_family_metadata_levels_files_num_deletions(cfmeta, lv, i);
+                    let oldest_blob_file_number =
+                        ll::rocks_column_family_metadata_levels_files_oldest_blob_file_number(cfmeta, lv, i);
+                    let oldest_ancester_time =
+                        ll::rocks_column_family_metadata_levels_files_oldest_ancester_time(cfmeta, lv, i);
+                    let file_creation_time =
+                        ll::rocks_column_family_metadata_levels_files_file_creation_time(cfmeta, lv, i);
 
                     let sst_file = SstFileMetaData {
                         size: size as u64,
@@ -620,6 +888,13 @@ impl ColumnFamily {
                         smallestkey: small_key,
                         largestkey: large_key,
                         being_compacted: being_compacted,
+                        num_reads_sampled,
+                        file_number,
+                        num_entries,
+                        num_deletions,
+                        oldest_blob_file_number,
+                        oldest_ancester_time,
+                        file_creation_time,
                     };
 
                     current_level.files.push(sst_file);
@@ -637,14 +912,106 @@ impl ColumnFamily {
     // ================================================================================
 }
 
+const CATCH_UP_ERROR_CAPACITY: usize = 16;
+
+/// Background catch-up of a secondary instance, returned by `DB::spawn_catch_up()`.
+///
+/// Dropping the handle stops the thread.
+#[derive(Debug)]
+pub struct CatchUpHandle {
+    stopped: Arc<(Mutex<bool>, Condvar)>,
+    thread: Option<JoinHandle<()>>,
+    errors: Receiver<Error>,
+}
+
+impl CatchUpHandle {
+    /// Errors returned by catch-up attempts. Up to 16 unread errors are buffered,
+    /// later ones are dropped.
+    pub fn errors(&self) -> &Receiver<Error> {
+        &self.errors
+    }
+
+    /// Stop the thread, waiting for an in-progress catch-up to finish.
+    pub fn stop(mut self) {
+        self.stop_and_join();
+    }
+
+    fn stop_and_join(&mut self) {
+        let (lock, cvar) = &*self.stopped;
+        *lock.lock().unwrap() = true;
+        cvar.notify_all();
+        if let Some(thread) = self.thread.take() {
+            let _ = thread.join();
+        }
+    }
+}
+
+impl Drop for CatchUpHandle {
+    fn drop(&mut self) {
+        self.stop_and_join();
+    }
+}
+
+const FLUSH_POLL_INTERVAL: Duration = Duration::from_millis(10);
+
+/// A non-blocking flush, returned by `ColumnFamily::flush_async()`.
+///
+/// Completion is polled from the column family properties: the flush is done once
+/// no immutable memtable is left, so that memtables switched later can delay it.
+/// A flush failing with a background error never completes, use `wait_timeout()`.
+#[derive(Debug)]
+pub struct FlushTicket {
+    column_family: ColumnFamily,
+}
+
+impl FlushTicket {
+    /// Whether the flushed memtables have been written to SST files.
+    pub fn is_done(&self) -> bool {
+        self.column_family.get_int_property(properties::NUM_IMMUTABLE_MEM_TABLE) == Some(0)
+    }
+
+    /// Block until the flush is done.
+    pub fn wait(&self) {
+        while !self.is_done() {
+            thread::sleep(FLUSH_POLL_INTERVAL);
+        }
+    }
+
+    /// Block until the flush is done, or `timeout` elapsed. Returns true if it is done.
+    pub fn wait_timeout(&self, timeout: Duration) -> bool {
+        let deadline = Instant::now() + timeout;
+        while !self.is_done() {
+            let now = Instant::now();
+            if now >= deadline {
+                return false;
+            }
+            thread::sleep(FLUSH_POLL_INTERVAL.min(deadline - now));
+        }
+        true
+    }
+}
+
 /// Borrowed DB handle
 pub struct DBRef {
     raw: *mut ll::rocks_db_t,
+    operations: OperationRegistry,
+    // serializes validation and commit of `transactional_update()`
+    update_lock: Mutex<()>,
+    // column families opened or created, by name
+    column_families: Mutex<HashMap<String, Arc<OwnedColumnFamilyHandle>>>,
+    // comparators of the column families, RocksDB only keeps raw pointers to them,
+    // dropped after the DB is closed
+    comparators: Mutex<Vec<Arc<ComparatorHandle>>>,
 }
 
 impl Drop for DBRef {
     #[inline]
     fn drop(&mut self) {
+        // handles must be destroyed before the DB
+        self.column_families
+            .get_mut()
+            .unwrap_or_else(PoisonError::into_inner)
+            .clear();
         unsafe {
             ll::rocks_db_destroy(self.raw);
         }
@@ -660,6 +1027,28 @@ impl ToRaw<ll::rocks_db_t> for DBRef {
 unsafe impl Sync for DBRef {}
 unsafe impl Send for DBRef {}
 
+impl DBRef {
+    pub(crate) fn keep_comparators<I: IntoIterator<Item = Arc<ComparatorHandle>>>(&self, comparators: I) {
+        self.comparators
+            .lock()
+            .unwrap_or_else(PoisonError::into_inner)
+            .extend(comparators);
+    }
+
+    /// Wraps a DB pointer handed out by RocksDB callbacks, the result must be `mem::forget`-ed.
+    ///
+    /// Such a handle has its own, empty, operation registry.
+    pub(crate) unsafe fn from_borrowed_ll(raw: *mut ll::rocks_db_t) -> DBRef {
+        DBRef {
+            raw: raw,
+            operations: OperationRegistry::default(),
+            update_lock: Mutex::new(()),
+            column_families: Mutex::new(HashMap::new()),
+            comparators: Mutex::new(Vec::new()),
+        }
+    }
+}
+
 /// A `DB` is a persistent ordered map from keys to values.
 ///
 /// A `DB` is safe for concurrent access from multiple threads without
@@ -710,7 +1099,13 @@ impl ToRaw<ll::rocks_db_t> for DB {
 
 impl FromRaw<ll::rocks_db_t> for DB {
     unsafe fn from_ll(raw: *mut ll::rocks_db_t) -> DB {
-        let context = DBRef { raw: raw };
+        let context = DBRef {
+            raw: raw,
+            operations: OperationRegistry::default(),
+            update_lock: Mutex::new(()),
+            column_families: Mutex::new(HashMap::new()),
+            comparators: Mutex::new(Vec::new()),
+        };
         DB {
             context: Arc::new(context),
         }
@@ -718,6 +1113,11 @@ impl FromRaw<ll::rocks_db_t> for DB {
 }
 
 impl DB {
+    fn with_comparators<I: IntoIterator<Item = Arc<ComparatorHandle>>>(self, comparators: I) -> DB {
+        self.context.keep_comparators(comparators);
+        self
+    }
+
     /// Open the database with the specified `name`.
     pub fn open<T: AsRef<Options>, P: AsRef<Path>>(options: T, name: P) -> Result<DB> {
         let opt = options.as_ref().raw();
@@ -725,7 +1125,7 @@ impl DB {
         let mut status = ptr::null_mut::<ll::rocks_status_t>();
         unsafe {
             let db_ptr = ll::rocks_db_open(opt, dbname.as_ptr(), &mut status);
-            Error::from_ll(status).map(|_| DB::from_ll(db_ptr))
+            Error::from_ll(status).map(|_| DB::from_ll(db_ptr).with_comparators(options.as_ref().comparator_handle()))
         }
     }
 
@@ -766,23 +1166,45 @@ impl DB {
                 &mut status,
             );
             Error::from_ll(status).map(|_| {
-                let db = DB::from_ll(db_ptr);
+                let db = DB::from_ll(db_ptr)
+                    .with_comparators(cfs.iter().filter_map(|cf| cf.options.comparator_handle()));
                 let db_ref = db.context.clone();
                 (
                     db,
                     cfhandles
                         .into_iter()
-                        .map(|p| ColumnFamily {
-                            handle: ColumnFamilyHandle { raw: p },
-                            db: db_ref.clone(),
-                            owned: true,
-                        })
+                        .map(|p| ColumnFamily::register(&db_ref, p))
                         .collect(),
                 )
             })
         }
     }
 
+    /// Open DB with all of its column families, each with the column family options of
+    /// `options` and then the matching `fixups` applied.
+    ///
+    /// A DB that does not exist yet is opened with only the default column family, other
+    /// errors listing the column families are returned.
+    pub fn open_with_fixups<P: AsRef<Path>>(
+        options: &Options,
+        name: P,
+        fixups: &ColumnFamilyFixups,
+    ) -> Result<(DB, Vec<ColumnFamily>)> {
+        let cf_names = match DB::list_column_families(options, name.as_ref()) {
+            Ok(names) => names,
+            Err(ref e) if e.is_path_not_found() => vec![DEFAULT_COLUMN_FAMILY_NAME.to_owned()],
+            Err(e) => return Err(e),
+        };
+        let cfs = cf_names
+            .iter()
+            .map(|cf_name| {
+                let cfopts = fixups.apply(cf_name, ColumnFamilyOptions::from_options(options));
+                ColumnFamilyDescriptor::new(cf_name, cfopts)
+            })
+            .collect::<Vec<_>>();
+        DB::open_with_column_families(&DBOptions::from_options(options), name, cfs)
+    }
+
     /// Open the database for read only. All DB interfaces
     /// that modify data, like `put/delete`, will return error.
     /// If the db is opened in read only mode, then no compactions
@@ -797,7 +1219,7 @@ impl DB {
                 error_if_log_file_exist as u8,
                 &mut status,
             );
-            Error::from_ll(status).map(|_| DB::from_ll(db_ptr))
+            Error::from_ll(status).map(|_| DB::from_ll(db_ptr).with_comparators(options.comparator_handle()))
         }
     }
 
@@ -846,17 +1268,14 @@ impl DB {
                 &mut status,
             );
             Error::from_ll(status).map(|_| {
-                let db = DB::from_ll(db_ptr);
+                let db = DB::from_ll(db_ptr)
+                    .with_comparators(cf_descs.iter().filter_map(|cf| cf.options.comparator_handle()));
                 let db_ref = db.context.clone();
                 (
                     db,
                     cfhandles
                         .into_iter()
-                        .map(|p| ColumnFamily {
-                            handle: ColumnFamilyHandle { raw: p },
-                            db: db_ref.clone(),
-                            owned: true,
-                        })
+                        .map(|p| ColumnFamily::register(&db_ref, p))
                         .collect(),
                 )
             })
@@ -876,7 +1295,7 @@ impl DB {
         unsafe {
             let db_ptr =
                 ll::rocks_db_open_as_secondary(options.raw(), dbname.as_ptr(), secondary_path.as_ptr(), &mut status);
-            Error::from_ll(status).map(|_| DB::from_ll(db_ptr))
+            Error::from_ll(status).map(|_| DB::from_ll(db_ptr).with_comparators(options.comparator_handle()))
         }
     }
 
@@ -924,23 +1343,61 @@ impl DB {
                 &mut status,
             );
             Error::from_ll(status).map(|_| {
-                let db = DB::from_ll(db_ptr);
+                let db = DB::from_ll(db_ptr)
+                    .with_comparators(cf_descs.iter().filter_map(|cf| cf.options.comparator_handle()));
                 let db_ref = db.context.clone();
                 (
                     db,
                     cfhandles
                         .into_iter()
-                        .map(|p| ColumnFamily {
-                            handle: ColumnFamilyHandle { raw: p },
-                            db: db_ref.clone(),
-                            owned: true,
-                        })
+                        .map(|p| ColumnFamily::register(&db_ref, p))
                         .collect(),
                 )
             })
         }
     }
 
+    /// The DB, shared with helpers that outlive the borrow, such as background threads.
+    pub(crate) fn shared(&self) -> Arc<DBRef> {
+        self.context.clone()
+    }
+
+    /// Run `try_catch_up_with_primary()` every `interval` on a background thread.
+    ///
+    /// Errors are reported through `CatchUpHandle::errors()`, the thread keeps
+    /// going after an error. It stops when the handle is stopped or dropped, and
+    /// keeps the underlying DB alive until then.
+    pub fn spawn_catch_up(&self, interval: Duration) -> CatchUpHandle {
+        let stopped = Arc::new((Mutex::new(false), Condvar::new()));
+        let (tx, rx) = mpsc::sync_channel(CATCH_UP_ERROR_CAPACITY);
+        let db = self.context.clone();
+        let stop = stopped.clone();
+        let thread = thread::Builder::new()
+            .name("rocks-catch-up".into())
+            .spawn(move || {
+                let (lock, cvar) = &*stop;
+                loop {
+                    {
+                        let stopped = lock.lock().unwrap();
+                        let (stopped, _) = cvar.wait_timeout_while(stopped, interval, |s| !*s).unwrap();
+                        if *stopped {
+                            break;
+                        }
+                    }
+                    if let Err(e) = db.try_catch_up_with_primary() {
+                        // drop errors nobody is reading
+                        let _ = tx.try_send(e);
+                    }
+                }
+            })
+            .expect("spawn catch-up thread");
+        CatchUpHandle {
+            stopped,
+            thread: Some(thread),
+            errors: rx,
+        }
+    }
+
     /// `ListColumnFamilies` will open the DB specified by argument name
     /// and return the list of all column nfamilies in that DB
     /// through `column_families` argument. The ordering of
@@ -973,102 +1430,1089 @@ impl DB {
         let mut status = ptr::null_mut::<ll::rocks_status_t>();
         unsafe {
             let handle = ll::rocks_db_create_column_family(self.raw(), cfopts.raw(), dbname.as_ptr(), &mut status);
-            Error::from_ll(status).map(|_| ColumnFamily {
-                handle: ColumnFamilyHandle { raw: handle },
-                db: self.context.clone(),
-                owned: true,
-            })
+            Error::from_ll(status)?;
+            self.keep_comparators(cfopts.comparator_handle());
+            Ok(ColumnFamily::register(&self.context, handle))
+        }
+    }
+
+    /// Create a column family from SST files exported by `Checkpoint::export_column_family()`,
+    /// without writing the data through the memtable.
+    ///
+    /// The files are copied, or moved with `ImportColumnFamilyOptions::move_files`, into
+    /// the DB, keeping their levels and sequence numbers. The comparator of `cfopts` must
+    /// match `metadata.db_comparator_name`.
+    pub fn create_column_family_with_import(
+        &self,
+        cfopts: &ColumnFamilyOptions,
+        column_family_name: &str,
+        import_options: &ImportColumnFamilyOptions,
+        metadata: &ExportImportFilesMetaData,
+    ) -> Result<ColumnFamily> {
+        let dbname = CString::new(column_family_name).unwrap();
+        let mut status = ptr::null_mut::<ll::rocks_status_t>();
+        unsafe {
+            let files = metadata.files_to_ll();
+            let num_files = Some(metadata.files.len());
+            let handle = self.track_named(
+                OperationKind::ImportColumnFamily,
+                Some(column_family_name.to_owned()),
+                num_files,
+                || {
+                    ll::rocks_db_create_column_family_with_import(
+                        self.raw(),
+                        cfopts.raw(),
+                        dbname.as_ptr(),
+                        import_options.move_files as u8,
+                        metadata.db_comparator_name.as_ptr() as *const _,
+                        metadata.db_comparator_name.len(),
+                        files,
+                        &mut status,
+                    )
+                },
+            );
+            ll::rocks_livefiles_destroy(files);
+            Error::from_ll(status)?;
+            self.keep_comparators(cfopts.comparator_handle());
+            Ok(ColumnFamily::register(&self.context, handle))
         }
     }
+
     /// Drop a column family specified by column_family handle. This call
     /// only records a drop record in the manifest and prevents the column
     /// family from flushing and compacting.
+    ///
+    /// The column family is no longer returned by `column_family()`. Handles of it stay
+    /// valid until the last `ColumnFamily` clone is dropped, reads through them and
+    /// existing iterators keep working.
     pub fn drop_column_family(&self, column_family: &ColumnFamilyHandle) -> Result<()> {
         let mut status = ptr::null_mut::<ll::rocks_status_t>();
         unsafe {
             ll::rocks_db_drop_column_family(self.raw(), column_family.raw(), &mut status);
-            Error::from_ll(status)
+            Error::from_ll(status)?;
         }
+        self.unregister_column_families(&[column_family.id()]);
+        Ok(())
     }
 
-    /// Returns default column family handle
-    pub fn default_column_family(&self) -> ColumnFamily {
-        ColumnFamily {
-            handle: ColumnFamilyHandle {
-                raw: unsafe { ll::rocks_db_default_column_family(self.raw()) },
-            },
-            db: self.context.clone(),
-            owned: false,
+    /// Bulk drop column families. This call only records drop records in the
+    /// manifest and prevents the column families from flushing and compacting.
+    /// In case of error, the request may succeed partially. User may call
+    /// `list_column_families()` to check the result.
+    pub fn drop_column_families(&self, column_families: &[&ColumnFamilyHandle]) -> Result<()> {
+        let handles = column_families.iter().map(|cf| cf.raw() as *const _).collect::<Vec<_>>();
+        let mut status = ptr::null_mut::<ll::rocks_status_t>();
+        let ret = unsafe {
+            ll::rocks_db_drop_column_families(self.raw(), handles.as_ptr(), handles.len(), &mut status);
+            Error::from_ll(status)
+        };
+        // on partial failure, dropped ones are unknown, the registry is left as is
+        if ret.is_ok() {
+            let ids = column_families.iter().map(|cf| cf.id()).collect::<Vec<_>>();
+            self.unregister_column_families(&ids);
         }
+        ret
     }
-}
 
-impl DBRef {
-    /// Returns default column family handle
-    fn raw_default_column_family(&self) -> *mut ll::rocks_column_family_handle_t {
-        unsafe { ll::rocks_db_default_column_family(self.raw()) }
+    fn unregister_column_families(&self, ids: &[u32]) {
+        self.context
+            .column_families
+            .lock()
+            .unwrap()
+            .retain(|_, handle| !ids.contains(&handle.handle.id()));
     }
 
-    /// Close the DB by releasing resources, closing files etc. This should be
-    /// called before calling the destructor so that the caller can get back a
-    /// status in case there are any errors. This will not fsync the WAL files.
-    /// If syncing is required, the caller must first call SyncWAL(), or Write()
-    /// using an empty write batch with WriteOptions.sync=true.
-    ///
-    /// If the return status is Aborted(), closing fails because there is
-    /// unreleased snapshot in the system. In this case, users can release
-    /// the unreleased snapshots and try again and expect it to succeed. For
-    /// other status, recalling Close() will be no-op.
+    /// Wait until no flush or compaction is pending or running, in any column family.
     ///
-    /// If the return status is NotSupported(), then the DB implementation does
-    /// cleanup in the destructor
+    /// Compactions scheduled by finished jobs are waited for as well, so that the DB is
+    /// quiescent when this returns, e.g. before a shutdown or a benchmark. Writes issued
+    /// concurrently may keep it waiting. Returns an `Aborted` error when a background
+    /// error happens while waiting.
     ///
-    /// NOTE for Rust: segmentation fault if the db is accessed after close
-    pub unsafe fn close(&self) -> Result<()> {
-        let mut status = ptr::null_mut::<ll::rocks_status_t>();
-        ll::rocks_db_close(self.raw(), &mut status);
-        Error::from_ll(status)
+    /// Background work paused by `pause_background_work()` is never done, use a timeout.
+    pub fn wait_for_compact(&self, options: &WaitForCompactOptions) -> Result<()> {
+        let deadline = options.timeout.map(|timeout| Instant::now() + timeout);
+        let column_families = self.column_families();
+        let background_errors = self.get_int_property(properties::BACKGROUND_ERRORS);
+        if options.flush {
+            for cf in &column_families {
+                cf.flush(&FlushOptions::default().allow_write_stall(true))?;
+            }
+        }
+        loop {
+            let running = self.get_int_property(properties::NUM_RUNNING_FLUSHES) != Some(0)
+                || self.get_int_property(properties::NUM_RUNNING_COMPACTIONS) != Some(0);
+            let pending = column_families.iter().any(|cf| {
+                cf.get_int_property(properties::MEM_TABLE_FLUSH_PENDING) != Some(0)
+                    || cf.get_int_property(properties::COMPACTION_PENDING) != Some(0)
+            });
+            if !running && !pending {
+                return Ok(());
+            }
+            if self.get_int_property(properties::BACKGROUND_ERRORS) != background_errors {
+                return Err(Error::aborted("background error while waiting for compaction"));
+            }
+            let mut interval = FLUSH_POLL_INTERVAL;
+            if let Some(deadline) = deadline {
+                let now = Instant::now();
+                if now >= deadline {
+                    return Err(Error::timed_out("waiting for compaction"));
+                }
+                interval = interval.min(deadline - now);
+            }
+            thread::sleep(interval);
+        }
     }
 
-    /// Manually resume the DB and put it in read-write mode.
-    /// This function will flush memtables for all the column families,
-    /// clear the error, purge any obsolete files, and restart
-    /// background flush and compaction operations.
-    pub fn resume(&self) -> Result<()> {
-        let mut status = ptr::null_mut::<ll::rocks_status_t>();
-        unsafe {
-            ll::rocks_db_resume(self.raw(), &mut status);
-        }
-        Error::from_ll(status)
+    /// `ColumnFamily::flush_async()` on the default column family.
+    pub fn flush_async(&self) -> Result<FlushTicket> {
+        self.default_column_family().flush_async()
     }
 
-    /// Set the database entry for `"key"` to `"value"`.
-    /// If `"key"` already exists, it will be overwritten.
-    /// Returns OK on success, and a non-OK status on error.
+    /// `ColumnFamily::flush_async()`.
+    pub fn flush_async_cf(&self, column_family: &ColumnFamily) -> Result<FlushTicket> {
+        column_family.flush_async()
+    }
+
+    /// Returns default column family handle
+    pub fn default_column_family(&self) -> ColumnFamily {
+        ColumnFamily::new(
+            &self.context,
+            unsafe { ll::rocks_db_default_column_family(self.raw()) },
+            false,
+        )
+    }
+
+    /// Returns an opened column family by name, or `None` if it is not opened by this DB
+    /// or has been dropped.
     ///
-    /// Note: consider setting `options.sync = true`.
-    pub fn put(&self, options: &WriteOptions, key: &[u8], value: &[u8]) -> Result<()> {
-        let mut status = ptr::null_mut::<ll::rocks_status_t>();
-        unsafe {
-            ll::rocks_db_put(
-                self.raw(),
-                options.raw(),
-                key.as_ptr() as *const _,
-                key.len(),
-                value.as_ptr() as *const _,
-                value.len(),
-                &mut status,
-            );
-            Error::from_ll(status)
+    /// Column families opened with the DB or by `create_column_family()` are
+    /// available here until the DB is closed.
+    pub fn column_family(&self, name: &str) -> Option<ColumnFamily> {
+        if name == DEFAULT_COLUMN_FAMILY_NAME {
+            return Some(self.default_column_family());
         }
+        self.context
+            .column_families
+            .lock()
+            .unwrap()
+            .get(name)
+            .map(|handle| ColumnFamily {
+                handle: handle.clone(),
+                db: self.context.clone(),
+            })
     }
 
-    pub fn put_cf(
-        &self,
-        options: &WriteOptions,
-        column_family: &ColumnFamilyHandle,
-        key: &[u8],
-        value: &[u8],
+    /// All opened column families, ordered by column family ID, the default column family first.
+    pub fn column_families(&self) -> Vec<ColumnFamily> {
+        let mut cfs = vec![self.default_column_family()];
+        cfs.extend(
+            self.context
+                .column_families
+                .lock()
+                .unwrap()
+                .iter()
+                .filter(|(name, _)| *name != DEFAULT_COLUMN_FAMILY_NAME)
+                .map(|(_, handle)| ColumnFamily {
+                    handle: handle.clone(),
+                    db: self.context.clone(),
+                }),
+        );
+        cfs.sort_by_key(|cf| cf.id());
+        cfs
+    }
+}
+
+/// A DB with TTL, i.e. `DBWithTTL` of RocksDB.
+///
+/// Key-values inserted are given a timestamp, values older than the TTL of their
+/// column family are removed by compactions. Expired values may still be read
+/// before they are compacted. A TTL of zero never expires.
+///
+/// The value format differs from a plain DB (a 4-byte timestamp is appended), so a
+/// DB created by `TtlDB` must always be opened with `TtlDB` and vice versa.
+pub struct TtlDB {
+    db: DB,
+    // column family ID => TTL
+    ttls: Mutex<HashMap<u32, Duration>>,
+}
+
+impl ops::Deref for TtlDB {
+    type Target = DB;
+
+    fn deref(&self) -> &DB {
+        &self.db
+    }
+}
+
+impl fmt::Debug for TtlDB {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.debug_struct("TtlDB")
+            .field("name", &self.name())
+            .field("ttls", &*self.ttls.lock().unwrap())
+            .finish()
+    }
+}
+
+fn ttl_as_secs(ttl: Duration) -> i32 {
+    ttl.as_secs().min(i32::MAX as u64) as i32
+}
+
+impl TtlDB {
+    /// Open the database with the specified `name`, `ttl` applies to the default column family.
+    ///
+    /// In `read_only` mode no compactions happen, so no value expires.
+    pub fn open<T: AsRef<Options>, P: AsRef<Path>>(
+        options: T,
+        name: P,
+        ttl: Duration,
+        read_only: bool,
+    ) -> Result<TtlDB> {
+        let dbname = CString::new(path_to_bytes(name)).unwrap();
+        let mut status = ptr::null_mut::<ll::rocks_status_t>();
+        unsafe {
+            let db_ptr = ll::rocks_db_open_with_ttl(
+                options.as_ref().raw(),
+                dbname.as_ptr(),
+                ttl_as_secs(ttl),
+                read_only as u8,
+                &mut status,
+            );
+            Error::from_ll(status)?;
+            let mut ttls = HashMap::new();
+            ttls.insert(0, ttl);
+            Ok(TtlDB {
+                db: DB::from_ll(db_ptr).with_comparators(options.as_ref().comparator_handle()),
+                ttls: Mutex::new(ttls),
+            })
+        }
+    }
+
+    /// Open DB with column families, each with a TTL.
+    pub fn open_with_column_families<
+        CF: Into<ColumnFamilyDescriptor>,
+        P: AsRef<Path>,
+        I: IntoIterator<Item = (CF, Duration)>,
+    >(
+        options: &DBOptions,
+        name: P,
+        column_families: I,
+        read_only: bool,
+    ) -> Result<(TtlDB, Vec<ColumnFamily>)> {
+        let dbname = CString::new(path_to_bytes(name)).unwrap();
+
+        let (cfs, ttls): (Vec<ColumnFamilyDescriptor>, Vec<Duration>) = column_families
+            .into_iter()
+            .map(|(desc, ttl)| (desc.into(), ttl))
+            .unzip();
+
+        let num_column_families = cfs.len();
+        // for ffi
+        let mut cfnames: Vec<*const c_char> = Vec::with_capacity(num_column_families);
+        let mut cfopts: Vec<*const ll::rocks_cfoptions_t> = Vec::with_capacity(num_column_families);
+        let mut cfhandles = vec![ptr::null_mut(); num_column_families];
+        let c_ttls: Vec<i32> = ttls.iter().map(|&ttl| ttl_as_secs(ttl)).collect();
+
+        for cf in &cfs {
+            cfnames.push(cf.name_as_ptr());
+            cfopts.push(cf.options.raw());
+        }
+
+        let mut status = ptr::null_mut::<ll::rocks_status_t>();
+        unsafe {
+            let db_ptr = ll::rocks_db_open_column_families_with_ttl(
+                options.raw(),
+                dbname.as_ptr(),
+                num_column_families as c_int,
+                cfnames.as_ptr(),
+                cfopts.as_ptr(),
+                cfhandles.as_mut_ptr(),
+                c_ttls.as_ptr(),
+                read_only as u8,
+                &mut status,
+            );
+            Error::from_ll(status)?;
+            let db = DB::from_ll(db_ptr)
+                .with_comparators(cfs.iter().filter_map(|cf| cf.options.comparator_handle()));
+            let db_ref = db.context.clone();
+            let cfs: Vec<ColumnFamily> = cfhandles
+                .into_iter()
+                .map(|p| ColumnFamily::register(&db_ref, p))
+                .collect();
+            let ttls = cfs.iter().map(|cf| cf.id()).zip(ttls).collect();
+            Ok((
+                TtlDB {
+                    db,
+                    ttls: Mutex::new(ttls),
+                },
+                cfs,
+            ))
+        }
+    }
+
+    /// Create a column family with a TTL.
+    pub fn create_column_family_with_ttl(
+        &self,
+        cfopts: &ColumnFamilyOptions,
+        column_family_name: &str,
+        ttl: Duration,
+    ) -> Result<ColumnFamily> {
+        let name = CString::new(column_family_name).unwrap();
+        let mut status = ptr::null_mut::<ll::rocks_status_t>();
+        let cf = unsafe {
+            let handle = ll::rocks_db_create_column_family_with_ttl(
+                self.raw(),
+                cfopts.raw(),
+                name.as_ptr(),
+                ttl_as_secs(ttl),
+                &mut status,
+            );
+            Error::from_ll(status)?;
+            self.keep_comparators(cfopts.comparator_handle());
+            ColumnFamily::register(&self.db.context, handle)
+        };
+        self.ttls.lock().unwrap().insert(cf.id(), ttl);
+        Ok(cf)
+    }
+
+    /// Change the TTL of a column family at runtime, takes effect on following compactions.
+    pub fn set_ttl(&self, column_family: &ColumnFamilyHandle, ttl: Duration) {
+        unsafe {
+            ll::rocks_db_set_ttl(self.raw(), column_family.raw(), ttl_as_secs(ttl));
+        }
+        self.ttls.lock().unwrap().insert(column_family.id(), ttl);
+    }
+
+    /// The TTL of a column family, as opened, created or set by this `TtlDB`.
+    ///
+    /// Column families created by `DB::create_column_family()` have a TTL of zero.
+    pub fn ttl(&self, column_family: &ColumnFamilyHandle) -> Duration {
+        self.ttls
+            .lock()
+            .unwrap()
+            .get(&column_family.id())
+            .cloned()
+            .unwrap_or_default()
+    }
+
+    /// The underlying DB.
+    pub fn into_db(self) -> DB {
+        self.db
+    }
+}
+
+/// A DB with pessimistic transactions, i.e. `TransactionDB` of RocksDB.
+///
+/// Writes of `Transaction`s lock their keys. Plain writes through the `DB` are wrapped in
+/// internal transactions, so they also respect the locks.
+pub struct TransactionDB {
+    db: DB,
+}
+
+impl ops::Deref for TransactionDB {
+    type Target = DB;
+
+    fn deref(&self) -> &DB {
+        &self.db
+    }
+}
+
+impl fmt::Debug for TransactionDB {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.debug_struct("TransactionDB").field("name", &self.name()).finish()
+    }
+}
+
+impl TransactionDB {
+    /// Open the database with the specified `name`.
+    pub fn open<T: AsRef<Options>, P: AsRef<Path>>(
+        options: T,
+        txn_db_options: &TransactionDBOptions,
+        name: P,
+    ) -> Result<TransactionDB> {
+        let dbname = CString::new(path_to_bytes(name)).unwrap();
+        let mut status = ptr::null_mut::<ll::rocks_status_t>();
+        unsafe {
+            let db_ptr = ll::rocks_transactiondb_open(
+                options.as_ref().raw(),
+                txn_db_options.raw(),
+                dbname.as_ptr(),
+                &mut status,
+            );
+            Error::from_ll(status).map(|_| TransactionDB {
+                db: DB::from_ll(db_ptr).with_comparators(options.as_ref().comparator_handle()),
+            })
+        }
+    }
+
+    /// Open DB with column families.
+    pub fn open_with_column_families<CF: Into<ColumnFamilyDescriptor>, P: AsRef<Path>, I: IntoIterator<Item = CF>>(
+        options: &DBOptions,
+        txn_db_options: &TransactionDBOptions,
+        name: P,
+        column_families: I,
+    ) -> Result<(TransactionDB, Vec<ColumnFamily>)> {
+        let dbname = CString::new(path_to_bytes(name)).unwrap();
+
+        let cfs = column_families
+            .into_iter()
+            .map(|desc| desc.into())
+            .collect::<Vec<ColumnFamilyDescriptor>>();
+
+        let num_column_families = cfs.len();
+        // for ffi
+        let mut cfnames: Vec<*const c_char> = Vec::with_capacity(num_column_families);
+        let mut cfopts: Vec<*const ll::rocks_cfoptions_t> = Vec::with_capacity(num_column_families);
+        let mut cfhandles = vec![ptr::null_mut(); num_column_families];
+
+        for cf in &cfs {
+            cfnames.push(cf.name_as_ptr());
+            cfopts.push(cf.options.raw());
+        }
+
+        let mut status = ptr::null_mut::<ll::rocks_status_t>();
+        unsafe {
+            let db_ptr = ll::rocks_transactiondb_open_column_families(
+                options.raw(),
+                txn_db_options.raw(),
+                dbname.as_ptr(),
+                num_column_families as c_int,
+                cfnames.as_ptr(),
+                cfopts.as_ptr(),
+                cfhandles.as_mut_ptr(),
+                &mut status,
+            );
+            Error::from_ll(status).map(|_| {
+                let db = DB::from_ll(db_ptr)
+                    .with_comparators(cfs.iter().filter_map(|cf| cf.options.comparator_handle()));
+                let db_ref = db.context.clone();
+                (
+                    TransactionDB { db },
+                    cfhandles
+                        .into_iter()
+                        .map(|p| ColumnFamily::register(&db_ref, p))
+                        .collect(),
+                )
+            })
+        }
+    }
+
+    /// Start a transaction.
+    pub fn begin_transaction(&self, write_options: &WriteOptions, txn_options: &TransactionOptions) -> Transaction<'_> {
+        unsafe {
+            Transaction::from_ll(ll::rocks_transactiondb_begin_transaction(
+                self.raw(),
+                write_options.raw(),
+                txn_options.raw(),
+            ))
+        }
+    }
+
+    /// Transactions prepared but neither committed nor rolled back, recovered from the WAL
+    /// when the DB is opened.
+    ///
+    /// Each of them should be committed or rolled back, by the coordinator of the two-phase commit.
+    pub fn get_all_prepared_transactions(&self) -> Vec<Transaction<'_>> {
+        let mut count = 0;
+        unsafe {
+            let txns = ll::rocks_transactiondb_get_all_prepared_transactions(self.raw(), &mut count);
+            let ret = slice::from_raw_parts(txns, count)
+                .iter()
+                .map(|&raw| Transaction::from_ll(raw))
+                .collect();
+            ll::rocks_transaction_array_destroy(txns);
+            ret
+        }
+    }
+}
+
+/// A DB with optimistic transactions, i.e. `OptimisticTransactionDB` of RocksDB.
+///
+/// `Transaction`s take no locks, conflicting writes are detected when they commit, which then
+/// fails with `Busy`. Suits workloads with rare conflicts.
+pub struct OptimisticTransactionDB {
+    db: DB,
+}
+
+impl ops::Deref for OptimisticTransactionDB {
+    type Target = DB;
+
+    fn deref(&self) -> &DB {
+        &self.db
+    }
+}
+
+impl fmt::Debug for OptimisticTransactionDB {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.debug_struct("OptimisticTransactionDB")
+            .field("name", &self.name())
+            .finish()
+    }
+}
+
+impl OptimisticTransactionDB {
+    /// Open the database with the specified `name`.
+    pub fn open<T: AsRef<Options>, P: AsRef<Path>>(options: T, name: P) -> Result<OptimisticTransactionDB> {
+        let dbname = CString::new(path_to_bytes(name)).unwrap();
+        let mut status = ptr::null_mut::<ll::rocks_status_t>();
+        unsafe {
+            let db_ptr = ll::rocks_optimistic_transactiondb_open(options.as_ref().raw(), dbname.as_ptr(), &mut status);
+            Error::from_ll(status).map(|_| OptimisticTransactionDB {
+                db: DB::from_ll(db_ptr).with_comparators(options.as_ref().comparator_handle()),
+            })
+        }
+    }
+
+    /// Open DB with column families.
+    pub fn open_with_column_families<CF: Into<ColumnFamilyDescriptor>, P: AsRef<Path>, I: IntoIterator<Item = CF>>(
+        options: &DBOptions,
+        name: P,
+        column_families: I,
+    ) -> Result<(OptimisticTransactionDB, Vec<ColumnFamily>)> {
+        let dbname = CString::new(path_to_bytes(name)).unwrap();
+
+        let cfs = column_families
+            .into_iter()
+            .map(|desc| desc.into())
+            .collect::<Vec<ColumnFamilyDescriptor>>();
+
+        let num_column_families = cfs.len();
+        // for ffi
+        let mut cfnames: Vec<*const c_char> = Vec::with_capacity(num_column_families);
+        let mut cfopts: Vec<*const ll::rocks_cfoptions_t> = Vec::with_capacity(num_column_families);
+        let mut cfhandles = vec![ptr::null_mut(); num_column_families];
+
+        for cf in &cfs {
+            cfnames.push(cf.name_as_ptr());
+            cfopts.push(cf.options.raw());
+        }
+
+        let mut status = ptr::null_mut::<ll::rocks_status_t>();
+        unsafe {
+            let db_ptr = ll::rocks_optimistic_transactiondb_open_column_families(
+                options.raw(),
+                dbname.as_ptr(),
+                num_column_families as c_int,
+                cfnames.as_ptr(),
+                cfopts.as_ptr(),
+                cfhandles.as_mut_ptr(),
+                &mut status,
+            );
+            Error::from_ll(status).map(|_| {
+                let db = DB::from_ll(db_ptr)
+                    .with_comparators(cfs.iter().filter_map(|cf| cf.options.comparator_handle()));
+                let db_ref = db.context.clone();
+                (
+                    OptimisticTransactionDB { db },
+                    cfhandles
+                        .into_iter()
+                        .map(|p| ColumnFamily::register(&db_ref, p))
+                        .collect(),
+                )
+            })
+        }
+    }
+
+    /// Start a transaction.
+    pub fn begin_transaction(
+        &self,
+        write_options: &WriteOptions,
+        txn_options: &OptimisticTransactionOptions,
+    ) -> Transaction<'_> {
+        unsafe {
+            Transaction::from_ll(ll::rocks_optimistic_transactiondb_begin_transaction(
+                self.raw(),
+                write_options.raw(),
+                txn_options.raw(),
+            ))
+        }
+    }
+}
+
+/// A column family of a `ReadOnlyDB` or `SecondaryDB`, with only the read methods of `ColumnFamily`.
+#[derive(Clone)]
+pub struct ReadOnlyColumnFamily {
+    cf: ColumnFamily,
+}
+
+impl AsRef<ColumnFamilyHandle> for ReadOnlyColumnFamily {
+    fn as_ref(&self) -> &ColumnFamilyHandle {
+        self.cf.as_ref()
+    }
+}
+
+impl ops::Deref for ReadOnlyColumnFamily {
+    type Target = ColumnFamilyHandle;
+    fn deref(&self) -> &ColumnFamilyHandle {
+        &self.cf
+    }
+}
+
+impl fmt::Debug for ReadOnlyColumnFamily {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.debug_struct("ReadOnlyColumnFamily")
+            .field("db", &self.cf.db.name())
+            .field("name", &self.name())
+            .field("id", &self.id())
+            .finish()
+    }
+}
+
+impl ReadOnlyColumnFamily {
+    pub fn get(&self, options: &ReadOptions, key: &[u8]) -> Result<PinnableSlice> {
+        self.cf.get(options, key)
+    }
+
+    pub fn get_opt(&self, options: &ReadOptions, key: &[u8]) -> Result<Option<PinnableSlice>> {
+        self.cf.get_opt(options, key)
+    }
+
+    pub fn get_pinned(&self, options: &ReadOptions, key: &[u8], value: &mut PinnableSlice) -> Result<()> {
+        self.cf.get_pinned(options, key, value)
+    }
+
+    pub fn get_into(&self, options: &ReadOptions, key: &[u8], buf: &mut Vec<u8>) -> Result<Option<usize>> {
+        self.cf.get_into(options, key, buf)
+    }
+
+    pub fn multi_get(&self, options: &ReadOptions, keys: &[&[u8]]) -> Vec<Result<PinnableSlice>> {
+        self.cf.multi_get(options, keys)
+    }
+
+    pub fn multi_get_opt(&self, options: &ReadOptions, keys: &[&[u8]]) -> Vec<Result<Option<PinnableSlice>>> {
+        self.cf.multi_get_opt(options, keys)
+    }
+
+    pub fn key_may_exist(&self, options: &ReadOptions, key: &[u8]) -> bool {
+        self.cf.key_may_exist(options, key)
+    }
+
+    pub fn key_may_get(&self, options: &ReadOptions, key: &[u8]) -> (bool, Option<Vec<u8>>) {
+        self.cf.key_may_get(options, key)
+    }
+
+    pub fn new_iterator(&self, options: &ReadOptions) -> Iterator<'_> {
+        self.cf.new_iterator(options)
+    }
+
+    pub fn prefix_iterator(&self, options: &ReadOptions, prefix: &[u8]) -> Iterator<'_> {
+        self.cf.prefix_iterator(options, prefix)
+    }
+
+    pub fn get_property(&self, property: &str) -> Option<String> {
+        self.cf.get_property(property)
+    }
+
+    pub fn get_int_property(&self, property: &str) -> Option<u64> {
+        self.cf.get_int_property(property)
+    }
+
+    pub fn metadata(&self) -> ColumnFamilyMetaData {
+        self.cf.metadata()
+    }
+
+    /// The underlying column family, with write methods that fail at runtime.
+    pub fn into_column_family(self) -> ColumnFamily {
+        self.cf
+    }
+}
+
+/// A DB opened for read only, with only the read methods of `DB`.
+///
+/// Unlike `DB::open_for_readonly()`, writes to it are rejected at compile time.
+/// No compactions or flushes happen, writes of other processes after open are
+/// not seen, see `SecondaryDB` for that.
+pub struct ReadOnlyDB {
+    db: DB,
+}
+
+impl fmt::Debug for ReadOnlyDB {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.debug_struct("ReadOnlyDB").field("name", &self.name()).finish()
+    }
+}
+
+/// A read-only view of a DB, e.g. to hand to readers.
+impl From<DB> for ReadOnlyDB {
+    fn from(db: DB) -> ReadOnlyDB {
+        ReadOnlyDB { db }
+    }
+}
+
+impl ReadOnlyDB {
+    /// Open the database for read only, see `DB::open_for_readonly()`.
+    pub fn open<P: AsRef<Path>>(options: &Options, name: P, error_if_log_file_exist: bool) -> Result<ReadOnlyDB> {
+        DB::open_for_readonly(options, name, error_if_log_file_exist).map(ReadOnlyDB::from)
+    }
+
+    /// Open the database for read only with a subset of column families, see
+    /// `DB::open_for_readonly_with_column_families()`.
+    pub fn open_with_column_families<
+        CF: Into<ColumnFamilyDescriptor>,
+        P: AsRef<Path>,
+        I: IntoIterator<Item = CF>,
+    >(
+        options: &DBOptions,
+        name: P,
+        column_families: I,
+        error_if_log_file_exist: bool,
+    ) -> Result<(ReadOnlyDB, Vec<ReadOnlyColumnFamily>)> {
+        DB::open_for_readonly_with_column_families(options, name, column_families, error_if_log_file_exist)
+            .map(|(db, cfs)| (ReadOnlyDB::from(db), read_only_column_families(cfs)))
+    }
+
+    pub fn name(&self) -> String {
+        self.db.name()
+    }
+
+    pub fn default_column_family(&self) -> ReadOnlyColumnFamily {
+        ReadOnlyColumnFamily {
+            cf: self.db.default_column_family(),
+        }
+    }
+
+    /// An opened column family by name, see `DB::column_family()`.
+    pub fn column_family(&self, name: &str) -> Option<ReadOnlyColumnFamily> {
+        self.db.column_family(name).map(|cf| ReadOnlyColumnFamily { cf })
+    }
+
+    /// All opened column families, see `DB::column_families()`.
+    pub fn column_families(&self) -> Vec<ReadOnlyColumnFamily> {
+        read_only_column_families(self.db.column_families())
+    }
+
+    pub fn get(&self, options: &ReadOptions, key: &[u8]) -> Result<PinnableSlice> {
+        self.db.get(options, key)
+    }
+
+    pub fn get_opt(&self, options: &ReadOptions, key: &[u8]) -> Result<Option<PinnableSlice>> {
+        self.db.get_opt(options, key)
+    }
+
+    pub fn get_pinned(&self, options: &ReadOptions, key: &[u8], value: &mut PinnableSlice) -> Result<()> {
+        self.db.get_pinned(options, key, value)
+    }
+
+    pub fn get_into(&self, options: &ReadOptions, key: &[u8], buf: &mut Vec<u8>) -> Result<Option<usize>> {
+        self.db.get_into(options, key, buf)
+    }
+
+    pub fn get_cf(
+        &self,
+        options: &ReadOptions,
+        column_family: &ColumnFamilyHandle,
+        key: &[u8],
+    ) -> Result<PinnableSlice> {
+        self.db.get_cf(options, column_family, key)
+    }
+
+    pub fn get_cf_opt(
+        &self,
+        options: &ReadOptions,
+        column_family: &ColumnFamilyHandle,
+        key: &[u8],
+    ) -> Result<Option<PinnableSlice>> {
+        self.db.get_cf_opt(options, column_family, key)
+    }
+
+    pub fn multi_get(&self, options: &ReadOptions, keys: &[&[u8]]) -> Vec<Result<PinnableSlice>> {
+        self.db.multi_get(options, keys)
+    }
+
+    pub fn multi_get_opt(&self, options: &ReadOptions, keys: &[&[u8]]) -> Vec<Result<Option<PinnableSlice>>> {
+        self.db.multi_get_opt(options, keys)
+    }
+
+    pub fn multi_get_cf(
+        &self,
+        options: &ReadOptions,
+        column_families: &[&ColumnFamilyHandle],
+        keys: &[&[u8]],
+    ) -> Vec<Result<PinnableSlice>> {
+        self.db.multi_get_cf(options, column_families, keys)
+    }
+
+    pub fn multi_get_cf_opt(
+        &self,
+        options: &ReadOptions,
+        column_families: &[&ColumnFamilyHandle],
+        keys: &[&[u8]],
+    ) -> Vec<Result<Option<PinnableSlice>>> {
+        self.db.multi_get_cf_opt(options, column_families, keys)
+    }
+
+    pub fn key_may_exist(&self, options: &ReadOptions, key: &[u8]) -> bool {
+        self.db.key_may_exist(options, key)
+    }
+
+    pub fn key_may_get(&self, options: &ReadOptions, key: &[u8]) -> (bool, Option<Vec<u8>>) {
+        self.db.key_may_get(options, key)
+    }
+
+    pub fn new_iterator<'c, 'd: 'c>(&'d self, options: &ReadOptions) -> Iterator<'c> {
+        self.db.new_iterator(options)
+    }
+
+    pub fn new_iterator_cf<'c, 'd: 'c>(&self, options: &ReadOptions, cf: &'d ColumnFamilyHandle) -> Iterator<'c> {
+        self.db.new_iterator_cf(options, cf)
+    }
+
+    pub fn new_iterators<'c, 'b: 'c, T: AsRef<ColumnFamilyHandle>>(
+        &'b self,
+        options: &ReadOptions,
+        cfs: &[T],
+    ) -> Result<Vec<Iterator<'c>>> {
+        self.db.new_iterators(options, cfs)
+    }
+
+    pub fn new_merged_iterator<'c, 'b: 'c, T: AsRef<ColumnFamilyHandle>>(
+        &'b self,
+        options: &ReadOptions,
+        cfs: &[T],
+    ) -> Result<MergedIterator<'c>> {
+        self.db.new_merged_iterator(options, cfs)
+    }
+
+    pub fn get_snapshot(&self) -> Option<Snapshot<'_>> {
+        self.db.get_snapshot()
+    }
+
+    pub fn release_snapshot(&self, snapshot: Snapshot) {
+        self.db.release_snapshot(snapshot)
+    }
+
+    pub fn get_snapshot_if_latest(&self, seq: SequenceNumber) -> Result<Snapshot<'_>> {
+        self.db.get_snapshot_if_latest(seq)
+    }
+
+    pub fn get_property(&self, property: &str) -> Option<String> {
+        self.db.get_property(property)
+    }
+
+    pub fn get_int_property(&self, property: &str) -> Option<u64> {
+        self.db.get_int_property(property)
+    }
+
+    pub fn get_map_property(&self, property: &str) -> Option<HashMap<String, String>> {
+        self.db.get_map_property(property)
+    }
+
+    pub fn get_latest_sequence_number(&self) -> SequenceNumber {
+        self.db.get_latest_sequence_number()
+    }
+
+    pub fn get_db_identity(&self) -> Result<String> {
+        self.db.get_db_identity()
+    }
+
+    pub fn verify_checksum(&self) -> Result<()> {
+        self.db.verify_checksum()
+    }
+
+    pub fn get_live_files_metadata(&self) -> Vec<LiveFileMetaData> {
+        self.db.get_live_files_metadata()
+    }
+
+    pub fn get_column_family_metadata(&self, column_family: &ColumnFamilyHandle) -> ColumnFamilyMetaData {
+        self.db.get_column_family_metadata(column_family)
+    }
+
+    /// The underlying DB, with write methods that fail at runtime.
+    pub fn into_db(self) -> DB {
+        self.db
+    }
+}
+
+/// Maps a `NotFound` error to `Ok(None)`.
+fn not_found_to_none<T>(ret: Result<T>) -> Result<Option<T>> {
+    match ret {
+        Ok(value) => Ok(Some(value)),
+        Err(ref e) if e.is_not_found() => Ok(None),
+        Err(e) => Err(e),
+    }
+}
+
+fn read_only_column_families(cfs: Vec<ColumnFamily>) -> Vec<ReadOnlyColumnFamily> {
+    cfs.into_iter().map(|cf| ReadOnlyColumnFamily { cf }).collect()
+}
+
+/// A secondary instance of a DB, a `ReadOnlyDB` that can catch up with the primary.
+///
+/// The secondary keeps its own info log and MANIFEST copy in `secondary_path`.
+pub struct SecondaryDB {
+    db: ReadOnlyDB,
+}
+
+impl ops::Deref for SecondaryDB {
+    type Target = ReadOnlyDB;
+
+    fn deref(&self) -> &ReadOnlyDB {
+        &self.db
+    }
+}
+
+impl fmt::Debug for SecondaryDB {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.debug_struct("SecondaryDB").field("name", &self.name()).finish()
+    }
+}
+
+impl SecondaryDB {
+    /// Open DB as secondary instance with only the default column family, see `DB::open_as_secondary()`.
+    pub fn open<P1: AsRef<Path>, P2: AsRef<Path>>(
+        options: &Options,
+        name: P1,
+        secondary_path: P2,
+    ) -> Result<SecondaryDB> {
+        DB::open_as_secondary(options, name, secondary_path).map(|db| SecondaryDB { db: db.into() })
+    }
+
+    /// Open DB as secondary instance with a subset of column families, see
+    /// `DB::open_as_secondary_with_column_families()`.
+    pub fn open_with_column_families<
+        P1: AsRef<Path>,
+        P2: AsRef<Path>,
+        CF: Into<ColumnFamilyDescriptor>,
+        I: IntoIterator<Item = CF>,
+    >(
+        options: &DBOptions,
+        name: P1,
+        secondary_path: P2,
+        column_families: I,
+    ) -> Result<(SecondaryDB, Vec<ReadOnlyColumnFamily>)> {
+        DB::open_as_secondary_with_column_families(options, name, secondary_path, column_families)
+            .map(|(db, cfs)| (SecondaryDB { db: db.into() }, read_only_column_families(cfs)))
+    }
+
+    /// See `DBRef::try_catch_up_with_primary()`.
+    pub fn try_catch_up_with_primary(&self) -> Result<()> {
+        self.db.db.try_catch_up_with_primary()
+    }
+
+    /// See `DB::spawn_catch_up()`.
+    pub fn spawn_catch_up(&self, interval: Duration) -> CatchUpHandle {
+        self.db.db.spawn_catch_up(interval)
+    }
+
+    /// The underlying DB, with write methods that fail at runtime.
+    pub fn into_db(self) -> DB {
+        self.db.db
+    }
+}
+
+impl DBRef {
+    /// Long-running operations, manual compactions, file ingestion and imports,
+    /// checkpoints and exports, currently in flight through this DB handle.
+    pub fn operations(&self) -> Vec<OperationInfo> {
+        self.operations.list()
+    }
+
+    /// Cancel a running operation, returns false if it is not running or not cancellable.
+    ///
+    /// This is not scoped to the operation: RocksDB can only pause manual compactions
+    /// of the whole DB, via `DisableManualCompaction()`. Cancelling a `compact_range()`
+    /// also fails every other manual compaction of the DB with `Incomplete`, on any
+    /// column family, including ones started before the cancelled operation returns.
+    /// Manual compaction is re-enabled once all cancelled operations have returned.
+    pub fn cancel_operation(&self, id: OperationId) -> bool {
+        self.operations
+            .request_cancel(id, || unsafe { ll::rocks_db_disable_manual_compaction(self.raw) })
+            .is_some()
+    }
+
+    pub(crate) fn track<R, F: FnOnce() -> R>(
+        &self,
+        kind: OperationKind,
+        column_family: &ColumnFamilyHandle,
+        num_files: Option<usize>,
+        f: F,
+    ) -> R {
+        self.track_named(kind, Some(column_family.name().to_owned()), num_files, f)
+    }
+
+    pub(crate) fn track_default<R, F: FnOnce() -> R>(&self, kind: OperationKind, num_files: Option<usize>, f: F) -> R {
+        self.track_named(kind, None, num_files, f)
+    }
+
+    fn track_named<R, F: FnOnce() -> R>(
+        &self,
+        kind: OperationKind,
+        column_family: Option<String>,
+        num_files: Option<usize>,
+        f: F,
+    ) -> R {
+        let _guard = OperationGuard {
+            db: self,
+            id: self.operations.start(kind, column_family, num_files),
+        };
+        f()
+    }
+
+    /// Returns default column family handle
+    fn raw_default_column_family(&self) -> *mut ll::rocks_column_family_handle_t {
+        unsafe { ll::rocks_db_default_column_family(self.raw()) }
+    }
+
+    /// Close the DB by releasing resources, closing files etc. This should be
+    /// called before calling the destructor so that the caller can get back a
+    /// status in case there are any errors. This will not fsync the WAL files.
+    /// If syncing is required, the caller must first call SyncWAL(), or Write()
+    /// using an empty write batch with WriteOptions.sync=true.
+    ///
+    /// If the return status is Aborted(), closing fails because there is
+    /// unreleased snapshot in the system. In this case, users can release
+    /// the unreleased snapshots and try again and expect it to succeed. For
+    /// other status, recalling Close() will be no-op.
+    ///
+    /// If the return status is NotSupported(), then the DB implementation does
+    /// cleanup in the destructor
+    ///
+    /// NOTE for Rust: segmentation fault if the db is accessed after close
+    pub unsafe fn close(&self) -> Result<()> {
+        let mut status = ptr::null_mut::<ll::rocks_status_t>();
+        ll::rocks_db_close(self.raw(), &mut status);
+        Error::from_ll(status)
+    }
+
+    /// Manually resume the DB and put it in read-write mode.
+    /// This function will flush memtables for all the column families,
+    /// clear the error, purge any obsolete files, and restart
+    /// background flush and compaction operations.
+    pub fn resume(&self) -> Result<()> {
+        let mut status = ptr::null_mut::<ll::rocks_status_t>();
+        unsafe {
+            ll::rocks_db_resume(self.raw(), &mut status);
+        }
+        Error::from_ll(status)
+    }
+
+    /// Set the database entry for `"key"` to `"value"`.
+    /// If `"key"` already exists, it will be overwritten.
+    /// Returns OK on success, and a non-OK status on error.
+    ///
+    /// Note: consider setting `options.sync = true`.
+    pub fn put(&self, options: &WriteOptions, key: &[u8], value: &[u8]) -> Result<()> {
+        let mut status = ptr::null_mut::<ll::rocks_status_t>();
+        unsafe {
+            ll::rocks_db_put(
+                self.raw(),
+                options.raw(),
+                key.as_ptr() as *const _,
+                key.len(),
+                value.as_ptr() as *const _,
+                value.len(),
+                &mut status,
+            );
+            Error::from_ll(status)
+        }
+    }
+
+    pub fn put_cf(
+        &self,
+        options: &WriteOptions,
+        column_family: &ColumnFamilyHandle,
+        key: &[u8],
+        value: &[u8],
     ) -> Result<()> {
         let mut status = ptr::null_mut::<ll::rocks_status_t>();
         unsafe {
@@ -1268,6 +2712,80 @@ impl DBRef {
         }
     }
 
+    /// Atomic read-modify-write across column families, without a TransactionDB.
+    ///
+    /// `keys` are `(cf, key)` pairs, `cf` being an index into `column_families`. They
+    /// are read under a snapshot and handed to `f`, in order, which returns the writes
+    /// to apply. Before the writes are committed as a single `WriteBatch`, the keys are
+    /// checked to be unchanged since the snapshot. On conflict, the whole read-compute
+    /// cycle is retried, up to `TRANSACTIONAL_UPDATE_MAX_ATTEMPTS` times, then `Busy`
+    /// is returned. `f` may be called several times. A `cf` index out of range fails
+    /// with `InvalidArgument`, nothing is written.
+    ///
+    /// Conflicts between concurrent `transactional_update()` calls on the same DB are
+    /// always detected. A plain write landing between the check and the commit is not,
+    /// use a `TransactionDB` if that matters.
+    pub fn transactional_update<F>(
+        &self,
+        options: &WriteOptions,
+        column_families: &[&ColumnFamilyHandle],
+        keys: &[(usize, &[u8])],
+        mut f: F,
+    ) -> Result<()>
+    where
+        F: FnMut(&[Option<Vec<u8>>]) -> Vec<UpdateOp>,
+    {
+        for _ in 0..TRANSACTIONAL_UPDATE_MAX_ATTEMPTS {
+            let snapshot = match self.get_snapshot() {
+                Some(snapshot) => snapshot,
+                None => return Err(Error::busy("transactional_update: can not take snapshot")),
+            };
+            let seqno = snapshot.get_sequence_number();
+            let values = {
+                let ropts = ReadOptions::default().snapshot(Some(&snapshot));
+                self.read_update_keys(&ropts, column_families, keys)
+            };
+            self.release_snapshot(snapshot);
+            let values = values?;
+
+            let mut batch = WriteBatch::new();
+            for op in f(&values) {
+                match op {
+                    UpdateOp::Put { cf, key, value } => batch.put_cf(update_cf(column_families, cf)?, &key, &value),
+                    UpdateOp::Delete { cf, key } => batch.delete_cf(update_cf(column_families, cf)?, &key),
+                    UpdateOp::Merge { cf, key, value } => batch.merge_cf(update_cf(column_families, cf)?, &key, &value),
+                };
+            }
+
+            let _guard = self.update_lock.lock().unwrap();
+            // fast path, nothing at all written since the snapshot
+            if self.get_latest_sequence_number() != seqno
+                && self.read_update_keys(ReadOptions::default_instance(), column_families, keys)? != values
+            {
+                continue;
+            }
+            if batch.count() == 0 {
+                return Ok(());
+            }
+            return self.write(options, &batch);
+        }
+        Err(Error::busy("transactional_update: too many conflicts"))
+    }
+
+    fn read_update_keys(
+        &self,
+        options: &ReadOptions,
+        column_families: &[&ColumnFamilyHandle],
+        keys: &[(usize, &[u8])],
+    ) -> Result<Vec<Option<Vec<u8>>>> {
+        keys.iter()
+            .map(|&(cf, key)| {
+                self.get_cf_opt(options, update_cf(column_families, cf)?, key)
+                    .map(|value| value.map(|v| v.to_vec()))
+            })
+            .collect()
+    }
+
     /// If the database contains an entry for "key" store the
     /// corresponding value in *value and return OK.
     ///
@@ -1276,43 +2794,118 @@ impl DBRef {
     ///
     /// May return some other Error on an error.
     pub fn get(&self, options: &ReadOptions, key: &[u8]) -> Result<PinnableSlice> {
+        let mut value = PinnableSlice::new();
+        self.get_pinned(options, key, &mut value).map(|_| value)
+    }
+
+    /// Like `get()`, but returns `Ok(None)` if there is no entry for `key`, so that
+    /// only real failures are errors.
+    pub fn get_opt(&self, options: &ReadOptions, key: &[u8]) -> Result<Option<PinnableSlice>> {
+        not_found_to_none(self.get(options, key))
+    }
+
+    /// Like `get()`, but reuses `value`, which is released first, to save an
+    /// allocation per read in hot loops.
+    pub fn get_pinned(&self, options: &ReadOptions, key: &[u8], value: &mut PinnableSlice) -> Result<()> {
+        let mut status = ptr::null_mut::<ll::rocks_status_t>();
+        value.release();
+        unsafe {
+            ll::rocks_db_get_pinnable(
+                self.raw(),
+                options.raw(),
+                key.as_ptr() as *const _,
+                key.len(),
+                value.raw(),
+                &mut status,
+            );
+        }
+        Error::from_ll(status)
+    }
+
+    pub fn get_cf(
+        &self,
+        options: &ReadOptions,
+        column_family: &ColumnFamilyHandle,
+        key: &[u8],
+    ) -> Result<PinnableSlice> {
+        let mut value = PinnableSlice::new();
+        self.get_cf_pinned(options, column_family, key, &mut value).map(|_| value)
+    }
+
+    /// Like `get_cf()`, but returns `Ok(None)` if there is no entry for `key`.
+    pub fn get_cf_opt(
+        &self,
+        options: &ReadOptions,
+        column_family: &ColumnFamilyHandle,
+        key: &[u8],
+    ) -> Result<Option<PinnableSlice>> {
+        not_found_to_none(self.get_cf(options, column_family, key))
+    }
+
+    /// Like `get_cf()`, but reuses `value`, see `get_pinned()`.
+    pub fn get_cf_pinned(
+        &self,
+        options: &ReadOptions,
+        column_family: &ColumnFamilyHandle,
+        key: &[u8],
+        value: &mut PinnableSlice,
+    ) -> Result<()> {
         let mut status = ptr::null_mut::<ll::rocks_status_t>();
-        // FIXME: should be mut
-        let pinnable_val = PinnableSlice::new();
+        value.release();
         unsafe {
-            ll::rocks_db_get_pinnable(
+            ll::rocks_db_get_cf_pinnable(
                 self.raw(),
                 options.raw(),
-                key.as_ptr() as *const _,
+                column_family.raw(),
+                key.as_ptr() as _,
                 key.len(),
-                pinnable_val.raw(),
+                value.raw(),
                 &mut status,
             );
-            Error::from_ll(status).map(|_| pinnable_val)
         }
+        Error::from_ll(status)
     }
 
-    pub fn get_cf(
+    /// Copy the value of `key` into `buf`, reusing its capacity, and return the value length,
+    /// or `None` if there is no entry for `key`, leaving `buf` unchanged.
+    ///
+    /// Unlike `get()`, no `PinnableSlice` is allocated, so that buffers can be pooled by
+    /// high-QPS point reads.
+    pub fn get_into(&self, options: &ReadOptions, key: &[u8], buf: &mut Vec<u8>) -> Result<Option<usize>> {
+        self.get_into_raw(options, ptr::null_mut(), key, buf)
+    }
+
+    /// Like `get_into()`, in a column family.
+    pub fn get_cf_into(
         &self,
         options: &ReadOptions,
         column_family: &ColumnFamilyHandle,
         key: &[u8],
-    ) -> Result<PinnableSlice> {
+        buf: &mut Vec<u8>,
+    ) -> Result<Option<usize>> {
+        self.get_into_raw(options, column_family.raw(), key, buf)
+    }
+
+    fn get_into_raw(
+        &self,
+        options: &ReadOptions,
+        column_family: *mut ll::rocks_column_family_handle_t,
+        key: &[u8],
+        buf: &mut Vec<u8>,
+    ) -> Result<Option<usize>> {
         let mut status = ptr::null_mut::<ll::rocks_status_t>();
-        // FIXME: should be mut
-        let pinnable_val = PinnableSlice::new();
-        unsafe {
-            ll::rocks_db_get_cf_pinnable(
+        let found = unsafe {
+            ll::rocks_db_get_into(
                 self.raw(),
                 options.raw(),
-                column_family.raw(),
+                column_family,
                 key.as_ptr() as _,
                 key.len(),
-                pinnable_val.raw(),
+                buf as *mut Vec<u8> as *mut c_void,
                 &mut status,
-            );
-            Error::from_ll(status).map(|_| pinnable_val)
-        }
+            )
+        };
+        Error::from_ll(status).map(|_| if found != 0 { Some(buf.len()) } else { None })
     }
 
     /// If keys[i] does not exist in the database, then the i'th returned
@@ -1394,6 +2987,24 @@ impl DBRef {
             .collect()
     }
 
+    /// Like `multi_get()`, but keys with no entry are `Ok(None)`.
+    pub fn multi_get_opt(&self, options: &ReadOptions, keys: &[&[u8]]) -> Vec<Result<Option<PinnableSlice>>> {
+        self.multi_get(options, keys).into_iter().map(not_found_to_none).collect()
+    }
+
+    /// Like `multi_get_cf()`, but keys with no entry are `Ok(None)`.
+    pub fn multi_get_cf_opt(
+        &self,
+        options: &ReadOptions,
+        column_families: &[&ColumnFamilyHandle],
+        keys: &[&[u8]],
+    ) -> Vec<Result<Option<PinnableSlice>>> {
+        self.multi_get_cf(options, column_families, keys)
+            .into_iter()
+            .map(not_found_to_none)
+            .collect()
+    }
+
     /// If the key definitely does not exist in the database, then this method
     /// returns false, else true. If the caller wants to obtain value when the key
     /// is found in memory, a bool for 'value_found' must be passed. 'value_found'
@@ -1480,6 +3091,43 @@ impl DBRef {
         }
     }
 
+    /// `key_may_get()` as a `KeyLookup`.
+    pub fn key_lookup(&self, options: &ReadOptions, key: &[u8]) -> KeyLookup {
+        self.key_may_get(options, key).into()
+    }
+
+    /// `key_may_get_cf()` as a `KeyLookup`.
+    pub fn key_lookup_cf(&self, options: &ReadOptions, column_family: &ColumnFamilyHandle, key: &[u8]) -> KeyLookup {
+        self.key_may_get_cf(options, column_family, key).into()
+    }
+
+    /// Whether `key` exists, without IO when filters rule it out or the value is in memory.
+    ///
+    /// With `confirm`, a key that may exist is read to rule out filter false positives.
+    /// Without it, only filters and memory are checked, and false positives return `true`.
+    pub fn key_exists(&self, options: &ReadOptions, key: &[u8], confirm: bool) -> Result<bool> {
+        match self.key_lookup(options, key) {
+            KeyLookup::MaybeExists if confirm => self.get_opt(options, key).map(|v| v.is_some()),
+            lookup => Ok(lookup.may_exist()),
+        }
+    }
+
+    /// Like `key_exists()`, in a column family.
+    pub fn key_exists_cf(
+        &self,
+        options: &ReadOptions,
+        column_family: &ColumnFamilyHandle,
+        key: &[u8],
+        confirm: bool,
+    ) -> Result<bool> {
+        match self.key_lookup_cf(options, column_family, key) {
+            KeyLookup::MaybeExists if confirm => self
+                .get_cf_opt(options, column_family, key)
+                .map(|v| v.is_some()),
+            lookup => Ok(lookup.may_exist()),
+        }
+    }
+
     /// Return a heap-allocated iterator over the contents of the database.
     /// The result of NewIterator() is initially invalid (caller must
     /// call one of the Seek methods on the iterator before using it).
@@ -1493,6 +3141,68 @@ impl DBRef {
         }
     }
 
+    /// `ColumnFamily::scan_rate_limited()` on the default column family.
+    pub fn scan_rate_limited<'c, 'd: 'c, 'k, R: ops::RangeBounds<&'k [u8]>>(
+        &'d self,
+        range: R,
+        bytes_per_sec: u64,
+    ) -> RateLimitedIterator<'c> {
+        RateLimitedIterator::new(
+            self.new_iterator(&ReadOptions::default().fill_cache(false)),
+            range,
+            bytes_per_sec,
+        )
+    }
+
+    /// An iterator over the keys starting with `prefix`, positioned at the first one.
+    ///
+    /// Picks the seek mode from the prefix extractor of the column family: a prefix seek,
+    /// using prefix bloom filters, when every key starting with `prefix` has `prefix` as
+    /// its extracted prefix, e.g. a fixed length extractor and a prefix of that length.
+    /// Otherwise, e.g. a shorter prefix or no extractor, a total order seek. Either way,
+    /// iteration stops at the end of the prefix, `iterate_upper_bound` is set to the first
+    /// key past it.
+    ///
+    /// `total_order_seek`, `prefix_same_as_start` and `iterate_upper_bound` of `options`
+    /// are overridden, other options apply.
+    pub fn prefix_iterator<'c, 'd: 'c>(&'d self, options: &ReadOptions, prefix: &[u8]) -> Iterator<'c> {
+        unsafe { self.create_prefix_iterator(options, ptr::null_mut(), prefix) }
+    }
+
+    /// `prefix_iterator()` over a column family.
+    pub fn prefix_iterator_cf<'c, 'd: 'c>(
+        &'d self,
+        options: &ReadOptions,
+        cf: &ColumnFamilyHandle,
+        prefix: &[u8],
+    ) -> Iterator<'c> {
+        unsafe { self.create_prefix_iterator(options, cf.raw(), prefix) }
+    }
+
+    unsafe fn create_prefix_iterator<'c>(
+        &self,
+        options: &ReadOptions,
+        cf: *mut ll::rocks_column_family_handle_t,
+        prefix: &[u8],
+    ) -> Iterator<'c> {
+        let ptr = ll::rocks_db_create_prefix_iterator_cf(
+            self.raw(),
+            options.raw(),
+            cf,
+            prefix.as_ptr() as *const _,
+            prefix.len(),
+        );
+        // already at prefix, from_ll() would move an exhausted one to the first key
+        Iterator::from_ll_positioned(ptr)
+    }
+
+    /// A tailing iterator over the default column family, see `ReadOptions::tailing()`.
+    ///
+    /// It sees writes made after it is created, for following a key range as it grows.
+    pub fn new_tailing_iterator<'c, 'd: 'c>(&'d self) -> Iterator<'c> {
+        self.new_iterator(&ReadOptions::default().tailing(true))
+    }
+
     pub fn new_iterator_cf<'c, 'd: 'c>(&self, options: &ReadOptions, cf: &'d ColumnFamilyHandle) -> Iterator<'c> {
         unsafe {
             let ptr = ll::rocks_db_create_iterator_cf(self.raw(), options.raw(), cf.raw());
@@ -1522,6 +3232,16 @@ impl DBRef {
         }
     }
 
+    /// Iterators of `cfs` merged into one, in global key order. Entries are tagged with the
+    /// index of their column family in `cfs`. See `MergedIterator`.
+    pub fn new_merged_iterator<'c, 'b: 'c, T: AsRef<ColumnFamilyHandle>>(
+        &'b self,
+        options: &ReadOptions,
+        cfs: &[T],
+    ) -> Result<MergedIterator<'c>> {
+        self.new_iterators(options, cfs).map(MergedIterator::new)
+    }
+
     /// Return a handle to the current DB state.  Iterators created with
     /// this handle will all observe a stable snapshot of the current DB
     /// state.  The caller must call ReleaseSnapshot(result) when the
@@ -1548,6 +3268,34 @@ impl DBRef {
         }
     }
 
+    /// A snapshot of the latest state, if it is as of sequence number `seq`, e.g. for
+    /// checking that a replica read up to `seq` matches its primary.
+    ///
+    /// RocksDB can not take snapshots of past states. Fails with `NotSupported` once writes
+    /// later than `seq` are made, and with `InvalidArgument` if `seq` is not written yet. To
+    /// read as of `seq` later on, take a snapshot with `get_snapshot()` at that time and keep it.
+    pub fn get_snapshot_if_latest(&self, seq: SequenceNumber) -> Result<Snapshot<'_>> {
+        let snapshot = self
+            .get_snapshot()
+            .ok_or_else(|| Error::not_supported("snapshots are not supported"))?;
+        let latest = snapshot.get_sequence_number();
+        if latest.0 == seq.0 {
+            return Ok(snapshot);
+        }
+        self.release_snapshot(snapshot);
+        if latest.0 > seq.0 {
+            Err(Error::not_supported(&format!(
+                "can not read as of sequence {}, the latest is {}",
+                seq, latest
+            )))
+        } else {
+            Err(Error::invalid_argument(&format!(
+                "sequence {} is not written yet, the latest is {}",
+                seq, latest
+            )))
+        }
+    }
+
     /// DB implementations can export properties about their state via this method.
     /// If "property" is a valid property understood by this DB implementation (see
     /// Properties struct above for valid options), fills "*value" with its current
@@ -1587,9 +3335,84 @@ impl DBRef {
         }
     }
 
-    // TODO:
-    pub fn get_map_property(&self, property: &str) -> Option<()> {
-        unimplemented!()
+    /// Same as `get_property()`, but for properties returning a map, like
+    /// `"rocksdb.cfstats"`.
+    pub fn get_map_property(&self, property: &str) -> Option<HashMap<String, String>> {
+        let default_cf = ColumnFamilyHandle {
+            raw: self.raw_default_column_family(),
+        };
+        self.get_map_property_cf(&default_cf, property)
+    }
+
+    pub fn get_map_property_cf(
+        &self,
+        column_family: &ColumnFamilyHandle,
+        property: &str,
+    ) -> Option<HashMap<String, String>> {
+        unsafe {
+            let keys = ll::cxx_string_vector_create();
+            let values = ll::cxx_string_vector_create();
+            let ok = ll::rocks_db_get_map_property_cf(
+                self.raw(),
+                column_family.raw(),
+                property.as_bytes().as_ptr() as *const _,
+                property.len(),
+                keys,
+                values,
+            ) != 0;
+            let ret = if ok {
+                let nth = |v, i| {
+                    let s = slice::from_raw_parts(
+                        ll::cxx_string_vector_nth(v, i) as *const u8,
+                        ll::cxx_string_vector_nth_size(v, i),
+                    );
+                    String::from_utf8_lossy(s).into_owned()
+                };
+                Some(
+                    (0..ll::cxx_string_vector_size(keys))
+                        .map(|i| (nth(keys, i), nth(values, i)))
+                        .collect(),
+                )
+            } else {
+                None
+            };
+            ll::cxx_string_vector_destory(keys);
+            ll::cxx_string_vector_destory(values);
+            ret
+        }
+    }
+
+    /// Parsed `"rocksdb.levelstats"` of the default column family.
+    pub fn levelstats(&self) -> Option<Vec<LevelStats>> {
+        self.get_property(properties::LEVELSTATS)
+            .and_then(|s| LevelStats::parse(&s))
+    }
+
+    /// Parsed `"rocksdb.levelstats"` of a column family.
+    pub fn levelstats_cf(&self, column_family: &ColumnFamilyHandle) -> Option<Vec<LevelStats>> {
+        self.get_property_cf(column_family, properties::LEVELSTATS)
+            .and_then(|s| LevelStats::parse(&s))
+    }
+
+    /// Parsed map form of `"rocksdb.cfstats"` of the default column family.
+    pub fn cfstats(&self) -> Option<CFStats> {
+        self.get_map_property(properties::CFSTATS).map(|m| CFStats::from_map(&m))
+    }
+
+    /// Parsed map form of `"rocksdb.cfstats"` of a column family.
+    pub fn cfstats_cf(&self, column_family: &ColumnFamilyHandle) -> Option<CFStats> {
+        self.get_map_property_cf(column_family, properties::CFSTATS)
+            .map(|m| CFStats::from_map(&m))
+    }
+
+    /// Current write stall state, for backpressure.
+    ///
+    /// Stalls are DB wide, any column family can trigger them.
+    pub fn write_stall_state(&self) -> WriteStallState {
+        WriteStallState::from_properties(
+            self.get_int_property(properties::IS_WRITE_STOPPED).unwrap_or(0),
+            self.get_int_property(properties::ACTUAL_DELAYED_WRITE_RATE).unwrap_or(0),
+        )
     }
 
     /// Similar to `GetProperty()`, but only works for a subset of properties whose
@@ -1677,8 +3500,10 @@ impl DBRef {
         }
     }
 
+    /// Approximate file system space used by keys in `ranges`, only SST files are counted.
+    ///
+    /// See `get_approximate_sizes_with_options()` for including mem-tables.
     pub fn get_approximate_sizes(&self, column_family: &ColumnFamilyHandle, ranges: &[ops::Range<&[u8]>]) -> Vec<u64> {
-        // include_flags: u8
         let num_ranges = ranges.len();
         let mut range_start_ptrs = Vec::with_capacity(num_ranges);
         let mut range_start_lens = Vec::with_capacity(num_ranges);
@@ -1706,6 +3531,17 @@ impl DBRef {
         sizes
     }
 
+    /// For each `[start, limit)` in `ranges`, returns the approximate size used by keys in it,
+    /// including mem-tables and/or SST files as `options` specifies.
+    pub fn get_approximate_sizes_with_options(
+        &self,
+        options: &SizeApproximationOptions,
+        column_family: &ColumnFamilyHandle,
+        ranges: &[ops::Range<&[u8]>],
+    ) -> Result<Vec<u64>> {
+        get_approximate_sizes_with_options(self.raw(), column_family, options, ranges)
+    }
+
     pub fn get_approximate_memtable_stats(
         &self,
         column_family: &ColumnFamilyHandle,
@@ -1751,7 +3587,7 @@ impl DBRef {
     /// For Rust: use range expr, and since `compact_range()` use superset of range.
     pub fn compact_range<R: AsCompactRange>(&self, options: &CompactRangeOptions, range: R) -> Result<()> {
         let mut status = ptr::null_mut::<ll::rocks_status_t>();
-        unsafe {
+        self.track_default(OperationKind::CompactRange, None, || unsafe {
             ll::rocks_db_compact_range_opt(
                 self.raw(),
                 options.raw(),
@@ -1761,8 +3597,8 @@ impl DBRef {
                 range.end_key_len(),
                 &mut status,
             );
-            Error::from_ll(status)
-        }
+        });
+        Error::from_ll(status)
     }
 
     pub fn set_options<T, H>(&self, column_family: &ColumnFamilyHandle, new_options: H) -> Result<()>
@@ -1857,7 +3693,7 @@ impl DBRef {
             c_file_name_sizes.push(file_path.len());
         }
         let mut status = ptr::null_mut();
-        unsafe {
+        self.track_default(OperationKind::CompactFiles, Some(c_file_names.len()), || unsafe {
             ll::rocks_db_compact_files(
                 self.raw(),
                 compact_options.raw(),
@@ -1868,8 +3704,8 @@ impl DBRef {
                 output_path_id as c_int,
                 &mut status,
             );
-            Error::from_ll(status)
-        }
+        });
+        Error::from_ll(status)
     }
 
     /// This function will wait until all currently running background processes
@@ -1883,6 +3719,8 @@ impl DBRef {
         }
     }
 
+    /// Resume background work paused by `pause_background_work()`. Pauses are
+    /// counted, each one must be matched by a call of this.
     pub fn continue_background_work(&self) -> Result<()> {
         let mut status = ptr::null_mut::<ll::rocks_status_t>();
         unsafe {
@@ -1892,6 +3730,10 @@ impl DBRef {
     }
 
     /// Request stopping background work, if wait is true wait until it's done
+    ///
+    /// Only jobs of this DB are affected, the thread pools of its `Env` are left
+    /// running. Unlike `pause_background_work()`, there is no way to resume, the
+    /// DB should be closed afterwards.
     pub fn cancel_background_work(&self, wait: bool) {
         unsafe {
             ll::rocks_cancel_all_background_work(self.raw(), wait as u8);
@@ -1958,6 +3800,29 @@ impl DBRef {
         }
     }
 
+    /// Flush all mem-table data of a column family.
+    pub fn flush_cf(&self, options: &FlushOptions, column_family: &ColumnFamilyHandle) -> Result<()> {
+        let mut status = ptr::null_mut::<ll::rocks_status_t>();
+        unsafe {
+            ll::rocks_db_flush_cf(self.raw(), options.raw(), column_family.raw(), &mut status);
+            Error::from_ll(status)
+        }
+    }
+
+    /// Flushes multiple column families.
+    ///
+    /// If atomic flush is not enabled, this is equivalent to calling `flush_cf()` multiple times.
+    /// If atomic flush is enabled, it will flush all column families specified up to the latest
+    /// sequence number at the time when flush is requested, and commit the results atomically.
+    pub fn flush_cfs(&self, options: &FlushOptions, column_families: &[&ColumnFamilyHandle]) -> Result<()> {
+        let cfs: Vec<_> = column_families.iter().map(|cf| cf.raw() as *const _).collect();
+        let mut status = ptr::null_mut::<ll::rocks_status_t>();
+        unsafe {
+            ll::rocks_db_flush_cfs(self.raw(), options.raw(), cfs.as_ptr(), cfs.len(), &mut status);
+            Error::from_ll(status)
+        }
+    }
+
     /// Sync the wal. Note that Write() followed by SyncWAL() is not exactly the
     /// same as Write() with sync=true: in the latter case the changes won't be
     /// visible until the sync is done.
@@ -1971,6 +3836,19 @@ impl DBRef {
         }
     }
 
+    /// Flush the WAL memory buffer to the file. If sync is true, it calls `sync_wal()`
+    /// afterwards.
+    ///
+    /// Only needed with `DBOptions::manual_wal_flush(true)`, otherwise the WAL is
+    /// written on every write.
+    pub fn flush_wal(&self, sync: bool) -> Result<()> {
+        let mut status = ptr::null_mut::<ll::rocks_status_t>();
+        unsafe {
+            ll::rocks_db_flush_wal(self.raw(), sync as u8, &mut status);
+            Error::from_ll(status)
+        }
+    }
+
     /// The sequence number of the most recent transaction.
     pub fn get_latest_sequence_number(&self) -> SequenceNumber {
         unsafe { ll::rocks_db_get_latest_sequence_number(self.raw()).into() }
@@ -2049,28 +3927,52 @@ impl DBRef {
         let mut status = ptr::null_mut::<ll::rocks_status_t>();
         unsafe {
             let cfiles = ll::rocks_db_get_sorted_wal_files(self.raw(), &mut status);
-            Error::from_ll(status).map(|()| {
-                let num_files = ll::rocks_logfiles_size(cfiles);
-                let mut files = Vec::with_capacity(num_files);
-                for i in 0..num_files {
-                    let mut path_name = String::new();
-                    ll::rocks_logfiles_nth_path_name(cfiles, i, &mut path_name as *mut String as *mut c_void);
-                    let log_num = ll::rocks_logfiles_nth_log_number(cfiles, i);
-                    let file_type = mem::transmute(ll::rocks_logfiles_nth_type(cfiles, i));
-                    let start_seq = ll::rocks_logfiles_nth_start_sequence(cfiles, i);
-                    let file_size = ll::rocks_logfiles_nth_file_size(cfiles, i);
-                    files.push(LogFile {
-                        path_name: path_name,
-                        log_number: log_num,
-                        file_type: file_type,
-                        start_sequence: start_seq.into(),
-                        size_in_bytes: file_size,
-                    })
-                }
-                ll::rocks_logfiles_destroy(cfiles);
-                files
-            })
+            Error::from_ll(status).map(|()| LogFile::from_ll_list(cfiles))
+        }
+    }
+
+    /// Retrieve information about the current wal file
+    ///
+    /// Note that the log might have rolled after this call in which case
+    /// the returned file would not be the current log file.
+    ///
+    /// Additionally, for the sake of optimization `start_sequence` would always
+    /// be set to 0
+    pub fn get_current_wal_file(&self) -> Result<LogFile> {
+        let mut status = ptr::null_mut::<ll::rocks_status_t>();
+        unsafe {
+            let cfiles = ll::rocks_db_get_current_wal_file(self.raw(), &mut status);
+            Error::from_ll(status).map(|()| LogFile::from_ll_list(cfiles).pop().unwrap())
+        }
+    }
+
+    /// Archived WAL files, with earliest file first, see `DBOptions::wal_ttl_seconds()`.
+    pub fn get_archived_wal_files(&self) -> Result<Vec<LogFile>> {
+        self.get_sorted_wal_files()
+            .map(|files| files.into_iter().filter(|f| f.file_type == WalFileType::Archived).collect())
+    }
+
+    /// Delete archived WAL files holding only writes before `seq`, e.g. once they are
+    /// shipped to a backup, instead of waiting for `DBOptions::wal_ttl_seconds()`.
+    ///
+    /// A file is deleted if the next WAL file starts at or before `seq`. Live WAL files
+    /// are never deleted. Returns the deleted files.
+    pub fn purge_archived_wal_files(&self, seq: SequenceNumber) -> Result<Vec<LogFile>> {
+        let files = self.get_sorted_wal_files()?;
+        let mut purgeable = 0;
+        for (i, f) in files.iter().enumerate() {
+            let next_start = files.get(i + 1).map_or(u64::MAX, |next| next.start_sequence.0);
+            if f.file_type != WalFileType::Archived || next_start > seq.0 {
+                break;
+            }
+            purgeable += 1;
         }
+        let mut purged = Vec::with_capacity(purgeable);
+        for f in files.into_iter().take(purgeable) {
+            self.delete_file(&f.path_name)?;
+            purged.push(f);
+        }
+        Ok(purged)
     }
 
     /// Sets iter to an iterator that is positioned at a write-batch containing
@@ -2091,6 +3993,16 @@ impl DBRef {
         }
     }
 
+    /// Snapshots of statistics taken in `[start_time, end_time)`, unix time in seconds,
+    /// see `DBOptions::stats_persist_period_sec()`.
+    pub fn get_stats_history(&self, start_time: u64, end_time: u64) -> Result<StatsHistoryIterator<'_>> {
+        let mut status = ptr::null_mut::<ll::rocks_status_t>();
+        unsafe {
+            let iter_raw_ptr = ll::rocks_db_get_stats_history(self.raw(), start_time, end_time, &mut status);
+            Error::from_ll(status).map(|_| StatsHistoryIterator::from_ll(iter_raw_ptr))
+        }
+    }
+
     /// Delete the file name from the db directory and update the internal state to
     /// reflect that. Supports deletion of sst and log files only. 'name' must be
     /// path relative to the db directory. eg. 000001.sst, /archive/000003.log
@@ -2129,63 +4041,141 @@ impl DBRef {
         }
     }
 
+    /// Mark files overlapping `range` for compaction, they are compacted by
+    /// background compactions later, unlike `compact_range()`.
+    ///
+    /// NOTE: experimental API of RocksDB.
+    pub fn suggest_compact_range<R: AsCompactRange>(&self, column_family: &ColumnFamilyHandle, range: R) -> Result<()> {
+        let mut status = ptr::null_mut::<ll::rocks_status_t>();
+        unsafe {
+            ll::rocks_db_suggest_compact_range(
+                self.raw(),
+                column_family.raw(),
+                range.start_key() as *const _,
+                range.start_key_len(),
+                range.end_key() as *const _,
+                range.end_key_len(),
+                &mut status,
+            );
+            Error::from_ll(status)
+        }
+    }
+
+    /// Move all L0 files to `target_level`, skipping compaction.
+    ///
+    /// Fails with `InvalidArgument` if the L0 files overlap each other, or if any level
+    /// between 1 and `target_level` is not empty. Level compaction style only.
+    ///
+    /// NOTE: experimental API of RocksDB.
+    pub fn promote_l0(&self, column_family: &ColumnFamilyHandle, target_level: i32) -> Result<()> {
+        let mut status = ptr::null_mut::<ll::rocks_status_t>();
+        unsafe {
+            ll::rocks_db_promote_l0(self.raw(), column_family.raw(), target_level, &mut status);
+            Error::from_ll(status)
+        }
+    }
+
+    /// Delete files which are entirely in any of the given ranges, in one go.
+    ///
+    /// `include_end` makes the end keys inclusive. Ranges should not overlap.
+    /// Files of level 0 are never deleted.
+    ///
+    /// Like `delete_files_in_range()`, could leave some keys in the ranges, and
+    /// snapshots before the delete might not see the data in the given ranges.
+    pub fn delete_files_in_ranges(
+        &self,
+        column_family: &ColumnFamilyHandle,
+        ranges: &[ops::Range<&[u8]>],
+        include_end: bool,
+    ) -> Result<()> {
+        let start_ptrs = ranges.iter().map(|r| r.start.as_ptr() as *const c_char).collect::<Vec<_>>();
+        let start_lens = ranges.iter().map(|r| r.start.len()).collect::<Vec<_>>();
+        let limit_ptrs = ranges.iter().map(|r| r.end.as_ptr() as *const c_char).collect::<Vec<_>>();
+        let limit_lens = ranges.iter().map(|r| r.end.len()).collect::<Vec<_>>();
+        let mut status = ptr::null_mut::<ll::rocks_status_t>();
+        unsafe {
+            ll::rocks_db_delete_files_in_ranges(
+                self.raw(),
+                column_family.raw(),
+                start_ptrs.as_ptr(),
+                start_lens.as_ptr(),
+                limit_ptrs.as_ptr(),
+                limit_lens.as_ptr(),
+                ranges.len(),
+                include_end as u8,
+                &mut status,
+            );
+            Error::from_ll(status)
+        }
+    }
+
+    /// Verify the block checksums of all live SST files of all column families.
+    ///
+    /// Returns `Corruption` on the first mismatch.
+    pub fn verify_checksum(&self) -> Result<()> {
+        self.verify_checksum_with_options(ReadOptions::default_instance())
+    }
+
+    /// Same as `verify_checksum()`, with `ReadOptions` controlling the reads, e.g. `readahead_size`.
+    pub fn verify_checksum_with_options(&self, options: &ReadOptions) -> Result<()> {
+        let mut status = ptr::null_mut::<ll::rocks_status_t>();
+        unsafe {
+            ll::rocks_db_verify_checksum(self.raw(), options.raw(), &mut status);
+            Error::from_ll(status)
+        }
+    }
+
     /// Returns a list of all table files with their level, start key
     /// and end key
+    ///
+    /// `GetLiveFilesMetaData()` of RocksDB leaves the file times unset, they are taken from
+    /// the metadata of the opened column families, 0 for files of other column families.
     pub fn get_live_files_metadata(&self) -> Vec<LiveFileMetaData> {
-        unsafe {
-            let livefiles = ll::rocks_db_get_livefiles_metadata(self.raw());
-
-            let cnt = ll::rocks_livefiles_count(livefiles);
-            let mut ret = Vec::with_capacity(cnt as usize);
-            for i in 0..cnt {
-                let name = CStr::from_ptr(ll::rocks_livefiles_name(livefiles, i))
-                    .to_string_lossy()
-                    .to_owned()
-                    .to_string();
-                let db_path: String = CStr::from_ptr(ll::rocks_livefiles_db_path(livefiles, i))
-                    .to_string_lossy()
-                    .to_owned()
-                    .to_string();
-                let size = ll::rocks_livefiles_size(livefiles, i);
-
-                let small_seqno = ll::rocks_livefiles_smallest_seqno(livefiles, i);
-                let large_seqno = ll::rocks_livefiles_largest_seqno(livefiles, i);
-
-                let mut key_len = 0;
-                let small_key_ptr = ll::rocks_livefiles_smallestkey(livefiles, i, &mut key_len);
-                let small_key = slice::from_raw_parts(small_key_ptr as *const u8, key_len).to_vec();
-
-                let large_key_ptr = ll::rocks_livefiles_largestkey(livefiles, i, &mut key_len);
-                let large_key = slice::from_raw_parts(large_key_ptr as *const u8, key_len).to_vec();
-
-                let being_compacted = ll::rocks_livefiles_being_compacted(livefiles, i) != 0;
-
-                let cf_name = CStr::from_ptr(ll::rocks_livefiles_column_family_name(livefiles, i))
-                    .to_string_lossy()
-                    .to_owned()
-                    .to_string();
-                let level = ll::rocks_livefiles_level(livefiles, i);
-
-                let meta = LiveFileMetaData {
-                    sst_file: SstFileMetaData {
-                        size: size as u64,
-                        name: name,
-                        db_path: db_path,
-                        smallest_seqno: small_seqno.into(),
-                        largest_seqno: large_seqno.into(),
-                        smallestkey: small_key,
-                        largestkey: large_key,
-                        being_compacted: being_compacted,
-                    },
-                    column_family_name: cf_name,
-                    level: level as u32,
-                };
+        let mut files = unsafe { LiveFileMetaData::from_ll_list(ll::rocks_db_get_livefiles_metadata(self.raw())) };
 
-                ret.push(meta);
+        let mut times = HashMap::new();
+        let default_cf = ColumnFamilyHandle {
+            raw: self.raw_default_column_family(),
+        };
+        let cfs = self.column_families.lock().unwrap();
+        let mut handles = vec![&default_cf];
+        handles.extend(cfs.values().map(|cf| &cf.handle));
+        for handle in handles {
+            for level in self.get_column_family_metadata(handle).levels {
+                for file in level.files {
+                    times.insert(file.file_number, (file.oldest_ancester_time, file.file_creation_time));
+                }
             }
-            ll::rocks_livefiles_destroy(livefiles);
-            ret
         }
+        for file in &mut files {
+            let (oldest_ancester_time, file_creation_time) = times.get(&file.file_number).cloned().unwrap_or_default();
+            file.sst_file.oldest_ancester_time = oldest_ancester_time;
+            file.sst_file.file_creation_time = file_creation_time;
+        }
+        files
+    }
+
+    /// Returns the creation time, in seconds since the epoch, of the oldest live SST file.
+    ///
+    /// `Some(0)` if the creation time of any file is unknown, `None` if there are no SST files.
+    pub fn get_creation_time_of_oldest_file(&self) -> Option<u64> {
+        let files = self.get_live_files_metadata();
+        if files.iter().any(|f| f.file_creation_time == 0) {
+            return Some(0);
+        }
+        files.iter().map(|f| f.file_creation_time).min()
+    }
+
+    /// Split the key space of a column family into at most `n` ranges of roughly equal size,
+    /// see `ColumnFamilyMetaData::split_into_ranges()`.
+    pub fn split_into_ranges(&self, column_family: &ColumnFamilyHandle, n: usize) -> Vec<KeyRange> {
+        self.get_column_family_metadata(column_family).split_into_ranges(n)
+    }
+
+    /// Returns all live SST files bucketed by data age, see `DataAgeHistogram::new()`.
+    pub fn get_data_age_histogram(&self, bounds: &[Duration]) -> DataAgeHistogram {
+        let files = self.get_live_files_metadata();
+        DataAgeHistogram::new(files.iter().map(|f| &f.sst_file), SystemTime::now(), bounds)
     }
 
     /// Obtains the meta data of the specified column family of the DB.
@@ -2247,6 +4237,17 @@ impl DBRef {
 
                     let being_compacted =
                         ll::rocks_column_family_metadata_levels_files_being_compacted(cfmeta, lv, i) != 0;
+                    let num_reads_sampled =
+                        ll::rocks_column_family_metadata_levels_files_num_reads_sampled(cfmeta, lv, i);
+                    let file_number = ll::rocks_column_family_metadata_levels_files_file_number(cfmeta, lv, i);
+                    let num_entries = ll::rocks_column_family_metadata_levels_files_num_entries(cfmeta, lv, i);
+                    let num_deletions = ll::rocks_column_family_metadata_levels_files_num_deletions(cfmeta, lv, i);
+                    let oldest_blob_file_number =
+                        ll::rocks_column_family_metadata_levels_files_oldest_blob_file_number(cfmeta, lv, i);
+                    let oldest_ancester_time =
+                        ll::rocks_column_family_metadata_levels_files_oldest_ancester_time(cfmeta, lv, i);
+                    let file_creation_time =
+                        ll::rocks_column_family_metadata_levels_files_file_creation_time(cfmeta, lv, i);
 
                     let sst_file = SstFileMetaData {
                         size: size as u64,
@@ -2257,6 +4258,13 @@ impl DBRef {
                         smallestkey: small_key,
                         largestkey: large_key,
                         being_compacted: being_compacted,
+                        num_reads_sampled,
+                        file_number,
+                        num_entries,
+                        num_deletions,
+                        oldest_blob_file_number,
+                        oldest_ancester_time,
+                        file_creation_time,
                     };
 
                     current_level.files.push(sst_file);
@@ -2295,7 +4303,7 @@ impl DBRef {
             num_files += 1;
         }
         let mut status = ptr::null_mut::<ll::rocks_status_t>();
-        unsafe {
+        self.track_default(OperationKind::IngestExternalFile, Some(num_files), || unsafe {
             ll::rocks_db_ingest_external_file(
                 self.raw(),
                 c_files.as_ptr() as *const _,
@@ -2304,8 +4312,8 @@ impl DBRef {
                 options.raw(),
                 &mut status,
             );
-            Error::from_ll(status)
-        }
+        });
+        Error::from_ll(status)
     }
 
     pub fn ingest_external_file_cf<P: AsRef<Path>, T: IntoIterator<Item = P>>(
@@ -2324,7 +4332,7 @@ impl DBRef {
             num_files += 1;
         }
         let mut status = ptr::null_mut::<ll::rocks_status_t>();
-        unsafe {
+        self.track(OperationKind::IngestExternalFile, column_family, Some(num_files), || unsafe {
             ll::rocks_db_ingest_external_file_cf(
                 self.raw(),
                 column_family.raw,
@@ -2334,8 +4342,8 @@ impl DBRef {
                 options.raw(),
                 &mut status,
             );
-            Error::from_ll(status)
-        }
+        });
+        Error::from_ll(status)
     }
 
     /// Sets the globally unique ID created at database creation time by invoking
@@ -2457,6 +4465,38 @@ impl DBRef {
     */
 }
 
+fn get_approximate_sizes_with_options(
+    db: *mut ll::rocks_db_t,
+    column_family: &ColumnFamilyHandle,
+    options: &SizeApproximationOptions,
+    ranges: &[ops::Range<&[u8]>],
+) -> Result<Vec<u64>> {
+    let num_ranges = ranges.len();
+    let range_start_ptrs: Vec<_> = ranges.iter().map(|r| r.start.as_ptr() as *const c_char).collect();
+    let range_start_lens: Vec<_> = ranges.iter().map(|r| r.start.len()).collect();
+    let range_end_ptrs: Vec<_> = ranges.iter().map(|r| r.end.as_ptr() as *const c_char).collect();
+    let range_end_lens: Vec<_> = ranges.iter().map(|r| r.end.len()).collect();
+    let mut sizes = vec![0_u64; num_ranges];
+    let mut status = ptr::null_mut::<ll::rocks_status_t>();
+    unsafe {
+        ll::rocks_db_get_approximate_sizes_cf_opt(
+            db,
+            column_family.raw(),
+            options.include_memtables as u8,
+            options.include_files as u8,
+            options.files_size_error_margin,
+            num_ranges,
+            range_start_ptrs.as_ptr(),
+            range_start_lens.as_ptr(),
+            range_end_ptrs.as_ptr(),
+            range_end_lens.as_ptr(),
+            sizes.as_mut_ptr(),
+            &mut status,
+        );
+        Error::from_ll(status).map(|()| sizes)
+    }
+}
+
 // ==================================================
 
 // public functions
@@ -1,15 +1,22 @@
 //! Dump and un-dump tools for rocksdb
+//!
+//! A ROCKDUMP file holds the key-values of a single column family, uncompressed.
+//! Compression of an un-dumped DB follows the `Options` passed to `DbUndumpTool::run()`.
 
 use std::path::Path;
+use std::ptr;
 
 use rocks_sys as ll;
 
-use crate::options::Options;
+use crate::options::{ColumnFamilyOptions, Options};
 use crate::to_raw::ToRaw;
+use crate::{Error, Result};
 
 /// Dumps db to a ROCKDUMP file
 pub struct DbDumpTool {
     raw: *mut ll::rocks_dump_options_t,
+    // kept alive for comparators and other pointers shared with the copies
+    cf_options: Vec<ColumnFamilyOptions>,
 }
 
 impl Drop for DbDumpTool {
@@ -30,7 +37,10 @@ impl DbDumpTool {
                 dump_location.as_bytes().as_ptr() as *const _,
                 dump_location.as_bytes().len(),
             );
-            DbDumpTool { raw: raw }
+            DbDumpTool {
+                raw,
+                cf_options: vec![],
+            }
         }
     }
 
@@ -44,14 +54,49 @@ impl DbDumpTool {
         self
     }
 
+    /// The column family to dump, must exist.
+    ///
+    /// DEFAULT: "default"
+    pub fn column_family(self, name: &str) -> Self {
+        unsafe {
+            ll::rocks_dump_options_set_column_family(self.raw, name.as_ptr() as *const _, name.len());
+        }
+        self
+    }
+
+    /// Options to open the column family `name` with, e.g. for a custom comparator.
+    /// Column families without their own options use the `Options` passed to `try_run()`.
+    pub fn column_family_options(mut self, name: &str, options: ColumnFamilyOptions) -> Self {
+        unsafe {
+            ll::rocks_dump_options_set_column_family_options(
+                self.raw,
+                name.as_ptr() as *const _,
+                name.len(),
+                options.raw(),
+            );
+        }
+        self.cf_options.push(options);
+        self
+    }
+
+    /// Dump the DB, opened read only with `options`.
+    pub fn try_run(self, options: &Options) -> Result<()> {
+        let mut status = ptr::null_mut();
+        unsafe {
+            ll::rocks_db_dump(self.raw, options.raw(), &mut status);
+        }
+        Error::from_ll(status)
+    }
+
     pub fn run(self, options: &Options) -> bool {
-        unsafe { ll::rocks_db_dump_tool_run(self.raw, options.raw()) != 0 }
+        self.try_run(options).is_ok()
     }
 }
 
 /// Undumps(load) db from a ROCKDUMP file
 pub struct DbUndumpTool {
     raw: *mut ll::rocks_undump_options_t,
+    cf_options: Vec<ColumnFamilyOptions>,
 }
 
 impl Drop for DbUndumpTool {
@@ -76,7 +121,10 @@ impl DbUndumpTool {
                 dump_location.as_bytes().as_ptr() as *const _,
                 dump_location.as_bytes().len(),
             );
-            DbUndumpTool { raw: raw }
+            DbUndumpTool {
+                raw,
+                cf_options: vec![],
+            }
         }
     }
 
@@ -90,8 +138,43 @@ impl DbUndumpTool {
         self
     }
 
+    /// The column family to load into, created if missing.
+    ///
+    /// DEFAULT: "default"
+    pub fn column_family(self, name: &str) -> Self {
+        unsafe {
+            ll::rocks_undump_options_set_column_family(self.raw, name.as_ptr() as *const _, name.len());
+        }
+        self
+    }
+
+    /// Options to open or create the column family `name` with, e.g. for a custom comparator.
+    /// Column families without their own options use the `Options` passed to `try_run()`.
+    pub fn column_family_options(mut self, name: &str, options: ColumnFamilyOptions) -> Self {
+        unsafe {
+            ll::rocks_undump_options_set_column_family_options(
+                self.raw,
+                name.as_ptr() as *const _,
+                name.len(),
+                options.raw(),
+            );
+        }
+        self.cf_options.push(options);
+        self
+    }
+
+    /// Load the dump file into the DB, opened with `options` and created if missing.
+    /// Existing keys are overwritten.
+    pub fn try_run(self, options: &Options) -> Result<()> {
+        let mut status = ptr::null_mut();
+        unsafe {
+            ll::rocks_db_undump(self.raw, options.raw(), &mut status);
+        }
+        Error::from_ll(status)
+    }
+
     pub fn run(self, options: &Options) -> bool {
-        unsafe { ll::rocks_db_undump_tool_run(self.raw, options.raw()) != 0 }
+        self.try_run(options).is_ok()
     }
 }
 
@@ -144,4 +227,127 @@ mod tests {
             assert_eq!(db.get(&Default::default(), b"key_").as_ref().unwrap(), b"BY1CQ".as_ref());
         }
     }
+
+    #[test]
+    fn db_dump_and_undump_column_family() {
+        let tmp_dir = ::tempdir::TempDir::new_in(".", "rocks").unwrap();
+        {
+            let opt = Options::default().map_db_options(|db| db.create_if_missing(true));
+            let db = DB::open(opt, &tmp_dir).unwrap();
+            let cf = db.create_column_family(&ColumnFamilyOptions::default(), "events").unwrap();
+            assert!(db.put(WriteOptions::default_instance(), b"default-key", b"v").is_ok());
+            for i in 0..100 {
+                let key = format!("event{:03}", i);
+                assert!(cf.put(WriteOptions::default_instance(), key.as_bytes(), b"").is_ok());
+            }
+            assert!(cf.put(WriteOptions::default_instance(), b"", b"empty key").is_ok());
+        }
+
+        let dumps_dir = ::tempdir::TempDir::new_in(".", "dumps").unwrap();
+        let dump_file = dumps_dir.path().join("events");
+
+        assert!(DbDumpTool::new(&tmp_dir, &dump_file)
+            .column_family("not-exist")
+            .try_run(&Options::default())
+            .is_err());
+        assert!(DbDumpTool::new(&tmp_dir, &dump_file)
+            .column_family("events")
+            .anonymous(true)
+            .try_run(&Options::default())
+            .is_ok());
+
+        let tmp_dir2 = ::tempdir::TempDir::new_in(".", "rocks").unwrap();
+        assert!(DbUndumpTool::new(&tmp_dir2, &dump_file)
+            .column_family("restored")
+            .compact_db(true)
+            .try_run(&Options::default())
+            .is_ok());
+        // not a dump file
+        assert!(DbUndumpTool::new(&tmp_dir2, tmp_dir2.path().join("CURRENT"))
+            .try_run(&Options::default())
+            .is_err());
+
+        let (db, cfs) = DB::open_with_column_families(
+            &DBOptions::default(),
+            &tmp_dir2,
+            vec![DEFAULT_COLUMN_FAMILY_NAME, "restored"],
+        )
+        .unwrap();
+        assert!(db.get(ReadOptions::default_instance(), b"default-key").is_err());
+        let restored = &cfs[1];
+        assert_eq!(restored.get(ReadOptions::default_instance(), b"event042").unwrap(), b"");
+        assert_eq!(restored.get(ReadOptions::default_instance(), b"").unwrap(), b"empty key");
+        let mut it = restored.new_iterator(ReadOptions::default_instance());
+        it.seek_to_first();
+        assert_eq!(it.count(), 101);
+    }
+
+    #[test]
+    fn db_dump_with_column_family_options() {
+        let tmp_dir = ::tempdir::TempDir::new_in(".", "rocks").unwrap();
+        let reversed = || ColumnFamilyOptions::default().bitwise_comparator_reversed(true);
+        {
+            let opt = Options::default().map_db_options(|db| db.create_if_missing(true));
+            let db = DB::open(opt, &tmp_dir).unwrap();
+            let cf = db.create_column_family(&reversed(), "say \"hi\"").unwrap();
+            for i in 0..10 {
+                let key = format!("k{}", i);
+                assert!(cf.put(WriteOptions::default_instance(), key.as_bytes(), b"v").is_ok());
+            }
+        }
+
+        let dumps_dir = ::tempdir::TempDir::new_in(".", "dumps").unwrap();
+        let dump_file = dumps_dir.path().join("reversed");
+        // the comparator does not match
+        assert!(DbDumpTool::new(&tmp_dir, &dump_file)
+            .column_family("say \"hi\"")
+            .try_run(&Options::default())
+            .is_err());
+        assert!(DbDumpTool::new(&tmp_dir, &dump_file)
+            .column_family("say \"hi\"")
+            .column_family_options("say \"hi\"", reversed())
+            .try_run(&Options::default())
+            .is_ok());
+        let dump = ::std::fs::read(&dump_file).unwrap();
+        let header = String::from_utf8_lossy(&dump[20..]);
+        assert!(header.contains(r#""column-family": "say \"hi\"""#), "{}", header);
+
+        let tmp_dir2 = ::tempdir::TempDir::new_in(".", "rocks").unwrap();
+        assert!(DbUndumpTool::new(&tmp_dir2, &dump_file)
+            .column_family("restored")
+            .column_family_options("restored", reversed())
+            .try_run(&Options::default())
+            .is_ok());
+        let (_db, cfs) = DB::open_with_column_families(
+            &DBOptions::default(),
+            &tmp_dir2,
+            vec![
+                ColumnFamilyDescriptor::new(DEFAULT_COLUMN_FAMILY_NAME, ColumnFamilyOptions::default()),
+                ColumnFamilyDescriptor::new("restored", reversed()),
+            ],
+        )
+        .unwrap();
+        let mut it = cfs[1].new_iterator(ReadOptions::default_instance());
+        it.seek_to_first();
+        assert_eq!(it.key(), b"k9");
+    }
+
+    #[test]
+    fn db_undump_corrupt_length() {
+        let dumps_dir = ::tempdir::TempDir::new_in(".", "dumps").unwrap();
+        let dump_file = dumps_dir.path().join("corrupt");
+        let mut dump = b"ROCKDUMP\0\0\0\0\0\0\0\x01".to_vec();
+        dump.extend_from_slice(&2u32.to_le_bytes());
+        dump.extend_from_slice(b"{}");
+        // a key length far past the end of the file
+        dump.extend_from_slice(&u32::max_value().to_le_bytes());
+        dump.extend_from_slice(b"key");
+        ::std::fs::write(&dump_file, &dump).unwrap();
+
+        let tmp_dir = ::tempdir::TempDir::new_in(".", "rocks").unwrap();
+        let err = DbUndumpTool::new(&tmp_dir, &dump_file)
+            .try_run(&Options::default())
+            .unwrap_err();
+        assert!(err.is_corruption(), "{:?}", err);
+    }
 }
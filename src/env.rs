@@ -8,14 +8,18 @@
 //! multiple threads without any external synchronization.
 
 use lazy_static::lazy_static;
-use std::ffi::CStr;
+use std::ffi::{CStr, CString};
+use std::fmt;
+use std::io;
 use std::mem;
+use std::os::raw::c_int;
 use std::path::Path;
 use std::ptr;
 use std::str;
 
 use rocks_sys as ll;
 
+use crate::options::{ColumnFamilyOptions, DBOptions, Options};
 use crate::thread_status::ThreadStatus;
 use crate::to_raw::{FromRaw, ToRaw};
 use crate::{Error, Result};
@@ -31,13 +35,64 @@ lazy_static! {
     };
 }
 
-/// Priority for scheduling job in thread pool
+/// Priority for scheduling job in thread pool, also IO priority of `RateLimiter`
 #[repr(C)]
 #[derive(Debug, Copy, Clone, PartialEq, Eq, Hash)]
 pub enum Priority {
-    Low,
-    High,
-    Total,
+    Low = 0,
+    High = 1,
+    Total = 2,
+    /// The bottommost thread pool, for compactions to the bottommost level. Not an IO priority.
+    Bottom = 3,
+}
+
+/// Filesystem operations that latency can be injected into, see `Env::new_latency_injection()`
+#[repr(C)]
+#[derive(Debug, Copy, Clone, PartialEq, Eq, Hash)]
+pub enum IoOperation {
+    /// Sequential and random reads
+    Read = 0,
+    /// Appends to writable files
+    Write = 1,
+    /// `Sync()`, `Fsync()` and `RangeSync()` of writable files
+    Sync = 2,
+}
+
+/// Latency distribution of an `IoOperation`.
+///
+/// Each operation is delayed with the given probability, by a duration drawn
+/// uniformly from `[min_micros, max_micros]`.
+#[derive(Debug, Copy, Clone, PartialEq, Default)]
+pub struct InjectedLatency {
+    pub probability: f64,
+    pub min_micros: u64,
+    pub max_micros: u64,
+}
+
+impl InjectedLatency {
+    /// No latency injected.
+    pub fn none() -> InjectedLatency {
+        InjectedLatency::default()
+    }
+
+    /// Delay every operation by exactly `micros`.
+    pub fn fixed(micros: u64) -> InjectedLatency {
+        InjectedLatency::uniform(micros, micros)
+    }
+
+    /// Delay every operation by a duration uniformly drawn from `[min_micros, max_micros]`.
+    pub fn uniform(min_micros: u64, max_micros: u64) -> InjectedLatency {
+        InjectedLatency {
+            probability: 1.0,
+            min_micros,
+            max_micros,
+        }
+    }
+
+    /// Only delay a fraction of the operations, e.g. for occasional stalls.
+    pub fn with_probability(self, probability: f64) -> InjectedLatency {
+        InjectedLatency { probability, ..self }
+    }
 }
 
 /// Options while opening a file to read/write
@@ -189,6 +244,40 @@ pub enum InfoLogLevel {
     Header,
 }
 
+/// Receives the info log of a DB, implemented in Rust, see `Logger::new()`.
+///
+/// Called from foreground and background threads concurrently.
+pub trait InfoLogger: Send + Sync {
+    /// A formatted log line, only for levels at or above the level of the `Logger`.
+    fn logv(&self, log_level: InfoLogLevel, msg: &str);
+
+    fn flush(&self) {}
+}
+
+/// Routes the info log to the `log` crate, with target `rocksdb`.
+///
+/// `Fatal` is logged as `Error`, `Header` as `Info`.
+#[cfg(feature = "log")]
+#[derive(Debug, Clone, Copy, Default)]
+pub struct LogCrateLogger;
+
+#[cfg(feature = "log")]
+impl InfoLogger for LogCrateLogger {
+    fn logv(&self, log_level: InfoLogLevel, msg: &str) {
+        let level = match log_level {
+            InfoLogLevel::Debug => ::log::Level::Debug,
+            InfoLogLevel::Info | InfoLogLevel::Header => ::log::Level::Info,
+            InfoLogLevel::Warn => ::log::Level::Warn,
+            InfoLogLevel::Error | InfoLogLevel::Fatal => ::log::Level::Error,
+        };
+        ::log::log!(target: "rocksdb", level, "{}", msg);
+    }
+
+    fn flush(&self) {
+        ::log::logger().flush();
+    }
+}
+
 /// An interface for writing log messages.
 #[derive(Debug)]
 pub struct Logger {
@@ -214,6 +303,40 @@ impl Logger {
         Logger { raw: raw }
     }
 
+    /// A logger writing lines at or above `log_level` to stderr.
+    pub fn stderr(log_level: InfoLogLevel) -> Logger {
+        unsafe { Logger::from_ll(ll::rocks_logger_create_stderr(log_level as c_int)) }
+    }
+
+    /// A logger discarding everything, to silence the info log without a LOG file.
+    pub fn devnull() -> Logger {
+        unsafe { Logger::from_ll(ll::rocks_logger_create_devnull()) }
+    }
+
+    /// The logger a DB at `path` creates when `DBOptions::info_log()` is not set: a `LOG` file
+    /// in `DBOptions::db_log_dir()`, or in `path` if unset, rotated as configured by `options`.
+    ///
+    /// Useful to share one info log between DBs, or to open one before the DB.
+    pub fn from_options<P: AsRef<Path>>(path: P, options: &DBOptions) -> Result<Logger> {
+        let mut status = ptr::null_mut();
+        let name = CString::new(path.as_ref().to_str().unwrap()).unwrap();
+        unsafe {
+            // only the DBOptions part is used
+            let cf_options = ColumnFamilyOptions::default();
+            let raw_options = ll::rocks_options_create_from_db_cf_options(options.raw(), cf_options.raw());
+            let options = Options::from_ll(raw_options);
+            let logger = ll::rocks_create_logger_from_options(name.as_ptr(), options.raw(), &mut status);
+            Error::from_ll(status).map(|_| Logger::from_ll(logger))
+        }
+    }
+
+    /// A logger writing to `logger`, for `DBOptions::info_log()`. Lines below `log_level`
+    /// are not formatted.
+    pub fn new<L: InfoLogger + 'static>(logger: L, log_level: InfoLogLevel) -> Logger {
+        let logger: Box<Box<dyn InfoLogger>> = Box::new(Box::new(logger));
+        unsafe { Logger::from_ll(ll::rocks_logger_create_rust(Box::into_raw(logger) as *mut _, log_level as c_int)) }
+    }
+
     /// Write an entry to the log file with the specified log level
     /// and format.  Any log with level under the internal log level
     /// of *this (see @SetInfoLogLevel and @GetInfoLogLevel) will not be
@@ -242,6 +365,118 @@ impl Logger {
     }
 }
 
+/// A file abstraction for reading sequentially through a file.
+pub trait SequentialFile: Send {
+    /// Read up to `buf.len()` bytes, returns the number of bytes read, 0 at end of file.
+    fn read(&mut self, buf: &mut [u8]) -> io::Result<usize>;
+
+    /// Skip `n` bytes. Skipping past the end of file is not an error.
+    fn skip(&mut self, n: u64) -> io::Result<()>;
+}
+
+/// A file abstraction for randomly reading the contents of a file.
+///
+/// Reads may happen concurrently from multiple threads.
+pub trait RandomAccessFile: Send + Sync {
+    /// Read up to `buf.len()` bytes starting at `offset`, returns the number of bytes read.
+    /// Short reads are retried until the buffer is full or 0 is returned.
+    fn read_at(&self, offset: u64, buf: &mut [u8]) -> io::Result<usize>;
+}
+
+/// A file abstraction for sequential writing.
+pub trait WritableFile: Send {
+    fn append(&mut self, data: &[u8]) -> io::Result<()>;
+
+    fn flush(&mut self) -> io::Result<()>;
+
+    /// Persist data written so far.
+    fn sync(&mut self) -> io::Result<()>;
+
+    fn close(&mut self) -> io::Result<()>;
+}
+
+/// File system operations backing an `Env` created by `Env::from_file_system()`.
+///
+/// Paths are those built by RocksDB from the DB path, WAL dir and so on. Errors of kind
+/// `io::ErrorKind::NotFound` are reported to RocksDB as `NotFound`, which it relies on
+/// e.g. when probing for an existing DB; `io::ErrorKind::Unsupported` as `NotSupported`,
+/// everything else as `IOError`.
+///
+/// Threads, clocks and scheduling are still those of the default environment. File locks
+/// are process local, and there is no `LOG` file, set `DBOptions::info_log` instead.
+pub trait FileSystem: Send + Sync {
+    fn new_sequential_file(&self, fname: &str) -> io::Result<Box<dyn SequentialFile>>;
+
+    fn new_random_access_file(&self, fname: &str) -> io::Result<Box<dyn RandomAccessFile>>;
+
+    /// Create a new file, truncating an existing file of the same name.
+    fn new_writable_file(&self, fname: &str) -> io::Result<Box<dyn WritableFile>>;
+
+    /// `Ok(())` if the file exists, an error of kind `NotFound` if it does not.
+    fn file_exists(&self, fname: &str) -> io::Result<()>;
+
+    /// Names of the children of `dir`, relative to it.
+    fn get_children(&self, dir: &str) -> io::Result<Vec<String>>;
+
+    fn delete_file(&self, fname: &str) -> io::Result<()>;
+
+    /// Create `dir`, fails if it already exists.
+    fn create_dir(&self, dir: &str) -> io::Result<()>;
+
+    fn create_dir_if_missing(&self, dir: &str) -> io::Result<()>;
+
+    fn delete_dir(&self, dir: &str) -> io::Result<()>;
+
+    fn get_file_size(&self, fname: &str) -> io::Result<u64>;
+
+    /// Seconds since the epoch.
+    fn get_file_modification_time(&self, fname: &str) -> io::Result<u64>;
+
+    /// Rename `src` to `target`, replacing it if it exists.
+    fn rename_file(&self, src: &str, target: &str) -> io::Result<()>;
+
+    /// Persist the entries of `dir`, e.g. after creating or renaming files in it.
+    fn fsync_dir(&self, _dir: &str) -> io::Result<()> {
+        Ok(())
+    }
+}
+
+/// Identifies a locked file, see `Env::lock_file()`. Unlocked on drop.
+pub struct FileLock<'a> {
+    env: &'a Env,
+    raw: *mut ll::rocks_filelock_t,
+}
+
+impl<'a> fmt::Debug for FileLock<'a> {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.debug_struct("FileLock").finish()
+    }
+}
+
+impl<'a> Drop for FileLock<'a> {
+    fn drop(&mut self) {
+        let mut status = ptr::null_mut();
+        unsafe {
+            ll::rocks_env_unlock_file(self.env.raw, self.raw, &mut status);
+            // nothing to do about a failed unlock
+            let _ = Error::from_ll(status);
+        }
+    }
+}
+
+/// A block cipher for `Env::new_encrypted_env()`, e.g. AES from RustCrypto.
+///
+/// Files are encrypted in CTR mode, so only `encrypt()` is used for data; both
+/// must work on a single block in place.
+pub trait BlockCipher: Send + Sync {
+    /// Size of a block in bytes, usually 16.
+    fn block_size(&self) -> usize;
+
+    fn encrypt(&self, block: &mut [u8]);
+
+    fn decrypt(&self, block: &mut [u8]);
+}
+
 /// An `Env` is an interface used by the rocksdb implementation to access
 /// operating system functionality like the filesystem etc.
 pub struct Env {
@@ -276,9 +511,9 @@ impl Env {
     }
 
     /// Returns a new environment that stores its data in memory and delegates
-    /// all non-file-storage tasks to base_env.
+    /// all non-file-storage tasks to the default environment.
     ///
-    /// FIXME: missing base_env
+    /// See also `new_mem_with_base()`.
     pub fn new_mem() -> Env {
         Env {
             raw: unsafe { ll::rocks_create_mem_env() },
@@ -290,25 +525,100 @@ impl Env {
     ///
     /// This is a factory method for TimedEnv defined in utilities/env_timed.cc.
     ///
-    /// FIXME: missing base_env
+    /// See also `new_timed_with_base()`.
     pub fn new_timed() -> Env {
         Env {
             raw: unsafe { ll::rocks_create_timed_env() },
         }
     }
 
-    /// The number of background worker threads of a specific thread pool
-    pub fn set_low_priority_background_threads(&self, number: i32) {
+    /// Returns a new environment that stores its data in memory and delegates
+    /// all non-file-storage tasks to `base`.
+    pub fn new_mem_with_base(base: &'static Env) -> Env {
+        Env {
+            raw: unsafe { ll::rocks_create_mem_env_with_base(base.raw) },
+        }
+    }
+
+    /// Returns a new environment that measures function call times of `base`,
+    /// e.g. `Env::new_timed_with_base(mem_env)` for timing an in-memory DB.
+    pub fn new_timed_with_base(base: &'static Env) -> Env {
+        Env {
+            raw: unsafe { ll::rocks_create_timed_env_with_base(base.raw) },
+        }
+    }
+
+    /// Returns a new environment that encrypts all files written through `base`, in CTR mode
+    /// with the ROT13 block cipher shipped with RocksDB.
+    ///
+    /// ROT13 is NOT secure, it's meant for testing the encrypted env code path, e.g. that a
+    /// deployment works with encrypted files at all. A DB can only be opened by the env it
+    /// was created with.
+    pub fn new_rot13_encrypted(base: &'static Env, block_size: usize) -> Env {
+        Env {
+            raw: unsafe { ll::rocks_create_rot13_encrypted_env(base.raw, block_size) },
+        }
+    }
+
+    /// Returns a new environment that transparently encrypts all files written through
+    /// `base`, SST, WAL and MANIFEST alike, with `cipher` in CTR mode.
+    ///
+    /// Each file starts with a prefix holding its random initial counter, so the file
+    /// sizes on disk are larger than those reported to RocksDB. The DB must always be
+    /// opened with the same cipher and key.
+    pub fn new_encrypted_env<C: BlockCipher + 'static>(base: &'static Env, cipher: C) -> Env {
+        let cipher: Box<Box<dyn BlockCipher>> = Box::new(Box::new(cipher));
+        Env {
+            raw: unsafe { ll::rocks_create_encrypted_env(base.raw, Box::into_raw(cipher) as *mut _) },
+        }
+    }
+
+    /// Returns a new environment that delays file reads, writes and syncs of the
+    /// default environment, for reproducing slow-disk behavior in tests.
+    ///
+    /// No latency is injected until configured via `set_injected_latency()`.
+    pub fn new_latency_injection() -> Env {
+        Env {
+            raw: unsafe { ll::rocks_create_latency_injection_env() },
+        }
+    }
+
+    /// Returns a new environment whose file system operations are implemented by `fs`,
+    /// e.g. an in-memory or object-store-backed storage. Non-file tasks are delegated
+    /// to the default environment.
+    ///
+    /// `DBOptions::env()` takes a `&'static Env`, use `Box::leak()` to get one.
+    pub fn from_file_system<F: FileSystem + 'static>(fs: F) -> Env {
+        let fs: Box<Box<dyn FileSystem>> = Box::new(Box::new(fs));
+        Env {
+            raw: unsafe { ll::rocks_create_rust_env(Box::into_raw(fs) as *mut _) },
+        }
+    }
+
+    /// Configure the latency distribution of an operation type. Can be changed
+    /// at any time, also while a DB is using this environment.
+    ///
+    /// Returns false if this `Env` is not created by `new_latency_injection()`.
+    pub fn set_injected_latency(&self, op: IoOperation, latency: InjectedLatency) -> bool {
         unsafe {
-            ll::rocks_env_set_background_threads(self.raw, number);
+            ll::rocks_env_set_injected_latency(
+                self.raw,
+                op as i32,
+                latency.probability,
+                latency.min_micros,
+                latency.max_micros,
+            ) != 0
         }
     }
 
+    /// The number of background worker threads of a specific thread pool
+    pub fn set_low_priority_background_threads(&self, number: i32) {
+        self.set_background_threads(number, Priority::Low);
+    }
+
     /// The number of background worker threads of a high priority thread pool
     pub fn set_high_priority_background_threads(&self, number: i32) {
-        unsafe {
-            ll::rocks_env_set_high_priority_background_threads(self.raw, number);
-        }
+        self.set_background_threads(number, Priority::High);
     }
 
     /// Wait for all threads started by StartThread to terminate.
@@ -320,7 +630,7 @@ impl Env {
 
     /// Get thread pool queue length for specific thrad pool.
     pub fn get_thread_pool_queue_len(&self, pri: Priority) -> u32 {
-        unsafe { ll::rocks_env_get_thread_pool_queue_len(self.raw, mem::transmute(pri)) as u32 }
+        unsafe { ll::rocks_env_get_thread_pool_queue_len(self.raw, pri as c_int) as u32 }
     }
 
     /// Create and return a log file for storing informational messages.
@@ -333,6 +643,99 @@ impl Env {
         }
     }
 
+    /// Lock the specified file. Used to prevent concurrent access to
+    /// the same db by multiple processes. On failure, returns an error.
+    ///
+    /// The lock is released when the returned guard is dropped. Fails
+    /// immediately if another process, or this process, holds the lock;
+    /// never blocks.
+    pub fn lock_file<P: AsRef<Path>>(&self, fname: P) -> Result<FileLock<'_>> {
+        let mut status = ptr::null_mut();
+        unsafe {
+            let name = fname.as_ref().to_str().unwrap();
+            let lock = ll::rocks_env_lock_file(self.raw, name.as_ptr() as *const _, name.len(), &mut status);
+            Error::from_ll(status).map(|_| FileLock { env: self, raw: lock })
+        }
+    }
+
+    /// The buffer alignment direct IO requires on the file system of `fname`, i.e. its
+    /// logical sector size, probed by creating and deleting `fname` with direct writes.
+    ///
+    /// Fails if the file system does not support direct IO, e.g. tmpfs.
+    pub fn get_direct_io_alignment<P: AsRef<Path>>(&self, fname: P) -> Result<usize> {
+        let mut status = ptr::null_mut();
+        unsafe {
+            let name = fname.as_ref().to_str().unwrap();
+            let alignment =
+                ll::rocks_env_get_direct_io_alignment(self.raw, name.as_ptr() as *const _, name.len(), &mut status);
+            Error::from_ll(status).map(|_| alignment)
+        }
+    }
+
+    /// The names of the children of the directory `dir`, relative to it, in no particular order.
+    pub fn get_children<P: AsRef<Path>>(&self, dir: P) -> Result<Vec<String>> {
+        let mut status = ptr::null_mut();
+        let mut len = 0;
+        unsafe {
+            let name = dir.as_ref().to_str().unwrap();
+            let children =
+                ll::rocks_env_get_children(self.raw, name.as_ptr() as *const _, name.len(), &mut len, &mut status);
+            Error::from_ll(status).map(|_| {
+                let ret = (0..len)
+                    .map(|i| CStr::from_ptr(*children.add(i)).to_string_lossy().into_owned())
+                    .collect();
+                ll::rocks_env_get_children_destroy(children, len);
+                ret
+            })
+        }
+    }
+
+    /// Whether the file exists, errors other than `NotFound` are returned.
+    pub fn file_exists<P: AsRef<Path>>(&self, fname: P) -> Result<bool> {
+        let mut status = ptr::null_mut();
+        let ret = unsafe {
+            let name = fname.as_ref().to_str().unwrap();
+            ll::rocks_env_file_exists(self.raw, name.as_ptr() as *const _, name.len(), &mut status);
+            Error::from_ll(status)
+        };
+        match ret {
+            Ok(()) => Ok(true),
+            Err(ref e) if e.is_not_found() => Ok(false),
+            Err(e) => Err(e),
+        }
+    }
+
+    /// The size of the file in bytes.
+    pub fn get_file_size<P: AsRef<Path>>(&self, fname: P) -> Result<u64> {
+        let mut status = ptr::null_mut();
+        unsafe {
+            let name = fname.as_ref().to_str().unwrap();
+            let size = ll::rocks_env_get_file_size(self.raw, name.as_ptr() as *const _, name.len(), &mut status);
+            Error::from_ll(status).map(|_| size)
+        }
+    }
+
+    /// The last modification time of the file, in seconds since the Epoch.
+    pub fn get_file_modification_time<P: AsRef<Path>>(&self, fname: P) -> Result<u64> {
+        let mut status = ptr::null_mut();
+        unsafe {
+            let name = fname.as_ref().to_str().unwrap();
+            let mtime =
+                ll::rocks_env_get_file_modification_time(self.raw, name.as_ptr() as *const _, name.len(), &mut status);
+            Error::from_ll(status).map(|_| mtime)
+        }
+    }
+
+    /// Delete the file.
+    pub fn delete_file<P: AsRef<Path>>(&self, fname: P) -> Result<()> {
+        let mut status = ptr::null_mut();
+        unsafe {
+            let name = fname.as_ref().to_str().unwrap();
+            ll::rocks_env_delete_file(self.raw, name.as_ptr() as *const _, name.len(), &mut status);
+            Error::from_ll(status)
+        }
+    }
+
     /// Returns the number of micro-seconds since some fixed point in time.
     /// It is often used as system time such as in GenericRateLimiter
     /// and other places so a port needs to return system time in order to work.
@@ -392,19 +795,18 @@ impl Env {
     /// The number of background worker threads of a specific thread pool
     /// for this environment. 'LOW' is the default pool.
     ///
-    /// default number: 1
+    /// default number: 1, 0 for the `Bottom` pool, whose compactions then run in the `Low` pool
     ///
     /// FIXME: &mut self ?
     pub fn set_background_threads(&self, number: i32, pri: Priority) {
-        match pri {
-            Priority::Low => self.set_low_priority_background_threads(number),
-            Priority::High => self.set_high_priority_background_threads(number),
-            _ => unreachable!("wrong pri for thread pool"),
+        assert!(pri != Priority::Total, "wrong pri for thread pool");
+        unsafe {
+            ll::rocks_env_set_background_threads(self.raw, number, pri as c_int);
         }
     }
 
     pub fn get_background_threads(&self, pri: Priority) -> i32 {
-        unsafe { ll::rocks_env_get_background_threads(self.raw, mem::transmute(pri)) as i32 }
+        unsafe { ll::rocks_env_get_background_threads(self.raw, pri as c_int) as i32 }
     }
 
     /// Enlarge number of background worker threads of a specific thread pool
@@ -412,14 +814,14 @@ impl Env {
     /// pool.
     pub fn inc_background_threads_if_needed(&self, number: i32, pri: Priority) {
         unsafe {
-            ll::rocks_env_inc_background_threads_if_needed(self.raw, number, mem::transmute(pri));
+            ll::rocks_env_inc_background_threads_if_needed(self.raw, number, pri as c_int);
         }
     }
 
     /// Lower IO priority for threads from the specified pool.
     pub fn lower_thread_pool_io_priority(&self, pool: Priority) {
         unsafe {
-            ll::rocks_env_lower_thread_pool_io_priority(self.raw, mem::transmute(pool));
+            ll::rocks_env_lower_thread_pool_io_priority(self.raw, pool as c_int);
         }
     }
 
@@ -443,11 +845,361 @@ impl Env {
     }
 }
 
+// call rust fn in C
+#[doc(hidden)]
+pub mod c {
+    use std::os::raw::{c_char, c_int, c_uchar};
+    use std::slice;
+
+    use super::*;
+
+    // must be the same as C part
+    unsafe fn io_result(ret: io::Result<()>, msg: *mut ()) -> c_int {
+        match ret {
+            Ok(()) => 0,
+            Err(e) => {
+                let m = e.to_string();
+                ll::cxx_string_assign(msg as *mut _, m.as_ptr() as *const _, m.len());
+                match e.kind() {
+                    io::ErrorKind::NotFound => 1,
+                    io::ErrorKind::Unsupported => 3,
+                    _ => 2,
+                }
+            },
+        }
+    }
+
+    unsafe fn path(p: *const c_char, len: usize) -> String {
+        String::from_utf8_lossy(slice::from_raw_parts(p as *const u8, len)).into_owned()
+    }
+
+    unsafe fn file_system<'a>(fs: *mut ()) -> &'a dyn FileSystem {
+        assert!(!fs.is_null());
+        &**(fs as *mut Box<dyn FileSystem>)
+    }
+
+    #[no_mangle]
+    pub unsafe extern "C" fn rust_file_system_new_sequential_file(
+        fs: *mut (),
+        fname: *const c_char,
+        fname_len: usize,
+        file: *mut *mut (),
+        msg: *mut (), // *std::string
+    ) -> c_int {
+        let ret = file_system(fs)
+            .new_sequential_file(&path(fname, fname_len))
+            .map(|f| *file = Box::into_raw(Box::new(f)) as *mut ());
+        io_result(ret, msg)
+    }
+
+    #[no_mangle]
+    pub unsafe extern "C" fn rust_file_system_new_random_access_file(
+        fs: *mut (),
+        fname: *const c_char,
+        fname_len: usize,
+        file: *mut *mut (),
+        msg: *mut (),
+    ) -> c_int {
+        let ret = file_system(fs)
+            .new_random_access_file(&path(fname, fname_len))
+            .map(|f| *file = Box::into_raw(Box::new(f)) as *mut ());
+        io_result(ret, msg)
+    }
+
+    #[no_mangle]
+    pub unsafe extern "C" fn rust_file_system_new_writable_file(
+        fs: *mut (),
+        fname: *const c_char,
+        fname_len: usize,
+        file: *mut *mut (),
+        msg: *mut (),
+    ) -> c_int {
+        let ret = file_system(fs)
+            .new_writable_file(&path(fname, fname_len))
+            .map(|f| *file = Box::into_raw(Box::new(f)) as *mut ());
+        io_result(ret, msg)
+    }
+
+    #[no_mangle]
+    pub unsafe extern "C" fn rust_file_system_file_exists(
+        fs: *mut (),
+        fname: *const c_char,
+        fname_len: usize,
+        msg: *mut (),
+    ) -> c_int {
+        io_result(file_system(fs).file_exists(&path(fname, fname_len)), msg)
+    }
+
+    #[no_mangle]
+    pub unsafe extern "C" fn rust_file_system_get_children(
+        fs: *mut (),
+        dir: *const c_char,
+        dir_len: usize,
+        result: *mut ll::cxx_string_vector_t,
+        msg: *mut (),
+    ) -> c_int {
+        let ret = file_system(fs).get_children(&path(dir, dir_len)).map(|children| {
+            for child in children {
+                ll::cxx_string_vector_push(result, child.as_ptr() as *const _, child.len());
+            }
+        });
+        io_result(ret, msg)
+    }
+
+    #[no_mangle]
+    pub unsafe extern "C" fn rust_file_system_delete_file(
+        fs: *mut (),
+        fname: *const c_char,
+        fname_len: usize,
+        msg: *mut (),
+    ) -> c_int {
+        io_result(file_system(fs).delete_file(&path(fname, fname_len)), msg)
+    }
+
+    #[no_mangle]
+    pub unsafe extern "C" fn rust_file_system_create_dir(
+        fs: *mut (),
+        dir: *const c_char,
+        dir_len: usize,
+        if_missing: c_uchar,
+        msg: *mut (),
+    ) -> c_int {
+        let dir = path(dir, dir_len);
+        if if_missing != 0 {
+            io_result(file_system(fs).create_dir_if_missing(&dir), msg)
+        } else {
+            io_result(file_system(fs).create_dir(&dir), msg)
+        }
+    }
+
+    #[no_mangle]
+    pub unsafe extern "C" fn rust_file_system_delete_dir(
+        fs: *mut (),
+        dir: *const c_char,
+        dir_len: usize,
+        msg: *mut (),
+    ) -> c_int {
+        io_result(file_system(fs).delete_dir(&path(dir, dir_len)), msg)
+    }
+
+    #[no_mangle]
+    pub unsafe extern "C" fn rust_file_system_get_file_size(
+        fs: *mut (),
+        fname: *const c_char,
+        fname_len: usize,
+        size: *mut u64,
+        msg: *mut (),
+    ) -> c_int {
+        let ret = file_system(fs)
+            .get_file_size(&path(fname, fname_len))
+            .map(|s| *size = s);
+        io_result(ret, msg)
+    }
+
+    #[no_mangle]
+    pub unsafe extern "C" fn rust_file_system_get_file_modification_time(
+        fs: *mut (),
+        fname: *const c_char,
+        fname_len: usize,
+        mtime: *mut u64,
+        msg: *mut (),
+    ) -> c_int {
+        let ret = file_system(fs)
+            .get_file_modification_time(&path(fname, fname_len))
+            .map(|t| *mtime = t);
+        io_result(ret, msg)
+    }
+
+    #[no_mangle]
+    pub unsafe extern "C" fn rust_file_system_rename_file(
+        fs: *mut (),
+        src: *const c_char,
+        src_len: usize,
+        target: *const c_char,
+        target_len: usize,
+        msg: *mut (),
+    ) -> c_int {
+        io_result(
+            file_system(fs).rename_file(&path(src, src_len), &path(target, target_len)),
+            msg,
+        )
+    }
+
+    #[no_mangle]
+    pub unsafe extern "C" fn rust_file_system_fsync_dir(
+        fs: *mut (),
+        dir: *const c_char,
+        dir_len: usize,
+        msg: *mut (),
+    ) -> c_int {
+        io_result(file_system(fs).fsync_dir(&path(dir, dir_len)), msg)
+    }
+
+    #[no_mangle]
+    pub unsafe extern "C" fn rust_file_system_drop(fs: *mut ()) {
+        assert!(!fs.is_null());
+        drop(Box::from_raw(fs as *mut Box<dyn FileSystem>));
+    }
+
+    #[no_mangle]
+    pub unsafe extern "C" fn rust_sequential_file_read(
+        f: *mut (),
+        buf: *mut c_char,
+        n: usize,
+        read: *mut usize,
+        msg: *mut (),
+    ) -> c_int {
+        assert!(!f.is_null());
+        let file = &mut **(f as *mut Box<dyn SequentialFile>);
+        let buf = slice::from_raw_parts_mut(buf as *mut u8, n);
+        let mut total = 0;
+        // fill the buffer, a short read means end of file to RocksDB
+        while total < n {
+            match file.read(&mut buf[total..]) {
+                Ok(0) => break,
+                Ok(len) => total += len,
+                Err(ref e) if e.kind() == io::ErrorKind::Interrupted => (),
+                Err(e) => return io_result(Err(e), msg),
+            }
+        }
+        *read = total;
+        0
+    }
+
+    #[no_mangle]
+    pub unsafe extern "C" fn rust_sequential_file_skip(f: *mut (), n: u64, msg: *mut ()) -> c_int {
+        assert!(!f.is_null());
+        let file = &mut **(f as *mut Box<dyn SequentialFile>);
+        io_result(file.skip(n), msg)
+    }
+
+    #[no_mangle]
+    pub unsafe extern "C" fn rust_sequential_file_drop(f: *mut ()) {
+        assert!(!f.is_null());
+        drop(Box::from_raw(f as *mut Box<dyn SequentialFile>));
+    }
+
+    #[no_mangle]
+    pub unsafe extern "C" fn rust_random_access_file_read(
+        f: *mut (),
+        offset: u64,
+        buf: *mut c_char,
+        n: usize,
+        read: *mut usize,
+        msg: *mut (),
+    ) -> c_int {
+        assert!(!f.is_null());
+        let file = &**(f as *mut Box<dyn RandomAccessFile>);
+        let buf = slice::from_raw_parts_mut(buf as *mut u8, n);
+        let mut total = 0;
+        while total < n {
+            match file.read_at(offset + total as u64, &mut buf[total..]) {
+                Ok(0) => break,
+                Ok(len) => total += len,
+                Err(ref e) if e.kind() == io::ErrorKind::Interrupted => (),
+                Err(e) => return io_result(Err(e), msg),
+            }
+        }
+        *read = total;
+        0
+    }
+
+    #[no_mangle]
+    pub unsafe extern "C" fn rust_random_access_file_drop(f: *mut ()) {
+        assert!(!f.is_null());
+        drop(Box::from_raw(f as *mut Box<dyn RandomAccessFile>));
+    }
+
+    #[no_mangle]
+    pub unsafe extern "C" fn rust_writable_file_append(
+        f: *mut (),
+        data: *const c_char,
+        len: usize,
+        msg: *mut (),
+    ) -> c_int {
+        assert!(!f.is_null());
+        let file = &mut **(f as *mut Box<dyn WritableFile>);
+        io_result(file.append(slice::from_raw_parts(data as *const u8, len)), msg)
+    }
+
+    #[no_mangle]
+    pub unsafe extern "C" fn rust_writable_file_close(f: *mut (), msg: *mut ()) -> c_int {
+        assert!(!f.is_null());
+        io_result((**(f as *mut Box<dyn WritableFile>)).close(), msg)
+    }
+
+    #[no_mangle]
+    pub unsafe extern "C" fn rust_writable_file_flush(f: *mut (), msg: *mut ()) -> c_int {
+        assert!(!f.is_null());
+        io_result((**(f as *mut Box<dyn WritableFile>)).flush(), msg)
+    }
+
+    #[no_mangle]
+    pub unsafe extern "C" fn rust_writable_file_sync(f: *mut (), msg: *mut ()) -> c_int {
+        assert!(!f.is_null());
+        io_result((**(f as *mut Box<dyn WritableFile>)).sync(), msg)
+    }
+
+    #[no_mangle]
+    pub unsafe extern "C" fn rust_writable_file_drop(f: *mut ()) {
+        assert!(!f.is_null());
+        drop(Box::from_raw(f as *mut Box<dyn WritableFile>));
+    }
+
+    #[no_mangle]
+    pub unsafe extern "C" fn rust_block_cipher_block_size(c: *mut ()) -> usize {
+        assert!(!c.is_null());
+        (**(c as *mut Box<dyn BlockCipher>)).block_size()
+    }
+
+    #[no_mangle]
+    pub unsafe extern "C" fn rust_block_cipher_encrypt(c: *mut (), data: *mut c_char, len: usize) {
+        assert!(!c.is_null());
+        (**(c as *mut Box<dyn BlockCipher>)).encrypt(slice::from_raw_parts_mut(data as *mut u8, len))
+    }
+
+    #[no_mangle]
+    pub unsafe extern "C" fn rust_block_cipher_decrypt(c: *mut (), data: *mut c_char, len: usize) {
+        assert!(!c.is_null());
+        (**(c as *mut Box<dyn BlockCipher>)).decrypt(slice::from_raw_parts_mut(data as *mut u8, len))
+    }
+
+    #[no_mangle]
+    pub unsafe extern "C" fn rust_block_cipher_drop(c: *mut ()) {
+        assert!(!c.is_null());
+        drop(Box::from_raw(c as *mut Box<dyn BlockCipher>));
+    }
+
+    unsafe fn info_logger<'a>(l: *mut ()) -> &'a dyn InfoLogger {
+        assert!(!l.is_null());
+        &**(l as *mut Box<dyn InfoLogger>)
+    }
+
+    #[no_mangle]
+    pub unsafe extern "C" fn rust_info_logger_logv(l: *mut (), log_level: c_int, msg: *const c_char, len: usize) {
+        let msg = String::from_utf8_lossy(slice::from_raw_parts(msg as *const u8, len));
+        info_logger(l).logv(mem::transmute::<c_int, InfoLogLevel>(log_level), &msg);
+    }
+
+    #[no_mangle]
+    pub unsafe extern "C" fn rust_info_logger_flush(l: *mut ()) {
+        info_logger(l).flush();
+    }
+
+    #[no_mangle]
+    pub unsafe extern "C" fn rust_info_logger_drop(l: *mut ()) {
+        assert!(!l.is_null());
+        drop(Box::from_raw(l as *mut Box<dyn InfoLogger>));
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
+    use std::collections::HashMap;
     use std::fs::File;
     use std::io::prelude::*;
+    use std::sync::{Arc, Mutex};
 
     #[test]
     fn env_basic() {
@@ -460,6 +1212,22 @@ mod tests {
         assert!(env.time_to_string(env.get_current_time().unwrap()).len() > 10);
     }
 
+    #[test]
+    fn thread_pools() {
+        let env = Env::default_instance();
+
+        env.set_background_threads(3, Priority::Low);
+        env.set_background_threads(2, Priority::High);
+        env.set_background_threads(1, Priority::Bottom);
+        assert_eq!(env.get_background_threads(Priority::Low), 3);
+        assert_eq!(env.get_background_threads(Priority::High), 2);
+        assert_eq!(env.get_background_threads(Priority::Bottom), 1);
+
+        env.inc_background_threads_if_needed(2, Priority::Bottom);
+        assert_eq!(env.get_background_threads(Priority::Bottom), 2);
+        assert_eq!(env.get_thread_pool_queue_len(Priority::Bottom), 0);
+    }
+
     #[test]
     fn logger() {
         let log_dir = ::tempdir::TempDir::new_in(".", "log").unwrap();
@@ -486,4 +1254,361 @@ mod tests {
         assert!(s.contains("[ERROR] test log message"));
         assert!(!s.contains("debug log message"));
     }
+
+    #[test]
+    fn latency_injection() {
+        use crate::rocksdb::*;
+        use std::time::{Duration, Instant};
+
+        let tmp_dir = ::tempdir::TempDir::new_in(".", "rocks").unwrap();
+        let env: &'static Env = Box::leak(Box::new(Env::new_latency_injection()));
+        assert!(!Env::default_instance().set_injected_latency(IoOperation::Sync, InjectedLatency::fixed(1)));
+
+        let db = DB::open(
+            Options::default().map_db_options(|db| db.create_if_missing(true).env(env)),
+            &tmp_dir,
+        )
+        .unwrap();
+
+        assert!(env.set_injected_latency(IoOperation::Sync, InjectedLatency::fixed(50_000)));
+        let start = Instant::now();
+        assert!(db.put(&WriteOptions::default().sync(true), b"key", b"value").is_ok());
+        assert!(start.elapsed() >= Duration::from_millis(50));
+
+        assert!(env.set_injected_latency(IoOperation::Sync, InjectedLatency::none()));
+        assert_eq!(db.get(&ReadOptions::default(), b"key").unwrap(), b"value");
+    }
+
+    #[test]
+    fn file_operations() {
+        use crate::rocksdb::*;
+
+        let mem: &'static Env = Box::leak(Box::new(Env::new_mem()));
+        let db = DB::open(
+            Options::default().map_db_options(|db| db.create_if_missing(true).env(mem)),
+            "/in-memory/files",
+        )
+        .unwrap();
+        assert!(db.put(&WriteOptions::default(), b"key", b"value").is_ok());
+        assert!(db.flush(&FlushOptions::default().wait(true)).is_ok());
+
+        // only visible through the env
+        assert!(!Path::new("/in-memory/files").exists());
+        let children = mem.get_children("/in-memory/files").unwrap();
+        assert!(children.iter().any(|name| name == "CURRENT"), "{:?}", children);
+        let sst = children.iter().find(|name| name.ends_with(".sst")).unwrap();
+        let sst = format!("/in-memory/files/{}", sst);
+
+        assert!(mem.file_exists(&sst).unwrap());
+        assert!(mem.get_file_size(&sst).unwrap() > 0);
+        assert!(mem.get_file_modification_time(&sst).unwrap() > 0);
+        assert!(!mem.file_exists("/in-memory/files/MISSING").unwrap());
+        assert!(mem.get_file_size("/in-memory/files/MISSING").is_err());
+
+        drop(db);
+        assert!(mem.delete_file(&sst).is_ok());
+        assert!(!mem.file_exists(&sst).unwrap());
+        assert!(mem.delete_file(&sst).is_err());
+    }
+
+    #[test]
+    fn rust_info_logger() {
+        use crate::rocksdb::*;
+
+        #[derive(Clone, Default)]
+        struct Lines(Arc<Mutex<Vec<(InfoLogLevel, String)>>>);
+
+        impl InfoLogger for Lines {
+            fn logv(&self, log_level: InfoLogLevel, msg: &str) {
+                self.0.lock().unwrap().push((log_level, msg.to_owned()));
+            }
+        }
+
+        let tmp_dir = ::tempdir::TempDir::new_in(".", "rocks").unwrap();
+        let lines = Lines::default();
+        let db = DB::open(
+            Options::default().map_db_options(|db| {
+                db.create_if_missing(true)
+                    .info_log(Some(Logger::new(lines.clone(), InfoLogLevel::Info)))
+            }),
+            &tmp_dir,
+        )
+        .unwrap();
+        assert!(db.put(&WriteOptions::default(), b"key", b"value").is_ok());
+        assert!(db.flush(&FlushOptions::default().wait(true)).is_ok());
+        drop(db);
+
+        let lines = lines.0.lock().unwrap();
+        assert!(lines
+            .iter()
+            .any(|(level, msg)| *level == InfoLogLevel::Header && msg.contains("RocksDB version")));
+        assert!(lines.iter().any(|(level, _)| *level == InfoLogLevel::Info));
+        assert!(lines.iter().all(|(level, _)| *level != InfoLogLevel::Debug));
+        // no LOG file
+        assert!(!tmp_dir.path().join("LOG").exists());
+    }
+
+    #[test]
+    fn builtin_loggers() {
+        use crate::rocksdb::*;
+
+        let tmp_dir = ::tempdir::TempDir::new_in(".", "rocks").unwrap();
+        {
+            let db = DB::open(
+                Options::default().map_db_options(|db| db.create_if_missing(true).info_log(Some(Logger::devnull()))),
+                &tmp_dir,
+            )
+            .unwrap();
+            assert!(db.put(&WriteOptions::default(), b"key", b"value").is_ok());
+        }
+        assert!(!tmp_dir.path().join("LOG").exists());
+
+        let mut logger = Logger::stderr(InfoLogLevel::Warn);
+        assert_eq!(logger.get_log_level(), InfoLogLevel::Warn);
+        logger.set_log_level(InfoLogLevel::Error);
+        logger.log(InfoLogLevel::Error, "to stderr");
+        logger.flush();
+
+        let log_dir = ::tempdir::TempDir::new_in(".", "rocks").unwrap();
+        let options = DBOptions::default().db_log_dir(log_dir.path());
+        let logger = Logger::from_options(tmp_dir.path(), &options).unwrap();
+        logger.flush();
+        assert_eq!(log_dir.path().read_dir().unwrap().count(), 1);
+        assert!(!tmp_dir.path().join("LOG").exists());
+    }
+
+    #[test]
+    fn composite_envs() {
+        use crate::rocksdb::*;
+
+        let mem: &'static Env = Box::leak(Box::new(Env::new_mem_with_base(Env::default_instance())));
+        let timed: &'static Env = Box::leak(Box::new(Env::new_timed_with_base(mem)));
+        {
+            let db = DB::open(
+                Options::default().map_db_options(|db| db.create_if_missing(true).env(timed)),
+                "/in-memory/db",
+            )
+            .unwrap();
+            assert!(db.put(&WriteOptions::default(), b"key", b"value").is_ok());
+            assert_eq!(db.get(&ReadOptions::default(), b"key").unwrap(), b"value");
+        }
+        assert!(!Path::new("/in-memory/db").exists());
+
+        let tmp_dir = ::tempdir::TempDir::new_in(".", "rocks").unwrap();
+        let encrypted: &'static Env = Box::leak(Box::new(Env::new_rot13_encrypted(Env::default_instance(), 32)));
+        let opts = || Options::default().map_db_options(|db| db.create_if_missing(true).env(encrypted));
+        {
+            let db = DB::open(opts(), &tmp_dir).unwrap();
+            assert!(db.put(&WriteOptions::default(), b"key", b"plaintext-value").is_ok());
+            assert!(db.flush(&FlushOptions::default().wait(true)).is_ok());
+        }
+        for entry in std::fs::read_dir(&tmp_dir).unwrap() {
+            let content = std::fs::read(entry.unwrap().path()).unwrap();
+            assert!(!content.windows(15).any(|w| w == b"plaintext-value"));
+        }
+        let db = DB::open(opts(), &tmp_dir).unwrap();
+        assert_eq!(db.get(&ReadOptions::default(), b"key").unwrap(), b"plaintext-value");
+    }
+
+    #[test]
+    fn encrypted_env() {
+        use crate::rocksdb::*;
+
+        // NOT a cipher, just enough to scramble the files
+        struct XorCipher(u8);
+
+        impl BlockCipher for XorCipher {
+            fn block_size(&self) -> usize {
+                16
+            }
+
+            fn encrypt(&self, block: &mut [u8]) {
+                for (i, b) in block.iter_mut().enumerate() {
+                    *b ^= self.0.wrapping_add(i as u8);
+                }
+            }
+
+            fn decrypt(&self, block: &mut [u8]) {
+                self.encrypt(block)
+            }
+        }
+
+        let tmp_dir = ::tempdir::TempDir::new_in(".", "rocks").unwrap();
+        let env: &'static Env = Box::leak(Box::new(Env::new_encrypted_env(
+            Env::default_instance(),
+            XorCipher(0x5a),
+        )));
+        let opts = || Options::default().map_db_options(|db| db.create_if_missing(true).env(env));
+        {
+            let db = DB::open(opts(), &tmp_dir).unwrap();
+            assert!(db.put(&WriteOptions::default(), b"key", b"plaintext-value").is_ok());
+            assert!(db.flush(&FlushOptions::default().wait(true)).is_ok());
+            assert!(db.put(&WriteOptions::default(), b"wal-key", b"plaintext-in-wal").is_ok());
+        }
+        for entry in std::fs::read_dir(&tmp_dir).unwrap() {
+            let content = std::fs::read(entry.unwrap().path()).unwrap();
+            assert!(!content.windows(9).any(|w| w == b"plaintext"));
+        }
+
+        let db = DB::open(opts(), &tmp_dir).unwrap();
+        assert_eq!(db.get(&ReadOptions::default(), b"key").unwrap(), b"plaintext-value");
+        assert_eq!(db.get(&ReadOptions::default(), b"wal-key").unwrap(), b"plaintext-in-wal");
+        drop(db);
+        assert!(DB::open(Options::default(), &tmp_dir).is_err());
+    }
+
+    #[derive(Default, Clone)]
+    struct MemFileSystem {
+        files: Arc<Mutex<HashMap<String, Arc<Mutex<Vec<u8>>>>>>,
+    }
+
+    struct MemFile {
+        data: Arc<Mutex<Vec<u8>>>,
+        pos: usize,
+    }
+
+    impl SequentialFile for MemFile {
+        fn read(&mut self, buf: &mut [u8]) -> io::Result<usize> {
+            let n = self.read_at(self.pos as u64, buf)?;
+            self.pos += n;
+            Ok(n)
+        }
+
+        fn skip(&mut self, n: u64) -> io::Result<()> {
+            self.pos += n as usize;
+            Ok(())
+        }
+    }
+
+    impl RandomAccessFile for MemFile {
+        fn read_at(&self, offset: u64, buf: &mut [u8]) -> io::Result<usize> {
+            let data = self.data.lock().unwrap();
+            let start = (offset as usize).min(data.len());
+            let n = buf.len().min(data.len() - start);
+            buf[..n].copy_from_slice(&data[start..start + n]);
+            Ok(n)
+        }
+    }
+
+    impl WritableFile for MemFile {
+        fn append(&mut self, data: &[u8]) -> io::Result<()> {
+            self.data.lock().unwrap().extend_from_slice(data);
+            Ok(())
+        }
+
+        fn flush(&mut self) -> io::Result<()> {
+            Ok(())
+        }
+
+        fn sync(&mut self) -> io::Result<()> {
+            Ok(())
+        }
+
+        fn close(&mut self) -> io::Result<()> {
+            Ok(())
+        }
+    }
+
+    impl MemFileSystem {
+        fn open(&self, fname: &str) -> io::Result<MemFile> {
+            match self.files.lock().unwrap().get(fname) {
+                Some(data) => Ok(MemFile {
+                    data: data.clone(),
+                    pos: 0,
+                }),
+                None => Err(io::Error::new(io::ErrorKind::NotFound, fname)),
+            }
+        }
+    }
+
+    impl FileSystem for MemFileSystem {
+        fn new_sequential_file(&self, fname: &str) -> io::Result<Box<dyn SequentialFile>> {
+            Ok(Box::new(self.open(fname)?))
+        }
+
+        fn new_random_access_file(&self, fname: &str) -> io::Result<Box<dyn RandomAccessFile>> {
+            Ok(Box::new(self.open(fname)?))
+        }
+
+        fn new_writable_file(&self, fname: &str) -> io::Result<Box<dyn WritableFile>> {
+            let data = Arc::new(Mutex::new(vec![]));
+            self.files.lock().unwrap().insert(fname.to_owned(), data.clone());
+            Ok(Box::new(MemFile { data, pos: 0 }))
+        }
+
+        fn file_exists(&self, fname: &str) -> io::Result<()> {
+            self.open(fname).map(|_| ())
+        }
+
+        fn get_children(&self, dir: &str) -> io::Result<Vec<String>> {
+            let prefix = format!("{}/", dir);
+            Ok(self
+                .files
+                .lock()
+                .unwrap()
+                .keys()
+                .filter_map(|name| name.strip_prefix(&prefix))
+                .map(|name| name.to_owned())
+                .collect())
+        }
+
+        fn delete_file(&self, fname: &str) -> io::Result<()> {
+            self.files
+                .lock()
+                .unwrap()
+                .remove(fname)
+                .map(|_| ())
+                .ok_or_else(|| io::Error::new(io::ErrorKind::NotFound, fname))
+        }
+
+        fn create_dir(&self, _dir: &str) -> io::Result<()> {
+            Ok(())
+        }
+
+        fn create_dir_if_missing(&self, _dir: &str) -> io::Result<()> {
+            Ok(())
+        }
+
+        fn delete_dir(&self, _dir: &str) -> io::Result<()> {
+            Ok(())
+        }
+
+        fn get_file_size(&self, fname: &str) -> io::Result<u64> {
+            Ok(self.open(fname)?.data.lock().unwrap().len() as u64)
+        }
+
+        fn get_file_modification_time(&self, _fname: &str) -> io::Result<u64> {
+            Ok(0)
+        }
+
+        fn rename_file(&self, src: &str, target: &str) -> io::Result<()> {
+            let mut files = self.files.lock().unwrap();
+            let data = files
+                .remove(src)
+                .ok_or_else(|| io::Error::new(io::ErrorKind::NotFound, src))?;
+            files.insert(target.to_owned(), data);
+            Ok(())
+        }
+    }
+
+    #[test]
+    fn custom_file_system() {
+        use crate::rocksdb::*;
+
+        let fs = MemFileSystem::default();
+        let env: &'static Env = Box::leak(Box::new(Env::from_file_system(fs.clone())));
+        let opts = || Options::default().map_db_options(|db| db.create_if_missing(true).env(env));
+
+        {
+            let db = DB::open(opts(), "/virtual/db").unwrap();
+            assert!(db.put(&WriteOptions::default(), b"key", b"value").is_ok());
+            assert!(db.flush(&FlushOptions::default().wait(true)).is_ok());
+        }
+        assert!(!Path::new("/virtual/db").exists());
+        assert!(fs.files.lock().unwrap().contains_key("/virtual/db/CURRENT"));
+
+        let db = DB::open(opts(), "/virtual/db").unwrap();
+        assert_eq!(db.get(&ReadOptions::default(), b"key").unwrap(), b"value");
+        assert!(DB::open(Options::default().map_db_options(|db| db.env(env)), "/virtual/missing").is_err());
+    }
 }
@@ -8,7 +8,7 @@
 //! non-const method, all threads accessing the same Status must use
 //! external synchronization.
 
-use std::ffi::CStr;
+use std::ffi::{CStr, CString};
 use std::fmt;
 use std::mem;
 use std::str;
@@ -65,11 +65,22 @@ pub enum Severity {
     UnrecoverableError = 4,
 }
 
-#[derive(Clone, PartialEq, Eq, Hash)]
+#[derive(PartialEq, Eq, Hash)]
 pub enum Error {
     LowLevel(*mut ll::rocks_status_t),
 }
 
+// a copy of the status, each Error frees its own
+impl Clone for Error {
+    fn clone(&self) -> Self {
+        Error::LowLevel(unsafe { ll::rocks_status_copy(self.raw()) })
+    }
+}
+
+// Status is an immutable value, owned by the Error
+unsafe impl Send for Error {}
+unsafe impl Sync for Error {}
+
 impl ToRaw<ll::rocks_status_t> for Error {
     fn raw(&self) -> *mut ll::rocks_status_t {
         match *self {
@@ -101,6 +112,88 @@ impl Error {
         self.code() == Code::NotFound
     }
 
+    pub fn is_corruption(&self) -> bool {
+        self.code() == Code::Corruption
+    }
+
+    pub fn is_not_supported(&self) -> bool {
+        self.code() == Code::NotSupported
+    }
+
+    pub fn is_invalid_argument(&self) -> bool {
+        self.code() == Code::InvalidArgument
+    }
+
+    pub fn is_io_error(&self) -> bool {
+        self.code() == Code::IOError
+    }
+
+    pub fn is_merge_in_progress(&self) -> bool {
+        self.code() == Code::MergeInProgress
+    }
+
+    pub fn is_incomplete(&self) -> bool {
+        self.code() == Code::Incomplete
+    }
+
+    pub fn is_shutdown_in_progress(&self) -> bool {
+        self.code() == Code::ShutdownInProgress
+    }
+
+    pub fn is_timed_out(&self) -> bool {
+        self.code() == Code::TimedOut
+    }
+
+    pub fn is_aborted(&self) -> bool {
+        self.code() == Code::Aborted
+    }
+
+    pub fn is_busy(&self) -> bool {
+        self.code() == Code::Busy
+    }
+
+    pub fn is_expired(&self) -> bool {
+        self.code() == Code::Expired
+    }
+
+    pub fn is_try_again(&self) -> bool {
+        self.code() == Code::TryAgain
+    }
+
+    pub fn is_compaction_too_large(&self) -> bool {
+        self.code() == Code::CompactionTooLarge
+    }
+
+    pub fn is_column_family_dropped(&self) -> bool {
+        self.code() == Code::ColumnFamilyDropped
+    }
+
+    /// An `IOError` because the disk is full.
+    pub fn is_no_space(&self) -> bool {
+        self.code() == Code::IOError && self.subcode() == SubCode::NoSpace
+    }
+
+    /// An `Aborted` error because a memory limit is reached.
+    pub fn is_memory_limit(&self) -> bool {
+        self.code() == Code::Aborted && self.subcode() == SubCode::MemoryLimit
+    }
+
+    /// An `IOError` or `NotFound` error because a file or directory does not exist.
+    pub fn is_path_not_found(&self) -> bool {
+        (self.code() == Code::IOError || self.code() == Code::NotFound) && self.subcode() == SubCode::PathNotFound
+    }
+
+    /// An `Incomplete` error because a manual compaction is paused.
+    pub fn is_manual_compaction_paused(&self) -> bool {
+        self.code() == Code::Incomplete && self.subcode() == SubCode::ManualCompactionPaused
+    }
+
+    /// Whether the same operation may succeed if retried later, i.e. `Busy`, `TryAgain`
+    /// and `TimedOut`, conflicts and lock timeouts of transactions.
+    pub fn is_retryable(&self) -> bool {
+        matches!(self.code(), Code::Busy | Code::TryAgain | Code::TimedOut)
+    }
+
     pub fn code(&self) -> Code {
         unsafe { mem::transmute(ll::rocks_status_code(self.raw())) }
     }
@@ -124,6 +217,54 @@ impl Error {
     pub(crate) fn from_ll(raw: *mut ll::rocks_status_t) -> Result<(), Self> {
         unsafe { FromRaw::from_ll(raw) }
     }
+
+    /// A `Busy` error created on the Rust side.
+    pub(crate) fn busy(msg: &str) -> Error {
+        let msg = status_message(msg);
+        Error::LowLevel(unsafe { ll::rocks_status_create_busy(msg.as_ptr()) })
+    }
+
+    /// An `InvalidArgument` error created on the Rust side.
+    pub(crate) fn invalid_argument(msg: &str) -> Error {
+        let msg = status_message(msg);
+        Error::LowLevel(unsafe { ll::rocks_status_create_invalid_argument(msg.as_ptr()) })
+    }
+
+    /// A `TimedOut` error created on the Rust side.
+    pub(crate) fn timed_out(msg: &str) -> Error {
+        let msg = status_message(msg);
+        Error::LowLevel(unsafe { ll::rocks_status_create_timed_out(msg.as_ptr()) })
+    }
+
+    /// A `NotSupported` error created on the Rust side.
+    pub(crate) fn not_supported(msg: &str) -> Error {
+        let msg = status_message(msg);
+        Error::LowLevel(unsafe { ll::rocks_status_create_not_supported(msg.as_ptr()) })
+    }
+
+    /// A `Corruption` error created on the Rust side.
+    pub(crate) fn corruption(msg: &str) -> Error {
+        let msg = status_message(msg);
+        Error::LowLevel(unsafe { ll::rocks_status_create_corruption(msg.as_ptr()) })
+    }
+
+    /// An `Aborted` error created on the Rust side.
+    pub(crate) fn aborted(msg: &str) -> Error {
+        let msg = status_message(msg);
+        Error::LowLevel(unsafe { ll::rocks_status_create_aborted(msg.as_ptr()) })
+    }
+
+    /// Hand the underlying status over to C++, which is responsible for freeing it.
+    pub(crate) fn into_raw(self) -> *mut ll::rocks_status_t {
+        let raw = self.raw();
+        mem::forget(self);
+        raw
+    }
+}
+
+// NUL bytes would cut the message short on the C++ side, so they are escaped
+fn status_message(msg: &str) -> CString {
+    CString::new(msg.replace('\0', "\\0")).expect("no NUL bytes")
 }
 
 impl fmt::Display for Error {
@@ -139,3 +280,43 @@ impl fmt::Debug for Error {
 }
 
 impl ::std::error::Error for Error {}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn error_kinds() {
+        let err = Error::busy("write conflict");
+        assert_eq!(err.code(), Code::Busy);
+        assert_eq!(err.subcode(), SubCode::None);
+        assert_eq!(err.state(), "write conflict");
+        assert!(err.is_busy() && err.is_retryable());
+        assert!(!err.is_not_found() && !err.is_try_again());
+
+        let err = Error::invalid_argument("bad option");
+        assert!(err.is_invalid_argument());
+        assert!(!err.is_retryable());
+
+        let err: Box<dyn ::std::error::Error + Send + Sync> = Box::new(Error::timed_out("lock"));
+        assert!(err.downcast_ref::<Error>().unwrap().is_timed_out());
+        assert!(err.to_string().contains("TimedOut"));
+    }
+
+    #[test]
+    fn error_message_with_nul() {
+        let err = Error::invalid_argument("no such index: a\0b");
+        assert!(err.is_invalid_argument());
+        assert_eq!(err.state(), "no such index: a\\0b");
+    }
+
+    #[test]
+    fn error_clone() {
+        let err = Error::busy("write conflict");
+        let cloned = err.clone();
+        assert_ne!(err.raw(), cloned.raw());
+        drop(err);
+        assert!(cloned.is_busy());
+        assert_eq!(cloned.state(), "write conflict");
+    }
+}
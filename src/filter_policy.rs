@@ -9,6 +9,9 @@
 //! Most people will want to use the builtin bloom filter support (see
 //! `NewBloomFilterPolicy()` below).
 
+use std::os::raw::c_char;
+use std::slice;
+
 use rocks_sys as ll;
 
 use crate::to_raw::ToRaw;
@@ -56,51 +59,169 @@ impl FilterPolicy {
             raw: unsafe { ll::rocks_raw_filterpolicy_new_bloomfilter(bits_per_key, use_block_based_builder as u8) },
         }
     }
+
+    /// Return a new filter policy backed by a Rust `CustomFilterPolicy`.
+    ///
+    /// Custom policies always build block based filters.
+    pub fn new_custom<T: CustomFilterPolicy + 'static>(policy: T) -> FilterPolicy {
+        let raw_ptr = Box::into_raw(Box::new(Box::new(policy) as Box<dyn CustomFilterPolicy>));
+        FilterPolicy {
+            raw: unsafe { ll::rocks_raw_filterpolicy_new_rust(raw_ptr as *mut _) },
+        }
+    }
+}
+
+/// A user defined filter policy, used for block based filters.
+///
+/// Registered via `BlockBasedTableOptions::filter_policy(Some(FilterPolicy::new_custom(...)))`.
+/// Must be thread-safe since rocksdb may invoke its methods concurrently
+/// from multiple threads.
+pub trait CustomFilterPolicy: Send + Sync {
+    /// Return the name of this policy.  Note that if the filter encoding
+    /// changes in an incompatible way, the name returned by this method
+    /// must be changed.  Otherwise, old incompatible filters may be
+    /// passed to methods of this type.
+    // FIXME: \0 ended
+    fn name(&self) -> &str {
+        "rust-rocks.FilterPolicy\0"
+    }
+
+    /// keys[0,n-1] contains a list of keys (potentially with duplicates)
+    /// that are ordered according to the user supplied comparator.
+    /// Append a filter that summarizes keys[0,n-1] to `dst`.
+    ///
+    /// `dst` is passed empty, its contents are appended to the filters
+    /// already built for the table.
+    fn create_filter(&self, keys: &[&[u8]], dst: &mut Vec<u8>);
+
+    /// `filter` contains the data appended by a preceding call to
+    /// `create_filter()` on this class.  This method must return true if
+    /// the key was in the list of keys passed to `create_filter()`.
+    /// This method may return true or false if the key was not on the
+    /// list, but it should aim to return false with a high probability.
+    fn key_may_match(&self, key: &[u8], filter: &[u8]) -> bool;
+}
+
+#[doc(hidden)]
+pub mod c {
+    use super::*;
+
+    #[no_mangle]
+    pub unsafe extern "C" fn rust_filter_policy_name(fp: *mut ()) -> *const c_char {
+        let policy = fp as *mut Box<dyn CustomFilterPolicy>;
+        (*policy).name().as_ptr() as *const _
+    }
+
+    #[no_mangle]
+    pub unsafe extern "C" fn rust_filter_policy_create_filter(
+        fp: *mut (),
+        keys: *const &[u8], // const Slice*
+        n: i32,
+        dst: *mut (), // std::string*
+    ) {
+        let policy = fp as *mut Box<dyn CustomFilterPolicy>;
+        let keys = slice::from_raw_parts(keys, n as usize);
+        // dst holds the filters of previous blocks, only the new one is copied over
+        let mut buf = Vec::new();
+        (*policy).create_filter(keys, &mut buf);
+        ll::cxx_string_append(dst as *mut _, buf.as_ptr() as *const _, buf.len());
+    }
+
+    #[no_mangle]
+    pub unsafe extern "C" fn rust_filter_policy_key_may_match(fp: *mut (), key: &&[u8], filter: &&[u8]) -> c_char {
+        let policy = fp as *mut Box<dyn CustomFilterPolicy>;
+        (*policy).key_may_match(key, filter) as c_char
+    }
+
+    #[no_mangle]
+    pub unsafe extern "C" fn rust_filter_policy_drop(fp: *mut ()) {
+        let policy = fp as *mut Box<dyn CustomFilterPolicy>;
+        drop(Box::from_raw(policy));
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::sync::atomic::{AtomicUsize, Ordering};
+    use std::sync::Arc;
+
+    use super::super::rocksdb::*;
+    use super::*;
+
+    /// Stores every key with a one byte length prefix.
+    struct ExactFilter {
+        probes: Arc<AtomicUsize>,
+    }
+
+    impl CustomFilterPolicy for ExactFilter {
+        fn name(&self) -> &str {
+            "ExactFilter\0"
+        }
+
+        fn create_filter(&self, keys: &[&[u8]], dst: &mut Vec<u8>) {
+            for key in keys {
+                dst.push(key.len() as u8);
+                dst.extend_from_slice(key);
+            }
+        }
+
+        fn key_may_match(&self, key: &[u8], mut filter: &[u8]) -> bool {
+            self.probes.fetch_add(1, Ordering::SeqCst);
+            while !filter.is_empty() {
+                let len = filter[0] as usize;
+                if &filter[1..1 + len] == key {
+                    return true;
+                }
+                filter = &filter[1 + len..];
+            }
+            false
+        }
+    }
+
+    #[test]
+    fn custom_filter_policy() {
+        let tmp_dir = ::tempdir::TempDir::new_in(".", "rocks").unwrap();
+        let probes = Arc::new(AtomicUsize::new(0));
+        let policy = FilterPolicy::new_custom(ExactFilter { probes: probes.clone() });
+        let db = DB::open(
+            Options::default()
+                .map_db_options(|db| db.create_if_missing(true))
+                .map_cf_options(|cf| {
+                    cf.table_factory_block_based(BlockBasedTableOptions::default().filter_policy(Some(policy)))
+                }),
+            &tmp_dir,
+        )
+        .unwrap();
+
+        for i in 0..100 {
+            let key = format!("k{}", i);
+            assert!(db.put(WriteOptions::default_instance(), key.as_bytes(), b"v").is_ok());
+        }
+        assert!(db.flush(&FlushOptions::default().wait(true)).is_ok());
+
+        assert!(db.get(ReadOptions::default_instance(), b"k42").is_ok());
+        assert!(db.get(ReadOptions::default_instance(), b"k420").unwrap_err().is_not_found());
+        assert!(probes.load(Ordering::SeqCst) >= 2);
+    }
+
+    #[test]
+    fn bloom_filter_formats() {
+        for &use_block_based_builder in &[true, false] {
+            let tmp_dir = ::tempdir::TempDir::new_in(".", "rocks").unwrap();
+            let policy = FilterPolicy::new_bloom_filter(10, use_block_based_builder);
+            let db = DB::open(
+                Options::default()
+                    .map_db_options(|db| db.create_if_missing(true))
+                    .map_cf_options(|cf| {
+                        cf.table_factory_block_based(BlockBasedTableOptions::default().filter_policy(Some(policy)))
+                    }),
+                &tmp_dir,
+            )
+            .unwrap();
+            assert!(db.put(WriteOptions::default_instance(), b"k", b"v").is_ok());
+            assert!(db.flush(&FlushOptions::default().wait(true)).is_ok());
+            assert!(db.get(ReadOptions::default_instance(), b"k").is_ok());
+        }
+    }
 }
 
-// We add a new format of filter block called full filter block
-// This new interface gives you more space of customization
-//
-// For the full filter block, you can plug in your version by implement
-// the FilterBitsBuilder and FilterBitsReader
-//
-// There are two sets of interface in FilterPolicy
-//
-// Set 1: CreateFilter, KeyMayMatch: used for blockbased filter
-//
-// Set 2: GetFilterBitsBuilder, GetFilterBitsReader, they are used for
-// full filter.
-//
-// Set 1 MUST be implemented correctly, Set 2 is optional
-//
-// RocksDB would first try using functions in Set 2. if they return nullptr,
-// it would use Set 1 instead.
-//
-// You can choose filter type in NewBloomFilterPolicy
-// pub trait FilterPolicy {
-// Return the name of this policy.  Note that if the filter encoding
-// changes in an incompatible way, the name returned by this method
-// must be changed.  Otherwise, old incompatible filters may be
-// passed to methods of this type.
-// fn name(&self) -> &str {
-// "RustFilterPolicy\0"
-// }
-//
-// keys[0,n-1] contains a list of keys (potentially with duplicates)
-// that are ordered according to the user supplied comparator.
-// Append a filter that summarizes keys[0,n-1] to *dst.
-//
-// Warning: do not change the initial contents of *dst.  Instead,
-// append the newly constructed filter to *dst.
-//
-// For Rust: must call dst.extend_from_slice() or dst.push()
-// fn create_filter(&self, keys: &[&[u8]], dst: &mut Vec<u8>);
-//
-// "filter" contains the data appended by a preceding call to
-// CreateFilter() on this class.  This method must return true if
-// the key was in the list of keys passed to CreateFilter().
-// This method may return true or false if the key was not on the
-// list, but it should aim to return false with a high probability.
-// fn key_may_match(&self, key: &[u8], filter: &[u8]) -> bool;
-// }
-//
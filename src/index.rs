@@ -0,0 +1,324 @@
+//! Secondary index maintenance over column families.
+//!
+//! An `IndexedColumnFamily` wraps a primary column family and keeps one or more
+//! secondary index column families in sync. Index entries are written in the
+//! same `WriteBatch` as the primary write, so they are atomic with it.
+//!
+//! Index entry keys are `index_value | primary_key | u32 BE len(index_value)`,
+//! with empty values. All entries of an index value start with it, but are not
+//! contiguous, entries of longer index values sharing the prefix are interleaved
+//! with them, e.g. `ab` of `abc`. `lookup()` scans the prefix and skips those.
+
+use std::fmt;
+use std::sync::Mutex;
+
+use crate::db::{ColumnFamilyHandle, DB};
+use crate::options::{ReadOptions, WriteOptions};
+use crate::write_batch::WriteBatch;
+use crate::{Error, Result};
+
+type Extractor = Box<dyn Fn(&[u8], &[u8]) -> Vec<Vec<u8>> + Send + Sync>;
+
+/// Number of index entries written per batch by `IndexedColumnFamily::backfill()`.
+const BACKFILL_BATCH_SIZE: usize = 1024;
+
+/// Encode an index entry key.
+pub fn encode_index_key(index_value: &[u8], primary_key: &[u8]) -> Vec<u8> {
+    let mut key = Vec::with_capacity(index_value.len() + primary_key.len() + 4);
+    key.extend_from_slice(index_value);
+    key.extend_from_slice(primary_key);
+    key.extend_from_slice(&(index_value.len() as u32).to_be_bytes());
+    key
+}
+
+/// Decode an index entry key into `(index_value, primary_key)`.
+pub fn decode_index_key(key: &[u8]) -> Option<(&[u8], &[u8])> {
+    if key.len() < 4 {
+        return None;
+    }
+    let (rest, len) = key.split_at(key.len() - 4);
+    let len = u32::from_be_bytes([len[0], len[1], len[2], len[3]]) as usize;
+    if len > rest.len() {
+        return None;
+    }
+    Some(rest.split_at(len))
+}
+
+struct SecondaryIndex<'a> {
+    name: String,
+    column_family: &'a ColumnFamilyHandle,
+    extractor: Extractor,
+}
+
+/// Result of `IndexedColumnFamily::check()`.
+#[derive(Debug, Default, PartialEq, Eq)]
+pub struct IndexCheckReport {
+    /// Number of index entries scanned.
+    pub num_entries: u64,
+    /// `(index_value, primary_key)` pairs derived from primary records but absent in the index.
+    pub missing: Vec<(Vec<u8>, Vec<u8>)>,
+    /// `(index_value, primary_key)` pairs in the index without a matching primary record.
+    pub dangling: Vec<(Vec<u8>, Vec<u8>)>,
+}
+
+impl IndexCheckReport {
+    pub fn is_consistent(&self) -> bool {
+        self.missing.is_empty() && self.dangling.is_empty()
+    }
+}
+
+/// A primary column family with its secondary indexes.
+///
+/// Writes must go through `put()` / `delete()` to keep indexes up to date. They
+/// are serialized within one `IndexedColumnFamily`, other writers of the primary
+/// column family make indexes inconsistent, use `check()` and `backfill()` to repair.
+pub struct IndexedColumnFamily<'a> {
+    db: &'a DB,
+    primary: &'a ColumnFamilyHandle,
+    indexes: Vec<SecondaryIndex<'a>>,
+    write_lock: Mutex<()>,
+}
+
+impl<'a> fmt::Debug for IndexedColumnFamily<'a> {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.debug_struct("IndexedColumnFamily")
+            .field("primary", &self.primary.name())
+            .field("indexes", &self.indexes.iter().map(|idx| &idx.name).collect::<Vec<_>>())
+            .finish()
+    }
+}
+
+impl<'a> IndexedColumnFamily<'a> {
+    pub fn new(db: &'a DB, primary: &'a ColumnFamilyHandle) -> IndexedColumnFamily<'a> {
+        IndexedColumnFamily {
+            db,
+            primary,
+            indexes: vec![],
+            write_lock: Mutex::new(()),
+        }
+    }
+
+    /// Add an index stored in `column_family`. `extractor` maps a primary record
+    /// `(key, value)` to its index values, a record can have zero or more index values.
+    pub fn add_index<F>(mut self, name: &str, column_family: &'a ColumnFamilyHandle, extractor: F) -> Self
+    where
+        F: Fn(&[u8], &[u8]) -> Vec<Vec<u8>> + Send + Sync + 'static,
+    {
+        self.indexes.push(SecondaryIndex {
+            name: name.to_owned(),
+            column_family,
+            extractor: Box::new(extractor),
+        });
+        self
+    }
+
+    fn index(&self, name: &str) -> Result<&SecondaryIndex<'a>> {
+        self.indexes
+            .iter()
+            .find(|idx| idx.name == name)
+            .ok_or_else(|| Error::invalid_argument(&format!("no such index: {}", name)))
+    }
+
+    fn get_primary(&self, key: &[u8]) -> Result<Option<Vec<u8>>> {
+        self.db
+            .get_cf_opt(ReadOptions::default_instance(), self.primary, key)
+            .map(|val| val.map(|v| v.to_vec()))
+    }
+
+    /// Add primary and index writes of `key => value` to `batch`, `old_value` being
+    /// the current value of `key`.
+    fn batch_put(&self, batch: &mut WriteBatch, key: &[u8], old_value: Option<&[u8]>, value: Option<&[u8]>) {
+        for idx in &self.indexes {
+            let old_entries = old_value.map(|v| (idx.extractor)(key, v)).unwrap_or_default();
+            let new_entries = value.map(|v| (idx.extractor)(key, v)).unwrap_or_default();
+            for entry in old_entries.iter().filter(|e| !new_entries.contains(e)) {
+                batch.delete_cf(idx.column_family, &encode_index_key(entry, key));
+            }
+            for entry in new_entries.iter().filter(|e| !old_entries.contains(e)) {
+                batch.put_cf(idx.column_family, &encode_index_key(entry, key), b"");
+            }
+        }
+        match value {
+            Some(value) => batch.put_cf(self.primary, key, value),
+            None => batch.delete_cf(self.primary, key),
+        };
+    }
+
+    /// Put a primary record, updating all indexes atomically.
+    pub fn put(&self, options: &WriteOptions, key: &[u8], value: &[u8]) -> Result<()> {
+        let _guard = self.write_lock.lock().unwrap();
+        let old_value = self.get_primary(key)?;
+        let mut batch = WriteBatch::new();
+        self.batch_put(&mut batch, key, old_value.as_deref(), Some(value));
+        self.db.write(options, &batch)
+    }
+
+    /// Delete a primary record and its index entries atomically.
+    pub fn delete(&self, options: &WriteOptions, key: &[u8]) -> Result<()> {
+        let _guard = self.write_lock.lock().unwrap();
+        if let Some(old_value) = self.get_primary(key)? {
+            let mut batch = WriteBatch::new();
+            self.batch_put(&mut batch, key, Some(&old_value), None);
+            self.db.write(options, &batch)?;
+        }
+        Ok(())
+    }
+
+    /// Primary keys with `index_value` in index `name`, in index order.
+    ///
+    /// Fails with `InvalidArgument` if there is no such index.
+    pub fn lookup(&self, options: &ReadOptions, name: &str, index_value: &[u8]) -> Result<Vec<Vec<u8>>> {
+        let idx = self.index(name)?;
+        let mut it = self.db.new_iterator_cf(options, idx.column_family);
+        let mut keys = vec![];
+        it.seek(index_value);
+        while it.is_valid() && it.key().starts_with(index_value) {
+            if let Some((value, primary_key)) = decode_index_key(it.key()) {
+                if value == index_value {
+                    keys.push(primary_key.to_vec());
+                }
+            }
+            it.next();
+        }
+        it.status()?;
+        Ok(keys)
+    }
+
+    /// (Re)build index `name` from all primary records, returns number of entries written.
+    ///
+    /// Existing entries are kept, run `check()` to find dangling ones.
+    pub fn backfill(&self, options: &WriteOptions, name: &str) -> Result<u64> {
+        let idx = self.index(name)?;
+        let mut it = self
+            .db
+            .new_iterator_cf(&ReadOptions::default().fill_cache(false), self.primary);
+        let mut batch = WriteBatch::new();
+        let mut num_entries = 0;
+        it.seek_to_first();
+        while it.is_valid() {
+            for entry in (idx.extractor)(it.key(), it.value()) {
+                batch.put_cf(idx.column_family, &encode_index_key(&entry, it.key()), b"");
+                num_entries += 1;
+            }
+            if batch.count() >= BACKFILL_BATCH_SIZE {
+                self.db.write(options, &batch)?;
+                batch.clear();
+            }
+            it.next();
+        }
+        it.status()?;
+        if batch.count() > 0 {
+            self.db.write(options, &batch)?;
+        }
+        Ok(num_entries)
+    }
+
+    /// Verify index `name` against the primary column family.
+    ///
+    /// Use a snapshot in `options` for a consistent view under concurrent writes.
+    pub fn check(&self, options: &ReadOptions, name: &str) -> Result<IndexCheckReport> {
+        let idx = self.index(name)?;
+        let mut report = IndexCheckReport::default();
+
+        let mut it = self.db.new_iterator_cf(options, idx.column_family);
+        it.seek_to_first();
+        while it.is_valid() {
+            report.num_entries += 1;
+            let consistent = match decode_index_key(it.key()) {
+                Some((value, primary_key)) => match self.db.get_cf(options, self.primary, primary_key) {
+                    Ok(record) => (idx.extractor)(primary_key, &record).iter().any(|v| v == value),
+                    Err(e) if e.is_not_found() => false,
+                    Err(e) => return Err(e),
+                },
+                None => false,
+            };
+            if !consistent {
+                let (value, primary_key) = decode_index_key(it.key()).unwrap_or((it.key(), b""));
+                report.dangling.push((value.to_vec(), primary_key.to_vec()));
+            }
+            it.next();
+        }
+        it.status()?;
+
+        let mut it = self.db.new_iterator_cf(options, self.primary);
+        it.seek_to_first();
+        while it.is_valid() {
+            for value in (idx.extractor)(it.key(), it.value()) {
+                match self
+                    .db
+                    .get_cf(options, idx.column_family, &encode_index_key(&value, it.key()))
+                {
+                    Ok(_) => (),
+                    Err(e) if e.is_not_found() => report.missing.push((value, it.key().to_vec())),
+                    Err(e) => return Err(e),
+                }
+            }
+            it.next();
+        }
+        it.status()?;
+        Ok(report)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::rocksdb::*;
+
+    // value is "city,name"
+    fn city(_key: &[u8], value: &[u8]) -> Vec<Vec<u8>> {
+        value.split(|&b| b == b',').take(1).map(|s| s.to_vec()).collect()
+    }
+
+    #[test]
+    fn index_key_encoding() {
+        let key = encode_index_key(b"ab", b"cde");
+        assert_eq!(decode_index_key(&key), Some((&b"ab"[..], &b"cde"[..])));
+        assert_eq!(decode_index_key(&encode_index_key(b"", b"")), Some((&b""[..], &b""[..])));
+        assert_eq!(decode_index_key(b"ab"), None);
+    }
+
+    #[test]
+    fn secondary_index() {
+        let tmp_dir = ::tempdir::TempDir::new_in(".", "rocks").unwrap();
+        let db = DB::open(
+            Options::default().map_db_options(|db| db.create_if_missing(true)),
+            &tmp_dir,
+        )
+        .unwrap();
+        let users = db.default_column_family();
+        let by_city = db.create_column_family(&ColumnFamilyOptions::default(), "by_city").unwrap();
+
+        let wopts = WriteOptions::default_instance();
+        let ropts = ReadOptions::default_instance();
+
+        // written before the index exists
+        assert!(db.put(wopts, b"u0", b"paris,zoe").is_ok());
+
+        let table = IndexedColumnFamily::new(&db, &users).add_index("city", &by_city, city);
+        assert!(table.put(wopts, b"u1", b"berlin,alice").is_ok());
+        assert!(table.put(wopts, b"u2", b"berlin,bob").is_ok());
+        assert!(table.put(wopts, b"u3", b"bern,carol").is_ok());
+        assert_eq!(table.lookup(ropts, "city", b"berlin").unwrap(), vec![b"u1".to_vec(), b"u2".to_vec()]);
+        assert_eq!(table.lookup(ropts, "city", b"bern").unwrap(), vec![b"u3".to_vec()]);
+
+        assert!(table.put(wopts, b"u2", b"bern,bob").is_ok());
+        assert!(table.delete(wopts, b"u3").is_ok());
+        assert_eq!(table.lookup(ropts, "city", b"berlin").unwrap(), vec![b"u1".to_vec()]);
+        assert_eq!(table.lookup(ropts, "city", b"bern").unwrap(), vec![b"u2".to_vec()]);
+        assert!(table.lookup(ropts, "age", b"42").is_err());
+
+        let report = table.check(ropts, "city").unwrap();
+        assert_eq!(report.missing, vec![(b"paris".to_vec(), b"u0".to_vec())]);
+        assert!(report.dangling.is_empty());
+
+        assert_eq!(table.backfill(wopts, "city").unwrap(), 3);
+        assert!(table.check(ropts, "city").unwrap().is_consistent());
+        assert_eq!(table.lookup(ropts, "city", b"paris").unwrap(), vec![b"u0".to_vec()]);
+
+        // bypass the index
+        assert!(db.delete(wopts, b"u1").is_ok());
+        let report = table.check(ropts, "city").unwrap();
+        assert_eq!(report.dangling, vec![(b"berlin".to_vec(), b"u1".to_vec())]);
+    }
+}
@@ -4,14 +4,35 @@ use std::fmt;
 use std::iter;
 use std::marker::PhantomData;
 use std::mem;
+use std::ops::{Bound, RangeBounds};
 use std::os::raw::c_void;
 use std::slice;
+use std::thread;
+use std::time::{Duration, Instant};
 
 use rocks_sys as ll;
 
+use crate::env::Priority;
+use crate::perf_context::PerfContext;
+use crate::rate_limiter::RateLimiter;
 use crate::to_raw::FromRaw;
 use crate::{Error, Result};
 
+/// Internal entries skipped over by an iterator, see `Iterator::skip_stats()`.
+///
+/// Only collected after `Iterator::track_skip_stats()`.
+///
+/// High counts relative to the number of keys returned indicate a scan
+/// wading through tombstones or overwritten versions, and that compacting
+/// the scanned range may help.
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct IteratorSkipStats {
+    /// Internal keys skipped, see `PerfContext::internal_key_skipped_count`.
+    pub internal_key_skipped_count: u64,
+    /// Tombstones skipped, see `PerfContext::internal_delete_skipped_count`.
+    pub internal_delete_skipped_count: u64,
+}
+
 /// An iterator yields a sequence of key/value pairs from a source.
 ///
 /// Multiple threads can invoke const methods on an Iterator without
@@ -21,6 +42,8 @@ use crate::{Error, Result};
 pub struct Iterator<'a> {
     raw: *mut ll::rocks_iterator_t,
     initial: bool,
+    track_skips: bool,
+    skip_stats: IteratorSkipStats,
     _marker: PhantomData<&'a ()>,
 }
 
@@ -48,11 +71,7 @@ impl<'a> Drop for Iterator<'a> {
 
 impl<'a> FromRaw<ll::rocks_iterator_t> for Iterator<'a> {
     unsafe fn from_ll(raw: *mut ll::rocks_iterator_t) -> Self {
-        let mut it = Iterator {
-            raw: raw,
-            initial: true,
-            _marker: PhantomData,
-        };
+        let mut it = Iterator::from_ll_positioned(raw);
         if !it.is_valid() {
             it.seek_to_first();
         }
@@ -69,6 +88,17 @@ impl<'a> FromRaw<ll::rocks_iterator_t> for Iterator<'a> {
 }
 
 impl<'a> Iterator<'a> {
+    /// Wraps an iterator already positioned by the shim, an invalid one is left as is.
+    pub(crate) unsafe fn from_ll_positioned(raw: *mut ll::rocks_iterator_t) -> Self {
+        Iterator {
+            raw,
+            initial: true,
+            track_skips: false,
+            skip_stats: IteratorSkipStats::default(),
+            _marker: PhantomData,
+        }
+    }
+
     /// An iterator is either positioned at a key/value pair, or
     /// not valid.  This method returns true iff the iterator is valid.
     pub fn is_valid(&self) -> bool {
@@ -78,35 +108,27 @@ impl<'a> Iterator<'a> {
     /// Position at the first key in the source.  The iterator `is_valid()`
     /// after this call iff the source is not empty.
     pub fn seek_to_first(&mut self) {
-        unsafe {
-            ll::rocks_iter_seek_to_first(self.raw);
-        }
+        self.track_skips(|raw| unsafe { ll::rocks_iter_seek_to_first(raw) });
     }
 
     /// Position at the last key in the source.  The iterator
     /// `is_valid()` after this call iff the source is not empty.
     pub fn seek_to_last(&mut self) {
-        unsafe {
-            ll::rocks_iter_seek_to_last(self.raw);
-        }
+        self.track_skips(|raw| unsafe { ll::rocks_iter_seek_to_last(raw) });
     }
 
     /// Position at the first key in the source that at or past target
     /// The iterator `is_valid()` after this call iff the source contains
     /// an entry that comes at or past target.
     pub fn seek(&mut self, target: &[u8]) {
-        unsafe {
-            ll::rocks_iter_seek(self.raw, target.as_ptr() as _, target.len());
-        }
+        self.track_skips(|raw| unsafe { ll::rocks_iter_seek(raw, target.as_ptr() as _, target.len()) });
     }
 
     /// Position at the last key in the source that at or before target
     /// The iterator `is_valid()` after this call iff the source contains
     /// an entry that comes at or before target.
     pub fn seek_for_prev(&mut self, target: &[u8]) {
-        unsafe {
-            ll::rocks_iter_seek_for_prev(self.raw, target.as_ptr() as _, target.len());
-        }
+        self.track_skips(|raw| unsafe { ll::rocks_iter_seek_for_prev(raw, target.as_ptr() as _, target.len()) });
     }
 
     /// Moves to the next entry in the source.  After this call, `is_valid()` is
@@ -114,9 +136,7 @@ impl<'a> Iterator<'a> {
     ///
     /// REQUIRES: `is_valid()`
     pub fn next(&mut self) {
-        unsafe {
-            ll::rocks_iter_next(self.raw);
-        }
+        self.track_skips(|raw| unsafe { ll::rocks_iter_next(raw) });
     }
 
     /// Moves to the previous entry in the source.  After this call, `is_valid()` is
@@ -124,9 +144,43 @@ impl<'a> Iterator<'a> {
     ///
     /// REQUIRES: `is_valid()`
     pub fn prev(&mut self) {
-        unsafe {
-            ll::rocks_iter_prev(self.raw);
+        self.track_skips(|raw| unsafe { ll::rocks_iter_prev(raw) });
+    }
+
+    /// Collect `skip_stats()` from now on, for every following seek and move.
+    ///
+    /// Costs two reads of the thread local `PerfContext` per call, so is off by default.
+    pub fn track_skip_stats(mut self) -> Self {
+        self.track_skips = true;
+        self
+    }
+
+    /// Internal entries skipped by this iterator since `track_skip_stats()`, or since
+    /// the last `reset_skip_stats()`.
+    ///
+    /// Collected from the thread local `PerfContext`, so stays zero unless the perf
+    /// level of the threads moving the iterator is at least `PerfLevel::EnableCount`.
+    pub fn skip_stats(&self) -> IteratorSkipStats {
+        self.skip_stats
+    }
+
+    /// Reset the counters of `skip_stats()`, e.g. before starting a new scan.
+    pub fn reset_skip_stats(&mut self) {
+        self.skip_stats = IteratorSkipStats::default();
+    }
+
+    fn track_skips<F: FnOnce(*mut ll::rocks_iterator_t)>(&mut self, f: F) {
+        if !self.track_skips {
+            return f(self.raw);
         }
+        let (keys, deletes) = {
+            let ctx = PerfContext::current();
+            (ctx.internal_key_skipped_count, ctx.internal_delete_skipped_count)
+        };
+        f(self.raw);
+        let ctx = PerfContext::current();
+        self.skip_stats.internal_key_skipped_count += ctx.internal_key_skipped_count.saturating_sub(keys);
+        self.skip_stats.internal_delete_skipped_count += ctx.internal_delete_skipped_count.saturating_sub(deletes);
     }
 
     /// Return the key for the current entry.  The underlying storage for
@@ -194,6 +248,34 @@ impl<'a> Iterator<'a> {
         }
     }
 
+    /// Property `"rocksdb.iterator.is-key-pinned"`, see `get_property()`.
+    pub fn is_key_pinned(&self) -> bool {
+        self.get_property("rocksdb.iterator.is-key-pinned").map(|s| s == "1").unwrap_or(false)
+    }
+
+    /// Property `"rocksdb.iterator.super-version-number"`, see `get_property()`.
+    pub fn super_version_number(&self) -> Result<u64> {
+        self.get_property("rocksdb.iterator.super-version-number").map(|s| s.parse().unwrap_or_default())
+    }
+
+    /// Update the iterator to the latest state of the DB, i.e. a new super version,
+    /// without re-creating it, releasing the memtables and SST files it pins.
+    ///
+    /// The iterator is invalid afterwards, it must be re-seeked. Long-lived scanners
+    /// should refresh periodically, so that obsolete files can be deleted.
+    ///
+    /// Returns `NotSupported` for iterators with a snapshot in `ReadOptions`, or not
+    /// created from a DB.
+    pub fn refresh(&mut self) -> Result<()> {
+        let mut status = std::ptr::null_mut();
+        unsafe {
+            ll::rocks_iter_refresh(self.raw, &mut status);
+        }
+        // like a new iterator, the first entry is yielded after a seek
+        self.initial = true;
+        Error::from_ll(status)
+    }
+
     /// Consume and make a reversed rustic style iterator.
     pub fn rev(mut self) -> IntoRevIter<'a> {
         self.seek_to_last();
@@ -377,6 +459,289 @@ impl<'a> iter::Iterator for RevValues<'a> {
     }
 }
 
+/// Forward iterator over a key range, throttled by the bytes of keys and values
+/// it yields, see `ColumnFamily::scan_rate_limited()`.
+///
+/// Throttles by sleeping at a fixed rate, or by requesting the bytes from a shared
+/// `RateLimiter` set with `rate_limiter()`, so that scans and background IO share one
+/// budget. Block reads themselves are not throttled.
+pub struct RateLimitedIterator<'a> {
+    inner: Iterator<'a>,
+    start: Bound<Vec<u8>>,
+    end: Bound<Vec<u8>>,
+    positioned: bool,
+    bytes_per_sec: u64,
+    rate_limiter: Option<RateLimiter>,
+    started: Instant,
+    bytes: u64,
+}
+
+impl<'a> fmt::Debug for RateLimitedIterator<'a> {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.debug_struct("RateLimitedIterator")
+            .field("inner", &self.inner)
+            .field("bytes_per_sec", &self.bytes_per_sec)
+            .field("bytes", &self.bytes)
+            .finish()
+    }
+}
+
+impl<'a> RateLimitedIterator<'a> {
+    pub(crate) fn new<'k, R: RangeBounds<&'k [u8]>>(
+        inner: Iterator<'a>,
+        range: R,
+        bytes_per_sec: u64,
+    ) -> RateLimitedIterator<'a> {
+        let owned = |b: Bound<&&[u8]>| match b {
+            Bound::Included(k) => Bound::Included(k.to_vec()),
+            Bound::Excluded(k) => Bound::Excluded(k.to_vec()),
+            Bound::Unbounded => Bound::Unbounded,
+        };
+        RateLimitedIterator {
+            inner,
+            start: owned(range.start_bound()),
+            end: owned(range.end_bound()),
+            positioned: false,
+            bytes_per_sec,
+            rate_limiter: None,
+            started: Instant::now(),
+            bytes: 0,
+        }
+    }
+
+    /// Request yielded bytes from `limiter` at low IO priority, instead of sleeping
+    /// at `bytes_per_sec`.
+    pub fn rate_limiter(mut self, limiter: RateLimiter) -> Self {
+        self.rate_limiter = Some(limiter);
+        self
+    }
+
+    /// Bytes of keys and values yielded so far.
+    pub fn bytes_read(&self) -> u64 {
+        self.bytes
+    }
+
+    /// Error of the underlying iterator, check after the iteration ends.
+    pub fn status(&self) -> Result<()> {
+        self.inner.status()
+    }
+
+    fn position(&mut self) {
+        match self.start {
+            Bound::Included(ref k) => self.inner.seek(k),
+            Bound::Excluded(ref k) => {
+                self.inner.seek(k);
+                if self.inner.is_valid() && self.inner.key() == &k[..] {
+                    self.inner.next();
+                }
+            },
+            Bound::Unbounded => self.inner.seek_to_first(),
+        }
+    }
+
+    fn throttle(&mut self, n: usize) {
+        self.bytes += n as u64;
+        if let Some(ref limiter) = self.rate_limiter {
+            limiter.request(n, Priority::Low);
+        } else if self.bytes_per_sec > 0 {
+            let due = Duration::from_secs_f64(self.bytes as f64 / self.bytes_per_sec as f64);
+            let elapsed = self.started.elapsed();
+            if due > elapsed {
+                thread::sleep(due - elapsed);
+            }
+        }
+    }
+}
+
+impl<'a> iter::Iterator for RateLimitedIterator<'a> {
+    type Item = (&'a [u8], &'a [u8]);
+
+    fn next(&mut self) -> Option<Self::Item> {
+        if self.positioned {
+            if self.inner.is_valid() {
+                self.inner.next();
+            }
+        } else {
+            self.positioned = true;
+            self.position();
+        }
+        if !self.inner.is_valid() {
+            return None;
+        }
+        let (key, value) = (self.inner.key(), self.inner.value());
+        let in_range = match self.end {
+            Bound::Included(ref end) => key <= &end[..],
+            Bound::Excluded(ref end) => key < &end[..],
+            Bound::Unbounded => true,
+        };
+        if !in_range {
+            return None;
+        }
+        self.throttle(key.len() + value.len());
+        Some((key, value))
+    }
+}
+
+/// Iterators of several column families merged in key order, see `DB::new_merged_iterator()`.
+///
+/// Every entry is tagged with the index of the iterator it comes from, i.e. the index of
+/// its column family in the slice passed to `DB::new_merged_iterator()`. Equal keys of
+/// different column families are all yielded, in index order when iterating forward.
+///
+/// Keys are compared bytewise, the column families must use `BytewiseComparator`.
+pub struct MergedIterator<'a> {
+    children: Vec<Iterator<'a>>,
+    current: Option<usize>,
+    forward: bool,
+    initial: bool,
+}
+
+impl<'a> fmt::Debug for MergedIterator<'a> {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.debug_struct("MergedIterator")
+            .field("children", &self.children.len())
+            .field("current", &self.current)
+            .finish()
+    }
+}
+
+impl<'a> MergedIterator<'a> {
+    /// Merge `children`, which are positioned at their first keys.
+    pub fn new(children: Vec<Iterator<'a>>) -> MergedIterator<'a> {
+        let mut it = MergedIterator {
+            children,
+            current: None,
+            forward: true,
+            initial: true,
+        };
+        it.find_smallest();
+        it
+    }
+
+    pub fn is_valid(&self) -> bool {
+        self.current.is_some()
+    }
+
+    pub fn seek_to_first(&mut self) {
+        self.children.iter_mut().for_each(|it| it.seek_to_first());
+        self.find_smallest();
+    }
+
+    pub fn seek_to_last(&mut self) {
+        self.children.iter_mut().for_each(|it| it.seek_to_last());
+        self.find_largest();
+    }
+
+    pub fn seek(&mut self, target: &[u8]) {
+        self.children.iter_mut().for_each(|it| it.seek(target));
+        self.find_smallest();
+    }
+
+    pub fn seek_for_prev(&mut self, target: &[u8]) {
+        self.children.iter_mut().for_each(|it| it.seek_for_prev(target));
+        self.find_largest();
+    }
+
+    /// REQUIRES: `is_valid()`
+    pub fn next(&mut self) {
+        let cur = self.current.expect("iterator is not valid");
+        if !self.forward {
+            // other children are before the current key, move them after it
+            let key = self.key().to_vec();
+            for (i, it) in self.children.iter_mut().enumerate() {
+                if i != cur {
+                    it.seek(&key);
+                    if i < cur && it.is_valid() && it.key() == &key[..] {
+                        it.next();
+                    }
+                }
+            }
+        }
+        self.children[cur].next();
+        self.find_smallest();
+    }
+
+    /// REQUIRES: `is_valid()`
+    pub fn prev(&mut self) {
+        let cur = self.current.expect("iterator is not valid");
+        if self.forward {
+            // other children are after the current key, move them before it
+            let key = self.key().to_vec();
+            for (i, it) in self.children.iter_mut().enumerate() {
+                if i != cur {
+                    it.seek_for_prev(&key);
+                    if i > cur && it.is_valid() && it.key() == &key[..] {
+                        it.prev();
+                    }
+                }
+            }
+        }
+        self.children[cur].prev();
+        self.find_largest();
+    }
+
+    /// Index of the column family of the current entry.
+    ///
+    /// REQUIRES: `is_valid()`
+    pub fn index(&self) -> usize {
+        self.current.expect("iterator is not valid")
+    }
+
+    /// REQUIRES: `is_valid()`
+    pub fn key(&self) -> &'a [u8] {
+        self.children[self.index()].key()
+    }
+
+    /// REQUIRES: `is_valid()`
+    pub fn value(&self) -> &'a [u8] {
+        self.children[self.index()].value()
+    }
+
+    /// The first error of the merged iterators.
+    pub fn status(&self) -> Result<()> {
+        self.children.iter().try_for_each(|it| it.status())
+    }
+
+    /// The merged iterators.
+    pub fn into_inner(self) -> Vec<Iterator<'a>> {
+        self.children
+    }
+
+    fn find_smallest(&mut self) {
+        self.forward = true;
+        self.current = None;
+        for (i, it) in self.children.iter().enumerate() {
+            if it.is_valid() && self.current.map_or(true, |cur| it.key() < self.children[cur].key()) {
+                self.current = Some(i);
+            }
+        }
+    }
+
+    fn find_largest(&mut self) {
+        self.forward = false;
+        self.current = None;
+        for (i, it) in self.children.iter().enumerate() {
+            if it.is_valid() && self.current.map_or(true, |cur| it.key() >= self.children[cur].key()) {
+                self.current = Some(i);
+            }
+        }
+    }
+}
+
+impl<'a> iter::Iterator for MergedIterator<'a> {
+    /// (index of the column family, key, value)
+    type Item = (usize, &'a [u8], &'a [u8]);
+
+    fn next(&mut self) -> Option<Self::Item> {
+        if self.initial {
+            self.initial = false;
+        } else if self.is_valid() {
+            self.next();
+        }
+        self.current.map(|i| (i, self.children[i].key(), self.children[i].value()))
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use super::super::rocksdb::*;
@@ -483,4 +848,150 @@ mod tests {
             .collect();
         assert_eq!(keys, vec!["k9", "k8", "k6", "k5", "k4", "k3", "k2", "k1"]);
     }
+    #[test]
+    fn iterator_skip_stats() {
+        use crate::perf_level::{set_perf_level, PerfLevel};
+        use tempdir::TempDir;
+
+        let tmp_dir = TempDir::new_in(".", "rocks").unwrap();
+        let opt = Options::default().map_db_options(|db| db.create_if_missing(true));
+        let db = DB::open(opt, tmp_dir.path()).unwrap();
+        for i in 0..100 {
+            let key = format!("k{:03}", i);
+            assert!(db.put(WriteOptions::default_instance(), key.as_bytes(), b"v").is_ok());
+        }
+        assert!(db.flush(&FlushOptions::default().wait(true)).is_ok());
+        for i in 0..90 {
+            let key = format!("k{:03}", i);
+            assert!(db.delete(WriteOptions::default_instance(), key.as_bytes()).is_ok());
+        }
+
+        set_perf_level(PerfLevel::EnableCount);
+        let mut it = db.new_iterator(ReadOptions::default_instance());
+        assert_eq!(it.skip_stats(), super::IteratorSkipStats::default());
+        it = it.track_skip_stats();
+        it.seek_to_first();
+        assert!(it.skip_stats().internal_delete_skipped_count >= 90);
+        assert_eq!(it.key(), b"k090");
+        it.reset_skip_stats();
+        it.next();
+        assert_eq!(it.key(), b"k091");
+        assert_eq!(it.skip_stats().internal_delete_skipped_count, 0);
+        set_perf_level(PerfLevel::Disable);
+    }
+
+    #[test]
+    fn scan_rate_limited() {
+        use crate::rate_limiter::RateLimiter;
+        use std::ops::Bound;
+        use std::time::{Duration, Instant};
+
+        let tmp_dir = ::tempdir::TempDir::new_in(".", "rocks").unwrap();
+        let db = DB::open(
+            Options::default().map_db_options(|db| db.create_if_missing(true)),
+            &tmp_dir,
+        )
+        .unwrap();
+        for i in 0..100 {
+            let key = format!("k{:03}", i);
+            assert!(db.put(WriteOptions::default_instance(), key.as_bytes(), &[b'v'; 96]).is_ok());
+        }
+
+        // 10 entries of 100 bytes at 5000 bytes/s
+        let start = Instant::now();
+        let mut it = db.scan_rate_limited(&b"k010"[..]..&b"k020"[..], 5000);
+        let keys = it.by_ref().map(|(k, _)| k.to_vec()).collect::<Vec<_>>();
+        assert!(it.status().is_ok());
+        assert_eq!(keys.len(), 10);
+        assert_eq!(keys[0], b"k010");
+        assert_eq!(it.bytes_read(), 1000);
+        assert!(start.elapsed() >= Duration::from_millis(150));
+
+        let limiter = RateLimiter::new(1 << 20, 100_000, 10);
+        let it = db
+            .scan_rate_limited((Bound::Excluded(&b"k090"[..]), Bound::Unbounded), 0)
+            .rate_limiter(limiter.clone());
+        assert_eq!(it.count(), 9);
+        assert_eq!(db.scan_rate_limited(&b"k050"[..]..=&b"k052"[..], 0).count(), 3);
+    }
+
+    #[test]
+    fn merged_iterator() {
+        let tmp_dir = ::tempdir::TempDir::new_in(".", "rocks").unwrap();
+        let db = DB::open(Options::default().map_db_options(|db| db.create_if_missing(true)), &tmp_dir).unwrap();
+        let cfs = vec![
+            db.default_column_family(),
+            db.create_column_family(&ColumnFamilyOptions::default(), "odd").unwrap(),
+            db.create_column_family(&ColumnFamilyOptions::default(), "empty").unwrap(),
+        ];
+        for i in 0..6 {
+            let key = format!("k{}", i);
+            assert!(cfs[i % 2].put(WriteOptions::default_instance(), key.as_bytes(), b"v").is_ok());
+        }
+        // the same key in both
+        assert!(cfs[1].put(WriteOptions::default_instance(), b"k2", b"dup").is_ok());
+
+        let it = db.new_merged_iterator(ReadOptions::default_instance(), &cfs).unwrap();
+        let entries = it
+            .map(|(i, k, _)| (i, String::from_utf8_lossy(k).into_owned()))
+            .collect::<Vec<_>>();
+        let expected = [(0, "k0"), (1, "k1"), (0, "k2"), (1, "k2"), (1, "k3"), (0, "k4"), (1, "k5")]
+            .iter()
+            .map(|&(i, k)| (i, k.to_owned()))
+            .collect::<Vec<_>>();
+        assert_eq!(entries, expected);
+
+        let mut it = db.new_merged_iterator(ReadOptions::default_instance(), &cfs).unwrap();
+        it.seek_to_last();
+        let mut reversed = vec![];
+        while it.is_valid() {
+            reversed.push((it.index(), String::from_utf8_lossy(it.key()).into_owned()));
+            it.prev();
+        }
+        assert_eq!(reversed, expected.iter().rev().cloned().collect::<Vec<_>>());
+
+        // switching direction
+        it.seek(b"k2");
+        assert_eq!((it.index(), it.key()), (0, &b"k2"[..]));
+        it.next();
+        assert_eq!((it.index(), it.value()), (1, &b"dup"[..]));
+        it.prev();
+        assert_eq!((it.index(), it.key()), (0, &b"k2"[..]));
+        it.prev();
+        assert_eq!((it.index(), it.key()), (1, &b"k1"[..]));
+        it.next();
+        it.next();
+        assert_eq!((it.index(), it.key()), (1, &b"k2"[..]));
+
+        it.seek_for_prev(b"k35");
+        assert_eq!((it.index(), it.key()), (1, &b"k3"[..]));
+        assert!(it.status().is_ok());
+    }
+    #[test]
+    fn iterator_refresh() {
+        let tmp_dir = ::tempdir::TempDir::new_in(".", "rocks").unwrap();
+        let db = DB::open(Options::default().map_db_options(|db| db.create_if_missing(true)), &tmp_dir).unwrap();
+        assert!(db.put(WriteOptions::default_instance(), b"k1", b"v1").is_ok());
+
+        let mut it = db.new_iterator(&ReadOptions::default().pin_data(true));
+        assert!(it.status().is_ok());
+        assert!(it.is_key_pinned());
+        let version = it.super_version_number().unwrap();
+
+        assert!(db.put(WriteOptions::default_instance(), b"k2", b"v2").is_ok());
+        assert!(db.flush(&FlushOptions::default()).is_ok());
+        // still on the old super version
+        it.seek_to_first();
+        assert_eq!(it.by_ref().count(), 1);
+
+        assert!(it.refresh().is_ok());
+        assert!(it.super_version_number().unwrap() > version);
+        it.seek_to_first();
+        assert_eq!(it.keys().collect::<Vec<_>>(), vec![&b"k1"[..], &b"k2"[..]]);
+
+        // not supported with an explicit snapshot
+        let snapshot = db.get_snapshot();
+        let mut it = db.new_iterator(&ReadOptions::default().snapshot(snapshot.as_ref()));
+        assert!(it.refresh().is_err());
+    }
 }
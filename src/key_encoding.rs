@@ -0,0 +1,261 @@
+//! Order-preserving key encodings.
+//!
+//! Encoded keys compare bytewise in the same order as the values they encode,
+//! so they work with the default comparator, and with prefix extractors and
+//! range deletions, unlike a custom comparator.
+//!
+//! - unsigned integers are big endian
+//! - signed integers are big endian with the sign bit flipped
+//! - floats have the sign bit flipped when positive, all bits flipped when
+//!   negative, so that `-0.0 < 0.0` and NaNs sort last (or first, for negative NaNs)
+//! - byte strings are escaped, `0x00` becomes `0x00 0xff`, and terminated by
+//!   `0x00 0x01`, so that a shorter string sorts before its extensions
+//!
+//! Tuples are encoded by concatenating their components with `KeyEncoder`, and
+//! read back in the same order with `KeyDecoder`.
+//!
+//! ```
+//! use rocks::key_encoding::{KeyDecoder, KeyEncoder};
+//!
+//! let key = KeyEncoder::new().bytes(b"user").i64(-42).f64(1.5).finish();
+//! let mut decoder = KeyDecoder::new(&key);
+//! assert_eq!(decoder.bytes().unwrap(), b"user");
+//! assert_eq!(decoder.i64(), Some(-42));
+//! assert_eq!(decoder.f64(), Some(1.5));
+//! assert!(decoder.is_empty());
+//! ```
+
+use std::convert::TryInto;
+
+const SIGN_BIT: u64 = 1 << 63;
+const ESCAPE: u8 = 0x00;
+const ESCAPED_NUL: u8 = 0xff;
+const TERMINATOR: u8 = 0x01;
+
+/// Encode a `u64` as 8 bytes.
+pub fn encode_u64(val: u64) -> [u8; 8] {
+    val.to_be_bytes()
+}
+
+/// Decode a `u64` from 8 bytes.
+pub fn decode_u64(buf: &[u8]) -> Option<u64> {
+    buf.try_into().ok().map(u64::from_be_bytes)
+}
+
+/// Encode an `i64` as 8 bytes.
+pub fn encode_i64(val: i64) -> [u8; 8] {
+    encode_u64(val as u64 ^ SIGN_BIT)
+}
+
+/// Decode an `i64` from 8 bytes.
+pub fn decode_i64(buf: &[u8]) -> Option<i64> {
+    decode_u64(buf).map(|v| (v ^ SIGN_BIT) as i64)
+}
+
+/// Encode an `f64` as 8 bytes.
+pub fn encode_f64(val: f64) -> [u8; 8] {
+    let bits = val.to_bits();
+    let bits = if bits & SIGN_BIT != 0 { !bits } else { bits ^ SIGN_BIT };
+    encode_u64(bits)
+}
+
+/// Decode an `f64` from 8 bytes.
+pub fn decode_f64(buf: &[u8]) -> Option<f64> {
+    decode_u64(buf).map(|bits| {
+        let bits = if bits & SIGN_BIT != 0 { bits ^ SIGN_BIT } else { !bits };
+        f64::from_bits(bits)
+    })
+}
+
+/// Builds a key from components, see the module document.
+#[derive(Debug, Clone, Default)]
+pub struct KeyEncoder {
+    buf: Vec<u8>,
+}
+
+impl KeyEncoder {
+    pub fn new() -> KeyEncoder {
+        KeyEncoder::default()
+    }
+
+    /// Raw bytes, not escaped. Only order-preserving as the last component,
+    /// or when all keys have the same length at this position.
+    pub fn raw(mut self, val: &[u8]) -> Self {
+        self.buf.extend_from_slice(val);
+        self
+    }
+
+    pub fn u64(mut self, val: u64) -> Self {
+        self.buf.extend_from_slice(&encode_u64(val));
+        self
+    }
+
+    pub fn i64(mut self, val: i64) -> Self {
+        self.buf.extend_from_slice(&encode_i64(val));
+        self
+    }
+
+    pub fn f64(mut self, val: f64) -> Self {
+        self.buf.extend_from_slice(&encode_f64(val));
+        self
+    }
+
+    /// A byte string of any length.
+    pub fn bytes(mut self, val: &[u8]) -> Self {
+        for &b in val {
+            self.buf.push(b);
+            if b == ESCAPE {
+                self.buf.push(ESCAPED_NUL);
+            }
+        }
+        self.buf.push(ESCAPE);
+        self.buf.push(TERMINATOR);
+        self
+    }
+
+    /// A string of any length, see `bytes()`.
+    pub fn str(self, val: &str) -> Self {
+        self.bytes(val.as_bytes())
+    }
+
+    /// The encoded key.
+    pub fn finish(self) -> Vec<u8> {
+        self.buf
+    }
+}
+
+/// Reads components of a key built by `KeyEncoder`, in the same order.
+///
+/// Each method returns `None` if the remaining key is not a valid encoding
+/// of the component, leaving the decoder unchanged.
+#[derive(Debug, Clone)]
+pub struct KeyDecoder<'a> {
+    buf: &'a [u8],
+}
+
+impl<'a> KeyDecoder<'a> {
+    pub fn new(key: &'a [u8]) -> KeyDecoder<'a> {
+        KeyDecoder { buf: key }
+    }
+
+    /// Whether all components are read.
+    pub fn is_empty(&self) -> bool {
+        self.buf.is_empty()
+    }
+
+    /// The remaining bytes.
+    pub fn remaining(&self) -> &'a [u8] {
+        self.buf
+    }
+
+    /// `n` raw bytes.
+    pub fn raw(&mut self, n: usize) -> Option<&'a [u8]> {
+        if self.buf.len() < n {
+            return None;
+        }
+        let (val, rest) = self.buf.split_at(n);
+        self.buf = rest;
+        Some(val)
+    }
+
+    pub fn u64(&mut self) -> Option<u64> {
+        self.fixed8().and_then(decode_u64)
+    }
+
+    pub fn i64(&mut self) -> Option<i64> {
+        self.fixed8().and_then(decode_i64)
+    }
+
+    pub fn f64(&mut self) -> Option<f64> {
+        self.fixed8().and_then(decode_f64)
+    }
+
+    /// A byte string, unescaped.
+    pub fn bytes(&mut self) -> Option<Vec<u8>> {
+        let mut val = Vec::new();
+        let mut i = 0;
+        while i < self.buf.len() {
+            let b = self.buf[i];
+            if b != ESCAPE {
+                val.push(b);
+                i += 1;
+                continue;
+            }
+            match self.buf.get(i + 1) {
+                Some(&ESCAPED_NUL) => {
+                    val.push(ESCAPE);
+                    i += 2;
+                }
+                Some(&TERMINATOR) => {
+                    self.buf = &self.buf[i + 2..];
+                    return Some(val);
+                }
+                _ => return None,
+            }
+        }
+        None
+    }
+
+    /// A string, see `bytes()`.
+    pub fn string(&mut self) -> Option<String> {
+        let saved = self.buf;
+        let val = self.bytes().and_then(|val| String::from_utf8(val).ok());
+        if val.is_none() {
+            self.buf = saved;
+        }
+        val
+    }
+
+    fn fixed8(&mut self) -> Option<&'a [u8]> {
+        self.raw(8)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn integer_and_float_order() {
+        let ints = [i64::min_value(), -256, -1, 0, 1, 255, i64::max_value()];
+        for w in ints.windows(2) {
+            assert!(encode_i64(w[0]) < encode_i64(w[1]), "{} < {}", w[0], w[1]);
+        }
+        for &i in &ints {
+            assert_eq!(decode_i64(&encode_i64(i)), Some(i));
+        }
+
+        let floats = [f64::NEG_INFINITY, -1e10, -1.5, -0.0, 0.0, 1e-300, 1.5, f64::INFINITY];
+        for w in floats.windows(2) {
+            assert!(encode_f64(w[0]) < encode_f64(w[1]), "{} < {}", w[0], w[1]);
+        }
+        for &f in &floats {
+            assert_eq!(decode_f64(&encode_f64(f)).map(f64::to_bits), Some(f.to_bits()));
+        }
+
+        assert_eq!(decode_u64(b"short"), None);
+    }
+
+    #[test]
+    fn tuple_order() {
+        let key = |s: &[u8], n: i64| KeyEncoder::new().bytes(s).i64(n).finish();
+
+        // a shorter string sorts before its extensions, whatever follows it
+        assert!(key(b"ab", i64::max_value()) < key(b"ab\x00", i64::min_value()));
+        assert!(key(b"ab\x00", 0) < key(b"ab\x00\x00", 0));
+        assert!(key(b"ab", 0) < key(b"abc", 0));
+        assert!(key(b"ab", -1) < key(b"ab", 1));
+
+        let encoded = KeyEncoder::new().bytes(b"a\x00b").str("name").u64(7).raw(b"tail").finish();
+        let mut decoder = KeyDecoder::new(&encoded);
+        assert_eq!(decoder.bytes().unwrap(), b"a\x00b");
+        assert_eq!(decoder.string().unwrap(), "name");
+        assert_eq!(decoder.u64(), Some(7));
+        assert_eq!(decoder.remaining(), b"tail");
+
+        // unterminated string
+        let mut decoder = KeyDecoder::new(b"abc\x00");
+        assert_eq!(decoder.bytes(), None);
+        assert_eq!(decoder.remaining(), b"abc\x00");
+    }
+}
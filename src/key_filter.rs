@@ -0,0 +1,283 @@
+//! Offline key membership filter built over all keys of a column family.
+//!
+//! A `KeyFilter` is a plain Bloom filter with a fixed hash function and a
+//! stable serialized format, so it can be shipped to another system and
+//! probed there without RocksDB, e.g. for anti-entropy checks between
+//! replicas, without exporting the full key set.
+
+use std::sync::Arc;
+use std::thread;
+
+use crate::db::{ColumnFamilyHandle, DB};
+use crate::options::ReadOptions;
+use crate::snapshot::Snapshot;
+use crate::to_raw::{FromRaw, ToRaw};
+use crate::{Error, Result};
+
+const MAGIC: &[u8; 4] = b"RKF1";
+const HEADER_SIZE: usize = 12;
+
+/// A Bloom filter over user keys.
+///
+/// Serialized format (all integers little endian):
+///
+/// ```text
+/// magic: "RKF1" | num_probes: u32 | num_bytes: u32 | bits: [u8; num_bytes]
+/// ```
+///
+/// Keys are hashed with 64-bit FNV-1a, probe positions are derived by double
+/// hashing, so any implementation following this description can probe a
+/// filter produced here.
+#[derive(Clone, PartialEq, Eq)]
+pub struct KeyFilter {
+    num_probes: u32,
+    bits: Vec<u8>,
+}
+
+impl ::std::fmt::Debug for KeyFilter {
+    fn fmt(&self, f: &mut ::std::fmt::Formatter) -> ::std::fmt::Result {
+        f.debug_struct("KeyFilter")
+            .field("num_bits", &self.num_bits())
+            .field("num_probes", &self.num_probes)
+            .finish()
+    }
+}
+
+impl KeyFilter {
+    /// Create an empty filter sized for `expected_keys` keys using `bits_per_key` bits each.
+    ///
+    /// The number of probes is chosen the same way as RocksDB's bloom filter,
+    /// `bits_per_key * ln(2)`, clamped to `[1, 30]`.
+    pub fn new(expected_keys: usize, bits_per_key: usize) -> KeyFilter {
+        let num_probes = ((bits_per_key as f64 * 0.69) as u32).clamp(1, 30);
+        // at least 64 bits, avoids a very high false positive rate for tiny key sets
+        let num_bytes = ((expected_keys.max(1) * bits_per_key.max(1) + 7) / 8).max(8);
+        KeyFilter {
+            num_probes,
+            bits: vec![0; num_bytes],
+        }
+    }
+
+    /// Number of bits in the filter.
+    pub fn num_bits(&self) -> usize {
+        self.bits.len() * 8
+    }
+
+    /// Number of probes per key.
+    pub fn num_probes(&self) -> u32 {
+        self.num_probes
+    }
+
+    /// Add a key to the filter.
+    pub fn insert(&mut self, key: &[u8]) {
+        let num_bits = self.num_bits() as u64;
+        let (mut h, delta) = probe_seed(key);
+        for _ in 0..self.num_probes {
+            let pos = (h % num_bits) as usize;
+            self.bits[pos / 8] |= 1 << (pos % 8);
+            h = h.wrapping_add(delta);
+        }
+    }
+
+    /// Returns false if the key is definitely not in the set, true if it may be.
+    pub fn may_contain(&self, key: &[u8]) -> bool {
+        let num_bits = self.num_bits() as u64;
+        let (mut h, delta) = probe_seed(key);
+        for _ in 0..self.num_probes {
+            let pos = (h % num_bits) as usize;
+            if self.bits[pos / 8] & (1 << (pos % 8)) == 0 {
+                return false;
+            }
+            h = h.wrapping_add(delta);
+        }
+        true
+    }
+
+    /// Union another filter of the same shape into this one.
+    ///
+    /// Returns false and leaves `self` untouched if the filters differ in size
+    /// or number of probes.
+    pub fn merge(&mut self, other: &KeyFilter) -> bool {
+        if self.num_probes != other.num_probes || self.bits.len() != other.bits.len() {
+            return false;
+        }
+        for (a, b) in self.bits.iter_mut().zip(other.bits.iter()) {
+            *a |= *b;
+        }
+        true
+    }
+
+    /// Serialize the filter.
+    pub fn to_bytes(&self) -> Vec<u8> {
+        let mut buf = Vec::with_capacity(HEADER_SIZE + self.bits.len());
+        buf.extend_from_slice(MAGIC);
+        buf.extend_from_slice(&self.num_probes.to_le_bytes());
+        buf.extend_from_slice(&(self.bits.len() as u32).to_le_bytes());
+        buf.extend_from_slice(&self.bits);
+        buf
+    }
+
+    /// Deserialize a filter produced by `to_bytes()`, returns `None` on malformed input.
+    pub fn from_bytes(buf: &[u8]) -> Option<KeyFilter> {
+        if buf.len() < HEADER_SIZE || &buf[..4] != MAGIC {
+            return None;
+        }
+        let mut word = [0u8; 4];
+        word.copy_from_slice(&buf[4..8]);
+        let num_probes = u32::from_le_bytes(word);
+        word.copy_from_slice(&buf[8..12]);
+        let num_bytes = u32::from_le_bytes(word) as usize;
+        if num_probes == 0 || num_bytes == 0 || buf.len() != HEADER_SIZE + num_bytes {
+            return None;
+        }
+        Some(KeyFilter {
+            num_probes,
+            bits: buf[HEADER_SIZE..].to_vec(),
+        })
+    }
+
+    /// Build a filter over all keys of a column family, as of a consistent snapshot.
+    ///
+    /// The key space is split at SST file boundaries into up to `parallelism`
+    /// ranges, each scanned on its own thread without filling the block cache.
+    /// The filter is sized from `"rocksdb.estimate-num-keys"`.
+    ///
+    /// Fails with `InvalidArgument` if `column_family` is not opened by `db`.
+    pub fn build(
+        db: &DB,
+        column_family: &ColumnFamilyHandle,
+        bits_per_key: usize,
+        parallelism: usize,
+    ) -> Result<KeyFilter> {
+        let expected_keys = db
+            .get_int_property_cf(column_family, "rocksdb.estimate-num-keys")
+            .unwrap_or(0) as usize;
+        let cf = db
+            .column_family(column_family.name())
+            .ok_or_else(|| Error::invalid_argument("column family is not opened by this DB"))?;
+        let split_keys = Arc::new(split_points(db, column_family, parallelism.max(1)));
+
+        let snapshot = db.get_snapshot();
+        let workers = (0..=split_keys.len())
+            .map(|i| {
+                let cf = cf.clone();
+                let split_keys = split_keys.clone();
+                // workers are all joined before the snapshot is released
+                let snapshot = snapshot
+                    .as_ref()
+                    .map(|s| unsafe { Snapshot::<'static>::from_ll(s.raw()) });
+                thread::spawn(move || {
+                    let lower = if i == 0 { None } else { Some(&split_keys[i - 1][..]) };
+                    let upper = split_keys.get(i).map(|k| &k[..]);
+                    let mut ropts = ReadOptions::default().fill_cache(false).snapshot(snapshot.as_ref());
+                    if let Some(lower) = lower {
+                        ropts = ropts.iterate_lower_bound(lower);
+                    }
+                    if let Some(upper) = upper {
+                        ropts = ropts.iterate_upper_bound(upper);
+                    }
+                    let mut filter = KeyFilter::new(expected_keys, bits_per_key);
+                    let mut it = cf.new_iterator(&ropts);
+                    while it.is_valid() {
+                        filter.insert(it.key());
+                        it.next();
+                    }
+                    it.status().map(|_| filter)
+                })
+            })
+            .collect::<Vec<_>>();
+        let parts = workers.into_iter().map(|worker| worker.join()).collect::<Vec<_>>();
+        if let Some(snapshot) = snapshot {
+            db.release_snapshot(snapshot);
+        }
+
+        let mut filter = KeyFilter::new(expected_keys, bits_per_key);
+        for part in parts {
+            filter.merge(&part.expect("key filter worker panicked")?);
+        }
+        Ok(filter)
+    }
+}
+
+/// Pick up to `parallelism - 1` sorted split keys from the smallest keys of live SST files.
+fn split_points(db: &DB, column_family: &ColumnFamilyHandle, parallelism: usize) -> Vec<Vec<u8>> {
+    let mut keys = db
+        .get_column_family_metadata(column_family)
+        .levels
+        .into_iter()
+        .flat_map(|level| level.files.into_iter().map(|f| f.smallestkey))
+        .filter(|key| !key.is_empty())
+        .collect::<Vec<_>>();
+    keys.sort();
+    keys.dedup();
+    if parallelism <= 1 || keys.is_empty() {
+        return vec![];
+    }
+    let step = (keys.len() as f64 / parallelism as f64).max(1.0);
+    let mut ret: Vec<Vec<u8>> = (1..parallelism)
+        .map(|i| (i as f64 * step) as usize)
+        .take_while(|&idx| idx < keys.len())
+        .map(|idx| keys[idx].clone())
+        .collect();
+    ret.dedup();
+    ret
+}
+
+/// 64-bit FNV-1a, plus an odd delta for double hashing.
+fn probe_seed(key: &[u8]) -> (u64, u64) {
+    let mut h: u64 = 0xcbf2_9ce4_8422_2325;
+    for &b in key {
+        h ^= u64::from(b);
+        h = h.wrapping_mul(0x0000_0100_0000_01b3);
+    }
+    (h, h.rotate_right(17) | 1)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::super::rocksdb::*;
+    use super::*;
+
+    #[test]
+    fn key_filter_serialize() {
+        let mut filter = KeyFilter::new(100, 10);
+        for i in 0..100 {
+            filter.insert(format!("key-{}", i).as_bytes());
+        }
+        let decoded = KeyFilter::from_bytes(&filter.to_bytes()).unwrap();
+        assert_eq!(decoded, filter);
+        for i in 0..100 {
+            assert!(decoded.may_contain(format!("key-{}", i).as_bytes()));
+        }
+        let false_positives = (0..1000)
+            .filter(|i| decoded.may_contain(format!("other-{}", i).as_bytes()))
+            .count();
+        assert!(false_positives < 50, "too many false positives: {}", false_positives);
+
+        assert!(KeyFilter::from_bytes(b"RKF1").is_none());
+        assert!(!filter.clone().merge(&KeyFilter::new(1000, 10)));
+    }
+
+    #[test]
+    fn key_filter_build() {
+        let tmp_dir = ::tempdir::TempDir::new_in(".", "rocks").unwrap();
+        let db = DB::open(
+            Options::default().map_db_options(|db| db.create_if_missing(true)),
+            &tmp_dir,
+        )
+        .unwrap();
+
+        for i in 0..1000 {
+            let key = format!("k{:04}", i);
+            assert!(db.put(WriteOptions::default_instance(), key.as_bytes(), b"").is_ok());
+            if i % 250 == 0 {
+                assert!(db.flush(&FlushOptions::default().wait(true)).is_ok());
+            }
+        }
+
+        let filter = KeyFilter::build(&db, &db.default_column_family(), 10, 4).unwrap();
+        for i in 0..1000 {
+            assert!(filter.may_contain(format!("k{:04}", i).as_bytes()));
+        }
+    }
+}
@@ -0,0 +1,280 @@
+//! Key space heatmap export, a.k.a. key visualizer.
+//!
+//! Buckets the key space of a column family and periodically samples approximate
+//! sizes, key counts and read counts of every bucket, producing data for a
+//! key space heatmap(x for time, y for key range).
+//!
+//! All numbers are estimations:
+//!
+//! - sizes come from `DB::get_approximate_sizes()` plus the memtable stats
+//! - key counts are the memtable count plus the SST size times the average entries per byte of the column family
+//! - read counts come from the sampled SST file reads(`SstFileMetaData::num_reads_sampled()`), spread evenly over
+//!   the buckets a file overlaps. Reads served from memtables or the block cache are not sampled.
+//!
+//! Keys are compared bytewise, not by the column family comparator, so buckets only follow
+//! the key order of column families using the default bytewise comparator.
+
+use std::collections::{HashMap, VecDeque};
+use std::fmt::Write;
+use std::time::{SystemTime, UNIX_EPOCH};
+
+use crate::db::{ColumnFamilyHandle, DB};
+use crate::options::ReadOptions;
+use crate::Result;
+
+/// How to split the key space into buckets.
+#[derive(Debug, Clone)]
+pub enum BucketSplit {
+    /// Use SST file boundaries as split points, at most `max_buckets` buckets.
+    FileBoundaries { max_buckets: usize },
+    /// Fixed split points, compared bytewise.
+    SplitPoints(Vec<Vec<u8>>),
+}
+
+/// A key range `[start, end)` and its stats. `None` end means the end of the key space.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct HeatmapBucket {
+    pub start: Vec<u8>,
+    pub end: Option<Vec<u8>>,
+    pub approximate_size: u64,
+    pub approximate_keys: u64,
+    /// Sampled reads since the previous sample.
+    pub reads_sampled: u64,
+}
+
+/// Buckets of a single point in time.
+#[derive(Debug, Clone)]
+pub struct HeatmapSample {
+    /// Seconds since the UNIX epoch.
+    pub timestamp: u64,
+    pub buckets: Vec<HeatmapBucket>,
+}
+
+/// Collects heatmap samples of a column family over time.
+#[derive(Debug)]
+pub struct KeyVisualizer {
+    split: BucketSplit,
+    max_samples: usize,
+    samples: VecDeque<HeatmapSample>,
+    // file name => num_reads_sampled at previous sample
+    last_reads: HashMap<String, u64>,
+}
+
+impl KeyVisualizer {
+    pub fn new(split: BucketSplit) -> KeyVisualizer {
+        KeyVisualizer {
+            split,
+            max_samples: 1024,
+            samples: VecDeque::new(),
+            last_reads: HashMap::new(),
+        }
+    }
+
+    /// Number of samples kept, older ones are dropped. Default: 1024
+    pub fn max_samples(mut self, val: usize) -> Self {
+        self.max_samples = val.max(1);
+        self
+    }
+
+    /// Take a sample of `column_family` now.
+    pub fn sample(&mut self, db: &DB, column_family: &ColumnFamilyHandle) -> Result<&HeatmapSample> {
+        let meta = db.get_column_family_metadata(column_family);
+        let files: Vec<_> = meta.levels.iter().flat_map(|level| level.files.iter()).collect();
+
+        let split_points = match self.split {
+            BucketSplit::SplitPoints(ref points) => points.clone(),
+            BucketSplit::FileBoundaries { max_buckets } => {
+                let mut points: Vec<Vec<u8>> = files.iter().map(|f| f.smallestkey.clone()).collect();
+                points.sort();
+                points.dedup();
+                // the first bucket starts from the beginning of the key space
+                if !points.is_empty() {
+                    points.remove(0);
+                }
+                if max_buckets <= 1 {
+                    points.clear();
+                }
+                let step = (points.len() + max_buckets.saturating_sub(2)) / max_buckets.saturating_sub(1).max(1);
+                points.into_iter().step_by(step.max(1)).collect()
+            },
+        };
+
+        // a key after all keys of the column family, to close the last bucket
+        let upper = {
+            let mut it = db.new_iterator_cf(&ReadOptions::default().fill_cache(false), column_family);
+            it.seek_to_last();
+            let mut key = if it.is_valid() { it.key().to_vec() } else { vec![] };
+            it.status()?;
+            key.push(0);
+            key
+        };
+
+        let mut ranges: Vec<(Vec<u8>, Option<Vec<u8>>)> = Vec::with_capacity(split_points.len() + 1);
+        let mut start = vec![];
+        for point in split_points {
+            if point > start {
+                ranges.push((start, Some(point.clone())));
+                start = point;
+            }
+        }
+        ranges.push((start, None));
+
+        let sizes = {
+            let key_ranges: Vec<_> = ranges
+                .iter()
+                .map(|(start, end)| &start[..]..end.as_ref().map(|k| &k[..]).unwrap_or(&upper[..]))
+                .collect();
+            db.get_approximate_sizes(column_family, &key_ranges)
+        };
+
+        let (total_entries, total_data_size) = db
+            .get_properties_of_all_tables_cf(column_family)?
+            .iter()
+            .fold((0, 0), |(entries, size), (_, prop)| {
+                (entries + prop.num_entries(), size + prop.data_size())
+            });
+        let entries_per_byte = if total_data_size == 0 {
+            0.0
+        } else {
+            total_entries as f64 / total_data_size as f64
+        };
+
+        let mut buckets: Vec<HeatmapBucket> = ranges
+            .into_iter()
+            .zip(sizes)
+            .map(|((start, end), sst_size)| {
+                let (mem_count, mem_size) = db.get_approximate_memtable_stats(
+                    column_family,
+                    &start[..]..end.as_ref().map(|k| &k[..]).unwrap_or(&upper[..]),
+                );
+                HeatmapBucket {
+                    start,
+                    end,
+                    approximate_size: sst_size + mem_size,
+                    approximate_keys: mem_count + (sst_size as f64 * entries_per_byte) as u64,
+                    reads_sampled: 0,
+                }
+            })
+            .collect();
+
+        let mut current_reads = HashMap::with_capacity(files.len());
+        for file in &files {
+            let reads = file
+                .num_reads_sampled()
+                .saturating_sub(self.last_reads.get(&file.name).cloned().unwrap_or(0));
+            current_reads.insert(file.name.clone(), file.num_reads_sampled());
+            if reads == 0 {
+                continue;
+            }
+            let overlapping: Vec<usize> = buckets
+                .iter()
+                .enumerate()
+                .filter(|(_, b)| {
+                    b.start <= file.largestkey
+                        && match b.end {
+                            Some(ref end) => *end > file.smallestkey,
+                            None => true,
+                        }
+                })
+                .map(|(i, _)| i)
+                .collect();
+            let n = overlapping.len() as u64;
+            for (j, &i) in overlapping.iter().enumerate() {
+                // spread the remainder over the first buckets
+                buckets[i].reads_sampled += reads / n + if (j as u64) < reads % n { 1 } else { 0 };
+            }
+        }
+        self.last_reads = current_reads;
+
+        let timestamp = SystemTime::now()
+            .duration_since(UNIX_EPOCH)
+            .map(|d| d.as_secs())
+            .unwrap_or(0);
+        if self.samples.len() >= self.max_samples {
+            self.samples.pop_front();
+        }
+        self.samples.push_back(HeatmapSample { timestamp, buckets });
+        Ok(self.samples.back().unwrap())
+    }
+
+    /// Samples taken so far, oldest first.
+    pub fn samples(&self) -> &VecDeque<HeatmapSample> {
+        &self.samples
+    }
+
+    /// Export all samples as CSV, keys are hex encoded.
+    ///
+    /// Columns: `timestamp,start,end,approximate_size,approximate_keys,reads_sampled`
+    pub fn to_csv(&self) -> String {
+        let mut out = String::from("timestamp,start,end,approximate_size,approximate_keys,reads_sampled\n");
+        for sample in &self.samples {
+            for bucket in &sample.buckets {
+                let _ = writeln!(
+                    out,
+                    "{},{},{},{},{},{}",
+                    sample.timestamp,
+                    to_hex(&bucket.start),
+                    bucket.end.as_ref().map(|k| to_hex(k)).unwrap_or_default(),
+                    bucket.approximate_size,
+                    bucket.approximate_keys,
+                    bucket.reads_sampled
+                );
+            }
+        }
+        out
+    }
+}
+
+fn to_hex(key: &[u8]) -> String {
+    key.iter().fold(String::with_capacity(key.len() * 2), |mut s, b| {
+        let _ = write!(s, "{:02x}", b);
+        s
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::rocksdb::*;
+
+    #[test]
+    fn key_visualizer() {
+        let tmp_dir = ::tempdir::TempDir::new_in(".", "rocks").unwrap();
+        let db = DB::open(
+            Options::default().map_db_options(|db| db.create_if_missing(true)),
+            &tmp_dir,
+        )
+        .unwrap();
+        let cf = db.default_column_family();
+
+        for i in 0..1000 {
+            let key = format!("k{:04}", i);
+            assert!(db.put(WriteOptions::default_instance(), key.as_bytes(), &[b'v'; 100]).is_ok());
+            if i % 250 == 249 {
+                assert!(db.flush(&FlushOptions::default().wait(true)).is_ok());
+            }
+        }
+
+        let mut vis = KeyVisualizer::new(BucketSplit::SplitPoints(vec![b"k0500".to_vec()])).max_samples(2);
+        let sample = vis.sample(&db, &cf).unwrap();
+        assert_eq!(sample.buckets.len(), 2);
+        assert_eq!(sample.buckets[0].start, b"");
+        assert_eq!(sample.buckets[0].end.as_ref().unwrap(), b"k0500");
+        assert!(sample.buckets[1].end.is_none());
+        assert!(sample.buckets.iter().all(|b| b.approximate_size > 0 && b.approximate_keys > 0));
+
+        for _ in 0..3 {
+            vis.sample(&db, &cf).unwrap();
+        }
+        assert_eq!(vis.samples().len(), 2);
+        let csv = vis.to_csv();
+        assert!(csv.starts_with("timestamp,start,end,"));
+        assert_eq!(csv.lines().count(), 1 + 2 * 2);
+        assert!(csv.contains(",6b30353030,")); // "k0500"
+
+        let mut vis = KeyVisualizer::new(BucketSplit::FileBoundaries { max_buckets: 2 });
+        let sample = vis.sample(&db, &cf).unwrap();
+        assert_eq!(sample.buckets.len(), 2);
+        assert!(sample.buckets.iter().map(|b| b.approximate_keys).sum::<u64>() > 0);
+    }
+}
@@ -0,0 +1,368 @@
+//! Primary/secondary role coordination for processes sharing a DB directory.
+//!
+//! Exactly one process may open a DB read-write, the primary; all others must use
+//! `DB::open_as_secondary()`. RocksDB's own `LOCK` file stops a second read-write open,
+//! but not a process that picks the wrong open call, nor does it tell anyone who the
+//! primary is.
+//!
+//! A `LeaseFile` records the current primary in a `LEASE` file in the DB directory,
+//! with an expiry the primary keeps pushing forward. `RoleElection` runs the protocol
+//! in a background thread: it takes the lease when it is free or expired, opens the DB
+//! in the matching role, and hands every newly opened DB to a callback.
+//!
+//! Leases are advisory and rely on roughly synchronized clocks; the `LOCK` file still
+//! guards against two read-write opens.
+
+use std::fs;
+use std::io;
+use std::path::{Path, PathBuf};
+use std::sync::{Arc, Condvar, Mutex};
+use std::thread::{self, JoinHandle};
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
+
+use crate::db::DB;
+use crate::env::{Env, FileLock};
+use crate::options::Options;
+
+/// Name of the lease file in the DB directory.
+pub const LEASE_FILE_NAME: &str = "LEASE";
+// guards read-modify-write of the lease file across processes
+const LEASE_LOCK_FILE_NAME: &str = "LEASE.lock";
+const LOCK_ATTEMPTS: u32 = 100;
+
+/// Content of a lease file.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Lease {
+    pub owner: String,
+    /// Incremented on every change of owner, usable as a fencing token.
+    pub epoch: u64,
+    pub expires: SystemTime,
+}
+
+impl Lease {
+    pub fn is_expired(&self) -> bool {
+        SystemTime::now() >= self.expires
+    }
+
+    fn parse(s: &str) -> Option<Lease> {
+        let mut owner = None;
+        let mut epoch = None;
+        let mut expires = None;
+        for line in s.lines() {
+            let mut kv = line.splitn(2, '=');
+            match (kv.next(), kv.next()) {
+                (Some("owner"), Some(v)) => owner = Some(v.to_owned()),
+                (Some("epoch"), Some(v)) => epoch = v.parse().ok(),
+                (Some("expires_ms"), Some(v)) => {
+                    expires = v.parse().ok().map(|ms| UNIX_EPOCH + Duration::from_millis(ms))
+                },
+                _ => (),
+            }
+        }
+        Some(Lease {
+            owner: owner?,
+            epoch: epoch?,
+            expires: expires?,
+        })
+    }
+
+    fn serialize(&self) -> String {
+        let expires_ms = self.expires.duration_since(UNIX_EPOCH).unwrap_or_default().as_millis();
+        format!("owner={}\nepoch={}\nexpires_ms={}\n", self.owner, self.epoch, expires_ms)
+    }
+}
+
+/// A lease file in a DB directory, held on behalf of `owner`.
+#[derive(Debug, Clone)]
+pub struct LeaseFile {
+    dir: PathBuf,
+    owner: String,
+    duration: Duration,
+}
+
+impl LeaseFile {
+    /// `owner` identifies this process, e.g. `hostname:pid`, and must not contain newlines.
+    pub fn new<P: AsRef<Path>>(dir: P, owner: &str, duration: Duration) -> LeaseFile {
+        assert!(!owner.contains('\n'), "owner must be a single line");
+        LeaseFile {
+            dir: dir.as_ref().to_owned(),
+            owner: owner.to_owned(),
+            duration,
+        }
+    }
+
+    pub fn owner(&self) -> &str {
+        &self.owner
+    }
+
+    pub fn duration(&self) -> Duration {
+        self.duration
+    }
+
+    /// The current lease, expired or not, `None` if there never was one.
+    pub fn read(&self) -> io::Result<Option<Lease>> {
+        match fs::read_to_string(self.dir.join(LEASE_FILE_NAME)) {
+            Ok(s) => Lease::parse(&s)
+                .map(Some)
+                .ok_or_else(|| io::Error::new(io::ErrorKind::InvalidData, "malformed lease file")),
+            Err(ref e) if e.kind() == io::ErrorKind::NotFound => Ok(None),
+            Err(e) => Err(e),
+        }
+    }
+
+    /// Acquire the lease if it is free or expired, or renew it if already held.
+    ///
+    /// Returns the lease if this owner holds it afterwards, `None` if another owner
+    /// holds an unexpired lease.
+    pub fn try_acquire(&self) -> io::Result<Option<Lease>> {
+        let _lock = self.lock()?;
+        let current = self.read()?;
+        let epoch = match current {
+            Some(ref lease) if lease.owner == self.owner => lease.epoch,
+            Some(ref lease) if !lease.is_expired() => return Ok(None),
+            Some(ref lease) => lease.epoch + 1,
+            None => 1,
+        };
+        let lease = Lease {
+            owner: self.owner.clone(),
+            epoch,
+            expires: SystemTime::now() + self.duration,
+        };
+        self.write(&lease)?;
+        Ok(Some(lease))
+    }
+
+    /// Give up the lease, if held, so that another owner can take it over right away.
+    pub fn release(&self) -> io::Result<()> {
+        let _lock = self.lock()?;
+        match self.read()? {
+            Some(mut lease) if lease.owner == self.owner => {
+                lease.expires = UNIX_EPOCH;
+                self.write(&lease)
+            },
+            _ => Ok(()),
+        }
+    }
+
+    fn write(&self, lease: &Lease) -> io::Result<()> {
+        let tmp = self.dir.join(format!("{}.tmp", LEASE_FILE_NAME));
+        fs::write(&tmp, lease.serialize())?;
+        fs::File::open(&tmp)?.sync_all()?;
+        fs::rename(&tmp, self.dir.join(LEASE_FILE_NAME))
+    }
+
+    fn lock(&self) -> io::Result<FileLock<'static>> {
+        let path = self.dir.join(LEASE_LOCK_FILE_NAME);
+        for _ in 0..LOCK_ATTEMPTS {
+            if let Ok(lock) = Env::default_instance().lock_file(&path) {
+                return Ok(lock);
+            }
+            thread::sleep(Duration::from_millis(10));
+        }
+        Err(io::Error::new(io::ErrorKind::WouldBlock, "lease lock is held"))
+    }
+}
+
+/// Role of a process in a `RoleElection`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum Role {
+    /// Holds the lease, DB opened read-write.
+    Primary,
+    /// DB opened with `DB::open_as_secondary()`.
+    Secondary,
+}
+
+/// Background thread running the lease protocol, see module docs.
+///
+/// Dropping it stops the thread and releases the lease if held.
+pub struct RoleElection {
+    role: Arc<Mutex<Option<Role>>>,
+    stopped: Arc<(Mutex<bool>, Condvar)>,
+    thread: Option<JoinHandle<()>>,
+}
+
+impl ::std::fmt::Debug for RoleElection {
+    fn fmt(&self, f: &mut ::std::fmt::Formatter) -> ::std::fmt::Result {
+        f.debug_struct("RoleElection").field("role", &self.role()).finish()
+    }
+}
+
+impl RoleElection {
+    /// Start electing, checking the lease every third of its duration.
+    ///
+    /// `options` creates the `Options` for every open. `on_role_change` receives each
+    /// newly opened DB with its role and must drop the previously received DB: after a
+    /// demotion, which happens when the lease could not be renewed in time, another
+    /// process may already be promoted. A promotion only succeeds once the old primary
+    /// closed the DB, so a failed read-write open gives the lease back and retries.
+    ///
+    /// Secondaries are opened with `secondary_path` for their info logs, and must be
+    /// caught up with the primary by the caller, e.g. with `DB::spawn_catch_up()`.
+    pub fn spawn<O, F>(
+        options: O,
+        db_path: PathBuf,
+        secondary_path: PathBuf,
+        lease: LeaseFile,
+        mut on_role_change: F,
+    ) -> RoleElection
+    where
+        O: Fn() -> Options + Send + 'static,
+        F: FnMut(Role, DB) + Send + 'static,
+    {
+        let role = Arc::new(Mutex::new(None));
+        let stopped = Arc::new((Mutex::new(false), Condvar::new()));
+        let current = role.clone();
+        let stop = stopped.clone();
+        let interval = lease.duration() / 3;
+        let thread = thread::Builder::new()
+            .name("rocks-election".into())
+            .spawn(move || {
+                let (lock, cvar) = &*stop;
+                // local deadline of the held lease, in case renewals fail
+                let mut held_until = None;
+                loop {
+                    let was = *current.lock().unwrap();
+                    let acquired = match lease.try_acquire() {
+                        Ok(Some(l)) => {
+                            held_until = Some(l.expires);
+                            true
+                        },
+                        Ok(None) => false,
+                        // keep the role while the lease we hold is still valid
+                        Err(_) => held_until.map_or(false, |t| SystemTime::now() < t),
+                    };
+                    let next = match (acquired, was) {
+                        (true, Some(Role::Primary)) | (false, Some(Role::Secondary)) => None,
+                        (true, _) => match DB::open(options(), &db_path) {
+                            Ok(db) => Some((Role::Primary, db)),
+                            Err(_) => {
+                                held_until = None;
+                                let _ = lease.release();
+                                match was {
+                                    Some(_) => None,
+                                    None => DB::open_as_secondary(&options(), &db_path, &secondary_path)
+                                        .ok()
+                                        .map(|db| (Role::Secondary, db)),
+                                }
+                            },
+                        },
+                        (false, _) => {
+                            held_until = None;
+                            DB::open_as_secondary(&options(), &db_path, &secondary_path)
+                                .ok()
+                                .map(|db| (Role::Secondary, db))
+                        },
+                    };
+                    if let Some((r, db)) = next {
+                        *current.lock().unwrap() = Some(r);
+                        on_role_change(r, db);
+                    }
+
+                    let stopped = lock.lock().unwrap();
+                    let (stopped, _) = cvar.wait_timeout_while(stopped, interval, |s| !*s).unwrap();
+                    if *stopped {
+                        break;
+                    }
+                }
+                if *current.lock().unwrap() == Some(Role::Primary) {
+                    let _ = lease.release();
+                }
+            })
+            .expect("spawn election thread");
+        RoleElection {
+            role,
+            stopped,
+            thread: Some(thread),
+        }
+    }
+
+    /// Current role, `None` until the DB was opened.
+    pub fn role(&self) -> Option<Role> {
+        *self.role.lock().unwrap()
+    }
+
+    /// Stop the thread, releasing the lease if held. DBs handed out stay open.
+    pub fn stop(mut self) {
+        self.stop_and_join();
+    }
+
+    fn stop_and_join(&mut self) {
+        let (lock, cvar) = &*self.stopped;
+        *lock.lock().unwrap() = true;
+        cvar.notify_all();
+        if let Some(thread) = self.thread.take() {
+            let _ = thread.join();
+        }
+    }
+}
+
+impl Drop for RoleElection {
+    fn drop(&mut self) {
+        self.stop_and_join();
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::sync::mpsc;
+
+    use super::*;
+
+    #[test]
+    fn lease_file() {
+        let tmp_dir = ::tempdir::TempDir::new_in(".", "rocks").unwrap();
+        let a = LeaseFile::new(tmp_dir.path(), "a", Duration::from_millis(200));
+        let b = LeaseFile::new(tmp_dir.path(), "b", Duration::from_millis(200));
+        assert_eq!(a.read().unwrap(), None);
+
+        let lease = a.try_acquire().unwrap().unwrap();
+        assert_eq!(lease.epoch, 1);
+        assert_eq!(b.try_acquire().unwrap(), None);
+        assert_eq!(a.try_acquire().unwrap().unwrap().epoch, 1);
+
+        thread::sleep(Duration::from_millis(250));
+        assert!(a.read().unwrap().unwrap().is_expired());
+        assert_eq!(b.try_acquire().unwrap().unwrap().epoch, 2);
+        assert!(b.release().is_ok());
+        assert_eq!(a.try_acquire().unwrap().unwrap().epoch, 3);
+    }
+
+    #[test]
+    fn role_election() {
+        let tmp_dir = ::tempdir::TempDir::new_in(".", "rocks").unwrap();
+        let secondary_dir = ::tempdir::TempDir::new_in(".", "rocks").unwrap();
+        let spawn = |owner: &str| {
+            let (tx, rx) = mpsc::channel();
+            let slot = Arc::new(Mutex::new(None));
+            let db_slot = slot.clone();
+            let election = RoleElection::spawn(
+                || Options::default().map_db_options(|db| db.create_if_missing(true).max_open_files(-1)),
+                tmp_dir.path().to_owned(),
+                secondary_dir.path().join(owner),
+                LeaseFile::new(tmp_dir.path(), owner, Duration::from_millis(300)),
+                move |role, db| {
+                    // drops the previous DB
+                    *db_slot.lock().unwrap() = Some(db);
+                    tx.send(role).unwrap();
+                },
+            );
+            (election, rx, slot)
+        };
+
+        let (first, first_roles, first_db) = spawn("first");
+        assert_eq!(first_roles.recv().unwrap(), Role::Primary);
+        assert_eq!(first.role(), Some(Role::Primary));
+
+        let (second, second_roles, _second_db) = spawn("second");
+        assert_eq!(second_roles.recv().unwrap(), Role::Secondary);
+
+        // the lease is released on stop, but the DB is still open
+        first.stop();
+        assert!(second_roles.recv_timeout(Duration::from_millis(500)).is_err());
+        assert_eq!(second.role(), Some(Role::Secondary));
+
+        drop(first_db.lock().unwrap().take());
+        assert_eq!(second_roles.recv_timeout(Duration::from_secs(5)).unwrap(), Role::Primary);
+        assert_eq!(second.role(), Some(Role::Primary));
+    }
+}
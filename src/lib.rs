@@ -29,8 +29,12 @@ pub use error::Error;
 /// The result type returned by RocksDB, wraps Status
 pub type Result<T> = std::result::Result<T, Error>;
 
+pub mod admin;
 pub mod advanced_options;
+pub mod batched_writer;
 pub mod cache;
+pub mod change_feed;
+pub mod checkpoint;
 pub mod compaction_filter;
 pub mod compaction_job_stats;
 pub mod comparator;
@@ -42,15 +46,26 @@ pub mod env;
 pub mod error;
 pub mod filter_policy;
 pub mod flush_block_policy;
+pub mod index;
 pub mod iostats_context;
 pub mod iterator;
+pub mod key_encoding;
+pub mod key_filter;
+pub mod key_visualizer;
+pub mod lease;
 pub mod listener;
+pub mod memory_controller;
+pub mod memory_util;
 pub mod merge_operator;
 pub mod metadata;
+#[cfg(feature = "metrics")]
+pub mod metrics;
+pub mod operation;
 pub mod options;
 pub mod perf_context;
 pub mod perf_level;
 pub mod persistent_cache;
+pub mod properties;
 pub mod rate_limiter;
 pub mod slice;
 pub mod slice_transform;
@@ -58,15 +73,19 @@ pub mod snapshot;
 pub mod sst_file_manager;
 pub mod sst_file_writer;
 pub mod statistics;
+pub mod stats_history;
 pub mod table;
 pub mod table_properties;
 pub mod thread_status;
+pub mod transaction;
 pub mod transaction_log;
+pub mod typed_cf;
 pub mod types;
 pub mod universal_compaction;
 pub mod utilities;
 pub mod version;
 pub mod wal_filter;
+pub mod wal_inspect;
 pub mod write_batch;
 pub mod write_buffer_manager;
 
@@ -3,12 +3,15 @@
 
 use rocks_sys as ll;
 
+use std::borrow::Cow;
 use std::fmt;
 use std::marker::PhantomData;
 use std::mem;
 use std::ptr;
 use std::slice;
 use std::str;
+use std::sync::{Arc, Mutex};
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
 
 use crate::compaction_job_stats::CompactionJobStats;
 use crate::db::{ColumnFamilyHandle, DBRef};
@@ -192,6 +195,54 @@ pub enum BackgroundErrorReason {
     MemTable,
 }
 
+#[repr(C)]
+#[derive(Debug, Copy, Clone, PartialEq, Eq, Hash)]
+pub enum WriteStallCondition {
+    Normal,
+    Delayed,
+    Stopped,
+}
+
+impl WriteStallCondition {
+    fn from_ll(val: i32) -> Option<WriteStallCondition> {
+        match val {
+            0 => Some(WriteStallCondition::Normal),
+            1 => Some(WriteStallCondition::Delayed),
+            2 => Some(WriteStallCondition::Stopped),
+            _ => None,
+        }
+    }
+}
+
+#[derive(Debug)]
+pub struct WriteStallInfo<'a> {
+    /// the name of the column family
+    pub cf_name: Cow<'a, str>,
+    /// current state of the write controller
+    pub cur: WriteStallCondition,
+    /// previous state of the write controller
+    pub prev: WriteStallCondition,
+}
+
+#[derive(Debug)]
+pub struct FileOperationInfo<'a> {
+    pub path: Cow<'a, str>,
+    pub offset: u64,
+    pub length: usize,
+    pub start_timestamp: SystemTime,
+    pub finish_timestamp: SystemTime,
+    pub status: Result<()>,
+}
+
+impl<'a> FileOperationInfo<'a> {
+    /// Time spent on the file operation.
+    pub fn duration(&self) -> Duration {
+        self.finish_timestamp
+            .duration_since(self.start_timestamp)
+            .unwrap_or_default()
+    }
+}
+
 #[derive(Debug)]
 pub struct TableFileDeletionInfo<'a> {
     /// The name of the database where the file was deleted.
@@ -458,6 +509,8 @@ pub enum CompactionListenerValueType {
 /// iterator is compacting values. It is mean to be returned from
 /// `EventListner::GetCompactionEventListner()` at the beginning of compaction
 /// job.
+///
+/// RocksDB 6.0 removed this interface, see `EventListener::get_compaction_event_listener()`.
 pub trait CompactionEventListener {
     fn on_compaction(
         &mut self,
@@ -654,17 +707,124 @@ pub trait EventListener {
     /// computations or blocking calls in this function.
     ///
     /// Rust: use `Ok(())` to suppress errors, use `Err(bg_error)` otherwise and default impl.
+    /// Returning another `Err` replaces the background error.
     fn on_background_error(&mut self, reason: BackgroundErrorReason, bg_error: Error) -> Result<()> {
         Err(bg_error)
     }
 
+    /// A call-back function for RocksDB which will be called whenever a change
+    /// of superversion triggers a change of the stall conditions.
+    ///
+    /// Note that the this function must be implemented in a way such that
+    /// it should not run for an extended period of time before the function
+    /// returns.  Otherwise, RocksDB may be blocked.
+    fn on_stall_conditions_changed(&mut self, info: &WriteStallInfo) {}
+
+    /// A call-back function for RocksDB which will be called whenever a file read
+    /// operation finishes. Only called when `should_be_notified_on_file_io()` returns true.
+    ///
+    /// Note that this function is called concurrently from the threads doing the IO,
+    /// so any state must be kept behind atomics or locks.
+    fn on_file_read_finish(&self, info: &FileOperationInfo) {}
+
+    /// A call-back function for RocksDB which will be called whenever a file write
+    /// operation finishes. Only called when `should_be_notified_on_file_io()` returns true.
+    ///
+    /// Note that this function is called concurrently from the threads doing the IO,
+    /// so any state must be kept behind atomics or locks.
+    fn on_file_write_finish(&self, info: &FileOperationInfo) {}
+
+    /// If true, the `on_file_*_finish` functions will be called. If false, then
+    /// they will not be called.
+    fn should_be_notified_on_file_io(&mut self) -> bool {
+        false
+    }
+
+    /// A call-back function for RocksDB which will be called once the database
+    /// is recovered from read-only mode after an error. When this is called, it
+    /// means normal writes to the database can be issued and the user can
+    /// initiate any further recovery actions needed.
+    fn on_error_recovery_completed(&mut self, old_bg_error: Error) {}
+
     /// Factory method to return CompactionEventListener. If multiple listeners
     /// provides CompactionEventListner, only the first one will be used.
+    ///
+    /// Never called: `CompactionEventListener` and
+    /// `EventListener::GetCompactionEventListener()` were removed in RocksDB 6.0,
+    /// there is nothing left to hook it to. Use a `CompactionFilter` to observe
+    /// keys going through compaction.
+    #[deprecated(note = "removed in RocksDB 6.0, never called; use a CompactionFilter to observe compacted keys")]
     fn get_compaction_event_listener(&mut self) -> Option<&mut dyn CompactionEventListener> {
         None
     }
 }
 
+/// A listener shared between DBs, see `DBOptions::add_shared_listener()`.
+///
+/// Call-backs from all DBs are serialized by the mutex. The inner listener is dropped
+/// when the last `Arc` is gone, i.e. every DB it was registered to is closed.
+impl<T: EventListener> EventListener for Arc<Mutex<T>> {
+    fn on_flush_completed(&mut self, db: &DBRef, flush_job_info: &FlushJobInfo) {
+        self.lock().unwrap().on_flush_completed(db, flush_job_info)
+    }
+
+    fn on_flush_begin(&mut self, db: &DBRef, flush_job_info: &FlushJobInfo) {
+        self.lock().unwrap().on_flush_begin(db, flush_job_info)
+    }
+
+    fn on_table_file_deleted(&mut self, info: &TableFileDeletionInfo) {
+        self.lock().unwrap().on_table_file_deleted(info)
+    }
+
+    fn on_compaction_completed(&mut self, db: &DBRef, ci: &CompactionJobInfo) {
+        self.lock().unwrap().on_compaction_completed(db, ci)
+    }
+
+    fn on_table_file_created(&mut self, info: &TableFileCreationInfo) {
+        self.lock().unwrap().on_table_file_created(info)
+    }
+
+    fn on_table_file_creation_started(&mut self, info: &TableFileCreationBriefInfo) {
+        self.lock().unwrap().on_table_file_creation_started(info)
+    }
+
+    fn on_memtable_sealed(&mut self, info: &MemTableInfo) {
+        self.lock().unwrap().on_memtable_sealed(info)
+    }
+
+    fn on_column_family_handle_deletion_started(&mut self, handle: &ColumnFamilyHandle) {
+        self.lock().unwrap().on_column_family_handle_deletion_started(handle)
+    }
+
+    fn on_external_file_ingested(&mut self, db: &DBRef, info: &ExternalFileIngestionInfo) {
+        self.lock().unwrap().on_external_file_ingested(db, info)
+    }
+
+    fn on_background_error(&mut self, reason: BackgroundErrorReason, bg_error: Error) -> Result<()> {
+        self.lock().unwrap().on_background_error(reason, bg_error)
+    }
+
+    fn on_stall_conditions_changed(&mut self, info: &WriteStallInfo) {
+        self.lock().unwrap().on_stall_conditions_changed(info)
+    }
+
+    fn on_file_read_finish(&self, info: &FileOperationInfo) {
+        self.lock().unwrap().on_file_read_finish(info)
+    }
+
+    fn on_file_write_finish(&self, info: &FileOperationInfo) {
+        self.lock().unwrap().on_file_write_finish(info)
+    }
+
+    fn should_be_notified_on_file_io(&mut self) -> bool {
+        self.lock().unwrap().should_be_notified_on_file_io()
+    }
+
+    fn on_error_recovery_completed(&mut self, old_bg_error: Error) {
+        self.lock().unwrap().on_error_recovery_completed(old_bg_error)
+    }
+}
+
 #[doc(hidden)]
 pub mod c {
     use super::*;
@@ -710,7 +870,7 @@ pub mod c {
         info: *mut ll::rocks_flush_job_info_t,
     ) {
         let listener = l as *mut Box<dyn EventListener>;
-        let db_ref = mem::transmute::<_, DBRef>(db);
+        let db_ref = DBRef::from_borrowed_ll(db as *mut ll::rocks_db_t);
         let flush_job_info = flush_job_info_convert(info);
 
         (*listener).on_flush_completed(&db_ref, &flush_job_info);
@@ -724,7 +884,7 @@ pub mod c {
         info: *mut ll::rocks_flush_job_info_t,
     ) {
         let listener = l as *mut Box<dyn EventListener>;
-        let db_ref = mem::transmute::<_, DBRef>(db);
+        let db_ref = DBRef::from_borrowed_ll(db as *mut ll::rocks_db_t);
         let flush_job_info = flush_job_info_convert(info);
 
         (*listener).on_flush_begin(&db_ref, &flush_job_info);
@@ -766,7 +926,7 @@ pub mod c {
         ci: *mut ll::rocks_compaction_job_info_t,
     ) {
         let listener = l as *mut Box<dyn EventListener>;
-        let db_ref = mem::transmute::<_, DBRef>(db);
+        let db_ref = DBRef::from_borrowed_ll(db as *mut ll::rocks_db_t);
         let info = CompactionJobInfo {
             raw: ci,
             _marker: PhantomData,
@@ -820,7 +980,7 @@ pub mod c {
         info: *const ll::rocks_external_file_ingestion_info_t,
     ) {
         let listener = l as *mut Box<dyn EventListener>;
-        let db_ref = mem::transmute::<_, DBRef>(db);
+        let db_ref = DBRef::from_borrowed_ll(db as *mut ll::rocks_db_t);
         let info = ExternalFileIngestionInfo { raw: info };
         (*listener).on_external_file_ingested(&db_ref, &info);
         mem::forget(db_ref);
@@ -831,18 +991,96 @@ pub mod c {
         l: *mut (),
         reason: BackgroundErrorReason,
         bg_error: *mut ll::rocks_status_t,
-    ) -> u8 {
+    ) -> *mut ll::rocks_status_t {
         let listener = l as *mut Box<dyn EventListener>;
         let result = Result::from_ll(bg_error);
-        let ret = (*listener).on_background_error(reason, result.unwrap_err());
-        if ret.is_ok() {
-            0
-        } else {
-            1
+        match (*listener).on_background_error(reason, result.unwrap_err()) {
+            Ok(()) => ptr::null_mut(),
+            Err(e) => e.into_raw(),
+        }
+    }
+
+    #[no_mangle]
+    pub unsafe extern "C" fn rust_event_listener_on_stall_conditions_changed(
+        l: *mut (),
+        info: *const ll::rocks_write_stall_info_t,
+    ) {
+        let listener = l as *mut Box<dyn EventListener>;
+        let cur = WriteStallCondition::from_ll(ll::rocks_write_stall_info_get_cur(info));
+        let prev = WriteStallCondition::from_ll(ll::rocks_write_stall_info_get_prev(info));
+        // unknown conditions from a newer RocksDB are not reported
+        if let (Some(cur), Some(prev)) = (cur, prev) {
+            let info = WriteStallInfo {
+                cf_name: {
+                    let mut len = 0;
+                    let ptr = ll::rocks_write_stall_info_get_cf_name(info, &mut len);
+                    String::from_utf8_lossy(slice::from_raw_parts(ptr as *const u8, len))
+                },
+                cur,
+                prev,
+            };
+            (*listener).on_stall_conditions_changed(&info);
+        }
+    }
+
+    unsafe fn file_operation_info_convert<'a>(info: *const ll::rocks_file_operation_info_t) -> FileOperationInfo<'a> {
+        let to_system_time = |nanos: i64| UNIX_EPOCH + Duration::from_nanos(nanos.max(0) as u64);
+        FileOperationInfo {
+            path: {
+                let mut len = 0;
+                let ptr = ll::rocks_file_operation_info_get_path(info, &mut len);
+                String::from_utf8_lossy(slice::from_raw_parts(ptr as *const u8, len))
+            },
+            offset: ll::rocks_file_operation_info_get_offset(info),
+            length: ll::rocks_file_operation_info_get_length(info),
+            start_timestamp: to_system_time(ll::rocks_file_operation_info_get_start_timestamp(info)),
+            finish_timestamp: to_system_time(ll::rocks_file_operation_info_get_finish_timestamp(info)),
+            status: {
+                let mut status = ptr::null_mut::<ll::rocks_status_t>();
+                ll::rocks_file_operation_info_get_status(info, &mut status);
+                Result::from_ll(status)
+            },
         }
     }
 
     #[no_mangle]
+    pub unsafe extern "C" fn rust_event_listener_on_file_read_finish(
+        l: *mut (),
+        info: *const ll::rocks_file_operation_info_t,
+    ) {
+        let listener = l as *const Box<dyn EventListener>;
+        (*listener).on_file_read_finish(&file_operation_info_convert(info));
+    }
+
+    #[no_mangle]
+    pub unsafe extern "C" fn rust_event_listener_on_file_write_finish(
+        l: *mut (),
+        info: *const ll::rocks_file_operation_info_t,
+    ) {
+        let listener = l as *const Box<dyn EventListener>;
+        (*listener).on_file_write_finish(&file_operation_info_convert(info));
+    }
+
+    #[no_mangle]
+    pub unsafe extern "C" fn rust_event_listener_should_be_notified_on_file_io(l: *mut ()) -> u8 {
+        let listener = l as *mut Box<dyn EventListener>;
+        (*listener).should_be_notified_on_file_io() as u8
+    }
+
+    #[no_mangle]
+    pub unsafe extern "C" fn rust_event_listener_on_error_recovery_completed(
+        l: *mut (),
+        old_bg_error: *mut ll::rocks_status_t,
+    ) {
+        let listener = l as *mut Box<dyn EventListener>;
+        if let Err(e) = Result::from_ll(old_bg_error) {
+            (*listener).on_error_recovery_completed(e);
+        }
+    }
+
+    // NOTE: not referenced by the C++ shim, kept for ABI compatibility
+    #[no_mangle]
+    #[allow(deprecated)]
     pub unsafe extern "C" fn rust_event_listener_get_compaction_event_listener(l: *mut ()) -> *mut () {
         let listener = l as *mut Box<dyn EventListener>;
         match (*listener).get_compaction_event_listener() {
@@ -875,6 +1113,8 @@ pub mod c {
 
 #[cfg(test)]
 mod tests {
+    use std::sync::{Arc, Mutex};
+
     use super::super::rocksdb::*;
     use super::*;
 
@@ -1038,4 +1278,143 @@ mod tests {
         // safe shutdown
         assert!(db.pause_background_work().is_ok());
     }
+
+    // (status is ok, path is not empty, finish timestamp is not before start)
+    type FileOp = (bool, bool, bool);
+
+    #[derive(Default)]
+    struct StallAndFileIOListener {
+        stall_conditions: Arc<Mutex<Vec<(String, WriteStallCondition, WriteStallCondition)>>>,
+        file_writes: Arc<Mutex<Vec<FileOp>>>,
+        file_reads: Arc<Mutex<Vec<FileOp>>>,
+    }
+
+    fn file_op(info: &FileOperationInfo) -> FileOp {
+        (
+            info.status.is_ok(),
+            !info.path.is_empty(),
+            info.finish_timestamp >= info.start_timestamp,
+        )
+    }
+
+    impl EventListener for StallAndFileIOListener {
+        fn on_stall_conditions_changed(&mut self, info: &WriteStallInfo) {
+            self.stall_conditions
+                .lock()
+                .unwrap()
+                .push((info.cf_name.to_string(), info.prev, info.cur));
+        }
+
+        fn on_file_read_finish(&self, info: &FileOperationInfo) {
+            self.file_reads.lock().unwrap().push(file_op(info));
+        }
+
+        fn on_file_write_finish(&self, info: &FileOperationInfo) {
+            self.file_writes.lock().unwrap().push(file_op(info));
+        }
+
+        fn should_be_notified_on_file_io(&mut self) -> bool {
+            true
+        }
+    }
+
+    #[test]
+    fn event_listener_stall_and_file_io() {
+        let listener = StallAndFileIOListener::default();
+        let stall_conditions = listener.stall_conditions.clone();
+        let file_writes = listener.file_writes.clone();
+        let file_reads = listener.file_reads.clone();
+
+        let tmp_dir = ::tempdir::TempDir::new_in(".", "rocks").unwrap();
+        let db = DB::open(
+            Options::default()
+                .map_db_options(|db| db.create_if_missing(true).add_listener(listener))
+                .map_cf_options(|cf| {
+                    // slowdown trigger is sanitized to be no less than compaction trigger
+                    cf.level0_file_num_compaction_trigger(2)
+                        .level0_slowdown_writes_trigger(2)
+                        .level0_stop_writes_trigger(10)
+                }),
+            &tmp_dir,
+        )
+        .unwrap();
+
+        for i in 0..3 {
+            let key = format!("k{}", i);
+            assert!(db.put(WriteOptions::default_instance(), key.as_bytes(), b"v").is_ok());
+            assert!(db.flush(&FlushOptions::default().wait(true)).is_ok());
+        }
+        assert!(db.get(ReadOptions::default_instance(), b"k0").is_ok());
+
+        let stall_conditions = stall_conditions.lock().unwrap();
+        assert!(stall_conditions.iter().all(|(cf_name, _, _)| cf_name == "default"));
+        assert!(stall_conditions
+            .iter()
+            .any(|&(_, prev, cur)| (prev, cur) == (WriteStallCondition::Normal, WriteStallCondition::Delayed)));
+        let file_writes = file_writes.lock().unwrap();
+        assert!(!file_writes.is_empty());
+        assert!(file_writes.iter().all(|&op| op == (true, true, true)));
+        let file_reads = file_reads.lock().unwrap();
+        assert!(!file_reads.is_empty());
+        assert!(file_reads.iter().all(|&(ok, _, _)| ok));
+    }
+
+    #[derive(Default)]
+    struct FlushCounter {
+        dbs: Vec<String>,
+    }
+
+    impl EventListener for FlushCounter {
+        fn on_flush_completed(&mut self, db: &DBRef, _info: &FlushJobInfo) {
+            self.dbs.push(db.name());
+        }
+    }
+
+    #[test]
+    fn shared_event_listener() {
+        let listener = Arc::new(Mutex::new(FlushCounter::default()));
+
+        let dir1 = ::tempdir::TempDir::new_in(".", "rocks").unwrap();
+        let dir2 = ::tempdir::TempDir::new_in(".", "rocks").unwrap();
+        let db1 = DB::open(
+            Options::default().map_db_options(|db| db.create_if_missing(true).add_shared_listener(&listener)),
+            &dir1,
+        )
+        .unwrap();
+        let db2 = DB::open(
+            Options::default().map_db_options(|db| db.create_if_missing(true).add_shared_listener(&listener)),
+            &dir2,
+        )
+        .unwrap();
+        assert!(Arc::strong_count(&listener) > 1);
+
+        for db in &[&db1, &db2] {
+            db.put(WriteOptions::default_instance(), b"key", b"value").unwrap();
+            assert!(db.flush(&FlushOptions::default().wait(true)).is_ok());
+        }
+        assert_eq!(listener.lock().unwrap().dbs.len(), 2);
+        assert_ne!(listener.lock().unwrap().dbs[0], listener.lock().unwrap().dbs[1]);
+
+        drop(db1);
+        assert!(Arc::strong_count(&listener) > 1);
+        drop(db2);
+        assert_eq!(Arc::strong_count(&listener), 1);
+
+        // removed before open
+        let dir3 = ::tempdir::TempDir::new_in(".", "rocks").unwrap();
+        let db3 = DB::open(
+            Options::default().map_db_options(|db| {
+                db.create_if_missing(true)
+                    .add_shared_listener(&listener)
+                    .add_shared_listener(&listener)
+                    .remove_listener(&listener)
+            }),
+            &dir3,
+        )
+        .unwrap();
+        assert_eq!(Arc::strong_count(&listener), 1);
+        db3.put(WriteOptions::default_instance(), b"key", b"value").unwrap();
+        assert!(db3.flush(&FlushOptions::default().wait(true)).is_ok());
+        assert_eq!(listener.lock().unwrap().dbs.len(), 2);
+    }
 }
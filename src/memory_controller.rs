@@ -0,0 +1,321 @@
+//! Block cache capacity controller driven by system memory pressure.
+//!
+//! The controller periodically samples memory usage of the process's cgroup,
+//! falling back to `/proc/meminfo`, and shrinks the block cache when usage
+//! crosses a high watermark, growing it back when usage drops below a low
+//! watermark. The gap between the two watermarks is the hysteresis band,
+//! within which the capacity is left alone.
+//!
+//! `WriteBufferManager` has no way to change its limit after creation in the
+//! RocksDB version this crate builds against, so only cache capacity is
+//! controlled.
+
+use std::fmt;
+use std::fs;
+use std::sync::{Arc, Condvar, Mutex};
+use std::thread::{self, JoinHandle};
+use std::time::Duration;
+
+use crate::cache::Cache;
+
+/// Memory usage sample, in bytes.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct MemoryUsage {
+    pub limit: u64,
+    pub used: u64,
+}
+
+impl MemoryUsage {
+    /// Fraction of the limit in use.
+    pub fn ratio(&self) -> f64 {
+        if self.limit == 0 {
+            0.0
+        } else {
+            self.used as f64 / self.limit as f64
+        }
+    }
+
+    /// Read the memory limit and usage of the current cgroup (v2, then v1),
+    /// or of the whole system when not limited by a cgroup.
+    ///
+    /// Returns `None` when none of those are readable, e.g. on non-Linux systems.
+    pub fn current() -> Option<MemoryUsage> {
+        cgroup_v2()
+            .or_else(cgroup_v1)
+            .or_else(meminfo)
+    }
+}
+
+fn read_u64(path: &str) -> Option<u64> {
+    fs::read_to_string(path).ok()?.trim().parse().ok()
+}
+
+fn cgroup_v2() -> Option<MemoryUsage> {
+    // "max" when unlimited, fails to parse
+    let limit = read_u64("/sys/fs/cgroup/memory.max")?;
+    let used = read_u64("/sys/fs/cgroup/memory.current")?;
+    Some(MemoryUsage { limit, used })
+}
+
+fn cgroup_v1() -> Option<MemoryUsage> {
+    let limit = read_u64("/sys/fs/cgroup/memory/memory.limit_in_bytes")?;
+    let used = read_u64("/sys/fs/cgroup/memory/memory.usage_in_bytes")?;
+    // unlimited is reported as a huge page-aligned number
+    if limit >= i64::MAX as u64 / 2 {
+        return None;
+    }
+    Some(MemoryUsage { limit, used })
+}
+
+fn meminfo() -> Option<MemoryUsage> {
+    let content = fs::read_to_string("/proc/meminfo").ok()?;
+    let field = |name: &str| -> Option<u64> {
+        let line = content.lines().find(|line| line.starts_with(name))?;
+        let kb: u64 = line[name.len()..].trim().trim_end_matches("kB").trim().parse().ok()?;
+        Some(kb * 1024)
+    };
+    let limit = field("MemTotal:")?;
+    let available = field("MemAvailable:")?;
+    Some(MemoryUsage {
+        limit,
+        used: limit.saturating_sub(available),
+    })
+}
+
+/// A capacity change made by the controller.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct ResizeEvent {
+    pub old_capacity: usize,
+    pub new_capacity: usize,
+    /// The memory sample that triggered the change.
+    pub usage: MemoryUsage,
+}
+
+type ResizeCallback = Box<dyn Fn(&ResizeEvent) + Send>;
+
+/// Configuration of a `MemoryController`.
+pub struct MemoryControllerOptions {
+    min_capacity: usize,
+    max_capacity: usize,
+    high_watermark: f64,
+    low_watermark: f64,
+    step_ratio: f64,
+    interval: Duration,
+    on_resize: Option<ResizeCallback>,
+}
+
+impl fmt::Debug for MemoryControllerOptions {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.debug_struct("MemoryControllerOptions")
+            .field("min_capacity", &self.min_capacity)
+            .field("max_capacity", &self.max_capacity)
+            .field("high_watermark", &self.high_watermark)
+            .field("low_watermark", &self.low_watermark)
+            .field("step_ratio", &self.step_ratio)
+            .field("interval", &self.interval)
+            .finish()
+    }
+}
+
+impl MemoryControllerOptions {
+    /// Keep the cache capacity within `[min_capacity, max_capacity]`.
+    pub fn new(min_capacity: usize, max_capacity: usize) -> MemoryControllerOptions {
+        MemoryControllerOptions {
+            min_capacity: min_capacity.min(max_capacity),
+            max_capacity,
+            high_watermark: 0.9,
+            low_watermark: 0.75,
+            step_ratio: 0.1,
+            interval: Duration::from_secs(5),
+            on_resize: None,
+        }
+    }
+
+    /// Shrink the cache when used memory is above this fraction of the limit.
+    ///
+    /// Default: 0.9
+    pub fn high_watermark(mut self, val: f64) -> Self {
+        self.high_watermark = val;
+        self
+    }
+
+    /// Grow the cache when used memory is below this fraction of the limit.
+    ///
+    /// Default: 0.75
+    pub fn low_watermark(mut self, val: f64) -> Self {
+        self.low_watermark = val;
+        self
+    }
+
+    /// Each adjustment changes capacity by this fraction of `max_capacity`.
+    ///
+    /// Default: 0.1
+    pub fn step_ratio(mut self, val: f64) -> Self {
+        self.step_ratio = val;
+        self
+    }
+
+    /// How often memory usage is sampled.
+    ///
+    /// Default: 5s
+    pub fn interval(mut self, val: Duration) -> Self {
+        self.interval = val;
+        self
+    }
+
+    /// Called on the controller thread after every capacity change.
+    pub fn on_resize<F: Fn(&ResizeEvent) + Send + 'static>(mut self, f: F) -> Self {
+        self.on_resize = Some(Box::new(f));
+        self
+    }
+
+    /// Capacity to switch to for a memory sample, `None` to keep the current one.
+    pub fn next_capacity(&self, current: usize, usage: &MemoryUsage) -> Option<usize> {
+        let step = ((self.max_capacity as f64 * self.step_ratio) as usize).max(1);
+        let ratio = usage.ratio();
+        let target = if ratio > self.high_watermark {
+            current.saturating_sub(step).max(self.min_capacity)
+        } else if ratio < self.low_watermark {
+            current.saturating_add(step).min(self.max_capacity)
+        } else {
+            current
+        };
+        if target != current {
+            Some(target)
+        } else {
+            None
+        }
+    }
+}
+
+/// Background thread adjusting a block cache's capacity, see module docs.
+///
+/// Dropping the controller stops the thread.
+pub struct MemoryController {
+    stopped: Arc<(Mutex<bool>, Condvar)>,
+    thread: Option<JoinHandle<()>>,
+}
+
+impl fmt::Debug for MemoryController {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.debug_struct("MemoryController").finish()
+    }
+}
+
+impl MemoryController {
+    /// Start controlling `cache`, sampling memory with `MemoryUsage::current()`.
+    pub fn spawn(cache: Cache, options: MemoryControllerOptions) -> MemoryController {
+        MemoryController::spawn_with_sampler(cache, options, MemoryUsage::current)
+    }
+
+    /// Start controlling `cache` with a custom memory sampler. Samples of `None`
+    /// are skipped.
+    pub fn spawn_with_sampler<S>(cache: Cache, options: MemoryControllerOptions, sampler: S) -> MemoryController
+    where
+        S: Fn() -> Option<MemoryUsage> + Send + 'static,
+    {
+        let stopped = Arc::new((Mutex::new(false), Condvar::new()));
+        let stop = stopped.clone();
+        let thread = thread::Builder::new()
+            .name("rocks-mem-ctrl".into())
+            .spawn(move || {
+                let (lock, cvar) = &*stop;
+                loop {
+                    {
+                        let stopped = lock.lock().unwrap();
+                        let (stopped, _) = cvar.wait_timeout_while(stopped, options.interval, |s| !*s).unwrap();
+                        if *stopped {
+                            break;
+                        }
+                    }
+                    let usage = match sampler() {
+                        Some(usage) => usage,
+                        None => continue,
+                    };
+                    let old_capacity = cache.get_capacity();
+                    if let Some(new_capacity) = options.next_capacity(old_capacity, &usage) {
+                        cache.set_capacity(new_capacity);
+                        if let Some(ref on_resize) = options.on_resize {
+                            on_resize(&ResizeEvent {
+                                old_capacity,
+                                new_capacity,
+                                usage,
+                            });
+                        }
+                    }
+                }
+            })
+            .expect("spawn memory controller thread");
+        MemoryController {
+            stopped,
+            thread: Some(thread),
+        }
+    }
+
+    /// Stop the thread. The cache keeps its current capacity.
+    pub fn stop(mut self) {
+        self.stop_and_join();
+    }
+
+    fn stop_and_join(&mut self) {
+        let (lock, cvar) = &*self.stopped;
+        *lock.lock().unwrap() = true;
+        cvar.notify_all();
+        if let Some(thread) = self.thread.take() {
+            let _ = thread.join();
+        }
+    }
+}
+
+impl Drop for MemoryController {
+    fn drop(&mut self) {
+        self.stop_and_join();
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::sync::mpsc;
+
+    use super::*;
+    use crate::cache::CacheBuilder;
+
+    #[test]
+    fn next_capacity_hysteresis() {
+        let opts = MemoryControllerOptions::new(100, 1000);
+        let usage = |used| MemoryUsage { limit: 100, used };
+
+        assert_eq!(opts.next_capacity(1000, &usage(95)), Some(900));
+        assert_eq!(opts.next_capacity(150, &usage(95)), Some(100));
+        assert_eq!(opts.next_capacity(100, &usage(95)), None);
+        // within the band
+        assert_eq!(opts.next_capacity(500, &usage(80)), None);
+        assert_eq!(opts.next_capacity(500, &usage(50)), Some(600));
+        assert_eq!(opts.next_capacity(1000, &usage(50)), None);
+    }
+
+    #[test]
+    fn memory_controller() {
+        let cache = CacheBuilder::new_lru(1 << 20).build().unwrap();
+        let (tx, rx) = mpsc::channel();
+        let tx = Mutex::new(tx);
+        let controller = MemoryController::spawn_with_sampler(
+            cache.clone(),
+            MemoryControllerOptions::new(1 << 18, 1 << 20)
+                .interval(Duration::from_millis(1))
+                .on_resize(move |ev| tx.lock().unwrap().send(*ev).unwrap()),
+            || Some(MemoryUsage { limit: 100, used: 99 }),
+        );
+
+        let ev = rx.recv().unwrap();
+        assert_eq!(ev.old_capacity, 1 << 20);
+        assert!(ev.new_capacity < 1 << 20);
+        controller.stop();
+        assert!(cache.get_capacity() >= 1 << 18);
+        assert!(cache.get_capacity() < 1 << 20);
+
+        if let Some(usage) = MemoryUsage::current() {
+            assert!(usage.limit > 0);
+        }
+    }
+}
@@ -0,0 +1,93 @@
+//! Approximate memory usage of a set of DBs and caches.
+//!
+//! Mirrors `rocksdb::MemoryUtil`, useful for capacity planning when several
+//! DB instances share a process.
+
+use std::ptr;
+
+use rocks_sys as ll;
+
+use crate::cache::Cache;
+use crate::db::DB;
+use crate::to_raw::ToRaw;
+use crate::{Error, Result};
+
+/// Approximate memory usage in bytes, by usage type.
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq)]
+pub struct MemoryUsageByType {
+    /// Memory usage of all the mem-tables.
+    pub mem_table_total: u64,
+    /// Memory usage of those un-flushed mem-tables.
+    pub mem_table_unflushed: u64,
+    /// Memory usage of all the table readers.
+    pub table_readers_total: u64,
+    /// Memory usage by the caches. Caches shared by several DBs are counted once.
+    pub cache_total: u64,
+}
+
+/// Returns the approximate memory usage of different types in the input
+/// list of DBs and caches.
+///
+/// Only the caches in `caches` are counted in `cache_total`, block caches used
+/// by `dbs` are not picked up from their options and must be passed explicitly.
+pub fn get_approximate_memory_usage_by_type(dbs: &[&DB], caches: &[&Cache]) -> Result<MemoryUsageByType> {
+    let c_dbs = dbs.iter().map(|db| db.raw()).collect::<Vec<_>>();
+    let c_caches = caches.iter().map(|cache| cache.raw()).collect::<Vec<_>>();
+    let mut usage = [0_u64; 4];
+    let mut status = ptr::null_mut();
+    unsafe {
+        ll::rocks_get_approximate_memory_usage_by_type(
+            c_dbs.as_ptr(),
+            c_dbs.len(),
+            c_caches.as_ptr(),
+            c_caches.len(),
+            usage.as_mut_ptr(),
+            &mut status,
+        );
+    }
+    Error::from_ll(status).map(|_| MemoryUsageByType {
+        mem_table_total: usage[0],
+        mem_table_unflushed: usage[1],
+        table_readers_total: usage[2],
+        cache_total: usage[3],
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::super::rocksdb::*;
+    use super::*;
+    use crate::cache::CacheBuilder;
+
+    #[test]
+    fn memory_usage_by_type() {
+        let cache = CacheBuilder::new_lru(1 << 20).build().unwrap();
+        let tmp_dir1 = ::tempdir::TempDir::new_in(".", "rocks").unwrap();
+        let tmp_dir2 = ::tempdir::TempDir::new_in(".", "rocks").unwrap();
+        let opts = Options::default()
+            .map_db_options(|db| db.create_if_missing(true))
+            .map_cf_options(|cf| {
+                cf.table_factory_block_based(BlockBasedTableOptions::default().block_cache(Some(cache.clone())))
+            });
+        let db1 = DB::open(&opts, &tmp_dir1).unwrap();
+        let db2 = DB::open(&opts, &tmp_dir2).unwrap();
+
+        for i in 0..100 {
+            let key = format!("k{:03}", i);
+            assert!(db1.put(WriteOptions::default_instance(), key.as_bytes(), b"value").is_ok());
+            assert!(db2.put(WriteOptions::default_instance(), key.as_bytes(), b"value").is_ok());
+        }
+        assert!(db2.flush(&FlushOptions::default().wait(true)).is_ok());
+        assert!(db2.get(ReadOptions::default_instance(), b"k000").is_ok());
+
+        let usage = get_approximate_memory_usage_by_type(&[&db1, &db2], &[&cache]).unwrap();
+        assert!(usage.mem_table_total > 0);
+        assert!(usage.mem_table_unflushed > 0);
+        assert!(usage.mem_table_total >= usage.mem_table_unflushed);
+        assert!(usage.table_readers_total > 0);
+        assert!(usage.cache_total > 0 && usage.cache_total <= 1 << 20);
+
+        let empty = get_approximate_memory_usage_by_type(&[], &[]).unwrap();
+        assert_eq!(empty, MemoryUsageByType::default());
+    }
+}
@@ -142,6 +142,67 @@ pub trait MergeOperator {
         false
     }
 
+    /// This function performs merge(left_op, right_op)
+    /// when both the operands are themselves merge operation types
+    /// that you would have passed to a DB::Merge() call in the same order
+    /// (i.e.: DB::Merge(key,left_op), followed by DB::Merge(key,right_op)).
+    ///
+    /// PartialMerge should combine them into a single merge operation.
+    /// The return value should be constructed such that a call to
+    /// DB::Merge(key, new_value) would yield the same result as a call
+    /// to DB::Merge(key, left_op) followed by DB::Merge(key, right_op).
+    ///
+    /// The default implementation of `partial_merge_multi` will use this function
+    /// as a helper, for backward compatibility.  Any successor class of
+    /// MergeOperator should either implement PartialMerge or PartialMergeMulti,
+    /// although implementing PartialMergeMulti is suggested as it is in general
+    /// more effective to merge multiple operands at a time instead of two
+    /// operands at a time.
+    ///
+    /// If it is impossible or infeasible to combine the two operations,
+    /// return `None`. The library will internally keep track of the operations,
+    /// and apply them in the correct order once a base-value (a Put/Delete/End-of-Database) is seen.
+    fn partial_merge(&self, key: &[u8], left_operand: &[u8], right_operand: &[u8]) -> Option<Vec<u8>> {
+        None
+    }
+
+    /// This function performs merge when all the operands are themselves merge
+    /// operation types that you would have passed to a DB::Merge() call in the
+    /// same order (front() first)
+    /// (i.e. DB::Merge(key, operand_list[0]), followed by
+    ///  DB::Merge(key, operand_list[1]), ...)
+    ///
+    /// PartialMergeMulti should combine them into a single merge operation.
+    /// The return value should be constructed such that a call to
+    /// DB::Merge(key, new_value) would yield the same result as a call
+    /// to DB::Merge(key,operand_list[0]), followed by
+    ///  DB::Merge(key,operand_list[1]), ...
+    ///
+    /// If it is impossible or infeasible to combine the operations, return `None`.
+    /// The library will internally keep track of the operations, and apply them in the
+    /// correct order once a base-value (a Put/Delete/End-of-Database) is seen.
+    ///
+    /// The default implementation calls `partial_merge` on each pair of operands,
+    /// front to back.
+    fn partial_merge_multi(&self, key: &[u8], operands: &[&[u8]]) -> Option<Vec<u8>> {
+        let (first, rest) = operands.split_first()?;
+        let mut merged = first.to_vec();
+        for operand in rest {
+            merged = self.partial_merge(key, &merged, operand)?;
+        }
+        Some(merged)
+    }
+
+    /// Allows to control when to invoke a full merge during Get.
+    /// This could be used to limit the number of merge operands that are looked at
+    /// during a point lookup, thereby helping in limiting the number of levels to
+    /// read from.
+    /// Also, if true, `partial_merge_multi` is called with a single operand, e.g. to
+    /// rewrite operands during compaction.
+    fn allow_single_operand(&self) -> bool {
+        false
+    }
+
     /// The name of the MergeOperator. Used to check for MergeOperator
     /// mismatches (i.e., a DB created with one MergeOperator is
     /// accessed using a different MergeOperator)
@@ -212,6 +273,33 @@ pub mod c {
         }
     }
 
+    #[no_mangle]
+    pub unsafe extern "C" fn rust_merge_operator_call_partial_merge_multi(
+        op: *mut (),
+        key: &&[u8],
+        operands: *const &[u8],
+        num_operands: usize,
+        new_value: *mut (),
+    ) -> i32 {
+        assert!(!op.is_null());
+        let operator = op as *mut Box<dyn MergeOperator>;
+        let operands = slice::from_raw_parts(operands, num_operands);
+        match (*operator).partial_merge_multi(key, operands) {
+            Some(val) => {
+                ll::cxx_string_assign(new_value as *mut _, val.as_ptr() as *const _, val.len());
+                1
+            }
+            None => 0,
+        }
+    }
+
+    #[no_mangle]
+    pub unsafe extern "C" fn rust_merge_operator_allow_single_operand(op: *mut ()) -> u8 {
+        assert!(!op.is_null());
+        let operator = op as *mut Box<dyn MergeOperator>;
+        (*operator).allow_single_operand() as u8
+    }
+
     #[no_mangle]
     pub extern "C" fn rust_merge_operator_drop(op: *mut ()) {
         assert!(!op.is_null());
@@ -348,6 +436,51 @@ mod tests {
         );
     }
 
+    #[test]
+    fn materialize_key() {
+        use crate::debug::ValueType;
+        use tempdir::TempDir;
+        let tmp_dir = TempDir::new_in(".", "rocks").unwrap();
+
+        pub struct ConcatMergeOp;
+
+        impl AssociativeMergeOperator for ConcatMergeOp {
+            fn merge(
+                &self,
+                key: &[u8],
+                existing_value: Option<&[u8]>,
+                value: &[u8],
+                logger: &Logger,
+            ) -> Option<Vec<u8>> {
+                let mut ret: Vec<u8> = existing_value.map(|s| s.into()).unwrap_or_default();
+                ret.extend_from_slice(value);
+                Some(ret)
+            }
+        }
+
+        let db = DB::open(
+            Options::default()
+                .map_db_options(|db| db.create_if_missing(true))
+                .map_cf_options(|cf| cf.associative_merge_operator(Box::new(ConcatMergeOp))),
+            &tmp_dir,
+        )
+        .unwrap();
+        let cf = db.default_column_family();
+
+        for c in b"abcde" {
+            assert!(cf.merge(WriteOptions::default_instance(), b"key", &[*c]).is_ok());
+        }
+        assert_eq!(cf.materialize_key(WriteOptions::default_instance(), b"key"), Ok(true));
+        assert_eq!(cf.materialize_key(WriteOptions::default_instance(), b"missing"), Ok(false));
+        assert!(cf.get(&ReadOptions::default(), b"missing").is_err());
+
+        assert!(cf.compact_range(&Default::default(), &b"key"[..]..=&b"key"[..]).is_ok());
+        let versions = db.get_all_key_versions(b"key", b"key").unwrap();
+        assert_eq!(versions.len(), 1);
+        assert_eq!(versions[0].value_type(), ValueType::Value);
+        assert_eq!(versions[0].value(), b"abcde");
+    }
+
     #[test]
     fn merge_assign_concat_operands() {
         use crate::merge_operator::{MergeOperationInput, MergeOperationOutput};
@@ -445,4 +578,68 @@ mod tests {
         // println!("ret => {:?}", ret.as_ref().map(|s| String::from_utf8_lossy(s)));
         assert_eq!(ret.unwrap().as_ref(), b"I-am-the-test-233");
     }
+
+    #[test]
+    fn partial_merge_on_flush() {
+        use crate::debug::ValueType;
+        use std::sync::atomic::{AtomicUsize, Ordering};
+        use std::sync::Arc;
+
+        let tmp_dir = ::tempdir::TempDir::new_in(".", "rocks").unwrap();
+
+        struct CounterMergeOp {
+            partial_merges: Arc<AtomicUsize>,
+        }
+
+        fn decode(val: &[u8]) -> u64 {
+            let mut buf = [0; 8];
+            buf.copy_from_slice(val);
+            u64::from_le_bytes(buf)
+        }
+
+        impl MergeOperator for CounterMergeOp {
+            fn full_merge(&self, merge_in: &MergeOperationInput, merge_out: &mut MergeOperationOutput) -> bool {
+                let base = merge_in.existing_value().map(decode).unwrap_or(0);
+                let sum = merge_in.operands().iter().map(|op| decode(op)).sum::<u64>() + base;
+                merge_out.assign(&sum.to_le_bytes());
+                true
+            }
+
+            // pairs, through the default `partial_merge_multi`
+            fn partial_merge(&self, _key: &[u8], left: &[u8], right: &[u8]) -> Option<Vec<u8>> {
+                self.partial_merges.fetch_add(1, Ordering::SeqCst);
+                Some((decode(left) + decode(right)).to_le_bytes().to_vec())
+            }
+        }
+
+        let partial_merges = Arc::new(AtomicUsize::new(0));
+        let db = DB::open(
+            Options::default()
+                .map_db_options(|db| db.create_if_missing(true))
+                .map_cf_options(|cf| {
+                    cf.merge_operator(Box::new(CounterMergeOp {
+                        partial_merges: partial_merges.clone(),
+                    }))
+                }),
+            &tmp_dir,
+        )
+        .unwrap();
+
+        for i in 1..=4u64 {
+            assert!(db.merge(WriteOptions::default_instance(), b"counter", &i.to_le_bytes()).is_ok());
+        }
+        // no base value in the memtable, operands are combined by partial merges
+        assert!(db.flush(&FlushOptions::default().wait(true)).is_ok());
+        assert_eq!(partial_merges.load(Ordering::SeqCst), 3);
+
+        let versions = db.get_all_key_versions(b"counter", b"counter").unwrap();
+        assert_eq!(versions.len(), 1);
+        assert_eq!(versions[0].value_type(), ValueType::Merge);
+        assert_eq!(decode(versions[0].value()), 10);
+
+        assert!(db.put(WriteOptions::default_instance(), b"base", &5u64.to_le_bytes()).is_ok());
+        assert!(db.merge(WriteOptions::default_instance(), b"base", &1u64.to_le_bytes()).is_ok());
+        assert_eq!(decode(&db.get(ReadOptions::default_instance(), b"counter").unwrap()), 10);
+        assert_eq!(decode(&db.get(ReadOptions::default_instance(), b"base").unwrap()), 6);
+    }
 }
@@ -1,7 +1,13 @@
 //! The metadata that describes a column family, a level, or a SST file,
 
+use std::ffi::CStr;
 use std::fmt;
+use std::mem;
 use std::ops::Deref;
+use std::slice;
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
+
+use rocks_sys as ll;
 
 use crate::types::SequenceNumber;
 
@@ -18,6 +24,74 @@ pub struct ColumnFamilyMetaData {
     pub levels: Vec<LevelMetaData>,
 }
 
+impl ColumnFamilyMetaData {
+    /// All sst files in this column family, level by level.
+    pub fn files(&self) -> impl Iterator<Item = &SstFileMetaData> {
+        self.levels.iter().flat_map(|level| level.files.iter())
+    }
+
+    /// The sst files of this column family bucketed by data age, see `DataAgeHistogram::new()`.
+    pub fn data_age_histogram(&self, bounds: &[Duration]) -> DataAgeHistogram {
+        DataAgeHistogram::new(self.files(), SystemTime::now(), bounds)
+    }
+
+    /// Split the key space into at most `n` ranges of roughly equal sst file size, at
+    /// file boundaries, e.g. for parallel full scans.
+    ///
+    /// Files are ordered by their smallest keys bytewise, so ranges are only balanced
+    /// with the default comparator. Data in memtables is not counted, and with fewer
+    /// files than `n`, fewer ranges are returned. The ranges cover the whole key space.
+    pub fn split_into_ranges(&self, n: usize) -> Vec<KeyRange> {
+        let n = n.max(1) as u64;
+        let mut files: Vec<_> = self.files().collect();
+        files.sort_by(|a, b| a.smallestkey.cmp(&b.smallestkey));
+        let total_size: u64 = files.iter().map(|f| f.size).sum();
+
+        let mut ranges = Vec::new();
+        let mut start = vec![];
+        let mut size = 0;
+        for file in files {
+            let num_ranges = ranges.len() as u64 + 1;
+            // cut before the first file whose middle is past the share of the current range,
+            // never before the first file, that range would be empty
+            if num_ranges < n
+                && size > 0
+                && size + file.size / 2 >= total_size * num_ranges / n
+                && file.smallestkey > start
+            {
+                let end = file.smallestkey.clone();
+                ranges.push(KeyRange {
+                    start: mem::replace(&mut start, end.clone()),
+                    end: Some(end),
+                });
+            }
+            size += file.size;
+        }
+        ranges.push(KeyRange { start, end: None });
+        ranges
+    }
+}
+
+/// A key range `[start, end)`, returned by `ColumnFamilyMetaData::split_into_ranges()`.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct KeyRange {
+    /// Inclusive, empty for the beginning of the key space.
+    pub start: Vec<u8>,
+    /// Exclusive, `None` for the end of the key space.
+    pub end: Option<Vec<u8>>,
+}
+
+impl KeyRange {
+    /// Whether `key` is in this range, compared bytewise.
+    pub fn contains(&self, key: &[u8]) -> bool {
+        key >= &self.start[..]
+            && match self.end {
+                Some(ref end) => key < &end[..],
+                None => true,
+            }
+    }
+}
+
 impl fmt::Debug for ColumnFamilyMetaData {
     fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
         f.debug_struct("ColumnFamilyMetaData")
@@ -72,6 +146,24 @@ pub struct SstFileMetaData {
     pub largestkey: Vec<u8>,
     /// true if the file is currently being compacted.
     pub being_compacted: bool,
+    // see num_reads_sampled()
+    pub(crate) num_reads_sampled: u64,
+    /// The number of the file, as in its name `<file_number>.sst`.
+    pub file_number: u64,
+    /// Number of entries in the file, deletions included.
+    pub num_entries: u64,
+    /// Number of deletion entries in the file.
+    pub num_deletions: u64,
+    /// The number of the oldest blob file referenced by the file, 0 if none, only
+    /// set with BlobDB.
+    pub oldest_blob_file_number: u64,
+    /// The oldest creation time, in seconds since the epoch, of the files this file
+    /// was compacted from, or of the file itself if flushed. 0 if unknown.
+    ///
+    /// This is what TTL and periodic compactions go by.
+    pub oldest_ancester_time: u64,
+    /// When the file was created, in seconds since the epoch. 0 if unknown.
+    pub file_creation_time: u64,
 }
 
 impl fmt::Debug for SstFileMetaData {
@@ -80,6 +172,28 @@ impl fmt::Debug for SstFileMetaData {
     }
 }
 
+impl SstFileMetaData {
+    /// Number of times the file has been read, sampled.
+    pub fn num_reads_sampled(&self) -> u64 {
+        self.num_reads_sampled
+    }
+
+    /// The age of the oldest data in the file at `now`, going by `oldest_ancester_time`,
+    /// or `file_creation_time` if it is not recorded. `None` if neither is known.
+    pub fn data_age(&self, now: SystemTime) -> Option<Duration> {
+        let time = if self.oldest_ancester_time != 0 {
+            self.oldest_ancester_time
+        } else {
+            self.file_creation_time
+        };
+        if time == 0 {
+            return None;
+        }
+        let now = now.duration_since(UNIX_EPOCH).map(|d| d.as_secs()).unwrap_or(0);
+        Some(Duration::from_secs(now.saturating_sub(time)))
+    }
+}
+
 /// The full set of metadata associated with each SST file.
 pub struct LiveFileMetaData {
     pub sst_file: SstFileMetaData,
@@ -89,6 +203,72 @@ pub struct LiveFileMetaData {
     pub level: u32,
 }
 
+impl LiveFileMetaData {
+    /// Converts and destroys a list of live files.
+    pub(crate) unsafe fn from_ll_list(livefiles: *const ll::rocks_livefiles_t) -> Vec<LiveFileMetaData> {
+        let cnt = ll::rocks_livefiles_count(livefiles);
+        let mut ret = Vec::with_capacity(cnt as usize);
+        for i in 0..cnt {
+            let name = CStr::from_ptr(ll::rocks_livefiles_name(livefiles, i))
+                .to_string_lossy()
+                .into_owned();
+            let db_path: String = CStr::from_ptr(ll::rocks_livefiles_db_path(livefiles, i))
+                .to_string_lossy()
+                .into_owned();
+            let size = ll::rocks_livefiles_size(livefiles, i);
+
+            let small_seqno = ll::rocks_livefiles_smallest_seqno(livefiles, i);
+            let large_seqno = ll::rocks_livefiles_largest_seqno(livefiles, i);
+
+            let mut key_len = 0;
+            let small_key_ptr = ll::rocks_livefiles_smallestkey(livefiles, i, &mut key_len);
+            let small_key = slice::from_raw_parts(small_key_ptr as *const u8, key_len).to_vec();
+
+            let large_key_ptr = ll::rocks_livefiles_largestkey(livefiles, i, &mut key_len);
+            let large_key = slice::from_raw_parts(large_key_ptr as *const u8, key_len).to_vec();
+
+            let being_compacted = ll::rocks_livefiles_being_compacted(livefiles, i) != 0;
+            let num_reads_sampled = ll::rocks_livefiles_num_reads_sampled(livefiles, i);
+            let file_number = ll::rocks_livefiles_file_number(livefiles, i);
+            let num_entries = ll::rocks_livefiles_num_entries(livefiles, i);
+            let num_deletions = ll::rocks_livefiles_num_deletions(livefiles, i);
+            let oldest_blob_file_number = ll::rocks_livefiles_oldest_blob_file_number(livefiles, i);
+
+            let cf_name = CStr::from_ptr(ll::rocks_livefiles_column_family_name(livefiles, i))
+                .to_string_lossy()
+                .into_owned();
+            let level = ll::rocks_livefiles_level(livefiles, i);
+
+            let meta = LiveFileMetaData {
+                sst_file: SstFileMetaData {
+                    size: size as u64,
+                    name,
+                    db_path,
+                    smallest_seqno: small_seqno.into(),
+                    largest_seqno: large_seqno.into(),
+                    smallestkey: small_key,
+                    largestkey: large_key,
+                    being_compacted,
+                    num_reads_sampled,
+                    file_number,
+                    num_entries,
+                    num_deletions,
+                    oldest_blob_file_number,
+                    // not set by GetLiveFilesMetaData(), see `DBRef::get_live_files_metadata()`
+                    oldest_ancester_time: 0,
+                    file_creation_time: 0,
+                },
+                column_family_name: cf_name,
+                level: level as u32,
+            };
+
+            ret.push(meta);
+        }
+        ll::rocks_livefiles_destroy(livefiles);
+        ret
+    }
+}
+
 impl Deref for LiveFileMetaData {
     type Target = SstFileMetaData;
 
@@ -111,3 +291,117 @@ impl fmt::Debug for LiveFileMetaData {
             .finish()
     }
 }
+
+/// SST files in one age range of a `DataAgeHistogram`.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct DataAgeBucket {
+    /// Files in this bucket are at most this old, and older than the previous bucket.
+    /// `None` for the last bucket.
+    pub max_age: Option<Duration>,
+    /// The number of files.
+    pub num_files: usize,
+    /// The total size of the files in bytes.
+    pub size: u64,
+    /// The total number of entries in the files, deletions included.
+    pub num_entries: u64,
+}
+
+impl DataAgeBucket {
+    fn add(&mut self, file: &SstFileMetaData) {
+        self.num_files += 1;
+        self.size += file.size;
+        self.num_entries += file.num_entries;
+    }
+}
+
+/// SST files bucketed by the age of their oldest data, see `SstFileMetaData::data_age()`.
+///
+/// With TTL or FIFO compaction, data older than the TTL should be dropped by compactions,
+/// i.e. the buckets past the TTL should be empty, apart from files still waiting to be compacted.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct DataAgeHistogram {
+    /// One bucket per bound, plus one for data older than the last bound.
+    pub buckets: Vec<DataAgeBucket>,
+    /// Files of unknown age, i.e. written by RocksDB versions that did not record it.
+    pub unknown: DataAgeBucket,
+}
+
+impl DataAgeHistogram {
+    /// Bucket `files` by their data age at `now`, `bounds` are the upper bounds of buckets,
+    /// in ascending order.
+    pub fn new<'a, I>(files: I, now: SystemTime, bounds: &[Duration]) -> DataAgeHistogram
+    where
+        I: IntoIterator<Item = &'a SstFileMetaData>,
+    {
+        let mut buckets: Vec<_> = bounds
+            .iter()
+            .map(|&bound| Some(bound))
+            .chain(Some(None))
+            .map(|max_age| DataAgeBucket {
+                max_age,
+                ..Default::default()
+            })
+            .collect();
+        let mut unknown = DataAgeBucket::default();
+        for file in files {
+            match file.data_age(now) {
+                Some(age) => {
+                    let idx = bounds.iter().position(|&bound| age <= bound).unwrap_or(bounds.len());
+                    buckets[idx].add(file);
+                }
+                None => unknown.add(file),
+            }
+        }
+        DataAgeHistogram { buckets, unknown }
+    }
+
+    /// Total size in bytes of files older than `age`, counting only whole buckets,
+    /// i.e. `age` should be one of the bounds.
+    pub fn size_older_than(&self, age: Duration) -> u64 {
+        self.buckets
+            .iter()
+            .filter(|bucket| match bucket.max_age {
+                Some(max_age) => max_age > age,
+                None => true,
+            })
+            .map(|bucket| bucket.size)
+            .sum()
+    }
+}
+
+/// SST files of a column family exported by `Checkpoint::export_column_family()`, to be
+/// imported by `DBRef::create_column_family_with_import()`.
+#[derive(Debug)]
+pub struct ExportImportFilesMetaData {
+    /// The comparator of the exported column family, which the importing column family must use.
+    pub db_comparator_name: String,
+    /// The exported files, `db_path` being the export directory.
+    pub files: Vec<LiveFileMetaData>,
+}
+
+impl ExportImportFilesMetaData {
+    /// The files as a C++ list, to be destroyed by the caller.
+    pub(crate) fn files_to_ll(&self) -> *mut ll::rocks_livefiles_t {
+        unsafe {
+            let files = ll::rocks_livefiles_create();
+            for file in &self.files {
+                ll::rocks_livefiles_push(
+                    files,
+                    file.name.as_ptr() as *const _,
+                    file.name.len(),
+                    file.db_path.as_ptr() as *const _,
+                    file.db_path.len(),
+                    file.level as i32,
+                    file.smallest_seqno.0,
+                    file.largest_seqno.0,
+                    file.smallestkey.as_ptr() as *const _,
+                    file.smallestkey.len(),
+                    file.largestkey.as_ptr() as *const _,
+                    file.largestkey.len(),
+                    file.size as usize,
+                );
+            }
+            files
+        }
+    }
+}
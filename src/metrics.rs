@@ -0,0 +1,237 @@
+//! Export statistics and properties via the `metrics` crate facade.
+//!
+//! Requires the `metrics` feature. Metric names are RocksDB names with `.` and `-` replaced by `_`:
+//!
+//! - tickers are counters, e.g. `rocksdb_block_cache_miss`
+//! - histograms are gauges with a `quantile` label(`0.5`, `0.95`, `0.99`, `1`), plus
+//!   `<name>_count` and `<name>_sum` counters, i.e. a Prometheus summary
+//! - int properties are gauges with a `cf` label of the column family name
+
+use std::fmt;
+use std::sync::{Arc, Condvar, Mutex};
+use std::thread::{self, JoinHandle};
+use std::time::Duration;
+
+use crate::db::{ColumnFamily, DB};
+use crate::properties;
+use crate::statistics::Statistics;
+
+/// Int properties sampled by default.
+pub const DEFAULT_INT_PROPERTIES: &[&str] = &[
+    properties::NUM_IMMUTABLE_MEM_TABLE,
+    properties::MEM_TABLE_FLUSH_PENDING,
+    properties::NUM_RUNNING_FLUSHES,
+    properties::COMPACTION_PENDING,
+    properties::NUM_RUNNING_COMPACTIONS,
+    properties::BACKGROUND_ERRORS,
+    properties::CUR_SIZE_ALL_MEM_TABLES,
+    properties::ESTIMATE_NUM_KEYS,
+    properties::ESTIMATE_TABLE_READERS_MEM,
+    properties::NUM_SNAPSHOTS,
+    properties::NUM_LIVE_VERSIONS,
+    properties::ESTIMATE_LIVE_DATA_SIZE,
+    properties::TOTAL_SST_FILES_SIZE,
+    properties::LIVE_SST_FILES_SIZE,
+    properties::ESTIMATE_PENDING_COMPACTION_BYTES,
+    properties::ACTUAL_DELAYED_WRITE_RATE,
+    properties::IS_WRITE_STOPPED,
+    properties::BLOCK_CACHE_USAGE,
+    properties::BLOCK_CACHE_PINNED_USAGE,
+];
+
+/// Samples statistics and properties of a DB, and emits them to the installed `metrics` recorder.
+#[derive(Clone)]
+pub struct Recorder {
+    statistics: Option<Statistics>,
+    column_families: Vec<ColumnFamily>,
+    int_properties: Vec<String>,
+}
+
+impl fmt::Debug for Recorder {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.debug_struct("Recorder")
+            .field("statistics", &self.statistics.is_some())
+            .field("column_families", &self.column_families)
+            .field("int_properties", &self.int_properties)
+            .finish()
+    }
+}
+
+impl Recorder {
+    /// A recorder of all opened column families of `db`, with `DEFAULT_INT_PROPERTIES`.
+    pub fn new(db: &DB) -> Recorder {
+        Recorder {
+            statistics: None,
+            column_families: db.column_families(),
+            int_properties: DEFAULT_INT_PROPERTIES.iter().map(|s| s.to_string()).collect(),
+        }
+    }
+
+    /// Statistics to sample tickers and histograms from, usually the one set by `DBOptions::statistics()`.
+    pub fn statistics(mut self, val: Statistics) -> Self {
+        self.statistics = Some(val);
+        self
+    }
+
+    /// Column families to sample int properties of.
+    pub fn column_families(mut self, val: Vec<ColumnFamily>) -> Self {
+        self.column_families = val;
+        self
+    }
+
+    /// Int properties to sample, see `properties`.
+    pub fn int_properties(mut self, val: &[&str]) -> Self {
+        self.int_properties = val.iter().map(|s| s.to_string()).collect();
+        self
+    }
+
+    /// Sample once and emit.
+    pub fn sample(&self) {
+        if let Some(ref stat) = self.statistics {
+            for (name, count) in stat.to_map() {
+                ::metrics::counter!(metric_name(&name)).absolute(count);
+            }
+            for (name, data) in stat.to_histogram_map() {
+                let name = metric_name(&name);
+                for &(quantile, value) in &[
+                    ("0.5", data.median),
+                    ("0.95", data.percentile95),
+                    ("0.99", data.percentile99),
+                    ("1", data.max),
+                ] {
+                    ::metrics::gauge!(name.clone(), "quantile" => quantile).set(value);
+                }
+                ::metrics::counter!(format!("{}_count", name)).absolute(data.count);
+                ::metrics::counter!(format!("{}_sum", name)).absolute(data.sum);
+            }
+        }
+        for cf in &self.column_families {
+            for prop in &self.int_properties {
+                if let Some(value) = cf.get_int_property(prop) {
+                    ::metrics::gauge!(metric_name(prop), "cf" => cf.name().to_owned()).set(value as f64);
+                }
+            }
+        }
+    }
+
+    /// Sample every `interval` in a background thread, until the returned handle is dropped.
+    pub fn spawn(self, interval: Duration) -> RecorderHandle {
+        let stopped = Arc::new((Mutex::new(false), Condvar::new()));
+        let stop = stopped.clone();
+        let thread = thread::Builder::new()
+            .name("rocks-metrics".into())
+            .spawn(move || {
+                let (lock, cvar) = &*stop;
+                loop {
+                    self.sample();
+                    let stopped = lock.lock().unwrap();
+                    let (stopped, _) = cvar.wait_timeout_while(stopped, interval, |s| !*s).unwrap();
+                    if *stopped {
+                        break;
+                    }
+                }
+            })
+            .expect("spawn metrics thread");
+        RecorderHandle {
+            stopped,
+            thread: Some(thread),
+        }
+    }
+}
+
+/// Background sampling of a `Recorder`, returned by `Recorder::spawn()`.
+///
+/// Dropping the handle stops the thread.
+#[derive(Debug)]
+pub struct RecorderHandle {
+    stopped: Arc<(Mutex<bool>, Condvar)>,
+    thread: Option<JoinHandle<()>>,
+}
+
+impl RecorderHandle {
+    /// Stop the thread, waiting for an in-progress sample to finish.
+    pub fn stop(mut self) {
+        self.stop_and_join();
+    }
+
+    fn stop_and_join(&mut self) {
+        let (lock, cvar) = &*self.stopped;
+        *lock.lock().unwrap() = true;
+        cvar.notify_all();
+        if let Some(thread) = self.thread.take() {
+            let _ = thread.join();
+        }
+    }
+}
+
+impl Drop for RecorderHandle {
+    fn drop(&mut self) {
+        self.stop_and_join();
+    }
+}
+
+fn metric_name(name: &str) -> String {
+    name.replace(['.', '-'], "_")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::rocksdb::*;
+    use ::metrics::{Counter, Gauge, Histogram, Key, KeyName, Metadata, SharedString, Unit};
+
+    #[derive(Default)]
+    struct KeyRecorder {
+        keys: Mutex<Vec<String>>,
+    }
+
+    impl ::metrics::Recorder for KeyRecorder {
+        fn describe_counter(&self, _: KeyName, _: Option<Unit>, _: SharedString) {}
+        fn describe_gauge(&self, _: KeyName, _: Option<Unit>, _: SharedString) {}
+        fn describe_histogram(&self, _: KeyName, _: Option<Unit>, _: SharedString) {}
+
+        fn register_counter(&self, key: &Key, _: &Metadata<'_>) -> Counter {
+            self.keys.lock().unwrap().push(key.name().to_owned());
+            Counter::noop()
+        }
+
+        fn register_gauge(&self, key: &Key, _: &Metadata<'_>) -> Gauge {
+            let labels: Vec<_> = key.labels().map(|l| format!("{}={}", l.key(), l.value())).collect();
+            self.keys.lock().unwrap().push(format!("{}{{{}}}", key.name(), labels.join(",")));
+            Gauge::noop()
+        }
+
+        fn register_histogram(&self, key: &Key, _: &Metadata<'_>) -> Histogram {
+            Histogram::noop()
+        }
+    }
+
+    #[test]
+    fn metrics_recorder() {
+        let tmp_dir = ::tempdir::TempDir::new_in(".", "rocks").unwrap();
+        let stat = Statistics::new();
+        let db = DB::open(
+            Options::default().map_db_options(|db| db.create_if_missing(true).statistics(Some(stat.clone()))),
+            &tmp_dir,
+        )
+        .unwrap();
+        let _cf = db.create_column_family(&ColumnFamilyOptions::default(), "metrics").unwrap();
+        assert!(db.put(WriteOptions::default_instance(), b"k", b"v").is_ok());
+
+        let recorder = Recorder::new(&db).statistics(stat);
+        let keys = KeyRecorder::default();
+        ::metrics::with_local_recorder(&keys, || recorder.sample());
+
+        let keys = keys.keys.into_inner().unwrap();
+        assert!(keys.iter().any(|k| k == "rocksdb_number_keys_written"));
+        assert!(keys.iter().any(|k| k == "rocksdb_db_write_micros{quantile=0.99}"));
+        assert!(keys.iter().any(|k| k == "rocksdb_db_write_micros_count"));
+        assert!(keys.iter().any(|k| k == "rocksdb_estimate_num_keys{cf=default}"));
+        assert!(keys.iter().any(|k| k == "rocksdb_estimate_num_keys{cf=metrics}"));
+
+        // no recorder installed, samples are discarded
+        let handle = recorder.spawn(Duration::from_millis(10));
+        thread::sleep(Duration::from_millis(50));
+        handle.stop();
+    }
+}
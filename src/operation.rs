@@ -0,0 +1,266 @@
+//! Registry of long-running operations started through a `DB` handle.
+//!
+//! Manual compactions, external file ingestion and column family imports (bulk
+//! loads), checkpoints and column family exports (backups) can take minutes. Every
+//! such call is recorded for its whole duration, so that admin endpoints can
+//! list what is in flight via `DB::operations()` and cancel it where RocksDB
+//! supports cancellation.
+//!
+//! RocksDB reports no progress for any of these calls, the number of input files
+//! is the only size hint, when known up front.
+
+use std::collections::BTreeMap;
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::Mutex;
+use std::time::{Duration, Instant, SystemTime};
+
+/// Identifier of an operation, unique within a DB handle.
+pub type OperationId = u64;
+
+/// Kind of a long-running operation.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum OperationKind {
+    /// `compact_range()`, cancellable.
+    CompactRange,
+    /// `compact_files()`, runs on the calling thread.
+    CompactFiles,
+    /// `ingest_external_file()`.
+    IngestExternalFile,
+    /// `create_column_family_with_import()`.
+    ImportColumnFamily,
+    /// `Checkpoint::create_checkpoint()`.
+    CreateCheckpoint,
+    /// `Checkpoint::export_column_family()`.
+    ExportColumnFamily,
+}
+
+impl OperationKind {
+    /// Whether `DB::cancel_operation()` can stop an operation of this kind.
+    pub fn is_cancellable(self) -> bool {
+        self == OperationKind::CompactRange
+    }
+}
+
+/// Snapshot of a running operation.
+#[derive(Debug, Clone)]
+pub struct OperationInfo {
+    pub id: OperationId,
+    pub kind: OperationKind,
+    /// Target column family, `None` for the default column family.
+    pub column_family: Option<String>,
+    /// Wall clock time when the operation started.
+    pub start_time: SystemTime,
+    /// Time since the operation started.
+    pub elapsed: Duration,
+    /// Number of input files, when known up front.
+    pub num_files: Option<usize>,
+    /// Set once `DB::cancel_operation()` was called on it.
+    pub cancel_requested: bool,
+}
+
+#[derive(Debug)]
+struct Entry {
+    kind: OperationKind,
+    column_family: Option<String>,
+    start_time: SystemTime,
+    started: Instant,
+    num_files: Option<usize>,
+    cancel_requested: bool,
+}
+
+#[derive(Debug, Default)]
+pub(crate) struct OperationRegistry {
+    next_id: AtomicU64,
+    running: Mutex<BTreeMap<OperationId, Entry>>,
+}
+
+impl OperationRegistry {
+    pub fn start(&self, kind: OperationKind, column_family: Option<String>, num_files: Option<usize>) -> OperationId {
+        let id = self.next_id.fetch_add(1, Ordering::Relaxed) + 1;
+        let entry = Entry {
+            kind,
+            column_family,
+            start_time: SystemTime::now(),
+            started: Instant::now(),
+            num_files,
+            cancel_requested: false,
+        };
+        self.running.lock().unwrap().insert(id, entry);
+        id
+    }
+
+    /// Unregister an operation. `resume` is called if it was the last cancelled one still
+    /// running, with the registry locked so that it can not race with `request_cancel()`.
+    pub fn finish<F: FnOnce()>(&self, id: OperationId, resume: F) {
+        let mut running = self.running.lock().unwrap();
+        if let Some(entry) = running.remove(&id) {
+            if entry.cancel_requested && running.values().all(|e| !e.cancel_requested) {
+                resume();
+            }
+        }
+    }
+
+    pub fn list(&self) -> Vec<OperationInfo> {
+        self.running
+            .lock()
+            .unwrap()
+            .iter()
+            .map(|(&id, entry)| OperationInfo {
+                id,
+                kind: entry.kind,
+                column_family: entry.column_family.clone(),
+                start_time: entry.start_time,
+                elapsed: entry.started.elapsed(),
+                num_files: entry.num_files,
+                cancel_requested: entry.cancel_requested,
+            })
+            .collect()
+    }
+
+    /// Mark an operation as cancelled, returns its kind if it is running and cancellable.
+    ///
+    /// `pause` is called with the registry locked, so that it is ordered with the `resume`
+    /// of `finish()`.
+    pub fn request_cancel<F: FnOnce()>(&self, id: OperationId, pause: F) -> Option<OperationKind> {
+        let mut running = self.running.lock().unwrap();
+        let entry = running.get_mut(&id)?;
+        if !entry.kind.is_cancellable() {
+            return None;
+        }
+        entry.cancel_requested = true;
+        pause();
+        Some(entry.kind)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::cell::Cell;
+    use std::panic;
+    use std::sync::atomic::AtomicBool;
+    use std::sync::Arc;
+    use std::thread;
+
+    use lazy_static::lazy_static;
+
+    use super::super::compaction_filter::{CompactionFilter, Decision, ValueType};
+    use super::super::rocksdb::*;
+    use super::*;
+
+    #[test]
+    fn operation_registry() {
+        let registry = OperationRegistry::default();
+        assert!(registry.list().is_empty());
+
+        let paused = Cell::new(0);
+        let resumed = Cell::new(0);
+        let compaction = registry.start(OperationKind::CompactRange, None, None);
+        let ingest = registry.start(OperationKind::IngestExternalFile, Some("hot".into()), Some(3));
+        let ops = registry.list();
+        assert_eq!(ops.len(), 2);
+        assert_eq!(ops[1].id, ingest);
+        assert_eq!(ops[1].column_family.as_ref().map(|s| &s[..]), Some("hot"));
+        assert_eq!(ops[1].num_files, Some(3));
+
+        assert!(registry.request_cancel(ingest, || paused.set(paused.get() + 1)).is_none());
+        assert_eq!(paused.get(), 0);
+        assert_eq!(
+            registry.request_cancel(compaction, || paused.set(paused.get() + 1)),
+            Some(OperationKind::CompactRange)
+        );
+        assert_eq!(paused.get(), 1);
+        registry.finish(ingest, || resumed.set(resumed.get() + 1));
+        assert_eq!(resumed.get(), 0);
+
+        let ops = registry.list();
+        assert_eq!(ops.len(), 1);
+        assert!(ops[0].cancel_requested);
+        registry.finish(compaction, || resumed.set(resumed.get() + 1));
+        assert_eq!(resumed.get(), 1);
+        assert!(registry.list().is_empty());
+    }
+
+    static FILTER_STARTED: AtomicBool = AtomicBool::new(false);
+    static FILTER_SLOW: AtomicBool = AtomicBool::new(true);
+
+    // slows a compaction down until the test cancelled it
+    struct SlowFilter;
+
+    impl CompactionFilter for SlowFilter {
+        fn filter(&mut self, _level: i32, _key: &[u8], _value_type: ValueType, _value: &[u8]) -> Decision {
+            FILTER_STARTED.store(true, Ordering::SeqCst);
+            if FILTER_SLOW.load(Ordering::SeqCst) {
+                thread::sleep(Duration::from_millis(5));
+            }
+            Decision::Keep
+        }
+    }
+
+    lazy_static! {
+        static ref SLOW_FILTER: SlowFilter = SlowFilter;
+    }
+
+    #[test]
+    fn cancel_compact_range() {
+        let tmp_dir = ::tempdir::TempDir::new_in(".", "rocks").unwrap();
+        let db = DB::open(
+            Options::default().map_db_options(|db| db.create_if_missing(true)).map_cf_options(|cf| {
+                cf.disable_auto_compactions(true)
+                    .compaction_filter(&*SLOW_FILTER)
+            }),
+            &tmp_dir,
+        )
+        .unwrap();
+        for i in 0..1000 {
+            let key = format!("k{:04}", i);
+            assert!(db.put(WriteOptions::default_instance(), key.as_bytes(), b"v").is_ok());
+            if i % 100 == 99 {
+                assert!(db.flush(&FlushOptions::default().wait(true)).is_ok());
+            }
+        }
+        assert!(db.operations().is_empty());
+        assert!(!db.cancel_operation(42));
+
+        let db = Arc::new(db);
+        let compaction = {
+            let db = db.clone();
+            thread::spawn(move || db.compact_range(&Default::default(), ..))
+        };
+        while !FILTER_STARTED.load(Ordering::SeqCst) {
+            thread::sleep(Duration::from_millis(1));
+        }
+        let ops = db.operations();
+        assert_eq!(ops.len(), 1);
+        assert_eq!(ops[0].kind, OperationKind::CompactRange);
+        assert!(db.cancel_operation(ops[0].id));
+        assert!(db.operations()[0].cancel_requested);
+
+        let err = compaction.join().unwrap().unwrap_err();
+        assert!(err.is_manual_compaction_paused(), "{:?}", err);
+        assert!(db.operations().is_empty());
+
+        // manual compaction is enabled again once the cancelled call returned
+        FILTER_SLOW.store(false, Ordering::SeqCst);
+        assert!(db.compact_range(&Default::default(), ..).is_ok());
+        assert!(db.operations().is_empty());
+    }
+
+    #[test]
+    fn panicking_operation_is_unregistered() {
+        let tmp_dir = ::tempdir::TempDir::new_in(".", "rocks").unwrap();
+        let db = DB::open(
+            Options::default().map_db_options(|db| db.create_if_missing(true)),
+            &tmp_dir,
+        )
+        .unwrap();
+
+        let ret = panic::catch_unwind(panic::AssertUnwindSafe(|| {
+            db.track_default(OperationKind::CreateCheckpoint, None, || {
+                assert_eq!(db.operations().len(), 1);
+                panic!("checkpoint failed");
+            })
+        }));
+        assert!(ret.is_err());
+        assert!(db.operations().is_empty());
+    }
+}
@@ -9,6 +9,8 @@ use std::path::{Path, PathBuf};
 use std::ptr;
 use std::slice;
 use std::str;
+use std::sync::{Arc, Mutex};
+use std::time::Duration;
 use std::u64;
 
 use rocks_sys as ll;
@@ -16,7 +18,7 @@ use rocks_sys as ll;
 use crate::advanced_options::{CompactionOptionsFIFO, CompactionPri, CompactionStyle, CompressionOptions};
 use crate::cache::Cache;
 use crate::compaction_filter::{CompactionFilter, CompactionFilterFactory};
-use crate::comparator::Comparator;
+use crate::comparator::{Comparator, ComparatorHandle};
 use crate::env::{Env, InfoLogLevel, Logger};
 use crate::listener::EventListener;
 use crate::merge_operator::{AssociativeMergeOperator, MergeOperator};
@@ -30,6 +32,7 @@ use crate::table_properties::TablePropertiesCollectorFactory;
 use crate::types::SequenceNumber;
 use crate::universal_compaction::CompactionOptionsUniversal;
 use crate::write_buffer_manager::WriteBufferManager;
+use crate::{Error, Result};
 
 use crate::to_raw::{FromRaw, ToRaw};
 
@@ -81,6 +84,7 @@ pub enum CompressionType {
 
 /// Recovery mode to control the consistency while replaying WAL
 #[repr(C)]
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
 pub enum WALRecoveryMode {
     /// Original levelDB recovery
     /// We tolerate incomplete record in trailing data on all logs
@@ -147,6 +151,8 @@ impl<T: Into<PathBuf>> From<T> for DbPath {
 /// Options for a column family
 pub struct ColumnFamilyOptions {
     raw: *mut ll::rocks_cfoptions_t,
+    // keeps the comparator alive, RocksDB only holds a raw pointer to it
+    comparator: Option<Arc<ComparatorHandle>>,
 }
 
 impl ToRaw<ll::rocks_cfoptions_t> for ColumnFamilyOptions {
@@ -157,7 +163,7 @@ impl ToRaw<ll::rocks_cfoptions_t> for ColumnFamilyOptions {
 
 impl FromRaw<ll::rocks_cfoptions_t> for ColumnFamilyOptions {
     unsafe fn from_ll(raw: *mut ll::rocks_cfoptions_t) -> Self {
-        ColumnFamilyOptions { raw }
+        ColumnFamilyOptions { raw, comparator: None }
     }
 }
 
@@ -165,6 +171,7 @@ impl Default for ColumnFamilyOptions {
     fn default() -> Self {
         ColumnFamilyOptions {
             raw: unsafe { ll::rocks_cfoptions_create() },
+            comparator: None,
         }
     }
 }
@@ -180,37 +187,70 @@ impl Drop for ColumnFamilyOptions {
 impl fmt::Debug for ColumnFamilyOptions {
     fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
         write!(f, "ColumnFamilyOptions {{ ")?;
-        unsafe {
-            let cxx_string = ll::rocks_get_string_from_cfoptions(self.raw);
-            let len = ll::cxx_string_size(cxx_string);
-            let base = ll::cxx_string_data(cxx_string);
-            if !cxx_string.is_null() {
-                let str_rep = str::from_utf8_unchecked(slice::from_raw_parts(base as *const u8, len));
-                f.write_str(str_rep)?;
-                ll::cxx_string_destroy(cxx_string);
-            } else {
-                write!(f, "error while converting to String")?;
-            }
+        match self.to_options_string() {
+            Some(s) => f.write_str(&s)?,
+            None => write!(f, "error while converting to String")?,
         }
         write!(f, "}}")
     }
 }
 
 impl ColumnFamilyOptions {
+    pub(crate) fn comparator_handle(&self) -> Option<Arc<ComparatorHandle>> {
+        self.comparator.clone()
+    }
+
     /// Create ColumnFamilyOptions with default values for all fields
     pub fn new() -> ColumnFamilyOptions {
         ColumnFamilyOptions {
             raw: unsafe { ll::rocks_cfoptions_create() },
+            comparator: None,
         }
     }
 
     unsafe fn from_ll(raw: *mut ll::rocks_cfoptions_t) -> ColumnFamilyOptions {
-        ColumnFamilyOptions { raw: raw }
+        ColumnFamilyOptions { raw, comparator: None }
     }
 
     pub fn from_options(opt: &Options) -> ColumnFamilyOptions {
         ColumnFamilyOptions {
             raw: unsafe { ll::rocks_cfoptions_create_from_options(opt.raw()) },
+            comparator: opt.comparator.clone(),
+        }
+    }
+
+    /// Options from an options string, e.g. `"write_buffer_size=1024;compaction_options_universal={size_ratio=2}"`,
+    /// options not in the string are copied from `base`.
+    pub fn from_options_string(base: &ColumnFamilyOptions, opts_str: &str) -> Result<ColumnFamilyOptions> {
+        let mut status = ptr::null_mut();
+        unsafe {
+            let raw = ll::rocks_get_cfoptions_from_string(
+                base.raw,
+                opts_str.as_ptr() as *const _,
+                opts_str.len(),
+                &mut status,
+            );
+            Error::from_ll(status).map(|_| ColumnFamilyOptions {
+                raw,
+                comparator: base.comparator.clone(),
+            })
+        }
+    }
+
+    /// Serialize to an options string, which can be parsed by `from_options_string()`.
+    ///
+    /// Pointer typed options, like comparator or table factory, are not serialized.
+    pub fn to_options_string(&self) -> Option<String> {
+        unsafe {
+            let cxx_string = ll::rocks_get_string_from_cfoptions(self.raw);
+            if cxx_string.is_null() {
+                return None;
+            }
+            let len = ll::cxx_string_size(cxx_string);
+            let base = ll::cxx_string_data(cxx_string);
+            let s = String::from_utf8_lossy(slice::from_raw_parts(base as *const u8, len)).into_owned();
+            ll::cxx_string_destroy(cxx_string);
+            Some(s)
         }
     }
 
@@ -244,7 +284,7 @@ impl ColumnFamilyOptions {
     /// Factor for big data sets, but increases Space Amplification. You can learn
     /// more about the different styles here:
     /// https://github.com/facebook/rocksdb/wiki/Rocksdb-Architecture-Guide
-    /// Make sure to also call IncreaseParallelism(), which will provide the
+    /// Make sure to also call `DBOptions::increase_parallelism()`, which will provide the
     /// biggest performance gains.
     ///
     /// Note: we might use more memory than memtable_memory_budget during high
@@ -277,20 +317,24 @@ impl ColumnFamilyOptions {
     /// REQUIRES: The client must ensure that the comparator supplied
     /// here has the same name and orders keys *exactly* the same as the
     /// comparator provided to previous open calls on the same DB.
-    pub fn comparator<T: Comparator>(self, val: &'static T) -> Self {
+    ///
+    /// The comparator is released once these options, and all DBs and column
+    /// families opened with them, are dropped.
+    pub fn comparator(mut self, val: Arc<dyn Comparator + Send + Sync>) -> Self {
+        let handle = Arc::new(ComparatorHandle::new(val));
         unsafe {
-            // Box<&dyn Comparator>
-            let raw_ptr = Box::into_raw(Box::new(val as &dyn Comparator));
-            ll::rocks_cfoptions_set_comparator_by_trait(self.raw, raw_ptr as *mut _);
+            ll::rocks_cfoptions_set_comparator(self.raw, handle.raw());
         }
+        self.comparator = Some(handle);
         self
     }
 
     /// Use bitwise comparator and set if reversed.
-    pub fn bitwise_comparator_reversed(self, val: bool) -> Self {
+    pub fn bitwise_comparator_reversed(mut self, val: bool) -> Self {
         unsafe {
             ll::rocks_cfoptions_set_bitwise_comparator(self.raw, val as u8);
         }
+        self.comparator = None;
         self
     }
 
@@ -467,9 +511,11 @@ impl ColumnFamilyOptions {
     /// 4) prefix(prefix(key)) == prefix(key)
     ///
     /// Default: nullptr
-    // FIXME: split other prefix extractor variants
+    ///
+    /// See `slice_transform::from_fn()` for a prefix extractor of closures.
     pub fn prefix_extractor(self, val: Box<dyn SliceTransform + Sync>) -> Self {
         unsafe {
+            // Box<Box<dyn SliceTransform>>
             let raw_ptr = Box::into_raw(Box::new(val));
             ll::rocks_cfoptions_set_prefix_extractor_by_trait(self.raw, raw_ptr as *mut _);
         }
@@ -644,6 +690,40 @@ impl ColumnFamilyOptions {
         self
     }
 
+    /// The total maximum size(bytes) of write buffers to maintain in memory
+    /// including copies of buffers that have already been flushed. This parameter
+    /// only affects trimming of flushed buffers and does not affect flushing.
+    /// This controls the maximum amount of write history that will be available
+    /// in memory for conflict checking when Transactions are used.
+    ///
+    /// When using an OptimisticTransactionDB:
+    /// If this value is too low, some transactions may fail at commit time due
+    /// to not being able to determine whether there were any write conflicts.
+    ///
+    /// When using a TransactionDB:
+    /// If Transaction::SetSnapshot is used, TransactionDB will read either
+    /// in-memory write buffers or SST files to do write-conflict checking.
+    /// Increasing this value can reduce the number of reads to SST files
+    /// done for conflict detection.
+    ///
+    /// Setting this value to 0 will cause write buffers to be freed immediately
+    /// after they are flushed. If this value is set to -1,
+    /// 'max_write_buffer_number * write_buffer_size' will be used.
+    ///
+    /// Takes precedence over `max_write_buffer_number_to_maintain` when both are set.
+    ///
+    /// Default:
+    ///
+    /// If using a TransactionDB/OptimisticTransactionDB, the default value will
+    /// be set to the value of 'max_write_buffer_number * write_buffer_size'
+    /// if it is not explicitly set by the user.  Otherwise, the default is 0.
+    pub fn max_write_buffer_size_to_maintain(self, val: i64) -> Self {
+        unsafe {
+            ll::rocks_cfoptions_set_max_write_buffer_size_to_maintain(self.raw, val);
+        }
+        self
+    }
+
     /// Allows thread-safe inplace updates. If this is true, there is no way to
     /// achieve point-in-time consistency using snapshot or iterator (assuming
     /// concurrent updates). Hence iterator and multi-get will return results
@@ -745,6 +825,20 @@ impl ColumnFamilyOptions {
         self
     }
 
+    /// Enable whole key bloom filter in memtable. Note this will only take effect
+    /// if memtable_prefix_bloom_size_ratio is not 0. Enabling whole key filtering
+    /// can potentially reduce CPU usage for point-look-ups.
+    ///
+    /// Default: false (disable)
+    ///
+    /// Dynamically changeable through `SetOptions()` API
+    pub fn memtable_whole_key_filtering(self, val: bool) -> Self {
+        unsafe {
+            ll::rocks_cfoptions_set_memtable_whole_key_filtering(self.raw, val as u8);
+        }
+        self
+    }
+
     /// Page size for huge page for the arena used by the memtable. If <=0, it
     /// won't allocate from huge page but from malloc.
     /// Users are responsible to reserve huge pages for it to be allocated. For
@@ -1130,6 +1224,65 @@ impl ColumnFamilyOptions {
         self
     }
 
+    /// Non-bottom-level files older than TTL will go through the compaction
+    /// process. This needs max_open_files to be set to -1.
+    ///
+    /// For FIFO compaction style, files older than TTL (in seconds) are
+    /// deleted, which is how time-series workloads expire old data by age.
+    /// This replaces the deprecated `CompactionOptionsFIFO::ttl`.
+    ///
+    /// Enabled only for level compaction and FIFO compaction.
+    ///
+    /// Default: 0 (disabled)
+    ///
+    /// Dynamically changeable through SetOptions() API
+    pub fn ttl(self, secs: u64) -> Self {
+        unsafe {
+            ll::rocks_cfoptions_set_ttl(self.raw, secs);
+        }
+        self
+    }
+
+    /// Like `DBOptions::db_paths()`, the paths where SST files of this column
+    /// family are put, with their target sizes, newer data in earlier paths.
+    ///
+    /// This is how SST files are tiered across devices, e.g. recent data on
+    /// flash, older levels on hard drives. Which path a file ended up in is
+    /// `SstFileMetaData::db_path`.
+    ///
+    /// If left empty, `db_paths` is used.
+    ///
+    /// Default: empty
+    pub fn cf_paths<P: Into<DbPath>, T: IntoIterator<Item = P>>(self, val: T) -> Self {
+        let paths = val.into_iter().map(|p| p.into()).collect::<Vec<_>>();
+        // must hold PathBuf.to_str()
+        let path_strs = paths
+            .iter()
+            .map(|s| (s.path.to_str().unwrap(), s.target_size))
+            .collect::<Vec<_>>();
+
+        let num_paths = paths.len();
+        let mut cpaths = Vec::with_capacity(num_paths);
+        let mut cpath_lens = Vec::with_capacity(num_paths);
+        let mut sizes = Vec::with_capacity(num_paths);
+        for path in path_strs {
+            cpaths.push(path.0.as_ptr() as _);
+            cpath_lens.push(path.0.len());
+            sizes.push(path.1);
+        }
+
+        unsafe {
+            ll::rocks_cfoptions_set_cf_paths(
+                self.raw,
+                cpaths.as_ptr(),
+                cpath_lens.as_ptr(),
+                sizes.as_ptr(),
+                num_paths as c_int,
+            );
+        }
+        self
+    }
+
     /// An iteration->Next() sequentially skips over keys with the same
     /// user-key unless this option is set. This number specifies the number
     /// of keys (with the same userkey) that will be sequentially
@@ -1406,6 +1559,21 @@ impl DBOptions {
         }
     }
 
+    /// Serialize to an options string, like `ColumnFamilyOptions::to_options_string()`.
+    pub fn to_options_string(&self) -> Option<String> {
+        unsafe {
+            let cxx_string = ll::rocks_get_string_from_dboptions(self.raw);
+            if cxx_string.is_null() {
+                return None;
+            }
+            let len = ll::cxx_string_size(cxx_string);
+            let base = ll::cxx_string_data(cxx_string);
+            let s = String::from_utf8_lossy(slice::from_raw_parts(base as *const u8, len)).into_owned();
+            ll::cxx_string_destroy(cxx_string);
+            Some(s)
+        }
+    }
+
     /// By default, RocksDB uses only one background thread for flush and
     /// compaction. Calling this function will set it up such that total of
     /// `total_threads` is used. Good value for `total_threads` is the number of
@@ -1420,6 +1588,16 @@ impl DBOptions {
         self
     }
 
+    /// Shorten `DB::Open()` of large DBs, which loads table properties of some SST files
+    /// for compaction statistics.
+    ///
+    /// Skips that, see `skip_stats_update_on_db_open()`. With `max_open_files(-1)`, all
+    /// table files are also opened during `DB::Open()`, by `max_file_opening_threads`
+    /// threads, this doubles them to 32.
+    pub fn optimize_for_fast_open(self) -> Self {
+        self.skip_stats_update_on_db_open(true).max_file_opening_threads(32)
+    }
+
     /// If true, the database will be created if it is missing.
     ///
     /// Default: false
@@ -1704,6 +1882,10 @@ impl DBOptions {
 
     /// Maximum number of concurrent background jobs (compactions and flushes).
     ///
+    /// A quarter of them, at least 1, are flushes, scheduled in the `Priority::High`
+    /// thread pool of the `Env`; the rest are compactions, scheduled in the
+    /// `Priority::Low` pool. Opening the DB grows both pools to fit if needed.
+    ///
     /// Default: 2
     pub fn max_background_jobs(self, val: i32) -> Self {
         unsafe {
@@ -1712,6 +1894,48 @@ impl DBOptions {
         self
     }
 
+    /// NOT SUPPORTED ANYMORE: RocksDB automatically decides this based on the
+    /// value of `max_background_jobs`. This option is ignored.
+    ///
+    /// Kept so that configs ported from older versions still parse.
+    pub fn base_background_compactions(self, val: i32) -> Self {
+        unsafe {
+            ll::rocks_dboptions_set_base_background_compactions(self.raw, val);
+        }
+        self
+    }
+
+    /// DEPRECATED: prefer `max_background_jobs`.
+    ///
+    /// Maximum number of concurrent background compaction jobs, submitted to
+    /// the `Priority::Low` thread pool of the `Env`. If either this or
+    /// `max_background_flushes` is not -1, both are used instead of
+    /// `max_background_jobs`, with -1 meaning 1 here.
+    ///
+    /// Default: -1
+    pub fn max_background_compactions(self, val: i32) -> Self {
+        unsafe {
+            ll::rocks_dboptions_set_max_background_compactions(self.raw, val);
+        }
+        self
+    }
+
+    /// DEPRECATED: prefer `max_background_jobs`.
+    ///
+    /// Maximum number of concurrent background memtable flush jobs, submitted to
+    /// the `Priority::High` thread pool of the `Env`, or, if that pool has no threads,
+    /// to the `Priority::Low` pool shared with compactions. If either this or
+    /// `max_background_compactions` is not -1, both are used instead of
+    /// `max_background_jobs`, with -1 meaning 1 here.
+    ///
+    /// Default: -1
+    pub fn max_background_flushes(self, val: i32) -> Self {
+        unsafe {
+            ll::rocks_dboptions_set_max_background_flushes(self.raw, val);
+        }
+        self
+    }
+
     /// This value represents the maximum number of threads that will
     /// concurrently perform a compaction job by breaking it into multiple,
     /// smaller ones that are run simultaneously.
@@ -1912,6 +2136,29 @@ impl DBOptions {
         self
     }
 
+    /// if not zero, dump rocksdb.stats to RocksDB every stats_persist_period_sec
+    ///
+    /// The stats are kept in memory, see `DBRef::get_stats_history()`.
+    ///
+    /// Default: 600
+    pub fn stats_persist_period_sec(self, val: u32) -> Self {
+        unsafe {
+            ll::rocks_dboptions_set_stats_persist_period_sec(self.raw, val);
+        }
+        self
+    }
+
+    /// if not zero, periodically take stats snapshots and store in memory, the
+    /// memory size for stats snapshots is capped at stats_history_buffer_size
+    ///
+    /// Default: 1MB
+    pub fn stats_history_buffer_size(self, val: usize) -> Self {
+        unsafe {
+            ll::rocks_dboptions_set_stats_history_buffer_size(self.raw, val);
+        }
+        self
+    }
+
     /// If set true, will hint the underlying file system that the file
     /// access pattern is random, when a sst file is opened.
     ///
@@ -2085,6 +2332,9 @@ impl DBOptions {
 
     /// A vector of EventListeners which call-back functions will be called
     /// when specific RocksDB event happens.
+    ///
+    /// The listener is owned by the options and every DB opened with them,
+    /// it is dropped when the last of them is dropped.
     pub fn add_listener<T: EventListener>(self, val: T) -> Self {
         unsafe {
             ll::rocks_dboptions_add_listener(
@@ -2095,6 +2345,39 @@ impl DBOptions {
         self
     }
 
+    /// Add a listener which can be registered on multiple DBs, and removed by
+    /// `remove_listener()`.
+    ///
+    /// Every registration holds a reference of the `Arc`, released when the DB is closed.
+    pub fn add_shared_listener<T: EventListener + 'static>(self, val: &Arc<Mutex<T>>) -> Self {
+        unsafe {
+            ll::rocks_dboptions_add_shared_listener(
+                self.raw,
+                Box::into_raw(Box::new(Box::new(val.clone()) as Box<dyn EventListener>)) as *mut _,
+                Arc::as_ptr(val) as *const _,
+            );
+        }
+        self
+    }
+
+    /// Remove all registrations of a shared listener added by `add_shared_listener()`.
+    ///
+    /// DBs already opened are not affected.
+    pub fn remove_listener<T: EventListener>(self, val: &Arc<Mutex<T>>) -> Self {
+        unsafe {
+            ll::rocks_dboptions_remove_listener(self.raw, Arc::as_ptr(val) as *const _);
+        }
+        self
+    }
+
+    /// Remove all listeners.
+    pub fn clear_listeners(self) -> Self {
+        unsafe {
+            ll::rocks_dboptions_clear_listeners(self.raw);
+        }
+        self
+    }
+
     /// If true, then the status of the threads involved in this DB will
     /// be tracked and available via GetThreadList() API.
     ///
@@ -2205,6 +2488,8 @@ impl DBOptions {
 
     /// if set to false then recovery will fail when a prepared
     /// transaction is encountered in the WAL
+    ///
+    /// Required by `Transaction::prepare()` of a `TransactionDB`.
     pub fn allow_2pc(self, val: bool) -> Self {
         unsafe {
             ll::rocks_dboptions_set_allow_2pc(self.raw, val as u8);
@@ -2319,6 +2604,42 @@ impl DBOptions {
         }
         self
     }
+
+    /// If true, RocksDB supports flushing multiple column families and committing
+    /// their results atomically to MANIFEST. Note that it is not
+    /// necessary to set atomic_flush to true if WAL is always enabled since WAL
+    /// allows the database to be restored to the last persistent state in WAL.
+    /// This option is useful when there are column families with writes NOT
+    /// protected by WAL.
+    /// For manual flush, application has to specify which column families to
+    /// flush atomically in `DB::flush_cfs`.
+    /// For auto-triggered flush, RocksDB atomically flushes ALL column families.
+    ///
+    /// Currently, any WAL-enabled writes after atomic flush may be replayed
+    /// independently if the process crashes later and tries to recover.
+    ///
+    /// Default: false
+    pub fn atomic_flush(self, val: bool) -> Self {
+        unsafe {
+            ll::rocks_dboptions_set_atomic_flush(self.raw, val as u8);
+        }
+        self
+    }
+
+    /// If true, working thread may avoid doing unnecessary and long-latency
+    /// operation (such as deleting obsolete files directly or deleting memtable)
+    /// and will instead schedule a background job to do it.
+    /// Use it if you're latency-sensitive.
+    /// If set to true, takes precedence over
+    /// `ReadOptions::background_purge_on_iterator_cleanup`.
+    ///
+    /// Default: false
+    pub fn avoid_unnecessary_blocking_io(self, val: bool) -> Self {
+        unsafe {
+            ll::rocks_dboptions_set_avoid_unnecessary_blocking_io(self.raw, val as u8);
+        }
+        self
+    }
 }
 
 /// Options to control the behavior of a database (passed to `DB::Open`)
@@ -2332,6 +2653,8 @@ impl DBOptions {
 /// ```
 pub struct Options {
     raw: *mut ll::rocks_options_t,
+    // see `ColumnFamilyOptions`
+    comparator: Option<Arc<ComparatorHandle>>,
 }
 
 unsafe impl Sync for Options {}
@@ -2346,6 +2669,7 @@ impl Default for Options {
     fn default() -> Self {
         Options {
             raw: unsafe { ll::rocks_options_create() },
+            comparator: None,
         }
     }
 }
@@ -2366,11 +2690,15 @@ impl ToRaw<ll::rocks_options_t> for Options {
 
 impl FromRaw<ll::rocks_options_t> for Options {
     unsafe fn from_ll(raw: *mut ll::rocks_options_t) -> Options {
-        Options { raw: raw }
+        Options { raw, comparator: None }
     }
 }
 
 impl Options {
+    pub(crate) fn comparator_handle(&self) -> Option<Arc<ComparatorHandle>> {
+        self.comparator.clone()
+    }
+
     /// default `Options` with `create_if_missing = true`
     #[inline]
     pub fn default_instance() -> &'static Options {
@@ -2382,6 +2710,7 @@ impl Options {
         let cfopt = cfopt.unwrap_or_default();
         Options {
             raw: unsafe { ll::rocks_options_create_from_db_cf_options(dbopt.raw(), cfopt.raw()) },
+            comparator: cfopt.comparator.clone(),
         }
     }
 
@@ -2399,26 +2728,16 @@ impl Options {
     pub fn map_db_options<F: FnOnce(DBOptions) -> DBOptions>(self, f: F) -> Self {
         let dbopt = unsafe { DBOptions::from_ll(ll::rocks_dboptions_create_from_options(self.raw)) };
         let new_dbopt = f(dbopt);
-        let old_cfopt = unsafe { ColumnFamilyOptions::from_ll(ll::rocks_cfoptions_create_from_options(self.raw)) };
-        unsafe {
-            Options::from_ll(ll::rocks_options_create_from_db_cf_options(
-                new_dbopt.raw(),
-                old_cfopt.raw(),
-            ))
-        }
+        let old_cfopt = ColumnFamilyOptions::from_options(&self);
+        Options::new(Some(new_dbopt), Some(old_cfopt))
     }
 
     /// Configure ColumnFamilyOptions using builder style.
     pub fn map_cf_options<F: FnOnce(ColumnFamilyOptions) -> ColumnFamilyOptions>(self, f: F) -> Self {
-        let cfopt = unsafe { ColumnFamilyOptions::from_ll(ll::rocks_cfoptions_create_from_options(self.raw)) };
+        let cfopt = ColumnFamilyOptions::from_options(&self);
         let new_cfopt = f(cfopt);
         let old_dbopt = unsafe { DBOptions::from_ll(ll::rocks_dboptions_create_from_options(self.raw)) };
-        unsafe {
-            Options::from_ll(ll::rocks_options_create_from_db_cf_options(
-                old_dbopt.raw(),
-                new_cfopt.raw(),
-            ))
-        }
+        Options::new(Some(old_dbopt), Some(new_cfopt))
     }
 
     /// Set appropriate parameters for bulk loading.
@@ -2437,6 +2756,59 @@ impl Options {
         unsafe { ll::rocks_options_optimize_for_small_db(self.raw) };
         self
     }
+
+    // ! Workload presets, applied on top of the current options
+
+    /// Preset for point lookups, i.e. `get()` / `multi_get()` without iterators.
+    ///
+    /// A block cache of `block_cache_size_mb`, bloom filters, hash index in data blocks,
+    /// and whole key bloom filter in memtable, see `ColumnFamilyOptions::optimize_for_point_lookup()`.
+    pub fn preset_point_lookup(self, block_cache_size_mb: u64) -> Self {
+        self.map_cf_options(|cf| {
+            cf.optimize_for_point_lookup(block_cache_size_mb)
+                .memtable_whole_key_filtering(true)
+        })
+    }
+
+    /// Preset for time series, i.e. mostly increasing keys read by range scans.
+    ///
+    /// Level style compaction with dynamic level sizing, so that sequential writes are
+    /// trivially moved down, and larger data blocks without bloom filters, which don't
+    /// help range scans.
+    pub fn preset_time_series(self) -> Self {
+        self.map_cf_options(|cf| {
+            cf.optimize_level_style_compaction(512 * 1024 * 1024)
+                .level_compaction_dynamic_level_bytes(true)
+                .table_factory_block_based(BlockBasedTableOptions::default().block_size(16 * 1024))
+        })
+        .map_db_options(|db| db.bytes_per_sync(1024 * 1024))
+    }
+
+    /// Preset for loading lots of data at once, e.g. the initial import of a DB.
+    ///
+    /// See `prepare_for_bulk_load()`, plus a vector memtable, which is the fastest to
+    /// fill but the slowest to read. All data stays in level 0 until compacted manually,
+    /// switch to another preset and reopen the DB after the load.
+    pub fn preset_bulk_ingest(self) -> Self {
+        self.prepare_for_bulk_load()
+            .map_cf_options(|cf| cf.memtable_factory_vector_rep(0))
+            .map_db_options(|db| db.allow_concurrent_memtable_write(false))
+    }
+
+    /// Set appropriate parameters for reproducible SST layouts, e.g. golden-file
+    /// tests of compaction filters and merge operators.
+    ///
+    /// Auto compactions are disabled, so compactions only happen on explicit
+    /// `compact_range()` / `compact_files()` calls, which run single-threaded
+    /// without subcompactions. Time based compactions, trivial moves of universal
+    /// compaction and dynamic level sizing are disabled as well.
+    ///
+    /// Use together with `CompactRangeOptions::deterministic()`, and write with
+    /// a single thread, since flush points depend on write order.
+    pub fn deterministic(self) -> Self {
+        unsafe { ll::rocks_options_deterministic(self.raw) };
+        self
+    }
 }
 
 /// An application can issue a read request (via Get/Iterators) and specify
@@ -2523,6 +2895,26 @@ impl<'a> ReadOptions<'a> {
         }
     }
 
+    /// Copy all settings into a new `ReadOptions`, except `snapshot` and the
+    /// iterate bounds, which borrow from this one.
+    ///
+    /// Takes a single FFI call, so a template built once can cheaply produce
+    /// per-call variants on hot paths:
+    ///
+    /// ```no_run
+    /// # use rocks::rocksdb::*;
+    /// # let db = DB::open(&Options::default(), "./data").unwrap();
+    /// let template = ReadOptions::default().fill_cache(false).verify_checksums(false);
+    /// let snapshot = db.get_snapshot();
+    /// let val = db.get(&template.derive().snapshot(snapshot.as_ref()), b"key");
+    /// ```
+    pub fn derive<'b>(&self) -> ReadOptions<'b> {
+        ReadOptions {
+            raw: unsafe { ll::rocks_readoptions_create_from(self.raw) },
+            _marker: PhantomData,
+        }
+    }
+
     /// If `snapshot` is non-nullptr, read as of the supplied snapshot
     /// (which must belong to the DB that is being read and which must
     /// not have been released).  If `snapshot` is nullptr, use an implicit
@@ -2557,8 +2949,7 @@ impl<'a> ReadOptions<'a> {
     /// not a valid entry.  If `iterator_extractor` is not null, the Seek target
     /// and `iterator_upper_bound` need to have the same prefix.
     /// This is because ordering is not guaranteed outside of prefix domain.
-    /// There is no lower bound on the iterator. If needed, that can be easily
-    /// implemented
+    /// See `iterate_lower_bound()` for the backward direction.
     ///
     /// Default: nullptr
     pub fn iterate_upper_bound<'b: 'a>(self, val: &'b [u8]) -> Self {
@@ -3014,6 +3405,17 @@ impl ToRaw<ll::rocks_compactrange_options_t> for CompactRangeOptions {
 }
 
 impl CompactRangeOptions {
+    /// Options for reproducible manual compactions, see `Options::deterministic()`.
+    ///
+    /// Exclusive and single-threaded, and always rewrites the bottommost level, so
+    /// the resulting files don't depend on previous layouts.
+    pub fn deterministic() -> CompactRangeOptions {
+        CompactRangeOptions::default()
+            .exclusive_manual_compaction(true)
+            .max_subcompactions(1)
+            .bottommost_level_compaction(BottommostLevelCompaction::Force)
+    }
+
     /// If true, no other compaction will run at the same time as this
     /// manual compaction
     pub fn exclusive_manual_compaction(self, val: bool) -> Self {
@@ -3058,10 +3460,113 @@ impl CompactRangeOptions {
         }
         self
     }
+
+    /// If true, will execute immediately even if doing so would cause the DB to
+    /// enter write stall mode. Otherwise, it'll sleep until load is low enough.
+    ///
+    /// Default: false
+    pub fn allow_write_stall(self, val: bool) -> Self {
+        unsafe {
+            ll::rocks_compactrange_options_set_allow_write_stall(self.raw, val as u8);
+        }
+        self
+    }
+
+    /// If > 0, it will replace the option in the DBOptions for this compaction,
+    /// splitting it into up to this many subcompactions run in parallel.
+    ///
+    /// Default: 0, use `DBOptions::max_subcompactions`
+    ///
+    /// There is no `canceled` flag in this RocksDB version, see `DB::cancel_operation()`
+    /// for stopping a running manual compaction.
+    pub fn max_subcompactions(self, val: u32) -> Self {
+        unsafe {
+            ll::rocks_compactrange_options_set_max_subcompactions(self.raw, val);
+        }
+        self
+    }
 }
 
 unsafe impl Sync for CompactRangeOptions {}
 
+/// `SizeApproximationOptions` is used by `get_approximate_sizes_with_options()`
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct SizeApproximationOptions {
+    /// Defines whether the returned size should include the recently written
+    /// data in the mem-tables. If set to false, include_files must be true.
+    pub include_memtables: bool,
+    /// Defines whether the returned size should include data serialized to disk.
+    /// If set to false, include_memtables must be true.
+    pub include_files: bool,
+    /// When approximating the files total size that is used to store a keys range
+    /// using `get_approximate_sizes_with_options`, allow approximation with an error
+    /// margin of up to total_files_size * files_size_error_margin. This allows
+    /// to take some shortcuts in files size approximation, resulting in better
+    /// performance, while guaranteeing the resulting error is within a reasonable
+    /// margin.
+    ///
+    /// E.g., if the value is 0.1, then the error margin of the returned files size
+    /// approximation will be within 10%.
+    ///
+    /// If the value is non-positive - a more precise yet more CPU intensive
+    /// estimation is performed.
+    pub files_size_error_margin: f64,
+}
+
+impl Default for SizeApproximationOptions {
+    fn default() -> Self {
+        SizeApproximationOptions {
+            include_memtables: false,
+            include_files: true,
+            files_size_error_margin: -1.0,
+        }
+    }
+}
+
+impl SizeApproximationOptions {
+    pub fn include_memtables(mut self, val: bool) -> Self {
+        self.include_memtables = val;
+        self
+    }
+
+    pub fn include_files(mut self, val: bool) -> Self {
+        self.include_files = val;
+        self
+    }
+
+    pub fn files_size_error_margin(mut self, val: f64) -> Self {
+        self.files_size_error_margin = val;
+        self
+    }
+}
+
+/// `WaitForCompactOptions` is used by `wait_for_compact()`
+#[derive(Debug, Clone, Copy, PartialEq, Default)]
+pub struct WaitForCompactOptions {
+    /// Flush all column families before waiting, so that data in memtables is
+    /// also compacted.
+    ///
+    /// Default: false
+    pub flush: bool,
+    /// Give up and return a `TimedOut` error when background jobs are still
+    /// running after this long.
+    ///
+    /// Default: None, wait forever
+    pub timeout: Option<Duration>,
+}
+
+impl WaitForCompactOptions {
+    pub fn flush(mut self, val: bool) -> Self {
+        self.flush = val;
+        self
+    }
+
+    pub fn timeout(mut self, val: Duration) -> Self {
+        self.timeout = Some(val);
+        self
+    }
+}
+
 /// `IngestExternalFileOptions` is used by `ingest_external_file()`
 #[repr(C)]
 pub struct IngestExternalFileOptions {
@@ -3144,10 +3649,33 @@ impl IngestExternalFileOptions {
 
 unsafe impl Sync for IngestExternalFileOptions {}
 
+/// `ImportColumnFamilyOptions` is used by `create_column_family_with_import()`
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub struct ImportColumnFamilyOptions {
+    /// Can be set to true to move the files instead of copying them.
+    ///
+    /// Default: false
+    pub move_files: bool,
+}
+
+impl ImportColumnFamilyOptions {
+    pub fn move_files(mut self, val: bool) -> Self {
+        self.move_files = val;
+        self
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use super::super::rocksdb::*;
     use super::*;
+    use crate::cache::CacheBuilder;
+    use crate::compaction_filter::{CompactionFilter, Decision, ValueType};
+    use crate::error::Code;
+    use lazy_static::lazy_static;
+    use std::sync::atomic::{AtomicBool, Ordering as AtomicOrdering};
+    use std::thread;
+    use std::time::Duration;
 
     #[test]
     fn dboptions_stringify() {
@@ -3184,6 +3712,37 @@ mod tests {
         assert!(val.is_ok());
     }
 
+    #[test]
+    fn no_block_cache_conflicts() {
+        let plain = || BlockBasedTableOptions::default().no_block_cache(true).block_cache(None);
+        assert!(plain().validate().is_ok());
+        assert!(BlockBasedTableOptions::default().validate().is_ok());
+
+        let cache = CacheBuilder::new_lru(1 << 20).build();
+        let err = plain().block_cache(cache).validate().unwrap_err();
+        assert_eq!(err.code(), Code::InvalidArgument);
+
+        let tmp_dir = ::tempdir::TempDir::new_in(".", "rocks").unwrap();
+        for table_opts in vec![
+            plain().cache_index_and_filter_blocks(true),
+            plain().pin_l0_filter_and_index_blocks_in_cache(true),
+        ] {
+            let opts = Options::default()
+                .map_db_options(|db| db.create_if_missing(true))
+                .map_cf_options(|cf| cf.table_factory_block_based(table_opts));
+            let err = DB::open(&opts, &tmp_dir).unwrap_err();
+            assert_eq!(err.code(), Code::InvalidArgument);
+        }
+
+        let db = DB::open(
+            Options::default().map_db_options(|db| db.create_if_missing(true)),
+            &tmp_dir,
+        )
+        .unwrap();
+        let cfopts = ColumnFamilyOptions::default().table_factory_block_based(plain());
+        assert!(db.create_column_family(&cfopts, "plain").is_ok());
+    }
+
     #[test]
     fn default_instance() {
         let w1 = WriteOptions::default_instance();
@@ -3197,6 +3756,67 @@ mod tests {
         assert_eq!(w1.raw, w2.raw);
     }
 
+    #[test]
+    fn read_options_derive() {
+        let tmp_dir = ::tempdir::TempDir::new_in(".", "rocks").unwrap();
+        let db = DB::open(
+            Options::default().map_db_options(|db| db.create_if_missing(true)),
+            &tmp_dir,
+        )
+        .unwrap();
+        assert!(db.put(&Default::default(), b"k1", b"v1").is_ok());
+        let snapshot = db.get_snapshot();
+        assert!(db.put(&Default::default(), b"k1", b"v2").is_ok());
+        assert!(db.put(&Default::default(), b"k2", b"v2").is_ok());
+
+        let upper = b"k2".to_vec();
+        let template = ReadOptions::default()
+            .fill_cache(false)
+            .snapshot(snapshot.as_ref())
+            .iterate_upper_bound(&upper);
+
+        // snapshot and bounds are not inherited
+        let derived = template.derive();
+        drop(template);
+        drop(upper);
+        assert_eq!(db.get(&derived, b"k1").unwrap().as_ref(), b"v2");
+        assert_eq!(db.new_iterator(&derived).count(), 2);
+
+        let derived = derived.derive().snapshot(snapshot.as_ref());
+        assert_eq!(db.get(&derived, b"k1").unwrap().as_ref(), b"v1");
+        assert!(db.get(&derived, b"k2").is_err());
+    }
+
+    #[test]
+    fn read_options_iterate_bounds() {
+        let tmp_dir = ::tempdir::TempDir::new_in(".", "rocks").unwrap();
+        let db = DB::open(
+            Options::default().map_db_options(|db| db.create_if_missing(true)),
+            &tmp_dir,
+        )
+        .unwrap();
+        for i in 0..10 {
+            assert!(db.put(&Default::default(), format!("k{}", i).as_bytes(), b"v").is_ok());
+        }
+
+        let lower = b"k3".to_vec();
+        let upper = b"k7".to_vec();
+        let ropts = ReadOptions::default()
+            .iterate_lower_bound(&lower)
+            .iterate_upper_bound(&upper);
+        let keys = db.new_iterator(&ropts).rev().map(|(k, _)| k.to_vec()).collect::<Vec<_>>();
+        assert_eq!(keys, vec![b"k6".to_vec(), b"k5".to_vec(), b"k4".to_vec(), b"k3".to_vec()]);
+
+        // the lower bound is inclusive, stops backward seeks
+        let mut it = db.new_iterator(&ropts);
+        it.seek_for_prev(b"k3");
+        assert_eq!(it.key(), b"k3");
+        it.prev();
+        assert!(!it.is_valid());
+        it.seek_for_prev(b"k1");
+        assert!(!it.is_valid());
+    }
+
     #[test]
     fn compact_range_options() {
         let tmp_dir = ::tempdir::TempDir::new_in(".", "rocks").unwrap();
@@ -3229,4 +3849,144 @@ mod tests {
         assert_eq!(meta.levels[3].files.len(), 0);
         assert!(meta.levels[4].files.len() > 0);
     }
+
+    #[test]
+    fn deterministic_compaction() {
+        fn layout() -> Vec<(u32, Vec<u8>, Vec<u8>)> {
+            let tmp_dir = ::tempdir::TempDir::new_in(".", "rocks").unwrap();
+            let db = DB::open(
+                Options::default()
+                    .map_db_options(|db| db.create_if_missing(true))
+                    .map_cf_options(|cf| cf.write_buffer_size(64 << 10))
+                    .deterministic(),
+                &tmp_dir,
+            )
+            .unwrap();
+            for i in 0..5000 {
+                let key = format!("k{:05}", (i * 7919) % 5000);
+                assert!(db.put(&Default::default(), key.as_bytes(), &[b'v'; 64]).is_ok());
+            }
+            assert!(db.flush(&FlushOptions::default().wait(true)).is_ok());
+            assert!(db.compact_range(&CompactRangeOptions::deterministic(), ..).is_ok());
+
+            let mut files: Vec<_> = db
+                .get_live_files_metadata()
+                .into_iter()
+                .map(|f| (f.level, f.smallestkey.clone(), f.largestkey.clone()))
+                .collect();
+            files.sort();
+            files
+        }
+
+        let first = layout();
+        assert!(!first.is_empty());
+        assert_eq!(first, layout());
+    }
+
+    #[test]
+    fn compact_range_subcompactions() {
+        let tmp_dir = ::tempdir::TempDir::new_in(".", "rocks").unwrap();
+        let db = DB::open(
+            Options::default().map_db_options(|db| db.create_if_missing(true)),
+            &tmp_dir,
+        )
+        .unwrap();
+        for i in 0..100 {
+            let key = format!("k{:03}", i);
+            assert!(db.put(&Default::default(), key.as_bytes(), b"v").is_ok());
+            if i % 10 == 0 {
+                assert!(db.flush(&FlushOptions::default().wait(true)).is_ok());
+            }
+        }
+
+        assert!(db
+            .compact_range(
+                &CompactRangeOptions::default()
+                    .max_subcompactions(4)
+                    .allow_write_stall(true),
+                ..,
+            )
+            .is_ok());
+
+        let meta = db.get_column_family_metadata(&db.default_column_family());
+        assert_eq!(meta.levels[0].files.len(), 0);
+        assert_eq!(db.get(&Default::default(), b"k042").unwrap().as_ref(), b"v");
+    }
+
+    static COMPACTION_BLOCKED: AtomicBool = AtomicBool::new(true);
+
+    // holds the automatic compaction, so that L0 does not drain
+    struct BlockingFilter;
+
+    impl CompactionFilter for BlockingFilter {
+        fn filter(&mut self, _level: i32, _key: &[u8], _value_type: ValueType, _value: &[u8]) -> Decision {
+            while COMPACTION_BLOCKED.load(AtomicOrdering::SeqCst) {
+                thread::sleep(Duration::from_millis(1));
+            }
+            Decision::Keep
+        }
+    }
+
+    lazy_static! {
+        static ref BLOCKING_FILTER: BlockingFilter = BlockingFilter;
+    }
+
+    // releases the compaction before the DB is closed, even if an assertion fails
+    struct Unblock;
+
+    impl Drop for Unblock {
+        fn drop(&mut self) {
+            COMPACTION_BLOCKED.store(false, AtomicOrdering::SeqCst);
+        }
+    }
+
+    #[test]
+    fn compact_range_allow_write_stall() {
+        let tmp_dir = ::tempdir::TempDir::new_in(".", "rocks").unwrap();
+        let db = DB::open(
+            Options::default()
+                .map_db_options(|db| db.create_if_missing(true))
+                .map_cf_options(|cf| {
+                    cf.level0_file_num_compaction_trigger(2)
+                        .level0_slowdown_writes_trigger(3)
+                        .level0_stop_writes_trigger(3)
+                        .compaction_filter(&*BLOCKING_FILTER)
+                }),
+            &tmp_dir,
+        )
+        .map(Arc::new)
+        .unwrap();
+        let unblock = Unblock;
+
+        // the compaction triggered by the 2nd file hangs, a 3rd file would stop writes
+        for i in 0..2 {
+            assert!(db.put(&Default::default(), format!("k{}", i).as_bytes(), b"v").is_ok());
+            assert!(db.flush(&FlushOptions::default().wait(true)).is_ok());
+        }
+        assert!(db.put(&Default::default(), b"k2", b"v").is_ok());
+        assert!(!db.write_stall_state().is_stalled());
+
+        let compaction = {
+            let db = db.clone();
+            thread::spawn(move || {
+                let options = CompactRangeOptions::default().allow_write_stall(true);
+                db.compact_range(&options, ..)
+            })
+        };
+
+        // the memtable is flushed into the stop, without waiting for L0 to drain
+        let mut waited = 0;
+        while !db.write_stall_state().is_stopped() {
+            assert!(waited < 10_000, "the flush of compact_range() waited for the stall to clear");
+            thread::sleep(Duration::from_millis(1));
+            waited += 1;
+        }
+
+        drop(unblock);
+        assert!(compaction.join().unwrap().is_ok());
+        assert!(!db.write_stall_state().is_stalled());
+        let meta = db.get_column_family_metadata(&db.default_column_family());
+        assert_eq!(meta.levels[0].files.len(), 0);
+        assert_eq!(db.get(&Default::default(), b"k2").unwrap().as_ref(), b"v");
+    }
 }
@@ -1,7 +1,9 @@
 //! A thread local context for gathering performance counter efficiently
 //! and transparently.
 
+use std::collections::BTreeMap;
 use std::fmt;
+use std::ptr;
 
 use rocks_sys as ll;
 
@@ -129,6 +131,52 @@ pub struct PerfContext {
     pub bloom_sst_miss_count: u64,
 }
 
+/// Per-level counters of a `PerfContext`, gathered when per-level perf context
+/// is enabled.
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq)]
+#[repr(C)]
+pub struct PerfContextByLevel {
+    /// # of times bloom filter has avoided file reads, i.e., negatives.
+    pub bloom_filter_useful: u64,
+    /// # of times bloom FullFilter has not avoided the reads.
+    pub bloom_filter_full_positive: u64,
+    /// # of times bloom FullFilter has not avoided the reads and data actually
+    /// exist.
+    pub bloom_filter_full_true_positive: u64,
+    /// total number of user key returned (only include keys that are found, does
+    /// not include keys that are deleted or merged without a final put
+    pub user_key_return_count: u64,
+    /// total nanos spent on reading data from SST files
+    pub get_from_table_nanos: u64,
+    /// total number of block cache hits
+    pub block_cache_hit_count: u64,
+    /// total number of block cache misses
+    pub block_cache_miss_count: u64,
+}
+
+impl PerfContextByLevel {
+    /// Block cache hits over lookups, `None` if there was no lookup.
+    pub fn block_cache_hit_ratio(&self) -> Option<f64> {
+        let total = self.block_cache_hit_count + self.block_cache_miss_count;
+        if total == 0 {
+            None
+        } else {
+            Some(self.block_cache_hit_count as f64 / total as f64)
+        }
+    }
+
+    /// Bloom filter full positives that turned out to be false, `None` if the
+    /// filter was never positive.
+    pub fn bloom_filter_false_positive_ratio(&self) -> Option<f64> {
+        if self.bloom_filter_full_positive == 0 {
+            None
+        } else {
+            let false_positive = self.bloom_filter_full_positive - self.bloom_filter_full_true_positive;
+            Some(false_positive as f64 / self.bloom_filter_full_positive as f64)
+        }
+    }
+}
+
 impl PerfContext {
     /// PerfContext for current thread
     pub fn current() -> &'static mut PerfContext {
@@ -145,6 +193,42 @@ impl PerfContext {
             ll::rocks_perf_context_reset(ptr);
         }
     }
+
+    fn raw_mut(&mut self) -> *mut ll::rocks_perf_context_t {
+        self as *mut PerfContext as *mut ll::rocks_perf_context_t
+    }
+
+    /// Start gathering per-level counters on the current thread.
+    pub fn enable_per_level_perf_context(&mut self) {
+        unsafe { ll::rocks_perf_context_enable_per_level(self.raw_mut()) }
+    }
+
+    /// Stop gathering per-level counters, gathered counters are kept.
+    pub fn disable_per_level_perf_context(&mut self) {
+        unsafe { ll::rocks_perf_context_disable_per_level(self.raw_mut()) }
+    }
+
+    /// Drop all gathered per-level counters.
+    pub fn clear_per_level_perf_context(&mut self) {
+        unsafe { ll::rocks_perf_context_clear_per_level(self.raw_mut()) }
+    }
+
+    /// Per-level counters, keyed by level. Levels never touched are absent.
+    pub fn by_level(&self) -> BTreeMap<u32, PerfContextByLevel> {
+        let ptr = self as *const PerfContext as *const ll::rocks_perf_context_t;
+        unsafe {
+            let len = ll::rocks_perf_context_by_level(ptr, ptr::null_mut(), ptr::null_mut(), 0);
+            let mut levels = vec![0; len];
+            let mut stats = vec![ptr::null(); len];
+            let len = ll::rocks_perf_context_by_level(ptr, levels.as_mut_ptr(), stats.as_mut_ptr(), len).min(len);
+            levels
+                .into_iter()
+                .zip(stats)
+                .take(len)
+                .map(|(level, stat)| (level, *(stat as *const PerfContextByLevel)))
+                .collect()
+        }
+    }
 }
 
 impl fmt::Display for PerfContext {
@@ -196,4 +280,38 @@ mod tests {
         stat.reset();
         assert_eq!(stat.user_key_comparison_count, 0);
     }
+
+    #[test]
+    fn perf_context_by_level() {
+        set_perf_level(PerfLevel::EnableTime);
+
+        let tmp_dir = ::tempdir::TempDir::new_in(".", "rocks").unwrap();
+        let db = DB::open(
+            Options::default().map_db_options(|db| db.create_if_missing(true)),
+            &tmp_dir,
+        )
+        .unwrap();
+        for i in 0..100 {
+            assert!(db.put(&Default::default(), format!("k{:03}", i).as_bytes(), b"v").is_ok());
+        }
+        assert!(db.compact_range(&Default::default(), ..).is_ok());
+
+        let stat = PerfContext::current();
+        stat.reset();
+        stat.clear_per_level_perf_context();
+        stat.enable_per_level_perf_context();
+        for i in 0..100 {
+            assert!(db.get(&Default::default(), format!("k{:03}", i).as_bytes()).is_ok());
+        }
+        stat.disable_per_level_perf_context();
+
+        let by_level = stat.by_level();
+        assert!(!by_level.is_empty());
+        let total_returned: u64 = by_level.values().map(|s| s.user_key_return_count).sum();
+        assert_eq!(total_returned, 100);
+        assert!(by_level.values().any(|s| s.block_cache_hit_ratio().is_some()));
+
+        stat.clear_per_level_perf_context();
+        assert!(stat.by_level().is_empty());
+    }
 }
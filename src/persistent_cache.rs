@@ -1,7 +1,7 @@
 //! Persistent cache interface for caching IO pages on a persistent medium.
 
 use std::fmt;
-use std::path::Path;
+use std::path::{Path, PathBuf};
 use std::ptr;
 use std::slice;
 use std::str;
@@ -84,6 +84,58 @@ impl PersistentCache {
     }
 }
 
+/// Builder for a tiered persistent cache, to be attached to
+/// `BlockBasedTableOptions::persistent_cache`.
+pub struct PersistentCacheBuilder<'a> {
+    env: &'a Env,
+    path: PathBuf,
+    size: u64,
+    logger: Option<&'a Logger>,
+    optimized_for_nvm: bool,
+}
+
+impl<'a> PersistentCacheBuilder<'a> {
+    /// Cache files are stored under `path`, using at most `size` bytes.
+    ///
+    /// NOTE: RocksDB requires size to be big enough, at least several cache files.
+    pub fn new<P: AsRef<Path>>(path: P, size: u64) -> PersistentCacheBuilder<'a> {
+        PersistentCacheBuilder {
+            env: Env::default_instance(),
+            path: path.as_ref().to_path_buf(),
+            size,
+            logger: None,
+            optimized_for_nvm: false,
+        }
+    }
+
+    /// Env used to access the cache files.
+    pub fn env(mut self, env: &'a Env) -> Self {
+        self.env = env;
+        self
+    }
+
+    /// Info log of the cache tier.
+    pub fn logger(mut self, logger: &'a Logger) -> Self {
+        self.logger = Some(logger);
+        self
+    }
+
+    /// The default settings are optimized for SSD. NVM devices are better
+    /// accessed with 4K direct IO and written with parallelism, setting this
+    /// enables direct writes and parallel 4K write dispatch.
+    ///
+    /// This is the only way to enable direct IO on the cache files through the
+    /// public RocksDB API.
+    pub fn optimized_for_nvm(mut self, val: bool) -> Self {
+        self.optimized_for_nvm = val;
+        self
+    }
+
+    pub fn build(&self) -> Result<PersistentCache> {
+        PersistentCache::new(self.env, &self.path, self.size, self.logger, self.optimized_for_nvm)
+    }
+}
+
 #[test]
 fn test_persistent_cache() {
     let tmp_dir = ::tempdir::TempDir::new_in("", "rocks").unwrap();
@@ -95,3 +147,32 @@ fn test_persistent_cache() {
 
     assert!(format!("{:?}", pcache).contains("is_compressed: 1"));
 }
+
+#[test]
+fn test_persistent_cache_in_table_options() {
+    use crate::options::{FlushOptions, Options, ReadOptions, WriteOptions};
+    use crate::table::BlockBasedTableOptions;
+    use crate::db::DB;
+
+    let tmp_dir = ::tempdir::TempDir::new_in("", "rocks").unwrap();
+    let pcache = PersistentCacheBuilder::new(tmp_dir.path().join("pcache"), 1 << 30)
+        .optimized_for_nvm(false)
+        .build()
+        .unwrap();
+
+    let db = DB::open(
+        Options::default()
+            .map_db_options(|db| db.create_if_missing(true))
+            .map_cf_options(|cf| {
+                cf.table_factory_block_based(BlockBasedTableOptions::default().persistent_cache(Some(pcache.clone())))
+            }),
+        tmp_dir.path().join("db"),
+    )
+    .unwrap();
+    for i in 0..100 {
+        let key = format!("k{}", i);
+        assert!(db.put(WriteOptions::default_instance(), key.as_bytes(), b"v").is_ok());
+    }
+    assert!(db.flush(&FlushOptions::default().wait(true)).is_ok());
+    assert_eq!(db.get(ReadOptions::default_instance(), b"k42").unwrap().as_ref(), b"v");
+}
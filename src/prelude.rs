@@ -9,6 +9,7 @@ pub use crate::perf_level::*;
 pub use crate::slice::PinnableSlice;
 pub use crate::table::*;
 pub use crate::table_properties::{TableProperties, TablePropertiesCollection};
+pub use crate::transaction::{OptimisticTransactionOptions, Transaction, TransactionDBOptions, TransactionOptions};
 pub use crate::transaction_log::LogFile;
 pub use crate::types::SequenceNumber;
 pub use crate::version::version;
@@ -0,0 +1,348 @@
+//! DB property names, and parsed forms of some string properties.
+//!
+//! Names ending with `_PREFIX` take a level number appended, e.g.
+//! `format!("{}{}", NUM_FILES_AT_LEVEL_PREFIX, 0)`.
+
+use std::collections::{BTreeMap, HashMap};
+
+use crate::listener::WriteStallCondition;
+
+/// Number of files at level N, N appended.
+pub const NUM_FILES_AT_LEVEL_PREFIX: &str = "rocksdb.num-files-at-level";
+/// Compression ratio of data at level N, N appended.
+pub const COMPRESSION_RATIO_AT_LEVEL_PREFIX: &str = "rocksdb.compression-ratio-at-level";
+/// Multi-line string of general statistics, `CFSTATS` + `DBSTATS`.
+pub const STATS: &str = "rocksdb.stats";
+/// Multi-line string summarizing the current SST files.
+pub const SSTABLES: &str = "rocksdb.sstables";
+/// Per column family compaction and stall statistics, also a map property.
+pub const CFSTATS: &str = "rocksdb.cfstats";
+/// `CFSTATS` without the file read latency histogram.
+pub const CFSTATS_NO_FILE_HISTOGRAM: &str = "rocksdb.cfstats-no-file-histogram";
+/// File read latency histogram per level.
+pub const CF_FILE_HISTOGRAM: &str = "rocksdb.cf-file-histogram";
+/// DB-wide write and stall statistics.
+pub const DBSTATS: &str = "rocksdb.dbstats";
+/// Number of files and total size per level, see `LevelStats`.
+pub const LEVELSTATS: &str = "rocksdb.levelstats";
+pub const NUM_IMMUTABLE_MEM_TABLE: &str = "rocksdb.num-immutable-mem-table";
+pub const NUM_IMMUTABLE_MEM_TABLE_FLUSHED: &str = "rocksdb.num-immutable-mem-table-flushed";
+pub const MEM_TABLE_FLUSH_PENDING: &str = "rocksdb.mem-table-flush-pending";
+pub const NUM_RUNNING_FLUSHES: &str = "rocksdb.num-running-flushes";
+pub const COMPACTION_PENDING: &str = "rocksdb.compaction-pending";
+pub const NUM_RUNNING_COMPACTIONS: &str = "rocksdb.num-running-compactions";
+pub const BACKGROUND_ERRORS: &str = "rocksdb.background-errors";
+pub const CUR_SIZE_ACTIVE_MEM_TABLE: &str = "rocksdb.cur-size-active-mem-table";
+pub const CUR_SIZE_ALL_MEM_TABLES: &str = "rocksdb.cur-size-all-mem-tables";
+pub const SIZE_ALL_MEM_TABLES: &str = "rocksdb.size-all-mem-tables";
+pub const NUM_ENTRIES_ACTIVE_MEM_TABLE: &str = "rocksdb.num-entries-active-mem-table";
+pub const NUM_ENTRIES_IMM_MEM_TABLES: &str = "rocksdb.num-entries-imm-mem-tables";
+pub const NUM_DELETES_ACTIVE_MEM_TABLE: &str = "rocksdb.num-deletes-active-mem-table";
+pub const NUM_DELETES_IMM_MEM_TABLES: &str = "rocksdb.num-deletes-imm-mem-tables";
+pub const ESTIMATE_NUM_KEYS: &str = "rocksdb.estimate-num-keys";
+pub const ESTIMATE_TABLE_READERS_MEM: &str = "rocksdb.estimate-table-readers-mem";
+pub const IS_FILE_DELETIONS_ENABLED: &str = "rocksdb.is-file-deletions-enabled";
+pub const NUM_SNAPSHOTS: &str = "rocksdb.num-snapshots";
+pub const OLDEST_SNAPSHOT_TIME: &str = "rocksdb.oldest-snapshot-time";
+pub const NUM_LIVE_VERSIONS: &str = "rocksdb.num-live-versions";
+pub const CURRENT_SUPER_VERSION_NUMBER: &str = "rocksdb.current-super-version-number";
+pub const ESTIMATE_LIVE_DATA_SIZE: &str = "rocksdb.estimate-live-data-size";
+pub const MIN_LOG_NUMBER_TO_KEEP: &str = "rocksdb.min-log-number-to-keep";
+pub const MIN_OBSOLETE_SST_NUMBER_TO_KEEP: &str = "rocksdb.min-obsolete-sst-number-to-keep";
+pub const TOTAL_SST_FILES_SIZE: &str = "rocksdb.total-sst-files-size";
+pub const LIVE_SST_FILES_SIZE: &str = "rocksdb.live-sst-files-size";
+pub const BASE_LEVEL: &str = "rocksdb.base-level";
+pub const ESTIMATE_PENDING_COMPACTION_BYTES: &str = "rocksdb.estimate-pending-compaction-bytes";
+pub const AGGREGATED_TABLE_PROPERTIES: &str = "rocksdb.aggregated-table-properties";
+/// Aggregated table properties at level N, N appended.
+pub const AGGREGATED_TABLE_PROPERTIES_AT_LEVEL_PREFIX: &str = "rocksdb.aggregated-table-properties-at-level";
+pub const ACTUAL_DELAYED_WRITE_RATE: &str = "rocksdb.actual-delayed-write-rate";
+pub const IS_WRITE_STOPPED: &str = "rocksdb.is-write-stopped";
+pub const ESTIMATE_OLDEST_KEY_TIME: &str = "rocksdb.estimate-oldest-key-time";
+pub const BLOCK_CACHE_CAPACITY: &str = "rocksdb.block-cache-capacity";
+pub const BLOCK_CACHE_USAGE: &str = "rocksdb.block-cache-usage";
+pub const BLOCK_CACHE_PINNED_USAGE: &str = "rocksdb.block-cache-pinned-usage";
+pub const OPTIONS_STATISTICS: &str = "rocksdb.options-statistics";
+
+/// One row of `"rocksdb.levelstats"`.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct LevelStats {
+    pub level: u32,
+    pub num_files: u64,
+    /// Total file size, rounded to whole MB by RocksDB.
+    pub size_mb: f64,
+}
+
+impl LevelStats {
+    /// Parse the output of `"rocksdb.levelstats"`:
+    ///
+    /// ```text
+    /// Level Files Size(MB)
+    /// --------------------
+    ///   0        1        0
+    ///   1        0        0
+    /// ```
+    pub fn parse(s: &str) -> Option<Vec<LevelStats>> {
+        s.lines()
+            .skip(2)
+            .filter(|line| !line.trim().is_empty())
+            .map(|line| {
+                let mut fields = line.split_whitespace();
+                let level = fields.next()?.parse().ok()?;
+                let num_files = fields.next()?.parse().ok()?;
+                let size_mb = fields.next()?.parse().ok()?;
+                Some(LevelStats {
+                    level,
+                    num_files,
+                    size_mb,
+                })
+            })
+            .collect()
+    }
+}
+
+/// Compaction statistics of a level, from the map form of `"rocksdb.cfstats"`.
+#[derive(Debug, Default, Clone, Copy, PartialEq)]
+pub struct LevelCompactionStats {
+    pub num_files: u64,
+    /// Files being compacted.
+    pub compacted_files: u64,
+    pub size_bytes: u64,
+    pub score: f64,
+    pub read_gb: f64,
+    /// Bytes read from level N, in GB.
+    pub rn_gb: f64,
+    /// Bytes read from level N+1, in GB.
+    pub rnp1_gb: f64,
+    pub write_gb: f64,
+    /// Bytes written minus bytes read from level N+1, in GB.
+    pub wnew_gb: f64,
+    /// Bytes moved to the next level without rewriting, in GB.
+    pub moved_gb: f64,
+    /// Bytes written over bytes read from level N.
+    pub write_amp: f64,
+    pub read_mbps: f64,
+    pub write_mbps: f64,
+    pub comp_sec: f64,
+    pub comp_merge_cpu_sec: f64,
+    pub comp_count: u64,
+    pub avg_sec: f64,
+    pub key_in: u64,
+    pub key_drop: u64,
+}
+
+impl LevelCompactionStats {
+    fn set(&mut self, name: &str, value: f64) {
+        match name {
+            "NumFiles" => self.num_files = value as u64,
+            "CompactedFiles" => self.compacted_files = value as u64,
+            "SizeBytes" => self.size_bytes = value as u64,
+            "Score" => self.score = value,
+            "ReadGB" => self.read_gb = value,
+            "RnGB" => self.rn_gb = value,
+            "Rnp1GB" => self.rnp1_gb = value,
+            "WriteGB" => self.write_gb = value,
+            "WnewGB" => self.wnew_gb = value,
+            "MovedGB" => self.moved_gb = value,
+            "WriteAmp" => self.write_amp = value,
+            "ReadMBps" => self.read_mbps = value,
+            "WriteMBps" => self.write_mbps = value,
+            "CompSec" => self.comp_sec = value,
+            "CompMergeCPU" => self.comp_merge_cpu_sec = value,
+            "CompCount" => self.comp_count = value as u64,
+            "AvgSec" => self.avg_sec = value,
+            "KeyIn" => self.key_in = value as u64,
+            "KeyDrop" => self.key_drop = value as u64,
+            _ => (),
+        }
+    }
+}
+
+/// Map form of `"rocksdb.cfstats"`.
+#[derive(Debug, Default, Clone, PartialEq)]
+pub struct CFStats {
+    /// Levels that have files or compaction history.
+    pub levels: BTreeMap<u32, LevelCompactionStats>,
+    /// Sum over all levels.
+    pub sum: LevelCompactionStats,
+    /// Write stall counters, keyed without the `"io_stalls."` prefix.
+    pub io_stalls: HashMap<String, u64>,
+}
+
+impl CFStats {
+    /// Build from the output of `get_map_property("rocksdb.cfstats")`, unknown keys are ignored.
+    pub fn from_map(map: &HashMap<String, String>) -> CFStats {
+        let mut stats = CFStats::default();
+        for (key, value) in map {
+            let value = match value.trim().parse::<f64>() {
+                Ok(v) => v,
+                Err(_) => continue,
+            };
+            if let Some(stall) = key.strip_prefix("io_stalls.") {
+                stats.io_stalls.insert(stall.to_owned(), value as u64);
+            } else if let Some(rest) = key.strip_prefix("compaction.") {
+                let mut parts = rest.splitn(2, '.');
+                let (level, name) = match (parts.next(), parts.next()) {
+                    (Some(level), Some(name)) => (level, name),
+                    _ => continue,
+                };
+                if level == "Sum" {
+                    stats.sum.set(name, value);
+                } else if let Some(n) = level.strip_prefix('L').and_then(|n| n.parse().ok()) {
+                    stats.levels.entry(n).or_default().set(name, value);
+                }
+            }
+        }
+        stats
+    }
+}
+
+/// Write stall state of a DB, from `IS_WRITE_STOPPED` and `ACTUAL_DELAYED_WRITE_RATE`.
+///
+/// Use `EventListener::on_stall_conditions_changed()` to get notified of changes.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct WriteStallState {
+    pub condition: WriteStallCondition,
+    /// Current delayed write rate in bytes per second, 0 if writes are not delayed.
+    pub delayed_write_rate: u64,
+}
+
+impl WriteStallState {
+    /// Build from the values of `"rocksdb.is-write-stopped"` and `"rocksdb.actual-delayed-write-rate"`.
+    pub fn from_properties(is_write_stopped: u64, actual_delayed_write_rate: u64) -> WriteStallState {
+        let condition = if is_write_stopped != 0 {
+            WriteStallCondition::Stopped
+        } else if actual_delayed_write_rate != 0 {
+            WriteStallCondition::Delayed
+        } else {
+            WriteStallCondition::Normal
+        };
+        WriteStallState {
+            condition,
+            delayed_write_rate: actual_delayed_write_rate,
+        }
+    }
+
+    /// Writes are delayed or stopped.
+    pub fn is_stalled(&self) -> bool {
+        self.condition != WriteStallCondition::Normal
+    }
+
+    pub fn is_stopped(&self) -> bool {
+        self.condition == WriteStallCondition::Stopped
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::sync::atomic::{AtomicBool, Ordering};
+    use std::thread;
+    use std::time::Duration;
+
+    use lazy_static::lazy_static;
+
+    use super::super::compaction_filter::{CompactionFilter, Decision, ValueType};
+    use super::super::rocksdb::*;
+    use super::*;
+
+    #[test]
+    fn parse_levelstats() {
+        let s = "Level Files Size(MB)\n--------------------\n  0        2        1\n  1        0        0\n";
+        let stats = LevelStats::parse(s).unwrap();
+        assert_eq!(stats.len(), 2);
+        assert_eq!(stats[0].num_files, 2);
+        assert_eq!(stats[0].size_mb, 1.0);
+        assert!(LevelStats::parse("Level Files Size(MB)\n----\n  x\n").is_none());
+    }
+
+    #[test]
+    fn typed_properties() {
+        let tmp_dir = ::tempdir::TempDir::new_in(".", "rocks").unwrap();
+        let db = DB::open(
+            Options::default().map_db_options(|db| db.create_if_missing(true)),
+            &tmp_dir,
+        )
+        .unwrap();
+        for i in 0..3 {
+            assert!(db.put(&Default::default(), format!("k{}", i).as_bytes(), b"v").is_ok());
+            assert!(db.flush(&FlushOptions::default().wait(true)).is_ok());
+        }
+        assert_eq!(db.get_int_property(NUM_IMMUTABLE_MEM_TABLE), Some(0));
+        assert_eq!(db.get_property(&format!("{}0", NUM_FILES_AT_LEVEL_PREFIX)), Some("3".into()));
+
+        let levels = db.levelstats().unwrap();
+        assert_eq!(levels[0].level, 0);
+        assert_eq!(levels[0].num_files, 3);
+
+        let cfstats = db.cfstats().unwrap();
+        assert_eq!(cfstats.levels[&0].num_files, 3);
+        assert!(cfstats.levels[&0].size_bytes > 0);
+        assert_eq!(cfstats.sum.num_files, 3);
+        assert!(!cfstats.io_stalls.is_empty());
+    }
+
+    static COMPACTION_BLOCKED: AtomicBool = AtomicBool::new(true);
+
+    // holds the first compaction, so that L0 files pile up
+    struct BlockingFilter;
+
+    impl CompactionFilter for BlockingFilter {
+        fn filter(&mut self, _level: i32, _key: &[u8], _value_type: ValueType, _value: &[u8]) -> Decision {
+            while COMPACTION_BLOCKED.load(Ordering::SeqCst) {
+                thread::sleep(Duration::from_millis(1));
+            }
+            Decision::Keep
+        }
+    }
+
+    lazy_static! {
+        static ref BLOCKING_FILTER: BlockingFilter = BlockingFilter;
+    }
+
+    // releases the compaction before the DB is closed, even if an assertion fails
+    struct Unblock;
+
+    impl Drop for Unblock {
+        fn drop(&mut self) {
+            COMPACTION_BLOCKED.store(false, Ordering::SeqCst);
+        }
+    }
+
+    #[test]
+    fn write_stall_state() {
+        assert_eq!(WriteStallState::from_properties(0, 0).condition, WriteStallCondition::Normal);
+        assert_eq!(WriteStallState::from_properties(0, 1024).condition, WriteStallCondition::Delayed);
+        assert!(WriteStallState::from_properties(1, 0).is_stopped());
+
+        let tmp_dir = ::tempdir::TempDir::new_in(".", "rocks").unwrap();
+        let db = DB::open(
+            Options::default()
+                .map_db_options(|db| db.create_if_missing(true))
+                .map_cf_options(|cf| {
+                    cf.level0_file_num_compaction_trigger(2)
+                        .level0_slowdown_writes_trigger(2)
+                        .level0_stop_writes_trigger(3)
+                        .compaction_filter(&*BLOCKING_FILTER)
+                }),
+            &tmp_dir,
+        )
+        .unwrap();
+        let unblock = Unblock;
+        assert!(!db.write_stall_state().is_stalled());
+
+        // the compaction triggered by the 2nd file hangs, the 3rd file stops writes
+        for i in 0..3 {
+            assert!(db.put(&Default::default(), format!("k{}", i).as_bytes(), b"v").is_ok());
+            assert!(db.flush(&FlushOptions::default().wait(true)).is_ok());
+        }
+        let state = db.write_stall_state();
+        assert!(state.is_stopped(), "state: {:?}", state);
+        assert!(db.put(&WriteOptions::default().no_slowdown(true), b"k", b"v").is_err());
+
+        drop(unblock);
+
+        assert!(db.compact_range(&CompactRangeOptions::default(), ..).is_ok());
+        assert_eq!(db.write_stall_state().condition, WriteStallCondition::Normal);
+        assert!(db.put(&WriteOptions::default().no_slowdown(true), b"k", b"v").is_ok());
+    }
+}
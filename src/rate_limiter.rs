@@ -3,10 +3,22 @@
 
 use rocks_sys as ll;
 
+use crate::env::Priority;
 use crate::to_raw::ToRaw;
 
+/// Which IO a `RateLimiter` throttles.
+#[repr(C)]
+#[derive(Debug, Copy, Clone, PartialEq, Eq, Hash)]
+pub enum RateLimiterMode {
+    ReadsOnly,
+    WritesOnly,
+    AllIo,
+}
+
 /// `RateLimiter` object, which can be shared among RocksDB instances to
 /// control write rate of flush and compaction.
+///
+/// Clones share the same underlying limiter.
 pub struct RateLimiter {
     raw: *mut ll::rocks_ratelimiter_t,
 }
@@ -19,6 +31,17 @@ impl Drop for RateLimiter {
     }
 }
 
+impl Clone for RateLimiter {
+    fn clone(&self) -> Self {
+        RateLimiter {
+            raw: unsafe { ll::rocks_ratelimiter_clone(self.raw) },
+        }
+    }
+}
+
+unsafe impl Send for RateLimiter {}
+unsafe impl Sync for RateLimiter {}
+
 impl ToRaw<ll::rocks_ratelimiter_t> for RateLimiter {
     fn raw(&self) -> *mut ll::rocks_ratelimiter_t {
         self.raw
@@ -53,4 +76,114 @@ impl RateLimiter {
             },
         }
     }
+
+    /// Create a RateLimiter with all parameters, see `new()`.
+    ///
+    /// `mode`: which of reads and writes of flush and compaction are throttled.
+    ///
+    /// `auto_tuned`: enables dynamic adjustment of the rate limit within the range
+    /// `[rate_bytes_per_sec / 20, rate_bytes_per_sec]`, according to the recent demand
+    /// for background IO.
+    pub fn with_mode(
+        rate_bytes_per_sec: i64,
+        refill_period_us: i64,
+        fairness: i32,
+        mode: RateLimiterMode,
+        auto_tuned: bool,
+    ) -> RateLimiter {
+        RateLimiter {
+            raw: unsafe {
+                ll::rocks_ratelimiter_create_with_mode(
+                    rate_bytes_per_sec,
+                    refill_period_us,
+                    fairness,
+                    mode as _,
+                    auto_tuned as _,
+                )
+            },
+        }
+    }
+
+    /// Create an auto-tuned RateLimiter for writes, with the default refill period
+    /// of 100ms and fairness of 10. `rate_bytes_per_sec` is the upper bound.
+    pub fn new_auto_tuned(rate_bytes_per_sec: i64) -> RateLimiter {
+        RateLimiter::with_mode(rate_bytes_per_sec, 100 * 1000, 10, RateLimiterMode::WritesOnly, true)
+    }
+
+    /// This API allows user to dynamically change rate limiter's bytes per second.
+    /// REQUIRED: bytes_per_second > 0
+    pub fn set_bytes_per_second(&self, bytes_per_second: i64) {
+        assert!(bytes_per_second > 0, "bytes_per_second must be positive");
+        unsafe {
+            ll::rocks_ratelimiter_set_bytes_per_second(self.raw, bytes_per_second);
+        }
+    }
+
+    /// Current rate limit, changes over time when auto-tuned.
+    pub fn get_bytes_per_second(&self) -> i64 {
+        unsafe { ll::rocks_ratelimiter_get_bytes_per_second(self.raw) }
+    }
+
+    /// Max bytes that can be granted in a single burst.
+    pub fn get_single_burst_bytes(&self) -> i64 {
+        unsafe { ll::rocks_ratelimiter_get_single_burst_bytes(self.raw) }
+    }
+
+    /// Total bytes that go through rate limiter, of priority `pri`,
+    /// or of all priorities for `Priority::Total`. `Priority::Bottom` is not allowed.
+    pub fn get_total_bytes_through(&self, pri: Priority) -> i64 {
+        assert!(pri != Priority::Bottom, "invalid IO priority");
+        unsafe { ll::rocks_ratelimiter_get_total_bytes_through(self.raw, pri as _) }
+    }
+
+    /// Total number of requests that go through rate limiter, of priority `pri`,
+    /// or of all priorities for `Priority::Total`. `Priority::Bottom` is not allowed.
+    pub fn get_total_requests(&self, pri: Priority) -> i64 {
+        assert!(pri != Priority::Bottom, "invalid IO priority");
+        unsafe { ll::rocks_ratelimiter_get_total_requests(self.raw, pri as _) }
+    }
+
+    /// Request `bytes` through the limiter, blocking until granted, e.g. to throttle
+    /// application IO together with flush and compaction. Only `Priority::Low` and `Priority::High`
+    /// are allowed.
+    ///
+    /// Requests larger than the single burst size are split.
+    pub fn request(&self, bytes: usize, pri: Priority) {
+        assert!(pri == Priority::Low || pri == Priority::High, "invalid IO priority");
+        unsafe {
+            ll::rocks_ratelimiter_request(self.raw, bytes as i64, pri as _);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn rate_limiter() {
+        let limiter = RateLimiter::with_mode(1 << 20, 100 * 1000, 10, RateLimiterMode::AllIo, false);
+        assert_eq!(limiter.get_bytes_per_second(), 1 << 20);
+        assert_eq!(limiter.get_single_burst_bytes(), (1 << 20) / 10);
+
+        limiter.request(100, Priority::Low);
+        limiter.request(200, Priority::High);
+        assert_eq!(limiter.get_total_bytes_through(Priority::Low), 100);
+        assert_eq!(limiter.get_total_bytes_through(Priority::Total), 300);
+        assert_eq!(limiter.get_total_requests(Priority::Total), 2);
+
+        // clones share the limiter
+        limiter.clone().set_bytes_per_second(2 << 20);
+        assert_eq!(limiter.get_bytes_per_second(), 2 << 20);
+
+        let auto_tuned = RateLimiter::new_auto_tuned(10 << 20);
+        assert!(auto_tuned.get_bytes_per_second() <= 10 << 20);
+    }
+
+    #[test]
+    #[should_panic(expected = "invalid IO priority")]
+    fn rate_limiter_bottom_priority() {
+        let limiter = RateLimiter::new(1 << 20, 100 * 1000, 10);
+        limiter.get_total_bytes_through(Priority::Bottom);
+    }
 }
@@ -46,6 +46,25 @@ impl PinnableSlice {
     pub fn size(&self) -> usize {
         unsafe { ll::rocks_pinnable_slice_size(self.raw) as usize }
     }
+
+    /// Whether the data is pinned in memory owned by RocksDB, e.g. a block cache
+    /// entry or a memtable, instead of copied into the slice.
+    pub fn is_pinned(&self) -> bool {
+        unsafe { ll::rocks_pinnable_slice_is_pinned(self.raw) != 0 }
+    }
+
+    /// Release the pinned resource and make the slice empty, so that it can be
+    /// reused, e.g. by `DB::get_pinned()`.
+    ///
+    /// Holding a pinned slice keeps its block cache entry, or its memtable, alive.
+    pub fn release(&mut self) {
+        unsafe { ll::rocks_pinnable_slice_reset(self.raw) }
+    }
+
+    /// Copy the data out, releasing the pinned resource.
+    pub fn into_vec(self) -> Vec<u8> {
+        self.to_vec()
+    }
 }
 
 impl fmt::Debug for PinnableSlice {
@@ -74,18 +93,36 @@ impl AsRef<[u8]> for PinnableSlice {
     }
 }
 
-impl<'a> PartialEq<&'a [u8]> for PinnableSlice {
+impl PartialEq<&[u8]> for PinnableSlice {
     fn eq(&self, rhs: &&[u8]) -> bool {
         &self.as_ref() == rhs
     }
 }
 
-impl<'a, 'b> PartialEq<&'b [u8]> for &'a PinnableSlice {
+impl PartialEq<&[u8]> for &PinnableSlice {
     fn eq(&self, rhs: &&[u8]) -> bool {
         &self.as_ref() == rhs
     }
 }
 
+impl<const N: usize> PartialEq<[u8; N]> for PinnableSlice {
+    fn eq(&self, rhs: &[u8; N]) -> bool {
+        self.as_ref() == rhs
+    }
+}
+
+impl<const N: usize> PartialEq<&[u8; N]> for PinnableSlice {
+    fn eq(&self, rhs: &&[u8; N]) -> bool {
+        self.as_ref() == *rhs
+    }
+}
+
+impl PartialEq<Vec<u8>> for PinnableSlice {
+    fn eq(&self, rhs: &Vec<u8>) -> bool {
+        self.as_ref() == &rhs[..]
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -94,6 +131,39 @@ mod tests {
     fn pinnable_slice() {
         let s = PinnableSlice::new();
         assert_eq!(s, b"");
+        assert_eq!(&s, &b""[..]);
         assert_eq!(&format!("{:?}", s), "\"\"");
     }
+
+    #[test]
+    fn pinnable_slice_reuse() {
+        use crate::rocksdb::*;
+
+        let tmp_dir = ::tempdir::TempDir::new_in(".", "rocks").unwrap();
+        let db = DB::open(Options::default().map_db_options(|db| db.create_if_missing(true)), &tmp_dir).unwrap();
+        assert!(db.put(WriteOptions::default_instance(), b"k1", b"v1").is_ok());
+        assert!(db.put(WriteOptions::default_instance(), b"k2", b"value2").is_ok());
+        assert!(db.flush(&FlushOptions::default()).is_ok());
+
+        let mut value = PinnableSlice::new();
+        assert!(db.get_pinned(ReadOptions::default_instance(), b"k1", &mut value).is_ok());
+        assert_eq!(value, b"v1");
+        // read from a block in the block cache
+        assert!(value.is_pinned());
+        assert!(db.get_pinned(ReadOptions::default_instance(), b"k2", &mut value).is_ok());
+        assert_eq!(&*value, &b"value2"[..]);
+        assert_eq!(value, b"value2".to_vec());
+        assert!(value.starts_with(b"val"));
+
+        let cf = db.default_column_family();
+        assert!(cf.get_pinned(ReadOptions::default_instance(), b"k3", &mut value).unwrap_err().is_not_found());
+
+        assert!(db.get_pinned(ReadOptions::default_instance(), b"k1", &mut value).is_ok());
+        assert_eq!(value.into_vec(), b"v1");
+
+        let mut value = db.get(ReadOptions::default_instance(), b"k2").unwrap();
+        value.release();
+        assert!(value.is_empty());
+        assert!(!value.is_pinned());
+    }
 }
@@ -6,6 +6,8 @@
 //! define InDomain and InRange to determine which slices are in either
 //! of these sets respectively.
 
+use std::fmt;
+
 /// A `SliceTranform` is a generic pluggable way of transforming one string
 /// to another. Its primary use-case is in configuring rocksdb
 /// to store prefix blooms by setting prefix_extractor in
@@ -35,12 +37,79 @@ pub trait SliceTransform {
         true // default: use transform
     }
 
+    /// This is currently not used and remains here for backward compatibility.
+    fn in_range(&self, _dst: &[u8]) -> bool {
+        false
+    }
+
     /// Return the name of this transformation.
     fn name(&self) -> &str {
         "RustSliceTransform\0"
     }
 }
 
+/// A `SliceTransform` of closures, returned by `from_fn()`.
+pub struct FnSliceTransform<F, D> {
+    name: String,
+    transform: F,
+    in_domain: D,
+}
+
+impl<F, D> fmt::Debug for FnSliceTransform<F, D> {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.debug_struct("FnSliceTransform")
+            .field("name", &self.name.trim_end_matches('\0'))
+            .finish()
+    }
+}
+
+impl<F, D> SliceTransform for FnSliceTransform<F, D>
+where
+    F: Fn(&[u8]) -> &[u8],
+    D: Fn(&[u8]) -> bool,
+{
+    fn transform<'a>(&self, key: &'a [u8]) -> &'a [u8] {
+        (self.transform)(key)
+    }
+
+    fn in_domain(&self, key: &[u8]) -> bool {
+        (self.in_domain)(key)
+    }
+
+    fn name(&self) -> &str {
+        &self.name
+    }
+}
+
+/// A `SliceTransform` named `name`, extracting prefixes by `transform` from keys
+/// accepted by `in_domain`.
+///
+/// # Examples
+///
+/// ```no_run
+/// use rocks::rocksdb::*;
+/// use rocks::slice_transform;
+///
+/// // prefix is the table name, i.e. "table:" in "table:row"
+/// let extractor = slice_transform::from_fn(
+///     "TablePrefix",
+///     |key| &key[..=key.iter().position(|&c| c == b':').unwrap()],
+///     |key| key.contains(&b':'),
+/// );
+/// let _ = ColumnFamilyOptions::default().prefix_extractor(Box::new(extractor));
+/// ```
+pub fn from_fn<F, D>(name: &str, transform: F, in_domain: D) -> FnSliceTransform<F, D>
+where
+    F: Fn(&[u8]) -> &[u8],
+    D: Fn(&[u8]) -> bool,
+{
+    FnSliceTransform {
+        name: format!("{}\0", name),
+        transform,
+        in_domain,
+    }
+}
+
 // rust -> c part
 #[doc(hidden)]
 pub mod c {
@@ -48,6 +117,9 @@ pub mod c {
 
     use super::SliceTransform;
 
+    // Box<Box<dyn SliceTransform>>
+    type SliceTransformObj = Box<dyn SliceTransform + Sync>;
+
     #[no_mangle]
     pub unsafe extern "C" fn rust_slice_transform_call(
         t: *mut (),
@@ -55,7 +127,7 @@ pub mod c {
         ret_value: *mut *const c_char,
         ret_len: *mut usize,
     ) {
-        let trans = t as *mut Box<dyn SliceTransform>;
+        let trans = t as *mut SliceTransformObj;
         let ret = (*trans).transform(key);
         *ret_value = ret.as_ptr() as *const _;
         *ret_len = ret.len();
@@ -63,20 +135,26 @@ pub mod c {
 
     #[no_mangle]
     pub unsafe extern "C" fn rust_slice_transform_name(t: *mut ()) -> *const c_char {
-        let trans = t as *mut Box<dyn SliceTransform>;
+        let trans = t as *mut SliceTransformObj;
         (*trans).name().as_ptr() as *const _
     }
 
     #[no_mangle]
     pub unsafe extern "C" fn rust_slice_transform_in_domain(t: *mut (), key: &&[u8]) -> c_char {
-        let trans = t as *mut Box<dyn SliceTransform>;
+        let trans = t as *mut SliceTransformObj;
         (*trans).in_domain(key) as c_char
     }
 
+    #[no_mangle]
+    pub unsafe extern "C" fn rust_slice_transform_in_range(t: *mut (), dst: &&[u8]) -> c_char {
+        let trans = t as *mut SliceTransformObj;
+        (*trans).in_range(dst) as c_char
+    }
+
     #[no_mangle]
     pub unsafe extern "C" fn rust_slice_transform_drop(t: *mut ()) {
-        let trans = t as *mut Box<dyn SliceTransform>;
-        Box::from_raw(trans);
+        let trans = t as *mut SliceTransformObj;
+        drop(Box::from_raw(trans));
     }
 }
 
@@ -175,4 +253,93 @@ mod tests {
         assert!(keys.contains(&"abc-002".to_string()));
         assert!(!keys.contains(&"def-000".to_string()));
     }
+    #[test]
+    fn closure_prefix_extractor() {
+        use crate::filter_policy::FilterPolicy;
+        use crate::statistics::Statistics;
+
+        let tmp_dir = ::tempdir::TempDir::new_in(".", "rocks").unwrap();
+        let stat = Statistics::new();
+        // "<table>:<row>", keys without a table are not in domain
+        let extractor = from_fn(
+            "TablePrefix",
+            |key| &key[..=key.iter().position(|&c| c == b':').unwrap()],
+            |key| key.contains(&b':'),
+        );
+        assert_eq!(extractor.name(), "TablePrefix\0");
+        let db = DB::open(
+            Options::default()
+                .map_db_options(|db| db.create_if_missing(true).statistics(Some(stat.clone())))
+                .map_cf_options(|cf| {
+                    cf.prefix_extractor(Box::new(extractor))
+                        .memtable_prefix_bloom_size_ratio(0.1)
+                        .table_factory_block_based(
+                            BlockBasedTableOptions::default()
+                                .filter_policy(Some(FilterPolicy::new_bloom_filter(10, false)))
+                                .whole_key_filtering(false),
+                        )
+                }),
+            &tmp_dir,
+        )
+        .unwrap();
+
+        for key in &[&b"a:1"[..], b"b:1", b"b:2", b"c:1", b"no-table"] {
+            assert!(db.put(&WriteOptions::default(), key, b"").is_ok());
+        }
+        assert!(db.flush(&FlushOptions::default()).is_ok());
+
+        let mut it = db.new_iterator(&ReadOptions::default().prefix_same_as_start(true));
+        it.seek(b"b:");
+        let keys = it.keys().map(|k| k.to_vec()).collect::<Vec<_>>();
+        assert_eq!(keys, vec![b"b:1".to_vec(), b"b:2".to_vec()]);
+
+        // out of domain keys are still readable
+        assert!(db.get(&ReadOptions::default(), b"no-table").is_ok());
+        assert_eq!(db.new_iterator(&ReadOptions::default().total_order_seek(true)).keys().count(), 5);
+
+        // prefix bloom skips the SST file
+        let mut it = db.new_iterator(&ReadOptions::default().prefix_same_as_start(true));
+        it.seek(b"x:");
+        assert!(!it.is_valid());
+        assert!(stat.to_map()["rocksdb.bloom.filter.prefix.useful"] > 0);
+    }
+
+    #[test]
+    fn prefix_iterator() {
+        use crate::filter_policy::FilterPolicy;
+
+        let tmp_dir = ::tempdir::TempDir::new_in(".", "rocks").unwrap();
+        let db = DB::open(
+            Options::default()
+                .map_db_options(|db| db.create_if_missing(true))
+                .map_cf_options(|cf| {
+                    cf.prefix_extractor_fixed(2).table_factory_block_based(
+                        BlockBasedTableOptions::default()
+                            .filter_policy(Some(FilterPolicy::new_bloom_filter(10, false)))
+                            .whole_key_filtering(false),
+                    )
+                }),
+            &tmp_dir,
+        )
+        .unwrap();
+
+        for key in &[&b"a"[..], b"aa1", b"aa2", b"ab1", b"b1", b"\xff\xff1"] {
+            assert!(db.put(&WriteOptions::default(), key, b"").is_ok());
+        }
+        assert!(db.flush(&FlushOptions::default()).is_ok());
+
+        let keys = |prefix: &[u8]| {
+            db.prefix_iterator(&ReadOptions::default(), prefix)
+                .keys()
+                .map(|k| k.to_vec())
+                .collect::<Vec<_>>()
+        };
+        // a prefix seek
+        assert_eq!(keys(b"aa"), vec![b"aa1".to_vec(), b"aa2".to_vec()]);
+        // shorter and longer than the extracted prefix, a total order seek
+        assert_eq!(keys(b"a"), vec![b"a".to_vec(), b"aa1".to_vec(), b"aa2".to_vec(), b"ab1".to_vec()]);
+        assert_eq!(keys(b"aa2"), vec![b"aa2".to_vec()]);
+        assert_eq!(keys(b"\xff"), vec![b"\xff\xff1".to_vec()]);
+        assert!(keys(b"c").is_empty());
+    }
 }
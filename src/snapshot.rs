@@ -174,4 +174,31 @@ mod tests {
 
         assert_eq!(db.get_int_property("rocksdb.num-snapshots"), Some(0));
     }
+
+    #[test]
+    fn snapshot_if_latest() {
+        let tmp_dir = ::tempdir::TempDir::new_in(".", "rocks").unwrap();
+        let db = DB::open(
+            Options::default().map_db_options(|db| db.create_if_missing(true)),
+            &tmp_dir,
+        )
+        .unwrap();
+
+        assert!(db.put(&WriteOptions::default(), b"k1", b"v1").is_ok());
+        let seq = db.get_latest_sequence_number();
+        let snap = db.get_snapshot_if_latest(seq).unwrap();
+        assert_eq!(snap.get_sequence_number(), seq);
+
+        assert!(db.put(&WriteOptions::default(), b"k1", b"v2").is_ok());
+        assert_eq!(
+            db.get(&ReadOptions::default().snapshot(Some(&snap)), b"k1").unwrap(),
+            b"v1"
+        );
+        db.release_snapshot(snap);
+
+        assert!(db.get_snapshot_if_latest(seq).unwrap_err().is_not_supported());
+        let next = SequenceNumber(db.get_latest_sequence_number().0 + 1);
+        assert!(db.get_snapshot_if_latest(next).unwrap_err().is_invalid_argument());
+        assert_eq!(db.get_int_property("rocksdb.num-snapshots"), Some(0));
+    }
 }
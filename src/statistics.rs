@@ -1,5 +1,7 @@
 //! Analyze the performance of a DB
 
+use std::collections::HashMap;
+use std::ffi::CStr;
 use std::fmt;
 use std::os::raw::c_void;
 use std::ptr;
@@ -25,6 +27,26 @@ pub struct HistogramData {
     pub min: f64,
 }
 
+/// Levels of statistics to collect.
+#[repr(u8)]
+#[derive(Debug, Copy, Clone, PartialEq, Eq, Hash)]
+pub enum StatsLevel {
+    /// Disable timer stats, and skip histogram stats
+    ExceptHistogramOrTimers = 0,
+    /// Skip timer stats
+    ExceptTimers,
+    /// Collect all stats except time inside mutex lock AND time spent on
+    /// compression.
+    ExceptDetailedTimers,
+    /// Collect all stats except the counters requiring to get time inside the
+    /// mutex lock.
+    ExceptTimeForMutex,
+    /// Collect all stats, including measuring duration of mutex operations.
+    /// If getting time is expensive on the platform to run, it can
+    /// reduce scalability to more threads, especially for writes.
+    All,
+}
+
 /// Analyze the performance of a db
 pub struct Statistics {
     raw: *mut ll::rocks_statistics_t,
@@ -113,6 +135,58 @@ impl Statistics {
         Error::from_ll(status)
     }
 
+    /// Default: `ExceptDetailedTimers`
+    pub fn stats_level(&self) -> StatsLevel {
+        match unsafe { ll::rocks_statistics_get_stats_level(self.raw) } {
+            0 => StatsLevel::ExceptHistogramOrTimers,
+            1 => StatsLevel::ExceptTimers,
+            2 => StatsLevel::ExceptDetailedTimers,
+            3 => StatsLevel::ExceptTimeForMutex,
+            _ => StatsLevel::All,
+        }
+    }
+
+    /// Change the level of statistics to collect, takes effect immediately.
+    pub fn set_stats_level(&self, level: StatsLevel) {
+        unsafe {
+            ll::rocks_statistics_set_stats_level(self.raw, level as u8);
+        }
+    }
+
+    /// All ticker values, by ticker name, in one call.
+    pub fn to_map(&self) -> HashMap<String, u64> {
+        unsafe {
+            let n = ll::rocks_statistics_num_tickers();
+            let mut names = vec![ptr::null(); n];
+            let mut counts = vec![0; n];
+            ll::rocks_statistics_get_all_ticker_counts(self.raw, names.as_mut_ptr(), counts.as_mut_ptr());
+            names
+                .into_iter()
+                .map(|name| CStr::from_ptr(name).to_string_lossy().into_owned())
+                .zip(counts)
+                .collect()
+        }
+    }
+
+    /// All histograms, by histogram name, in one call.
+    pub fn to_histogram_map(&self) -> HashMap<String, HistogramData> {
+        unsafe {
+            let n = ll::rocks_statistics_num_histograms();
+            let mut names = vec![ptr::null(); n];
+            let mut data = vec![HistogramData::default(); n];
+            ll::rocks_statistics_get_all_histogram_data(
+                self.raw,
+                names.as_mut_ptr(),
+                data.as_mut_ptr() as *mut ll::rocks_histogram_data_t,
+            );
+            names
+                .into_iter()
+                .map(|name| CStr::from_ptr(name).to_string_lossy().into_owned())
+                .zip(data)
+                .collect()
+        }
+    }
+
     /* NOTE: disable write to Statistics in Rust
     pub fn record_tick(&mut self, ticker_type: Tickers, count: u64) {
         unsafe {
@@ -201,4 +275,37 @@ mod tests {
         stat.get_and_reset_ticker_count("rocksdb.block.cache.bytes.write");
         assert_eq!(stat.get_ticker_count("rocksdb.block.cache.bytes.write"), 0);
     }
+
+    #[test]
+    fn statistics_to_map() {
+        let tmp_dir = ::tempdir::TempDir::new_in(".", "rocks").unwrap();
+
+        let stat = Statistics::new();
+        assert_eq!(stat.stats_level(), StatsLevel::ExceptDetailedTimers);
+        stat.set_stats_level(StatsLevel::ExceptHistogramOrTimers);
+        assert_eq!(stat.stats_level(), StatsLevel::ExceptHistogramOrTimers);
+
+        let db = DB::open(
+            Options::default().map_db_options(|db| db.create_if_missing(true).statistics(Some(stat.clone()))),
+            &tmp_dir,
+        )
+        .unwrap();
+        assert!(db.put(&Default::default(), b"a", b"1").is_ok());
+        assert!(db.get(&Default::default(), b"a").is_ok());
+
+        let tickers = stat.to_map();
+        assert!(tickers["rocksdb.number.keys.written"] >= 1);
+        assert_eq!(tickers["rocksdb.number.keys.read"], stat.get_ticker_count("rocksdb.number.keys.read"));
+        // histograms are skipped
+        assert_eq!(stat.to_histogram_map()["rocksdb.db.write.micros"].count, 0);
+
+        stat.set_stats_level(StatsLevel::All);
+        assert!(db.put(&Default::default(), b"b", b"2").is_ok());
+        let histograms = stat.to_histogram_map();
+        assert!(histograms["rocksdb.db.write.micros"].count > 0);
+        assert_eq!(
+            histograms["rocksdb.db.write.micros"].count,
+            stat.get_histogram_data("rocksdb.db.write.micros").count
+        );
+    }
 }
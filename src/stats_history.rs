@@ -0,0 +1,184 @@
+//! Statistics history kept in memory, see `DBOptions::stats_persist_period_sec()`.
+//!
+//! Every `stats_persist_period_sec`, the DB takes a snapshot of the tickers of
+//! `DBOptions::statistics()`, and keeps the non-zero changes since the last snapshot,
+//! up to `DBOptions::stats_history_buffer_size()` bytes. Nothing is recorded
+//! without a `Statistics`.
+
+use std::collections::BTreeMap;
+use std::fmt;
+use std::iter;
+use std::marker::PhantomData;
+use std::ptr;
+use std::slice;
+
+use rocks_sys as ll;
+
+use crate::to_raw::{FromRaw, ToRaw};
+use crate::{Error, Result};
+
+/// Ticker changes over one `stats_persist_period_sec`.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct StatsSnapshot {
+    /// Unix time in seconds the snapshot was taken at.
+    pub time: u64,
+    /// Ticker names to their increments since the previous snapshot, zero increments are left out.
+    pub stats: BTreeMap<String, u64>,
+}
+
+impl StatsSnapshot {
+    /// Increment of a ticker, 0 if not changed.
+    pub fn get(&self, name: &str) -> u64 {
+        self.stats.get(name).copied().unwrap_or(0)
+    }
+}
+
+/// Iterates stats snapshots of a time range, returned by `DBRef::get_stats_history()`.
+pub struct StatsHistoryIterator<'a> {
+    raw: *mut ll::rocks_stats_history_iterator_t,
+    _marker: PhantomData<&'a ()>,
+}
+
+impl<'a> fmt::Debug for StatsHistoryIterator<'a> {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "StatsHistoryIterator({:?})", self.raw)
+    }
+}
+
+impl<'a> ToRaw<ll::rocks_stats_history_iterator_t> for StatsHistoryIterator<'a> {
+    fn raw(&self) -> *mut ll::rocks_stats_history_iterator_t {
+        self.raw
+    }
+}
+
+impl<'a> FromRaw<ll::rocks_stats_history_iterator_t> for StatsHistoryIterator<'a> {
+    unsafe fn from_ll(raw: *mut ll::rocks_stats_history_iterator_t) -> StatsHistoryIterator<'a> {
+        StatsHistoryIterator {
+            raw,
+            _marker: PhantomData,
+        }
+    }
+}
+
+impl<'a> Drop for StatsHistoryIterator<'a> {
+    fn drop(&mut self) {
+        unsafe {
+            ll::rocks_stats_history_iterator_destroy(self.raw);
+        }
+    }
+}
+
+impl<'a> StatsHistoryIterator<'a> {
+    /// Whether the iterator is positioned at a snapshot.
+    pub fn is_valid(&self) -> bool {
+        unsafe { ll::rocks_stats_history_iterator_valid(self.raw) != 0 }
+    }
+
+    /// Moves to the next snapshot.
+    ///
+    /// REQUIRES: `is_valid()`
+    ///
+    /// Rust: avoid name collision with `Iterator::next`
+    pub fn move_next(&mut self) {
+        unsafe {
+            ll::rocks_stats_history_iterator_next(self.raw);
+        }
+    }
+
+    pub fn status(&self) -> Result<()> {
+        let mut status = ptr::null_mut();
+        unsafe {
+            ll::rocks_stats_history_iterator_status(self.raw, &mut status);
+            Error::from_ll(status)
+        }
+    }
+
+    /// Unix time in seconds of the current snapshot.
+    ///
+    /// REQUIRES: `is_valid()`
+    pub fn get_stats_time(&self) -> u64 {
+        unsafe { ll::rocks_stats_history_iterator_get_stats_time(self.raw) }
+    }
+
+    /// Ticker increments of the current snapshot.
+    ///
+    /// REQUIRES: `is_valid()`
+    pub fn get_stats_map(&self) -> BTreeMap<String, u64> {
+        unsafe {
+            let len = ll::rocks_stats_history_iterator_stats_map_size(self.raw);
+            let names = ll::cxx_string_vector_create();
+            let mut values = vec![0_u64; len];
+            ll::rocks_stats_history_iterator_get_stats_map(self.raw, names, values.as_mut_ptr());
+            let stats = values
+                .into_iter()
+                .enumerate()
+                .map(|(i, value)| {
+                    let name = slice::from_raw_parts(
+                        ll::cxx_string_vector_nth(names, i) as *const u8,
+                        ll::cxx_string_vector_nth_size(names, i),
+                    );
+                    (String::from_utf8_lossy(name).into_owned(), value)
+                })
+                .collect();
+            ll::cxx_string_vector_destory(names);
+            stats
+        }
+    }
+}
+
+impl<'a> iter::Iterator for StatsHistoryIterator<'a> {
+    type Item = StatsSnapshot;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        if !self.is_valid() {
+            return None;
+        }
+        let snapshot = StatsSnapshot {
+            time: self.get_stats_time(),
+            stats: self.get_stats_map(),
+        };
+        self.move_next();
+        Some(snapshot)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::thread;
+    use std::time::{Duration, Instant};
+
+    use super::*;
+    use crate::rocksdb::*;
+    use crate::statistics::Statistics;
+
+    #[test]
+    fn stats_history() {
+        let tmp_dir = ::tempdir::TempDir::new_in(".", "rocks").unwrap();
+        let db = DB::open(
+            Options::default().map_db_options(|db| {
+                db.create_if_missing(true)
+                    .statistics(Some(Statistics::new()))
+                    .stats_persist_period_sec(1)
+                    .stats_history_buffer_size(1 << 20)
+            }),
+            &tmp_dir,
+        )
+        .unwrap();
+
+        // the first snapshot is the baseline of the next
+        let deadline = Instant::now() + Duration::from_secs(10);
+        let mut snapshots = Vec::new();
+        while snapshots.iter().all(|s: &StatsSnapshot| s.get("rocksdb.number.keys.written") == 0) {
+            assert!(Instant::now() < deadline, "no stats snapshot");
+            assert!(db.put(WriteOptions::default_instance(), b"k", b"v").is_ok());
+            thread::sleep(Duration::from_millis(200));
+            let mut iter = db.get_stats_history(0, u64::max_value()).unwrap();
+            snapshots = iter.by_ref().collect();
+            assert!(iter.status().is_ok());
+        }
+        assert!(snapshots.windows(2).all(|w| w[0].time < w[1].time));
+
+        let last = snapshots.last().unwrap().time;
+        assert_eq!(db.get_stats_history(last + 1, u64::max_value()).unwrap().count(), 0);
+    }
+}
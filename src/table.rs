@@ -24,6 +24,7 @@ use crate::cache::Cache;
 use crate::filter_policy::FilterPolicy;
 use crate::persistent_cache::PersistentCache;
 use crate::to_raw::ToRaw;
+use crate::{Error, Result};
 
 #[derive(Debug, Copy, Clone, PartialEq, Eq, Hash)]
 #[repr(C)]
@@ -81,6 +82,21 @@ impl Drop for BlockBasedTableOptions {
 }
 
 impl BlockBasedTableOptions {
+    /// Check for conflicting options, returns `InvalidArgument` if `no_block_cache`
+    /// is set together with `block_cache`, `cache_index_and_filter_blocks`,
+    /// or `pin_l0_filter_and_index_blocks_in_cache`.
+    ///
+    /// `block_cache` is silently dropped once the options are handed to
+    /// `ColumnFamilyOptions`, so that conflict is only detected here. The others are
+    /// rejected by RocksDB when opening a DB.
+    pub fn validate(&self) -> Result<()> {
+        let mut status = ptr::null_mut();
+        unsafe {
+            ll::rocks_block_based_table_options_validate(self.raw, &mut status);
+        }
+        Error::from_ll(status)
+    }
+
     // `flush_block_policy_factory` creates the instances of flush block policy.
     // which provides a configurable way to determine when to flush a block in
     // the block based tables.  If not set, table builder will use the default
@@ -376,7 +392,11 @@ impl BlockBasedTableOptions {
     }
 }
 
+/// `user_key_len` of `PlainTableOptions` for variable length keys.
+pub const PLAIN_TABLE_VARIABLE_LENGTH: u32 = 0;
+
 #[repr(u8)]
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
 pub enum EncodingType {
     /// Always write full keys without any special encoding.
     Plain,
@@ -406,6 +426,15 @@ impl Default for PlainTableOptions {
     }
 }
 
+impl Drop for PlainTableOptions {
+    /// table factory makes a copy of the options
+    fn drop(&mut self) {
+        unsafe {
+            ll::rocks_plain_table_options_destroy(self.raw);
+        }
+    }
+}
+
 impl ToRaw<ll::rocks_plain_table_options_t> for PlainTableOptions {
     fn raw(&self) -> *mut ll::rocks_plain_table_options_t {
         self.raw
@@ -415,7 +444,7 @@ impl ToRaw<ll::rocks_plain_table_options_t> for PlainTableOptions {
 impl PlainTableOptions {
     /// @user_key_len: plain table has optimization for fix-sized keys, which can
     ///                be specified via user_key_len.  Alternatively, you can pass
-    ///                `kPlainTableVariableLength` if your keys have variable
+    ///                `PLAIN_TABLE_VARIABLE_LENGTH` if your keys have variable
     ///                lengths.
     pub fn user_key_len(self, val: u32) -> Self {
         unsafe {
@@ -516,6 +545,15 @@ impl Default for CuckooTableOptions {
     }
 }
 
+impl Drop for CuckooTableOptions {
+    /// table factory makes a copy of the options
+    fn drop(&mut self) {
+        unsafe {
+            ll::rocks_cuckoo_table_options_destroy(self.raw);
+        }
+    }
+}
+
 impl ToRaw<ll::rocks_cuckoo_table_options_t> for CuckooTableOptions {
     fn raw(&self) -> *mut ll::rocks_cuckoo_table_options_t {
         self.raw
@@ -575,3 +613,70 @@ impl CuckooTableOptions {
         self
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::rocksdb::*;
+
+    #[test]
+    fn plain_table() {
+        let tmp_dir = ::tempdir::TempDir::new_in(".", "rocks").unwrap();
+        let db = DB::open(
+            Options::default()
+                .map_db_options(|db| db.create_if_missing(true).allow_mmap_reads(true))
+                .map_cf_options(|cf| {
+                    cf.prefix_extractor_fixed(4).table_factory_plain(
+                        PlainTableOptions::default()
+                            .user_key_len(8)
+                            .bloom_bits_per_key(10)
+                            .hash_table_ratio(0.75)
+                            .index_sparseness(16)
+                            .huge_page_tlb_size(0)
+                            .encoding_type(EncodingType::Prefix)
+                            .full_scan_mode(false)
+                            .store_index_in_file(true),
+                    )
+                }),
+            &tmp_dir,
+        )
+        .unwrap();
+
+        for i in 0..100 {
+            let key = format!("key{:05}", i);
+            assert!(db.put(WriteOptions::default_instance(), key.as_bytes(), b"v").is_ok());
+        }
+        assert!(db.flush(&FlushOptions::default().wait(true)).is_ok());
+        assert_eq!(db.get(ReadOptions::default_instance(), b"key00042").unwrap().as_ref(), b"v");
+        assert!(db.get(ReadOptions::default_instance(), b"key00420").is_err());
+    }
+
+    #[test]
+    fn cuckoo_table() {
+        let tmp_dir = ::tempdir::TempDir::new_in(".", "rocks").unwrap();
+        let db = DB::open(
+            Options::default()
+                .map_db_options(|db| db.create_if_missing(true).allow_mmap_reads(true))
+                .map_cf_options(|cf| {
+                    cf.table_factory_cuckoo(
+                        CuckooTableOptions::default()
+                            .hash_table_ratio(0.9)
+                            .max_search_depth(100)
+                            .cuckoo_block_size(5)
+                            .identity_as_first_hash(false)
+                            .use_module_hash(true),
+                    )
+                }),
+            &tmp_dir,
+        )
+        .unwrap();
+
+        for i in 0..100 {
+            let key = format!("key{:05}", i);
+            assert!(db.put(WriteOptions::default_instance(), key.as_bytes(), b"v").is_ok());
+        }
+        assert!(db.flush(&FlushOptions::default().wait(true)).is_ok());
+        assert_eq!(db.get(ReadOptions::default_instance(), b"key00042").unwrap().as_ref(), b"v");
+        assert!(db.get(ReadOptions::default_instance(), b"key00420").is_err());
+    }
+}
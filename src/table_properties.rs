@@ -304,13 +304,55 @@ impl<'a> TableProperties<'a> {
     }
     /// If 0, key is variable length. Otherwise number of bytes for each key.
     pub fn fixed_key_len(&self) -> u64 {
-        unsafe { ll::rocks_table_props_get_format_version(self.raw) }
+        unsafe { ll::rocks_table_props_get_fixed_key_len(self.raw) }
     }
     /// ID of column family for this SST file, corresponding to the CF identified
     /// by column_family_name.
     pub fn column_family_id(&self) -> u32 {
         unsafe { ll::rocks_table_props_get_column_family_id(self.raw) }
     }
+    /// the number of index partitions if kTwoLevelIndexSearch is used
+    pub fn index_partitions(&self) -> u64 {
+        unsafe { ll::rocks_table_props_get_index_partitions(self.raw) }
+    }
+    /// total size of index partitions, if kTwoLevelIndexSearch is used
+    pub fn top_level_index_size(&self) -> u64 {
+        unsafe { ll::rocks_table_props_get_top_level_index_size(self.raw) }
+    }
+    /// Whether the index key is user key. Otherwise it includes 8 byte of sequence
+    /// number added by internal key format.
+    pub fn index_key_is_user_key(&self) -> bool {
+        unsafe { ll::rocks_table_props_get_index_key_is_user_key(self.raw) != 0 }
+    }
+    /// Whether delta encoding is used to encode the index values.
+    pub fn index_value_is_delta_encoded(&self) -> bool {
+        unsafe { ll::rocks_table_props_get_index_value_is_delta_encoded(self.raw) != 0 }
+    }
+    /// the number of deletions in the table
+    pub fn num_deletions(&self) -> u64 {
+        unsafe { ll::rocks_table_props_get_num_deletions(self.raw) }
+    }
+    /// the number of merge operands in the table
+    pub fn num_merge_operands(&self) -> u64 {
+        unsafe { ll::rocks_table_props_get_num_merge_operands(self.raw) }
+    }
+    /// the number of range deletions in this table
+    pub fn num_range_deletions(&self) -> u64 {
+        unsafe { ll::rocks_table_props_get_num_range_deletions(self.raw) }
+    }
+    /// The time when the SST file was created.
+    /// Since SST files are immutable, this is equivalent to last modified time.
+    pub fn creation_time(&self) -> u64 {
+        unsafe { ll::rocks_table_props_get_creation_time(self.raw) }
+    }
+    /// Timestamp of the earliest key. 0 means unknown.
+    pub fn oldest_key_time(&self) -> u64 {
+        unsafe { ll::rocks_table_props_get_oldest_key_time(self.raw) }
+    }
+    /// Actual SST file creation time. 0 means unknown.
+    pub fn file_creation_time(&self) -> u64 {
+        unsafe { ll::rocks_table_props_get_file_creation_time(self.raw) }
+    }
 
     /// Name of the column family with which this SST file is associated.
     /// If column family is unknown, `column_family_name` will be an empty string.
@@ -422,6 +464,8 @@ pub enum EntryType {
     EntryDelete, // value will be empty
     EntrySingleDelete, // value will be empty
     EntryMerge,
+    EntryRangeDeletion,
+    EntryBlobIndex,
     EntryOther,
 }
 
@@ -431,6 +475,13 @@ pub enum EntryType {
 /// building. It is construced with `TablePropertiesCollectorFactory`. The methods
 /// don't need to be thread-safe, as we will create exactly one
 /// `TablePropertiesCollector` object per table and then call it sequentially
+///
+/// Install it via `ColumnFamilyOptions::table_properties_collector_factory()`.
+/// Properties added in `finish()` are persisted in the properties block of the
+/// SST file, and can be read back with `TableProperties::user_collected_properties()`,
+/// e.g. from `DB::get_properties_of_all_tables_cf()`.
+///
+/// NOTE: `name()` must be `\0`-terminated.
 pub trait TablePropertiesCollector {
     /// AddUserKey() will be called when a new key/value pair is inserted into the
     /// table.
@@ -454,9 +505,11 @@ pub trait TablePropertiesCollector {
     /// Return the human-readable properties, where the key is property name and
     /// the value is the human-readable form of value.
     ///
-    /// TODO:
+    /// Called after `finish()`. Readable properties are not persisted in the table file,
+    /// they are available from `TableProperties::readable_properties()` of a newly
+    /// built table, e.g. in `EventListener::on_table_file_created`.
     fn readable_properties(&self) -> Vec<(String, String)> {
-        unimplemented!()
+        vec![]
     }
 
     /// Return whether the output file should be further compacted
@@ -526,6 +579,20 @@ pub mod c {
         (*collector).need_compact() as c_uchar
     }
 
+    #[no_mangle]
+    pub unsafe extern "C" fn rust_table_props_collector_readable_properties(
+        c: *mut (),
+        props: *mut UserCollectedProperties,
+    ) {
+        assert!(!c.is_null());
+        let collector = c as *mut Box<dyn TablePropertiesCollector>;
+        if let Some(p) = props.as_mut() {
+            for (key, value) in (*collector).readable_properties() {
+                p.insert(&key, value.as_bytes());
+            }
+        }
+    }
+
     // yes, will be called :)
     #[no_mangle]
     pub unsafe extern "C" fn rust_table_props_collector_drop(f: *mut ()) {
@@ -564,10 +631,12 @@ pub mod c {
 #[cfg(test)]
 mod tests {
     use std::iter;
+    use std::sync::{Arc, Mutex};
     use std::time;
 
     use super::*;
     use super::super::rocksdb::*;
+    use crate::listener::{EventListener, TableFileCreationInfo};
 
     #[derive(Default)]
     pub struct MyTblPropsCollector {
@@ -666,4 +735,150 @@ mod tests {
         counters.dedup(); // assure files returned are all unique
         assert_eq!(counters.len(), 100);
     }
+
+    #[test]
+    fn table_properties_garbage() {
+        let tmp_dir = ::tempdir::TempDir::new_in("", "rocks").unwrap();
+        let db = DB::open(
+            Options::default()
+                .map_db_options(|db| db.create_if_missing(true))
+                .map_cf_options(|cf| cf.disable_auto_compactions(true)),
+            &tmp_dir,
+        )
+        .unwrap();
+
+        for i in 0..10 {
+            let key = format!("k{}", i);
+            assert!(db.put(WriteOptions::default_instance(), key.as_bytes(), b"v").is_ok());
+        }
+        assert!(db.delete(WriteOptions::default_instance(), b"k1").is_ok());
+        assert!(db.single_delete(WriteOptions::default_instance(), b"k2").is_ok());
+        assert!(db
+            .delete_range_cf(WriteOptions::default_instance(), &db.default_column_family(), b"k5", b"k7")
+            .is_ok());
+        assert!(db.flush(&FlushOptions::default().wait(true)).is_ok());
+
+        let props = db.get_properties_of_all_tables_cf(&db.default_column_family()).unwrap();
+        assert_eq!(props.len(), 1);
+        let (_, prop) = props.iter().next().unwrap();
+        // flush may drop shadowed puts and single deletes
+        assert!(prop.num_deletions() >= 2); // including the range deletion
+        assert!(prop.num_entries() > prop.num_deletions());
+        assert_eq!(prop.num_merge_operands(), 0);
+        assert_eq!(prop.num_range_deletions(), 1);
+        assert_eq!(prop.fixed_key_len(), 0);
+        assert_eq!(prop.index_partitions(), 0);
+        assert!(prop.file_creation_time() > 0);
+        assert!(prop.creation_time() > 0);
+        assert!(prop.oldest_key_time() > 0);
+    }
+
+    #[derive(Default)]
+    struct EntryCountCollector {
+        puts: u64,
+        deletes: u64,
+        merges: u64,
+    }
+
+    impl TablePropertiesCollector for EntryCountCollector {
+        fn add_user_key(&mut self, _key: &[u8], _value: &[u8], type_: EntryType, _seq: SequenceNumber, _size: u64) {
+            match type_ {
+                EntryType::EntryPut => self.puts += 1,
+                EntryType::EntryDelete | EntryType::EntrySingleDelete => self.deletes += 1,
+                EntryType::EntryMerge => self.merges += 1,
+                _ => (),
+            }
+        }
+
+        fn finish(&mut self, props: &mut UserCollectedProperties) {
+            props.insert("entry-count.puts", &self.puts.to_be_bytes());
+            props.insert("entry-count.deletes", &self.deletes.to_be_bytes());
+            props.insert("entry-count.merges", &self.merges.to_be_bytes());
+        }
+
+        fn readable_properties(&self) -> Vec<(String, String)> {
+            vec![
+                ("entry-count.puts".to_owned(), self.puts.to_string()),
+                ("entry-count.deletes".to_owned(), self.deletes.to_string()),
+            ]
+        }
+
+        fn name(&self) -> &str {
+            "EntryCountCollector\0"
+        }
+    }
+
+    struct EntryCountCollectorFactory;
+
+    impl TablePropertiesCollectorFactory for EntryCountCollectorFactory {
+        fn new_collector(&mut self, _context: Context) -> Box<dyn TablePropertiesCollector> {
+            Box::new(EntryCountCollector::default())
+        }
+
+        fn name(&self) -> &str {
+            "EntryCountCollectorFactory\0"
+        }
+    }
+
+    struct ReadablePropsListener {
+        readable: Arc<Mutex<Vec<(String, Vec<u8>)>>>,
+    }
+
+    impl EventListener for ReadablePropsListener {
+        fn on_table_file_created(&mut self, info: &TableFileCreationInfo) {
+            let props = info.table_properties();
+            let mut readable = self.readable.lock().unwrap();
+            for (k, v) in props.readable_properties().iter() {
+                readable.push((k.to_owned(), v.to_vec()));
+            }
+        }
+    }
+
+    #[test]
+    fn custom_collector_properties() {
+        let tmp_dir = ::tempdir::TempDir::new_in("", "rocks").unwrap();
+        let readable = Arc::new(Mutex::new(vec![]));
+        let db = DB::open(
+            Options::default()
+                .map_db_options(|db| {
+                    db.create_if_missing(true).add_listener(ReadablePropsListener {
+                        readable: readable.clone(),
+                    })
+                })
+                .map_cf_options(|cf| {
+                    cf.disable_auto_compactions(true)
+                        .table_properties_collector_factory(Box::new(EntryCountCollectorFactory))
+                }),
+            &tmp_dir,
+        )
+        .unwrap();
+        let cf = db.default_column_family();
+
+        for i in 0..10 {
+            let key = format!("k{}", i);
+            db.put(WriteOptions::default_instance(), key.as_bytes(), b"value").unwrap();
+        }
+        for i in 0..3 {
+            let key = format!("d{}", i);
+            db.delete(WriteOptions::default_instance(), key.as_bytes()).unwrap();
+        }
+        assert!(db.flush(&FlushOptions::default().wait(true)).is_ok());
+
+        let props = db.get_properties_of_all_tables_cf(&cf).unwrap();
+        assert_eq!(props.len(), 1);
+        for (file, prop) in props.iter() {
+            assert!(file.ends_with(".sst"));
+            assert!(prop.property_collectors_names().contains("EntryCountCollectorFactory"));
+
+            let user_prop = prop.user_collected_properties();
+            assert_eq!(&user_prop["entry-count.puts"], &10u64.to_be_bytes());
+            assert_eq!(&user_prop["entry-count.deletes"], &3u64.to_be_bytes());
+            assert_eq!(&user_prop["entry-count.merges"], &0u64.to_be_bytes());
+        }
+
+        // readable properties are not persisted, only available while building the table
+        let readable = readable.lock().unwrap();
+        assert!(readable.contains(&("entry-count.puts".to_owned(), b"10".to_vec())));
+        assert!(readable.contains(&("entry-count.deletes".to_owned(), b"3".to_vec())));
+    }
 }
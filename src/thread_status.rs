@@ -6,6 +6,7 @@
 //! thus APIs and class definitions might subject to change at this point.
 //! Will remove this comment once the APIs have been finalized.
 
+use std::collections::HashMap;
 use std::mem;
 use std::str;
 use std::slice;
@@ -25,6 +26,8 @@ pub enum ThreadType {
     LowPriority,
     /// User thread (Non-RocksDB BG thread)
     User,
+    /// RocksDB BG thread in bottom-pri thread pool
+    BottomPriority,
 }
 
 /// The type used to refer to a thread operation.
@@ -115,6 +118,7 @@ impl fmt::Debug for ThreadStatus {
             .field("operation_type", &self.operation_type())
             .field("op_elapsed_micros", &self.op_elapsed_micros())
             .field("operation_stage", &self.operation_stage())
+            .field("op_properties", &self.interpret_op_properties())
             .field("state_type", &self.state_type())
             .finish()
     }
@@ -128,7 +132,7 @@ impl ThreadStatus {
     }
 
     /// The type of the thread, it could be HIGH_PRIORITY,
-    /// LOW_PRIORITY, and USER
+    /// LOW_PRIORITY, BOTTOM_PRIORITY and USER
     pub fn thread_type(&self) -> ThreadType {
         unsafe { mem::transmute(ll::rocks_thread_status_get_thread_type(self.raw)) }
     }
@@ -182,8 +186,116 @@ impl ThreadStatus {
         }
     }
 
+    /// The op_properties of the current operation, keyed by property name.
+    ///
+    /// See `interpret_operation_properties()`.
+    pub fn interpret_op_properties(&self) -> HashMap<&'static str, u64> {
+        interpret_operation_properties(self.operation_type(), self.op_properties())
+    }
+
     /// The state (lower-level action) that the current thread is involved.
     pub fn state_type(&self) -> StateType {
         unsafe { mem::transmute(ll::rocks_thread_status_get_state_type(self.raw)) }
     }
 }
+
+/// Obtain the name of the i-th operation property of the
+/// specified operation.
+pub fn operation_property_name(op_type: OperationType, i: usize) -> &'static str {
+    const COMPACTION_PROPERTY_NAMES: &[&str] = &[
+        "JobID",
+        "InputOutputLevel",
+        "Manual/Deletion/Trivial",
+        "TotalInputBytes",
+        "BytesRead",
+        "BytesWritten",
+    ];
+    const FLUSH_PROPERTY_NAMES: &[&str] = &["JobID", "BytesMemtables", "BytesWritten"];
+
+    let names = match op_type {
+        OperationType::Compaction => COMPACTION_PROPERTY_NAMES,
+        OperationType::Flush => FLUSH_PROPERTY_NAMES,
+        OperationType::Unknown => &[],
+    };
+    names.get(i).cloned().unwrap_or("")
+}
+
+/// Translate the op_properties of the specified operation into a map from
+/// property name to its value.
+///
+/// Packed compaction properties are split, i.e. `InputOutputLevel` becomes
+/// `BaseInputLevel` and `OutputLevel`, and the prop flags become `IsManual`,
+/// `IsDeletion` and `IsTrivialMove`.
+pub fn interpret_operation_properties(op_type: OperationType, op_properties: &[u64]) -> HashMap<&'static str, u64> {
+    let num_properties = match op_type {
+        OperationType::Compaction => CompactionPropertyType::BytesWritten as usize + 1,
+        OperationType::Flush => FlushPropertyType::BytesWritten as usize + 1,
+        OperationType::Unknown => 0,
+    };
+    let mut property_map = HashMap::new();
+    for (i, &value) in op_properties.iter().enumerate().take(num_properties) {
+        if op_type == OperationType::Compaction && i == CompactionPropertyType::InputOutputLevel as usize {
+            property_map.insert("BaseInputLevel", value >> 32);
+            property_map.insert("OutputLevel", value & 0xffff_ffff);
+        } else if op_type == OperationType::Compaction && i == CompactionPropertyType::PropFlags as usize {
+            property_map.insert("IsManual", (value & 2) >> 1);
+            property_map.insert("IsDeletion", (value & 4) >> 2);
+            property_map.insert("IsTrivialMove", (value & 8) >> 3);
+        } else {
+            property_map.insert(operation_property_name(op_type, i), value);
+        }
+    }
+    property_map
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::rocksdb::*;
+
+    #[test]
+    fn interpret_properties() {
+        let props = interpret_operation_properties(OperationType::Compaction, &[7, (1 << 32) | 2, 2, 100, 50, 60]);
+        assert_eq!(props["JobID"], 7);
+        assert_eq!(props["BaseInputLevel"], 1);
+        assert_eq!(props["OutputLevel"], 2);
+        assert_eq!(props["IsManual"], 1);
+        assert_eq!(props["IsDeletion"], 0);
+        assert_eq!(props["TotalInputBytes"], 100);
+        assert_eq!(props["BytesWritten"], 60);
+        assert!(!props.contains_key("InputOutputLevel"));
+
+        let props = interpret_operation_properties(OperationType::Flush, &[3, 1024, 512, 0, 0, 0]);
+        assert_eq!(props.len(), 3);
+        assert_eq!(props["BytesMemtables"], 1024);
+
+        assert!(interpret_operation_properties(OperationType::Unknown, &[1, 2, 3]).is_empty());
+    }
+
+    #[test]
+    fn thread_list() {
+        let tmp_dir = ::tempdir::TempDir::new_in(".", "rocks").unwrap();
+        let env = Env::default_instance();
+        let db = DB::open(
+            Options::default().map_db_options(|db| db.create_if_missing(true).enable_thread_tracking(true)),
+            &tmp_dir,
+        )
+        .unwrap();
+        for i in 0..100 {
+            let key = format!("k{}", i);
+            assert!(db.put(WriteOptions::default_instance(), key.as_bytes(), b"v").is_ok());
+        }
+        assert!(db.flush(&FlushOptions::default()).is_ok());
+        assert!(db.compact_range(&CompactRangeOptions::default(), ..).is_ok());
+
+        let threads = env.get_thread_list();
+        assert!(threads
+            .iter()
+            .any(|t| t.thread_type() == ThreadType::LowPriority || t.thread_type() == ThreadType::HighPriority));
+        for t in &threads {
+            if t.operation_type() == OperationType::Unknown {
+                assert!(t.interpret_op_properties().is_empty());
+            }
+        }
+    }
+}
@@ -0,0 +1,653 @@
+//! Transactions, see `TransactionDB` and `OptimisticTransactionDB`.
+//!
+//! A transaction buffers its writes, and applies them atomically on commit. In a `TransactionDB`,
+//! the keys written (and read with `get_for_update`) are locked. In an `OptimisticTransactionDB`,
+//! nothing is locked, the keys are instead checked for conflicting writes on commit.
+//!
+//! With `DBOptions::allow_2pc`, a named transaction can be prepared before commit. Prepared
+//! transactions survive a crash, and are handed back by `TransactionDB::get_all_prepared_transactions()`
+//! after reopening, to be committed or rolled back by an external coordinator, i.e. XA-style 2PC.
+
+use std::fmt;
+use std::marker::PhantomData;
+use std::ptr;
+
+use rocks_sys as ll;
+
+use crate::db::ColumnFamilyHandle;
+use crate::iterator::Iterator;
+use crate::options::ReadOptions;
+use crate::slice::PinnableSlice;
+use crate::snapshot::Snapshot;
+use crate::to_raw::{FromRaw, ToRaw};
+use crate::{Error, Result};
+
+/// Options of a `TransactionDB`.
+pub struct TransactionDBOptions {
+    raw: *mut ll::rocks_transactiondb_options_t,
+}
+
+impl Default for TransactionDBOptions {
+    fn default() -> Self {
+        TransactionDBOptions {
+            raw: unsafe { ll::rocks_transactiondb_options_create() },
+        }
+    }
+}
+
+impl Drop for TransactionDBOptions {
+    fn drop(&mut self) {
+        unsafe {
+            ll::rocks_transactiondb_options_destroy(self.raw);
+        }
+    }
+}
+
+impl ToRaw<ll::rocks_transactiondb_options_t> for TransactionDBOptions {
+    fn raw(&self) -> *mut ll::rocks_transactiondb_options_t {
+        self.raw
+    }
+}
+
+impl TransactionDBOptions {
+    /// Specifies the maximum number of keys that can be locked at the same time
+    /// per column family.
+    ///
+    /// If the number of locked keys is greater than `max_num_locks`, transaction
+    /// writes (or `get_for_update`) will return an error.
+    /// If this value is not positive, no limit will be enforced.
+    ///
+    /// Default: -1
+    pub fn max_num_locks(self, val: i64) -> Self {
+        unsafe {
+            ll::rocks_transactiondb_options_set_max_num_locks(self.raw, val);
+        }
+        self
+    }
+
+    /// Increasing this value will increase the concurrency by dividing the lock
+    /// table (per column family) into more sub-tables, each with their own
+    /// separate mutex.
+    ///
+    /// Default: 16
+    pub fn num_stripes(self, val: usize) -> Self {
+        unsafe {
+            ll::rocks_transactiondb_options_set_num_stripes(self.raw, val);
+        }
+        self
+    }
+
+    /// If positive, specifies the default wait timeout in milliseconds when
+    /// a transaction attempts to lock a key if not specified by
+    /// `TransactionOptions::lock_timeout`.
+    ///
+    /// If 0, no waiting is done if a lock cannot instantly be acquired.
+    /// If negative, there is no timeout. Not using a timeout is not recommended
+    /// as it can lead to deadlocks.
+    ///
+    /// Default: 1000
+    pub fn transaction_lock_timeout(self, val: i64) -> Self {
+        unsafe {
+            ll::rocks_transactiondb_options_set_transaction_lock_timeout(self.raw, val);
+        }
+        self
+    }
+
+    /// If positive, specifies the wait timeout in milliseconds when writing a key
+    /// OUTSIDE of a transaction (ie by calling `DB::put()`, `merge()`, `delete()` or `write()`
+    /// directly).
+    ///
+    /// If 0, no waiting is done if a lock cannot instantly be acquired.
+    /// If negative, there is no timeout and will block indefinitely when acquiring
+    /// a lock.
+    ///
+    /// Default: 1000
+    pub fn default_lock_timeout(self, val: i64) -> Self {
+        unsafe {
+            ll::rocks_transactiondb_options_set_default_lock_timeout(self.raw, val);
+        }
+        self
+    }
+}
+
+/// Options of a `Transaction`.
+pub struct TransactionOptions {
+    raw: *mut ll::rocks_transaction_options_t,
+}
+
+impl Default for TransactionOptions {
+    fn default() -> Self {
+        TransactionOptions {
+            raw: unsafe { ll::rocks_transaction_options_create() },
+        }
+    }
+}
+
+impl Drop for TransactionOptions {
+    fn drop(&mut self) {
+        unsafe {
+            ll::rocks_transaction_options_destroy(self.raw);
+        }
+    }
+}
+
+impl ToRaw<ll::rocks_transaction_options_t> for TransactionOptions {
+    fn raw(&self) -> *mut ll::rocks_transaction_options_t {
+        self.raw
+    }
+}
+
+impl TransactionOptions {
+    /// Setting set_snapshot=true is the same as calling
+    /// `Transaction::set_snapshot()`.
+    ///
+    /// Default: false
+    pub fn set_snapshot(self, val: bool) -> Self {
+        unsafe {
+            ll::rocks_transaction_options_set_set_snapshot(self.raw, val as u8);
+        }
+        self
+    }
+
+    /// Setting to true means that before acquiring locks, this transaction will
+    /// check if doing so will cause a deadlock. If so, it will return with
+    /// `Busy`. The user should retry their transaction.
+    ///
+    /// Default: false
+    pub fn deadlock_detect(self, val: bool) -> Self {
+        unsafe {
+            ll::rocks_transaction_options_set_deadlock_detect(self.raw, val as u8);
+        }
+        self
+    }
+
+    /// If positive, specifies the wait timeout in milliseconds when
+    /// a transaction attempts to lock a key.
+    ///
+    /// If 0, no waiting is done if a lock cannot instantly be acquired.
+    /// If negative, `TransactionDBOptions::transaction_lock_timeout` will be used.
+    ///
+    /// Default: -1
+    pub fn lock_timeout(self, val: i64) -> Self {
+        unsafe {
+            ll::rocks_transaction_options_set_lock_timeout(self.raw, val);
+        }
+        self
+    }
+
+    /// Expiration duration in milliseconds. If non-negative, transactions that
+    /// last longer than this many milliseconds will fail to commit. If not set,
+    /// a forgotten transaction that is never committed, rolled back, or deleted
+    /// will never relinquish any locks it holds. This could prevent keys from
+    /// being written by other writers.
+    ///
+    /// Default: -1
+    pub fn expiration(self, val: i64) -> Self {
+        unsafe {
+            ll::rocks_transaction_options_set_expiration(self.raw, val);
+        }
+        self
+    }
+}
+
+/// Options of an optimistic `Transaction`.
+pub struct OptimisticTransactionOptions {
+    raw: *mut ll::rocks_optimistic_transaction_options_t,
+}
+
+impl Default for OptimisticTransactionOptions {
+    fn default() -> Self {
+        OptimisticTransactionOptions {
+            raw: unsafe { ll::rocks_optimistic_transaction_options_create() },
+        }
+    }
+}
+
+impl Drop for OptimisticTransactionOptions {
+    fn drop(&mut self) {
+        unsafe {
+            ll::rocks_optimistic_transaction_options_destroy(self.raw);
+        }
+    }
+}
+
+impl ToRaw<ll::rocks_optimistic_transaction_options_t> for OptimisticTransactionOptions {
+    fn raw(&self) -> *mut ll::rocks_optimistic_transaction_options_t {
+        self.raw
+    }
+}
+
+impl OptimisticTransactionOptions {
+    /// Setting set_snapshot=true is the same as calling
+    /// `Transaction::set_snapshot()`.
+    ///
+    /// Default: false
+    pub fn set_snapshot(self, val: bool) -> Self {
+        unsafe {
+            ll::rocks_optimistic_transaction_options_set_set_snapshot(self.raw, val as u8);
+        }
+        self
+    }
+}
+
+/// A transaction, created by `TransactionDB::begin_transaction()` or
+/// `OptimisticTransactionDB::begin_transaction()`.
+///
+/// Dropping a transaction which is neither committed nor prepared rolls it back. A prepared
+/// transaction stays prepared, in the WAL, until it is committed or rolled back, possibly after
+/// the DB is reopened.
+///
+/// A transaction is not thread-safe.
+pub struct Transaction<'a> {
+    raw: *mut ll::rocks_transaction_t,
+    _marker: PhantomData<&'a ()>,
+}
+
+unsafe impl<'a> Send for Transaction<'a> {}
+
+impl<'a> Drop for Transaction<'a> {
+    fn drop(&mut self) {
+        unsafe {
+            ll::rocks_transaction_destroy(self.raw);
+        }
+    }
+}
+
+impl<'a> fmt::Debug for Transaction<'a> {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.debug_struct("Transaction")
+            .field("id", &self.id())
+            .field("name", &self.name())
+            .finish()
+    }
+}
+
+impl<'a> ToRaw<ll::rocks_transaction_t> for Transaction<'a> {
+    fn raw(&self) -> *mut ll::rocks_transaction_t {
+        self.raw
+    }
+}
+
+impl<'a> FromRaw<ll::rocks_transaction_t> for Transaction<'a> {
+    unsafe fn from_ll(raw: *mut ll::rocks_transaction_t) -> Transaction<'a> {
+        Transaction {
+            raw,
+            _marker: PhantomData,
+        }
+    }
+}
+
+impl<'a> Transaction<'a> {
+    /// Set the name of the transaction, required before `prepare()`.
+    ///
+    /// The name must be unique among the transactions of the DB, and can only be set once,
+    /// before any write.
+    pub fn set_name(&self, name: &str) -> Result<()> {
+        let mut status = ptr::null_mut::<ll::rocks_status_t>();
+        unsafe {
+            ll::rocks_transaction_set_name(self.raw, name.as_ptr() as *const _, name.len(), &mut status);
+            Error::from_ll(status)
+        }
+    }
+
+    /// The name of the transaction, empty if not set.
+    pub fn name(&self) -> String {
+        let mut name = String::new();
+        unsafe {
+            ll::rocks_transaction_get_name(self.raw, &mut name as *mut String as *mut _);
+        }
+        name
+    }
+
+    /// The unique ID of the transaction in the DB.
+    pub fn id(&self) -> u64 {
+        unsafe { ll::rocks_transaction_get_id(self.raw) }
+    }
+
+    /// Prepare the transaction, the first phase of a two-phase commit.
+    ///
+    /// Writes the transaction to the WAL, without making it visible. Requires a name, and
+    /// `DBOptions::allow_2pc`.
+    pub fn prepare(&self) -> Result<()> {
+        let mut status = ptr::null_mut::<ll::rocks_status_t>();
+        unsafe {
+            ll::rocks_transaction_prepare(self.raw, &mut status);
+            Error::from_ll(status)
+        }
+    }
+
+    /// Write all batched keys to the db atomically.
+    ///
+    /// Returns `Busy` or `TimedOut` if the transaction could not be committed, e.g. it has expired.
+    pub fn commit(&self) -> Result<()> {
+        let mut status = ptr::null_mut::<ll::rocks_status_t>();
+        unsafe {
+            ll::rocks_transaction_commit(self.raw, &mut status);
+            Error::from_ll(status)
+        }
+    }
+
+    /// Discard all batched writes in this transaction, and release its locks.
+    pub fn rollback(&self) -> Result<()> {
+        let mut status = ptr::null_mut::<ll::rocks_status_t>();
+        unsafe {
+            ll::rocks_transaction_rollback(self.raw, &mut status);
+            Error::from_ll(status)
+        }
+    }
+
+    /// Take a snapshot, so that the transaction fails to commit if any key it writes (or reads
+    /// with `get_for_update`) is written outside the transaction after this call.
+    ///
+    /// Reads are not affected, unless `ReadOptions::snapshot` is set to `get_snapshot()`.
+    pub fn set_snapshot(&mut self) {
+        unsafe {
+            ll::rocks_transaction_set_snapshot(self.raw);
+        }
+    }
+
+    /// The snapshot of `set_snapshot()`, if any, owned by the transaction.
+    pub fn get_snapshot(&self) -> Option<Snapshot<'_>> {
+        unsafe {
+            let raw = ll::rocks_transaction_get_snapshot(self.raw);
+            if raw.is_null() {
+                None
+            } else {
+                Some(Snapshot::from_ll(raw as *mut _))
+            }
+        }
+    }
+
+    /// Clears the snapshot of `set_snapshot()`, conflicts are checked as of each key's first write
+    /// (or `get_for_update`) again.
+    pub fn clear_snapshot(&mut self) {
+        unsafe {
+            ll::rocks_transaction_clear_snapshot(self.raw);
+        }
+    }
+
+    /// Records the state of the transaction for future calls to `rollback_to_savepoint()`.
+    pub fn set_savepoint(&self) {
+        unsafe {
+            ll::rocks_transaction_set_savepoint(self.raw);
+        }
+    }
+
+    /// Undo all operations in this transaction since the most recent call to `set_savepoint()`
+    /// and removes the most recent savepoint.
+    ///
+    /// Returns `NotFound` if there is no savepoint.
+    pub fn rollback_to_savepoint(&self) -> Result<()> {
+        let mut status = ptr::null_mut::<ll::rocks_status_t>();
+        unsafe {
+            ll::rocks_transaction_rollback_to_savepoint(self.raw, &mut status);
+            Error::from_ll(status)
+        }
+    }
+
+    /// Read a key in this transaction, seeing its own uncommitted writes. The key is not locked.
+    pub fn get(&self, options: &ReadOptions, key: &[u8]) -> Result<PinnableSlice> {
+        self.get_raw(options, ptr::null_mut(), key)
+    }
+
+    pub fn get_cf(
+        &self,
+        options: &ReadOptions,
+        column_family: &ColumnFamilyHandle,
+        key: &[u8],
+    ) -> Result<PinnableSlice> {
+        self.get_raw(options, column_family.raw(), key)
+    }
+
+    fn get_raw(
+        &self,
+        options: &ReadOptions,
+        column_family: *mut ll::rocks_column_family_handle_t,
+        key: &[u8],
+    ) -> Result<PinnableSlice> {
+        let mut status = ptr::null_mut::<ll::rocks_status_t>();
+        let pinnable_val = PinnableSlice::new();
+        unsafe {
+            ll::rocks_transaction_get_pinnable(
+                self.raw,
+                options.raw(),
+                column_family,
+                key.as_ptr() as *const _,
+                key.len(),
+                pinnable_val.raw(),
+                &mut status,
+            );
+            Error::from_ll(status).map(|_| pinnable_val)
+        }
+    }
+
+    /// Read a key and lock it exclusively, so that no other writer can change it until this
+    /// transaction ends. In an `OptimisticTransactionDB` the key is not locked, instead the
+    /// commit fails if it has been written since.
+    ///
+    /// Returns `Busy` if there is a write conflict, `TimedOut` if the lock could not be acquired.
+    pub fn get_for_update(&self, options: &ReadOptions, key: &[u8]) -> Result<PinnableSlice> {
+        self.get_for_update_raw(options, ptr::null_mut(), key)
+    }
+
+    pub fn get_for_update_cf(
+        &self,
+        options: &ReadOptions,
+        column_family: &ColumnFamilyHandle,
+        key: &[u8],
+    ) -> Result<PinnableSlice> {
+        self.get_for_update_raw(options, column_family.raw(), key)
+    }
+
+    fn get_for_update_raw(
+        &self,
+        options: &ReadOptions,
+        column_family: *mut ll::rocks_column_family_handle_t,
+        key: &[u8],
+    ) -> Result<PinnableSlice> {
+        let mut status = ptr::null_mut::<ll::rocks_status_t>();
+        let pinnable_val = PinnableSlice::new();
+        unsafe {
+            ll::rocks_transaction_get_for_update_pinnable(
+                self.raw,
+                options.raw(),
+                column_family,
+                key.as_ptr() as *const _,
+                key.len(),
+                pinnable_val.raw(),
+                1,
+                &mut status,
+            );
+            Error::from_ll(status).map(|_| pinnable_val)
+        }
+    }
+
+    /// Undo a `get_for_update()` of the key, so that it is no longer locked, or checked for
+    /// conflicts on commit.
+    ///
+    /// Has no effect if the key has been written in this transaction, or was read by
+    /// `get_for_update()` more times than it is undone.
+    pub fn undo_get_for_update(&self, key: &[u8]) {
+        unsafe {
+            ll::rocks_transaction_undo_get_for_update(self.raw, ptr::null_mut(), key.as_ptr() as *const _, key.len());
+        }
+    }
+
+    pub fn undo_get_for_update_cf(&self, column_family: &ColumnFamilyHandle, key: &[u8]) {
+        unsafe {
+            ll::rocks_transaction_undo_get_for_update(
+                self.raw,
+                column_family.raw(),
+                key.as_ptr() as *const _,
+                key.len(),
+            );
+        }
+    }
+
+    /// An iterator over the DB merged with the uncommitted writes of this transaction.
+    ///
+    /// Keys iterated are not locked, nor checked for conflicts.
+    pub fn new_iterator(&self, options: &ReadOptions) -> Iterator<'_> {
+        unsafe { Iterator::from_ll(ll::rocks_transaction_create_iterator(self.raw, options.raw(), ptr::null_mut())) }
+    }
+
+    pub fn new_iterator_cf(&self, options: &ReadOptions, column_family: &ColumnFamilyHandle) -> Iterator<'_> {
+        unsafe {
+            Iterator::from_ll(ll::rocks_transaction_create_iterator(
+                self.raw,
+                options.raw(),
+                column_family.raw(),
+            ))
+        }
+    }
+
+    /// Put a key in this transaction, locking it in a `TransactionDB`.
+    pub fn put(&self, key: &[u8], value: &[u8]) -> Result<()> {
+        self.put_raw(ptr::null_mut(), key, value)
+    }
+
+    pub fn put_cf(&self, column_family: &ColumnFamilyHandle, key: &[u8], value: &[u8]) -> Result<()> {
+        self.put_raw(column_family.raw(), key, value)
+    }
+
+    fn put_raw(&self, column_family: *mut ll::rocks_column_family_handle_t, key: &[u8], value: &[u8]) -> Result<()> {
+        let mut status = ptr::null_mut::<ll::rocks_status_t>();
+        unsafe {
+            ll::rocks_transaction_put(
+                self.raw,
+                column_family,
+                key.as_ptr() as *const _,
+                key.len(),
+                value.as_ptr() as *const _,
+                value.len(),
+                &mut status,
+            );
+            Error::from_ll(status)
+        }
+    }
+
+    /// Delete a key in this transaction, locking it in a `TransactionDB`.
+    pub fn delete(&self, key: &[u8]) -> Result<()> {
+        self.delete_raw(ptr::null_mut(), key)
+    }
+
+    pub fn delete_cf(&self, column_family: &ColumnFamilyHandle, key: &[u8]) -> Result<()> {
+        self.delete_raw(column_family.raw(), key)
+    }
+
+    fn delete_raw(&self, column_family: *mut ll::rocks_column_family_handle_t, key: &[u8]) -> Result<()> {
+        let mut status = ptr::null_mut::<ll::rocks_status_t>();
+        unsafe {
+            ll::rocks_transaction_delete(
+                self.raw,
+                column_family,
+                key.as_ptr() as *const _,
+                key.len(),
+                &mut status,
+            );
+            Error::from_ll(status)
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::error::Code;
+    use crate::rocksdb::*;
+
+    #[test]
+    fn transaction_two_phase_commit() {
+        let tmp_dir = ::tempdir::TempDir::new_in(".", "rocks").unwrap();
+        let opts = Options::default().map_db_options(|db| db.create_if_missing(true).allow_2pc(true));
+        {
+            let db = TransactionDB::open(&opts, &TransactionDBOptions::default(), &tmp_dir).unwrap();
+
+            let txn = db.begin_transaction(WriteOptions::default_instance(), &TransactionOptions::default());
+            assert!(txn.put(b"k1", b"v1").is_ok());
+            // unnamed
+            assert!(txn.prepare().is_err());
+            assert!(txn.rollback().is_ok());
+
+            let txn = db.begin_transaction(WriteOptions::default_instance(), &TransactionOptions::default());
+            assert!(txn.set_name("xid-1").is_ok());
+            assert_eq!(txn.name(), "xid-1");
+            assert!(txn.put(b"k1", b"v1").is_ok());
+            assert_eq!(txn.get(ReadOptions::default_instance(), b"k1").unwrap(), b"v1");
+            assert!(txn.prepare().is_ok());
+            assert!(db.get(ReadOptions::default_instance(), b"k1").unwrap_err().is_not_found());
+
+            let txn2 = db.begin_transaction(WriteOptions::default_instance(), &TransactionOptions::default());
+            assert!(txn2.set_name("xid-2").is_ok());
+            assert!(txn2.put(b"k2", b"v2").is_ok());
+            assert!(txn2.prepare().is_ok());
+            assert!(txn2.commit().is_ok());
+            assert_eq!(db.get(ReadOptions::default_instance(), b"k2").unwrap(), b"v2");
+        }
+
+        // recover the prepared transaction
+        let db = TransactionDB::open(&opts, &TransactionDBOptions::default(), &tmp_dir).unwrap();
+        let txns = db.get_all_prepared_transactions();
+        assert_eq!(txns.len(), 1);
+        assert_eq!(txns[0].name(), "xid-1");
+        assert!(txns[0].commit().is_ok());
+        assert_eq!(db.get(ReadOptions::default_instance(), b"k1").unwrap(), b"v1");
+    }
+
+    #[test]
+    fn optimistic_transaction_snapshot() {
+        let tmp_dir = ::tempdir::TempDir::new_in(".", "rocks").unwrap();
+        let db = OptimisticTransactionDB::open(
+            Options::default().map_db_options(|db| db.create_if_missing(true)),
+            &tmp_dir,
+        )
+        .unwrap();
+        let wopts = WriteOptions::default_instance();
+        assert!(db.put(wopts, b"k1", b"v1").is_ok());
+
+        // conflict on a key read for update
+        let txn = db.begin_transaction(wopts, &OptimisticTransactionOptions::default());
+        assert_eq!(txn.get_for_update(ReadOptions::default_instance(), b"k1").unwrap(), b"v1");
+        assert!(db.put(wopts, b"k1", b"v2").is_ok());
+        assert!(txn.put(b"k2", b"v2").is_ok());
+        assert_eq!(txn.commit().unwrap_err().code(), Code::Busy);
+
+        // not after undo
+        let txn = db.begin_transaction(wopts, &OptimisticTransactionOptions::default());
+        assert!(txn.get_for_update(ReadOptions::default_instance(), b"k1").is_ok());
+        txn.undo_get_for_update(b"k1");
+        assert!(db.put(wopts, b"k1", b"v3").is_ok());
+        assert!(txn.put(b"k2", b"v2").is_ok());
+        assert!(txn.commit().is_ok());
+
+        // conflict on a key written after the snapshot
+        let mut txn = db.begin_transaction(wopts, &OptimisticTransactionOptions::default());
+        assert!(txn.get_snapshot().is_none());
+        txn.set_snapshot();
+        assert!(db.put(wopts, b"k2", b"v4").is_ok());
+        {
+            let snap = txn.get_snapshot();
+            assert!(snap.is_some());
+            let ropts = ReadOptions::default().snapshot(snap.as_ref());
+            assert_eq!(txn.get(&ropts, b"k2").unwrap(), b"v2");
+        }
+        assert!(txn.put(b"k3", b"v3").is_ok());
+        let keys = txn
+            .new_iterator(ReadOptions::default_instance())
+            .map(|(k, _)| k.to_vec())
+            .collect::<Vec<_>>();
+        assert_eq!(keys, vec![b"k1".to_vec(), b"k2".to_vec(), b"k3".to_vec()]);
+        assert!(txn.put(b"k2", b"v5").is_ok());
+        assert_eq!(txn.commit().unwrap_err().code(), Code::Busy);
+
+        // not without
+        let mut txn = db.begin_transaction(wopts, &OptimisticTransactionOptions::default().set_snapshot(true));
+        assert!(txn.get_snapshot().is_some());
+        txn.clear_snapshot();
+        assert!(txn.get_snapshot().is_none());
+        assert!(db.put(wopts, b"k2", b"v6").is_ok());
+        assert!(txn.put(b"k2", b"v7").is_ok());
+        assert!(txn.commit().is_ok());
+        assert_eq!(db.get(ReadOptions::default_instance(), b"k2").unwrap(), b"v7");
+    }
+}
@@ -2,6 +2,7 @@
 
 use std::fmt;
 use std::iter;
+use std::os::raw::c_void;
 use std::ptr;
 
 use rocks_sys as ll;
@@ -42,6 +43,31 @@ pub struct LogFile {
     pub size_in_bytes: u64,
 }
 
+impl LogFile {
+    /// Converts and destroys a list of log files.
+    pub(crate) unsafe fn from_ll_list(cfiles: *mut ll::rocks_logfiles_t) -> Vec<LogFile> {
+        let num_files = ll::rocks_logfiles_size(cfiles);
+        let mut files = Vec::with_capacity(num_files);
+        for i in 0..num_files {
+            let mut path_name = String::new();
+            ll::rocks_logfiles_nth_path_name(cfiles, i, &mut path_name as *mut String as *mut c_void);
+            let file_type = match ll::rocks_logfiles_nth_type(cfiles, i) {
+                0 => WalFileType::Archived,
+                _ => WalFileType::Alive,
+            };
+            files.push(LogFile {
+                path_name,
+                log_number: ll::rocks_logfiles_nth_log_number(cfiles, i),
+                file_type,
+                start_sequence: ll::rocks_logfiles_nth_start_sequence(cfiles, i).into(),
+                size_in_bytes: ll::rocks_logfiles_nth_file_size(cfiles, i),
+            })
+        }
+        ll::rocks_logfiles_destroy(cfiles);
+        files
+    }
+}
+
 impl fmt::Debug for LogFile {
     fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
         write!(
@@ -59,6 +85,12 @@ pub struct BatchResult {
     pub write_batch: WriteBatch,
 }
 
+impl From<BatchResult> for (SequenceNumber, WriteBatch) {
+    fn from(batch: BatchResult) -> (SequenceNumber, WriteBatch) {
+        (batch.sequence, batch.write_batch)
+    }
+}
+
 /// A `TransactionLogIterator` is used to iterate over the transactions in a db.
 /// One run of the iterator is continuous, i.e. the iterator will stop at the
 /// beginning of any gap in sequences
@@ -132,6 +164,30 @@ impl TransactionLogIterator {
     }
 }
 
+impl TransactionLogIterator {
+    /// Returns the current batch and advances the iterator.
+    ///
+    /// Returns `Ok(None)` at the end of the log, or the iterator's error status.
+    pub fn try_next(&mut self) -> Result<Option<BatchResult>> {
+        if !self.is_valid() {
+            return self.status().map(|_| None);
+        }
+        let batch = self.get_batch();
+        self.move_next();
+        Ok(Some(batch))
+    }
+
+    /// Converts into an iterator of `(SequenceNumber, WriteBatch)`, which, unlike
+    /// iterating `TransactionLogIterator` directly, yields the error that ended
+    /// iteration, if any, as its last item.
+    pub fn into_results(self) -> TransactionLogResults {
+        TransactionLogResults {
+            iter: self,
+            done: false,
+        }
+    }
+}
+
 impl iter::Iterator for TransactionLogIterator {
     type Item = BatchResult;
 
@@ -146,6 +202,36 @@ impl iter::Iterator for TransactionLogIterator {
     }
 }
 
+/// Iterator of decoded WAL batches, returned by `TransactionLogIterator::into_results()`.
+#[derive(Debug)]
+pub struct TransactionLogResults {
+    iter: TransactionLogIterator,
+    done: bool,
+}
+
+impl iter::Iterator for TransactionLogResults {
+    type Item = Result<(SequenceNumber, WriteBatch)>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        if self.done {
+            return None;
+        }
+        match self.iter.try_next() {
+            Ok(Some(batch)) => Some(Ok(batch.into())),
+            Ok(None) => {
+                self.done = true;
+                None
+            }
+            Err(e) => {
+                self.done = true;
+                Some(Err(e))
+            }
+        }
+    }
+}
+
+impl iter::FusedIterator for TransactionLogResults {}
+
 #[cfg(test)]
 mod tests {
     use super::super::rocksdb::*;
@@ -208,5 +294,14 @@ mod tests {
             // first batch will contains current since seq_no, so jump backwards
             assert!(batch.sequence.0 > 20 - 3);
         }
+
+        let mut last_seq = 0;
+        for ret in db.get_updates_since(20.into()).unwrap().into_results() {
+            let (seq, batch) = ret.unwrap();
+            assert!(seq.0 > last_seq);
+            assert_eq!(batch.count(), 3);
+            last_seq = seq.0;
+        }
+        assert_eq!(last_seq + 3, db.get_latest_sequence_number().0 + 1);
     }
 }
@@ -0,0 +1,287 @@
+//! Column families of typed keys and values.
+//!
+//! `TypedCf<K, V>` wraps a `ColumnFamily`, encoding keys and values with the
+//! `Encode` and `Decode` traits. Integers are encoded with `key_encoding`, so that
+//! keys iterate in numeric order. Implement the traits for application types,
+//! e.g. with a serde format, or `KeyEncoder` for compound keys.
+//!
+//! `TypedCf::get_with()` passes the stored bytes to a closure without copying,
+//! for values that can be read in place.
+
+use std::fmt;
+use std::marker::PhantomData;
+
+use crate::db::ColumnFamily;
+use crate::iterator::Iterator;
+use crate::key_encoding::{decode_i64, decode_u64, encode_i64, encode_u64};
+use crate::options::{ReadOptions, WriteOptions};
+use crate::write_batch::WriteBatch;
+use crate::{Error, Result};
+
+/// Encodes a key or value.
+pub trait Encode {
+    fn encode(&self, buf: &mut Vec<u8>);
+
+    fn to_bytes(&self) -> Vec<u8> {
+        let mut buf = Vec::new();
+        self.encode(&mut buf);
+        buf
+    }
+}
+
+/// Decodes a key or value encoded by `Encode`.
+pub trait Decode: Sized {
+    /// Fails with a `Corruption` error if `bytes` is not a valid encoding.
+    fn decode(bytes: &[u8]) -> Result<Self>;
+}
+
+fn corruption(what: &str) -> Error {
+    Error::corruption(&format!("typed cf: invalid {} encoding", what))
+}
+
+impl Encode for [u8] {
+    fn encode(&self, buf: &mut Vec<u8>) {
+        buf.extend_from_slice(self);
+    }
+}
+
+impl Encode for Vec<u8> {
+    fn encode(&self, buf: &mut Vec<u8>) {
+        buf.extend_from_slice(self);
+    }
+}
+
+impl Decode for Vec<u8> {
+    fn decode(bytes: &[u8]) -> Result<Self> {
+        Ok(bytes.to_vec())
+    }
+}
+
+impl Encode for str {
+    fn encode(&self, buf: &mut Vec<u8>) {
+        buf.extend_from_slice(self.as_bytes());
+    }
+}
+
+impl Encode for String {
+    fn encode(&self, buf: &mut Vec<u8>) {
+        buf.extend_from_slice(self.as_bytes());
+    }
+}
+
+impl Decode for String {
+    fn decode(bytes: &[u8]) -> Result<Self> {
+        String::from_utf8(bytes.to_vec()).map_err(|_| corruption("string"))
+    }
+}
+
+impl Encode for u64 {
+    fn encode(&self, buf: &mut Vec<u8>) {
+        buf.extend_from_slice(&encode_u64(*self));
+    }
+}
+
+impl Decode for u64 {
+    fn decode(bytes: &[u8]) -> Result<Self> {
+        decode_u64(bytes).ok_or_else(|| corruption("u64"))
+    }
+}
+
+impl Encode for i64 {
+    fn encode(&self, buf: &mut Vec<u8>) {
+        buf.extend_from_slice(&encode_i64(*self));
+    }
+}
+
+impl Decode for i64 {
+    fn decode(bytes: &[u8]) -> Result<Self> {
+        decode_i64(bytes).ok_or_else(|| corruption("i64"))
+    }
+}
+
+/// A column family of `K => V`.
+pub struct TypedCf<K, V> {
+    cf: ColumnFamily,
+    _marker: PhantomData<fn() -> (K, V)>,
+}
+
+impl<K, V> Clone for TypedCf<K, V> {
+    fn clone(&self) -> Self {
+        TypedCf {
+            cf: self.cf.clone(),
+            _marker: PhantomData,
+        }
+    }
+}
+
+impl<K, V> fmt::Debug for TypedCf<K, V> {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.debug_tuple("TypedCf").field(&self.cf).finish()
+    }
+}
+
+impl<K: Encode + Decode, V: Encode + Decode> TypedCf<K, V> {
+    pub fn new(cf: ColumnFamily) -> TypedCf<K, V> {
+        TypedCf {
+            cf,
+            _marker: PhantomData,
+        }
+    }
+
+    /// The underlying column family, for untyped access.
+    pub fn column_family(&self) -> &ColumnFamily {
+        &self.cf
+    }
+
+    pub fn get(&self, options: &ReadOptions, key: &K) -> Result<Option<V>> {
+        self.get_with(options, key, V::decode)?.transpose()
+    }
+
+    /// Pass the stored value of `key` to `f`, without copying it out.
+    pub fn get_with<R, F: FnOnce(&[u8]) -> R>(&self, options: &ReadOptions, key: &K, f: F) -> Result<Option<R>> {
+        self.cf
+            .get_opt(options, &key.to_bytes())
+            .map(|value| value.map(|v| f(&v)))
+    }
+
+    pub fn put(&self, options: &WriteOptions, key: &K, value: &V) -> Result<()> {
+        self.cf.put(options, &key.to_bytes(), &value.to_bytes())
+    }
+
+    pub fn delete(&self, options: &WriteOptions, key: &K) -> Result<()> {
+        self.cf.delete(options, &key.to_bytes())
+    }
+
+    /// Merge an operand, which is encoded separately from values, see `MergeOperator`.
+    pub fn merge<O: Encode + ?Sized>(&self, options: &WriteOptions, key: &K, operand: &O) -> Result<()> {
+        self.cf.merge(options, &key.to_bytes(), &operand.to_bytes())
+    }
+
+    /// Add a put to `batch`, for atomic writes across typed column families.
+    pub fn batch_put<'b>(&self, batch: &'b mut WriteBatch, key: &K, value: &V) -> &'b mut WriteBatch {
+        batch.put_cf(&self.cf, &key.to_bytes(), &value.to_bytes())
+    }
+
+    pub fn batch_delete<'b>(&self, batch: &'b mut WriteBatch, key: &K) -> &'b mut WriteBatch {
+        batch.delete_cf(&self.cf, &key.to_bytes())
+    }
+
+    pub fn batch_merge<'b, O: Encode + ?Sized>(
+        &self,
+        batch: &'b mut WriteBatch,
+        key: &K,
+        operand: &O,
+    ) -> &'b mut WriteBatch {
+        batch.merge_cf(&self.cf, &key.to_bytes(), &operand.to_bytes())
+    }
+
+    /// Iterate all entries, in the order of encoded keys.
+    pub fn iter(&self, options: &ReadOptions) -> TypedIter<'_, K, V> {
+        let mut iter = self.cf.new_iterator(options);
+        iter.seek_to_first();
+        TypedIter::new(iter)
+    }
+
+    /// Iterate entries from `key` on.
+    pub fn iter_from(&self, options: &ReadOptions, key: &K) -> TypedIter<'_, K, V> {
+        let mut iter = self.cf.new_iterator(options);
+        iter.seek(&key.to_bytes());
+        TypedIter::new(iter)
+    }
+}
+
+/// Iterates decoded entries of a `TypedCf`.
+///
+/// Yields an error and stops at the first entry that fails to decode, or an error of the
+/// underlying iterator.
+pub struct TypedIter<'a, K, V> {
+    iter: Iterator<'a>,
+    done: bool,
+    _marker: PhantomData<fn() -> (K, V)>,
+}
+
+impl<'a, K, V> fmt::Debug for TypedIter<'a, K, V> {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.debug_struct("TypedIter").field("done", &self.done).finish()
+    }
+}
+
+impl<'a, K: Decode, V: Decode> TypedIter<'a, K, V> {
+    fn new(iter: Iterator<'a>) -> Self {
+        TypedIter {
+            iter,
+            done: false,
+            _marker: PhantomData,
+        }
+    }
+
+    /// The underlying iterator, positioned at the next entry.
+    pub fn into_inner(self) -> Iterator<'a> {
+        self.iter
+    }
+}
+
+impl<'a, K: Decode, V: Decode> ::std::iter::Iterator for TypedIter<'a, K, V> {
+    type Item = Result<(K, V)>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        if self.done {
+            return None;
+        }
+        if !self.iter.is_valid() {
+            self.done = true;
+            return self.iter.status().err().map(Err);
+        }
+        let entry = K::decode(self.iter.key()).and_then(|k| V::decode(self.iter.value()).map(|v| (k, v)));
+        if entry.is_err() {
+            self.done = true;
+        } else {
+            self.iter.next();
+        }
+        Some(entry)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::rocksdb::*;
+
+    #[test]
+    fn typed_column_family() {
+        let tmp_dir = ::tempdir::TempDir::new_in(".", "rocks").unwrap();
+        let db = DB::open(
+            Options::default().map_db_options(|db| db.create_if_missing(true)),
+            &tmp_dir,
+        )
+        .unwrap();
+        let cf = db.create_column_family(&ColumnFamilyOptions::default(), "typed").unwrap();
+        let users: TypedCf<i64, String> = TypedCf::new(cf);
+
+        let wopts = WriteOptions::default_instance();
+        let ropts = ReadOptions::default_instance();
+        for &id in &[3, -7, 100, 0] {
+            assert!(users.put(wopts, &id, &format!("user{}", id)).is_ok());
+        }
+        assert_eq!(users.get(ropts, &-7).unwrap(), Some("user-7".to_string()));
+        assert_eq!(users.get(ropts, &1).unwrap(), None);
+        assert_eq!(users.get_with(ropts, &100, |v| v.len()).unwrap(), Some(7));
+
+        let mut batch = WriteBatch::new();
+        users.batch_put(&mut batch, &42, &"user42".to_string());
+        users.batch_delete(&mut batch, &0);
+        assert!(db.write(wopts, &batch).is_ok());
+
+        // keys in numeric order, negatives first
+        let ids: Vec<i64> = users.iter(ropts).map(|e| e.unwrap().0).collect();
+        assert_eq!(ids, vec![-7, 3, 42, 100]);
+        let ids: Vec<i64> = users.iter_from(ropts, &4).map(|e| e.unwrap().0).collect();
+        assert_eq!(ids, vec![42, 100]);
+
+        // an entry that fails to decode ends iteration
+        assert!(users.column_family().put(wopts, b"\xffbad", b"value").is_ok());
+        let entries: Vec<_> = users.iter_from(ropts, &1000).collect();
+        assert_eq!(entries.len(), 1);
+        assert!(entries[0].is_err());
+    }
+}
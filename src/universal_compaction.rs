@@ -1,7 +1,6 @@
 //! Universal style of compaction.
 
-use std::os::raw::c_uint;
-use std::mem;
+use std::os::raw::{c_int, c_uint};
 
 use rocks_sys as ll;
 
@@ -10,6 +9,7 @@ use crate::to_raw::ToRaw;
 /// Algorithm used to make a compaction request stop picking new files
 /// into a single compaction run
 #[repr(C)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
 pub enum CompactionStopStyle {
     /// pick files of similar size
     SimilarSize,
@@ -121,13 +121,18 @@ impl CompactionOptionsUniversal {
     /// Default: kCompactionStopStyleTotalSize
     pub fn stop_style(self, val: CompactionStopStyle) -> Self {
         unsafe {
-            ll::rocks_universal_compaction_options_set_stop_style(self.raw, mem::transmute(val));
+            ll::rocks_universal_compaction_options_set_stop_style(self.raw, val as c_int);
         }
         self
     }
 
     /// Option to optimize the universal multi level compaction by enabling
     /// trivial move for non overlapping files.
+    ///
+    /// Set per column family via `ColumnFamilyOptions::compaction_options_universal()`,
+    /// or change it on an open DB with
+    /// `set_options([("compaction_options_universal", "{allow_trivial_move=true}")])`.
+    ///
     /// Default: false
     pub fn allow_trivial_move(self, val: bool) -> Self {
         unsafe {
@@ -136,3 +141,56 @@ impl CompactionOptionsUniversal {
         self
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::advanced_options::CompactionStyle;
+    use crate::options::ColumnFamilyOptions;
+
+    #[test]
+    fn universal_options_string_round_trip() {
+        let opts = ColumnFamilyOptions::default()
+            .compaction_style(CompactionStyle::CompactionStyleUniversal)
+            .compaction_options_universal(
+                CompactionOptionsUniversal::default()
+                    .size_ratio(3)
+                    .min_merge_width(4)
+                    .max_merge_width(16)
+                    .max_size_amplification_percent(150)
+                    .compression_size_percent(60)
+                    .stop_style(CompactionStopStyle::SimilarSize)
+                    .allow_trivial_move(true),
+            );
+        let fields = [
+            "size_ratio=3",
+            "min_merge_width=4",
+            "max_merge_width=16",
+            "max_size_amplification_percent=150",
+            "compression_size_percent=60",
+            "stop_style=kCompactionStopStyleSimilarSize",
+            "allow_trivial_move=true",
+        ];
+        let s = opts.to_options_string().unwrap();
+        for field in &fields {
+            assert!(s.contains(field), "{} not in {}", field, s);
+        }
+
+        let parsed = ColumnFamilyOptions::from_options_string(&ColumnFamilyOptions::default(), &s).unwrap();
+        let reparsed = parsed.to_options_string().unwrap();
+        for field in &fields {
+            assert!(reparsed.contains(field), "{} not in {}", field, reparsed);
+        }
+
+        let changed = ColumnFamilyOptions::from_options_string(
+            &parsed,
+            "compaction_options_universal={allow_trivial_move=false}",
+        )
+        .unwrap()
+        .to_options_string()
+        .unwrap();
+        assert!(changed.contains("allow_trivial_move=false"), "{}", changed);
+
+        assert!(ColumnFamilyOptions::from_options_string(&parsed, "no_such_option=1").is_err());
+    }
+}
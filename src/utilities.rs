@@ -1,9 +1,13 @@
+use std::collections::HashMap;
 use std::ffi::{CStr, CString};
+use std::fmt;
 use std::ptr;
 use std::path::Path;
 
-use crate::db::ColumnFamilyDescriptor;
-use crate::options::{ColumnFamilyOptions, DBOptions};
+use crate::db::{ColumnFamilyDescriptor, ColumnFamilyHandle, DB};
+use crate::options::{ColumnFamilyOptions, DBOptions, FlushOptions};
+use crate::properties;
+use crate::transaction_log::WalFileType;
 use crate::to_raw::{FromRaw, ToRaw};
 use crate::{Error, Result};
 
@@ -36,6 +40,169 @@ pub fn load_latest_options(path: &str) -> Result<(DBOptions, Vec<ColumnFamilyDes
     Ok((db_opt, cf_descs))
 }
 
+type Fixup = Box<dyn Fn(ColumnFamilyOptions) -> ColumnFamilyOptions + Send + Sync>;
+
+/// Column family options fixups, applied by `DB::open_with_fixups()` to every column family
+/// found in a DB.
+///
+/// Lets generic tooling open application DBs whose column families need a comparator or
+/// merge operator that can't be derived from the DB itself, without hardcoding every
+/// column family name.
+///
+/// Patterns are column family names where `*` matches any sequence of characters, e.g.
+/// `"counters_*"`. Fixups of all matching patterns are applied in registration order.
+#[derive(Default)]
+pub struct ColumnFamilyFixups {
+    fixups: Vec<(String, Fixup)>,
+}
+
+impl fmt::Debug for ColumnFamilyFixups {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.debug_list().entries(self.fixups.iter().map(|(pattern, _)| pattern)).finish()
+    }
+}
+
+impl ColumnFamilyFixups {
+    pub fn new() -> ColumnFamilyFixups {
+        ColumnFamilyFixups::default()
+    }
+
+    /// Register a fixup for column families whose name matches `pattern`.
+    pub fn add<F>(mut self, pattern: &str, f: F) -> Self
+    where
+        F: Fn(ColumnFamilyOptions) -> ColumnFamilyOptions + Send + Sync + 'static,
+    {
+        self.fixups.push((pattern.to_owned(), Box::new(f)));
+        self
+    }
+
+    /// Whether any fixup applies to the column family `name`.
+    pub fn matches(&self, name: &str) -> bool {
+        self.fixups.iter().any(|(pattern, _)| glob_match(pattern, name))
+    }
+
+    /// Apply all fixups matching the column family `name` to `options`.
+    pub fn apply(&self, name: &str, options: ColumnFamilyOptions) -> ColumnFamilyOptions {
+        self.fixups
+            .iter()
+            .filter(|(pattern, _)| glob_match(pattern, name))
+            .fold(options, |opts, (_, f)| f(opts))
+    }
+}
+
+/// `*` matches any sequence, everything else matches literally.
+fn glob_match(pattern: &str, name: &str) -> bool {
+    let mut parts = pattern.split('*');
+    // split always yields at least one item
+    let first = parts.next().unwrap();
+    if !name.starts_with(first) {
+        return false;
+    }
+    let mut rest = &name[first.len()..];
+    let parts: Vec<&str> = parts.collect();
+    match parts.split_last() {
+        None => rest.is_empty(),
+        Some((last, middle)) => {
+            for part in middle {
+                match rest.find(part) {
+                    Some(pos) => rest = &rest[pos + part.len()..],
+                    None => return false,
+                }
+            }
+            rest.len() >= last.len() && rest.ends_with(last)
+        },
+    }
+}
+
+/// Flush policy that bounds WAL retention per column family.
+///
+/// A WAL file can only be deleted after every column family with data in it has flushed.
+/// A cold column family that receives a trickle of writes never fills its memtable, so it
+/// pins the oldest WAL forever. `max_total_wal_size` works around this by flushing every
+/// column family, this policy only flushes the ones actually holding the old WAL files.
+///
+/// Call `check()` periodically, e.g. after each write batch or from a timer thread.
+pub struct WalFlushPolicy {
+    max_wal_bytes: u64,
+    flush_options: FlushOptions,
+    // cf id => oldest log number the cf's memtables may hold data from
+    pinned_since: HashMap<u32, u64>,
+    last_log_number: Option<u64>,
+}
+
+impl fmt::Debug for WalFlushPolicy {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.debug_struct("WalFlushPolicy")
+            .field("max_wal_bytes", &self.max_wal_bytes)
+            .field("pinned_since", &self.pinned_since)
+            .finish()
+    }
+}
+
+impl WalFlushPolicy {
+    /// Flush pinning column families once alive WAL files exceed `max_wal_bytes` in total.
+    pub fn new(max_wal_bytes: u64) -> WalFlushPolicy {
+        WalFlushPolicy {
+            max_wal_bytes,
+            flush_options: FlushOptions::default().wait(false),
+            pinned_since: HashMap::new(),
+            last_log_number: None,
+        }
+    }
+
+    /// Options used when flushing, defaults to a non-waiting flush.
+    pub fn flush_options(mut self, options: FlushOptions) -> Self {
+        self.flush_options = options;
+        self
+    }
+
+    /// Check WAL usage of `column_families`, flushing those pinning the oldest alive WAL
+    /// file when the limit is exceeded.
+    ///
+    /// Returns names of the flushed column families.
+    pub fn check(&mut self, db: &DB, column_families: &[&ColumnFamilyHandle]) -> Result<Vec<String>> {
+        let wal_files = db.get_sorted_wal_files()?;
+        let alive = wal_files.iter().filter(|f| f.file_type == WalFileType::Alive);
+        let total_bytes = alive.clone().map(|f| f.size_in_bytes).sum::<u64>();
+        let oldest_log = alive.clone().map(|f| f.log_number).min();
+        let latest_log = alive.map(|f| f.log_number).max();
+
+        for cf in column_families {
+            let num_entries = [
+                properties::NUM_ENTRIES_ACTIVE_MEM_TABLE,
+                properties::NUM_ENTRIES_IMM_MEM_TABLES,
+                properties::NUM_DELETES_ACTIVE_MEM_TABLE,
+                properties::NUM_DELETES_IMM_MEM_TABLES,
+            ]
+            .iter()
+            .map(|prop| db.get_int_property_cf(cf, prop).unwrap_or(0))
+            .sum::<u64>();
+            if num_entries == 0 {
+                self.pinned_since.remove(&cf.id());
+            } else {
+                // the memtable was empty at last check, so its data is no older than the log seen then
+                let since = self.last_log_number.or(oldest_log).unwrap_or(0);
+                self.pinned_since.entry(cf.id()).or_insert(since);
+            }
+        }
+        self.last_log_number = latest_log.or(self.last_log_number);
+
+        let mut flushed = vec![];
+        if total_bytes <= self.max_wal_bytes {
+            return Ok(flushed);
+        }
+        let oldest_log = oldest_log.unwrap_or(0);
+        for cf in column_families {
+            if self.pinned_since.get(&cf.id()).map_or(false, |&since| since <= oldest_log) {
+                db.flush_cf(&self.flush_options, cf)?;
+                self.pinned_since.remove(&cf.id());
+                flushed.push(cf.name().to_owned());
+            }
+        }
+        Ok(flushed)
+    }
+}
+
 
 #[cfg(unix)]
 #[inline]
@@ -57,6 +224,57 @@ pub(crate) fn path_to_bytes<P: AsRef<Path>>(path: P) -> Vec<u8> {
 mod tests {
     use super::*;
 
+    #[test]
+    fn fixup_patterns() {
+        assert!(glob_match("default", "default"));
+        assert!(!glob_match("default", "default2"));
+        assert!(glob_match("counters_*", "counters_"));
+        assert!(glob_match("counters_*", "counters_daily"));
+        assert!(!glob_match("counters_*", "counter"));
+        assert!(glob_match("*_idx", "user_idx"));
+        assert!(glob_match("a*b*c", "abbc"));
+        assert!(!glob_match("a*b*c", "acb"));
+        assert!(glob_match("*", ""));
+
+        let fixups = ColumnFamilyFixups::new()
+            .add("*", |cf| cf.max_write_buffer_number(3))
+            .add("hot_*", |cf| cf.max_write_buffer_number(5));
+        assert!(fixups.matches("cold"));
+        let hot = fixups.apply("hot_users", ColumnFamilyOptions::default());
+        assert!(format!("{:?}", hot).contains("max_write_buffer_number=5;"));
+        let cold = fixups.apply("cold", ColumnFamilyOptions::default());
+        assert!(format!("{:?}", cold).contains("max_write_buffer_number=3;"));
+    }
+
+    #[test]
+    fn wal_flush_policy() {
+        use crate::options::{Options, WriteOptions};
+
+        let tmp_dir = ::tempdir::TempDir::new_in(".", "rocks").unwrap();
+        let db = DB::open(
+            Options::default().map_db_options(|db| db.create_if_missing(true)),
+            &tmp_dir,
+        )
+        .unwrap();
+        let cold = db.create_column_family(&ColumnFamilyOptions::default(), "cold").unwrap();
+        let default = db.default_column_family();
+
+        assert!(db.put_cf(WriteOptions::default_instance(), &cold, b"k", b"v").is_ok());
+        let mut policy = WalFlushPolicy::new(0).flush_options(FlushOptions::default().wait(true));
+        for i in 0..3 {
+            let key = format!("k{}", i);
+            assert!(db.put(WriteOptions::default_instance(), key.as_bytes(), b"v").is_ok());
+            assert!(db.flush(&FlushOptions::default().wait(true)).is_ok());
+        }
+        // the cold column family is the only one pinning the oldest WAL
+        assert_eq!(policy.check(&db, &[&default, &cold]).unwrap(), vec!["cold".to_owned()]);
+        assert!(policy.check(&db, &[&default, &cold]).unwrap().is_empty());
+
+        let mut policy = WalFlushPolicy::new(u64::MAX);
+        assert!(db.put_cf(WriteOptions::default_instance(), &cold, b"k2", b"v").is_ok());
+        assert!(policy.check(&db, &[&default, &cold]).unwrap().is_empty());
+    }
+
     #[test]
     #[ignore]
     fn load_options() {
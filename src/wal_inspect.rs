@@ -0,0 +1,373 @@
+//! Offline WAL inspection, for recovering from torn writes and corrupted logs.
+//!
+//! Reads `*.log` files directly, following the RocksDB log format: 32KB blocks of
+//! physical records, each with a masked CRC32C, a length and a fragment type; the
+//! payloads of complete records are `WriteBatch`es. No DB needs to be opened, so
+//! this works on DBs that fail to open.
+//!
+//! Inspection never modifies files. `WalFileReport::truncate_tail()` drops everything
+//! after the last intact record and must be called explicitly.
+
+use std::fs::{self, File, OpenOptions};
+use std::io::{self, Read};
+use std::path::{Path, PathBuf};
+
+use crate::options::WALRecoveryMode;
+use crate::types::SequenceNumber;
+
+const BLOCK_SIZE: usize = 32 * 1024;
+const HEADER_SIZE: usize = 7;
+const RECYCLABLE_HEADER_SIZE: usize = 11;
+// sequence number and count
+pub(crate) const WRITE_BATCH_HEADER_SIZE: usize = 12;
+
+const ZERO_TYPE: u8 = 0;
+const FULL_TYPE: u8 = 1;
+const FIRST_TYPE: u8 = 2;
+const MIDDLE_TYPE: u8 = 3;
+const LAST_TYPE: u8 = 4;
+const RECYCLABLE_FULL_TYPE: u8 = 5;
+const RECYCLABLE_LAST_TYPE: u8 = 8;
+
+const CRC32C_TABLE: [u32; 256] = crc32c_table();
+
+const fn crc32c_table() -> [u32; 256] {
+    let mut table = [0; 256];
+    let mut i = 0;
+    while i < 256 {
+        let mut crc = i as u32;
+        let mut j = 0;
+        while j < 8 {
+            crc = if crc & 1 != 0 { (crc >> 1) ^ 0x82f6_3b78 } else { crc >> 1 };
+            j += 1;
+        }
+        table[i] = crc;
+        i += 1;
+    }
+    table
+}
+
+fn crc32c(data: &[u8]) -> u32 {
+    !data
+        .iter()
+        .fold(!0u32, |crc, &b| CRC32C_TABLE[((crc ^ b as u32) & 0xff) as usize] ^ (crc >> 8))
+}
+
+// same as crc32c::Unmask() in RocksDB
+fn unmask_crc(masked: u32) -> u32 {
+    masked.wrapping_sub(0xa282_ead8).rotate_left(15)
+}
+
+/// What is wrong with a WAL file.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum WalProblem {
+    /// The file ends in the middle of a record, typical for a write torn by a crash.
+    TruncatedTail {
+        /// Offset of the incomplete record.
+        offset: u64,
+    },
+    /// A record fails its checksum or is malformed.
+    Corruption {
+        /// Offset of the bad record.
+        offset: u64,
+        reason: String,
+    },
+}
+
+/// What to do about a WAL file, see `WalFileReport::advice()`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum WalAdvice {
+    /// No problem found.
+    Clean,
+    /// Only the last record is incomplete. Opening with `TolerateCorruptedTailRecords`
+    /// or `PointInTimeRecovery` drops it, or `truncate_tail()` removes it; only the
+    /// incomplete write is lost either way.
+    TruncateTail,
+    /// Intact records may follow the corruption. `PointInTimeRecovery` or
+    /// `truncate_tail()` keep the consistent prefix only; `SkipAnyCorruptedRecords`
+    /// also replays what follows, at the risk of an inconsistent state.
+    Corrupted,
+}
+
+impl WalAdvice {
+    /// The least lossy recovery mode that keeps the DB consistent.
+    pub fn recovery_mode(self) -> WALRecoveryMode {
+        match self {
+            WalAdvice::Clean => WALRecoveryMode::AbsoluteConsistency,
+            WalAdvice::TruncateTail => WALRecoveryMode::TolerateCorruptedTailRecords,
+            WalAdvice::Corrupted => WALRecoveryMode::PointInTimeRecovery,
+        }
+    }
+}
+
+/// Result of inspecting a WAL file.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct WalFileReport {
+    pub path: PathBuf,
+    /// Parsed from the file name.
+    pub log_number: Option<u64>,
+    pub file_size: u64,
+    /// Number of intact write batches before the first problem.
+    pub num_batches: u64,
+    /// Sequence number of the first intact write batch.
+    pub first_sequence: Option<SequenceNumber>,
+    /// Last sequence number of the last intact write batch.
+    pub last_sequence: Option<SequenceNumber>,
+    /// Bytes up to the end of the last intact record.
+    pub consistent_len: u64,
+    /// First problem found, `None` if the file is clean.
+    pub problem: Option<WalProblem>,
+}
+
+impl WalFileReport {
+    pub fn is_clean(&self) -> bool {
+        self.problem.is_none()
+    }
+
+    pub fn advice(&self) -> WalAdvice {
+        match self.problem {
+            None => WalAdvice::Clean,
+            Some(WalProblem::TruncatedTail { .. }) => WalAdvice::TruncateTail,
+            Some(WalProblem::Corruption { .. }) => WalAdvice::Corrupted,
+        }
+    }
+
+    /// Truncate the file to `consistent_len`, dropping the problem and everything after it.
+    ///
+    /// Fails if the file changed since it was inspected. Does nothing for a clean file.
+    pub fn truncate_tail(&self) -> io::Result<()> {
+        if self.is_clean() {
+            return Ok(());
+        }
+        let file = OpenOptions::new().write(true).open(&self.path)?;
+        if file.metadata()?.len() != self.file_size {
+            return Err(io::Error::new(io::ErrorKind::Other, "WAL file changed since inspection"));
+        }
+        file.set_len(self.consistent_len)?;
+        file.sync_all()
+    }
+}
+
+/// Inspect a single WAL file.
+pub fn inspect_wal_file<P: AsRef<Path>>(path: P) -> io::Result<WalFileReport> {
+    let path = path.as_ref();
+    let mut data = Vec::new();
+    File::open(path)?.read_to_end(&mut data)?;
+    let log_number = path
+        .file_name()
+        .and_then(|name| name.to_str())
+        .and_then(|name| name.strip_suffix(".log"))
+        .and_then(|num| num.parse().ok());
+
+    let mut report = WalFileReport {
+        path: path.to_owned(),
+        log_number,
+        file_size: data.len() as u64,
+        num_batches: 0,
+        first_sequence: None,
+        last_sequence: None,
+        consistent_len: 0,
+        problem: None,
+    };
+    report.problem = scan(&data, log_number, &mut report);
+    Ok(report)
+}
+
+/// Inspect all WAL files in `dir`, the DB dir or `DBOptions::wal_dir`, ordered by log number.
+pub fn inspect_wal_dir<P: AsRef<Path>>(dir: P) -> io::Result<Vec<WalFileReport>> {
+    let mut reports = Vec::new();
+    for entry in fs::read_dir(dir)? {
+        let path = entry?.path();
+        if path.extension().map_or(false, |ext| ext == "log") {
+            reports.push(inspect_wal_file(path)?);
+        }
+    }
+    reports.sort_by_key(|r| r.log_number);
+    Ok(reports)
+}
+
+/// The last sequence number that replaying `reports`, ordered by log number, reaches
+/// before the first problem, i.e. what `PointInTimeRecovery` would recover to.
+pub fn last_consistent_sequence(reports: &[WalFileReport]) -> Option<SequenceNumber> {
+    let mut last = None;
+    for report in reports {
+        last = report.last_sequence.or(last);
+        if !report.is_clean() {
+            break;
+        }
+    }
+    last
+}
+
+fn scan(data: &[u8], log_number: Option<u64>, report: &mut WalFileReport) -> Option<WalProblem> {
+    read_log_records(data, log_number, |_, end, record| {
+        if record.len() < WRITE_BATCH_HEADER_SIZE {
+            return Err("write batch too small".into());
+        }
+        let mut seq = [0; 8];
+        seq.copy_from_slice(&record[..8]);
+        let seq = u64::from_le_bytes(seq);
+        let count = u32::from_le_bytes([record[8], record[9], record[10], record[11]]) as u64;
+        report.num_batches += 1;
+        report.first_sequence.get_or_insert(SequenceNumber(seq));
+        report.last_sequence = Some(SequenceNumber(seq + count.saturating_sub(1)));
+        report.consistent_len = end as u64;
+        Ok(())
+    })
+}
+
+/// Read the records of a file in the log format, i.e. a WAL or a MANIFEST.
+///
+/// `on_record` is called with the start offset, the end offset and the payload of every
+/// complete record, until the first problem. An error returned by it is reported as a
+/// corruption of the record.
+pub(crate) fn read_log_records<F>(data: &[u8], log_number: Option<u64>, mut on_record: F) -> Option<WalProblem>
+where
+    F: FnMut(usize, usize, &[u8]) -> std::result::Result<(), String>,
+{
+    let corruption = |offset: usize, reason: &str| {
+        Some(WalProblem::Corruption {
+            offset: offset as u64,
+            reason: reason.to_owned(),
+        })
+    };
+    let mut pos = 0;
+    // start offset and payload of a fragmented record
+    let mut pending: Option<(usize, Vec<u8>)> = None;
+
+    while pos < data.len() {
+        let block_left = BLOCK_SIZE - pos % BLOCK_SIZE;
+        // block trailer, too short for a header, zero padded by the writer
+        let trailer = &data[pos..data.len().min(pos + block_left)];
+        if block_left < RECYCLABLE_HEADER_SIZE && trailer.iter().all(|&b| b == 0) {
+            pos += block_left;
+            continue;
+        }
+        if pos + HEADER_SIZE > data.len() {
+            break;
+        }
+        let header = &data[pos..pos + HEADER_SIZE];
+        let masked_crc = u32::from_le_bytes([header[0], header[1], header[2], header[3]]);
+        let length = u16::from_le_bytes([header[4], header[5]]) as usize;
+        let typ = header[6];
+        if typ == ZERO_TYPE && length == 0 {
+            // preallocated space, end of data
+            return pending.map(|(offset, _)| WalProblem::TruncatedTail { offset: offset as u64 });
+        }
+        let recyclable = (RECYCLABLE_FULL_TYPE..=RECYCLABLE_LAST_TYPE).contains(&typ);
+        let header_size = if recyclable { RECYCLABLE_HEADER_SIZE } else { HEADER_SIZE };
+        let end = pos + header_size + length;
+        if end > data.len() {
+            break;
+        }
+        if end > pos + block_left {
+            return corruption(pos, "record crosses block boundary");
+        }
+        if unmask_crc(masked_crc) != crc32c(&data[pos + 6..end]) {
+            return corruption(pos, "checksum mismatch");
+        }
+        if recyclable {
+            let mut num = [0; 4];
+            num.copy_from_slice(&data[pos + HEADER_SIZE..pos + RECYCLABLE_HEADER_SIZE]);
+            // leftover of the previous user of a recycled file
+            if log_number.map_or(false, |n| n as u32 != u32::from_le_bytes(num)) {
+                return pending.map(|(offset, _)| WalProblem::TruncatedTail { offset: offset as u64 });
+            }
+        }
+        let payload = &data[pos + header_size..end];
+        let fragment = if recyclable { typ - RECYCLABLE_FULL_TYPE + FULL_TYPE } else { typ };
+        let record = match fragment {
+            FULL_TYPE if pending.is_none() => Some((pos, payload.to_vec())),
+            FIRST_TYPE if pending.is_none() => {
+                pending = Some((pos, payload.to_vec()));
+                None
+            },
+            MIDDLE_TYPE if pending.is_some() => {
+                pending.as_mut().unwrap().1.extend_from_slice(payload);
+                None
+            },
+            LAST_TYPE if pending.is_some() => {
+                let (start, mut record) = pending.take().unwrap();
+                record.extend_from_slice(payload);
+                Some((start, record))
+            },
+            FULL_TYPE | FIRST_TYPE | MIDDLE_TYPE | LAST_TYPE => {
+                return corruption(pending.map_or(pos, |(offset, _)| offset), "unexpected fragment");
+            },
+            _ => return corruption(pos, "unknown record type"),
+        };
+        if let Some((start, record)) = record {
+            if let Err(reason) = on_record(start, end, &record) {
+                return corruption(pos, &reason);
+            }
+        }
+        pos = end;
+    }
+
+    if pos < data.len() || pending.is_some() {
+        let offset = pending.map_or(pos, |(offset, _)| offset);
+        Some(WalProblem::TruncatedTail { offset: offset as u64 })
+    } else {
+        None
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::super::rocksdb::*;
+    use super::*;
+
+    #[test]
+    fn crc32c_known_value() {
+        assert_eq!(crc32c(b"123456789"), 0xe306_9283);
+    }
+
+    #[test]
+    fn inspect_and_truncate() {
+        let tmp_dir = ::tempdir::TempDir::new_in(".", "rocks").unwrap();
+        {
+            let db = DB::open(
+                Options::default().map_db_options(|db| db.create_if_missing(true)),
+                &tmp_dir,
+            )
+            .unwrap();
+            for i in 0..100 {
+                let key = format!("k{:03}", i);
+                assert!(db.put(WriteOptions::default_instance(), key.as_bytes(), &[b'v'; 1000]).is_ok());
+            }
+        }
+
+        let reports = inspect_wal_dir(&tmp_dir).unwrap();
+        let report = reports.iter().find(|r| r.num_batches > 0).unwrap();
+        assert!(report.is_clean(), "{:?}", report);
+        assert_eq!(report.num_batches, 100);
+        assert_eq!(report.last_sequence, Some(SequenceNumber(100)));
+        assert_eq!(report.consistent_len, report.file_size);
+        assert_eq!(last_consistent_sequence(&reports), Some(SequenceNumber(100)));
+
+        // torn write
+        let file = OpenOptions::new().write(true).open(&report.path).unwrap();
+        file.set_len(report.file_size - 3).unwrap();
+        let torn = inspect_wal_file(&report.path).unwrap();
+        assert_eq!(torn.advice(), WalAdvice::TruncateTail);
+        assert_eq!(torn.last_sequence, Some(SequenceNumber(99)));
+        assert!(torn.truncate_tail().is_ok());
+        assert!(inspect_wal_file(&report.path).unwrap().is_clean());
+
+        // bit flip in the middle
+        let mut data = fs::read(&report.path).unwrap();
+        data[BLOCK_SIZE + 100] ^= 0xff;
+        fs::write(&report.path, &data).unwrap();
+        let corrupted = inspect_wal_file(&report.path).unwrap();
+        assert_eq!(corrupted.advice(), WalAdvice::Corrupted);
+        assert_eq!(corrupted.advice().recovery_mode(), WALRecoveryMode::PointInTimeRecovery);
+        assert!(corrupted.last_sequence.unwrap().0 < 99);
+
+        let db = DB::open(
+            Options::default().map_db_options(|db| db.wal_recovery_mode(corrupted.advice().recovery_mode())),
+            &tmp_dir,
+        )
+        .unwrap();
+        assert_eq!(db.get_latest_sequence_number(), corrupted.last_sequence.unwrap());
+    }
+}
@@ -23,6 +23,7 @@ use rocks_sys as ll;
 
 use crate::db::ColumnFamilyHandle;
 use crate::to_raw::{FromRaw, ToRaw};
+use crate::wal_inspect::WRITE_BATCH_HEADER_SIZE;
 use crate::{Error, Result};
 
 /// `WriteBatch` holds a collection of updates to apply atomically to a DB.
@@ -88,6 +89,25 @@ impl WriteBatch {
         }
     }
 
+    /// Reconstruct a batch from its serialized representation, as returned by
+    /// `data()`.
+    ///
+    /// The contents are not validated here, a malformed representation is
+    /// reported as `Corruption` by `iterate()` or when written to a DB.
+    pub fn from_data(data: &[u8]) -> WriteBatch {
+        WriteBatch {
+            raw: unsafe { ll::rocks_writebatch_create_from(data.as_ptr() as _, data.len()) },
+        }
+    }
+
+    /// A builder staging updates to one column family at a time.
+    pub fn builder<'a>() -> WriteBatchBuilder<'a> {
+        WriteBatchBuilder {
+            batch: WriteBatch::new(),
+            column_family: None,
+        }
+    }
+
     /// Clear all updates buffered in this batch.
     pub fn clear(&mut self) {
         unsafe {
@@ -390,7 +410,9 @@ impl WriteBatch {
     }
 
     /// Retrieve the serialized version of this batch.
-    pub fn get_data(&self) -> &[u8] {
+    ///
+    /// Use `WriteBatch::from_data()` to reconstruct the batch.
+    pub fn data(&self) -> &[u8] {
         let mut size = 0;
         unsafe {
             let ptr = ll::rocks_writebatch_data(self.raw, &mut size);
@@ -398,7 +420,7 @@ impl WriteBatch {
         }
     }
 
-    // FIXME: extra data bytes copied, should use GetDataSize()
+    /// Returns the size of the serialized version of this batch.
     pub fn get_data_size(&self) -> usize {
         let mut size = 0;
         unsafe {
@@ -407,6 +429,11 @@ impl WriteBatch {
         size as usize
     }
 
+    /// Returns true if the batch has no updates, log data included.
+    pub fn is_empty(&self) -> bool {
+        self.get_data_size() <= WRITE_BATCH_HEADER_SIZE
+    }
+
     /// Returns the number of updates in the batch
     pub fn count(&self) -> usize {
         unsafe { ll::rocks_writebatch_count(self.raw) as usize }
@@ -454,7 +481,105 @@ impl WriteBatch {
 
     /// Returns trie if MarkRollback will be called during Iterate
     pub fn has_rollback(&self) -> bool {
-        unsafe { ll::rocks_writebatch_has_put(self.raw) != 0 }
+        unsafe { ll::rocks_writebatch_has_rollback(self.raw) != 0 }
+    }
+}
+
+/// Builds a `WriteBatch` spanning column families, returned by `WriteBatch::builder()`.
+///
+/// Updates go to the column family selected by the last `column_family()` call, the
+/// default column family before any.
+///
+/// ```no_run
+/// # use rocks::rocksdb::*;
+/// # let db = DB::open(Options::default(), "./data").unwrap();
+/// # let users = db.create_column_family(&ColumnFamilyOptions::default(), "users").unwrap();
+/// let batch = WriteBatch::builder()
+///     .put(b"last-login", b"alice")
+///     .column_family(&users)
+///     .put(b"alice", b"{}")
+///     .delete(b"bob")
+///     .build();
+/// db.write(WriteOptions::default_instance(), &batch).unwrap();
+/// ```
+#[derive(Debug)]
+pub struct WriteBatchBuilder<'a> {
+    batch: WriteBatch,
+    column_family: Option<&'a ColumnFamilyHandle>,
+}
+
+impl<'a> WriteBatchBuilder<'a> {
+    /// Stage following updates to `column_family`.
+    pub fn column_family(mut self, column_family: &'a ColumnFamilyHandle) -> Self {
+        self.column_family = Some(column_family);
+        self
+    }
+
+    /// Stage following updates to the default column family.
+    pub fn default_column_family(mut self) -> Self {
+        self.column_family = None;
+        self
+    }
+
+    pub fn put(mut self, key: &[u8], value: &[u8]) -> Self {
+        match self.column_family {
+            Some(cf) => self.batch.put_cf(cf, key, value),
+            None => self.batch.put(key, value),
+        };
+        self
+    }
+
+    pub fn delete(mut self, key: &[u8]) -> Self {
+        match self.column_family {
+            Some(cf) => self.batch.delete_cf(cf, key),
+            None => self.batch.delete(key),
+        };
+        self
+    }
+
+    pub fn single_delete(mut self, key: &[u8]) -> Self {
+        match self.column_family {
+            Some(cf) => self.batch.single_delete_cf(cf, key),
+            None => self.batch.single_delete(key),
+        };
+        self
+    }
+
+    pub fn delete_range(mut self, begin_key: &[u8], end_key: &[u8]) -> Self {
+        match self.column_family {
+            Some(cf) => self.batch.delete_range_cf(cf, begin_key, end_key),
+            None => self.batch.delete_range(begin_key, end_key),
+        };
+        self
+    }
+
+    pub fn merge(mut self, key: &[u8], value: &[u8]) -> Self {
+        match self.column_family {
+            Some(cf) => self.batch.merge_cf(cf, key, value),
+            None => self.batch.merge(key, value),
+        };
+        self
+    }
+
+    /// See `WriteBatch::put_log_data()`, not tied to a column family.
+    pub fn log_data(mut self, blob: &[u8]) -> Self {
+        self.batch.put_log_data(blob);
+        self
+    }
+
+    /// The batch built so far.
+    pub fn batch(&self) -> &WriteBatch {
+        &self.batch
+    }
+
+    pub fn build(self) -> WriteBatch {
+        self.batch
+    }
+}
+
+impl<'a> From<WriteBatchBuilder<'a>> for WriteBatch {
+    fn from(builder: WriteBatchBuilder<'a>) -> WriteBatch {
+        builder.build()
     }
 }
 
@@ -710,6 +835,52 @@ mod tests {
         assert_eq!(handler.entries.len(), 3);
     }
 
+    #[test]
+    fn write_batch_from_data() {
+        let mut batch = WriteBatch::new();
+        batch
+            .put(b"name", b"rocksdb")
+            .merge(b"counter", b"1")
+            .delete_range(b"a", b"b")
+            .delete(b"name");
+
+        let decoded = WriteBatch::from_data(batch.data());
+        assert_eq!(decoded.count(), 4);
+        assert_eq!(decoded.data(), batch.data());
+        assert!(!decoded.has_rollback());
+
+        let mut handler = WriteBatchIteratorHandler::default();
+        assert!(decoded.iterate(&mut handler).is_ok());
+        assert_eq!(
+            handler.entries,
+            vec![
+                WriteBatchEntry::Put {
+                    column_family_id: 0,
+                    key: b"name".to_vec(),
+                    value: b"rocksdb".to_vec(),
+                },
+                WriteBatchEntry::Merge {
+                    column_family_id: 0,
+                    key: b"counter".to_vec(),
+                    value: b"1".to_vec(),
+                },
+                WriteBatchEntry::DeleteRange {
+                    column_family_id: 0,
+                    begin_key: b"a".to_vec(),
+                    end_key: b"b".to_vec(),
+                },
+                WriteBatchEntry::Delete {
+                    column_family_id: 0,
+                    key: b"name".to_vec(),
+                },
+            ]
+        );
+
+        // truncated
+        let broken = WriteBatch::from_data(&batch.data()[..batch.data().len() - 2]);
+        assert!(broken.iterate(&mut WriteBatchIteratorHandler::default()).is_err());
+    }
+
     #[test]
     fn write_batch() {
         let tmp_dir = ::tempdir::TempDir::new_in(".", "rocks").unwrap();
@@ -730,4 +901,60 @@ mod tests {
         assert_eq!(db.get(&ReadOptions::default(), b"name").unwrap().as_ref(), b"BH1XUW");
         assert_eq!(db.get(&ReadOptions::default(), b"site").unwrap().as_ref(), b"github");
     }
+
+    #[test]
+    fn write_batch_builder() {
+        let tmp_dir = ::tempdir::TempDir::new_in(".", "rocks").unwrap();
+        let db = DB::open(
+            Options::default().map_db_options(|db| db.create_if_missing(true)),
+            &tmp_dir,
+        )
+        .unwrap();
+        let users = db.create_column_family(&ColumnFamilyOptions::default(), "users").unwrap();
+
+        assert!(WriteBatch::new().is_empty());
+        let builder = WriteBatch::builder()
+            .put(b"k1", b"v1")
+            .column_family(&users)
+            .put(b"alice", b"1")
+            .put(b"bob", b"2")
+            .log_data(b"blob")
+            .delete(b"bob")
+            .default_column_family()
+            .delete(b"k0");
+        assert_eq!(builder.batch().count(), 5);
+        let mut batch = builder.build();
+        assert!(!batch.is_empty());
+
+        // partial rollback
+        let size = batch.get_data_size();
+        batch.set_save_point();
+        batch.put(b"k2", b"v2");
+        assert_eq!(batch.count(), 6);
+        assert!(batch.rollback_to_save_point().is_ok());
+        assert_eq!(batch.count(), 5);
+        assert_eq!(batch.get_data_size(), size);
+        assert!(batch.rollback_to_save_point().is_err());
+
+        let mut handler = WriteBatchIteratorHandler::default();
+        assert!(batch.iterate(&mut handler).is_ok());
+        let cf_ids: Vec<_> = handler
+            .entries
+            .iter()
+            .filter_map(|entry| match *entry {
+                WriteBatchEntry::Put { column_family_id, .. } | WriteBatchEntry::Delete { column_family_id, .. } => {
+                    Some(column_family_id)
+                },
+                _ => None,
+            })
+            .collect();
+        assert_eq!(cf_ids, vec![0, users.id(), users.id(), users.id(), 0]);
+
+        assert!(db.write(WriteOptions::default_instance(), &batch).is_ok());
+        assert_eq!(users.get(ReadOptions::default_instance(), b"alice").unwrap(), b"1");
+        assert!(users.get(ReadOptions::default_instance(), b"bob").is_err());
+
+        batch.clear();
+        assert!(batch.is_empty());
+    }
 }
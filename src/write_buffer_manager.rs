@@ -3,6 +3,7 @@
 
 use rocks_sys as ll;
 
+use crate::cache::Cache;
 use crate::to_raw::ToRaw;
 
 /// `WriteBufferManager` is for managing memory allocation for one or more
@@ -32,18 +33,44 @@ impl WriteBufferManager {
         WriteBufferManager { raw: unsafe { ll::rocks_write_buffer_manager_create(buffer_size) } }
     }
 
+    /// Like `new()`, but memtable memory is also charged to `cache`, by
+    /// inserting dummy entries, so that block cache and memtables share
+    /// one memory budget.
+    pub fn new_with_cache(buffer_size: usize, cache: &Cache) -> WriteBufferManager {
+        WriteBufferManager {
+            raw: unsafe { ll::rocks_write_buffer_manager_create_with_cache(buffer_size, cache.raw()) },
+        }
+    }
+
     pub fn enabled(&self) -> bool {
         unsafe { ll::rocks_write_buffer_manager_enabled(self.raw) != 0 }
     }
 
+    /// Whether memtable memory is charged to a block cache.
+    pub fn cost_to_cache(&self) -> bool {
+        unsafe { ll::rocks_write_buffer_manager_cost_to_cache(self.raw) != 0 }
+    }
+
     // Only valid if enabled()
     pub fn memory_usage(&self) -> usize {
         unsafe { ll::rocks_write_buffer_manager_memory_usage(self.raw) }
     }
 
+    /// Memory used by memtables that are not yet scheduled to flush.
+    ///
+    /// Only valid if enabled()
+    pub fn mutable_memtable_memory_usage(&self) -> usize {
+        unsafe { ll::rocks_write_buffer_manager_mutable_memtable_memory_usage(self.raw) }
+    }
+
     pub fn buffer_size(&self) -> usize {
         unsafe { ll::rocks_write_buffer_manager_buffer_size(self.raw) }
     }
+
+    /// Whether the memory limit is hit and a memtable should be flushed.
+    pub fn should_flush(&self) -> bool {
+        unsafe { ll::rocks_write_buffer_manager_should_flush(self.raw) != 0 }
+    }
 }
 
 #[cfg(test)]
@@ -51,6 +78,7 @@ mod tests {
     use std::iter;
     use super::*;
     use super::super::rocksdb::*;
+    use crate::cache::CacheBuilder;
 
     #[test]
     #[ignore]
@@ -109,4 +137,32 @@ mod tests {
         drop(db2);
         assert_eq!(manager.memory_usage(), 0);
     }
+
+    #[test]
+    fn write_buffer_manager_with_cache() {
+        let tmp_dir = ::tempdir::TempDir::new_in(".", "rocks").unwrap();
+        let cache = CacheBuilder::new_lru(64 << 20).build().unwrap();
+        let manager = WriteBufferManager::new_with_cache(8 << 20, &cache);
+
+        assert!(manager.enabled());
+        assert!(manager.cost_to_cache());
+        assert_eq!(manager.buffer_size(), 8 << 20);
+        assert!(!WriteBufferManager::new(8 << 20).cost_to_cache());
+
+        let db = DB::open(
+            Options::default().map_db_options(|db| db.create_if_missing(true).write_buffer_manager(&manager)),
+            &tmp_dir,
+        )
+        .unwrap();
+        for i in 0..1000 {
+            let key = format!("k{}", i);
+            assert!(db.put(WriteOptions::default_instance(), key.as_bytes(), b"value").is_ok());
+        }
+
+        assert!(manager.memory_usage() > 0);
+        assert!(manager.mutable_memtable_memory_usage() <= manager.memory_usage());
+        assert!(!manager.should_flush());
+        // dummy entries are charged to the block cache
+        assert!(cache.get_usage() > 0);
+    }
 }
@@ -1,4 +1,7 @@
 use rocks::prelude::*;
+use rocks::env::Priority;
+use rocks::metadata::KeyRange;
+use rocks::transaction_log::WalFileType;
 use std::collections::HashMap;
 use tempdir::TempDir;
 
@@ -42,6 +45,69 @@ fn test_open_for_readonly() {
     assert!(db.is_ok());
 }
 
+#[test]
+fn read_only_db_types() {
+    let tmp_dir = TempDir::new_in(".", "rocks").unwrap();
+    let secondary_dir = TempDir::new_in(".", "rocks").unwrap();
+    let db = DB::open(
+        Options::default().map_db_options(|db| db.create_if_missing(true)),
+        &tmp_dir,
+    )
+    .unwrap();
+    let cf = db.create_column_family(&ColumnFamilyOptions::default(), "cf1").unwrap();
+    assert!(cf.put(WriteOptions::default_instance(), b"k0", b"v0").is_ok());
+    assert!(db.flush_cf(&FlushOptions::default().wait(true), &cf).is_ok());
+
+    let (ro, cfs) = ReadOnlyDB::open_with_column_families(
+        &DBOptions::default(),
+        &tmp_dir,
+        vec!["default", "cf1"],
+        false,
+    )
+    .unwrap();
+    assert_eq!(cfs[1].get(ReadOptions::default_instance(), b"k0").unwrap(), b"v0");
+    assert_eq!(ro.get_cf(ReadOptions::default_instance(), &cfs[1], b"k0").unwrap(), b"v0");
+    assert_eq!(ro.column_family("cf1").unwrap().id(), cf.id());
+    let mut iter = ro.new_iterator_cf(ReadOptions::default_instance(), &cfs[1]);
+    iter.seek_to_first();
+    assert_eq!(iter.key(), b"k0");
+    drop(iter);
+
+    // the dynamic DB, writes fail at runtime
+    let (ro_db, ro_cf) = (ro.into_db(), cfs[1].clone().into_column_family());
+    assert!(ro_cf.put(WriteOptions::default_instance(), b"k1", b"v1").is_err());
+    drop((ro_db, cfs));
+
+    let secondary = SecondaryDB::open(
+        &Options::default().map_db_options(|db| db.max_open_files(-1)),
+        &tmp_dir,
+        &secondary_dir,
+    )
+    .unwrap();
+    assert!(secondary.get(ReadOptions::default_instance(), b"k1").is_err());
+    assert!(db.put(WriteOptions::default_instance(), b"k1", b"v1").is_ok());
+    assert!(secondary.try_catch_up_with_primary().is_ok());
+    assert_eq!(secondary.get(ReadOptions::default_instance(), b"k1").unwrap(), b"v1");
+
+    // a read-only view of a read-write DB
+    let view = ReadOnlyDB::from(db);
+    assert_eq!(view.get(ReadOptions::default_instance(), b"k1").unwrap(), b"v1");
+}
+
+#[test]
+fn open_optimized_for_fast_open() {
+    let tmp_dir = TempDir::new_in(".", "rocks").unwrap();
+    let opt = Options::default()
+        .map_db_options(|db| db.create_if_missing(true).max_open_files(-1).optimize_for_fast_open());
+    {
+        let db = DB::open(&opt, &tmp_dir).unwrap();
+        assert!(db.put(WriteOptions::default_instance(), b"k", b"v").is_ok());
+        assert!(db.flush(&FlushOptions::default().wait(true)).is_ok());
+    }
+    let db = DB::open(&opt, &tmp_dir).unwrap();
+    assert_eq!(db.get(ReadOptions::default_instance(), b"k").unwrap(), b"v");
+}
+
 #[test]
 fn test_list_cfs() {
     let tmp_dir = TempDir::new_in(".", "rocks").unwrap();
@@ -269,6 +335,32 @@ fn multi_get() {
     assert!(ret[5].as_ref().unwrap_err().is_not_found());
 }
 
+#[test]
+fn get_opt() {
+    let tmp_dir = ::tempdir::TempDir::new_in(".", "rocks").unwrap();
+    let db = DB::open(
+        Options::default().map_db_options(|db| db.create_if_missing(true)),
+        &tmp_dir,
+    )
+    .unwrap();
+    let cf = db.create_column_family(&Default::default(), "opt").unwrap();
+
+    assert!(db.put(&Default::default(), b"a", b"1").is_ok());
+    assert!(cf.put(&Default::default(), b"b", b"2").is_ok());
+
+    assert_eq!(db.get_opt(&ReadOptions::default(), b"a").unwrap().unwrap(), b"1".as_ref());
+    assert!(db.get_opt(&ReadOptions::default(), b"b").unwrap().is_none());
+    assert_eq!(db.get_cf_opt(&ReadOptions::default(), &cf, b"b").unwrap().unwrap(), b"2".as_ref());
+    assert!(cf.get_opt(&ReadOptions::default(), b"a").unwrap().is_none());
+
+    let ret = db.multi_get_opt(&ReadOptions::default(), &[b"a", b"b"]);
+    assert_eq!(ret[0].as_ref().unwrap().as_ref().unwrap(), b"1".as_ref());
+    assert!(ret[1].as_ref().unwrap().is_none());
+    let ret = cf.multi_get_opt(&ReadOptions::default(), &[b"a", b"b"]);
+    assert!(ret[0].as_ref().unwrap().is_none());
+    assert!(ret[1].as_ref().unwrap().is_some());
+}
+
 #[test]
 fn multi_get_cf() {
     let tmp_dir = ::tempdir::TempDir::new_in(".", "rocks").unwrap();
@@ -364,6 +456,27 @@ fn db_paths() {
     assert!(d.count() >= 2); // OPTIONS, MANIFEST, etc.
 }
 
+#[test]
+fn cf_paths() {
+    let tmp_dir = ::tempdir::TempDir::new_in(".", "rocks").unwrap();
+    let cold_dir = ::tempdir::TempDir::new_in(".", "rocks").unwrap();
+    let db = DB::open(
+        Options::default().map_db_options(|db| db.create_if_missing(true)),
+        &tmp_dir,
+    )
+    .unwrap();
+    let cf = db
+        .create_column_family(&ColumnFamilyOptions::default().cf_paths(vec![&cold_dir.path()]), "cold")
+        .unwrap();
+    assert!(cf.put(WriteOptions::default_instance(), b"k", b"v").is_ok());
+    assert!(cf.flush(&FlushOptions::default().wait(true)).is_ok());
+
+    let meta = cf.metadata();
+    let file = meta.files().next().unwrap();
+    assert_eq!(std::path::Path::new(&file.db_path), cold_dir.path());
+    assert!(cold_dir.path().join(file.name.trim_start_matches('/')).exists());
+}
+
 #[test]
 fn key_may_exist() {
     let tmp_dir = ::tempdir::TempDir::new_in(".", "rocks").unwrap();
@@ -389,6 +502,32 @@ fn key_may_exist() {
     assert!(!maybe_val.is_some());
 }
 
+#[test]
+fn key_exists() {
+    let tmp_dir = ::tempdir::TempDir::new_in(".", "rocks").unwrap();
+    let db = DB::open(
+        Options::default().map_db_options(|db| db.create_if_missing(true)),
+        &tmp_dir,
+    )
+    .unwrap();
+    let cf = db.create_column_family(&Default::default(), "exists").unwrap();
+
+    assert!(db.put(&Default::default(), b"mem", b"value").is_ok());
+    assert_eq!(
+        db.key_lookup(&ReadOptions::default(), b"mem"),
+        KeyLookup::FoundInMemory(b"value".to_vec())
+    );
+    assert_eq!(db.key_lookup(&ReadOptions::default(), b"none"), KeyLookup::DefinitelyMissing);
+
+    assert!(cf.put(&Default::default(), b"sst", b"value").is_ok());
+    assert!(cf.compact_range(&Default::default(), ..).is_ok());
+    assert!(cf.key_lookup(&ReadOptions::default(), b"sst").may_exist());
+    assert!(db.key_exists_cf(&ReadOptions::default(), &cf, b"sst", true).unwrap());
+    assert!(cf.key_exists(&ReadOptions::default(), b"sst", false).unwrap());
+    assert!(!cf.key_exists(&ReadOptions::default(), b"mem", true).unwrap());
+    assert!(!db.key_exists(&ReadOptions::default(), b"sst", true).unwrap());
+}
+
 #[test]
 fn get_prop() {
     let tmp_dir = ::tempdir::TempDir::new_in(".", "rocks").unwrap();
@@ -488,6 +627,29 @@ fn flush() {
     assert_eq!(*db.get_latest_sequence_number(), 4);
 }
 
+#[test]
+fn manual_wal_flush() {
+    let tmp_dir = ::tempdir::TempDir::new_in(".", "rocks").unwrap();
+    let db = DB::open(
+        Options::default().map_db_options(|db| db.create_if_missing(true).manual_wal_flush(true)),
+        &tmp_dir,
+    )
+    .unwrap();
+    let wal_size = || -> u64 { db.get_sorted_wal_files().unwrap().iter().map(|f| f.size_in_bytes).sum() };
+
+    assert!(db.put(&Default::default(), b"k", b"v").is_ok());
+    // buffered in memory
+    assert_eq!(wal_size(), 0);
+
+    assert!(db.flush_wal(false).is_ok());
+    let size = wal_size();
+    assert!(size > 0);
+
+    assert!(db.put(&Default::default(), b"k2", b"v").is_ok());
+    assert!(db.flush_wal(true).is_ok());
+    assert!(wal_size() > size);
+}
+
 #[test]
 fn livemetadata() {
     let tmp_dir = ::tempdir::TempDir::new_in(".", "rocks").unwrap();
@@ -526,6 +688,49 @@ fn livemetadata() {
     assert_eq!(meta[0].level, 1);
 }
 
+#[test]
+fn verify_checksum() {
+    let tmp_dir = ::tempdir::TempDir::new_in(".", "rocks").unwrap();
+    let db = DB::open(
+        Options::default().map_db_options(|db| db.create_if_missing(true)),
+        &tmp_dir,
+    )
+    .unwrap();
+
+    for i in 0..100 {
+        let key = format!("key-{:03}", i);
+        assert!(db.put(&Default::default(), key.as_bytes(), b"value").is_ok());
+    }
+    assert!(db.flush(&FlushOptions::default().wait(true)).is_ok());
+    assert!(db.verify_checksum().is_ok());
+    assert!(db
+        .verify_checksum_with_options(&ReadOptions::default().readahead_size(4096))
+        .is_ok());
+
+    let meta = db.get_live_files_metadata();
+    let path = format!("{}{}", meta[0].db_path, meta[0].name);
+    let mut content = ::std::fs::read(&path).unwrap();
+    content[10] ^= 0xff;
+    ::std::fs::write(&path, content).unwrap();
+    assert!(db.verify_checksum().is_err());
+}
+
+#[test]
+fn livemetadata_file_number() {
+    let tmp_dir = ::tempdir::TempDir::new_in(".", "rocks").unwrap();
+    let db = DB::open(
+        Options::default().map_db_options(|db| db.create_if_missing(true)),
+        &tmp_dir,
+    )
+    .unwrap();
+
+    assert!(db.put(&Default::default(), b"k", b"v").is_ok());
+    assert!(db.flush(&FlushOptions::default().wait(true)).is_ok());
+    let meta = db.get_live_files_metadata();
+    assert_eq!(meta.len(), 1);
+    assert!(meta[0].name.ends_with(&format!("{:06}.sst", meta[0].file_number)));
+}
+
 #[test]
 fn column_family_meta() {
     let tmp_dir = ::tempdir::TempDir::new_in(".", "rocks").unwrap();
@@ -560,6 +765,153 @@ fn column_family_meta() {
     assert!(meta.levels[4].files.len() == 0);
 }
 
+#[test]
+fn column_family_meta_entries() {
+    let tmp_dir = ::tempdir::TempDir::new_in(".", "rocks").unwrap();
+    let db = DB::open(
+        Options::default().map_db_options(|db| db.create_if_missing(true)),
+        &tmp_dir,
+    )
+    .unwrap();
+
+    // a put and a deletion in one file
+    db.put(&WriteOptions::default(), b"k0", b"v").unwrap();
+    db.delete(&WriteOptions::default(), b"k1").unwrap();
+    assert!(db.flush(&FlushOptions::default().wait(true)).is_ok());
+    let meta = db.get_column_family_metadata(&db.default_column_family());
+    assert_eq!(meta.levels[0].files.len(), 1);
+    let file = &meta.levels[0].files[0];
+    assert_eq!(file.num_entries, 2);
+    assert_eq!(file.num_deletions, 1);
+    assert!(file.file_creation_time > 0);
+    assert!(file.oldest_ancester_time > 0);
+    assert_eq!(file.oldest_blob_file_number, 0);
+
+    let live = db.get_live_files_metadata();
+    let live_file = live.iter().find(|f| f.file_number == file.file_number).unwrap();
+    assert_eq!(live_file.num_deletions, 1);
+    assert_eq!(live_file.file_creation_time, file.file_creation_time);
+}
+
+#[test]
+fn data_age_histogram() {
+    use std::time::{Duration, SystemTime, UNIX_EPOCH};
+
+    let tmp_dir = ::tempdir::TempDir::new_in(".", "rocks").unwrap();
+    let db = DB::open(
+        Options::default().map_db_options(|db| db.create_if_missing(true)),
+        &tmp_dir,
+    )
+    .unwrap();
+    assert_eq!(db.get_creation_time_of_oldest_file(), None);
+
+    for i in 0..3 {
+        db.put(&WriteOptions::default(), format!("k{}", i).as_bytes(), b"v")
+            .unwrap();
+        assert!(db.flush(&FlushOptions::default().wait(true)).is_ok());
+    }
+
+    let now = SystemTime::now().duration_since(UNIX_EPOCH).unwrap().as_secs();
+    let oldest = db.get_creation_time_of_oldest_file().unwrap();
+    assert!(oldest > 0 && oldest <= now);
+
+    let hour = Duration::from_secs(3600);
+    let hist = db.get_data_age_histogram(&[hour, 24 * hour]);
+    assert_eq!(hist.buckets.len(), 3);
+    assert_eq!(hist.buckets[0].max_age, Some(hour));
+    assert_eq!(hist.buckets[0].num_files, 3);
+    assert_eq!(hist.buckets[0].num_entries, 3);
+    assert_eq!(hist.buckets[2].max_age, None);
+    assert_eq!(hist.unknown.num_files, 0);
+    assert_eq!(hist.size_older_than(hour), 0);
+
+    // two days later, all data is past the last bound
+    let meta = db.get_column_family_metadata(&db.default_column_family());
+    let later = SystemTime::now() + 48 * hour;
+    let hist = rocks::metadata::DataAgeHistogram::new(meta.files(), later, &[hour, 24 * hour]);
+    assert_eq!(hist.buckets[2].num_files, 3);
+    assert_eq!(hist.size_older_than(hour), meta.size);
+    assert_eq!(meta.data_age_histogram(&[]).buckets[0].num_files, 3);
+}
+
+#[test]
+fn split_into_ranges() {
+    let tmp_dir = ::tempdir::TempDir::new_in(".", "rocks").unwrap();
+    let db = DB::open(
+        Options::default()
+            .map_db_options(|db| db.create_if_missing(true))
+            .map_cf_options(|cf| cf.disable_auto_compactions(true)),
+        &tmp_dir,
+    )
+    .unwrap();
+    let cf = db.default_column_family();
+    assert_eq!(
+        cf.split_into_ranges(4),
+        vec![KeyRange {
+            start: vec![],
+            end: None
+        }]
+    );
+
+    // 8 files of equal size
+    for i in 0..8 {
+        for j in 0..100 {
+            let key = format!("k{}-{:03}", i, j);
+            assert!(db.put(&WriteOptions::default(), key.as_bytes(), b"value").is_ok());
+        }
+        assert!(db.flush(&FlushOptions::default().wait(true)).is_ok());
+    }
+
+    let ranges = db.split_into_ranges(&cf, 4);
+    assert_eq!(ranges.len(), 4);
+    assert!(ranges[0].start.is_empty());
+    assert!(ranges[3].end.is_none());
+    for w in ranges.windows(2) {
+        assert_eq!(w[0].end.as_ref(), Some(&w[1].start));
+    }
+    let mut counts = vec![0; ranges.len()];
+    let mut it = db.new_iterator(&ReadOptions::default());
+    it.seek_to_first();
+    while it.is_valid() {
+        let idx = ranges.iter().position(|r| r.contains(it.key())).unwrap();
+        counts[idx] += 1;
+        it.next();
+    }
+    assert_eq!(counts, vec![200; 4]);
+
+    assert_eq!(db.split_into_ranges(&cf, 100).len(), 8);
+}
+
+#[test]
+fn split_into_ranges_large_first_file() {
+    let tmp_dir = ::tempdir::TempDir::new_in(".", "rocks").unwrap();
+    let db = DB::open(
+        Options::default()
+            .map_db_options(|db| db.create_if_missing(true))
+            .map_cf_options(|cf| cf.disable_auto_compactions(true)),
+        &tmp_dir,
+    )
+    .unwrap();
+    let cf = db.default_column_family();
+
+    // the first file holds most of the data
+    for j in 0..1000 {
+        let key = format!("a-{:04}", j);
+        assert!(db.put(&WriteOptions::default(), key.as_bytes(), b"value").is_ok());
+    }
+    assert!(db.flush(&FlushOptions::default().wait(true)).is_ok());
+    for i in 0..3 {
+        let key = format!("b-{}", i);
+        assert!(db.put(&WriteOptions::default(), key.as_bytes(), b"value").is_ok());
+        assert!(db.flush(&FlushOptions::default().wait(true)).is_ok());
+    }
+
+    let ranges = db.split_into_ranges(&cf, 4);
+    assert!(ranges[0].start.is_empty());
+    assert!(ranges[0].contains(b"a-0000"), "{:?}", ranges);
+    assert!(ranges[0].contains(b"a-0999"), "{:?}", ranges);
+}
+
 #[test]
 fn list_live_files() {
     let tmp_dir = ::tempdir::TempDir::new_in(".", "rocks").unwrap();
@@ -610,6 +962,68 @@ fn get_sorted_wal_files() {
     assert!(files.unwrap().len() > 2);
 }
 
+#[test]
+fn wal_file_management() {
+    let tmp_dir = ::tempdir::TempDir::new_in(".", "rocks").unwrap();
+    let db = DB::open(
+        Options::default().map_db_options(|db| db.create_if_missing(true).wal_ttl_seconds(1000)),
+        &tmp_dir,
+    )
+    .unwrap();
+    for i in 0..3 {
+        assert!(db.put(&Default::default(), format!("key{}", i).as_bytes(), b"val").is_ok());
+        // a new WAL file for every flush
+        assert!(db.flush(&FlushOptions::default().wait(true)).is_ok());
+    }
+    // empty WAL files are not listed
+    assert!(db.put(&Default::default(), b"key3", b"val").is_ok());
+    let current = db.get_current_wal_file().unwrap();
+    assert_eq!(current.file_type, WalFileType::Alive);
+
+    // obsolete WAL files may be archived shortly after the flush returns
+    let mut archived = db.get_archived_wal_files().unwrap();
+    for _ in 0..100 {
+        if archived.len() == 3 {
+            break;
+        }
+        std::thread::sleep(std::time::Duration::from_millis(10));
+        archived = db.get_archived_wal_files().unwrap();
+    }
+    assert_eq!(archived.len(), 3);
+    assert!(archived.iter().all(|f| f.log_number < current.log_number));
+
+    // only the first file holds writes before the second file starts
+    let purged = db.purge_archived_wal_files(archived[1].start_sequence).unwrap();
+    assert_eq!(purged.len(), 1);
+    assert_eq!(purged[0].log_number, archived[0].log_number);
+    assert_eq!(db.get_archived_wal_files().unwrap().len(), 2);
+
+    let purged = db.purge_archived_wal_files(db.get_latest_sequence_number()).unwrap();
+    assert_eq!(purged.len(), 2);
+    assert!(db.get_archived_wal_files().unwrap().is_empty());
+    assert!(db.get_sorted_wal_files().unwrap().iter().any(|f| f.log_number == current.log_number));
+}
+
+#[test]
+fn legacy_background_limits() {
+    let tmp_dir = ::tempdir::TempDir::new_in(".", "rocks").unwrap();
+    let db_options = DBOptions::default()
+        .create_if_missing(true)
+        .base_background_compactions(2)
+        .max_background_compactions(3)
+        .max_background_flushes(2);
+    let s = db_options.to_options_string().unwrap();
+    assert!(s.contains("max_background_compactions=3;"), "{}", s);
+    assert!(s.contains("max_background_flushes=2;"), "{}", s);
+
+    let db = DB::open(Options::new(Some(db_options), None), &tmp_dir).unwrap();
+    assert!(db.put(&Default::default(), b"key", b"val").is_ok());
+    assert!(db.flush(&FlushOptions::default().wait(true)).is_ok());
+    // the thread pools fit the legacy limits
+    assert!(Env::default_instance().get_background_threads(Priority::Low) >= 3);
+    assert!(Env::default_instance().get_background_threads(Priority::High) >= 2);
+}
+
 #[test]
 fn change_options() {
     let tmp_dir = ::tempdir::TempDir::new_in(".", "rocks").unwrap();
@@ -684,6 +1098,38 @@ fn approximate_sizes() {
     let (count, size) = db.get_approximate_memtable_stats(&default_cf, b"a".as_ref()..&b"z".as_ref());
     assert!(count > 0 && count < 200);
     assert!(size > 0);
+
+    let ranges = [&b"long-key-2"[..]..&b"long-key-3"[..]];
+    let files_only = db
+        .get_approximate_sizes_with_options(&Default::default(), &default_cf, &ranges)
+        .unwrap();
+    let mem_only = db
+        .get_approximate_sizes_with_options(
+            &SizeApproximationOptions::default()
+                .include_memtables(true)
+                .include_files(false),
+            &default_cf,
+            &ranges,
+        )
+        .unwrap();
+    assert!(mem_only[0] > files_only[0]);
+    let both = default_cf
+        .get_approximate_sizes_with_options(
+            &SizeApproximationOptions::default()
+                .include_memtables(true)
+                .files_size_error_margin(0.1),
+            &ranges,
+        )
+        .unwrap();
+    assert!(both[0] >= mem_only[0]);
+    // must include something
+    assert!(db
+        .get_approximate_sizes_with_options(
+            &SizeApproximationOptions::default().include_files(false),
+            &default_cf,
+            &ranges,
+        )
+        .is_err());
 }
 
 #[test]
@@ -738,6 +1184,29 @@ fn compact_files() {
     assert_eq!(result[0].level, 4); // compacted to 4
 }
 
+#[test]
+fn get_live_files_metadata_repeatedly() {
+    let tmp_dir = ::tempdir::TempDir::new_in("", "rocks").unwrap();
+    let db = DB::open(
+        Options::default()
+            .map_db_options(|db| db.create_if_missing(true))
+            .map_cf_options(|cf| cf.disable_auto_compactions(true)),
+        &tmp_dir,
+    )
+    .unwrap();
+
+    for i in 0..3 {
+        assert!(db.put(&WriteOptions::default(), format!("k{}", i).as_bytes(), b"v").is_ok());
+        assert!(db.flush(&FlushOptions::default().wait(true)).is_ok());
+    }
+    // each call reads and frees its own list
+    for _ in 0..3 {
+        let meta = db.get_live_files_metadata();
+        assert_eq!(meta.len(), 3);
+        assert!(meta.iter().all(|f| f.level == 0 && f.num_entries == 1));
+    }
+}
+
 #[test]
 fn get_properties_of_all_tables() {
     let tmp_dir = ::tempdir::TempDir::new_in("", "rocks").unwrap();
@@ -826,3 +1295,622 @@ fn delete_files_in_range() {
         assert!(old_files.contains(f));
     }
 }
+
+#[test]
+fn secondary_catch_up() {
+    use std::time::Duration;
+
+    let tmp_dir = TempDir::new_in(".", "rocks").unwrap();
+    let secondary_dir = TempDir::new_in(".", "rocks").unwrap();
+    let db = DB::open(
+        Options::default().map_db_options(|db| db.create_if_missing(true)),
+        &tmp_dir,
+    )
+    .unwrap();
+    assert!(db.put(WriteOptions::default_instance(), b"k0", b"v0").is_ok());
+
+    let secondary = DB::open_as_secondary(
+        &Options::default().map_db_options(|db| db.max_open_files(-1)),
+        &tmp_dir,
+        &secondary_dir,
+    )
+    .unwrap();
+    assert!(secondary.get(ReadOptions::default_instance(), b"k1").is_err());
+
+    let handle = secondary.spawn_catch_up(Duration::from_millis(10));
+    assert!(db.put(WriteOptions::default_instance(), b"k1", b"v1").is_ok());
+
+    let mut caught_up = false;
+    for _ in 0..200 {
+        if secondary.get(ReadOptions::default_instance(), b"k1").is_ok() {
+            caught_up = true;
+            break;
+        }
+        std::thread::sleep(Duration::from_millis(10));
+    }
+    assert!(caught_up);
+    assert!(handle.errors().try_recv().is_err());
+    handle.stop();
+}
+
+#[test]
+fn transactional_update_across_column_families() {
+    use rocks::db::UpdateOp;
+    use rocks::error::Code;
+    use std::sync::Arc;
+    use std::thread;
+
+    let tmp_dir = TempDir::new_in(".", "rocks").unwrap();
+    let db = DB::open(
+        Options::default().map_db_options(|db| db.create_if_missing(true)),
+        &tmp_dir,
+    )
+    .unwrap();
+    let accounts = db.create_column_family(&Default::default(), "accounts").unwrap();
+    let ledger = db.create_column_family(&Default::default(), "ledger").unwrap();
+    let state = Arc::new((db, accounts, ledger));
+
+    let to_u64 = |v: &Option<Vec<u8>>| {
+        v.as_ref()
+            .map(|v| String::from_utf8_lossy(v).parse::<u64>().unwrap())
+            .unwrap_or(0)
+    };
+
+    let threads = (0..4)
+        .map(|_| {
+            let state = state.clone();
+            thread::spawn(move || {
+                let (ref db, ref accounts, ref ledger) = *state;
+                for _ in 0..25 {
+                    loop {
+                        let ret = db.transactional_update(
+                            WriteOptions::default_instance(),
+                            &[accounts, ledger],
+                            &[(0, b"balance"), (1, b"entries")],
+                            |views| {
+                                vec![
+                                    UpdateOp::Put {
+                                        cf: 0,
+                                        key: b"balance".to_vec(),
+                                        value: (to_u64(&views[0]) + 10).to_string().into_bytes(),
+                                    },
+                                    UpdateOp::Put {
+                                        cf: 1,
+                                        key: b"entries".to_vec(),
+                                        value: (to_u64(&views[1]) + 1).to_string().into_bytes(),
+                                    },
+                                ]
+                            },
+                        );
+                        match ret {
+                            Ok(()) => break,
+                            Err(ref e) if e.code() == Code::Busy => continue,
+                            Err(e) => panic!("transactional_update: {:?}", e),
+                        }
+                    }
+                }
+            })
+        })
+        .collect::<Vec<_>>();
+    for t in threads {
+        t.join().unwrap();
+    }
+
+    let (ref db, ref accounts, ref ledger) = *state;
+    assert_eq!(accounts.get(ReadOptions::default_instance(), b"balance").unwrap(), &b"1000"[..]);
+    assert_eq!(ledger.get(ReadOptions::default_instance(), b"entries").unwrap(), &b"100"[..]);
+
+    // no ops, nothing written
+    let seqno = db.get_latest_sequence_number();
+    assert!(db
+        .transactional_update(WriteOptions::default_instance(), &[accounts], &[(0, b"balance")], |_| vec![])
+        .is_ok());
+    assert_eq!(db.get_latest_sequence_number(), seqno);
+}
+
+#[test]
+fn transactional_update_bad_column_family_index() {
+    use rocks::db::UpdateOp;
+
+    let tmp_dir = TempDir::new_in(".", "rocks").unwrap();
+    let db = DB::open(
+        Options::default().map_db_options(|db| db.create_if_missing(true)),
+        &tmp_dir,
+    )
+    .unwrap();
+    let cf = db.default_column_family();
+    let seqno = db.get_latest_sequence_number();
+
+    let ret = db.transactional_update(WriteOptions::default_instance(), &[&cf], &[(1, b"k")], |_| vec![]);
+    assert!(ret.unwrap_err().is_invalid_argument());
+
+    let ret = db.transactional_update(WriteOptions::default_instance(), &[&cf], &[(0, b"k")], |_| {
+        vec![
+            UpdateOp::Put {
+                cf: 0,
+                key: b"k".to_vec(),
+                value: b"v".to_vec(),
+            },
+            UpdateOp::Delete {
+                cf: 2,
+                key: b"k".to_vec(),
+            },
+        ]
+    });
+    assert!(ret.unwrap_err().is_invalid_argument());
+    assert_eq!(db.get_latest_sequence_number(), seqno);
+}
+
+#[test]
+fn open_with_fixups() {
+    use rocks::env::Logger;
+    use rocks::utilities::ColumnFamilyFixups;
+
+    struct ConcatMergeOp;
+
+    impl AssociativeMergeOperator for ConcatMergeOp {
+        fn merge(&self, _key: &[u8], existing_value: Option<&[u8]>, value: &[u8], _logger: &Logger) -> Option<Vec<u8>> {
+            let mut ret = existing_value.map(|v| v.to_vec()).unwrap_or_default();
+            ret.extend_from_slice(value);
+            Some(ret)
+        }
+    }
+
+    let tmp_dir = TempDir::new_in(".", "rocks").unwrap();
+    let opts = Options::default().map_db_options(|db| db.create_if_missing(true));
+    {
+        let db = DB::open(&opts, &tmp_dir).unwrap();
+        for name in &["counters_daily", "counters_total", "users"] {
+            assert!(db.create_column_family(&ColumnFamilyOptions::default(), name).is_ok());
+        }
+    }
+
+    let fixups =
+        ColumnFamilyFixups::new().add("counters_*", |cf| cf.associative_merge_operator(Box::new(ConcatMergeOp)));
+    let (db, cfs) = DB::open_with_fixups(&opts, &tmp_dir, &fixups).unwrap();
+    assert_eq!(cfs.len(), 4);
+    for cf in &cfs {
+        let ret = cf.merge(WriteOptions::default_instance(), b"k", b"a");
+        if cf.name().starts_with("counters_") {
+            assert!(ret.is_ok());
+            assert!(cf.merge(WriteOptions::default_instance(), b"k", b"b").is_ok());
+            assert_eq!(cf.get(ReadOptions::default_instance(), b"k").unwrap(), b"ab");
+        } else {
+            assert!(ret.is_err(), "no merge operator on {}", cf.name());
+        }
+    }
+    drop(cfs);
+    drop(db);
+
+    // a new DB gets the default column family only
+    let new_dir = TempDir::new_in(".", "rocks").unwrap();
+    let (_db, cfs) = DB::open_with_fixups(&opts, &new_dir, &fixups).unwrap();
+    assert_eq!(cfs.len(), 1);
+    assert_eq!(cfs[0].name(), "default");
+}
+
+#[test]
+fn open_with_fixups_corrupt_current() {
+    use rocks::utilities::ColumnFamilyFixups;
+
+    let tmp_dir = TempDir::new_in(".", "rocks").unwrap();
+    std::fs::write(tmp_dir.path().join("CURRENT"), b"MANIFEST-000001").unwrap();
+    let opts = Options::default().map_db_options(|db| db.create_if_missing(true));
+
+    // not mistaken for a new DB
+    let ret = DB::open_with_fixups(&opts, &tmp_dir, &ColumnFamilyFixups::new());
+    assert!(ret.unwrap_err().is_corruption());
+}
+
+#[test]
+fn column_family_by_name() {
+    let tmp_dir = TempDir::new_in(".", "rocks").unwrap();
+    let opts = Options::default().map_db_options(|db| db.create_if_missing(true));
+    {
+        let db = DB::open(&opts, &tmp_dir).unwrap();
+        assert!(db.create_column_family(&ColumnFamilyOptions::default(), "users").is_ok());
+    }
+
+    let (db, cfs) = DB::open_with_column_families(
+        &DBOptions::from_options(&opts),
+        &tmp_dir,
+        vec!["default", "users"],
+    )
+    .unwrap();
+    drop(cfs);
+
+    // handles outlive the returned column families
+    let users = db.column_family("users").unwrap();
+    assert!(users.put(WriteOptions::default_instance(), b"alice", b"1").is_ok());
+    assert_eq!(db.column_family("default").unwrap().id(), 0);
+    assert!(db.column_family("orders").is_none());
+
+    let orders = db.create_column_family(&ColumnFamilyOptions::default(), "orders").unwrap();
+    assert_eq!(db.column_family("orders").unwrap().id(), orders.id());
+    drop(orders);
+    let names = db.column_families().iter().map(|cf| cf.name().to_owned()).collect::<Vec<_>>();
+    assert_eq!(names, vec!["default", "users", "orders"]);
+
+    let orders = db.column_family("orders").unwrap();
+    assert!(db.drop_column_family(&orders).is_ok());
+    assert!(db.column_family("orders").is_none());
+    assert_eq!(db.column_families().len(), 2);
+    // the handle of a dropped column family stays valid
+    assert_eq!(orders.name(), "orders");
+
+    assert_eq!(
+        db.column_family("users").unwrap().get(ReadOptions::default_instance(), b"alice").unwrap(),
+        b"1"
+    );
+}
+
+#[test]
+fn drop_column_families() {
+    let tmp_dir = TempDir::new_in(".", "rocks").unwrap();
+    let db = DB::open(Options::default().map_db_options(|db| db.create_if_missing(true)), &tmp_dir).unwrap();
+    let cfs = ["a", "b", "c"]
+        .iter()
+        .map(|name| db.create_column_family(&ColumnFamilyOptions::default(), name).unwrap())
+        .collect::<Vec<_>>();
+    for cf in &cfs {
+        assert!(cf.put(WriteOptions::default_instance(), b"key", cf.name().as_bytes()).is_ok());
+    }
+
+    let mut it = db.new_iterator_cf(ReadOptions::default_instance(), &cfs[0]);
+    it.seek_to_first();
+    assert!(db.drop_column_families(&[&cfs[0], &cfs[1]]).is_ok());
+
+    // the iterator and handles of dropped column families are still valid
+    assert!(it.is_valid());
+    assert_eq!(it.value(), b"a");
+    drop(it);
+    assert_eq!(cfs[1].get(ReadOptions::default_instance(), b"key").unwrap(), b"b");
+
+    assert!(db.column_family("a").is_none());
+    assert!(db.column_family("b").is_none());
+    assert!(db.column_family("c").is_some());
+    drop(cfs);
+
+    let mut names = DB::list_column_families(&Options::default(), &tmp_dir).unwrap();
+    names.sort();
+    assert_eq!(names, vec!["c", "default"]);
+}
+
+#[test]
+fn delete_files_in_ranges_and_compact_ranges() {
+    let tmp_dir = TempDir::new_in(".", "rocks").unwrap();
+    let db = DB::open(
+        Options::default()
+            .map_db_options(|db| db.create_if_missing(true))
+            .map_cf_options(|cf| cf.disable_auto_compactions(true)),
+        &tmp_dir,
+    )
+    .unwrap();
+    let cf = db.default_column_family();
+
+    // one file per key, out of L0 which is skipped by delete_files_in_ranges()
+    for i in 0..10 {
+        let key = format!("k{}", i);
+        assert!(db.put(WriteOptions::default_instance(), key.as_bytes(), b"v").is_ok());
+        assert!(db.flush(&FlushOptions::default().wait(true)).is_ok());
+        assert!(db
+            .compact_range(&CompactRangeOptions::default(), key.as_bytes()..=key.as_bytes())
+            .is_ok());
+    }
+    assert_eq!(db.get_live_files_metadata().len(), 10);
+
+    assert!(db
+        .delete_files_in_ranges(&cf, &[&b"k1"[..]..&b"k3"[..], &b"k6"[..]..&b"k7"[..]], true)
+        .is_ok());
+    assert_eq!(db.get_live_files_metadata().len(), 10 - 3 - 2);
+    assert!(db.get(ReadOptions::default_instance(), b"k2").is_err());
+    assert!(db.get(ReadOptions::default_instance(), b"k4").is_ok());
+
+    for key in &[b"k0", b"k4", b"k8"] {
+        assert!(db.delete(WriteOptions::default_instance(), &key[..]).is_ok());
+    }
+    assert!(db.flush(&FlushOptions::default().wait(true)).is_ok());
+    // overlapping ranges are merged
+    assert!(cf
+        .compact_ranges(
+            &CompactRangeOptions::default(),
+            &[&b"k8"[..]..&b"k9"[..], &b"k0"[..]..&b"k4"[..], &b"k3"[..]..&b"k5"[..]],
+        )
+        .is_ok());
+    let mut keys = db
+        .new_iterator(ReadOptions::default_instance())
+        .map(|(k, _)| k.to_vec())
+        .collect::<Vec<_>>();
+    keys.sort();
+    assert_eq!(keys, vec![b"k5".to_vec(), b"k9".to_vec()]);
+    let live = db.get_live_files_metadata();
+    assert!(live.len() <= 2, "{:?}", live);
+}
+
+#[test]
+fn suggest_compact_range_and_promote_l0() {
+    let tmp_dir = TempDir::new_in(".", "rocks").unwrap();
+    let db = DB::open(
+        Options::default()
+            .map_db_options(|db| db.create_if_missing(true))
+            .map_cf_options(|cf| cf.disable_auto_compactions(true).num_levels(4)),
+        &tmp_dir,
+    )
+    .unwrap();
+    let cf = db.default_column_family();
+
+    // non-overlapping L0 files
+    for i in 0..3 {
+        let key = format!("k{}", i);
+        assert!(db.put(WriteOptions::default_instance(), key.as_bytes(), b"v").is_ok());
+        assert!(db.flush(&FlushOptions::default().wait(true)).is_ok());
+    }
+    let files_at = |level: i32| db.get_property(&format!("rocksdb.num-files-at-level{}", level));
+    assert_eq!(files_at(0), Some("3".into()));
+
+    assert!(db.promote_l0(&cf, 0).is_err());
+    assert!(db.promote_l0(&cf, 2).is_ok());
+    assert_eq!(files_at(0), Some("0".into()));
+    assert_eq!(files_at(2), Some("3".into()));
+    // level 2 is not empty any more
+    assert!(db.put(WriteOptions::default_instance(), b"k9", b"v").is_ok());
+    assert!(db.flush(&FlushOptions::default().wait(true)).is_ok());
+    assert!(db.promote_l0(&cf, 3).is_err());
+
+    // only marks files, compacted by background compactions which are disabled here
+    assert!(db.suggest_compact_range(&cf, &b"k0"[..]..=&b"k1"[..]).is_ok());
+    assert!(db.suggest_compact_range(&cf, ..).is_ok());
+    assert_eq!(db.get(ReadOptions::default_instance(), b"k1").unwrap(), b"v");
+}
+
+#[test]
+fn get_into() {
+    let tmp_dir = TempDir::new_in(".", "rocks").unwrap();
+    let db = DB::open(Options::default().map_db_options(|db| db.create_if_missing(true)), &tmp_dir).unwrap();
+    let cf = db.create_column_family(&ColumnFamilyOptions::default(), "into").unwrap();
+
+    assert!(db.put(WriteOptions::default_instance(), b"k1", b"value1").is_ok());
+    assert!(db.put(WriteOptions::default_instance(), b"k2", b"v2").is_ok());
+    assert!(cf.put(WriteOptions::default_instance(), b"k3", b"v3").is_ok());
+    assert!(db.flush(&FlushOptions::default()).is_ok());
+
+    let mut buf = Vec::with_capacity(64);
+    assert_eq!(db.get_into(ReadOptions::default_instance(), b"k1", &mut buf).unwrap(), Some(6));
+    assert_eq!(buf, b"value1");
+    // shorter value replaces the buffer content
+    assert_eq!(db.get_into(ReadOptions::default_instance(), b"k2", &mut buf).unwrap(), Some(2));
+    assert_eq!(buf, b"v2");
+    assert!(buf.capacity() >= 64);
+
+    // not found leaves the buffer as is
+    assert_eq!(db.get_into(ReadOptions::default_instance(), b"k3", &mut buf).unwrap(), None);
+    assert_eq!(buf, b"v2");
+
+    assert_eq!(cf.get_into(ReadOptions::default_instance(), b"k3", &mut buf).unwrap(), Some(2));
+    assert_eq!(buf, b"v3");
+    assert_eq!(db.get_cf_into(ReadOptions::default_instance(), &cf, b"k1", &mut buf).unwrap(), None);
+}
+
+#[test]
+fn flush_async() {
+    use std::time::Duration;
+
+    let tmp_dir = TempDir::new_in(".", "rocks").unwrap();
+    let db = DB::open(
+        Options::default()
+            .map_db_options(|db| db.create_if_missing(true))
+            .map_cf_options(|cf| cf.disable_auto_compactions(true)),
+        &tmp_dir,
+    )
+    .unwrap();
+    let cf = db.create_column_family(&ColumnFamilyOptions::default(), "async").unwrap();
+
+    assert!(db.put(WriteOptions::default_instance(), b"k1", b"v1").is_ok());
+    assert!(cf.put(WriteOptions::default_instance(), b"k2", b"v2").is_ok());
+
+    let ticket = db.flush_async().unwrap();
+    ticket.wait();
+    assert!(ticket.is_done());
+    assert_eq!(db.get_property("rocksdb.num-files-at-level0"), Some("1".into()));
+
+    let ticket = db.flush_async_cf(&cf).unwrap();
+    assert!(ticket.wait_timeout(Duration::from_secs(10)));
+    assert_eq!(cf.get_property("rocksdb.num-files-at-level0"), Some("1".into()));
+
+    // nothing to flush
+    assert!(cf.flush_async().unwrap().is_done());
+}
+
+#[test]
+fn wait_for_compact() {
+    use std::time::Duration;
+
+    let tmp_dir = TempDir::new_in(".", "rocks").unwrap();
+    let db = DB::open(
+        Options::default()
+            .map_db_options(|db| db.create_if_missing(true))
+            .map_cf_options(|cf| cf.level0_file_num_compaction_trigger(2)),
+        &tmp_dir,
+    )
+    .unwrap();
+
+    for i in 0..4 {
+        let key = format!("k{}", i);
+        assert!(db.put(WriteOptions::default_instance(), key.as_bytes(), b"v").is_ok());
+        assert!(db.flush(&FlushOptions::default()).is_ok());
+    }
+    assert!(db.put(WriteOptions::default_instance(), b"k", b"v").is_ok());
+
+    assert!(db.wait_for_compact(&WaitForCompactOptions::default().flush(true)).is_ok());
+    assert_eq!(db.get_int_property("rocksdb.compaction-pending"), Some(0));
+    assert_eq!(db.get_int_property("rocksdb.num-immutable-mem-table"), Some(0));
+    assert!(db.get_int_property("rocksdb.num-files-at-level0").unwrap() < 2);
+
+    // paused background work never finishes
+    assert!(db.pause_background_work().is_ok());
+    assert!(db.put(WriteOptions::default_instance(), b"k", b"v2").is_ok());
+    assert!(db.flush_async().is_ok());
+    let ret = db.wait_for_compact(&WaitForCompactOptions::default().timeout(Duration::from_millis(100)));
+    assert_eq!(ret.unwrap_err().code(), rocks::error::Code::TimedOut);
+    assert!(db.continue_background_work().is_ok());
+    assert!(db.wait_for_compact(&WaitForCompactOptions::default()).is_ok());
+}
+
+#[test]
+fn preset_point_lookup() {
+    use rocks::statistics::Statistics;
+
+    let tmp_dir = TempDir::new_in(".", "rocks").unwrap();
+    let stat = Statistics::new();
+    let opt = Options::default()
+        .map_db_options(|db| db.create_if_missing(true).statistics(Some(stat.clone())))
+        .preset_point_lookup(8);
+    assert!(opt
+        .to_cf_options()
+        .to_options_string()
+        .unwrap()
+        .contains("memtable_whole_key_filtering=true"));
+
+    let db = DB::open(&opt, &tmp_dir).unwrap();
+    for i in 0..100 {
+        let key = format!("k{}", i);
+        assert!(db.put(WriteOptions::default_instance(), key.as_bytes(), b"v").is_ok());
+    }
+    assert!(db.flush(&FlushOptions::default()).is_ok());
+    for i in 0..100 {
+        let key = format!("missing{}", i);
+        assert!(db.get(ReadOptions::default_instance(), key.as_bytes()).unwrap_err().is_not_found());
+    }
+    // missing keys are filtered out without reading data blocks
+    assert!(stat.to_map()["rocksdb.bloom.filter.useful"] > 0);
+}
+
+#[test]
+fn preset_time_series() {
+    let tmp_dir = TempDir::new_in(".", "rocks").unwrap();
+    let opt = Options::default()
+        .map_db_options(|db| db.create_if_missing(true))
+        .preset_time_series();
+    assert!(opt
+        .to_cf_options()
+        .to_options_string()
+        .unwrap()
+        .contains("level_compaction_dynamic_level_bytes=true"));
+
+    let db = DB::open(&opt, &tmp_dir).unwrap();
+    for i in 0..1000 {
+        let key = format!("ts{:08}", i);
+        assert!(db.put(WriteOptions::default_instance(), key.as_bytes(), b"v").is_ok());
+    }
+    assert!(db.flush(&FlushOptions::default()).is_ok());
+    assert!(db.compact_range(&CompactRangeOptions::default(), ..).is_ok());
+    // with dynamic level sizing, data goes straight to the last level
+    assert_eq!(db.get_int_property("rocksdb.num-files-at-level0"), Some(0));
+    assert!(db.get_int_property("rocksdb.num-files-at-level6").unwrap() > 0);
+}
+
+#[test]
+fn preset_bulk_ingest() {
+    let tmp_dir = TempDir::new_in(".", "rocks").unwrap();
+    let opt = Options::default()
+        .map_db_options(|db| db.create_if_missing(true))
+        .preset_bulk_ingest();
+    assert!(opt
+        .to_cf_options()
+        .to_options_string()
+        .unwrap()
+        .contains("disable_auto_compactions=true"));
+
+    let db = DB::open(&opt, &tmp_dir).unwrap();
+    for i in 0..10 {
+        let key = format!("k{}", i);
+        assert!(db.put(WriteOptions::default_instance(), key.as_bytes(), b"v").is_ok());
+        assert!(db.flush(&FlushOptions::default()).is_ok());
+    }
+    // no compaction, every flush stays in level 0
+    assert_eq!(db.get_int_property("rocksdb.num-files-at-level0"), Some(10));
+    assert_eq!(db.get(ReadOptions::default_instance(), b"k9").unwrap(), b"v");
+}
+
+#[test]
+fn fifo_compaction_ttl() {
+    use rocks::advanced_options::{CompactionOptionsFIFO, CompactionStyle};
+    use std::thread;
+    use std::time::Duration;
+
+    let tmp_dir = TempDir::new_in(".", "rocks").unwrap();
+    let mut opts_str = None;
+    let db = DB::open(
+        Options::default()
+            .map_db_options(|db| db.create_if_missing(true).max_open_files(-1))
+            .map_cf_options(|cf| {
+                let cf = cf
+                    .compaction_style(CompactionStyle::CompactionStyleFIFO)
+                    .compaction_options_fifo(CompactionOptionsFIFO::default().allow_compaction(true))
+                    .ttl(1);
+                opts_str = cf.to_options_string();
+                cf
+            }),
+        &tmp_dir,
+    )
+    .unwrap();
+
+    let opts_str = opts_str.unwrap();
+    assert!(opts_str.contains("ttl=1;"), "{}", opts_str);
+    assert!(opts_str.contains("allow_compaction=true"), "{}", opts_str);
+
+    assert!(db.put(WriteOptions::default_instance(), b"old", b"v").is_ok());
+    assert!(db.flush(&FlushOptions::default().wait(true)).is_ok());
+    thread::sleep(Duration::from_millis(2100));
+
+    // a new flush triggers the TTL compaction, which drops the expired file
+    assert!(db.put(WriteOptions::default_instance(), b"new", b"v").is_ok());
+    assert!(db.flush(&FlushOptions::default().wait(true)).is_ok());
+    for _ in 0..50 {
+        if db.get(ReadOptions::default_instance(), b"old").is_err() {
+            break;
+        }
+        thread::sleep(Duration::from_millis(100));
+    }
+    assert!(db.get(ReadOptions::default_instance(), b"old").is_err());
+    assert_eq!(db.get(ReadOptions::default_instance(), b"new").unwrap(), b"v");
+}
+
+#[test]
+fn ttl_db_set_ttl() {
+    use std::thread;
+    use std::time::Duration;
+
+    let tmp_dir = TempDir::new_in(".", "rocks").unwrap();
+    let db = TtlDB::open(
+        Options::default().map_db_options(|db| db.create_if_missing(true)),
+        &tmp_dir,
+        Duration::from_secs(3600),
+        false,
+    )
+    .unwrap();
+    let def = db.default_column_family();
+    assert_eq!(db.ttl(&def), Duration::from_secs(3600));
+
+    let cf = db
+        .create_column_family_with_ttl(&ColumnFamilyOptions::default(), "short", Duration::from_secs(1))
+        .unwrap();
+    assert_eq!(db.ttl(&cf), Duration::from_secs(1));
+
+    assert!(db.put(WriteOptions::default_instance(), b"k", b"v").is_ok());
+    assert!(cf.put(WriteOptions::default_instance(), b"k", b"v").is_ok());
+    thread::sleep(Duration::from_millis(2100));
+
+    // expired values are only dropped by compactions
+    assert_eq!(cf.get(ReadOptions::default_instance(), b"k").unwrap(), b"v");
+    assert!(cf.compact_range(&CompactRangeOptions::default(), ..).is_ok());
+    assert!(cf.get(ReadOptions::default_instance(), b"k").is_err());
+
+    assert!(db.compact_range(&CompactRangeOptions::default(), ..).is_ok());
+    assert_eq!(db.get(ReadOptions::default_instance(), b"k").unwrap(), b"v");
+
+    db.set_ttl(&def, Duration::from_secs(1));
+    assert_eq!(db.ttl(&def), Duration::from_secs(1));
+    assert!(db.compact_range(&CompactRangeOptions::default(), ..).is_ok());
+    assert!(db.get(ReadOptions::default_instance(), b"k").is_err());
+}